@@ -0,0 +1,189 @@
+// src/accesslist.rs
+
+use serde_json::Value;
+
+use crate::decode::{DecodedView, ResultDecoder};
+use crate::prefs::DisplayPrefs;
+
+pub const METHOD: &str = "eth_createAccessList";
+
+/// One access list entry: an address and the storage keys it touches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub address: String,
+    pub storage_keys: Vec<String>,
+}
+
+/// Parses an EIP-2930 access list (`[{"address", "storageKeys"}]`).
+/// Addresses and keys are lowercased so lists from different sources
+/// compare equal.
+pub fn parse(list: &Value) -> Option<Vec<Entry>> {
+    list.as_array()?
+        .iter()
+        .map(|item| {
+            let address = item["address"].as_str()?.to_lowercase();
+            let storage_keys = match &item["storageKeys"] {
+                Value::Null => Vec::new(),
+                keys => keys.as_array()?.iter().map(|k| k.as_str().map(str::to_lowercase)).collect::<Option<_>>()?,
+            };
+            Some(Entry { address, storage_keys })
+        })
+        .collect()
+}
+
+/// An address, or one of its storage keys, in a [`Diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub address: String,
+    /// `None` for the address entry as a whole.
+    pub key: Option<String>,
+}
+
+/// How the node's access list differs from the one sent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diff {
+    /// In the node's list only: the call needs them.
+    pub added: Vec<Item>,
+    /// In the sent list only: the call doesn't touch them.
+    pub unnecessary: Vec<Item>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.unnecessary.is_empty()
+    }
+
+    /// "+ 0xabc…" / "- 0xabc… key 0x01" lines, added first.
+    pub fn lines(&self) -> Vec<String> {
+        let line = |sign: char, item: &Item| match &item.key {
+            None => format!("{} {}", sign, item.address),
+            Some(key) => format!("{} {} key {}", sign, item.address, key),
+        };
+        self.added.iter().map(|i| line('+', i)).chain(self.unnecessary.iter().map(|i| line('-', i))).collect()
+    }
+}
+
+/// What is in `a` but not `b`: whole addresses, and keys of addresses both
+/// have. Duplicates count once.
+fn missing_from(a: &[Entry], b: &[Entry]) -> Vec<Item> {
+    let mut items = Vec::new();
+    let push = |items: &mut Vec<Item>, item: Item| {
+        if !items.contains(&item) {
+            items.push(item);
+        }
+    };
+    for entry in a {
+        let other: Vec<&Entry> = b.iter().filter(|e| e.address == entry.address).collect();
+        if other.is_empty() {
+            push(&mut items, Item { address: entry.address.clone(), key: None });
+            continue;
+        }
+        for key in &entry.storage_keys {
+            if !other.iter().any(|e| e.storage_keys.contains(key)) {
+                push(&mut items, Item { address: entry.address.clone(), key: Some(key.clone()) });
+            }
+        }
+    }
+    items
+}
+
+/// Compares the access list that was sent with the one the node built.
+pub fn diff(sent: &[Entry], generated: &[Entry]) -> Diff {
+    Diff { added: missing_from(generated, sent), unnecessary: missing_from(sent, generated) }
+}
+
+/// For an `eth_createAccessList` request whose call object carried an
+/// access list, the differences from the result's.
+pub fn diff_with_request(params: &Value, result: &Value) -> Option<Diff> {
+    let sent = parse(params.get(0)?.get("accessList")?)?;
+    Some(diff(&sent, &parse(&result["accessList"])?))
+}
+
+/// `eth_createAccessList` results: the gas estimate, then a table of
+/// address → storage keys.
+pub struct AccessListDecoder;
+
+impl ResultDecoder for AccessListDecoder {
+    fn matches(&self, method: &str) -> bool {
+        method == METHOD
+    }
+
+    fn decode(&self, result: &Value, prefs: &DisplayPrefs) -> Option<DecodedView> {
+        let entries = parse(&result["accessList"])?;
+        let mut rows = Vec::new();
+        for entry in &entries {
+            if entry.storage_keys.is_empty() {
+                rows.push(vec![entry.address.clone(), "(no keys)".to_string()]);
+            }
+            for (i, key) in entry.storage_keys.iter().enumerate() {
+                let address = if i == 0 { entry.address.clone() } else { String::new() };
+                rows.push(vec![address, key.clone()]);
+            }
+        }
+        let gas = result["gasUsed"].as_str().map(|g| prefs.quantity(g)).unwrap_or_else(|| "?".to_string());
+        let mut lines = vec![format!("gasUsed {} · {} address(es)", gas, entries.len())];
+        if let Some(error) = result["error"].as_str() {
+            lines.push(format!("error: {}", error));
+        }
+        lines.extend(DecodedView::Table { columns: vec!["address".into(), "storage key".into()], rows }.lines());
+        Some(DecodedView::Summary(lines))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const K0: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+    const K1: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    const K2: &str = "0x0000000000000000000000000000000000000000000000000000000000000002";
+
+    fn item(address: &str, key: Option<&str>) -> Item {
+        Item { address: address.into(), key: key.map(str::to_string) }
+    }
+
+    #[test]
+    fn diff_reports_added_and_unnecessary_entries() {
+        let sent = parse(&json!([
+            {"address": WETH.to_uppercase().replace("0X", "0x"), "storageKeys": [K0, K2]},
+            {"address": "0x00000000000000000000000000000000000000aa", "storageKeys": []}
+        ]))
+        .unwrap();
+        let generated = parse(&json!([
+            {"address": WETH, "storageKeys": [K0, K1]},
+            {"address": USDC, "storageKeys": [K0]}
+        ]))
+        .unwrap();
+        let d = diff(&sent, &generated);
+        assert_eq!(d.added, [item(WETH, Some(K1)), item(USDC, None)]);
+        assert_eq!(d.unnecessary, [item(WETH, Some(K2)), item("0x00000000000000000000000000000000000000aa", None)]);
+        assert_eq!(d.lines()[0], format!("+ {} key {}", WETH, K1));
+        assert!(diff(&generated, &generated).is_empty());
+    }
+
+    #[test]
+    fn diff_needs_an_access_list_in_the_request() {
+        let result = json!({"accessList": [{"address": WETH, "storageKeys": [K0]}], "gasUsed": "0x7b07"});
+        assert_eq!(diff_with_request(&json!([{"to": WETH}, "latest"]), &result), None);
+        let params = json!([{"to": WETH, "accessList": []}, "latest"]);
+        assert_eq!(diff_with_request(&params, &result).unwrap().added, [item(WETH, None)]);
+    }
+
+    #[test]
+    fn decodes_the_result_as_a_table() {
+        let result = json!({
+            "accessList": [{"address": WETH, "storageKeys": [K0, K1]}, {"address": USDC, "storageKeys": []}],
+            "gasUsed": "0x7b07"
+        });
+        let lines = AccessListDecoder.decode(&result, &DisplayPrefs::default()).unwrap().lines();
+        assert_eq!(lines[0], format!("gasUsed {} · 2 address(es)", DisplayPrefs::default().quantity("0x7b07")));
+        assert!(lines[1].starts_with("address"));
+        assert!(lines[2].starts_with(WETH) && lines[2].ends_with(K0));
+        assert!(lines[3].trim_start() == K1);
+        assert!(lines[4].starts_with(USDC) && lines[4].ends_with("(no keys)"));
+        assert_eq!(AccessListDecoder.decode(&json!("0x1"), &DisplayPrefs::default()), None);
+    }
+}
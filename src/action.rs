@@ -0,0 +1,328 @@
+// src/action.rs
+
+use serde_json::{json, Value};
+
+use crate::app::{App, AppMode, InputField};
+use crate::capability::{probe, probe_allowed};
+use crate::follow::{self, Link};
+use crate::queue::{PendingSend, MAX_QUEUED};
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse};
+use crate::shape::{shape_of, Shape};
+use crate::sync;
+use crate::txform;
+use crate::undo::EditKind;
+
+/// Operations with effects outside the UI: network traffic or writes to
+/// persisted state. Key handlers trigger them through [`dispatch`], which
+/// checks them against the session's restrictions in one place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Send (or dry-run) the request built in ParamInput mode.
+    Send,
+    /// Probe whether the session endpoint serves the highlighted method.
+    ProbeMethod,
+    /// Write UI state (display settings) to disk.
+    SaveUiState,
+    /// Fill the transaction form's nonce from the node.
+    FillNonce,
+    /// Request what a link leads to from the open response.
+    Follow(Link),
+}
+
+/// Toast text for actions blocked by `--spectator`.
+pub const SPECTATOR_DISABLED: &str = "disabled in spectator mode";
+
+impl Action {
+    pub const ALL: &'static [Action] = &[
+        Action::Send,
+        Action::ProbeMethod,
+        Action::SaveUiState,
+        Action::FillNonce,
+        Action::Follow(Link::Counterpart),
+        Action::Follow(Link::Block),
+    ];
+
+    /// Short name for toasts.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Send => "send",
+            Action::ProbeMethod => "probe",
+            Action::SaveUiState => "save settings",
+            Action::FillNonce => "fetch nonce",
+            Action::Follow(_) => "follow",
+        }
+    }
+
+    /// Whether the action talks to a node or writes persisted state.
+    pub fn is_mutating(self) -> bool {
+        match self {
+            Action::Send | Action::ProbeMethod | Action::SaveUiState | Action::FillNonce | Action::Follow(_) => true,
+        }
+    }
+}
+
+/// Why `app` may not perform `action` right now, if it may not.
+pub fn check(app: &App, action: Action) -> Result<(), &'static str> {
+    if app.spectator && action.is_mutating() {
+        return Err(SPECTATOR_DISABLED);
+    }
+    Ok(())
+}
+
+/// Performs `action`, or shows a toast saying why it can't be performed.
+pub async fn dispatch(app: &mut App, action: Action) {
+    if let Err(reason) = check(app, action) {
+        app.show_toast(format!("{}: {}", action.label(), reason));
+        return;
+    }
+    match action {
+        Action::Send => send_current_request(app).await,
+        Action::ProbeMethod => probe_selected_method(app),
+        Action::FillNonce => fill_nonce(app).await,
+        Action::Follow(link) => follow(app, link),
+        Action::SaveUiState => {
+            if let Err(e) = app.save_ui_state() {
+                app.notice = Some(format!("settings not saved: {}", e));
+            }
+        }
+    }
+    // Failed actions leave a notice and don't count as tutorial progress
+    if app.notice.is_none()
+        && let Some(tutorial) = &mut app.tutorial
+    {
+        tutorial.on_action(action);
+    }
+}
+
+/// Sends the request described by ParamInput mode.
+///
+/// The endpoint override, if any, applies to this send only; the session
+/// endpoint is never modified. Immutable requests seen before (or
+/// prefetched) are answered from the cache and recorded at once; anything
+/// else goes on the send queue, which records it when it completes (see
+/// [`App::drain_sends`]). Input errors and a full queue are left in
+/// `app.notice` and keep the user in ParamInput mode so they can fix them.
+///
+/// Params that don't match the method's spec (see [`App::validate_params`])
+/// are held with a warning the same way: sending them again unchanged goes
+/// through, since specs can't know every client's extensions.
+///
+/// The first request of the session using `latest` or `pending` while the
+/// session endpoint is known to be syncing is held with a warning instead;
+/// sending again goes through.
+///
+/// In dry-run mode the request is built and validated exactly the same way,
+/// then recorded and shown without touching the network. This is the only
+/// place requests are sent from ParamInput, so anything that sends must go
+/// through it.
+async fn send_current_request(app: &mut App) {
+    let (url, endpoint_override) = match app.send_target() {
+        Ok(target) => target,
+        Err(e) => {
+            app.notice = Some(e.to_string());
+            return;
+        }
+    };
+    if let Err(e) = app.validate_params() {
+        let fields = (app.selected_method().unwrap_or_default().to_string(), app.param_inputs.clone());
+        if app.validation_warned.as_ref() != Some(&fields) {
+            app.notice = Some(format!("{} — send again to send anyway", e));
+            app.validation_warned = Some(fields);
+            return;
+        }
+    }
+    let request = match app.build_request() {
+        Ok(request) => request,
+        Err(e) => {
+            app.notice = Some(e.to_string());
+            return;
+        }
+    };
+    if sync::should_warn(app.stats.last().sync, &request.params, app.sync.warned, app.suppress_sync_warning)
+        && url == app.endpoint
+    {
+        app.sync.warned = true;
+        let badge = app.stats.last().sync.badge(&app.prefs).unwrap_or_default();
+        app.notice = Some(format!("endpoint is {} — latest/pending may be stale; send again to confirm", badge));
+        return;
+    }
+    if app.dry_run {
+        app.record_dry_run(request, endpoint_override);
+        // Shown as if opened from History, where Esc leads
+        app.home();
+        app.enter(AppMode::History);
+        app.history_state.select(Some(app.history.len() - 1));
+        app.open_selected_response();
+        return;
+    }
+    if let Some(response) = app.cached_response(&url, &request) {
+        app.after_response(&url, &request, &response);
+        app.record(request, response, endpoint_override);
+    } else if app.sends.submit(PendingSend { url, request, endpoint_override }).is_err() {
+        app.notice = Some(format!("{} requests already queued — wait or press Esc in the method list to clear", MAX_QUEUED));
+        return;
+    }
+    app.home();
+}
+
+/// Probes the highlighted method on the session endpoint in the
+/// background, unless this session already knows the answer or a probe is
+/// still running; [`App::tick`] records the answer. Methods that may
+/// change node state (see [`probe_allowed`]) aren't probed.
+fn probe_selected_method(app: &mut App) {
+    let Some(method) = app.selected_method().map(str::to_string) else { return };
+    let url = app.endpoint.clone();
+    if app.capabilities.get(&url, &method).is_some() || app.capability_probe.is_some() {
+        return;
+    }
+    if !probe_allowed(&method) {
+        app.show_toast(format!("{} may change node state, so it isn't probed", method));
+        return;
+    }
+    let id = app.next_id;
+    app.next_id += 1;
+    let slot = crate::app::ProbeSlot::default();
+    let (sink, transport) = (slot.clone(), app.prefetch_transport.clone());
+    let handle = tokio::spawn(async move {
+        let availability = probe(transport.as_ref(), &url, &method, id).await;
+        *sink.lock().unwrap() = Some((url, method, availability));
+    });
+    app.capability_probe = Some((handle, slot));
+}
+
+/// Sends the request `link` leads to from the open response, to the same
+/// endpoint, without going through ParamInput. It is recorded with a link
+/// back to the response, which the view then switches to (see
+/// [`App::following`]). Dry-run mode records it without sending.
+fn follow(app: &mut App, link: Link) {
+    let Some(entry) = app.history.get(app.history_state.selected().unwrap_or(0)) else { return };
+    let source = entry.seq;
+    let Some((method, params)) = follow::target(entry, link) else {
+        app.show_toast(format!("nothing to follow with '{}' here", link.key()));
+        return;
+    };
+    let endpoint_override = entry.endpoint_override.clone();
+    let url = endpoint_override.clone().unwrap_or_else(|| app.endpoint.clone());
+    let request = JsonRpcRequest::new(method, params, app.next_id);
+    app.next_id += 1;
+    app.following = Some((request.id, source));
+    if app.dry_run {
+        app.record_dry_run(request, endpoint_override);
+    } else if let Some(response) = app.cached_response(&url, &request) {
+        app.after_response(&url, &request, &response);
+        app.record(request, response, endpoint_override);
+    } else if app.sends.submit(PendingSend { url, request, endpoint_override }).is_err() {
+        app.following = None;
+        app.show_toast(format!("{} requests already queued — try again shortly", MAX_QUEUED));
+    }
+}
+
+/// Sets the transaction form's nonce to the pending transaction count of
+/// its `from` address on the session endpoint.
+async fn fill_nonce(app: &mut App) {
+    let Some(from) = app.tx_form.as_ref().map(|f| f.fields[txform::FROM].trim().to_string()) else { return };
+    if shape_of(&from) != Shape::Address {
+        app.notice = Some("nonce: fill in `from` first".to_string());
+        return;
+    }
+    let id = app.next_id;
+    app.next_id += 1;
+    let request = JsonRpcRequest::new("eth_getTransactionCount", json!([from, "pending"]), id);
+    let nonce = match app.prefetch_transport.send(&app.endpoint, request).await {
+        Ok(JsonRpcResponse { result: Some(Value::String(nonce)), .. }) => nonce,
+        Ok(response) => {
+            let reason = response.error.map(|e| e["message"].as_str().unwrap_or("error").to_string());
+            app.notice = Some(format!("nonce: {}", reason.unwrap_or_else(|| "unexpected response".to_string())));
+            return;
+        }
+        Err(e) => {
+            app.notice = Some(format!("nonce: {}", e));
+            return;
+        }
+    };
+    if let Some(form) = &mut app.tx_form {
+        let before = std::mem::replace(&mut form.fields[txform::NONCE], nonce);
+        app.notice = None;
+        app.record_edit(InputField::TxField(txform::NONCE), before, EditKind::Replace);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn spectator_blocks_every_mutating_action_with_a_toast() {
+        for &action in Action::ALL {
+            let mut app = App::new();
+            app.spectator = true;
+            app.mode = AppMode::ParamInput;
+            app.endpoint = "http://127.0.0.1:1".into();
+            app.ui_state_path = Some(std::env::temp_dir().join("eli-spectator-never-written.json"));
+            let next_id = app.next_id;
+
+            dispatch(&mut app, action).await;
+
+            let toast = app.toast.as_ref().map(|t| t.message.as_str());
+            assert_eq!(toast, Some(format!("{}: {}", action.label(), SPECTATOR_DISABLED).as_str()));
+            assert_eq!(app.next_id, next_id, "{:?} built a request", action);
+            assert!(app.history.is_empty());
+            assert!(app.notice.is_none());
+            assert!(app.capabilities.get(&app.endpoint, app.selected_method().unwrap()).is_none());
+            assert!(!app.ui_state_path.as_ref().unwrap().exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn actions_run_outside_spectator_mode() {
+        let mut app = App::new();
+        app.dry_run = true;
+        dispatch(&mut app, Action::Send).await;
+        assert!(app.toast.is_none());
+        assert_eq!(app.history.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn syncing_endpoint_holds_the_first_latest_request() {
+        let mut app = App::new();
+        app.dry_run = true;
+        app.stats.shared().update(|s| s.sync = crate::sync::SyncState::Syncing { current: 10, highest: 20 });
+        app.stats.refresh();
+        app.methods_state.select(Some(1));
+        app.param_inputs = vec!["0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f".to_string(), "latest".to_string()];
+
+        dispatch(&mut app, Action::Send).await;
+        assert!(app.history.is_empty());
+        assert!(app.notice.as_deref().unwrap().contains("SYNCING (block 10 / 20)"));
+
+        // Only once per session
+        dispatch(&mut app, Action::Send).await;
+        assert_eq!(app.history.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn params_off_spec_are_held_once_with_a_warning() {
+        let mut app = App::new();
+        app.dry_run = true;
+        app.methods_state.select(Some(1));
+        app.param_inputs = vec!["0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f".to_string(), String::new()];
+
+        dispatch(&mut app, Action::Send).await;
+        assert!(app.history.is_empty());
+        let warning = "eth_getBalance takes 2 params; block is missing — send again to send anyway";
+        assert_eq!(app.notice.as_deref(), Some(warning));
+
+        // Edited: checked again
+        app.notice = None;
+        app.param_inputs[0].push('f');
+        dispatch(&mut app, Action::Send).await;
+        assert!(app.history.is_empty());
+        assert!(app.notice.is_some());
+
+        // Unchanged: sent as it is
+        app.notice = None;
+        dispatch(&mut app, Action::Send).await;
+        assert_eq!(app.history.len(), 1);
+        assert_eq!(app.history[0].request.params, serde_json::json!(["0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4ff"]));
+    }
+}
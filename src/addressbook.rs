@@ -0,0 +1,222 @@
+// src/addressbook.rs
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::schema;
+use crate::storage::{self, Loaded, Revisioned, Saved};
+
+/// Well-known addresses: (chain id, address, label). `None` applies on
+/// every EVM chain.
+const BUILTIN: &[(Option<u64>, &str, &str)] = &[
+    (None, "0x0000000000000000000000000000000000000000", "zero address"),
+    (None, "0x0000000000000000000000000000000000000001", "ecrecover"),
+    (None, "0x0000000000000000000000000000000000000002", "sha256"),
+    (None, "0x0000000000000000000000000000000000000003", "ripemd160"),
+    (None, "0x0000000000000000000000000000000000000004", "identity"),
+    (None, "0x0000000000000000000000000000000000000005", "modexp"),
+    (None, "0x0000000000000000000000000000000000000006", "ecAdd"),
+    (None, "0x0000000000000000000000000000000000000007", "ecMul"),
+    (None, "0x0000000000000000000000000000000000000008", "ecPairing"),
+    (None, "0x0000000000000000000000000000000000000009", "blake2f"),
+    (None, "0x000000000000000000000000000000000000000a", "point evaluation"),
+    (Some(1), "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", "WETH"),
+    (Some(1), "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", "USDC"),
+    (Some(1), "0xdac17f958d2ee523a2206206994597c13d831ec7", "USDT"),
+    (Some(1), "0x7a250d5630b4cf539739df2c5dacb4c659f2488d", "Uniswap V2 Router"),
+    (Some(1), "0xe592427a0aece92de3edee1f18e0157c05861564", "Uniswap V3 SwapRouter"),
+    (Some(1), "0x68b3465833fb72a70ecdf485e0e4c7bd8665fc45", "Uniswap SwapRouter02"),
+    (Some(1), "0x3fc91a3afd70395cd496c647d5a6cc9d4b2b7fad", "Uniswap Universal Router"),
+];
+
+/// A user-labelled address. Without a chain id it applies on every chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressEntry {
+    pub address: String,
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+}
+
+/// The `address-book.json` config file: the user's labels, and whether the
+/// builtin registry is consulted after them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AddressBook {
+    pub builtin: bool,
+    pub entries: Vec<AddressEntry>,
+    /// Bumped on every save; see [`storage::save_revisioned`].
+    pub revision: u64,
+}
+
+impl Default for AddressBook {
+    fn default() -> Self {
+        AddressBook { builtin: true, entries: Vec::new(), revision: 0 }
+    }
+}
+
+impl Revisioned for AddressBook {
+    fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn set_revision(&mut self, revision: u64) {
+        self.revision = revision;
+    }
+}
+
+impl AddressBook {
+    /// Label for `address` on `chain_id`, first match wins:
+    ///
+    /// 1. a user entry for this chain
+    /// 2. a user entry without a chain
+    /// 3. a builtin entry for this chain (only when the chain id is known)
+    /// 4. a chain-independent builtin entry
+    ///
+    /// Builtin entries are skipped entirely when `builtin` is off.
+    /// Addresses compare case-insensitively.
+    pub fn label(&self, chain_id: Option<u64>, address: &str) -> Option<&str> {
+        let user = |chain: Option<u64>| {
+            self.entries
+                .iter()
+                .find(|e| e.chain_id == chain && e.address.eq_ignore_ascii_case(address))
+                .map(|e| e.label.as_str())
+        };
+        let builtin = |chain: Option<u64>| {
+            BUILTIN
+                .iter()
+                .find(|(c, a, _)| *c == chain && a.eq_ignore_ascii_case(address))
+                .map(|(_, _, label)| *label)
+        };
+        chain_id
+            .and_then(|_| user(chain_id))
+            .or_else(|| user(None))
+            .or_else(|| self.builtin.then(|| chain_id.and_then(|_| builtin(chain_id))).flatten())
+            .or_else(|| self.builtin.then(|| builtin(None)).flatten())
+    }
+
+    /// Labels `address` on `chain_id` (every chain for `None`), replacing
+    /// the user's label there; an empty `label` removes it.
+    pub fn set_label(&mut self, chain_id: Option<u64>, address: &str, label: &str) {
+        self.entries.retain(|e| !(e.chain_id == chain_id && e.address.eq_ignore_ascii_case(address)));
+        if !label.is_empty() {
+            self.entries.push(AddressEntry { address: address.to_string(), label: label.to_string(), chain_id });
+        }
+    }
+
+    /// Labels of the addresses in `line`, in order of appearance, joined
+    /// with ", "; `None` if nothing on the line is labelled.
+    pub fn annotate(&self, chain_id: Option<u64>, line: &str) -> Option<String> {
+        let labels: Vec<&str> = addresses_in(line).filter_map(|a| self.label(chain_id, a)).collect();
+        (!labels.is_empty()).then(|| labels.join(", "))
+    }
+}
+
+/// Every `0x` + 40 hex digit token in `text` not embedded in longer hex.
+pub fn addresses_in(text: &str) -> impl Iterator<Item = &str> {
+    let bytes = text.as_bytes();
+    let hex_run = move |start: usize| bytes[start..].iter().take_while(|b| b.is_ascii_hexdigit()).count();
+    text.match_indices("0x").filter_map(move |(i, _)| {
+        let preceded = i > 0 && bytes[i - 1].is_ascii_alphanumeric();
+        (!preceded && hex_run(i + 2) == 40).then(|| &text[i..i + 42])
+    })
+}
+
+/// Reads the address book; a missing file yields the builtin registry only.
+pub fn load(path: &Path) -> Result<Loaded<AddressBook>> {
+    Ok(storage::load_versioned(path, &schema::ADDRESS_BOOK)?.unwrap_or_else(|| Loaded::fresh(AddressBook::default())))
+}
+
+/// Writes `book` atomically; `base` is the book as loaded. If another eli
+/// saved since, the entries are merged as a set: both sides' additions
+/// are kept and this one's removals (and relabellings) apply. Returns
+/// what was written.
+pub fn save(path: &Path, base: &AddressBook, book: AddressBook) -> Result<(AddressBook, Saved)> {
+    storage::save_revisioned(path, &schema::ADDRESS_BOOK, base.revision, book, |mut theirs, ours| {
+        theirs.entries.retain(|e| !base.entries.contains(e) || ours.entries.contains(e));
+        let builtin = if ours.builtin != base.builtin { ours.builtin } else { theirs.builtin };
+        let entries = storage::merge_union(theirs.entries, ours.entries);
+        Ok(AddressBook { builtin, entries, revision: theirs.revision })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WETH: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+    const ROUTER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+    const VAULT: &str = "0x00000000000000000000000000000000000000c1";
+
+    fn entry(address: &str, label: &str, chain_id: Option<u64>) -> AddressEntry {
+        AddressEntry { address: address.into(), label: label.into(), chain_id }
+    }
+
+    #[test]
+    fn builtin_applies_only_on_its_chain() {
+        let book = AddressBook::default();
+        assert_eq!(book.label(Some(1), WETH), Some("WETH"));
+        assert_eq!(book.label(Some(10), WETH), None);
+        assert_eq!(book.label(None, WETH), None);
+        // Precompiles are the same everywhere
+        assert_eq!(book.label(Some(10), "0x0000000000000000000000000000000000000001"), Some("ecrecover"));
+    }
+
+    #[test]
+    fn user_entries_take_precedence() {
+        let mut book = AddressBook::default();
+        book.entries.push(entry(&WETH.to_lowercase(), "wrapped ether (mine)", None));
+        assert_eq!(book.label(Some(1), WETH), Some("wrapped ether (mine)"));
+        book.entries.push(entry(WETH, "mainnet WETH", Some(1)));
+        assert_eq!(book.label(Some(1), WETH), Some("mainnet WETH"));
+        assert_eq!(book.label(Some(5), WETH), Some("wrapped ether (mine)"));
+    }
+
+    #[test]
+    fn builtin_can_be_disabled() {
+        let mut book = AddressBook { builtin: false, ..AddressBook::default() };
+        assert_eq!(book.label(Some(1), WETH), None);
+        book.entries.push(entry(WETH, "still mine", None));
+        assert_eq!(book.label(Some(1), WETH), Some("still mine"));
+    }
+
+    #[test]
+    fn annotates_whole_addresses_only() {
+        let book = AddressBook::default();
+        let line = format!("  \"to\": \"{}\",", WETH);
+        assert_eq!(book.annotate(Some(1), &line).as_deref(), Some("WETH"));
+        // The first 20 bytes of a longer hex value are not an address
+        let input = format!("  \"input\": \"{}{}\",", WETH, "00".repeat(4));
+        assert_eq!(book.annotate(Some(1), &input), None);
+        let pair = format!("[\"{}\", \"0xdAC17F958D2ee523a2206206994597C13D831ec7\"]", WETH);
+        assert_eq!(book.annotate(Some(1), &pair).as_deref(), Some("WETH, USDT"));
+    }
+
+    #[test]
+    fn concurrent_saves_keep_both_sides_labels() {
+        let dir = std::env::temp_dir().join(format!("eli-addressbook-{}", std::process::id()));
+        let path = dir.join("address-book.json");
+        let mut start = AddressBook::default();
+        start.set_label(None, WETH, "weth");
+        start.set_label(None, ROUTER, "router");
+        let (base, _) = save(&path, &AddressBook::default(), start).unwrap();
+
+        // Two instances load revision 1; the first adds a label and saves
+        let mut first = base.clone();
+        first.set_label(Some(1), VAULT, "vault");
+        save(&path, &base, first).unwrap();
+
+        // The second relabels one and removes another, unaware of the first
+        let mut second = base.clone();
+        second.set_label(None, WETH, "wrapped ether");
+        second.set_label(None, ROUTER, "");
+        let (written, saved) = save(&path, &base, second).unwrap();
+        assert_eq!(saved, Saved { revision: 3, merged: true });
+        let labels: Vec<&str> = written.entries.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, ["vault", "wrapped ether"]);
+        assert_eq!(load(&path).unwrap().value, written);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,6 +1,110 @@
 // src/app.rs
 
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
 use ratatui::widgets::ListState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::task::JoinHandle;
+
+use crate::addressbook::{self, AddressBook};
+use crate::cache::ResponseCache;
+use crate::capability::{classify, Availability, CapabilityCache};
+use crate::chains::{ChainInfo, ChainTable, NativeCurrency};
+use crate::clipboard::{self, Clipboard, Payload, SystemClipboard};
+use crate::clock::{Clock, ResumeDetector, SystemClock};
+use crate::dashboard::{Dashboard, DashboardConfig};
+use crate::decode::DecoderRegistry;
+use crate::defaults::ParamDefaults;
+use crate::docs::{DocsTab, Hydrator, SpecResolver};
+use crate::editor::{LineCursor, TextArea};
+use crate::extension::{self, ExtensionChoice, SpecExtension};
+use crate::auth::{self, AuthToken, Credentials, ShellRunner};
+use crate::endpoint::{resolve_endpoint, EndpointConfig, EndpointProfile, DEFAULT_ENDPOINT};
+use crate::fixture;
+use crate::focus::Focus;
+use crate::head::{self, HeadWatch};
+use crate::history::{self, HistoryStore};
+use crate::middleware::LatencyRecorder;
+use crate::migrate::{migrate_params, value_to_field, ParamsMigration};
+use crate::origin::{self, SpecStamp};
+use crate::prefetch::{neighbor_blocks, Prefetcher};
+use crate::popup::EditorPopup;
+use crate::prefs::DisplayPrefs;
+use crate::pretty;
+use crate::provenance::Trail;
+use crate::provider::{self, Provider};
+use crate::queue::SendQueue;
+use crate::raw::RawParams;
+use crate::schema::{self, Schema};
+use crate::schematree;
+use crate::simulate;
+use crate::spill;
+use crate::shape::{is_block_number, shape_of, to_quantity, Shape};
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse, RetryPolicy, RpcClient, TooLarge, Transport};
+use crate::perf::{self, FrameBudget};
+use crate::plugin::{self, Plugin, PluginDecoder, PluginMethod};
+use crate::search;
+use crate::spec::{self, MethodSpec, ParamKind, RENAMED_METHODS, RPC_SPECS};
+use crate::stats::{Health, StatsView};
+use crate::storage::{self, Lease};
+use crate::subscription::{Opened, PaneEvent, Subscriptions};
+use crate::sync::SyncWatch;
+use crate::tasks::{SharedTasks, TaskHandle, TaskKind};
+use crate::trace;
+use crate::txform::{self, TxForm};
+use crate::tutorial::{self, OfflineTransport, Tutorial};
+use crate::ws::{Connector, ReconnectPolicy, WsConnector};
+use crate::uistate::{self, UiState};
+use crate::undo::{EditKind, UndoStack};
+use crate::update;
+use crate::hints::Context as KeyContext;
+use crate::view::ResponseView;
+
+/// Where a startup chain-id probe leaves its answer.
+pub type ChainSlot = Arc<Mutex<Option<u64>>>;
+
+/// Where the update check leaves its toast.
+pub type UpdateSlot = Arc<Mutex<Option<String>>>;
+
+/// Where a capability probe leaves the endpoint, method and answer.
+pub type ProbeSlot = Arc<Mutex<Option<(String, String, Availability)>>>;
+
+/// The JSON value a param field stands for: its text parsed as JSON when
+/// it is JSON (numbers, booleans, objects, quoted strings), otherwise the
+/// text as a string, so `latest` and `0x1f` need no quotes. Input holding an
+/// integer too big for u64/i64 is kept as text too: serde_json would round
+/// it through f64 and send a different number. The inverse of
+/// [`value_to_field`].
+pub fn parse_param(input: &str) -> Value {
+    match serde_json::from_str(input) {
+        Ok(value) if !rounds_big_integer(&value) => value,
+        _ => Value::String(input.to_string()),
+    }
+}
+
+/// Whether `value` holds a float with no fraction beyond i64 range, the
+/// shape serde_json gives an integer literal it could not keep exactly.
+fn rounds_big_integer(value: &Value) -> bool {
+    match value {
+        Value::Number(n) => n.as_f64().is_some_and(|f| n.is_f64() && f.fract() == 0.0 && f.abs() >= i64::MAX as f64),
+        Value::Array(items) => items.iter().any(rounds_big_integer),
+        Value::Object(map) => map.values().any(rounds_big_integer),
+        _ => false,
+    }
+}
+
+fn parse_chain_id(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+}
+
+fn csv_file(path: &str) -> anyhow::Result<std::io::BufWriter<std::fs::File>> {
+    let file = std::fs::File::create(path).with_context(|| format!("writing {}", path))?;
+    Ok(std::io::BufWriter::new(file))
+}
 
 /// Represents the current UI mode of the application.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,17 +115,144 @@ pub enum AppMode {
     ParamInput,
     /// History mode: browse and reload previous requests.
     History,
+    /// Response view: read and search one response in full.
+    ResponseView,
+    /// Dashboard: a grid of polled values.
+    Dashboard,
+}
+
+impl AppMode {
+    /// Name in the breadcrumb.
+    pub fn label(self) -> &'static str {
+        match self {
+            AppMode::Main => "Main",
+            AppMode::ParamInput => "Params",
+            AppMode::History => "History",
+            AppMode::ResponseView => "Response",
+            AppMode::Dashboard => "Dashboard",
+        }
+    }
+}
+
+/// A completed request together with its response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// The request as it was sent.
+    pub request: JsonRpcRequest,
+    /// The response returned by the node.
+    pub response: JsonRpcResponse,
+    /// One-off endpoint the request was sent to, or `None` for the session endpoint.
+    pub endpoint_override: Option<String>,
+    /// Round-trip time measured by the client's latency middleware.
+    pub latency: Option<std::time::Duration>,
+    /// The method's param names in the spec at the time of sending, used to
+    /// detect spec changes when the entry is reloaded.
+    pub spec_params: Option<Vec<String>>,
+    /// Built and validated but never sent; `response` is an empty placeholder.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Hosted provider the request went to, when recognised.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Provider>,
+    /// How the params got from the fields into the request.
+    #[serde(default, skip_serializing_if = "Trail::is_empty")]
+    pub provenance: Trail,
+    /// [`seq`](HistoryEntry::seq) of the entry this request was followed
+    /// from (`x`/`b` in the response view).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derived_from: Option<u64>,
+    /// Which sources defined the method when the entry was recorded, used
+    /// like `spec_params` to flag spec changes on reload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<SpecStamp>,
+    /// File the result was spilled to for being too large to keep (see
+    /// [`spill::SPILL_LIMIT`]); `response.result` is `None` then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spill: Option<std::path::PathBuf>,
+    /// The entry's number for humans ("#12"): assigned when recorded,
+    /// persisted, and never reused, unlike list positions or the wire id
+    /// in `request.id`. 0 until assigned.
+    #[serde(default)]
+    pub seq: u64,
+    /// Whether reloading it realigns its params onto a changed spec, as
+    /// [`App::migrate_entry`] found when the entry was loaded or recorded,
+    /// or when the spec last changed. Not persisted.
+    #[serde(skip)]
+    pub needs_review: bool,
+}
+
+impl HistoryEntry {
+    /// `request` answered with `response`, every other field at its
+    /// default; set the ones that apply with struct update syntax.
+    pub fn new(request: JsonRpcRequest, response: JsonRpcResponse) -> Self {
+        HistoryEntry {
+            request,
+            response,
+            endpoint_override: None,
+            latency: None,
+            spec_params: None,
+            dry_run: false,
+            provider: None,
+            provenance: Trail::default(),
+            derived_from: None,
+            spec: None,
+            spill: None,
+            seq: 0,
+            needs_review: false,
+        }
+    }
+}
+
+/// How long a toast stays on screen.
+pub const TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// An action on history entries waiting for the user to confirm it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryAction {
+    Delete,
+    Export,
+}
+
+impl HistoryAction {
+    fn verb(self) -> &'static str {
+        match self {
+            HistoryAction::Delete => "delete",
+            HistoryAction::Export => "export",
+        }
+    }
+}
+
+/// A short message shown over any mode until it expires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toast {
+    pub message: String,
+    pub expires: std::time::Instant,
+}
+
+/// A ParamInput text field, which keeps its own undo history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InputField {
+    Param(usize),
+    /// A row of the transaction form.
+    TxField(usize),
+    /// The one-off endpoint override.
+    Override,
 }
 
 /// Application state shared across the TUI.
 pub struct App {
-    /// Current UI mode.
+    /// Current UI mode. Change it with [`App::enter`], [`App::back`] and
+    /// [`App::home`] so Esc can retrace the way.
     pub mode: AppMode,
+    /// The modes `mode` was opened from, the method list first.
+    pub nav: Vec<AppMode>,
     /// Flag to indicate when the app should quit.
     pub should_quit: bool,
 
     /// Current search string for filtering methods.
     pub search_input: String,
+    /// Where typing goes in `search_input`.
+    pub search_cursor: LineCursor,
     /// Full list of available JSON-RPC methods.
     pub all_methods: Vec<String>,
     /// Filtered list of methods matching `search_input`.
@@ -29,25 +260,227 @@ pub struct App {
     /// Stateful selection index for the methods list.
     pub methods_state: ListState,
 
+    /// Method reloaded from history that isn't in `all_methods`; sent in place
+    /// of the list selection until ParamInput mode is left.
+    pub unlisted_method: Option<String>,
+
     /// Current parameter inputs for the selected method.
     pub param_inputs: Vec<String>,
+    /// Index into `param_inputs` that typing goes to.
+    pub focused_param: usize,
+    /// Cursor in the focused ParamInput field (param, transaction form
+    /// row or endpoint override); back at the end whenever focus moves.
+    pub input_cursor: LineCursor,
+    /// Method last opened in ParamInput mode.
+    pub param_method: Option<String>,
+    /// The method opened before `param_method`, for quick switching.
+    pub previous_method: Option<String>,
+    /// Param fields each method had when another one was opened.
+    pub drafts: std::collections::HashMap<String, Vec<String>>,
+    /// Param provenance of built requests by id, until they are recorded.
+    pub provenance: std::collections::HashMap<u64, Trail>,
+    /// Configured default params, applied to empty fields on entering ParamInput.
+    pub param_defaults: ParamDefaults,
+    /// Default put into each of `param_inputs`, if any; a field still
+    /// holding it is drawn as a default.
+    pub seeded_defaults: Vec<Option<String>>,
+    /// Undo history of each ParamInput text field.
+    pub field_undo: BTreeMap<InputField, UndoStack<String>>,
+    /// Undo history of the raw-JSON buffer.
+    pub raw_undo: UndoStack<TextArea>,
+    /// Method and fields a validation warning was shown for; sending them
+    /// again unchanged goes through.
+    pub validation_warned: Option<(String, Vec<String>)>,
+    /// Endpoint override for the next send (URL or profile name); `None` while hidden.
+    pub endpoint_override: Option<String>,
+    /// Raw-JSON editor for the whole params value; `Some` while raw mode is active.
+    pub raw_params: Option<RawParams>,
+    /// Field form for `eth_sendTransaction`, used instead of `param_inputs`.
+    pub tx_form: Option<TxForm>,
+    /// Popup editor for a single long value; `Some` while it is open.
+    pub editor_popup: Option<EditorPopup>,
 
-    /// History of (request, response) pairs.
-    pub history: Vec<(crate::rpc::JsonRpcRequest, crate::rpc::JsonRpcResponse)>,
+    /// Session endpoint requests are sent to.
+    pub endpoint: String,
+    /// Named endpoints that can be used in place of a URL.
+    pub profiles: Vec<EndpointProfile>,
+    /// Fixed credentials and the endpoint they authorize, see
+    /// [`App::set_credentials`].
+    pub credentials: Option<(String, Credentials)>,
+    /// New session endpoint being typed (URL or profile name); `Some`
+    /// while the prompt is open.
+    pub endpoint_edit: Option<String>,
+    /// Selected row of the endpoint profile picker; `Some` while it is open.
+    pub profile_picker: Option<usize>,
+    /// Id assigned to the next outgoing request.
+    pub next_id: u64,
+    /// Client for user sends (the send queue holds a clone); carries the
+    /// latency middleware below.
+    pub client: RpcClient,
+    /// Latency samples of every request sent through `client`.
+    pub latency: LatencyRecorder,
+    /// Responses to immutable requests (blocks by number/hash, receipts, …).
+    pub cache: Arc<Mutex<ResponseCache>>,
+    /// Fetch neighbouring blocks in the background after viewing one.
+    pub prefetch: bool,
+    /// Background cache warmer; cancelled on any user input.
+    pub prefetcher: Prefetcher,
+    /// Transport for background traffic (prefetches, capability probes): no
+    /// latency middleware, so it stays out of stats.
+    pub prefetch_transport: Arc<dyn Transport>,
+    /// Which methods each endpoint serves, as learned this session.
+    pub capabilities: CapabilityCache,
+    /// Docs-panel metadata, looked up in the background for the selected method.
+    pub docs: Hydrator,
+    /// Docs panel tab; Left/Right switch it while the panel has focus.
+    pub docs_tab: DocsTab,
+    /// Levels of the result schema shown below the root.
+    pub schema_depth: usize,
+    /// Pane of the current mode that takes its keys; read it with
+    /// [`App::pane`].
+    pub focus: Option<Focus>,
+    /// Lines the focused scrolling pane (docs, preview or detail) is
+    /// scrolled by.
+    pub pane_scroll: u16,
+    /// Last error or notice to show the user.
+    pub notice: Option<String>,
+    /// Build and validate requests but record them instead of sending.
+    pub dry_run: bool,
+    /// Read-only session: actions that send or persist are refused.
+    pub spectator: bool,
+    /// Replace addresses with placeholders in exported fixtures.
+    pub redact_addresses: bool,
+    /// Transient message shown in any mode.
+    pub toast: Option<Toast>,
+    /// Where `y`/`Y` copy requests and responses to.
+    pub clipboard: Box<dyn Clipboard>,
+    /// Where toasts, ticks and the head status read the time.
+    pub clock: Arc<dyn Clock>,
+    /// Notices a resume from suspend between ticks.
+    pub resume: ResumeDetector,
+
+    /// History of completed requests.
+    pub history: Vec<HistoryEntry>,
+    /// Where new history entries are appended; `None` keeps them in memory.
+    pub history_store: Option<HistoryStore>,
+    /// Entries kept in `history` and its file; older ones are dropped.
+    pub history_limit: usize,
+    /// Where results too large to keep are written (see [`App::set_spill_dir`]);
+    /// `None` keeps them in memory.
+    pub spill_dir: Option<std::path::PathBuf>,
     /// Stateful selection index for the history list.
     pub history_state: ListState,
+    /// [`HistoryEntry::seq`]s of the entries marked in History mode, which
+    /// the history actions then apply to.
+    pub history_marks: BTreeSet<u64>,
+    /// Action on several entries, asked to be confirmed.
+    pub history_confirm: Option<HistoryAction>,
+    /// Rows of the methods or history list visible in the last draw, so
+    /// PageUp/PageDown move by what's on screen.
+    pub list_height: usize,
+    /// Response currently open in ResponseView mode.
+    pub response_view: Option<ResponseView>,
+
+    /// How numbers, times and addresses are rendered.
+    pub prefs: DisplayPrefs,
+    /// Method-specific renderings shown above response JSON.
+    pub decoders: DecoderRegistry,
+    /// Selected row of the settings popup; `Some` while it is open.
+    pub settings: Option<usize>,
+    /// Every periodic background task, for the activity popup.
+    pub tasks: SharedTasks,
+    /// Selected row of the background activity popup; `Some` while it is open.
+    pub activity: Option<usize>,
+    /// Keys listed by the help overlay; `Some` while it is open.
+    pub help: Option<KeyContext>,
+    /// Where UI state is persisted; `None` keeps it in memory only.
+    pub ui_state_path: Option<std::path::PathBuf>,
+    /// Input-to-frame timing, flagged in the UI when sluggish.
+    pub frame: FrameBudget,
+    /// UI state as last loaded or saved, the base for merging another
+    /// instance's saves.
+    pub ui_state: UiState,
+    /// Persisted files upgraded from an older version at startup, as
+    /// "file (vN)"; reported once by [`App::report_migrations`].
+    pub migrated: Vec<String>,
+    /// Known chains: builtin table merged with the user's `chains/` files.
+    pub chains: ChainTable,
+    /// Chain id of the session endpoint, learned from its last `eth_chainId` response.
+    pub chain_id: Option<u64>,
+    /// `eth_chainId` asked of the session endpoint at startup, until it answers.
+    pub chain_probe: Option<(JoinHandle<()>, ChainSlot)>,
+    /// Update check started after the first frame, until it finishes; its
+    /// slot holds the toast to show, if any.
+    pub update_probe: Option<(JoinHandle<()>, UpdateSlot)>,
+    /// Ctrl+P probe of a method in the background, until it answers.
+    pub capability_probe: Option<(JoinHandle<()>, ProbeSlot)>,
+    /// Which spec extension to use, from UI state.
+    pub extension_choice: ExtensionChoice,
+    /// Spec extension merged into `specs`, if any.
+    pub extension: Option<&'static SpecExtension>,
+    /// Methods the extension is merged into: the builtin registry, or the
+    /// `--spec` file.
+    pub base_specs: Vec<MethodSpec>,
+    /// The active spec: the base methods with the extension merged in.
+    pub specs: Vec<MethodSpec>,
+    /// Where the base methods come from ("builtin" or the `--spec` file
+    /// name) and the methods it defines, for the docs panel.
+    pub spec_source: (String, BTreeSet<String>),
+    /// Methods defined by plugin files, with their params templates and
+    /// extract pointers.
+    pub plugin_methods: Vec<PluginMethod>,
+    /// User address labels over the builtin registry, shown next to
+    /// addresses in responses.
+    pub address_book: AddressBook,
+    /// Where labels added in the response view are saved; `None` keeps
+    /// them in memory only.
+    pub address_book_path: Option<std::path::PathBuf>,
+    /// The address book as last loaded or saved, the base for merging
+    /// another instance's saves.
+    pub address_book_base: AddressBook,
+    /// User sends: one in flight, more queued behind it.
+    pub sends: SendQueue,
+    /// Request id of a follow-up sent from the response view, and the
+    /// [`HistoryEntry::seq`] it was followed from; the view switches to it
+    /// once it is recorded.
+    pub following: Option<(u64, u64)>,
+    /// Set by a first Ctrl+C while sends are pending; the next one quits.
+    pub quit_armed: bool,
+    /// Monitoring cards; polled only while Dashboard mode is shown.
+    pub dashboard: Dashboard,
+    /// Periodic `eth_syncing` probe of the session endpoint.
+    pub sync: SyncWatch,
+    /// Never hold latest/pending requests for the syncing warning.
+    pub suppress_sync_warning: bool,
+    /// Latest-block poll of the session endpoint.
+    pub head: HeadWatch,
+    /// `newHeads` subscription on the active profile's `ws_url`; the head
+    /// isn't polled while it runs.
+    pub live_heads: Option<Subscriptions>,
+    /// `live_heads` in the activity panel, which can pause or kill it.
+    live_heads_task: TaskHandle,
+    /// Set when another local eli holds the websocket connection live
+    /// heads wanted, until the user subscribes anyway or keeps polling.
+    pub lease_prompt: Option<Lease>,
+    /// Where endpoint leases are kept (the config dir). Live heads need
+    /// one, so other instances can see the connection is taken.
+    pub lease_dir: Option<std::path::PathBuf>,
+    /// Opens websocket connections; tests swap in scripted ones.
+    pub ws_connector: Arc<dyn Connector>,
+    /// Sync status and latest block, written by the background probes;
+    /// read through the last snapshot so drawing never waits on them.
+    pub stats: StatsView,
+    /// Seconds without a block before the chain is flagged as stalled;
+    /// `None` picks one by chain.
+    pub stall_after: Option<u64>,
+    /// Guided walkthrough (`--tutorial`); `Some` while it runs.
+    pub tutorial: Option<Tutorial>,
 }
 
 impl App {
     /// Constructs a new `App` with default values.
     pub fn new() -> Self {
-        let all_methods = vec![
-            "eth_blockNumber".to_string(),
-            "eth_getBalance".to_string(),
-            "eth_gasPrice".to_string(),
-            "eth_call".to_string(),
-            // ... add more methods as needed
-        ];
+        let all_methods: Vec<String> = RPC_SPECS.iter().map(|spec| spec.name.to_string()).collect();
 
         let mut methods_state = ListState::default();
         methods_state.select(Some(0));
@@ -57,81 +490,2516 @@ impl App {
 
         let filtered_methods = all_methods.clone();
 
+        let latency = LatencyRecorder::default();
+        let client = RpcClient::new().with_retry(RetryPolicy::default()).with_middleware(latency.clone());
+        let sends = SendQueue::new(Arc::new(client.clone()));
+
         App {
             mode: AppMode::Main,
+            nav: Vec::new(),
             should_quit: false,
             search_input: String::new(),
+            search_cursor: LineCursor::default(),
             all_methods,
             filtered_methods,
             methods_state,
+            unlisted_method: None,
             param_inputs: Vec::new(),
+            focused_param: 0,
+            input_cursor: LineCursor::default(),
+            param_method: None,
+            previous_method: None,
+            drafts: Default::default(),
+            provenance: Default::default(),
+            param_defaults: ParamDefaults::default(),
+            seeded_defaults: Vec::new(),
+            field_undo: BTreeMap::new(),
+            raw_undo: UndoStack::default(),
+            validation_warned: None,
+            endpoint_override: None,
+            raw_params: None,
+            tx_form: None,
+            editor_popup: None,
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            profiles: Vec::new(),
+            profile_picker: None,
+            credentials: None,
+            endpoint_edit: None,
+            next_id: 1,
+            client,
+            latency,
+            cache: Arc::new(Mutex::new(ResponseCache::default())),
+            prefetch: false,
+            prefetcher: Prefetcher::default(),
+            prefetch_transport: Arc::new(RpcClient::new()),
+            capabilities: CapabilityCache::default(),
+            docs: Hydrator::new(Arc::new(SpecResolver::default())),
+            docs_tab: DocsTab::default(),
+            focus: Focus::initial(AppMode::Main),
+            pane_scroll: 0,
+            schema_depth: schematree::DEFAULT_DEPTH,
+            notice: None,
+            dry_run: false,
+            spectator: false,
+            redact_addresses: false,
+            toast: None,
+            clipboard: Box::new(SystemClipboard::default()),
+            clock: Arc::new(SystemClock),
+            resume: ResumeDetector::default(),
             history: Vec::new(),
+            history_store: None,
+            history_limit: history::DEFAULT_LIMIT,
+            spill_dir: None,
+            history_marks: BTreeSet::new(),
+            history_confirm: None,
             history_state,
+            list_height: 0,
+            response_view: None,
+            prefs: DisplayPrefs::default(),
+            decoders: DecoderRegistry::default(),
+            settings: None,
+            tasks: SharedTasks::default(),
+            activity: None,
+            help: None,
+            ui_state_path: None,
+            frame: FrameBudget::default(),
+            ui_state: UiState::default(),
+            migrated: Vec::new(),
+            chains: ChainTable::builtin(),
+            chain_id: None,
+            chain_probe: None,
+            update_probe: None,
+            capability_probe: None,
+            extension_choice: ExtensionChoice::Auto,
+            extension: None,
+            base_specs: RPC_SPECS.to_vec(),
+            spec_source: ("builtin".to_string(), RPC_SPECS.iter().map(|m| m.name.to_string()).collect()),
+            specs: RPC_SPECS.to_vec(),
+            plugin_methods: Vec::new(),
+            address_book: AddressBook::default(),
+            address_book_path: None,
+            address_book_base: AddressBook::default(),
+            sends,
+            following: None,
+            quit_armed: false,
+            dashboard: Dashboard::new(DashboardConfig::default()),
+            sync: SyncWatch::default(),
+            suppress_sync_warning: false,
+            head: HeadWatch::default(),
+            live_heads: None,
+            live_heads_task: TaskHandle::default(),
+            lease_prompt: None,
+            lease_dir: None,
+            ws_connector: Arc::new(WsConnector::default()),
+            stats: StatsView::default(),
+            stall_after: None,
+            tutorial: None,
+        }
+    }
+
+    /// Opens `mode` over the current one, so [`App::back`] returns here. A
+    /// mode already further down is returned to instead, closing everything
+    /// above it.
+    pub fn enter(&mut self, mode: AppMode) {
+        if mode == self.mode {
+            return;
+        }
+        match self.nav.iter().position(|m| *m == mode) {
+            Some(at) => {
+                let above: Vec<AppMode> = self.nav.drain(at..).skip(1).collect();
+                self.leave(self.mode);
+                above.into_iter().rev().for_each(|m| self.leave(m));
+            }
+            None => self.nav.push(self.mode),
+        }
+        self.mode = mode;
+        self.reset_focus();
+    }
+
+    /// Closes the current mode, back to the one it was opened from (or the
+    /// method list). False at the method list, which has nothing under it.
+    pub fn back(&mut self) -> bool {
+        let previous = match self.nav.pop() {
+            Some(previous) => previous,
+            None if self.mode != AppMode::Main => AppMode::Main,
+            None => return false,
+        };
+        self.leave(self.mode);
+        self.mode = previous;
+        self.reset_focus();
+        true
+    }
+
+    /// The pane of the current mode that takes its keys: the focused one,
+    /// or the mode's first if focus was left on another mode's pane.
+    pub fn pane(&self) -> Option<Focus> {
+        self.focus.filter(|f| Focus::ring(self.mode).contains(f)).or_else(|| Focus::initial(self.mode))
+    }
+
+    /// Moves focus `step` panes along the current mode's ring.
+    pub fn cycle_pane(&mut self, step: isize) {
+        if let Some(next) = self.pane().and_then(|f| f.cycle(self.mode, step)) {
+            self.focus_pane(next);
+        }
+    }
+
+    /// Focuses `pane`, scrolled to its top if it wasn't focused already.
+    pub fn focus_pane(&mut self, pane: Focus) {
+        if self.focus != Some(pane) {
+            self.focus = Some(pane);
+            self.pane_scroll = 0;
+        }
+    }
+
+    /// Focus goes to a mode's first pane whenever it is shown, entered or
+    /// returned to.
+    fn reset_focus(&mut self) {
+        self.focus = Focus::initial(self.mode);
+        self.pane_scroll = 0;
+    }
+
+    /// Closes every mode, back to the method list.
+    pub fn home(&mut self) {
+        while self.back() {}
+    }
+
+    /// Drops what only made sense while `mode` was open.
+    fn leave(&mut self, mode: AppMode) {
+        match mode {
+            AppMode::Main => {}
+            AppMode::ParamInput => {
+                self.input_cursor.reset();
+                self.endpoint_override = None;
+                self.raw_params = None;
+                self.tx_form = None;
+                self.unlisted_method = None;
+                self.notice = None;
+                // Sent or abandoned: nothing left to undo
+                self.clear_undo();
+            }
+            AppMode::History => {
+                self.history_marks.clear();
+                self.history_confirm = None;
+            }
+            AppMode::ResponseView => self.response_view = None,
+            AppMode::Dashboard => self.dashboard.pause(&self.tasks),
+        }
+    }
+
+    /// Which keys apply to the screen shown, for its hint line and the
+    /// help overlay.
+    pub fn key_context(&self) -> KeyContext {
+        match self.mode {
+            AppMode::Main => KeyContext::Main,
+            AppMode::ParamInput if self.raw_params.is_some() => KeyContext::ParamRaw,
+            AppMode::ParamInput if self.tx_form.is_some() => KeyContext::TxForm,
+            AppMode::ParamInput if self.param_kind(self.focused_param) != ParamKind::Text => KeyContext::ParamToggle,
+            AppMode::ParamInput => KeyContext::ParamFields,
+            AppMode::History => KeyContext::History,
+            AppMode::ResponseView if self.response_view.as_ref().is_some_and(|v| v.hex.is_some()) => KeyContext::HexDump,
+            AppMode::ResponseView => KeyContext::Response,
+            AppMode::Dashboard => KeyContext::Dashboard,
         }
     }
 
-    /// Filters `all_methods` by the current `search_input`, updating `filtered_methods` and resetting selection.
+    /// Where the user is: the open modes, then any popup over them, e.g.
+    /// "Main › History › Response".
+    pub fn breadcrumb(&self) -> String {
+        let popup = if self.settings.is_some() {
+            Some("Settings")
+        } else if self.activity.is_some() {
+            Some("Activity")
+        } else if self.profile_picker.is_some() {
+            Some("Profiles")
+        } else if self.lease_prompt.is_some() {
+            Some("Live heads")
+        } else if self.editor_popup.is_some() {
+            Some("Editor")
+        } else if self.help.is_some() {
+            Some("Help")
+        } else {
+            None
+        };
+        // The method list is always at the bottom, even when not recorded
+        let root = (self.nav.first().unwrap_or(&self.mode) != &AppMode::Main).then_some(&AppMode::Main);
+        let modes = root.into_iter().chain(&self.nav).chain([&self.mode]).map(|m| m.label());
+        modes.chain(popup).collect::<Vec<_>>().join(" › ")
+    }
+
+    /// Filters `all_methods` by the current `search_input`, best fuzzy match
+    /// first, updating `filtered_methods` and resetting selection.
     pub fn filter_methods(&mut self) {
-        let query = self.search_input.to_lowercase();
-        if query.is_empty() {
+        self.frame.pass("filter");
+        self.filtered_methods = search::fuzzy_filter(&self.all_methods, &self.search_input);
+        // Reset selection index
+        self.methods_state.select(Some(0));
+        self.pane_scroll = 0;
+    }
+
+    /// Returns the method requests are built for: an unlisted method loaded
+    /// from history, else the highlighted one, if any.
+    pub fn selected_method(&self) -> Option<&str> {
+        if let Some(method) = &self.unlisted_method {
+            return Some(method);
+        }
+        self.methods_state
+            .selected()
+            .and_then(|i| self.filtered_methods.get(i))
+            .map(|m| m.as_str())
+    }
+
+    /// For `eth_getBlockByNumber` with a hash as the first param (or
+    /// `eth_getBlockByHash` with a number), the method the param fits and a
+    /// suggestion to show.
+    pub fn block_method_suggestion(&self) -> Option<(&'static str, &'static str)> {
+        if self.raw_params.is_some() {
+            return None;
+        }
+        let first = self.param_inputs.first()?;
+        match self.selected_method()? {
+            "eth_getBlockByNumber" if shape_of(first) == Shape::Hash32 => Some((
+                "eth_getBlockByHash",
+                "this looks like a block hash — switch to eth_getBlockByHash? (Ctrl+B)",
+            )),
+            "eth_getBlockByHash" if is_block_number(first) => Some((
+                "eth_getBlockByNumber",
+                "this looks like a block number — switch to eth_getBlockByNumber? (Ctrl+B)",
+            )),
+            _ => None,
+        }
+    }
+
+    /// Applies [`App::block_method_suggestion`]: selects the other method and
+    /// carries the params over (a decimal block number becomes hex; the
+    /// include-transactions flag is untouched). Returns whether it switched.
+    pub fn switch_block_method(&mut self) -> bool {
+        let Some((target, _)) = self.block_method_suggestion() else { return false };
+        if let Some(first) = self.param_inputs.first_mut() {
+            *first = to_quantity(first);
+        }
+        if !self.filtered_methods.iter().any(|m| m == target) {
+            self.search_input.clear();
             self.filtered_methods = self.all_methods.clone();
-        } else {
-            self.filtered_methods = self
-                .all_methods
+        }
+        let Some(idx) = self.filtered_methods.iter().position(|m| m == target) else {
+            // Not in the method list at all: send it by name
+            self.unlisted_method = Some(target.to_string());
+            return true;
+        };
+        self.unlisted_method = None;
+        self.methods_state.select(Some(idx));
+        true
+    }
+
+    /// Notes that `method` is being opened in ParamInput mode: the fields
+    /// of the method open before are kept as its draft, and it becomes the
+    /// previous method unless it's the same one.
+    pub fn track_param_method(&mut self, method: &str) {
+        if let Some(current) = self.param_method.take() {
+            self.drafts.insert(current.clone(), self.param_inputs.clone());
+            if current != method {
+                self.previous_method = Some(current);
+            }
+        }
+        self.param_method = Some(method.to_string());
+        self.clear_undo();
+    }
+
+    /// Like `cd -`: opens the previous method in ParamInput mode with its
+    /// draft params, so repeated calls alternate between two methods.
+    pub fn switch_to_previous_method(&mut self) {
+        let Some(previous) = self.previous_method.clone() else {
+            self.show_toast("no previous method yet");
+            return;
+        };
+        self.track_param_method(&previous);
+        self.search_input.clear();
+        self.filtered_methods = self.all_methods.clone();
+        match self.all_methods.iter().position(|m| *m == previous) {
+            Some(idx) => {
+                self.unlisted_method = None;
+                self.methods_state.select(Some(idx));
+            }
+            None => self.unlisted_method = Some(previous.clone()),
+        }
+        self.param_inputs = self.drafts.get(&previous).cloned().unwrap_or_else(|| self.blank_params(&previous));
+        self.focused_param = 0;
+        self.seeded_defaults = Vec::new();
+        self.tx_form = (previous == txform::METHOD).then(TxForm::default);
+        self.raw_params = None;
+        self.endpoint_override = None;
+        self.notice = None;
+        self.enter(AppMode::ParamInput);
+    }
+
+    /// Empty fields for `method`: one per spec param.
+    pub fn blank_params(&self, method: &str) -> Vec<String> {
+        // Methods missing from the registry get one free-form field
+        let count = self.find_spec(method).map_or(1, |s| s.params.len());
+        vec![String::new(); count]
+    }
+
+    /// Moves `focused_param` by `delta` fields, wrapping around.
+    pub fn move_param_focus(&mut self, delta: isize) {
+        let count = self.param_inputs.len().max(1) as isize;
+        self.focused_param = (self.focused_param as isize + delta).rem_euclid(count) as usize;
+        self.input_cursor.reset();
+    }
+
+    /// Fills empty param fields of the selected method from `param_defaults`.
+    pub fn apply_param_defaults(&mut self) {
+        let Some(method) = self.selected_method().map(str::to_string) else { return };
+        let params = self.find_spec(&method).map(|s| s.params.clone()).unwrap_or_default();
+        let params: Vec<&str> = params.iter().collect();
+        self.seeded_defaults = self.param_defaults.seed(&method, &params, &mut self.param_inputs);
+    }
+
+    /// Whether param `index` still holds the default it was seeded with.
+    pub fn is_default_param(&self, index: usize) -> bool {
+        match (self.seeded_defaults.get(index), self.param_inputs.get(index)) {
+            (Some(Some(default)), Some(value)) => default == value,
+            _ => false,
+        }
+    }
+
+    /// The active spec's entry for `method`.
+    pub fn find_spec(&self, method: &str) -> Option<&MethodSpec> {
+        self.specs.iter().find(|s| s.name == method)
+    }
+
+    /// Name of param `index` of the selected method, from the spec, or
+    /// "Param N" past its end and for methods it doesn't know.
+    pub fn param_label(&self, index: usize) -> String {
+        match self.selected_method().and_then(|m| self.find_spec(m)).and_then(|s| s.params.get(index)) {
+            Some(name) => name.to_string(),
+            None => format!("Param {}", index + 1),
+        }
+    }
+
+    /// How param `index` of the selected method is edited.
+    pub fn param_kind(&self, index: usize) -> ParamKind {
+        self.selected_method().and_then(|m| self.find_spec(m)).map_or(ParamKind::Text, |s| s.kind(index))
+    }
+
+    /// Flips the focused param if it is a toggle; false if it isn't one.
+    pub fn flip_focused_toggle(&mut self) -> bool {
+        let kind = self.param_kind(self.focused_param);
+        let ParamKind::Bool { default, .. } = kind else { return false };
+        let Some(field) = self.param_inputs.get_mut(self.focused_param) else { return false };
+        let on = kind.toggle_state(field).unwrap_or(default);
+        *field = (!on).to_string();
+        true
+    }
+
+    /// The ParamInput field under focus: the override when shown, else
+    /// the focused transaction form row, else the focused param.
+    pub fn focused_field(&self) -> InputField {
+        match (&self.endpoint_override, &self.tx_form) {
+            (Some(_), _) => InputField::Override,
+            (None, Some(form)) => InputField::TxField(form.focus),
+            (None, None) => InputField::Param(self.focused_param),
+        }
+    }
+
+    fn field_mut(&mut self, field: InputField) -> Option<&mut String> {
+        match field {
+            InputField::Param(i) => self.param_inputs.get_mut(i),
+            InputField::TxField(i) => self.tx_form.as_mut().and_then(|f| f.fields.get_mut(i)),
+            InputField::Override => self.endpoint_override.as_mut(),
+        }
+    }
+
+    /// Records an edit of `kind` that changed `field` from `before`.
+    pub fn record_edit(&mut self, field: InputField, before: String, kind: EditKind) {
+        let Some(after) = self.field_mut(field).cloned() else { return };
+        self.field_undo.entry(field).or_default().record(before, &after, kind);
+    }
+
+    /// Ends every run of typing, so the next keystroke is a step of its own.
+    pub fn seal_undo(&mut self) {
+        self.field_undo.values_mut().for_each(UndoStack::seal);
+        self.raw_undo.seal();
+    }
+
+    /// Undoes the last step in the buffer keystrokes go to (raw JSON in
+    /// raw mode, else the focused field), or redoes the last undone one.
+    /// False when there is none.
+    pub fn undo_edit(&mut self, redo: bool) -> bool {
+        if self.endpoint_override.is_none()
+            && let Some(raw) = &mut self.raw_params
+        {
+            let current = raw.area.clone();
+            let area = if redo { self.raw_undo.redo(current) } else { self.raw_undo.undo(current) };
+            let Some(area) = area else { return false };
+            raw.area = area;
+            raw.edited(std::time::Instant::now());
+            return true;
+        }
+        let field = self.focused_field();
+        let Some(current) = self.field_mut(field).cloned() else { return false };
+        let Some(stack) = self.field_undo.get_mut(&field) else { return false };
+        let Some(value) = (if redo { stack.redo(current) } else { stack.undo(current) }) else { return false };
+        if let Some(slot) = self.field_mut(field) {
+            *slot = value;
+        }
+        self.input_cursor.reset();
+        true
+    }
+
+    /// Drops the undo history of every field and the raw-JSON buffer.
+    pub fn clear_undo(&mut self) {
+        self.field_undo.clear();
+        self.raw_undo.clear();
+    }
+
+    /// Help text for param `index` of the selected method, from the spec.
+    pub fn param_help(&self, index: usize) -> Option<&str> {
+        self.find_spec(self.selected_method()?)?.help(index)
+    }
+
+    /// Asks the session endpoint for its chain id in the background, so the
+    /// matching spec extension is merged without the user sending
+    /// `eth_chainId`. [`App::tick`] picks up the answer.
+    pub fn detect_chain(&mut self) {
+        let slot: ChainSlot = Arc::default();
+        let (sink, transport, url) = (slot.clone(), self.prefetch_transport.clone(), self.endpoint.clone());
+        let id = self.next_id;
+        self.next_id += 1;
+        let handle = tokio::spawn(async move {
+            let request = JsonRpcRequest::new("eth_chainId", Value::Array(Vec::new()), id);
+            if let Ok(response) = transport.send(&url, request).await {
+                *sink.lock().unwrap() = response.result.as_ref().and_then(|r| r.as_str()).and_then(parse_chain_id);
+            }
+        });
+        self.chain_probe = Some((handle, slot));
+    }
+
+    /// Merges the spec extension `extension_choice` selects for the
+    /// current chain into `specs` and the method list, replacing any
+    /// previous one. Does nothing when the selection is unchanged.
+    pub fn apply_spec_extension(&mut self) {
+        let selected = extension::select(&self.extension_choice, self.chain_id);
+        if selected.map(|e| &e.name) == self.extension.map(|e| &e.name) {
+            return;
+        }
+        let specs = match selected {
+            Some(extension) => extension::merge(&self.base_specs, extension),
+            None => self.base_specs.clone(),
+        };
+        if let Some(extension) = selected {
+            self.show_toast(format!("{} RPC methods added", extension.name));
+        }
+        self.extension = selected;
+        // Cached docs may come from the replaced spec
+        self.docs = Hydrator::new(Arc::new(SpecResolver { extension: selected }));
+        self.apply_spec(specs);
+    }
+
+    /// Replaces the builtin registry with `specs` (from `--spec`), keeping
+    /// any extension merged in.
+    pub fn set_base_specs(&mut self, specs: Vec<MethodSpec>) {
+        self.base_specs = specs;
+        let merged = match self.extension {
+            Some(extension) => extension::merge(&self.base_specs, extension),
+            None => self.base_specs.clone(),
+        };
+        self.apply_spec(merged);
+        self.review_migrations();
+    }
+
+    /// Makes `specs`, loaded from `label`, the base methods.
+    pub fn set_spec_source(&mut self, label: impl Into<String>, specs: Vec<MethodSpec>) {
+        self.spec_source = (label.into(), specs.iter().map(|m| m.name.to_string()).collect());
+        self.set_base_specs(specs);
+    }
+
+    /// Which sources define `method`, joined with " + ", e.g. "openrpc.json
+    /// + optimism extension"; `None` for methods not in the spec.
+    pub fn spec_origin(&self, method: &str) -> Option<String> {
+        let mut sources = Vec::new();
+        if self.spec_source.1.contains(method) {
+            sources.push(self.spec_source.0.clone());
+        }
+        if self.plugin_methods.iter().any(|m| m.spec.name == method) {
+            sources.push("plugin".to_string());
+        }
+        if let Some(extension) = self.extension
+            && extension.methods.iter().any(|m| m.name == method)
+        {
+            sources.push(format!("{} extension", extension.name));
+        }
+        (!sources.is_empty()).then(|| sources.join(" + "))
+    }
+
+    /// Stable ids of the sources defining `method` and a hash of its
+    /// definition, stamped on the history entries sending it; `None` for
+    /// methods not in the spec.
+    pub fn spec_stamp(&self, method: &str) -> Option<SpecStamp> {
+        let spec = self.find_spec(method)?;
+        let mut sources = Vec::new();
+        if self.spec_source.1.contains(method) {
+            sources.push(format!("spec:{}", self.spec_source.0));
+        }
+        let plugins = self.plugin_methods.iter().filter(|m| m.spec.name == method);
+        sources.extend(plugins.map(|m| format!("plugin:{}", m.plugin)));
+        if let Some(extension) = self.extension
+            && extension.methods.iter().any(|m| m.name == method)
+        {
+            sources.push(format!("extension:{}", extension.name));
+        }
+        Some(SpecStamp { sources, hash: origin::definition_hash(spec) })
+    }
+
+    /// Adds the plugins' methods to the base spec, and their summaries and
+    /// extracted values to the response view.
+    pub fn add_plugins(&mut self, plugins: Vec<Plugin>) {
+        let methods: Vec<PluginMethod> = plugins.into_iter().flat_map(|p| p.methods).collect();
+        for method in &methods {
+            if let Some(decoder) = PluginDecoder::new(method) {
+                self.decoders.register(decoder);
+            }
+        }
+        let specs: Vec<MethodSpec> = methods.iter().map(|m| m.spec.clone()).collect();
+        self.set_base_specs(spec::merge(&self.base_specs, &specs));
+        self.plugin_methods.extend(methods);
+    }
+
+    /// Plugins' primary value pointers (their first extract), by method.
+    pub fn primary_pointers(&self) -> Vec<(&str, &str)> {
+        self.plugin_methods.iter().filter_map(|m| Some((&*m.spec.name, m.extract.first()?.pointer.as_str()))).collect()
+    }
+
+    /// Makes `new_specs` the active spec without disturbing the user.
+    ///
+    /// The method list drops methods the old spec had and the new one
+    /// doesn't, and gains the new spec's methods at the end; entries no
+    /// spec describes stay. The search is kept and re-run, and the
+    /// highlighted method stays highlighted if it's still listed. A
+    /// renamed method hands its highlight to its new name; otherwise the
+    /// highlight stays at the same position, clamped to the list. Per-method
+    /// state (drafts, capabilities) is kept for methods that disappeared,
+    /// in case they come back.
+    pub fn apply_spec(&mut self, new_specs: Vec<MethodSpec>) {
+        self.apply_spec_renamed(new_specs, RENAMED_METHODS);
+    }
+
+    fn apply_spec_renamed(&mut self, new_specs: Vec<MethodSpec>, renamed: &[(&str, &str)]) {
+        let highlighted = self.methods_state.selected().and_then(|i| self.filtered_methods.get(i)).cloned();
+        let position = self.methods_state.selected().unwrap_or(0);
+        let in_new = |m: &str| new_specs.iter().any(|s| s.name == m);
+        let in_old = |m: &str| self.specs.iter().any(|s| s.name == m);
+        let mut methods: Vec<String> = self.all_methods.iter().filter(|m| in_new(m) || !in_old(m)).cloned().collect();
+        for spec in &new_specs {
+            if !methods.iter().any(|m| *m == spec.name) {
+                methods.push(spec.name.to_string());
+            }
+        }
+        self.all_methods = methods;
+        self.specs = new_specs;
+
+        self.filtered_methods = search::fuzzy_filter(&self.all_methods, &self.search_input);
+        let find = |name: &str| self.filtered_methods.iter().position(|f| f == name);
+        let kept = highlighted.as_deref().and_then(|m| {
+            find(m).or_else(|| renamed.iter().find(|(old, _)| *old == m).and_then(|(_, new)| find(new)))
+        });
+        let index = match kept {
+            Some(index) => Some(index),
+            None if self.filtered_methods.is_empty() => None,
+            None => Some(position.min(self.filtered_methods.len() - 1)),
+        };
+        self.methods_state.select(index);
+    }
+
+    /// Params as they would be sent: the raw-JSON buffer when raw mode is
+    /// active, the assembled payload for the `eth_simulateV1` and
+    /// `trace_filter` builders or the transaction form, otherwise the non-empty `param_inputs` as a JSON array,
+    /// each read with [`parse_param`].
+    pub fn current_params(&self) -> anyhow::Result<Value> {
+        self.resolve_params(&mut Trail::default())
+    }
+
+    /// [`App::current_params`], noting in `trail` every way the sent params
+    /// differ from the fields: config defaults, conversions in the
+    /// transaction form, and empty fields dropped ahead of filled ones
+    /// (which shifts the later params left). Raw JSON is sent as written,
+    /// except that words typed for boolean params become booleans.
+    pub fn resolve_params(&self, trail: &mut Trail) -> anyhow::Result<Value> {
+        if let Some(raw) = &self.raw_params {
+            let params = raw.parse().map_err(|e| anyhow::anyhow!("invalid params JSON: {}", e.describe()))?;
+            return Ok(self.coerce_toggles(params));
+        }
+        if let Some(form) = &self.tx_form {
+            return Ok(Value::Array(vec![txform::assemble(&form.fields, trail)?]));
+        }
+        let names = self.selected_method().and_then(|m| self.find_spec(m)).map(|s| s.params.clone()).unwrap_or_default();
+        let simulating = self.selected_method() == Some(simulate::METHOD);
+        let filtering = self.selected_method() == Some(trace::FILTER);
+        for (i, value) in self.param_inputs.iter().enumerate() {
+            let name = names.get(i).map(|n| n.to_string()).unwrap_or_else(|| format!("param {}", i + 1));
+            if self.is_default_param(i) {
+                trail.record(&name, "", "config default", value);
+            }
+            if let ParamKind::Bool { default, .. } = self.param_kind(i)
+                && value.is_empty()
+            {
+                trail.record(&name, "", "spec default", &default.to_string());
+            }
+            if !simulating && !filtering && value.is_empty() && self.param_inputs[i + 1..].iter().any(|p| !p.is_empty()) {
+                trail.record(&name, "", "empty, dropped (later params shift left)", "");
+            }
+        }
+        if simulating {
+            let field = |i: usize| self.param_inputs.get(i).map(String::as_str).unwrap_or("");
+            return simulate::assemble(field(0), field(1));
+        }
+        if filtering {
+            return trace::assemble_filter(&self.param_inputs);
+        }
+        if let Some(template) = self.selected_method().and_then(|m| self.plugin_methods.iter().find(|p| p.spec.name == m)?.template.as_ref()) {
+            let values: Vec<(&str, Value)> = names
                 .iter()
-                .filter(|m| m.to_lowercase().contains(&query))
-                .cloned()
+                .enumerate()
+                .map(|(i, name)| {
+                    let text = self.param_inputs.get(i).map(String::as_str).unwrap_or("");
+                    let value = match self.param_kind(i).toggle_state(text) {
+                        Some(on) => Value::Bool(on),
+                        None if text.is_empty() => Value::Null,
+                        None => parse_param(text),
+                    };
+                    (name, value)
+                })
                 .collect();
+            return Ok(plugin::fill(template, &values));
         }
-        // Reset selection index
-        self.methods_state.select(Some(0));
+        let params = self
+            .param_inputs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| match self.param_kind(i).toggle_state(p) {
+                Some(on) => Some(Value::Bool(on)),
+                None => (!p.is_empty()).then(|| parse_param(p)),
+            })
+            .collect();
+        Ok(Value::Array(params))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// `params` with strings at boolean positions that spell a boolean
+    /// (`"true"`, `"False"`) turned into that boolean.
+    fn coerce_toggles(&self, mut params: Value) -> Value {
+        if let Value::Array(items) = &mut params {
+            for (i, item) in items.iter_mut().enumerate() {
+                let kind = self.param_kind(i);
+                if let Some(on) = item.as_str().filter(|t| !t.is_empty()).and_then(|t| kind.toggle_state(t)) {
+                    *item = Value::Bool(on);
+                }
+            }
+        }
+        params
+    }
 
-    #[test]
-    fn app_new_initializes_correctly() {
-        let app = App::new();
-        // Default mode is Main
-        assert_eq!(app.mode, AppMode::Main);
-        // Quit flag should be false
-        assert!(!app.should_quit);
-        // Search input should be empty
-        assert_eq!(app.search_input, "");
-        // Filtered methods should match all methods initially
-        assert_eq!(app.filtered_methods, app.all_methods);
-        // First method selected by default
-        assert_eq!(app.methods_state.selected(), Some(0));
-        // No parameters by default
-        assert!(app.param_inputs.is_empty());
-        // History empty by default
-        assert!(app.history.is_empty());
-        // History selection should be zero
-        assert_eq!(app.history_state.selected(), Some(0));
+    /// Checks the param fields against the selected method's spec (see
+    /// [`spec::validate`]). Raw params, the transaction form, builders that
+    /// assemble their own params and methods without a spec aren't checked.
+    pub fn validate_params(&self) -> Result<(), spec::ValidationError> {
+        let Some(method) = self.selected_method() else { return Ok(()) };
+        let assembled = [simulate::METHOD, trace::FILTER].contains(&method);
+        match self.find_spec(method) {
+            Some(spec) if self.raw_params.is_none() && self.tx_form.is_none() && !assembled => {
+                spec::validate(spec, &self.param_inputs)
+            }
+            _ => Ok(()),
+        }
     }
 
-    #[test]
-    fn filter_methods_filters_by_query() {
-        let mut app = App::new();
-        // Setup a known method list
-        app.all_methods = vec![
-            "foo".to_string(),
-            "bar".to_string(),
-            "baz".to_string(),
-        ];
-        // Case-insensitive filter
-        app.search_input = "B".to_string();
-        app.filter_methods();
-        assert_eq!(app.filtered_methods, vec!["bar", "baz"]);
-        // Selection resets to 0
-        assert_eq!(app.methods_state.selected(), Some(0));
+    /// Builds a request for the selected method from the current params, consuming an id.
+    pub fn build_request(&mut self) -> anyhow::Result<JsonRpcRequest> {
+        let method = self
+            .selected_method()
+            .ok_or_else(|| anyhow::anyhow!("no method selected"))?
+            .to_string();
+        let mut trail = Trail::default();
+        let params = self.resolve_params(&mut trail)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        if !trail.is_empty() {
+            self.provenance.insert(id, trail);
+        }
+        Ok(JsonRpcRequest::new(method, params, id))
+    }
 
-        // Empty query resets
-        app.search_input.clear();
-        app.filter_methods();
-        assert_eq!(app.filtered_methods, app.all_methods);
+    /// A raw-JSON editor for `text`, relaxed when the user opted in.
+    fn raw_editor(&self, text: &str) -> RawParams {
+        RawParams::with_relaxed(text, self.ui_state.relaxed_json)
+    }
+
+    /// Switches ParamInput between per-field and raw-JSON editing.
+    ///
+    /// Entering raw mode pretty-prints the current fields; leaving it only
+    /// succeeds when the buffer is a valid JSON array, which is split back
+    /// into fields.
+    pub fn toggle_raw_params(&mut self) -> anyhow::Result<()> {
+        match &self.raw_params {
+            None => {
+                let params = self.current_params()?;
+                let text = serde_json::to_string_pretty(&params)?;
+                self.raw_params = Some(self.raw_editor(&text));
+            }
+            Some(raw) => {
+                let params = raw.parse().map_err(|e| anyhow::anyhow!("cannot leave raw mode: {}", e.describe()))?;
+                if self.tx_form.is_some() {
+                    self.tx_form = Some(TxForm::from_params(&params).ok_or_else(|| {
+                        anyhow::anyhow!("cannot leave raw mode: the transaction uses fields the form doesn't cover")
+                    })?);
+                    self.raw_params = None;
+                    self.clear_undo();
+                    return Ok(());
+                }
+                let Value::Array(items) = params else {
+                    anyhow::bail!("cannot leave raw mode: params are not a JSON array");
+                };
+                self.param_inputs = items.iter().map(value_to_field).collect();
+                self.raw_params = None;
+            }
+        }
+        self.clear_undo();
+        Ok(())
+    }
+
+    /// Opens the selected history entry in ResponseView mode.
+    pub fn open_selected_response(&mut self) {
+        let selected = self.history_state.selected().unwrap_or(0);
+        if let Some(entry) = self.history.get(selected) {
+            self.frame.pass("format");
+            let mut view = ResponseView::new(entry, &self.prefs, self.chain(), &self.decoders);
+            view.label_addresses(&self.address_book, self.chain_id);
+            self.response_view = Some(view);
+            self.enter(AppMode::ResponseView);
+        }
+    }
+
+    /// Metadata of the session endpoint's chain, once its id is known.
+    pub fn chain(&self) -> Option<&ChainInfo> {
+        self.chain_id.and_then(|id| self.chains.get(id))
+    }
+
+    /// Native currency of the session endpoint's chain, ETH when unknown.
+    pub fn currency(&self) -> NativeCurrency {
+        self.chain().map(|c| c.native_currency.clone()).unwrap_or_default()
+    }
+
+    /// Re-renders the open response after a display preference changed,
+    /// keeping the scroll position and re-running any search.
+    pub fn apply_prefs(&mut self) {
+        let selected = self.history_state.selected().unwrap_or(0);
+        let Some(entry) = self.history.get(selected) else { return };
+        self.frame.pass("format");
+        let fresh = ResponseView::new(entry, &self.prefs, self.chain(), &self.decoders);
+        if let Some(view) = &mut self.response_view {
+            view.set_response_lines(fresh.lines);
+            view.formatting = fresh.formatting;
+            view.spill = fresh.spill;
+            view.label_addresses(&self.address_book, self.chain_id);
+            view.scroll_by(0);
+            if !view.search.editing && !view.search.query.is_empty() {
+                view.run_search();
+            }
+        }
+    }
+
+    /// Copies `payload` of the selected history entry (also the one in the
+    /// response view) to the clipboard, confirming with a toast.
+    pub fn copy_selected(&mut self, payload: Payload) {
+        let selected = self.history_state.selected().unwrap_or(0);
+        let Some(entry) = self.history.get(selected) else { return };
+        if payload == Payload::Response && entry.dry_run {
+            self.show_toast("dry runs have no response to copy");
+            return;
+        }
+        if payload == Payload::Response && entry.spill.is_some() {
+            self.show_toast("response is too large to copy — export it as CSV instead");
+            return;
+        }
+        let text = clipboard::payload_text(entry, payload);
+        self.copy_text(text, payload.label());
+    }
+
+    /// Labels `address` on the session's chain (on every chain while it is
+    /// unknown), or removes the label for an empty `label`, and saves the
+    /// address book. Labels another eli saved in the meantime are merged in.
+    pub fn label_address(&mut self, address: &str, label: &str) -> anyhow::Result<()> {
+        let mut book = self.address_book.clone();
+        book.set_label(self.chain_id, address, label);
+        if let Some(path) = &self.address_book_path {
+            let (written, _) = addressbook::save(path, &self.address_book_base, book)?;
+            self.address_book_base = written.clone();
+            book = written;
+        }
+        self.address_book = book;
+        if let Some(view) = &mut self.response_view {
+            view.label_addresses(&self.address_book, self.chain_id);
+        }
+        Ok(())
+    }
+
+    /// Puts `text` on the clipboard, toasting "copied {what}".
+    pub fn copy_text(&mut self, text: String, what: &str) {
+        match self.clipboard.set_text(text) {
+            Ok(()) => self.show_toast(format!("copied {}", what)),
+            Err(e) => self.show_toast(format!("copy failed: {:#}", e)),
+        }
+    }
+
+    /// Writes the result of the open response to `path` as CSV; returns
+    /// the number of rows written. A result spilled to disk is read back
+    /// an element at a time.
+    pub fn export_csv(&self, path: &str) -> anyhow::Result<usize> {
+        let selected = self.history_state.selected().unwrap_or(0);
+        let entry = self.history.get(selected).filter(|e| !e.dry_run);
+        let not_array = || anyhow::anyhow!("only array results can be exported");
+        let decimal = self.prefs.csv_decimal;
+        // A spilled result is read back an element at a time
+        if let Some(spilled) = entry.and_then(|e| e.spill.as_deref()) {
+            let index = spill::SpillIndex::build(spilled)?;
+            if index.container != spill::Container::Array {
+                return Err(not_array());
+            }
+            let mut out = csv_file(path)?;
+            let rows = crate::export::write_csv(&mut out, decimal, |row| index.for_each(|_, value| row(&value)))?;
+            out.flush().with_context(|| format!("writing {}", path))?;
+            return Ok(rows);
+        }
+        let rows = entry.and_then(|e| e.response.result.as_ref()).and_then(|r| r.as_array()).ok_or_else(not_array)?;
+        let mut out = csv_file(path)?;
+        let rows = crate::export::write_csv(&mut out, decimal, |row| rows.iter().try_for_each(row))?;
+        out.flush().with_context(|| format!("writing {}", path))?;
+        Ok(rows)
+    }
+
+    /// Marks the selected history entry, or unmarks it.
+    pub fn toggle_history_mark(&mut self) {
+        let Some(seq) = self.history_state.selected().and_then(|i| self.history.get(i)).map(|e| e.seq) else { return };
+        if !self.history_marks.remove(&seq) {
+            self.history_marks.insert(seq);
+        }
+    }
+
+    /// Marks every entry in the history list.
+    pub fn mark_all_history(&mut self) {
+        self.history_marks.extend(self.history.iter().map(|e| e.seq));
+    }
+
+    /// The entries history actions apply to: the marked ones, or else the
+    /// selected one.
+    pub fn history_targets(&self) -> Vec<u64> {
+        if !self.history_marks.is_empty() {
+            return self.history.iter().map(|e| e.seq).filter(|seq| self.history_marks.contains(seq)).collect();
+        }
+        self.history_state.selected().and_then(|i| self.history.get(i)).map(|e| e.seq).into_iter().collect()
+    }
+
+    /// "delete 14 entries?" while an action waits for confirmation.
+    pub fn history_prompt(&self) -> Option<String> {
+        let action = self.history_confirm?;
+        let count = self.history_targets().len();
+        Some(format!("{} {} entr{}?", action.verb(), count, if count == 1 { "y" } else { "ies" }))
+    }
+
+    /// Starts `action` on [`App::history_targets`]. Deleting, or acting on
+    /// marked entries, asks for confirmation first; exporting the selected
+    /// entry alone happens at once.
+    pub fn request_history_action(&mut self, action: HistoryAction) {
+        if self.history_targets().is_empty() {
+            return;
+        }
+        if action == HistoryAction::Delete && self.spectator {
+            self.show_toast("spectator mode: history is read-only");
+            return;
+        }
+        if action == HistoryAction::Delete || !self.history_marks.is_empty() {
+            self.history_confirm = Some(action);
+        } else {
+            self.run_history_action(action);
+        }
+    }
+
+    /// Runs the action waiting for confirmation, then clears the marks.
+    pub fn confirm_history_action(&mut self) {
+        if let Some(action) = self.history_confirm.take() {
+            self.run_history_action(action);
+            self.history_marks.clear();
+        }
+    }
+
+    fn run_history_action(&mut self, action: HistoryAction) {
+        let targets = self.history_targets();
+        match action {
+            HistoryAction::Delete => {
+                let deleted = self.delete_history(&targets);
+                self.show_toast(format!("deleted {} entr{}", deleted, if deleted == 1 { "y" } else { "ies" }));
+            }
+            HistoryAction::Export => match self.export_history(&targets, std::path::Path::new("."), head::unix_now()) {
+                Ok(path) => self.show_toast(format!("{} entr{} exported to {}", targets.len(), if targets.len() == 1 { "y" } else { "ies" }, path.display())),
+                Err(e) => self.show_toast(format!("export failed: {:#}", e)),
+            },
+        }
+    }
+
+    /// Removes the entries numbered `seqs` from history and its file;
+    /// returns how many were removed. The selection stays on the same
+    /// entry when it survives, else on the row now in its place.
+    pub fn delete_history(&mut self, seqs: &[u64]) -> usize {
+        let selected = self.history_state.selected();
+        let kept_seq = selected.and_then(|i| self.history.get(i)).map(|e| e.seq).filter(|seq| !seqs.contains(seq));
+        let before = self.history.len();
+        self.history.retain(|e| !seqs.contains(&e.seq));
+        let deleted = before - self.history.len();
+        self.history_marks.retain(|seq| !seqs.contains(seq));
+        let index = match kept_seq {
+            Some(seq) => self.history.iter().position(|e| e.seq == seq),
+            None if self.history.is_empty() => None,
+            None => selected.map(|i| i.min(self.history.len() - 1)),
+        };
+        self.history_state.select(index);
+        if deleted > 0
+            && let Some(store) = &mut self.history_store
+            && let Err(e) = store.replace(&self.history)
+        {
+            self.notice = Some(format!("history not saved: {:#}", e));
+        }
+        if deleted > 0 {
+            self.sweep_spill();
+        }
+        deleted
+    }
+
+    /// Spills large results to `dir` from now on, first removing the files
+    /// there that no history entry refers to any more.
+    pub fn set_spill_dir(&mut self, dir: std::path::PathBuf) {
+        self.spill_dir = Some(dir);
+        self.sweep_spill();
+    }
+
+    /// Removes the spill files of entries no longer in history.
+    fn sweep_spill(&mut self) {
+        let Some(dir) = &self.spill_dir else { return };
+        let keep: Vec<&std::path::Path> = self.history.iter().filter_map(|e| e.spill.as_deref()).collect();
+        if let Err(e) = spill::sweep(dir, &keep) {
+            self.notice = Some(format!("old responses not removed: {:#}", e));
+        }
+    }
+
+    /// Writes the entries numbered `seqs` to `history-<now>.json` under
+    /// `dir`, in the history file format; returns the file.
+    pub fn export_history(&self, seqs: &[u64], dir: &std::path::Path, now: u64) -> anyhow::Result<std::path::PathBuf> {
+        let entries: Vec<HistoryEntry> = self.history.iter().filter(|e| seqs.contains(&e.seq)).cloned().collect();
+        let path = dir.join(format!("history-{}.json", now));
+        storage::write_atomic(&path, history::encode(&entries)?.as_bytes())?;
+        Ok(path)
+    }
+
+    /// Writes the open history entry as a test fixture directory under
+    /// `parent` (see [`fixture::write`]); returns the directory.
+    pub fn export_fixture(&self, parent: &std::path::Path) -> anyhow::Result<std::path::PathBuf> {
+        let selected = self.history_state.selected().unwrap_or(0);
+        let entry = self
+            .history
+            .get(selected)
+            .filter(|e| !e.dry_run)
+            .ok_or_else(|| anyhow::anyhow!("dry runs have no response to export"))?;
+        if entry.spill.is_some() {
+            anyhow::bail!("response is too large for a fixture");
+        }
+        let endpoint = entry.endpoint_override.as_deref().unwrap_or(&self.endpoint);
+        let meta = fixture::Meta {
+            method: entry.request.method.clone(),
+            endpoint: fixture::scheme_and_port(endpoint),
+            chain_id: self.chain_id,
+            latest_block: self.stats.last().head.map(|h| h.number),
+            exported_at: head::unix_now(),
+            eli_version: env!("CARGO_PKG_VERSION").to_string(),
+            addresses_redacted: self.redact_addresses,
+            scrubbed: Vec::new(),
+        };
+        fixture::write(parent, entry, &meta, self.redact_addresses)
+    }
+
+    /// Loads persisted UI state from `path` and remembers it for saving.
+    /// A restore from backup is reported as a notice.
+    pub fn load_ui_state(&mut self, path: std::path::PathBuf) -> anyhow::Result<()> {
+        let state = uistate::load(&path);
+        self.ui_state_path = Some(path);
+        let state = state?;
+        self.note_migration(&schema::UI_STATE, state.migrated_from);
+        self.apply_ui_state(state.value);
+        if state.warning.is_some() {
+            self.notice = state.warning;
+        }
+        Ok(())
+    }
+
+    /// Remembers that `schema`'s file was upgraded from `from`, if it was.
+    pub fn note_migration(&mut self, schema: &Schema, from: Option<u64>) {
+        if let Some(from) = from {
+            self.migrated.push(format!("{} (v{})", schema.file, from));
+        }
+    }
+
+    /// Toasts the files upgraded at startup, if any.
+    pub fn report_migrations(&mut self) {
+        if !self.migrated.is_empty() {
+            let message = format!("upgraded {}; originals kept as .v<N>.bak", self.migrated.join(", "));
+            self.show_toast(message);
+        }
+    }
+
+    fn apply_ui_state(&mut self, state: UiState) {
+        self.prefs = state.prefs.clone();
+        self.suppress_sync_warning = state.suppress_sync_warning;
+        self.stall_after = state.stall_after_secs;
+        self.extension_choice = state.spec_extension.clone();
+        self.frame = FrameBudget::new(match state.frame_budget_ms {
+            None => Some(perf::DEFAULT_BUDGET),
+            Some(0) => None,
+            Some(ms) => Some(std::time::Duration::from_millis(ms)),
+        });
+        self.ui_state = state;
+        self.apply_spec_extension();
+    }
+
+    /// Writes UI state back to `ui_state_path`, if set. Settings another
+    /// eli saved in the meantime are merged in and take effect here too.
+    pub fn save_ui_state(&mut self) -> anyhow::Result<()> {
+        let Some(path) = &self.ui_state_path else { return Ok(()) };
+        let state = UiState {
+            prefs: self.prefs.clone(),
+            suppress_sync_warning: self.suppress_sync_warning,
+            stall_after_secs: self.stall_after,
+            spec_extension: self.extension_choice.clone(),
+            update_check: self.ui_state.update_check,
+            frame_budget_ms: self.ui_state.frame_budget_ms,
+            relaxed_json: self.ui_state.relaxed_json,
+            revision: self.ui_state.revision,
+        };
+        let (written, saved) = uistate::save(path, &self.ui_state, state)?;
+        if saved.merged {
+            self.apply_ui_state(written);
+            self.apply_prefs();
+            self.show_toast("merged settings saved by another eli");
+        } else {
+            self.ui_state = written;
+        }
+        Ok(())
+    }
+
+    /// Starts the crates.io update check in the background if the user
+    /// opted in, remembering when it last ran in `state_path`. Never runs
+    /// in the tutorial, which is offline, or in spectator sessions, which
+    /// save nothing.
+    pub fn start_update_check(&mut self, state_path: std::path::PathBuf) {
+        if !self.ui_state.update_check || self.tutorial.is_some() || self.spectator {
+            return;
+        }
+        let slot: UpdateSlot = Arc::default();
+        let sink = slot.clone();
+        let handle = tokio::spawn(async move {
+            let message = update::check(update::INDEX_URL, &state_path, env!("CARGO_PKG_VERSION"), head::unix_now()).await;
+            *sink.lock().unwrap() = message;
+        });
+        self.update_probe = Some((handle, slot));
+    }
+
+    /// Caps response bodies for every transport at `bytes`. Meant for
+    /// startup: sends already queued are dropped.
+    pub fn set_response_limit(&mut self, bytes: usize) {
+        self.client = self.client.clone().with_response_limit(bytes);
+        self.sends = SendQueue::new(Arc::new(self.client.clone()));
+        self.prefetch_transport = Arc::new(self.client.without_middleware());
+        self.ws_connector = Arc::new(WsConnector { response_limit: bytes });
+    }
+
+    /// Retries requests that fail in passing as `policy` says instead of
+    /// the default three attempts. Meant for startup, like
+    /// [`App::set_timeout`].
+    pub fn set_retry(&mut self, policy: RetryPolicy) {
+        self.client = self.client.clone().with_retry(policy);
+        self.sends = SendQueue::new(Arc::new(self.client.clone()));
+        self.prefetch_transport = Arc::new(self.client.without_middleware());
+    }
+
+    /// Gives up on requests from every transport after `timeout`, so a
+    /// dead endpoint shows an error instead of a send that never ends.
+    /// Meant for startup, like [`App::set_response_limit`].
+    pub fn set_timeout(&mut self, timeout: std::time::Duration) {
+        self.client = self.client.clone().with_timeout(timeout);
+        self.sends = SendQueue::new(Arc::new(self.client.clone()));
+        self.prefetch_transport = Arc::new(self.client.without_middleware());
+    }
+
+    /// Authorizes every transport's requests to the session endpoint with
+    /// `credentials`. Switching endpoints later doesn't take them along.
+    /// Meant for startup, like [`App::set_timeout`].
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        self.client = self.client.clone().with_credentials(&self.endpoint, credentials.clone());
+        self.sends = SendQueue::new(Arc::new(self.client.clone()));
+        self.prefetch_transport = Arc::new(self.client.without_middleware());
+        self.credentials = Some((self.endpoint.clone(), credentials));
+    }
+
+    /// The credentials requests to the session endpoint go with, if any.
+    pub fn session_credentials(&self) -> Option<&Credentials> {
+        self.credentials.as_ref().filter(|(url, _)| *url == self.endpoint).map(|(_, credentials)| credentials)
+    }
+
+    /// Takes the profiles and default headers of `endpoints.json`, and
+    /// authorizes every transport for the profiles with an auth command.
+    /// Meant for startup, like [`App::set_timeout`].
+    pub fn apply_endpoint_config(&mut self, config: EndpointConfig) -> anyhow::Result<()> {
+        let mut client = self.client.clone().with_headers(config.header_map()?);
+        for profile in &config.profiles {
+            if let Some(command) = &profile.auth_command {
+                let ttl = profile.auth_ttl_secs.map_or(auth::DEFAULT_TTL, std::time::Duration::from_secs);
+                client = client.with_auth(AuthToken::new(&profile.url, command, ttl, Arc::new(ShellRunner)));
+            }
+        }
+        self.client = client;
+        self.sends = SendQueue::new(Arc::new(self.client.clone()));
+        self.prefetch_transport = Arc::new(self.client.without_middleware());
+        self.profiles = config.profiles;
+        Ok(())
+    }
+
+    /// Starts the guided walkthrough: every request, background ones
+    /// included, is answered offline by [`OfflineTransport`].
+    pub fn start_tutorial(&mut self) {
+        self.endpoint = tutorial::ENDPOINT.to_string();
+        self.sends = SendQueue::new(Arc::new(OfflineTransport));
+        self.prefetch_transport = Arc::new(OfflineTransport);
+        self.tutorial = Some(Tutorial::default());
+    }
+
+    /// Shows `message` as a toast for [`TOAST_DURATION`].
+    pub fn show_toast(&mut self, message: impl Into<String>) {
+        let expires = self.clock.now() + TOAST_DURATION;
+        self.toast = Some(Toast { message: message.into(), expires });
+    }
+
+    /// Advances time-based state; called once per main-loop iteration.
+    pub fn tick(&mut self, now: std::time::Instant) {
+        if let Some(gap) = self.resume.check(now, self.clock.unix_now()) {
+            self.resumed(gap);
+        }
+        if self.toast.as_ref().is_some_and(|t| now >= t.expires) {
+            self.toast = None;
+        }
+        if let Some(raw) = &mut self.raw_params {
+            raw.tick(now);
+        }
+        self.drain_sends();
+        if let Some(view) = &mut self.response_view {
+            view.poll_formatting(&self.address_book, self.chain_id);
+            view.page_spill(&self.address_book, self.chain_id);
+        }
+        if self.mode == AppMode::Main
+            && let Some(method) = self.selected_method().map(str::to_string)
+        {
+            self.docs.select(&method);
+        }
+        self.sync.poll(now, self.prefetch_transport.clone(), &self.endpoint, self.stats.shared(), &self.tasks);
+        self.poll_live_heads();
+        if self.live_heads.is_none() {
+            self.head.poll(now, self.prefetch_transport.clone(), &self.endpoint, self.stats.shared(), &self.tasks);
+        }
+        self.stats.refresh();
+        if let Some((handle, slot)) = &self.chain_probe
+            && handle.is_finished()
+        {
+            let detected = slot.lock().unwrap().take();
+            self.chain_probe = None;
+            match (self.chain_id, detected) {
+                (None, _) => self.chain_id = detected,
+                // Only a profile's chain id can disagree; the node wins
+                (Some(expected), Some(id)) if id != expected => {
+                    self.show_toast(format!("endpoint reports chain {}, not {} as its profile says", id, expected));
+                    self.chain_id = Some(id);
+                }
+                _ => {}
+            }
+            self.apply_spec_extension();
+        }
+        if let Some((handle, slot)) = &self.capability_probe
+            && handle.is_finished()
+        {
+            let answer = slot.lock().unwrap().take();
+            self.capability_probe = None;
+            if let Some((url, method, availability)) = answer {
+                self.capabilities.set(&url, &method, availability);
+            }
+        }
+        if let Some((handle, slot)) = &self.update_probe
+            && handle.is_finished()
+        {
+            let message = slot.lock().unwrap().take();
+            self.update_probe = None;
+            if let Some(message) = message {
+                self.show_toast(message);
+            }
+        }
+        if let Some(mut tutorial) = self.tutorial.take() {
+            tutorial.on_state(self);
+            self.tutorial = Some(tutorial);
+        }
+        if self.mode == AppMode::Dashboard {
+            // Background traffic: keep it out of latency stats
+            self.dashboard.poll(now, self.prefetch_transport.clone(), &self.endpoint, &self.prefs, &self.tasks);
+        }
+    }
+
+    /// Picks up after a suspend of `gap`: what was measured before it is
+    /// stale. Background polls restart once each instead of catching up,
+    /// the head is forgotten until a fresh poll answers (rather than
+    /// reading hours old as a stall), and the frame timed across the gap is
+    /// dropped.
+    fn resumed(&mut self, gap: std::time::Duration) {
+        self.head.reschedule();
+        self.sync.reschedule();
+        self.dashboard.reschedule();
+        self.stats.shared().update(|s| {
+            s.head = None;
+            s.health = Health::Unknown;
+        });
+        self.frame.discard();
+        self.show_toast(format!("resumed after {}; refreshing", head::format_age(gap.as_secs())));
+    }
+
+    /// Aborts the send in flight, letting the next queued one go; returns
+    /// whether there was one.
+    pub fn cancel_send(&mut self) -> bool {
+        let Some(send) = self.sends.cancel() else { return false };
+        self.show_toast(format!("cancelled {}", send.request.method));
+        if !self.sends.is_busy() {
+            self.quit_armed = false;
+        }
+        true
+    }
+
+    /// Records every finished background send, in order; failures become
+    /// the notice.
+    pub fn drain_sends(&mut self) {
+        while let Some((send, result)) = self.sends.poll() {
+            if send.url == self.endpoint {
+                // A response too large to keep still came from a live endpoint
+                let health = match &result {
+                    Err(e) if e.downcast_ref::<TooLarge>().is_none() => Health::down(e),
+                    _ => Health::Up,
+                };
+                self.stats.shared().update(|s| s.health = health);
+            }
+            match result {
+                Ok(response) => {
+                    self.after_response(&send.url, &send.request, &response);
+                    self.record(send.request, response, send.endpoint_override);
+                }
+                Err(e) => match e.downcast_ref::<TooLarge>() {
+                    Some(too_large) => {
+                        self.notice = Some(format!(
+                            "{} response passed {} MiB and was dropped — narrow the query (e.g. a smaller block range) or raise --max-response-mib",
+                            too_large.method,
+                            too_large.limit / (1024 * 1024)
+                        ))
+                    }
+                    None => self.notice = Some(format!("request to {} failed: {}", send.url, e)),
+                },
+            }
+        }
+        if !self.sends.is_busy() {
+            self.quit_armed = false;
+        }
+    }
+
+    /// "last block 12s ago" for the session endpoint, and whether the
+    /// chain looks stalled.
+    pub fn head_status(&self, unix_now: u64) -> Option<(String, bool)> {
+        head::status(self.stats.last().head, unix_now, head::stall_threshold(self.stall_after, self.chain_id))
+    }
+
+    /// "⠙ eth_call 1,250 ms · 2 queued" while a send is in flight. The
+    /// spinner moves a frame per 100 ms, one per turn of the main loop.
+    pub fn send_status(&self, now: std::time::Instant) -> Option<String> {
+        const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+        let (send, started) = self.sends.in_flight()?;
+        let elapsed = now.saturating_duration_since(started);
+        let frame = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+        let mut status = format!("{} {} {}", frame, send.request.method, self.prefs.latency(elapsed));
+        if self.sends.queued() > 0 {
+            status.push_str(&format!(" · {} queued", self.sends.queued()));
+        }
+        Some(status)
+    }
+
+    /// Quits, unless sends are pending: then the first call only warns.
+    pub fn request_quit(&mut self) {
+        if self.sends.is_busy() && !self.quit_armed {
+            let pending = self.sends.queued() + 1;
+            self.quit_armed = true;
+            self.show_toast(format!("{} pending request(s) will be dropped — Ctrl+C again to quit", pending));
+            return;
+        }
+        self.should_quit = true;
+    }
+
+    /// Waits for every pending send and records it.
+    #[cfg(test)]
+    pub(crate) async fn finish_sends(&mut self) {
+        while self.sends.is_busy() {
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+            self.drain_sends();
+        }
+    }
+
+    /// Makes `input` (URL or profile name) the session endpoint. What was
+    /// learned about the old one (chain id and its spec extension) is
+    /// dropped and the new chain detected, starting from the chain id of
+    /// the new endpoint's profile if it names one; per-endpoint caches are
+    /// keyed by URL and need nothing.
+    pub fn set_endpoint(&mut self, input: &str) -> anyhow::Result<()> {
+        let url = resolve_endpoint(input, &self.profiles)?;
+        if url == self.endpoint {
+            return Ok(());
+        }
+        self.endpoint = url;
+        if let Some((handle, _)) = self.chain_probe.take() {
+            handle.abort();
+        }
+        self.chain_id = self.active_profile().and_then(|p| p.chain_id);
+        self.apply_spec_extension();
+        self.detect_chain();
+        self.open_live_heads(false);
+        Ok(())
+    }
+
+    /// The profile the session endpoint belongs to, if any.
+    pub fn active_profile(&self) -> Option<&EndpointProfile> {
+        self.profiles.iter().find(|p| p.url == self.endpoint)
+    }
+
+    /// The active profile's websocket URL, if it has one.
+    pub fn ws_endpoint(&self) -> Option<&str> {
+        self.active_profile().and_then(|p| p.ws_url.as_deref())
+    }
+
+    /// Subscribes to new heads on [`App::ws_endpoint`], taking its lease in
+    /// `lease_dir`. When another local eli holds it, `lease_prompt` asks
+    /// the user instead, unless `force` (they chose to proceed anyway).
+    /// Without a subscription the head is polled over HTTP.
+    pub fn open_live_heads(&mut self, force: bool) {
+        self.close_live_heads();
+        self.lease_prompt = None;
+        let (Some(url), Some(dir)) = (self.ws_endpoint().map(str::to_string), self.lease_dir.clone()) else { return };
+        let connector = self.ws_connector.clone();
+        match Subscriptions::open(&dir, &url, connector, ReconnectPolicy::default(), self.clock.clone(), force) {
+            Ok(Opened::Started(mut subs)) => {
+                subs.subscribe(serde_json::json!(["newHeads"]));
+                self.live_heads = Some(subs);
+            }
+            Ok(Opened::InUse(holder)) => self.lease_prompt = Some(holder),
+            Err(e) => self.notice = Some(format!("live heads unavailable: {:#}", e)),
+        }
+    }
+
+    /// Answers `lease_prompt`: subscribe anyway, sharing the node's
+    /// connection limit with the other instance, or keep polling over HTTP.
+    pub fn answer_lease_prompt(&mut self, proceed: bool) {
+        if self.lease_prompt.take().is_none() {
+            return;
+        }
+        if proceed {
+            self.open_live_heads(true);
+        } else {
+            self.show_toast("polling for new heads over HTTP");
+        }
+    }
+
+    /// Drops the `newHeads` subscription, if any, and its task entry.
+    fn close_live_heads(&mut self) {
+        self.live_heads = None;
+        self.live_heads_task.release(&self.tasks);
+    }
+
+    /// Takes in the heads pushed since the last tick and keeps the lease
+    /// fresh; a subscription that fails or is killed falls back to polling.
+    /// Heads pushed while it's paused are dropped, and a toast marks where
+    /// a dropped connection lost some.
+    fn poll_live_heads(&mut self) {
+        if self.live_heads.is_none() {
+            return;
+        }
+        let target = format!("eth_subscribe [\"newHeads\"] @ {}", self.ws_endpoint().unwrap_or_default());
+        let running = self.live_heads_task.may_run(&self.tasks, TaskKind::LiveHeads, &target, std::time::Duration::ZERO);
+        if self.live_heads_task.killed() {
+            self.close_live_heads();
+            self.notice = Some("live heads stopped — polling over HTTP".into());
+            return;
+        }
+        let Some(subs) = &mut self.live_heads else { return };
+        let now = self.clock.unix_now();
+        let _ = subs.renew_lease(now);
+        let (mut failed, mut gap, mut received) = (None, None, false);
+        while let Some(event) = subs.try_next() {
+            match event {
+                PaneEvent::Notification(..) if !running => {}
+                PaneEvent::Notification(_, block) => {
+                    received = true;
+                    if let Some((number, timestamp)) = head::block_number(&block) {
+                        self.stats.shared().update(|s| {
+                            s.health = Health::Up;
+                            head::observe(&mut s.head, number, timestamp, now);
+                        });
+                    }
+                }
+                PaneEvent::Gap(_, lost) => gap = Some(lost),
+                PaneEvent::Failed(_, reason) => failed = Some(reason),
+            }
+        }
+        if let Some(id) = self.live_heads_task.id()
+            && (received || failed.is_some())
+        {
+            self.tasks.update(|r| r.record(id, failed.is_none(), now));
+        }
+        if let Some(gap) = gap {
+            self.show_toast(gap.marker(&self.prefs));
+        }
+        if let Some(reason) = failed {
+            self.close_live_heads();
+            self.notice = Some(format!("live heads stopped: {} — polling over HTTP", reason));
+        }
+    }
+
+    /// Opens the profile picker on the active profile, else the first.
+    pub fn open_profile_picker(&mut self) {
+        let active = self.profiles.iter().position(|p| p.url == self.endpoint);
+        self.profile_picker = Some(active.unwrap_or(0));
+    }
+
+    /// Makes profile `index` the session endpoint (see
+    /// [`App::set_endpoint`]).
+    pub fn activate_profile(&mut self, index: usize) -> anyhow::Result<()> {
+        let Some(profile) = self.profiles.get(index) else {
+            anyhow::bail!("no endpoint profiles — add them to endpoints.json");
+        };
+        let name = profile.name.clone();
+        self.set_endpoint(&name)?;
+        self.show_toast(format!("switched to {}", name));
+        Ok(())
+    }
+
+    /// Resolves where the next request goes: the override if one was entered,
+    /// otherwise the session endpoint.
+    ///
+    /// Returns the URL and, when an override is in effect, the resolved override.
+    pub fn send_target(&self) -> anyhow::Result<(String, Option<String>)> {
+        match self.endpoint_override.as_deref().map(str::trim) {
+            Some(input) if !input.is_empty() => {
+                let url = resolve_endpoint(input, &self.profiles)?;
+                Ok((url.clone(), Some(url)))
+            }
+            _ => Ok((self.endpoint.clone(), None)),
+        }
+    }
+
+    /// Appends a completed request to the history, attaching the latency the
+    /// client's middleware measured for it.
+    pub fn record(&mut self, request: JsonRpcRequest, mut response: JsonRpcResponse, endpoint_override: Option<String>) {
+        let latency = self.latency.find(&request.method, request.id).map(|s| s.elapsed);
+        let spec_params = self.spec_params(&request.method);
+        if request.method == "eth_chainId"
+            && endpoint_override.is_none()
+            && let Some(id) = response.result.as_ref().and_then(|r| r.as_str())
+        {
+            self.chain_id = parse_chain_id(id);
+            self.apply_spec_extension();
+        }
+        let provenance = self.provenance.remove(&request.id).unwrap_or_default();
+        let provider = provider::detect(endpoint_override.as_deref().unwrap_or(&self.endpoint));
+        let spill = self.spill(&request, &mut response);
+        self.push_history(HistoryEntry {
+            endpoint_override,
+            latency,
+            spec_params,
+            provider,
+            provenance,
+            spill,
+            ..HistoryEntry::new(request, response)
+        });
+    }
+
+    /// Writes a result of more than [`spill::SPILL_LIMIT`] values to the
+    /// spill dir and takes it out of `response`; returns the file. The
+    /// result stays in memory when there's no spill dir or writing fails.
+    fn spill(&mut self, request: &JsonRpcRequest, response: &mut JsonRpcResponse) -> Option<std::path::PathBuf> {
+        let dir = self.spill_dir.as_ref()?;
+        if response.result.as_ref().map(pretty::count).unwrap_or(0) <= spill::SPILL_LIMIT {
+            return None;
+        }
+        match spill::write(dir, &format!("{}-{}", self.clock.unix_now(), request.id), response) {
+            Ok(path) => {
+                response.result = None;
+                Some(path)
+            }
+            Err(e) => {
+                self.notice = Some(format!("large response kept in memory: {:#}", e));
+                None
+            }
+        }
+    }
+
+    /// Records a dry-run entry: the request that would have been sent, with
+    /// an empty placeholder response.
+    pub fn record_dry_run(&mut self, request: JsonRpcRequest, endpoint_override: Option<String>) {
+        let response = JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: None, id: request.id };
+        let spec_params = self.spec_params(&request.method);
+        let provenance = self.provenance.remove(&request.id).unwrap_or_default();
+        self.push_history(HistoryEntry {
+            endpoint_override,
+            spec_params,
+            dry_run: true,
+            provenance,
+            ..HistoryEntry::new(request, response)
+        });
+    }
+
+    /// Adds `entry` to history and appends it to the history file, if any.
+    /// A pending follow-up is linked to its source and, while the response
+    /// view is open, shown in place of it.
+    fn push_history(&mut self, mut entry: HistoryEntry) {
+        entry.seq = self.history.iter().map(|e| e.seq).max().unwrap_or(0) + 1;
+        entry.spec = self.spec_stamp(&entry.request.method);
+        entry.needs_review = self.migrate_entry(&entry).needs_review();
+        let followed = match self.following {
+            Some((id, source)) if id == entry.request.id => {
+                self.following = None;
+                entry.derived_from = Some(source);
+                true
+            }
+            _ => false,
+        };
+        if let Some(store) = &mut self.history_store
+            && let Err(e) = store.append(&entry)
+        {
+            self.notice = Some(format!("history not saved: {:#}", e));
+        }
+        self.history.push(entry);
+        let dropped = history::trim(&mut self.history, self.history_limit);
+        if dropped > 0 {
+            let selected = self.history_state.selected().map(|i| i.saturating_sub(dropped));
+            self.history_state.select(selected);
+            if let Some(store) = &mut self.history_store
+                && let Err(e) = store.replace(&self.history)
+            {
+                self.notice = Some(format!("history not trimmed: {:#}", e));
+            }
+            self.sweep_spill();
+        }
+        if followed && self.mode == AppMode::ResponseView {
+            self.history_state.select(Some(self.history.len() - 1));
+            self.open_selected_response();
+        }
+    }
+
+    /// The method's current param names, recorded with history entries.
+    fn spec_params(&self, method: &str) -> Option<Vec<String>> {
+        self.base_specs
+            .iter()
+            .find(|s| s.name == method)
+            .map(|s| s.params.iter().map(|p| p.to_string()).collect())
+    }
+
+    /// Cached response for `req` at `url`, if it is immutable and was seen before.
+    pub fn cached_response(&self, url: &str, req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+        self.cache.lock().unwrap().get(url, req)
+    }
+
+    /// Caches a fresh response, notes what it says about the method's
+    /// availability (e.g. -32601), and, when enabled, starts prefetching the
+    /// neighbouring blocks of a block-by-number request.
+    pub fn after_response(&mut self, url: &str, req: &JsonRpcRequest, res: &JsonRpcResponse) {
+        self.cache.lock().unwrap().insert(url, req, res);
+        self.capabilities.set(url, &req.method, classify(res));
+        if self.prefetch {
+            let neighbors = neighbor_blocks(req);
+            self.prefetcher
+                .schedule(self.prefetch_transport.clone(), self.cache.clone(), url, neighbors);
+        }
+    }
+
+    /// Fits a history entry's params onto the current spec.
+    pub fn migrate_entry(&self, entry: &HistoryEntry) -> ParamsMigration {
+        migrate_params(
+            &entry.request.method,
+            &entry.request.params,
+            entry.spec_params.as_deref(),
+            &self.base_specs,
+            RENAMED_METHODS,
+        )
+    }
+
+    /// Refreshes every history entry's
+    /// [`needs_review`](HistoryEntry::needs_review), once the history is
+    /// loaded or the spec changes.
+    pub fn review_migrations(&mut self) {
+        let flags: Vec<bool> = self.history.iter().map(|e| self.migrate_entry(e).needs_review()).collect();
+        for (entry, flag) in self.history.iter_mut().zip(flags) {
+            entry.needs_review = flag;
+        }
+    }
+
+    /// Loads the history entry at `index` into ParamInput mode.
+    ///
+    /// Params are migrated onto the current spec; anything that doesn't fit
+    /// field by field opens in raw-JSON mode instead of being dropped, and
+    /// any realignment leaves a notice asking the user to verify. A method
+    /// missing from the method list is kept in `unlisted_method` and its
+    /// stored params open as raw JSON. When the sources defining the method
+    /// changed since the entry was recorded (see [`origin::drift`]), the
+    /// notice says so, and params that would be realigned onto renamed
+    /// fields open as raw JSON instead.
+    pub fn reload_history_entry(&mut self, index: usize) {
+        let Some(entry) = self.history.get(index).cloned() else { return };
+        let mut migration = self.migrate_entry(&entry);
+        let drift = entry.spec.as_ref().and_then(|recorded| {
+            origin::drift(&entry.request.method, recorded, self.spec_stamp(migration.method()).as_ref())
+        });
+        if drift.is_some()
+            && let ParamsMigration::Migrated { method, .. } = migration
+        {
+            migration = ParamsMigration::Raw { method, params: entry.request.params.clone() };
+        }
+        self.load_history_entry(&entry, migration);
+        if let Some(drift) = drift {
+            let action = if self.raw_params.is_some() { "editing raw params" } else { "verify params before sending" };
+            self.notice = Some(format!("{} — {}", drift, action));
+        }
+    }
+
+    /// Loads `entry`, its params migrated as `migration`, into ParamInput mode.
+    fn load_history_entry(&mut self, entry: &HistoryEntry, migration: ParamsMigration) {
+        // Reset filtered_methods and selection
+        self.search_input.clear();
+        self.filtered_methods = self.all_methods.clone();
+        self.endpoint_override = None;
+        self.tx_form = None;
+        self.seeded_defaults = Vec::new();
+        self.focused_param = 0;
+        let method = migration.method().to_string();
+        self.track_param_method(&method);
+        let Some(idx) = self.all_methods.iter().position(|m| *m == method) else {
+            let params = &entry.request.params;
+            let text = serde_json::to_string_pretty(params).unwrap_or_else(|_| params.to_string());
+            self.unlisted_method = Some(entry.request.method.clone());
+            self.methods_state.select((!self.filtered_methods.is_empty()).then_some(0));
+            self.param_inputs = Vec::new();
+            self.raw_params = Some(self.raw_editor(&text));
+            self.notice = Some("method not in current spec — editing raw params".to_string());
+            self.enter(AppMode::ParamInput);
+            return;
+        };
+        self.unlisted_method = None;
+        self.methods_state.select(Some(idx));
+
+        // Transactions reload into the form, or raw JSON when it can't hold them
+        if method == txform::METHOD {
+            let params = &entry.request.params;
+            let form = TxForm::from_params(params);
+            self.notice = None;
+            self.param_inputs = Vec::new();
+            self.raw_params = form.is_none().then(|| {
+                self.raw_editor(&serde_json::to_string_pretty(params).unwrap_or_else(|_| params.to_string()))
+            });
+            self.tx_form = Some(form.unwrap_or_default());
+            self.enter(AppMode::ParamInput);
+            return;
+        }
+
+        // Builder fields aren't the params themselves
+        if method == simulate::METHOD || method == trace::FILTER {
+            let params = &entry.request.params;
+            self.notice = None;
+            let fields = match method.as_str() {
+                simulate::METHOD => simulate::builder_fields(params),
+                _ => trace::filter_fields(params),
+            };
+            match fields {
+                Some(fields) => {
+                    self.param_inputs = fields;
+                    self.raw_params = None;
+                }
+                None => {
+                    let text = serde_json::to_string_pretty(params).unwrap_or_else(|_| params.to_string());
+                    self.param_inputs = Vec::new();
+                    self.raw_params = Some(self.raw_editor(&text));
+                }
+            }
+            self.enter(AppMode::ParamInput);
+            return;
+        }
+
+        self.notice = migration
+            .needs_review()
+            .then(|| "migrated — verify params before sending".to_string());
+        match migration {
+            ParamsMigration::Unchanged { fields, .. } | ParamsMigration::Migrated { fields, .. } => {
+                self.param_inputs = fields;
+                self.raw_params = None;
+            }
+            ParamsMigration::Raw { params, .. } => {
+                let text = serde_json::to_string_pretty(&params).unwrap_or_else(|_| params.to_string());
+                self.param_inputs = Vec::new();
+                self.raw_params = Some(self.raw_editor(&text));
+            }
+        }
+        self.enter(AppMode::ParamInput);
+    }
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_new_initializes_correctly() {
+        let app = App::new();
+        // Default mode is Main
+        assert_eq!(app.mode, AppMode::Main);
+        // Quit flag should be false
+        assert!(!app.should_quit);
+        // Search input should be empty
+        assert_eq!(app.search_input, "");
+        // Filtered methods should match all methods initially
+        assert_eq!(app.filtered_methods, app.all_methods);
+        // First method selected by default
+        assert_eq!(app.methods_state.selected(), Some(0));
+        // No parameters by default
+        assert!(app.param_inputs.is_empty());
+        // History empty by default
+        assert!(app.history.is_empty());
+        // History selection should be zero
+        assert_eq!(app.history_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn filter_methods_filters_by_query() {
+        let mut app = App::new();
+        // Setup a known method list
+        app.all_methods = vec![
+            "foo".to_string(),
+            "bar".to_string(),
+            "baz".to_string(),
+        ];
+        // Case-insensitive filter
+        app.search_input = "B".to_string();
+        app.filter_methods();
+        assert_eq!(app.filtered_methods, vec!["bar", "baz"]);
+        // Selection resets to 0
+        assert_eq!(app.methods_state.selected(), Some(0));
+
+        // Empty query resets
+        app.search_input.clear();
+        app.filter_methods();
+        assert_eq!(app.filtered_methods, app.all_methods);
+    }
+
+    #[test]
+    fn chain_id_response_selects_native_currency() {
+        let mut app = App::new();
+        assert_eq!(app.currency().symbol, "ETH");
+        let chain_id = |id: &str| JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(Value::from(id)), error: None, id: 1 };
+
+        // Overrides point at some other node and don't tell us the session chain
+        app.record(JsonRpcRequest::new("eth_chainId", Value::Array(vec![]), 1), chain_id("0x89"), Some("http://x".into()));
+        assert_eq!(app.chain_id, None);
+
+        app.record(JsonRpcRequest::new("eth_chainId", Value::Array(vec![]), 1), chain_id("0x64"), None);
+        assert_eq!(app.chain().unwrap().name, "Gnosis");
+        assert_eq!(app.currency().symbol, "XDAI");
+    }
+
+    #[test]
+    fn send_target_prefers_override_without_touching_session() {
+        let mut app = App::new();
+        app.profiles =
+            vec![EndpointProfile { name: "archive".into(), url: "http://archive:8545".into(), ..Default::default() }];
+
+        // No override: session endpoint
+        assert_eq!(app.send_target().unwrap(), (DEFAULT_ENDPOINT.to_string(), None));
+
+        // Blank override behaves like no override
+        app.endpoint_override = Some("  ".into());
+        assert_eq!(app.send_target().unwrap().1, None);
+
+        // Profile name resolves to its URL
+        app.endpoint_override = Some("archive".into());
+        let (url, used) = app.send_target().unwrap();
+        assert_eq!(url, "http://archive:8545");
+        assert_eq!(used.as_deref(), Some("http://archive:8545"));
+        assert_eq!(app.endpoint, DEFAULT_ENDPOINT);
+
+        // Invalid override is an error rather than a silent fallback
+        app.endpoint_override = Some("nope".into());
+        assert!(app.send_target().is_err());
+    }
+
+    #[test]
+    fn raw_mode_round_trips_fields() {
+        let mut app = App::new();
+        app.param_inputs = vec!["0xabc".to_string(), "latest".to_string()];
+        app.toggle_raw_params().unwrap();
+        let raw = app.raw_params.as_ref().unwrap();
+        assert!(raw.error.is_none());
+        assert_eq!(raw.parse().unwrap(), serde_json::json!(["0xabc", "latest"]));
+
+        // Invalid JSON keeps raw mode on and blocks the request
+        app.raw_params.as_mut().unwrap().area.insert_char(',');
+        assert!(app.toggle_raw_params().is_err());
+        assert!(app.raw_params.is_some());
+        assert!(app.build_request().is_err());
+
+        app.raw_params.as_mut().unwrap().area.backspace();
+        app.toggle_raw_params().unwrap();
+        assert!(app.raw_params.is_none());
+        assert_eq!(app.param_inputs, vec!["0xabc", "latest"]);
+    }
+
+    #[test]
+    fn dry_run_records_how_params_were_resolved() {
+        let mut app = App::new();
+        app.param_defaults = serde_json::from_value(serde_json::json!({"eth": {"block": "latest"}})).unwrap();
+        app.methods_state.select(Some(1));
+        app.param_inputs = vec![String::new(), String::new()];
+        app.apply_param_defaults();
+        let request = app.build_request().unwrap();
+        assert_eq!(request.params, serde_json::json!(["latest"]));
+        app.record_dry_run(request, None);
+
+        let trail = app.history[0].provenance.clone();
+        assert_eq!(
+            trail.lines(),
+            [
+                r#"address: typed """#,
+                r#"  empty, dropped (later params shift left): "" → """#,
+                r#"block: typed """#,
+                r#"  config default: "" → "latest""#,
+            ]
+        );
+        assert!(app.provenance.is_empty());
+
+        // The response view shows it on its own tab
+        app.open_selected_response();
+        let view = app.response_view.as_mut().unwrap();
+        view.toggle_tab();
+        assert_eq!(view.lines, trail.lines());
+        view.toggle_tab();
+        assert!(view.lines[0].starts_with("DRY RUN"));
+    }
+
+    #[test]
+    fn chain_10_merges_the_optimism_extension_unless_disabled() {
+        let chain_id = |id: &str| {
+            let request = JsonRpcRequest::new("eth_chainId", serde_json::json!([]), 1);
+            let response = JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(serde_json::json!(id)), error: None, id: 1 };
+            (request, response)
+        };
+        let mut app = App::new();
+        let base = app.all_methods.len();
+        let (request, response) = chain_id("0xa");
+        app.record(request, response, None);
+        assert_eq!(app.extension.map(|e| e.name.as_str()), Some("optimism"));
+        assert!(app.all_methods.iter().any(|m| m == "optimism_syncStatus"));
+        assert_eq!(app.find_spec("optimism_outputAtBlock").unwrap().params, ["block"]);
+        assert!(app.find_spec("eth_getBalance").is_some());
+
+        // Another chain swaps the extension's methods out
+        let (request, response) = chain_id("0x1");
+        app.record(request, response, None);
+        assert_eq!(app.extension.map(|e| e.name.as_str()), None);
+        assert_eq!(app.all_methods.len(), base);
+        assert!(app.find_spec("optimism_outputAtBlock").is_none());
+
+        let mut app = App::new();
+        app.extension_choice = ExtensionChoice::Off;
+        let (request, response) = chain_id("0xa");
+        app.record(request, response, None);
+        assert_eq!(app.chain_id, Some(10));
+        assert!(app.extension.is_none());
+        assert_eq!(app.all_methods.len(), base);
+    }
+
+    fn spec(name: &'static str) -> MethodSpec {
+        MethodSpec::builtin(name, &[], &[], &[])
+    }
+
+    #[test]
+    fn method_list_comes_from_the_registry() {
+        let app = App::new();
+        for method in &app.all_methods {
+            assert!(app.find_spec(method).is_some(), "{} has no MethodSpec", method);
+        }
+        assert_eq!(app.all_methods.len(), RPC_SPECS.len());
+        let mut names: Vec<&str> = RPC_SPECS.iter().map(|s| &*s.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), RPC_SPECS.len(), "duplicate names in RPC_SPECS");
+        for prefix in ["eth_", "net_", "web3_"] {
+            assert!(app.all_methods.iter().any(|m| m.starts_with(prefix)), "no {} methods", prefix);
+        }
+        // Every help list lines up with its params
+        assert!(RPC_SPECS.iter().all(|s| s.help.len() <= s.params.len()));
+    }
+
+    #[test]
+    fn spec_reload_keeps_search_and_highlighted_method() {
+        let mut app = App::new();
+        app.search_input = "block".into();
+        app.filter_methods();
+        let row = app.filtered_methods.iter().position(|m| m == "eth_getBlockByHash");
+        app.methods_state.select(row);
+        assert_eq!(app.selected_method(), Some("eth_getBlockByHash"));
+
+        app.all_methods.push("custom_unlisted".into());
+        let mut specs = RPC_SPECS.to_vec();
+        specs.insert(0, spec("debug_getRawBlock"));
+        app.apply_spec(specs);
+        assert_eq!(app.search_input, "block");
+        assert_eq!(app.filtered_methods.last().map(String::as_str), Some("debug_getRawBlock"));
+        assert_eq!(app.selected_method(), Some("eth_getBlockByHash"));
+        // Methods without a spec are never dropped
+        assert!(app.all_methods.iter().any(|m| m == "custom_unlisted"));
+    }
+
+    #[test]
+    fn spec_reload_falls_back_to_the_nearest_row_for_removed_methods() {
+        let mut app = App::new();
+        app.search_input = "blockby".into();
+        app.filter_methods();
+        assert_eq!(app.filtered_methods[..2], ["eth_getBlockByNumber", "eth_getBlockByHash"]);
+        app.methods_state.select(Some(1));
+        app.drafts.insert("eth_getBlockByHash".into(), vec!["0xabc".into()]);
+
+        let specs = RPC_SPECS.iter().filter(|s| s.name != "eth_getBlockByHash").cloned().collect();
+        app.apply_spec(specs);
+        assert_eq!(app.filtered_methods, ["eth_getBlockByNumber", "eth_getBlockTransactionCountByHash", "eth_getBlockTransactionCountByNumber"]);
+        assert_eq!(app.methods_state.selected(), Some(1));
+        // Hidden, not forgotten
+        assert!(app.drafts.contains_key("eth_getBlockByHash"));
+
+        app.search_input = "nothing matches".into();
+        app.apply_spec(RPC_SPECS.to_vec());
+        assert_eq!(app.methods_state.selected(), None);
+    }
+
+    #[test]
+    fn spec_reload_follows_renamed_methods() {
+        let mut app = App::new();
+        app.methods_state.select(Some(1));
+        assert_eq!(app.selected_method(), Some("eth_getBalance"));
+        let specs = RPC_SPECS
+            .iter()
+            .map(|s| if s.name == "eth_getBalance" { MethodSpec { name: "eth_getAccountBalance".into(), ..s.clone() } } else { s.clone() })
+            .collect();
+        app.apply_spec_renamed(specs, &[("eth_getBalance", "eth_getAccountBalance")]);
+        assert_eq!(app.selected_method(), Some("eth_getAccountBalance"));
+        assert!(!app.all_methods.iter().any(|m| m == "eth_getBalance"));
+    }
+
+    #[test]
+    fn spec_file_replaces_the_registry_under_the_extension() {
+        let mut app = App::new();
+        let specs = crate::spec::parse_document(
+            r#"[{"name": "eth_blockNumber"}, {"name": "anvil_mine", "params": [{"name": "blocks"}]}]"#,
+        )
+        .unwrap();
+        app.set_base_specs(specs);
+        assert_eq!(app.all_methods, ["eth_blockNumber", "anvil_mine"]);
+        assert_eq!(app.find_spec("anvil_mine").unwrap().params, ["blocks"]);
+        assert_eq!(app.spec_params("anvil_mine"), Some(vec!["blocks".to_string()]));
+
+        // A chain's extension still merges on top
+        app.extension_choice = ExtensionChoice::Force("optimism".into());
+        app.apply_spec_extension();
+        assert_eq!(&app.all_methods[..2], ["eth_blockNumber", "anvil_mine"]);
+        assert!(app.all_methods.iter().any(|m| m.starts_with("optimism_")));
+    }
+
+    #[test]
+    fn plugin_methods_are_listed_and_sent_through_their_template() {
+        let mut app = App::new();
+        let plugin = plugin::parse(include_str!("../tests/fixtures/plugins/myrollup.json")).unwrap();
+        app.add_plugins(vec![plugin]);
+        assert_eq!(&app.all_methods[app.all_methods.len() - 2..], ["myrollup_getBatch", "sequencer_status"]);
+        assert_eq!(app.primary_pointers(), [("myrollup_getBatch", "/number"), ("sequencer_status", "/healthy")]);
+
+        let row = app.filtered_methods.iter().position(|m| m == "myrollup_getBatch").unwrap();
+        app.methods_state.select(Some(row));
+        app.param_inputs = vec!["0x2a".into(), String::new()];
+        let request = app.build_request().unwrap();
+        assert_eq!(request.params, serde_json::json!([{"number": "0x2a", "full": false}]));
+
+        let decoded = app.decoders.decode("sequencer_status", &serde_json::json!({"mode": "active", "healthy": true}), &app.prefs);
+        assert_eq!(decoded.unwrap().lines(), ["sequencer active, healthy: true", "healthy: true"]);
+    }
+
+    fn entry(method: &str, params: Value, spec_params: Option<&[&str]>) -> HistoryEntry {
+        HistoryEntry {
+            spec_params: spec_params.map(|p| p.iter().map(|s| s.to_string()).collect()),
+            ..HistoryEntry::new(
+                JsonRpcRequest::new(method, params, 1),
+                JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: None, id: 1 },
+            )
+        }
+    }
+
+    #[test]
+    fn large_responses_fill_in_while_the_view_stays_open() {
+        let mut app = App::new();
+        let mut big = entry("eth_getLogs", serde_json::json!([{}]), None);
+        let logs: Vec<Value> = (0..8_000).map(|i| serde_json::json!({"logIndex": i, "topics": ["0xaa"]})).collect();
+        big.response.result = Some(Value::Array(logs));
+        app.history.push(big);
+        app.open_selected_response();
+        let view = app.response_view.as_mut().unwrap();
+        assert!(view.formatting_status().is_some());
+        view.search.query = "\"logIndex\": 7999".into();
+        view.run_search();
+
+        let started = std::time::Instant::now();
+        while view.formatting.is_some() {
+            assert!(started.elapsed() < std::time::Duration::from_secs(10), "formatting never finished");
+            view.poll_formatting(&app.address_book, app.chain_id);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        let expected: Vec<String> = serde_json::to_string_pretty(&app.history[0].response).unwrap().lines().map(String::from).collect();
+        assert_eq!(view.lines, expected);
+        assert_eq!(view.labels.len(), view.lines.len());
+        // The search typed early covers the whole response once it's in
+        assert_eq!(view.search.indicator().as_deref(), Some("match 1/1"));
+    }
+
+    #[test]
+    fn quantity_results_are_decoded_beneath_the_json() {
+        let mut app = App::new();
+        let mut balance = entry("eth_getBalance", serde_json::json!(["0xabc", "latest"]), None);
+        balance.response.result = Some(serde_json::json!("0xde0b6b3a7640000"));
+        app.history.push(balance);
+        app.open_selected_response();
+        let lines = &app.response_view.as_ref().unwrap().lines;
+        assert!(lines[0].starts_with('{'), "{:?}", lines);
+        assert_eq!(lines[lines.len() - 3..], ["hex      0xde0b6b3a7640000", "decimal  1000000000000000000", "ETH      1"]);
+    }
+
+    #[test]
+    fn reload_marks_entries_from_an_older_spec() {
+        let mut app = App::new();
+        // Same spec: loads as-is with no notice
+        app.history.push(entry("eth_getBalance", serde_json::json!(["0xabc", "latest"]), Some(&["address", "block"])));
+        // Recorded when eth_getBalance only had one param
+        app.history.push(entry("eth_getBalance", serde_json::json!(["0xabc"]), Some(&["address"])));
+        // More values than the spec has slots
+        app.history.push(entry("eth_getBalance", serde_json::json!(["0xabc", "latest", 1]), None));
+
+        app.reload_history_entry(0);
+        assert_eq!(app.param_inputs, vec!["0xabc", "latest"]);
+        assert!(app.notice.is_none());
+
+        app.reload_history_entry(1);
+        assert_eq!(app.mode, AppMode::ParamInput);
+        assert_eq!(app.param_inputs, vec!["0xabc", ""]);
+        assert!(app.notice.as_deref().unwrap().starts_with("migrated"));
+
+        app.reload_history_entry(2);
+        let raw = app.raw_params.as_ref().expect("extras should open raw mode");
+        assert_eq!(raw.parse().unwrap(), serde_json::json!(["0xabc", "latest", 1]));
+        assert!(app.notice.is_some());
+    }
+
+    #[test]
+    fn reload_flags_entries_whose_spec_source_changed() {
+        let spec = |params: &str| {
+            let document = format!(r#"[{{"name": "eth_getBalance", "params": {}}}, {{"name": "eth_chainId"}}]"#, params);
+            crate::spec::parse_document(&document).unwrap()
+        };
+        let mut app = App::new();
+        app.set_spec_source("node.json", spec(r#"[{"name": "address"}, {"name": "block"}]"#));
+        let params = serde_json::json!(["0xabc", "latest"]);
+        app.push_history(entry("eth_getBalance", params.clone(), Some(&["address", "block"])));
+        assert!(!app.history[0].needs_review);
+        let stamp = app.history[0].spec.clone().expect("recorded entries are stamped");
+        assert_eq!(stamp.sources, ["spec:node.json"]);
+        assert_eq!(stamp.hash, origin::definition_hash(app.find_spec("eth_getBalance").unwrap()));
+        app.reload_history_entry(0);
+        assert!(app.notice.is_none());
+
+        // The spec is upgraded and renames a param: edit raw rather than realign
+        app.set_spec_source("node.json", spec(r#"[{"name": "address"}, {"name": "blockTag"}]"#));
+        assert!(app.history[0].needs_review, "the list marks it without migrating per frame");
+        app.reload_history_entry(0);
+        assert_eq!(app.raw_params.as_ref().expect("raw mode").parse().unwrap(), params);
+        assert_eq!(
+            app.notice.as_deref(),
+            Some("eth_getBalance changed in spec:node.json since this was recorded — editing raw params")
+        );
+
+        // Same definition from another source: the fields still fit
+        app.set_spec_source("other.json", spec(r#"[{"name": "address"}, {"name": "block"}]"#));
+        assert!(!app.history[0].needs_review);
+        app.reload_history_entry(0);
+        assert_eq!(app.param_inputs, ["0xabc", "latest"]);
+        assert!(app.raw_params.is_none());
+        assert_eq!(
+            app.notice.as_deref(),
+            Some("eth_getBalance was from spec:node.json, now from spec:other.json — verify params before sending")
+        );
+    }
+
+    #[test]
+    fn params_round_trip_through_history_as_typed_json() {
+        let mut app = App::new();
+        app.methods_state.select(app.filtered_methods.iter().position(|m| m == "eth_call"));
+        let typed = ["{\"gas\":21000,\"to\":\"0xabc\"}", "latest"];
+        app.param_inputs = typed.iter().map(|s| s.to_string()).collect();
+        let params = app.current_params().unwrap();
+        assert_eq!(params, serde_json::json!([{"to": "0xabc", "gas": 21000}, "latest"]));
+
+        app.history.push(entry("eth_call", params.clone(), None));
+        app.param_inputs.clear();
+        app.reload_history_entry(0);
+        assert_eq!(app.param_inputs, typed);
+        assert_eq!(app.current_params().unwrap(), params);
+
+        // Strings that look like other JSON keep their quotes in the field
+        for value in [serde_json::json!(true), serde_json::json!(7), serde_json::json!("123"), serde_json::json!("true"), serde_json::json!("0x1f")] {
+            assert_eq!(parse_param(&value_to_field(&value)), value);
+        }
+        assert_eq!(value_to_field(&serde_json::json!("123")), "\"123\"");
+
+        // Integers past u64/i64 stay as typed instead of rounding through f64
+        let big = "123456789012345678901234567890";
+        assert_eq!(parse_param(big), serde_json::json!(big));
+        assert_eq!(value_to_field(&parse_param(big)), big);
+        assert_eq!(parse_param(&format!("[{big}]")), serde_json::json!(format!("[{big}]")));
+        assert_eq!(parse_param("18446744073709551615"), serde_json::json!(u64::MAX));
+        assert_eq!(parse_param("1.5"), serde_json::json!(1.5));
+    }
+
+    #[test]
+    fn reload_of_unknown_method_keeps_name_and_opens_raw() {
+        let mut app = App::new();
+        app.history.push(entry("bogus_method", serde_json::json!(["0x1", {"full": true}]), None));
+        // Start from a filtered search; the reload resets it
+        app.search_input = "eth_call".into();
+        app.filter_methods();
+
+        app.reload_history_entry(0);
+        assert_eq!(app.mode, AppMode::ParamInput);
+        assert_eq!(app.selected_method(), Some("bogus_method"));
+        assert_eq!(app.notice.as_deref(), Some("method not in current spec — editing raw params"));
+        let raw = app.raw_params.as_ref().expect("unknown method should open raw mode");
+        assert_eq!(raw.parse().unwrap(), serde_json::json!(["0x1", {"full": true}]));
+        let request = app.build_request().unwrap();
+        assert_eq!(request.method, "bogus_method");
+
+        // Main-mode selection stays within the (reset) list
+        assert_eq!(app.filtered_methods, app.all_methods);
+        assert!(app.methods_state.selected().unwrap() < app.filtered_methods.len());
+
+        // Reloading a listed method drops the unlisted one
+        app.history.push(entry("eth_getBalance", serde_json::json!(["0xabc", "latest"]), None));
+        app.reload_history_entry(1);
+        assert_eq!(app.selected_method(), Some("eth_getBalance"));
+    }
+
+    fn select(app: &mut App, method: &str) {
+        let idx = app.filtered_methods.iter().position(|m| m == method).unwrap();
+        app.methods_state.select(Some(idx));
+    }
+
+    #[test]
+    fn hash_in_get_block_by_number_suggests_by_hash() {
+        let mut app = App::new();
+        let hash = format!("0x{}", "ab".repeat(32));
+        select(&mut app, "eth_getBlockByNumber");
+        app.param_inputs = vec![hash.clone(), "true".into()];
+        let (target, message) = app.block_method_suggestion().unwrap();
+        assert_eq!(target, "eth_getBlockByHash");
+        assert!(message.contains("looks like a block hash"));
+
+        assert!(app.switch_block_method());
+        assert_eq!(app.selected_method(), Some("eth_getBlockByHash"));
+        assert_eq!(app.param_inputs, vec![hash, "true".to_string()]);
+        assert_eq!(app.block_method_suggestion(), None);
+    }
+
+    #[test]
+    fn number_in_get_block_by_hash_suggests_by_number() {
+        let mut app = App::new();
+        // Switching works even when a search hides the target method
+        app.search_input = "ByHash".into();
+        app.filter_methods();
+        select(&mut app, "eth_getBlockByHash");
+        app.param_inputs = vec!["17000000".into(), "false".into()];
+        assert_eq!(app.block_method_suggestion().unwrap().0, "eth_getBlockByNumber");
+
+        assert!(app.switch_block_method());
+        assert_eq!(app.selected_method(), Some("eth_getBlockByNumber"));
+        assert_eq!(app.param_inputs, vec!["0x1036640".to_string(), "false".to_string()]);
+    }
+
+    #[test]
+    fn matching_params_get_no_suggestion() {
+        let mut app = App::new();
+        select(&mut app, "eth_getBlockByNumber");
+        app.param_inputs = vec!["latest".into(), "false".into()];
+        assert_eq!(app.block_method_suggestion(), None);
+        assert!(!app.switch_block_method());
+        select(&mut app, "eth_getBalance");
+        app.param_inputs = vec![format!("0x{}", "ab".repeat(32))];
+        assert_eq!(app.block_method_suggestion(), None);
+    }
+
+    #[test]
+    fn simulate_builder_assembles_params_and_reloads_from_history() {
+        let mut app = App::new();
+        select(&mut app, simulate::METHOD);
+        app.param_inputs = vec![r#"{"to": "0xc1", "value": "0x1"}"#.into(), String::new()];
+        let request = app.build_request().unwrap();
+        assert_eq!(request.params, serde_json::json!([
+            {"blockStateCalls": [{"calls": [{"to": "0xc1", "value": "0x1"}]}]},
+            "latest"
+        ]));
+
+        app.record_dry_run(request, None);
+        app.param_inputs.clear();
+        app.reload_history_entry(0);
+        assert_eq!(app.param_inputs, vec![r#"[{"to":"0xc1","value":"0x1"}]"#.to_string(), String::new()]);
+        assert!(app.raw_params.is_none() && app.notice.is_none());
+    }
+
+    #[test]
+    fn trace_filter_builder_assembles_one_filter_object() {
+        let mut app = App::new();
+        select(&mut app, trace::FILTER);
+        let to = "0x00000000000000000000000000000000000000b2";
+        app.param_inputs = vec!["100".into(), String::new(), String::new(), to.into(), String::new(), "10".into()];
+        let request = app.build_request().unwrap();
+        assert_eq!(request.params, serde_json::json!([{"fromBlock": "0x64", "toAddress": [to], "count": 10}]));
+
+        app.record_dry_run(request, None);
+        app.param_inputs.clear();
+        app.reload_history_entry(0);
+        assert_eq!(app.param_inputs, ["0x64", "", "", to, "", "10"]);
+        assert!(app.raw_params.is_none());
+    }
+
+    #[test]
+    fn boolean_params_are_toggles_sent_as_booleans() {
+        let mut app = App::new();
+        select(&mut app, "eth_getBlockByNumber");
+        app.param_inputs = app.blank_params("eth_getBlockByNumber");
+        assert!(matches!(app.param_kind(1), ParamKind::Bool { default: false, .. }));
+        assert_eq!(app.param_kind(0), ParamKind::Text);
+
+        // Untouched, the toggle sends the spec default
+        app.param_inputs[0] = "latest".into();
+        let mut trail = Trail::default();
+        assert_eq!(app.resolve_params(&mut trail).unwrap(), serde_json::json!(["latest", false]));
+        assert!(trail.lines().iter().any(|l| l.contains("spec default")), "{:?}", trail.lines());
+
+        app.focused_param = 1;
+        assert!(app.flip_focused_toggle());
+        assert_eq!(app.current_params().unwrap(), serde_json::json!(["latest", true]));
+        // A mistyped word is still read as the boolean it spells
+        app.param_inputs[1] = "True".into();
+        assert_eq!(app.current_params().unwrap(), serde_json::json!(["latest", true]));
+
+        app.focused_param = 0;
+        assert!(!app.flip_focused_toggle());
+    }
+
+    #[test]
+    fn raw_mode_coerces_boolean_words_at_toggle_positions() {
+        let mut app = App::new();
+        select(&mut app, "eth_getBlockByNumber");
+        app.raw_params = Some(RawParams::new(r#"["true", "False"]"#));
+        // Only the second param is a boolean
+        assert_eq!(app.current_params().unwrap(), serde_json::json!(["true", false]));
+        app.raw_params = Some(RawParams::new(r#"["latest", "maybe"]"#));
+        assert_eq!(app.current_params().unwrap(), serde_json::json!(["latest", "maybe"]));
+    }
+
+    #[test]
+    fn relaxed_raw_params_are_opt_in_and_sent_strict() {
+        let mut app = App::new();
+        select(&mut app, "eth_getBlockByNumber");
+        app.param_inputs = vec!["latest".into(), String::new()];
+        app.toggle_raw_params().unwrap();
+        let text = "[\n  'latest', // head\n  true,\n]";
+        app.raw_params.as_mut().unwrap().area = crate::editor::TextArea::new(text);
+        assert!(app.current_params().is_err());
+
+        app.raw_params = None;
+        app.ui_state.relaxed_json = true;
+        app.toggle_raw_params().unwrap();
+        let raw = app.raw_params.as_mut().unwrap();
+        assert!(raw.relaxed);
+        raw.area = crate::editor::TextArea::new(text);
+        assert_eq!(app.current_params().unwrap(), serde_json::json!(["latest", true]));
+        // Leaving raw mode splits the normalized params into fields
+        app.toggle_raw_params().unwrap();
+        assert_eq!(app.param_inputs, ["latest", "true"]);
+    }
+
+    #[test]
+    fn reloaded_booleans_land_on_the_toggle() {
+        let mut app = App::new();
+        let params = serde_json::json!(["0x10", true]);
+        app.history.push(entry("eth_getBlockByNumber", params.clone(), Some(&["block", "include_transactions"])));
+        app.reload_history_entry(0);
+        assert_eq!(app.param_inputs, ["0x10", "true"]);
+        assert_eq!(app.param_kind(1).toggle_state(&app.param_inputs[1]), Some(true));
+        assert_eq!(app.current_params().unwrap(), params);
+    }
+
+    #[test]
+    fn history_keeps_the_newest_entries_up_to_the_limit() {
+        let mut app = App::new();
+        app.history_limit = 10;
+        for id in 1..=11 {
+            app.record_dry_run(JsonRpcRequest::new("eth_blockNumber", serde_json::json!([]), id), None);
+        }
+        app.history_state.select(Some(10));
+        app.record_dry_run(JsonRpcRequest::new("eth_blockNumber", serde_json::json!([]), 12), None);
+        assert_eq!(app.history.len(), 10);
+        assert_eq!(app.history.first().map(|e| e.seq), Some(3));
+        // The selection stays on the same entry
+        assert_eq!(app.history_state.selected(), Some(8));
+    }
+
+    #[test]
+    fn results_past_the_spill_limit_are_paged_from_disk() {
+        let dir = std::env::temp_dir().join(format!("eli-app-spill-{}", std::process::id()));
+        let mut app = App::new();
+        app.set_spill_dir(dir.join("spill"));
+        // Four values per log plus the array: just past the limit
+        let logs: Vec<Value> = (0..spill::SPILL_LIMIT / 4)
+            .map(|i| serde_json::json!({"logIndex": format!("0x{:x}", i), "removed": false, "topics": [format!("0x{:064x}", i)]}))
+            .collect();
+        let response = JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(Value::Array(logs)), error: None, id: 3 };
+        app.record(JsonRpcRequest::new("eth_getLogs", serde_json::json!([{}]), 3), response, None);
+        let spilled = app.history[0].spill.clone().unwrap();
+        assert_eq!(app.history[0].response.result, None);
+
+        app.history_state.select(Some(0));
+        app.open_selected_response();
+        let view = app.response_view.as_mut().unwrap();
+        let first = view.lines.iter().position(|l| l.starts_with("  [0] …")).unwrap();
+        (view.scroll, view.height) = (first, 3);
+        assert!(view.page_spill(&app.address_book, app.chain_id));
+        assert!(view.lines[first].starts_with(r#"  [0] {"logIndex":"0x0""#), "{}", view.lines[first]);
+        assert!(view.lines[first + 3].starts_with("  [3] …"));
+
+        let outline = view.lines.len();
+        assert!(view.toggle_spilled(&app.address_book, app.chain_id).unwrap());
+        assert_eq!(view.lines[first + 1], "    {");
+        assert!(view.lines.len() > outline);
+        assert!(view.toggle_spilled(&app.address_book, app.chain_id).unwrap());
+        assert_eq!(view.lines.len(), outline);
+
+        let csv = dir.join("logs.csv");
+        app.prefs.csv_decimal = true;
+        assert_eq!(app.export_csv(csv.to_str().unwrap()).unwrap(), spill::SPILL_LIMIT / 4);
+        let text = std::fs::read_to_string(&csv).unwrap();
+        assert_eq!(text.lines().next(), Some("logIndex,removed,topics"));
+        assert_eq!(text.lines().nth(2).map(|l| l.starts_with("1,false,")), Some(true));
+
+        app.delete_history(&[app.history[0].seq]);
+        assert!(!spilled.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resuming_from_suspend_refreshes_instead_of_reporting_a_stall() {
+        let clock = Arc::new(crate::clock::ManualClock::new(1_700_000_000));
+        let mut app = App::new();
+        app.start_tutorial();
+        app.clock = clock.clone();
+        app.tick(clock.now());
+        app.stats.shared().update(|s| s.head = Some(head::Head { number: 7, timestamp: None, observed: 1_700_000_000 }));
+        app.show_toast("copied request");
+
+        clock.advance(std::time::Duration::from_secs(1));
+        app.tick(clock.now());
+        assert_eq!(app.head_status(clock.unix_now()).unwrap().0, "last block 1s ago");
+
+        // Only the wall clock moves across the suspend
+        clock.sleep(std::time::Duration::from_secs(2 * 3600));
+        app.tick(clock.now());
+        assert_eq!(app.toast.as_ref().unwrap().message, "resumed after 2h 00m; refreshing");
+        assert_eq!(app.head_status(clock.unix_now()), None);
+
+        clock.advance(TOAST_DURATION);
+        app.tick(clock.now());
+        assert_eq!(app.toast, None);
+    }
+
+    #[tokio::test]
+    async fn live_heads_mark_gaps_and_can_be_killed_from_the_activity_panel() {
+        use std::time::Duration;
+
+        let mut app = App::new();
+        let (connector, mut sockets) = crate::ws::scripted(2);
+        let policy = ReconnectPolicy { initial_delay: Duration::from_millis(1), max_delay: Duration::from_millis(2), max_attempts: 3 };
+        let mut subs = Subscriptions::start("ws://node", connector, policy, app.clock.clone());
+        subs.subscribe(serde_json::json!(["newHeads"]));
+        app.live_heads = Some(subs);
+        let live_task = |app: &App| app.tasks.snapshot().tasks().iter().find(|t| t.kind == TaskKind::LiveHeads).cloned();
+
+        let request = sockets[0].requests.recv().await.unwrap();
+        sockets[0].reply(request.id, serde_json::json!("0xs1"));
+        sockets[0].notify("0xs1", serde_json::json!({"number": "0x1"}));
+        for _ in 0..100 {
+            app.tick(app.clock.now());
+            if app.stats.last().head.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(app.stats.last().head.map(|h| h.number), Some(1));
+        let task = live_task(&app).expect("registered");
+        assert_eq!((task.interval, task.errors), (Duration::ZERO, 0));
+        assert!(task.last_result.is_some());
+
+        // The node restarts; the resubscribe leaves a marker where heads were missed
+        drop(sockets.remove(0));
+        let request = sockets[0].requests.recv().await.unwrap();
+        sockets[0].reply(request.id, serde_json::json!("0xs2"));
+        for _ in 0..100 {
+            app.tick(app.clock.now());
+            if app.toast.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let toast = app.toast.as_ref().expect("gap marker").message.clone();
+        assert!(toast.starts_with("— connection lost ") && toast.contains(", resubscribed "), "{}", toast);
+
+        // Killed: the head is polled over HTTP again
+        app.tasks.update(|r| r.remove(task.id));
+        app.tick(app.clock.now());
+        assert!(app.live_heads.is_none());
+        assert!(live_task(&app).is_none());
+        assert_eq!(app.notice.as_deref(), Some("live heads stopped — polling over HTTP"));
     }
 }
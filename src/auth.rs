@@ -0,0 +1,366 @@
+// src/auth.rs
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use base64::Engine;
+use reqwest::header::HeaderValue;
+use tokio::sync::Mutex;
+
+/// Time an auth command gets to print a token.
+pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long a token is reused when the profile doesn't say. A token that
+/// expires sooner is replaced on the first 401 anyway.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Characters of stderr quoted in an [`AuthFailed`].
+const STDERR_SNIPPET: usize = 200;
+
+/// An endpoint profile's auth command didn't produce a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthFailed {
+    pub command: String,
+    /// Exit status and stderr, a timeout, or what was wrong with the output.
+    pub reason: String,
+}
+
+impl std::fmt::Display for AuthFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "auth command `{}` failed: {}", self.command, self.reason)
+    }
+}
+
+impl std::error::Error for AuthFailed {}
+
+/// Runs auth commands; tests swap in a fake.
+#[async_trait]
+pub trait CommandRunner: Send + Sync {
+    /// Runs `command` and returns its stdout, trimmed.
+    async fn run(&self, command: &str, timeout: Duration) -> Result<String, AuthFailed>;
+}
+
+/// Runs commands through the platform shell.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShellRunner;
+
+#[async_trait]
+impl CommandRunner for ShellRunner {
+    async fn run(&self, command: &str, timeout: Duration) -> Result<String, AuthFailed> {
+        let owned = command.to_string();
+        tokio::task::spawn_blocking(move || run_blocking(&owned, timeout))
+            .await
+            .unwrap_or_else(|e| Err(AuthFailed { command: command.to_string(), reason: e.to_string() }))
+    }
+}
+
+fn run_blocking(command: &str, timeout: Duration) -> Result<String, AuthFailed> {
+    let failed = |reason: String| AuthFailed { command: command.to_string(), reason };
+    let mut shell = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+    shell.arg(if cfg!(windows) { "/C" } else { "-c" }).arg(command);
+    let mut child = shell
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| failed(format!("cannot start it: {}", e)))?;
+    // Read as it's written: a command printing more than a pipe holds
+    // would otherwise block, never exit and time out
+    let (stdout, stderr) = (drain(child.stdout.take()), drain(child.stderr.take()));
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait().map_err(|e| failed(e.to_string()))? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(failed(format!("no token within {:?}", timeout)));
+            }
+            None => std::thread::sleep(Duration::from_millis(20)),
+        }
+    };
+    let (stdout, stderr) = (stdout.join().unwrap_or_default(), stderr.join().unwrap_or_default());
+    if !status.success() {
+        let snippet: String = stderr.trim().chars().take(STDERR_SNIPPET).collect();
+        return Err(failed(format!("{}: {}", status, if snippet.is_empty() { "no output on stderr" } else { &snippet })));
+    }
+    Ok(stdout.trim().to_string())
+}
+
+/// Reads `pipe` to the end on its own thread.
+fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut text);
+        }
+        text
+    })
+}
+
+/// The Authorization header for `token`: as printed when it already names
+/// its scheme ("Basic …"), else as a bearer token.
+pub fn header_value(token: &str) -> Option<HeaderValue> {
+    let value = if token.contains(' ') { token.to_string() } else { format!("Bearer {}", token) };
+    let mut value = HeaderValue::from_str(&value).ok()?;
+    value.set_sensitive(true);
+    Some(value)
+}
+
+/// Environment variable holding a bearer token for the session endpoint.
+pub const BEARER_ENV: &str = "ELI_BEARER_TOKEN";
+
+/// Environment variable holding `user:password` for the session endpoint.
+pub const BASIC_ENV: &str = "ELI_BASIC_AUTH";
+
+/// Fixed credentials for an endpoint, given on the command line or in the
+/// environment rather than printed by an auth command.
+///
+/// Neither `Display` nor `Debug` shows the secret.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Credentials {
+    Bearer(String),
+    Basic { user: String, password: String },
+}
+
+impl Credentials {
+    /// `user:password`, as curl's `-u` takes them; the password may hold
+    /// colons, the user can't.
+    pub fn basic(text: &str) -> Option<Self> {
+        let (user, password) = text.split_once(':')?;
+        (!user.is_empty()).then(|| Credentials::Basic { user: user.to_string(), password: password.to_string() })
+    }
+
+    /// Credentials from [`BEARER_ENV`], else [`BASIC_ENV`]; `var` looks a
+    /// variable up. Empty variables count as unset.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Option<Self>> {
+        let var = |name| var(name).filter(|v| !v.is_empty());
+        if let Some(token) = var(BEARER_ENV) {
+            return Ok(Some(Credentials::Bearer(token)));
+        }
+        match var(BASIC_ENV) {
+            Some(text) => match Credentials::basic(&text) {
+                Some(credentials) => Ok(Some(credentials)),
+                None => anyhow::bail!("{} expects user:password", BASIC_ENV),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// The Authorization header they make, marked sensitive.
+    pub fn header(&self) -> Option<HeaderValue> {
+        let value = match self {
+            Credentials::Bearer(token) => format!("Bearer {}", token.trim()),
+            Credentials::Basic { user, password } => {
+                let encoded = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password));
+                format!("Basic {}", encoded)
+            }
+        };
+        let mut value = HeaderValue::from_str(&value).ok()?;
+        value.set_sensitive(true);
+        Some(value)
+    }
+}
+
+impl std::fmt::Display for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Credentials::Bearer(_) => write!(f, "bearer token"),
+            Credentials::Basic { user, .. } => write!(f, "basic auth as {}", user),
+        }
+    }
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Credentials({})", self)
+    }
+}
+
+/// The token of one endpoint, from its profile's auth command.
+///
+/// The token is cached for `ttl`. Fetching holds the cache's lock across
+/// the command, so concurrent requests wait for one run instead of each
+/// starting their own.
+pub struct AuthToken {
+    url: String,
+    command: String,
+    ttl: Duration,
+    runner: Arc<dyn CommandRunner>,
+    /// The token and when the command printed it.
+    cached: Mutex<Option<(String, Instant)>>,
+}
+
+impl AuthToken {
+    pub fn new(url: &str, command: &str, ttl: Duration, runner: Arc<dyn CommandRunner>) -> Self {
+        AuthToken { url: url.to_string(), command: command.to_string(), ttl, runner, cached: Mutex::new(None) }
+    }
+
+    /// The endpoint the token is for.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The cached token while it is fresh, else a new one.
+    pub async fn token(&self) -> Result<String, AuthFailed> {
+        let mut cached = self.cached.lock().await;
+        if let Some((token, fetched)) = cached.as_ref()
+            && fetched.elapsed() < self.ttl
+        {
+            return Ok(token.clone());
+        }
+        self.fetch(&mut cached).await
+    }
+
+    /// A replacement for `rejected`, which the endpoint answered 401 to.
+    /// If another request already replaced it, that token is returned
+    /// without running the command again.
+    pub async fn refresh(&self, rejected: &str) -> Result<String, AuthFailed> {
+        let mut cached = self.cached.lock().await;
+        if let Some((token, _)) = cached.as_ref()
+            && token != rejected
+        {
+            return Ok(token.clone());
+        }
+        self.fetch(&mut cached).await
+    }
+
+    async fn fetch(&self, cached: &mut Option<(String, Instant)>) -> Result<String, AuthFailed> {
+        *cached = None;
+        let token = self.runner.run(&self.command, COMMAND_TIMEOUT).await?;
+        if token.is_empty() || header_value(&token).is_none() {
+            let reason =
+                if token.is_empty() { "it printed no token" } else { "it printed something that can't be a header" };
+            return Err(AuthFailed { command: self.command.clone(), reason: reason.to_string() });
+        }
+        *cached = Some((token.clone(), Instant::now()));
+        Ok(token)
+    }
+}
+
+impl std::fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthToken").field("url", &self.url).field("command", &self.command).field("ttl", &self.ttl).finish()
+    }
+}
+
+/// Prints "token-1", "token-2", … after `delay`, counting runs.
+#[cfg(test)]
+#[derive(Default)]
+pub struct FakeRunner {
+    pub runs: std::sync::atomic::AtomicUsize,
+    pub delay: Duration,
+    pub fail: bool,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl CommandRunner for FakeRunner {
+    async fn run(&self, command: &str, _timeout: Duration) -> Result<String, AuthFailed> {
+        let run = self.runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        tokio::time::sleep(self.delay).await;
+        if self.fail {
+            return Err(AuthFailed { command: command.to_string(), reason: "exit status: 1: not logged in".into() });
+        }
+        Ok(format!("token-{}", run))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    fn token(runner: &Arc<FakeRunner>, ttl: Duration) -> Arc<AuthToken> {
+        Arc::new(AuthToken::new("http://node", "get-token staging", ttl, runner.clone()))
+    }
+
+    #[tokio::test]
+    async fn tokens_are_cached_until_they_expire() {
+        let runner = Arc::new(FakeRunner::default());
+        let auth = token(&runner, Duration::from_millis(50));
+        assert_eq!(auth.token().await.unwrap(), "token-1");
+        assert_eq!(auth.token().await.unwrap(), "token-1");
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(auth.token().await.unwrap(), "token-2");
+        assert_eq!(runner.runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_share_one_run() {
+        let runner = Arc::new(FakeRunner { delay: Duration::from_millis(30), ..Default::default() });
+        let auth = token(&runner, DEFAULT_TTL);
+        let tokens: Vec<String> = futures_util::future::join_all((0..5).map(|_| {
+            let auth = auth.clone();
+            async move { auth.token().await.unwrap() }
+        }))
+        .await;
+        assert!(tokens.iter().all(|t| t == "token-1"), "{:?}", tokens);
+
+        // Five requests rejected with the same token: one refresh between them
+        let tokens: Vec<String> = futures_util::future::join_all((0..5).map(|_| {
+            let auth = auth.clone();
+            async move { auth.refresh("token-1").await.unwrap() }
+        }))
+        .await;
+        assert!(tokens.iter().all(|t| t == "token-2"), "{:?}", tokens);
+        assert_eq!(runner.runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn failures_are_not_cached() {
+        let runner = Arc::new(FakeRunner { fail: true, ..Default::default() });
+        let auth = token(&runner, DEFAULT_TTL);
+        let error = auth.token().await.unwrap_err();
+        assert_eq!(error.to_string(), "auth command `get-token staging` failed: exit status: 1: not logged in");
+        assert!(auth.token().await.is_err());
+        assert_eq!(runner.runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn the_shell_runner_reports_stderr_and_timeouts() {
+        assert_eq!(ShellRunner.run("echo ' abc '", COMMAND_TIMEOUT).await.unwrap(), "abc");
+        let error = ShellRunner.run("echo 'session expired' >&2; exit 3", COMMAND_TIMEOUT).await.unwrap_err();
+        assert_eq!(error.reason, "exit status: 3: session expired");
+        let error = ShellRunner.run("sleep 5", Duration::from_millis(100)).await.unwrap_err();
+        assert_eq!(error.reason, "no token within 100ms");
+        // More output than a pipe buffers, on both streams
+        let chatty = "head -c 200000 /dev/zero | tr '\\0' e >&2; head -c 200000 /dev/zero | tr '\\0' t";
+        assert_eq!(ShellRunner.run(chatty, Duration::from_secs(5)).await.unwrap().len(), 200_000);
+    }
+
+    #[test]
+    fn credentials_come_from_the_environment_and_never_show_the_secret() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(Credentials::from_env(env(&[])).unwrap(), None);
+        let basic = Credentials::from_env(env(&[(BASIC_ENV, "alice:s3cr:et")])).unwrap().unwrap();
+        assert_eq!(basic, Credentials::Basic { user: "alice".into(), password: "s3cr:et".into() });
+        assert_eq!(basic.header().unwrap(), "Basic YWxpY2U6czNjcjpldA==");
+        // The token wins; an empty one counts as unset
+        let both = env(&[(BEARER_ENV, "abc123"), (BASIC_ENV, "alice:pw")]);
+        assert_eq!(Credentials::from_env(both).unwrap(), Some(Credentials::Bearer("abc123".into())));
+        let empty = env(&[(BEARER_ENV, ""), (BASIC_ENV, "alice:pw")]);
+        assert!(matches!(Credentials::from_env(empty).unwrap(), Some(Credentials::Basic { .. })));
+        assert!(Credentials::from_env(env(&[(BASIC_ENV, "alice")])).is_err());
+
+        let bearer = Credentials::Bearer("abc123".into());
+        assert_eq!(bearer.header().unwrap(), "Bearer abc123");
+        for shown in [bearer.to_string(), format!("{:?}", bearer), basic.to_string(), format!("{:?}", basic)] {
+            assert!(!shown.contains("abc123") && !shown.contains("s3cr"), "{}", shown);
+        }
+        assert_eq!(basic.to_string(), "basic auth as alice");
+    }
+
+    #[test]
+    fn tokens_become_bearer_headers_unless_they_name_a_scheme() {
+        assert_eq!(header_value("abc").unwrap(), "Bearer abc");
+        assert_eq!(header_value("Basic dXNlcjpwdw==").unwrap(), "Basic dXNlcjpwdw==");
+        assert!(header_value("a\nb").is_none());
+    }
+}
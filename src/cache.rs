@@ -0,0 +1,168 @@
+// src/cache.rs
+
+use std::collections::{HashMap, VecDeque};
+
+use serde_json::Value;
+
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse};
+
+/// Methods whose result never changes once it exists, whatever the params.
+const BY_HASH_METHODS: &[&str] = &[
+    "eth_getBlockByHash",
+    "eth_getTransactionByHash",
+    "eth_getTransactionReceipt",
+    "eth_getBlockTransactionCountByHash",
+    "eth_getTransactionByBlockHashAndIndex",
+];
+
+/// Methods that also answer for pending transactions, with a null
+/// `blockHash` until they are mined.
+const MINED_ONLY_METHODS: &[&str] = &["eth_getTransactionByHash", "eth_getTransactionReceipt"];
+
+/// Methods that are immutable only when the block is an explicit number.
+const BY_NUMBER_METHODS: &[&str] = &[
+    "eth_getBlockByNumber",
+    "eth_getBlockTransactionCountByNumber",
+    "eth_getTransactionByBlockNumberAndIndex",
+];
+
+/// Whether `method` with `params` always returns the same result, so its
+/// response can be served from cache. Tagged blocks (latest, pending, …)
+/// never qualify.
+pub fn is_immutable(method: &str, params: &Value) -> bool {
+    if BY_HASH_METHODS.contains(&method) {
+        return true;
+    }
+    if BY_NUMBER_METHODS.contains(&method) {
+        return params
+            .get(0)
+            .and_then(Value::as_str)
+            .is_some_and(|b| b.starts_with("0x") && u64::from_str_radix(&b[2..], 16).is_ok());
+    }
+    false
+}
+
+/// Bounded cache of responses to immutable requests, keyed by endpoint,
+/// method, and params (the request id is ignored).
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    entries: HashMap<String, JsonRpcResponse>,
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(512)
+    }
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        ResponseCache { entries: HashMap::new(), order: VecDeque::new(), capacity: capacity.max(1) }
+    }
+
+    fn key(url: &str, req: &JsonRpcRequest) -> String {
+        format!("{} {} {}", url, req.method, req.params)
+    }
+
+    /// Cached response for `req`, re-tagged with the request's id.
+    pub fn get(&self, url: &str, req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+        let mut res = self.entries.get(&Self::key(url, req))?.clone();
+        res.id = req.id;
+        Some(res)
+    }
+
+    pub fn contains(&self, url: &str, req: &JsonRpcRequest) -> bool {
+        self.entries.contains_key(&Self::key(url, req))
+    }
+
+    /// Stores a successful, non-null response to an immutable request;
+    /// anything else is ignored, as is a transaction or receipt that isn't
+    /// in a block yet. Evicts the oldest entry when full.
+    pub fn insert(&mut self, url: &str, req: &JsonRpcRequest, res: &JsonRpcResponse) {
+        let has_result = res.error.is_none() && res.result.as_ref().is_some_and(|r| !r.is_null());
+        if !has_result || !is_immutable(&req.method, &req.params) {
+            return;
+        }
+        let mined = || res.result.as_ref().and_then(|r| r.get("blockHash")).is_some_and(Value::is_string);
+        if MINED_ONLY_METHODS.contains(&req.method.as_str()) && !mined() {
+            return;
+        }
+        let key = Self::key(url, req);
+        if self.entries.insert(key.clone(), res.clone()).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > self.capacity {
+            if let Some(old) = self.order.pop_front() {
+                self.entries.remove(&old);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ok(result: Value) -> JsonRpcResponse {
+        JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(result), error: None, id: 1 }
+    }
+
+    #[test]
+    fn tags_are_never_immutable() {
+        assert!(is_immutable("eth_getBlockByNumber", &json!(["0x10", false])));
+        for tag in ["latest", "pending", "safe", "finalized", "earliest"] {
+            assert!(!is_immutable("eth_getBlockByNumber", &json!([tag, false])));
+        }
+        assert!(is_immutable("eth_getTransactionReceipt", &json!(["0xabc"])));
+        assert!(!is_immutable("eth_blockNumber", &json!([])));
+    }
+
+    #[test]
+    fn caches_by_params_not_id_and_skips_nulls() {
+        let mut cache = ResponseCache::new(2);
+        let req = JsonRpcRequest::new("eth_getBlockByNumber", json!(["0x10", false]), 1);
+        cache.insert("u", &req, &ok(json!({"number": "0x10"})));
+        let again = JsonRpcRequest::new("eth_getBlockByNumber", json!(["0x10", false]), 9);
+        assert_eq!(cache.get("u", &again).unwrap().id, 9);
+        assert!(cache.get("other", &again).is_none());
+
+        // Block not produced yet
+        let future = JsonRpcRequest::new("eth_getBlockByNumber", json!(["0x99", false]), 2);
+        cache.insert("u", &future, &ok(Value::Null));
+        assert!(!cache.contains("u", &future));
+    }
+
+    #[test]
+    fn pending_transactions_are_not_cached() {
+        let mut cache = ResponseCache::default();
+        for method in ["eth_getTransactionByHash", "eth_getTransactionReceipt"] {
+            let req = JsonRpcRequest::new(method, json!(["0xabc"]), 1);
+            cache.insert("u", &req, &ok(json!({"hash": "0xabc", "blockHash": null, "blockNumber": null})));
+            assert!(!cache.contains("u", &req), "{}", method);
+            cache.insert("u", &req, &ok(json!({"hash": "0xabc", "blockHash": "0xb1", "blockNumber": "0x10"})));
+            assert!(cache.contains("u", &req), "{}", method);
+        }
+    }
+
+    #[test]
+    fn evicts_oldest_when_full() {
+        let mut cache = ResponseCache::new(2);
+        for n in 1..=3 {
+            let req = JsonRpcRequest::new("eth_getBlockByNumber", json!([format!("0x{:x}", n)]), n);
+            cache.insert("u", &req, &ok(json!(n)));
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains("u", &JsonRpcRequest::new("eth_getBlockByNumber", json!(["0x1"]), 0)));
+    }
+}
@@ -0,0 +1,204 @@
+// src/capability.rs
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse, Transport};
+
+/// Whether an endpoint serves a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Availability {
+    Available,
+    Unavailable,
+    /// The answer didn't settle it (rate limit, malformed request, …).
+    Unknown,
+}
+
+impl Availability {
+    /// Short marker shown next to the method name.
+    pub fn badge(self) -> &'static str {
+        match self {
+            Availability::Available => "✓",
+            Availability::Unavailable => "✗ unavailable",
+            Availability::Unknown => "?",
+        }
+    }
+}
+
+/// Server-error messages that mean the method itself is switched off.
+const DISABLED_HINTS: &[&str] = &["not supported", "unsupported", "disabled", "not available", "does not exist", "not found"];
+
+/// Classifies a JSON-RPC error by code (and, for server errors, message).
+///
+/// | code              | meaning                    | availability |
+/// |-------------------|----------------------------|--------------|
+/// | -32601            | method not found           | Unavailable  |
+/// | -32602            | invalid params             | Available    |
+/// | -32603            | internal error             | Available    |
+/// | -32600, -32700    | invalid request / parse    | Unknown      |
+/// | -32005            | limit exceeded             | Unknown      |
+/// | -32000..=-32099   | server error naming the method as disabled | Unavailable |
+/// | -32000..=-32099   | any other server error     | Available    |
+/// | anything else     |                            | Unknown      |
+pub fn classify_error(code: i64, message: &str) -> Availability {
+    let message = message.to_lowercase();
+    match code {
+        -32601 => Availability::Unavailable,
+        -32602 | -32603 => Availability::Available,
+        -32005 => Availability::Unknown,
+        -32099..=-32000 => {
+            if message.contains("method") && DISABLED_HINTS.iter().any(|h| message.contains(h)) {
+                Availability::Unavailable
+            } else {
+                Availability::Available
+            }
+        }
+        _ => Availability::Unknown,
+    }
+}
+
+/// Classifies any response: a result means the method is there.
+pub fn classify(response: &JsonRpcResponse) -> Availability {
+    match &response.error {
+        None => Availability::Available,
+        Some(err) => classify_error(
+            err.get("code").and_then(Value::as_i64).unwrap_or(0),
+            err.get("message").and_then(Value::as_str).unwrap_or(""),
+        ),
+    }
+}
+
+/// Namespaces (or method prefixes) that only read node state.
+const READ_ONLY_PREFIXES: &[&str] = &["eth_", "net_", "web3_", "txpool_", "trace_", "debug_trace", "debug_get", "ots_", "rpc_"];
+
+/// Methods under those prefixes that act even without params: sending,
+/// signing, mining work, and installing filters or subscriptions.
+const WRITING_PREFIXES: &[&str] =
+    &["eth_send", "eth_sign", "eth_submit", "eth_new", "eth_uninstall", "eth_subscribe", "eth_unsubscribe"];
+
+/// Whether probing `method` is safe. Methods that need no params run when
+/// probed, so only read-only namespaces are: never `anvil_mine`,
+/// `evm_mine`, `miner_start` or the like.
+pub fn probe_allowed(method: &str) -> bool {
+    READ_ONLY_PREFIXES.iter().any(|p| method.starts_with(p)) && !WRITING_PREFIXES.iter().any(|p| method.starts_with(p))
+}
+
+/// A harmless probe for `method`: no params at all, so calls that need
+/// params fail validation (-32602) instead of doing anything. Only for
+/// methods [`probe_allowed`] lets through.
+pub fn probe_request(method: &str, id: u64) -> JsonRpcRequest {
+    JsonRpcRequest::new(method, Value::Array(Vec::new()), id)
+}
+
+/// Sends a probe for `method` and classifies the answer. Transport failures
+/// are `Unknown`.
+pub async fn probe(transport: &dyn Transport, url: &str, method: &str, id: u64) -> Availability {
+    match transport.send(url, probe_request(method, id)).await {
+        Ok(response) => classify(&response),
+        Err(_) => Availability::Unknown,
+    }
+}
+
+/// Per-endpoint method availability learned during this session.
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityCache {
+    entries: HashMap<(String, String), Availability>,
+}
+
+impl CapabilityCache {
+    pub fn get(&self, url: &str, method: &str) -> Option<Availability> {
+        self.entries.get(&(url.to_string(), method.to_string())).copied()
+    }
+
+    /// Records `availability` unless it is `Unknown`, which would only
+    /// mask a later definitive answer.
+    pub fn set(&mut self, url: &str, method: &str, availability: Availability) {
+        if availability != Availability::Unknown {
+            self.entries.insert((url.to_string(), method.to_string()), availability);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    #[test]
+    fn classification_table() {
+        let cases: &[(i64, &str, Availability)] = &[
+            (-32601, "the method eth_feeHistory does not exist/is not available", Availability::Unavailable),
+            (-32602, "missing value for required argument 0", Availability::Available),
+            (-32603, "internal error", Availability::Available),
+            (-32600, "invalid request", Availability::Unknown),
+            (-32700, "parse error", Availability::Unknown),
+            (-32005, "daily request count exceeded", Availability::Unknown),
+            (-32000, "method eth_getProof is disabled", Availability::Unavailable),
+            (-32000, "Unsupported method: eth_getProof", Availability::Unavailable),
+            (-32000, "header not found", Availability::Available),
+            (-32000, "execution reverted", Availability::Available),
+            (3, "execution reverted", Availability::Unknown),
+        ];
+        for (code, message, expected) in cases {
+            assert_eq!(classify_error(*code, message), *expected, "{} {}", code, message);
+        }
+    }
+
+    #[test]
+    fn only_read_only_methods_are_probed() {
+        for method in ["eth_getProof", "eth_feeHistory", "net_peerCount", "txpool_status", "debug_traceTransaction"] {
+            assert!(probe_allowed(method), "{}", method);
+        }
+        for method in [
+            "anvil_mine",
+            "evm_mine",
+            "miner_start",
+            "admin_stopRPC",
+            "debug_setHead",
+            "eth_sendTransaction",
+            "eth_signTypedData_v4",
+            "eth_newBlockFilter",
+        ] {
+            assert!(!probe_allowed(method), "{}", method);
+        }
+    }
+
+    #[test]
+    fn results_are_available() {
+        let ok = JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!("0x1")), error: None, id: 1 };
+        assert_eq!(classify(&ok), Availability::Available);
+        let missing = JsonRpcResponse {
+            jsonrpc: "2.0".into(),
+            result: None,
+            error: Some(json!({"code": -32601, "message": "Method not found"})),
+            id: 1,
+        };
+        assert_eq!(classify(&missing), Availability::Unavailable);
+    }
+
+    #[test]
+    fn cache_is_per_endpoint_and_ignores_unknown() {
+        let mut cache = CapabilityCache::default();
+        cache.set("http://a", "eth_getProof", Availability::Unavailable);
+        cache.set("http://a", "eth_feeHistory", Availability::Unknown);
+        assert_eq!(cache.get("http://a", "eth_getProof"), Some(Availability::Unavailable));
+        assert_eq!(cache.get("http://b", "eth_getProof"), None);
+        assert_eq!(cache.get("http://a", "eth_feeHistory"), None);
+    }
+
+    struct Failing;
+
+    #[async_trait]
+    impl Transport for Failing {
+        async fn send(&self, _url: &str, _req: JsonRpcRequest) -> anyhow::Result<JsonRpcResponse> {
+            anyhow::bail!("connection refused")
+        }
+    }
+
+    #[tokio::test]
+    async fn transport_failure_is_unknown() {
+        assert_eq!(probe(&Failing, "http://a", "eth_chainId", 1).await, Availability::Unknown);
+    }
+}
@@ -0,0 +1,204 @@
+// src/chains.rs
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Native currency of a chain, as in ethereum-lists/chains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NativeCurrency {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u32,
+}
+
+impl Default for NativeCurrency {
+    fn default() -> Self {
+        NativeCurrency { name: "Ether".into(), symbol: "ETH".into(), decimals: 18 }
+    }
+}
+
+/// A block explorer entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Explorer {
+    pub name: String,
+    pub url: String,
+}
+
+/// Chain metadata in the ethereum-lists/chains schema. Only the fields eli
+/// uses are read; everything else in the file is ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainInfo {
+    pub name: String,
+    pub chain_id: u64,
+    #[serde(default)]
+    pub native_currency: NativeCurrency,
+    #[serde(default)]
+    pub explorers: Vec<Explorer>,
+}
+
+impl ChainInfo {
+    /// Base URL of the first explorer, without a trailing slash.
+    fn explorer(&self) -> Option<&str> {
+        self.explorers.first().map(|e| e.url.trim_end_matches('/'))
+    }
+
+    /// EIP-3091 link to a transaction.
+    pub fn explorer_tx_url(&self, hash: &str) -> Option<String> {
+        self.explorer().map(|base| format!("{}/tx/{}", base, hash))
+    }
+
+    /// EIP-3091 link to an address.
+    pub fn explorer_address_url(&self, address: &str) -> Option<String> {
+        self.explorer().map(|base| format!("{}/address/{}", base, address))
+    }
+
+    /// EIP-3091 link to a block by number or hash.
+    pub fn explorer_block_url(&self, block: &str) -> Option<String> {
+        self.explorer().map(|base| format!("{}/block/{}", base, block))
+    }
+}
+
+/// Chains known without any configuration: (id, name, symbol, explorer).
+const BUILTIN: &[(u64, &str, &str, &str)] = &[
+    (1, "Ethereum Mainnet", "ETH", "https://etherscan.io"),
+    (10, "OP Mainnet", "ETH", "https://optimistic.etherscan.io"),
+    (100, "Gnosis", "XDAI", "https://gnosisscan.io"),
+    (137, "Polygon Mainnet", "POL", "https://polygonscan.com"),
+    (8453, "Base", "ETH", "https://basescan.org"),
+    (17000, "Holesky", "ETH", "https://holesky.etherscan.io"),
+    (42161, "Arbitrum One", "ETH", "https://arbiscan.io"),
+    (11155111, "Sepolia", "ETH", "https://sepolia.etherscan.io"),
+];
+
+/// Chain metadata by chain id: the builtin table with user files merged over it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainTable {
+    chains: HashMap<u64, ChainInfo>,
+}
+
+impl Default for ChainTable {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl ChainTable {
+    pub fn builtin() -> Self {
+        let chains = BUILTIN
+            .iter()
+            .map(|(id, name, symbol, explorer)| {
+                let info = ChainInfo {
+                    name: name.to_string(),
+                    chain_id: *id,
+                    native_currency: NativeCurrency { symbol: symbol.to_string(), ..NativeCurrency::default() },
+                    explorers: vec![Explorer { name: "explorer".into(), url: explorer.to_string() }],
+                };
+                (*id, info)
+            })
+            .collect();
+        ChainTable { chains }
+    }
+
+    /// Adds `chains`, replacing any entry with the same chain id.
+    pub fn merge(&mut self, chains: Vec<ChainInfo>) {
+        for chain in chains {
+            self.chains.insert(chain.chain_id, chain);
+        }
+    }
+
+    pub fn get(&self, chain_id: u64) -> Option<&ChainInfo> {
+        self.chains.get(&chain_id)
+    }
+}
+
+/// Parses one ethereum-lists/chains file.
+pub fn parse_chain(text: &str) -> Result<ChainInfo> {
+    Ok(serde_json::from_str(text)?)
+}
+
+/// Reads every `*.json` file in `dir`, in file name order.
+///
+/// A missing directory is not an error. Files that fail to parse are
+/// skipped and reported in the second return value.
+pub fn load_dir(dir: &Path) -> Result<(Vec<ChainInfo>, Vec<String>)> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), Vec::new())),
+        Err(e) => return Err(e).with_context(|| format!("cannot read {}", dir.display())),
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut chains = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match std::fs::read_to_string(&path).map_err(anyhow::Error::from).and_then(|text| parse_chain(&text)) {
+            Ok(chain) => chains.push(chain),
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    Ok((chains, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAINNET: &str = include_str!("../tests/fixtures/chains/eip155-1.json");
+    const GNOSIS: &str = include_str!("../tests/fixtures/chains/eip155-100.json");
+
+    #[test]
+    fn parses_chainlist_files() {
+        let mainnet = parse_chain(MAINNET).unwrap();
+        assert_eq!(mainnet.chain_id, 1);
+        assert_eq!(mainnet.native_currency, NativeCurrency::default());
+        assert_eq!(mainnet.explorer_tx_url("0xab").as_deref(), Some("https://etherscan.io/tx/0xab"));
+
+        let gnosis = parse_chain(GNOSIS).unwrap();
+        assert_eq!(gnosis.name, "Gnosis");
+        assert_eq!(gnosis.native_currency.symbol, "XDAI");
+        assert_eq!(gnosis.explorers.len(), 2);
+    }
+
+    #[test]
+    fn minimal_file_without_currency_or_explorers() {
+        let chain = parse_chain(r#"{"name": "My Rollup", "chainId": 424242, "somethingNew": [1]}"#).unwrap();
+        assert_eq!(chain.native_currency.symbol, "ETH");
+        assert_eq!(chain.explorer_block_url("0x1"), None);
+    }
+
+    #[test]
+    fn user_files_override_builtin() {
+        let dir = std::env::temp_dir().join(format!("eli-chains-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("eip155-1.json"), MAINNET.replace("\"ETH\",", "\"TEST\",")).unwrap();
+        std::fs::write(dir.join("rollup.json"), r#"{"name": "My Rollup", "chainId": 424242}"#).unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let (chains, errors) = load_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("broken.json"));
+
+        let mut table = ChainTable::builtin();
+        assert_eq!(table.get(1).unwrap().native_currency.symbol, "ETH");
+        table.merge(chains);
+        assert_eq!(table.get(1).unwrap().native_currency.symbol, "TEST");
+        assert_eq!(table.get(424242).unwrap().name, "My Rollup");
+        assert_eq!(table.get(100).unwrap().native_currency.symbol, "XDAI");
+    }
+
+    #[test]
+    fn missing_dir_is_empty() {
+        let (chains, errors) = load_dir(Path::new("/nonexistent/eli/chains")).unwrap();
+        assert!(chains.is_empty() && errors.is_empty());
+    }
+}
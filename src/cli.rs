@@ -0,0 +1,350 @@
+// src/cli.rs
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::auth::Credentials;
+use crate::completions::{ListFormat, Shell, LIST_FLAGS, RECORD_FLAGS, SESSION_FLAGS};
+use crate::context::ContextFormat;
+use crate::endpoint::validate_url;
+use crate::fixture::ScrubRules;
+
+/// Command-line options.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Args {
+    /// Build and validate requests but never send them.
+    pub dry_run: bool,
+    /// Read-only session: browsing only, nothing is sent or saved.
+    pub spectator: bool,
+    /// Cap on a single response body, in MiB.
+    pub max_response_mib: Option<usize>,
+    /// Guided walkthrough against canned offline responses.
+    pub tutorial: bool,
+    /// Replace addresses with placeholders in exported fixtures.
+    pub redact_addresses: bool,
+    /// Session endpoint to start with instead of the default.
+    pub url: Option<String>,
+    /// Most history entries to keep.
+    pub history_limit: Option<usize>,
+    /// Seconds before a request with no answer fails.
+    pub timeout_secs: Option<u64>,
+    /// Times a request that fails in passing is sent again; 0 never retries.
+    pub retries: Option<u32>,
+    /// Authorization for the session endpoint, from `--bearer-token` or
+    /// `--basic-auth`; the environment is read when neither is given.
+    pub credentials: Option<Credentials>,
+    /// Method spec file to use instead of the builtin method list.
+    pub spec: Option<PathBuf>,
+    /// File the session's last value, method, endpoint and chain go to on exit.
+    pub emit_context: Option<PathBuf>,
+    pub emit_context_format: ContextFormat,
+    /// `eli fixture record …`: record a fixture instead of starting the UI.
+    pub record: Option<RecordArgs>,
+    /// `eli spec list`: print the active method list and exit.
+    pub spec_list: Option<ListFormat>,
+    /// `eli spec diff A B`: compare two method lists and exit.
+    pub spec_diff: Option<(String, String)>,
+    /// `eli gas`: print a gas price overview of the endpoint and exit.
+    pub gas: bool,
+    /// `eli completions <shell>`: print a completion script and exit.
+    pub completions: Option<Shell>,
+}
+
+/// Options of `eli fixture record`, a maintainer tool left out of the
+/// usual flags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordArgs {
+    pub method: String,
+    pub params: Value,
+    /// Directory the fixture and `index.json` go in.
+    pub out: PathBuf,
+    pub scrub: ScrubRules,
+}
+
+/// `arg` if `flags` lists it. The completion scripts are built from the
+/// same lists, so every flag the parser takes gets completed.
+fn listed<'a>(arg: &'a str, flags: &[&str], command: &str) -> Result<&'a str> {
+    if !flags.contains(&arg) {
+        bail!("unknown argument '{}'{}", arg, command);
+    }
+    Ok(arg)
+}
+
+/// Parses the arguments after the program name.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter().peekable();
+    if args.peek().map(String::as_str) == Some("fixture") {
+        args.next();
+        if args.next().as_deref() != Some("record") {
+            bail!("usage: eli fixture record --method M [--params JSON] --out DIR [--scrub f1,f2] [--scrub-hashes]");
+        }
+        return parse_record(args, parsed);
+    }
+    if args.peek().map(String::as_str) == Some("spec") {
+        args.next();
+        return match args.next().as_deref() {
+            Some("list") => parse_spec_list(args, parsed),
+            Some("diff") => parse_spec_diff(args, parsed),
+            _ => bail!("usage: eli spec list [--plain | --json] [--spec FILE]\n       eli spec diff A B [--spec FILE]"),
+        };
+    }
+    if args.peek().map(String::as_str) == Some("completions") {
+        args.next();
+        let Some(shell) = args.next() else { bail!("usage: eli completions bash|zsh|fish") };
+        parsed.completions = Some(Shell::parse(&shell)?);
+        if let Some(extra) = args.next() {
+            bail!("unknown argument '{}' for completions", extra);
+        }
+        return Ok(parsed);
+    }
+    // The session flags pick the endpoint and how to reach it
+    if args.peek().map(String::as_str) == Some("gas") {
+        args.next();
+        parsed.gas = true;
+    }
+    while let Some(arg) = args.next() {
+        match listed(&arg, SESSION_FLAGS, "")? {
+            "--dry-run" => parsed.dry_run = true,
+            "--spectator" => parsed.spectator = true,
+            "--tutorial" => parsed.tutorial = true,
+            "--redact-addresses" => parsed.redact_addresses = true,
+            "--url" => {
+                let Some(value) = args.next() else { bail!("--url expects an http(s) URL") };
+                parsed.url = Some(validate_url(&value)?);
+            }
+            "--max-response-mib" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<usize>() {
+                    Ok(mib) if mib > 0 => parsed.max_response_mib = Some(mib),
+                    _ => bail!("--max-response-mib expects a positive number of MiB, got '{}'", value),
+                }
+            }
+            "--history-limit" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<usize>() {
+                    Ok(limit) if limit > 0 => parsed.history_limit = Some(limit),
+                    _ => bail!("--history-limit expects a positive number of entries, got '{}'", value),
+                }
+            }
+            "--timeout" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<u64>() {
+                    Ok(secs) if secs > 0 => parsed.timeout_secs = Some(secs),
+                    _ => bail!("--timeout expects a positive number of seconds, got '{}'", value),
+                }
+            }
+            "--retries" => {
+                let value = args.next().unwrap_or_default();
+                match value.parse::<u32>() {
+                    Ok(retries) => parsed.retries = Some(retries),
+                    _ => bail!("--retries expects a number of retries, got '{}'", value),
+                }
+            }
+            "--bearer-token" => match args.next().filter(|t| !t.is_empty()) {
+                Some(token) => parsed.credentials = Some(Credentials::Bearer(token)),
+                None => bail!("--bearer-token expects a token"),
+            },
+            "--basic-auth" => match args.next().as_deref().and_then(Credentials::basic) {
+                Some(credentials) => parsed.credentials = Some(credentials),
+                None => bail!("--basic-auth expects user:password"),
+            },
+            "--spec" => {
+                let Some(path) = args.next() else { bail!("--spec expects an OpenRPC or JSON file") };
+                parsed.spec = Some(PathBuf::from(path));
+            }
+            "--emit-context" => {
+                let Some(path) = args.next() else { bail!("--emit-context expects a file") };
+                parsed.emit_context = Some(PathBuf::from(path));
+            }
+            "--emit-context-format" => parsed.emit_context_format = ContextFormat::parse(&args.next().unwrap_or_default())?,
+            other => bail!("unknown argument '{}'", other),
+        }
+    }
+    Ok(parsed)
+}
+
+/// The rest of `eli fixture record`; `--url` and `--timeout` work as usual.
+fn parse_record(mut args: impl Iterator<Item = String>, mut parsed: Args) -> Result<Args> {
+    let mut record = RecordArgs { params: Value::Array(Vec::new()), ..RecordArgs::default() };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().with_context(|| format!("{} expects a value", arg));
+        match listed(&arg, RECORD_FLAGS, " for fixture record")? {
+            "--method" => record.method = value()?,
+            "--params" => record.params = serde_json::from_str(&value()?).context("--params expects JSON")?,
+            "--out" => record.out = PathBuf::from(value()?),
+            "--scrub" => {
+                record.scrub.fields = value()?.split(',').map(str::trim).filter(|f| !f.is_empty()).map(String::from).collect()
+            }
+            "--scrub-hashes" => record.scrub.hashes = true,
+            "--url" => parsed.url = Some(validate_url(&value()?)?),
+            "--timeout" => match value()?.parse::<u64>() {
+                Ok(secs) if secs > 0 => parsed.timeout_secs = Some(secs),
+                _ => bail!("--timeout expects a positive number of seconds"),
+            },
+            other => bail!("unknown argument '{}' for fixture record", other),
+        }
+    }
+    if record.method.is_empty() || record.out.as_os_str().is_empty() {
+        bail!("fixture record needs --method and --out");
+    }
+    parsed.record = Some(record);
+    Ok(parsed)
+}
+
+/// The rest of `eli spec list`. `--spec` works as usual, so the listing
+/// is the method list a session would have.
+fn parse_spec_list(mut args: impl Iterator<Item = String>, mut parsed: Args) -> Result<Args> {
+    let mut format = ListFormat::Plain;
+    while let Some(arg) = args.next() {
+        match listed(&arg, LIST_FLAGS, " for spec list")? {
+            "--plain" => format = ListFormat::Plain,
+            "--json" => format = ListFormat::Json,
+            "--spec" => {
+                let Some(path) = args.next() else { bail!("--spec expects an OpenRPC or JSON file") };
+                parsed.spec = Some(PathBuf::from(path));
+            }
+            other => bail!("unknown argument '{}' for spec list", other),
+        }
+    }
+    parsed.spec_list = Some(format);
+    Ok(parsed)
+}
+
+/// The rest of `eli spec diff`: two sources (builtin, active, a file, or
+/// builtin/active+extension) and `--spec`, which "active" follows.
+fn parse_spec_diff(mut args: impl Iterator<Item = String>, mut parsed: Args) -> Result<Args> {
+    let mut sources = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--spec" => {
+                let Some(path) = args.next() else { bail!("--spec expects an OpenRPC or JSON file") };
+                parsed.spec = Some(PathBuf::from(path));
+            }
+            other if other.starts_with("--") => bail!("unknown argument '{}' for spec diff", other),
+            source => sources.push(source.to_string()),
+        }
+    }
+    let [a, b] = <[String; 2]>::try_from(sources)
+        .map_err(|_| anyhow::anyhow!("spec diff compares two sources: builtin, active, a file, or builtin+optimism"))?;
+    parsed.spec_diff = Some((a, b));
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Args> {
+        parse(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parses_flags_and_rejects_unknown() {
+        assert_eq!(args(&[]).unwrap(), Args::default());
+        assert!(args(&["--dry-run"]).unwrap().dry_run);
+        assert!(args(&["--spectator"]).unwrap().spectator);
+        assert!(args(&["--tutorial"]).unwrap().tutorial);
+        assert!(args(&["--redact-addresses"]).unwrap().redact_addresses);
+        assert!(args(&["--dry"]).unwrap_err().to_string().contains("--dry"));
+        assert_eq!(args(&["--max-response-mib", "256"]).unwrap().max_response_mib, Some(256));
+        assert!(args(&["--max-response-mib"]).is_err());
+        assert!(args(&["--max-response-mib", "0"]).is_err());
+        assert_eq!(args(&["--url", "https://rpc.example.org"]).unwrap().url.as_deref(), Some("https://rpc.example.org"));
+        assert!(args(&["--url"]).is_err());
+        assert!(args(&["--url", "localhost:8545"]).is_err());
+        assert_eq!(args(&["--history-limit", "50"]).unwrap().history_limit, Some(50));
+        assert!(args(&["--history-limit", "0"]).is_err());
+        assert!(args(&["--history-limit"]).is_err());
+        assert_eq!(args(&["--timeout", "5"]).unwrap().timeout_secs, Some(5));
+        assert!(args(&["--timeout", "0"]).is_err());
+        assert!(args(&["--timeout", "1.5"]).is_err());
+        assert_eq!(args(&["--retries", "0"]).unwrap().retries, Some(0));
+        assert!(args(&["--retries", "-1"]).is_err());
+        assert_eq!(args(&["--bearer-token", "abc"]).unwrap().credentials, Some(Credentials::Bearer("abc".into())));
+        assert!(matches!(args(&["--basic-auth", "alice:pw"]).unwrap().credentials, Some(Credentials::Basic { .. })));
+        assert!(args(&["--basic-auth", "alice"]).is_err());
+        assert!(args(&["--bearer-token"]).is_err());
+    }
+
+    #[test]
+    fn parses_fixture_record() {
+        let parsed = args(&[
+            "fixture", "record", "--method", "eth_getBlockByNumber", "--params", r#"["latest", false]"#,
+            "--out", "tests/fixtures/", "--scrub", "timestamp, nonce", "--scrub-hashes", "--url", "http://127.0.0.1:8545",
+        ])
+        .unwrap();
+        let record = parsed.record.unwrap();
+        assert_eq!(record.method, "eth_getBlockByNumber");
+        assert_eq!(record.params, serde_json::json!(["latest", false]));
+        assert_eq!(record.out, PathBuf::from("tests/fixtures/"));
+        assert_eq!(record.scrub, ScrubRules { fields: vec!["timestamp".into(), "nonce".into()], hashes: true });
+        assert_eq!(parsed.url.as_deref(), Some("http://127.0.0.1:8545"));
+
+        // Defaults: no params, the default scrub list
+        let record = args(&["fixture", "record", "--method", "eth_chainId", "--out", "x"]).unwrap().record.unwrap();
+        assert_eq!((record.params, record.scrub), (serde_json::json!([]), ScrubRules::default()));
+
+        assert!(args(&["fixture", "record", "--out", "x"]).is_err());
+        assert!(args(&["fixture", "record", "--method", "m", "--out", "x", "--params", "[latest]"]).is_err());
+        assert!(args(&["fixture"]).is_err());
+        assert!(args(&["fixture", "record", "--dry-run"]).is_err());
+    }
+
+    #[test]
+    fn parses_spec_file() {
+        assert_eq!(args(&["--spec", "reth.json"]).unwrap().spec, Some(PathBuf::from("reth.json")));
+        assert!(args(&["--spec"]).is_err());
+    }
+
+    #[test]
+    fn parses_emit_context() {
+        let parsed = args(&["--emit-context", "/tmp/eli.ctx", "--emit-context-format", "json"]).unwrap();
+        assert_eq!(parsed.emit_context, Some(PathBuf::from("/tmp/eli.ctx")));
+        assert_eq!(parsed.emit_context_format, ContextFormat::Json);
+        assert_eq!(args(&["--emit-context", "x"]).unwrap().emit_context_format, ContextFormat::Shell);
+        assert!(args(&["--emit-context"]).is_err());
+        assert!(args(&["--emit-context-format", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn parses_spec_list_and_completions() {
+        let parsed = args(&["spec", "list", "--json", "--spec", "reth.json"]).unwrap();
+        assert_eq!((parsed.spec_list, parsed.spec), (Some(ListFormat::Json), Some(PathBuf::from("reth.json"))));
+        assert_eq!(args(&["spec", "list"]).unwrap().spec_list, Some(ListFormat::Plain));
+        assert!(args(&["spec"]).is_err());
+        assert!(args(&["spec", "list", "--dry-run"]).is_err());
+        let diff = args(&["spec", "diff", "builtin", "node.json", "--spec", "reth.json"]).unwrap();
+        assert_eq!(diff.spec_diff, Some(("builtin".into(), "node.json".into())));
+        assert_eq!(diff.spec, Some(PathBuf::from("reth.json")));
+        assert!(args(&["spec", "diff", "builtin"]).is_err());
+        assert!(args(&["spec", "diff", "a", "b", "c"]).is_err());
+        assert!(args(&["spec", "diff", "a", "b", "--json"]).is_err());
+
+        assert_eq!(args(&["completions", "zsh"]).unwrap().completions, Some(Shell::Zsh));
+        assert!(args(&["completions"]).is_err());
+        assert!(args(&["completions", "bash", "extra"]).is_err());
+        let gas = args(&["gas", "--url", "https://rpc.example.org", "--retries", "1"]).unwrap();
+        assert!(gas.gas && gas.url.as_deref() == Some("https://rpc.example.org"));
+        assert!(args(&["gas", "--fast"]).is_err());
+
+        // The scripts offer exactly the flags parsed here: anything not in
+        // the lists is refused, and everything in them is handled
+        let handled = |list: &[&str]| {
+            let error = args(list).err().map(|e| e.to_string()).unwrap_or_default();
+            assert!(!error.starts_with("unknown argument"), "{:?}: {}", list, error);
+        };
+        for flag in SESSION_FLAGS {
+            handled(&[flag]);
+            handled(&["gas", flag]);
+        }
+        for flag in RECORD_FLAGS {
+            handled(&["fixture", "record", flag]);
+        }
+        for flag in LIST_FLAGS {
+            handled(&["spec", "list", flag]);
+        }
+    }
+}
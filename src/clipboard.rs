@@ -0,0 +1,95 @@
+// src/clipboard.rs
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::app::HistoryEntry;
+
+/// What of a history entry gets copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Payload {
+    Request,
+    Response,
+}
+
+impl Payload {
+    pub fn label(self) -> &'static str {
+        match self {
+            Payload::Request => "request",
+            Payload::Response => "response",
+        }
+    }
+}
+
+/// The text copied for `payload` of `entry`: the request exactly as it goes
+/// over the wire, or the response's result (its error, if it failed)
+/// pretty-printed.
+pub fn payload_text(entry: &HistoryEntry, payload: Payload) -> String {
+    match payload {
+        Payload::Request => serde_json::to_string(&entry.request).unwrap_or_default(),
+        Payload::Response => {
+            let response = &entry.response;
+            let value = response.result.as_ref().or(response.error.as_ref()).unwrap_or(&Value::Null);
+            serde_json::to_string_pretty(value).unwrap_or_default()
+        }
+    }
+}
+
+/// Somewhere copied text goes.
+pub trait Clipboard: Send {
+    fn set_text(&mut self, text: String) -> Result<()>;
+}
+
+/// The system clipboard, opened on first use. On X11 and Wayland the text
+/// is served by the open handle, so it is kept for the whole session.
+#[derive(Default)]
+pub struct SystemClipboard {
+    handle: Option<arboard::Clipboard>,
+}
+
+impl Clipboard for SystemClipboard {
+    fn set_text(&mut self, text: String) -> Result<()> {
+        let handle = match &mut self.handle {
+            Some(handle) => handle,
+            None => self.handle.insert(arboard::Clipboard::new().context("no clipboard available")?),
+        };
+        handle.set_text(text).context("cannot write to the clipboard")
+    }
+}
+
+/// Keeps whatever was copied last, for tests.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct MemoryClipboard(pub std::sync::Arc<std::sync::Mutex<Option<String>>>);
+
+#[cfg(test)]
+impl Clipboard for MemoryClipboard {
+    fn set_text(&mut self, text: String) -> Result<()> {
+        *self.0.lock().unwrap() = Some(text);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{JsonRpcRequest, JsonRpcResponse};
+    use serde_json::json;
+
+    #[test]
+    fn requests_are_copied_as_sent_and_results_pretty() {
+        let mut entry = HistoryEntry::new(
+            JsonRpcRequest::new("eth_getBalance", json!(["0xabc", "latest"]), 3),
+            JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!({"a": [1]})), error: None, id: 3 },
+        );
+        assert_eq!(
+            payload_text(&entry, Payload::Request),
+            r#"{"jsonrpc":"2.0","method":"eth_getBalance","params":["0xabc","latest"],"id":3}"#
+        );
+        assert_eq!(payload_text(&entry, Payload::Response), "{\n  \"a\": [\n    1\n  ]\n}");
+
+        entry.response.result = None;
+        entry.response.error = Some(json!({"code": -32602, "message": "invalid params"}));
+        assert!(payload_text(&entry, Payload::Response).contains("\"code\": -32602"));
+    }
+}
@@ -0,0 +1,115 @@
+// src/clock.rs
+
+use std::time::{Duration, Instant};
+
+/// Silence between two main-loop turns past which the machine is taken to
+/// have slept. A turn takes ~100 ms, so only a suspend (or a stopped
+/// process) gets anywhere near it.
+pub const RESUME_GAP: Duration = Duration::from_secs(30);
+
+/// Where the app reads the time, so tests can move it.
+pub trait Clock: Send + Sync {
+    /// Monotonic time, for intervals and expiry.
+    fn now(&self) -> Instant;
+    /// Wall-clock time in unix seconds, for ages shown to the user.
+    fn unix_now(&self) -> u64;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn unix_now(&self) -> u64 {
+        crate::head::unix_now()
+    }
+}
+
+/// Notices the main loop waking from a suspend.
+///
+/// On Linux and macOS the monotonic clock stops while the machine sleeps,
+/// so after a resume only the wall clock has jumped; elsewhere both have.
+/// Either one moving by [`RESUME_GAP`] between turns counts. A wall clock
+/// set backwards is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct ResumeDetector {
+    last: Option<(Instant, u64)>,
+}
+
+impl ResumeDetector {
+    /// Records a turn at `now`; returns how long the loop was away if that
+    /// was a resume.
+    pub fn check(&mut self, now: Instant, unix_now: u64) -> Option<Duration> {
+        let (last, last_unix) = self.last.replace((now, unix_now))?;
+        let gap = now.saturating_duration_since(last).max(Duration::from_secs(unix_now.saturating_sub(last_unix)));
+        (gap >= RESUME_GAP).then_some(gap)
+    }
+}
+
+/// A clock that only moves when told to, for tests.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct ManualClock(std::sync::Mutex<(Instant, u64)>);
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(unix_now: u64) -> Self {
+        ManualClock(std::sync::Mutex::new((Instant::now(), unix_now)))
+    }
+
+    /// Both clocks move by `by`.
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.0.lock().unwrap();
+        *now = (now.0 + by, now.1 + by.as_secs());
+    }
+
+    /// Only the wall clock moves, as across a suspend on Linux.
+    pub fn sleep(&self, by: Duration) {
+        self.0.lock().unwrap().1 += by.as_secs();
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.0.lock().unwrap().0
+    }
+
+    fn unix_now(&self) -> u64 {
+        self.0.lock().unwrap().1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_jump_of_either_clock_is_a_resume() {
+        let clock = ManualClock::new(1_700_000_000);
+        let mut detector = ResumeDetector::default();
+        let mut turn = || detector.check(clock.now(), clock.unix_now());
+        assert_eq!(turn(), None);
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(turn(), None);
+
+        // Monotonic time stood still, wall time didn't
+        clock.sleep(Duration::from_secs(7200));
+        assert_eq!(turn(), Some(Duration::from_secs(7200)));
+        clock.advance(Duration::from_secs(45));
+        assert_eq!(turn(), Some(Duration::from_secs(45)));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(turn(), None);
+    }
+
+    #[test]
+    fn a_wall_clock_set_back_is_not_a_resume() {
+        let mut detector = ResumeDetector::default();
+        let start = Instant::now();
+        detector.check(start, 1_700_000_000);
+        assert_eq!(detector.check(start + Duration::from_millis(100), 1_600_000_000), None);
+    }
+}
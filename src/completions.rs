@@ -0,0 +1,199 @@
+// src/completions.rs
+
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+
+use crate::spec::MethodSpec;
+
+/// How `eli spec list` prints the method list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListFormat {
+    /// One method name per line, for completion scripts.
+    #[default]
+    Plain,
+    /// Methods with their params and param types.
+    Json,
+}
+
+/// The method list in `format`, in spec order.
+pub fn list(specs: &[MethodSpec], format: ListFormat) -> String {
+    match format {
+        ListFormat::Plain => specs.iter().map(|s| format!("{}\n", s.name)).collect(),
+        ListFormat::Json => {
+            let methods: Vec<Value> = specs
+                .iter()
+                .map(|s| {
+                    let params: Vec<Value> = s
+                        .params
+                        .iter()
+                        .enumerate()
+                        .map(|(i, name)| json!({"name": name, "type": s.kind(i).label(), "help": s.help(i)}))
+                        .collect();
+                    json!({"name": s.name, "params": params})
+                })
+                .collect();
+            serde_json::to_string_pretty(&methods).unwrap_or_default() + "\n"
+        }
+    }
+}
+
+/// Shells `eli completions` writes a script for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub fn parse(text: &str) -> Result<Self> {
+        match text {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            other => bail!("completions expects bash, zsh or fish, got '{}'", other),
+        }
+    }
+}
+
+/// Flags of a session. `cli::parse` refuses any flag not listed here, so
+/// the scripts never miss one.
+pub const SESSION_FLAGS: &[&str] = &[
+    "--dry-run",
+    "--spectator",
+    "--tutorial",
+    "--redact-addresses",
+    "--url",
+    "--max-response-mib",
+    "--history-limit",
+    "--timeout",
+    "--retries",
+    "--bearer-token",
+    "--basic-auth",
+    "--spec",
+    "--emit-context",
+    "--emit-context-format",
+];
+
+/// Flags of `eli fixture record`, likewise the only ones it takes.
+pub const RECORD_FLAGS: &[&str] = &["--method", "--params", "--out", "--scrub", "--scrub-hashes", "--url", "--timeout"];
+
+/// Flags of `eli spec list`; `eli spec diff` takes `--spec` too.
+pub const LIST_FLAGS: &[&str] = &["--plain", "--json", "--spec"];
+
+const SUBCOMMANDS: &str = "fixture spec gas completions";
+
+/// The completion script for `shell`. Method names come from
+/// `eli spec list --plain` when completing, so they follow `--spec` files
+/// and plugins.
+pub fn script(shell: Shell) -> String {
+    let session = SESSION_FLAGS.join(" ");
+    let record = RECORD_FLAGS.join(" ");
+    let list = LIST_FLAGS.join(" ");
+    match shell {
+        Shell::Bash => format!(
+            r#"# eli completions for bash: source <(eli completions bash)
+_eli() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    case "$prev" in
+        --method) COMPREPLY=($(compgen -W "$(eli spec list --plain 2>/dev/null)" -- "$cur")); return ;;
+        --spec|--emit-context|--out) COMPREPLY=($(compgen -f -- "$cur")); return ;;
+        --emit-context-format) COMPREPLY=($(compgen -W "shell json" -- "$cur")); return ;;
+        completions) COMPREPLY=($(compgen -W "bash zsh fish" -- "$cur")); return ;;
+    esac
+    case "${{COMP_WORDS[1]}}" in
+        fixture) COMPREPLY=($(compgen -W "record {record}" -- "$cur")) ;;
+        spec) COMPREPLY=($(compgen -W "list diff {list}" -- "$cur")) ;;
+        *) COMPREPLY=($(compgen -W "{subcommands} {session}" -- "$cur")) ;;
+    esac
+}}
+complete -F _eli eli
+"#,
+            record = record,
+            list = list,
+            subcommands = SUBCOMMANDS,
+            session = session,
+        ),
+        Shell::Zsh => format!(
+            r#"#compdef eli
+# eli completions for zsh: eli completions zsh > "${{fpath[1]}}/_eli"
+_eli() {{
+    local prev=${{words[CURRENT-1]}}
+    case $prev in
+        --method) compadd -- ${{(f)"$(eli spec list --plain 2>/dev/null)"}}; return ;;
+        --spec|--emit-context|--out) _files; return ;;
+        --emit-context-format) compadd -- shell json; return ;;
+        completions) compadd -- bash zsh fish; return ;;
+    esac
+    case ${{words[2]}} in
+        fixture) compadd -- record {record} ;;
+        spec) compadd -- list diff {list} ;;
+        *) compadd -- {subcommands} {session} ;;
+    esac
+}}
+compdef _eli eli
+"#,
+            record = record,
+            list = list,
+            subcommands = SUBCOMMANDS,
+            session = session,
+        ),
+        Shell::Fish => {
+            let flags = |condition: &str, flags: &[&str]| -> String {
+                flags
+                    .iter()
+                    .map(|f| format!("complete -c eli -n '{}' -l {}\n", condition, f.trim_start_matches("--")))
+                    .collect()
+            };
+            format!(
+                "# eli completions for fish: eli completions fish > ~/.config/fish/completions/eli.fish\n\
+                 complete -c eli -f\n\
+                 complete -c eli -n '__fish_use_subcommand' -a '{subcommands}'\n\
+                 complete -c eli -n '__fish_seen_subcommand_from completions' -a 'bash zsh fish'\n\
+                 complete -c eli -n '__fish_seen_subcommand_from fixture' -a record\n\
+                 complete -c eli -n '__fish_seen_subcommand_from spec' -a 'list diff'\n\
+                 {session}{record}{list}\
+                 complete -c eli -l method -x -a '(eli spec list --plain 2>/dev/null)'\n\
+                 complete -c eli -l emit-context-format -x -a 'shell json'\n\
+                 complete -c eli -l spec -l emit-context -l out -r -F\n",
+                subcommands = SUBCOMMANDS,
+                session = flags("not __fish_seen_subcommand_from fixture spec completions", SESSION_FLAGS),
+                record = flags("__fish_seen_subcommand_from record", RECORD_FLAGS),
+                list = flags("__fish_seen_subcommand_from list", LIST_FLAGS),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::RPC_SPECS;
+
+    #[test]
+    fn lists_methods_plain_and_as_json() {
+        let plain = list(RPC_SPECS, ListFormat::Plain);
+        assert_eq!(plain.lines().count(), RPC_SPECS.len());
+        assert!(plain.lines().any(|l| l == "eth_getBalance"));
+
+        let json: Value = serde_json::from_str(&list(RPC_SPECS, ListFormat::Json)).unwrap();
+        let block = json.as_array().unwrap().iter().find(|m| m["name"] == "eth_getBlockByNumber").unwrap();
+        assert_eq!(block["params"][0]["name"], "block");
+        assert_eq!(block["params"][0]["type"], "text");
+        assert_eq!(block["params"][1]["type"], "bool");
+        assert_eq!(list(&[], ListFormat::Json), "[]\n");
+    }
+
+    #[test]
+    fn scripts_complete_methods_from_the_spec_listing() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let text = script(shell);
+            assert!(text.contains("eli spec list --plain"), "{:?}", shell);
+            for flag in SESSION_FLAGS.iter().chain(RECORD_FLAGS).chain(LIST_FLAGS) {
+                assert!(text.contains(flag.trim_start_matches("--")), "{:?} lacks {}", shell, flag);
+            }
+        }
+        assert!(script(Shell::Bash).ends_with("complete -F _eli eli\n"));
+        assert!(Shell::parse("powershell").is_err());
+    }
+}
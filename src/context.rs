@@ -0,0 +1,172 @@
+// src/context.rs
+
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::app::HistoryEntry;
+use crate::storage;
+
+/// Where the value worth keeping sits in a method's result, as a JSON
+/// pointer. Methods not listed fall back to the result itself when it is a
+/// scalar (a tx hash from `eth_sendRawTransaction`, a balance, ...).
+pub const PRIMARY_POINTERS: &[(&str, &str)] = &[
+    ("eth_getBlockByNumber", "/number"),
+    ("eth_getBlockByHash", "/number"),
+    ("eth_getTransactionByHash", "/hash"),
+    ("eth_getTransactionByBlockHashAndIndex", "/hash"),
+    ("eth_getTransactionByBlockNumberAndIndex", "/hash"),
+    ("eth_getTransactionReceipt", "/transactionHash"),
+    ("eth_getLogs", "/0/transactionHash"),
+    ("eth_createAccessList", "/gasUsed"),
+    ("eth_feeHistory", "/oldestBlock"),
+];
+
+/// How `--emit-context` writes the file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContextFormat {
+    /// `ELI_…='…'` assignments, for `eval` or `source`.
+    #[default]
+    Shell,
+    Json,
+}
+
+impl ContextFormat {
+    pub fn parse(text: &str) -> Result<Self> {
+        match text {
+            "shell" => Ok(ContextFormat::Shell),
+            "json" => Ok(ContextFormat::Json),
+            other => bail!("--emit-context-format expects shell or json, got '{}'", other),
+        }
+    }
+}
+
+/// `result`'s primary value as text: what `extra` (e.g. plugins' pointers)
+/// or [`PRIMARY_POINTERS`] point at for `method`, or else `result` itself
+/// when it is a scalar.
+pub fn primary_value(method: &str, result: &Value, extra: &[(&str, &str)]) -> Option<String> {
+    let value = match extra.iter().chain(PRIMARY_POINTERS).find(|(m, _)| *m == method) {
+        Some((_, pointer)) => result.pointer(pointer)?,
+        None => result,
+    };
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// What a session ended on, left behind for the shell.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SessionContext {
+    pub last_value: Option<String>,
+    pub last_method: Option<String>,
+    pub endpoint: String,
+    pub chain_id: Option<u64>,
+}
+
+impl SessionContext {
+    /// From the newest entry of `history` that was actually sent, reading
+    /// its value with [`primary_value`].
+    pub fn from_history(history: &[HistoryEntry], endpoint: &str, chain_id: Option<u64>, pointers: &[(&str, &str)]) -> Self {
+        let last = history.iter().rev().find(|e| !e.dry_run);
+        SessionContext {
+            last_value: last.and_then(|e| primary_value(&e.request.method, e.response.result.as_ref()?, pointers)),
+            last_method: last.map(|e| e.request.method.clone()),
+            endpoint: endpoint.to_string(),
+            chain_id,
+        }
+    }
+
+    /// The file's contents. Missing values are empty in shell form and
+    /// `null` in JSON.
+    pub fn render(&self, format: ContextFormat) -> String {
+        match format {
+            ContextFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default() + "\n",
+            ContextFormat::Shell => {
+                let chain_id = self.chain_id.map(|id| id.to_string());
+                [
+                    ("ELI_LAST_VALUE", self.last_value.as_deref()),
+                    ("ELI_LAST_METHOD", self.last_method.as_deref()),
+                    ("ELI_ENDPOINT", Some(self.endpoint.as_str())),
+                    ("ELI_CHAIN_ID", chain_id.as_deref()),
+                ]
+                .iter()
+                .map(|(name, value)| format!("{}={}\n", name, shell_quote(value.unwrap_or(""))))
+                .collect()
+            }
+        }
+    }
+
+    pub fn write(&self, path: &Path, format: ContextFormat) -> Result<()> {
+        storage::write_atomic(path, self.render(format).as_bytes())
+    }
+}
+
+/// `text` in single quotes, with any single quote in it closed, escaped
+/// and reopened.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{JsonRpcRequest, JsonRpcResponse};
+    use serde_json::json;
+
+    fn entry(method: &str, result: Value, dry_run: bool) -> HistoryEntry {
+        HistoryEntry {
+            dry_run,
+            ..HistoryEntry::new(
+                JsonRpcRequest::new(method, json!([]), 1),
+                JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(result), error: None, id: 1 },
+            )
+        }
+    }
+
+    #[test]
+    fn primary_values_follow_the_method_pointers() {
+        let receipt = json!({"transactionHash": "0xabc", "status": "0x1"});
+        assert_eq!(primary_value("eth_getTransactionReceipt", &receipt, &[]).as_deref(), Some("0xabc"));
+        assert_eq!(primary_value("eth_getBlockByNumber", &json!({"number": "0x10", "hash": "0xh"}), &[]).as_deref(), Some("0x10"));
+        assert_eq!(primary_value("eth_getLogs", &json!([]), &[]), None);
+        // Not listed: scalars as they are, structures not at all
+        assert_eq!(primary_value("eth_sendRawTransaction", &json!("0xfeed"), &[]).as_deref(), Some("0xfeed"));
+        assert_eq!(primary_value("net_listening", &json!(true), &[]).as_deref(), Some("true"));
+        assert_eq!(primary_value("eth_syncing", &json!({"currentBlock": "0x1"}), &[]), None);
+        // Plugins' pointers come first
+        let status = json!({"mode": "active", "healthy": true});
+        assert_eq!(primary_value("sequencer_status", &status, &[("sequencer_status", "/healthy")]).as_deref(), Some("true"));
+    }
+
+    #[test]
+    fn context_from_history_renders_for_shell_and_jq() {
+        let history = vec![
+            entry("eth_blockNumber", json!("0x1"), false),
+            entry("eth_getTransactionReceipt", json!({"transactionHash": "0xabc"}), false),
+            entry("eth_gasPrice", Value::Null, true),
+        ];
+        let context = SessionContext::from_history(&history, "https://rpc.example.org/?key='x'", Some(1), &[]);
+        assert_eq!(context.last_method.as_deref(), Some("eth_getTransactionReceipt"));
+        assert_eq!(context.last_value.as_deref(), Some("0xabc"));
+        assert_eq!(
+            context.render(ContextFormat::Shell),
+            "ELI_LAST_VALUE='0xabc'\nELI_LAST_METHOD='eth_getTransactionReceipt'\n\
+             ELI_ENDPOINT='https://rpc.example.org/?key='\\''x'\\'''\nELI_CHAIN_ID='1'\n"
+        );
+        let json: Value = serde_json::from_str(&context.render(ContextFormat::Json)).unwrap();
+        assert_eq!(json["last_value"], "0xabc");
+        assert_eq!(json["chain_id"], 1);
+
+        let empty = SessionContext::from_history(&[], "http://localhost:8545", None, &[]);
+        assert!(empty.render(ContextFormat::Shell).starts_with("ELI_LAST_VALUE=''\n"));
+        assert_eq!(serde_json::from_str::<Value>(&empty.render(ContextFormat::Json)).unwrap()["last_method"], Value::Null);
+
+        assert_eq!(ContextFormat::parse("json").unwrap(), ContextFormat::Json);
+        assert!(ContextFormat::parse("yaml").is_err());
+    }
+}
@@ -0,0 +1,423 @@
+// src/dashboard.rs
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+
+use crate::prefs::DisplayPrefs;
+use crate::rpc::{BatchOutcome, JsonRpcRequest, JsonRpcResponse, Transport};
+use crate::schema;
+use crate::storage::{self, Loaded};
+use crate::tasks::{SharedTasks, TaskHandle, TaskKind};
+
+/// Samples kept per card for its sparkline.
+pub const TREND_LEN: usize = 40;
+/// Narrowest a card gets before the grid drops a column.
+pub const CARD_MIN_WIDTH: u16 = 24;
+/// Height of a card: border, value line, two sparkline rows.
+pub const CARD_HEIGHT: u16 = 5;
+
+/// One request shown as a card.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardSpec {
+    pub label: String,
+    pub method: String,
+    #[serde(default = "empty_params")]
+    pub params: Value,
+}
+
+fn empty_params() -> Value {
+    Value::Array(Vec::new())
+}
+
+/// The `dashboard.json` config file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DashboardConfig {
+    /// Seconds between polls.
+    pub interval_secs: u64,
+    pub cards: Vec<CardSpec>,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        let card = |label: &str, method: &str| CardSpec { label: label.into(), method: method.into(), params: json!([]) };
+        DashboardConfig {
+            interval_secs: 5,
+            cards: vec![
+                card("Block", "eth_blockNumber"),
+                card("Gas price", "eth_gasPrice"),
+                card("Sync", "eth_syncing"),
+                card("Txpool", "txpool_status"),
+                card("Peers", "net_peerCount"),
+            ],
+        }
+    }
+}
+
+/// Reads the dashboard config; a missing file yields the default cards.
+/// A corrupt file falls back to its backup, with a warning.
+pub fn load(path: &Path) -> Result<Loaded<DashboardConfig>> {
+    Ok(storage::load_versioned(path, &schema::DASHBOARD)?.unwrap_or_else(|| Loaded::fresh(DashboardConfig::default())))
+}
+
+/// What a card shows: humanized text, plus a number for the sparkline when
+/// the value has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardValue {
+    pub text: String,
+    pub sample: Option<u64>,
+}
+
+fn hex_u64(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.strip_prefix("0x")?, 16).ok()
+}
+
+/// Extracts a card's value from a response.
+///
+/// Gas prices are shown in gwei (sampled in wei), `eth_syncing` as
+/// "synced" or current/highest block, `txpool_status` as pending/queued
+/// counts (sampling pending), and any other hex quantity as an integer.
+pub fn extract(method: &str, response: &JsonRpcResponse, prefs: &DisplayPrefs) -> CardValue {
+    let text = |text: String| CardValue { text, sample: None };
+    if let Some(err) = &response.error {
+        return text(format!("error: {}", err.get("message").and_then(Value::as_str).unwrap_or("unknown")));
+    }
+    let Some(result) = &response.result else { return text("null".into()) };
+    match method {
+        "eth_gasPrice" | "eth_maxPriorityFeePerGas" if hex_u64(result).is_some() => {
+            let wei = hex_u64(result).unwrap_or(0);
+            CardValue { text: format!("{:.2} gwei", wei as f64 / 1e9), sample: Some(wei) }
+        }
+        "eth_syncing" => match result {
+            Value::Bool(false) => CardValue { text: "synced".into(), sample: Some(0) },
+            sync => {
+                let (current, highest) = (hex_u64(&sync["currentBlock"]), hex_u64(&sync["highestBlock"]));
+                match (current, highest) {
+                    (Some(c), Some(h)) => CardValue {
+                        text: format!("{} / {}", prefs.integer(c as u128), prefs.integer(h as u128)),
+                        sample: Some(h.saturating_sub(c)),
+                    },
+                    _ => text("syncing".into()),
+                }
+            }
+        },
+        "txpool_status" => match (hex_u64(&result["pending"]), hex_u64(&result["queued"])) {
+            (Some(pending), Some(queued)) => CardValue {
+                text: format!("{} pending · {} queued", prefs.integer(pending as u128), prefs.integer(queued as u128)),
+                sample: Some(pending),
+            },
+            _ => text(result.to_string()),
+        },
+        _ => match hex_u64(result) {
+            Some(n) => CardValue { text: prefs.integer(n as u128), sample: Some(n) },
+            None => text(result.as_str().map(str::to_string).unwrap_or_else(|| result.to_string())),
+        },
+    }
+}
+
+/// Card rectangles for `count` cards in `area`: as many columns of at least
+/// [`CARD_MIN_WIDTH`] as fit (one at minimum), row-major, with leftover
+/// width spread over the first columns. Cards that don't fit vertically are
+/// left out.
+pub fn card_grid(area: Rect, count: usize) -> Vec<Rect> {
+    if count == 0 || area.width == 0 || area.height < CARD_HEIGHT {
+        return Vec::new();
+    }
+    let columns = ((area.width / CARD_MIN_WIDTH).max(1) as usize).min(count);
+    let visible_rows = (area.height / CARD_HEIGHT) as usize;
+    let (base, extra) = (area.width as usize / columns, area.width as usize % columns);
+    (0..count)
+        .take(visible_rows * columns)
+        .map(|i| {
+            let (row, col) = (i / columns, i % columns);
+            let x = area.x as usize + col * base + col.min(extra);
+            let width = base + usize::from(col < extra);
+            Rect::new(x as u16, area.y + row as u16 * CARD_HEIGHT, width as u16, CARD_HEIGHT)
+        })
+        .collect()
+}
+
+/// Latest value and recent samples of one card.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CardState {
+    pub value: Option<CardValue>,
+    pub trend: VecDeque<u64>,
+}
+
+impl CardState {
+    fn push(&mut self, value: CardValue) {
+        if let Some(sample) = value.sample {
+            if self.trend.len() == TREND_LEN {
+                self.trend.pop_front();
+            }
+            self.trend.push_back(sample);
+        }
+        self.value = Some(value);
+    }
+}
+
+type PollResults = Arc<Mutex<Option<Vec<Option<JsonRpcResponse>>>>>;
+
+/// A card's part of a batch as the response it would have had alone;
+/// `None` when the node left it out.
+fn response(outcome: BatchOutcome, id: u64) -> Option<JsonRpcResponse> {
+    let (result, error) = match outcome {
+        BatchOutcome::Ok(result) => (Some(result), None),
+        BatchOutcome::RpcError(error) => (None, Some(error)),
+        BatchOutcome::Missing => return None,
+    };
+    Some(JsonRpcResponse { jsonrpc: "2.0".into(), result, error, id })
+}
+
+/// Cards and their polling. Polls only happen through [`Dashboard::poll`],
+/// which the app calls while the dashboard is visible.
+#[derive(Debug, Default)]
+pub struct Dashboard {
+    pub config: DashboardConfig,
+    pub cards: Vec<CardState>,
+    last_poll: Option<Instant>,
+    in_flight: Option<(JoinHandle<()>, PollResults)>,
+    task: TaskHandle,
+}
+
+impl Dashboard {
+    pub fn new(config: DashboardConfig) -> Self {
+        let cards = vec![CardState::default(); config.cards.len()];
+        Dashboard { config, cards, last_poll: None, in_flight: None, task: TaskHandle::default() }
+    }
+
+    /// "5 cards: eth_blockNumber, eth_gasPrice, …" for the activity panel.
+    fn summary(&self) -> String {
+        let methods: Vec<&str> = self.config.cards.iter().map(|c| c.method.as_str()).collect();
+        format!("{} cards: {}", methods.len(), methods.join(", "))
+    }
+
+    /// Applies a finished poll, then starts the next one if the interval
+    /// has passed and polling isn't paused or killed in `tasks`. All cards
+    /// are sent as one batch, or concurrently when the node refuses
+    /// batches; a card whose request fails keeps its previous value.
+    pub fn poll(&mut self, now: Instant, transport: Arc<dyn Transport>, url: &str, prefs: &DisplayPrefs, tasks: &SharedTasks) {
+        if let Some((handle, results)) = &self.in_flight {
+            if !handle.is_finished() {
+                return;
+            }
+            let responses = results.lock().unwrap().take().unwrap_or_default();
+            let ok = responses.iter().all(Option::is_some);
+            if let Some(id) = self.task.id() {
+                tasks.update(|r| r.record(id, ok, crate::head::unix_now()));
+            }
+            self.apply(responses, prefs);
+            self.in_flight = None;
+        }
+        let interval = Duration::from_secs(self.config.interval_secs.max(1));
+        if !self.task.may_run(tasks, TaskKind::Dashboard, &self.summary(), interval) {
+            return;
+        }
+        if self.last_poll.is_some_and(|last| now.duration_since(last) < interval) || self.config.cards.is_empty() {
+            return;
+        }
+        self.last_poll = Some(now);
+
+        let requests: Vec<JsonRpcRequest> = self
+            .config
+            .cards
+            .iter()
+            .enumerate()
+            .map(|(i, card)| JsonRpcRequest::new(card.method.clone(), card.params.clone(), i as u64 + 1))
+            .collect();
+        let results: PollResults = Arc::default();
+        let (sink, url) = (results.clone(), url.to_string());
+        let handle = tokio::spawn(async move {
+            if let Ok(report) = transport.send_batch(&url, &requests).await {
+                let responses = report.outcomes.into_iter().zip(&requests).map(|(o, req)| response(o, req.id)).collect();
+                *sink.lock().unwrap() = Some(responses);
+                return;
+            }
+            let sends: Vec<_> = requests
+                .into_iter()
+                .map(|req| {
+                    let (transport, url) = (transport.clone(), url.clone());
+                    tokio::spawn(async move { transport.send(&url, req).await.ok() })
+                })
+                .collect();
+            let mut responses = Vec::with_capacity(sends.len());
+            for send in sends {
+                responses.push(send.await.ok().flatten());
+            }
+            *sink.lock().unwrap() = Some(responses);
+        });
+        self.in_flight = Some((handle, results));
+    }
+
+    /// Drops the poll in flight and polls again on the next call.
+    pub fn reschedule(&mut self) {
+        if let Some((handle, _)) = self.in_flight.take() {
+            handle.abort();
+        }
+        self.last_poll = None;
+    }
+
+    /// Stops any poll in flight and unregisters from `tasks`, e.g. when
+    /// the dashboard is closed.
+    pub fn pause(&mut self, tasks: &SharedTasks) {
+        if let Some((handle, _)) = self.in_flight.take() {
+            handle.abort();
+        }
+        self.last_poll = None;
+        self.task.release(tasks);
+    }
+
+    fn apply(&mut self, responses: Vec<Option<JsonRpcResponse>>, prefs: &DisplayPrefs) {
+        for ((state, spec), response) in self.cards.iter_mut().zip(&self.config.cards).zip(responses) {
+            if let Some(response) = response {
+                state.push(extract(&spec.method, &response, prefs));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    fn ok(result: Value) -> JsonRpcResponse {
+        JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(result), error: None, id: 1 }
+    }
+
+    #[test]
+    fn extracts_card_values() {
+        let prefs = DisplayPrefs::default();
+        let value = |method: &str, result: Value| extract(method, &ok(result), &prefs);
+        assert_eq!(value("eth_blockNumber", json!("0x1036640")), CardValue { text: "17,000,000".into(), sample: Some(17_000_000) });
+        assert_eq!(value("eth_gasPrice", json!("0x4a817c800")).text, "20.00 gwei");
+        assert_eq!(value("eth_syncing", json!(false)).text, "synced");
+        let syncing = value("eth_syncing", json!({"currentBlock": "0x10", "highestBlock": "0x20"}));
+        assert_eq!(syncing, CardValue { text: "16 / 32".into(), sample: Some(16) });
+        let pool = value("txpool_status", json!({"pending": "0x3e8", "queued": "0x2"}));
+        assert_eq!(pool, CardValue { text: "1,000 pending · 2 queued".into(), sample: Some(1000) });
+        assert_eq!(value("net_peerCount", json!("0x19")).sample, Some(25));
+        assert_eq!(value("web3_clientVersion", json!("Geth/v1.14")).text, "Geth/v1.14");
+
+        let err = JsonRpcResponse {
+            jsonrpc: "2.0".into(),
+            result: None,
+            error: Some(json!({"code": -32601, "message": "method not found"})),
+            id: 1,
+        };
+        assert_eq!(extract("txpool_status", &err, &prefs), CardValue { text: "error: method not found".into(), sample: None });
+    }
+
+    #[test]
+    fn grid_adapts_to_terminal_size() {
+        // Wide: all five cards on one row, leftover width spread from the left
+        let wide = card_grid(Rect::new(0, 0, 122, 20), 5);
+        assert_eq!(wide.len(), 5);
+        assert!(wide.iter().all(|r| r.y == 0));
+        assert_eq!(wide.iter().map(|r| r.width).collect::<Vec<_>>(), vec![25, 25, 24, 24, 24]);
+        assert_eq!(wide[4].right(), 122);
+
+        // Medium: three columns, two rows
+        let medium = card_grid(Rect::new(0, 1, 80, 20), 5);
+        assert_eq!(medium[3], Rect::new(0, 1 + CARD_HEIGHT, 27, CARD_HEIGHT));
+
+        // Narrow: one column, cut off at the bottom
+        let narrow = card_grid(Rect::new(0, 0, 20, 12), 5);
+        assert_eq!(narrow.len(), 2);
+        assert!(narrow.iter().all(|r| r.width == 20));
+
+        assert!(card_grid(Rect::new(0, 0, 80, 3), 5).is_empty());
+        assert!(card_grid(Rect::new(0, 0, 80, 20), 0).is_empty());
+    }
+
+    #[test]
+    fn trend_is_bounded() {
+        let mut state = CardState::default();
+        for n in 0..(TREND_LEN as u64 + 5) {
+            state.push(CardValue { text: n.to_string(), sample: Some(n) });
+        }
+        assert_eq!(state.trend.len(), TREND_LEN);
+        assert_eq!(state.trend.front(), Some(&5));
+    }
+
+    struct Counter;
+
+    #[async_trait]
+    impl Transport for Counter {
+        async fn send(&self, _url: &str, req: JsonRpcRequest) -> Result<JsonRpcResponse> {
+            Ok(ok(json!(format!("0x{:x}", req.id * 10))))
+        }
+    }
+
+    #[tokio::test]
+    async fn polls_on_interval_and_applies_results() {
+        let (prefs, tasks) = (DisplayPrefs::default(), SharedTasks::default());
+        let mut dashboard = Dashboard::new(DashboardConfig::default());
+        let start = Instant::now();
+        dashboard.poll(start, Arc::new(Counter), "http://node", &prefs, &tasks);
+        while !dashboard.in_flight.as_ref().unwrap().0.is_finished() {
+            tokio::task::yield_now().await;
+        }
+        // Collecting the results doesn't start another poll before the interval
+        dashboard.poll(start, Arc::new(Counter), "http://node", &prefs, &tasks);
+        assert!(dashboard.in_flight.is_none());
+        assert_eq!(dashboard.cards[0].value.as_ref().unwrap().text, "10");
+        assert_eq!(dashboard.cards[4].trend, VecDeque::from([50]));
+
+        let task = tasks.snapshot().tasks()[0].clone();
+        assert_eq!(task.target, "5 cards: eth_blockNumber, eth_gasPrice, eth_syncing, txpool_status, net_peerCount");
+        assert!(task.last_result.is_some());
+
+        dashboard.poll(start + Duration::from_secs(5), Arc::new(Counter), "http://node", &prefs, &tasks);
+        assert!(dashboard.in_flight.is_some());
+        dashboard.pause(&tasks);
+        assert!(dashboard.in_flight.is_none());
+        // Closed: no longer listed as background activity
+        assert!(tasks.snapshot().tasks().is_empty());
+    }
+
+    /// Answers batches only: the first card, an error for the second, and
+    /// nothing for the rest.
+    #[derive(Default)]
+    struct Batching {
+        singles: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Batching {
+        async fn send(&self, _url: &str, _req: JsonRpcRequest) -> Result<JsonRpcResponse> {
+            self.singles.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            anyhow::bail!("sent on its own")
+        }
+
+        async fn send_batch(&self, _url: &str, requests: &[JsonRpcRequest]) -> Result<crate::rpc::BatchReport> {
+            let mut outcomes = vec![BatchOutcome::Ok(json!("0x10")), BatchOutcome::RpcError(json!({"message": "busy"}))];
+            outcomes.resize(requests.len(), BatchOutcome::Missing);
+            Ok(crate::rpc::BatchReport { outcomes, unmatched: 0 })
+        }
+    }
+
+    #[tokio::test]
+    async fn cards_are_polled_in_one_batch() {
+        let (prefs, tasks) = (DisplayPrefs::default(), SharedTasks::default());
+        let mut dashboard = Dashboard::new(DashboardConfig::default());
+        let transport = Arc::new(Batching::default());
+        let start = Instant::now();
+        dashboard.poll(start, transport.clone(), "http://node", &prefs, &tasks);
+        while !dashboard.in_flight.as_ref().unwrap().0.is_finished() {
+            tokio::task::yield_now().await;
+        }
+        dashboard.poll(start, transport.clone(), "http://node", &prefs, &tasks);
+        assert_eq!(transport.singles.load(std::sync::atomic::Ordering::SeqCst), 0);
+        let text = |i: usize| dashboard.cards[i].value.as_ref().map(|v| v.text.clone());
+        assert_eq!((text(0), text(1), text(2)), (Some("16".into()), Some("error: busy".into()), None));
+    }
+}
@@ -0,0 +1,278 @@
+// src/decode.rs
+
+use serde_json::Value;
+
+use crate::accesslist::AccessListDecoder;
+use crate::chains::NativeCurrency;
+use crate::prefs::DisplayPrefs;
+use crate::simulate;
+use crate::trace::FlatTraceDecoder;
+use crate::tx;
+
+/// A method-specific rendering of a result, shown above its JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedView {
+    /// Rows under column headers.
+    Table { columns: Vec<String>, rows: Vec<Vec<String>> },
+    /// A fraction between 0 and 1, e.g. sync progress.
+    Gauge { label: String, ratio: f64 },
+    /// A series drawn as bars, e.g. base fees per block.
+    Sparkline { label: String, values: Vec<u64> },
+    /// Indented lines: (depth, text).
+    Tree(Vec<(usize, String)>),
+    /// Plain lines.
+    Summary(Vec<String>),
+}
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const GAUGE_WIDTH: usize = 20;
+
+impl DecodedView {
+    /// The view as text lines for the response pane.
+    pub fn lines(&self) -> Vec<String> {
+        match self {
+            DecodedView::Table { columns, rows } => {
+                let mut widths: Vec<usize> = columns.iter().map(|c| c.chars().count()).collect();
+                for row in rows {
+                    for (i, cell) in row.iter().enumerate() {
+                        if i < widths.len() {
+                            widths[i] = widths[i].max(cell.chars().count());
+                        }
+                    }
+                }
+                let line = |cells: &[String]| {
+                    let padded: Vec<String> =
+                        cells.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+                    padded.join("  ").trim_end().to_string()
+                };
+                std::iter::once(line(columns)).chain(rows.iter().map(|row| line(row))).collect()
+            }
+            DecodedView::Gauge { label, ratio } => {
+                let ratio = ratio.clamp(0.0, 1.0);
+                let filled = (ratio * GAUGE_WIDTH as f64).round() as usize;
+                vec![format!(
+                    "{} [{}{}] {:.0}%",
+                    label,
+                    "█".repeat(filled),
+                    "░".repeat(GAUGE_WIDTH - filled),
+                    ratio * 100.0
+                )]
+            }
+            DecodedView::Sparkline { label, values } => {
+                let (min, max) = (values.iter().min().copied().unwrap_or(0), values.iter().max().copied().unwrap_or(0));
+                let bar = |v: u64| match max - min {
+                    0 => BARS[0],
+                    span => BARS[((v - min) as u128 * (BARS.len() as u128 - 1) / span as u128) as usize],
+                };
+                vec![format!("{} {} (min {}, max {})", label, values.iter().map(|v| bar(*v)).collect::<String>(), min, max)]
+            }
+            DecodedView::Tree(nodes) => nodes.iter().map(|(depth, text)| format!("{}{}", "  ".repeat(*depth), text)).collect(),
+            DecodedView::Summary(lines) => lines.clone(),
+        }
+    }
+}
+
+/// Method-specific handling of results.
+///
+/// Register one on the app's [`DecoderRegistry`] to give a method its own
+/// rendering; the response view asks every decoder that matches, newest
+/// first, and falls back to the plain JSON alone when none decodes.
+pub trait ResultDecoder: Send + Sync {
+    fn matches(&self, method: &str) -> bool;
+    /// The rendering of `result`, or `None` if it isn't the expected shape.
+    fn decode(&self, result: &Value, prefs: &DisplayPrefs) -> Option<DecodedView>;
+}
+
+/// The registered decoders; starts with the builtin ones.
+pub struct DecoderRegistry {
+    decoders: Vec<Box<dyn ResultDecoder>>,
+}
+
+impl Default for DecoderRegistry {
+    fn default() -> Self {
+        let mut registry = DecoderRegistry::empty();
+        registry.register(SenderCheck);
+        registry.register(SimulateSummary);
+        registry.register(AccessListDecoder);
+        registry.register(FlatTraceDecoder);
+        registry
+    }
+}
+
+impl DecoderRegistry {
+    /// A registry without the builtin decoders.
+    pub fn empty() -> Self {
+        DecoderRegistry { decoders: Vec::new() }
+    }
+
+    /// Adds `decoder`; it is consulted before those registered earlier, so
+    /// it can take over a builtin's methods.
+    pub fn register(&mut self, decoder: impl ResultDecoder + 'static) {
+        self.decoders.push(Box::new(decoder));
+    }
+
+    /// The first rendering a matching decoder produces for `result`.
+    pub fn decode(&self, method: &str, result: &Value, prefs: &DisplayPrefs) -> Option<DecodedView> {
+        self.decoders.iter().rev().filter(|d| d.matches(method)).find_map(|d| d.decode(result, prefs))
+    }
+}
+
+/// Transaction type and local sender check for `eth_getTransactionByHash`.
+struct SenderCheck;
+
+impl ResultDecoder for SenderCheck {
+    fn matches(&self, method: &str) -> bool {
+        method == "eth_getTransactionByHash"
+    }
+
+    fn decode(&self, result: &Value, prefs: &DisplayPrefs) -> Option<DecodedView> {
+        result.is_object().then(|| DecodedView::Summary(vec![tx::sender_summary(result, prefs)]))
+    }
+}
+
+/// Per-block, per-call summary of `eth_simulateV1` results.
+struct SimulateSummary;
+
+impl ResultDecoder for SimulateSummary {
+    fn matches(&self, method: &str) -> bool {
+        method == simulate::METHOD
+    }
+
+    fn decode(&self, result: &Value, prefs: &DisplayPrefs) -> Option<DecodedView> {
+        let lines = simulate::summarize(result, prefs);
+        (!lines.is_empty()).then_some(DecodedView::Summary(lines))
+    }
+}
+
+/// What the single hex quantity a method returns measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Measure {
+    Count,
+    /// Wei per gas, also shown in gwei.
+    GasPrice,
+    /// Wei, also shown in the chain's native currency.
+    Balance,
+}
+
+const QUANTITY_METHODS: &[(&str, Measure)] = &[
+    ("eth_blockNumber", Measure::Count),
+    ("eth_chainId", Measure::Count),
+    ("net_peerCount", Measure::Count),
+    ("eth_getTransactionCount", Measure::Count),
+    ("eth_estimateGas", Measure::Count),
+    ("eth_getBlockTransactionCountByNumber", Measure::Count),
+    ("eth_getBlockTransactionCountByHash", Measure::Count),
+    ("eth_gasPrice", Measure::GasPrice),
+    ("eth_maxPriorityFeePerGas", Measure::GasPrice),
+    ("eth_blobBaseFee", Measure::GasPrice),
+    ("eth_getBalance", Measure::Balance),
+];
+
+/// `n` divided by 10^`decimals`, without trailing zeros.
+fn scaled(n: u128, decimals: u32) -> String {
+    let scale = 10u128.pow(decimals.min(38));
+    let frac = format!("{:0width$}", n % scale, width = decimals.min(38) as usize);
+    match frac.trim_end_matches('0') {
+        "" => (n / scale).to_string(),
+        frac => format!("{}.{}", n / scale, frac),
+    }
+}
+
+/// The result of a quantity-returning method (`eth_blockNumber`,
+/// `eth_gasPrice`, `eth_getBalance`, …) as hex and decimal, plus gwei for
+/// gas prices and `currency` for balances; shown beneath the JSON. `None`
+/// for other methods and results that aren't a hex quantity.
+pub fn quantity_lines(method: &str, result: &Value, currency: &NativeCurrency) -> Option<Vec<String>> {
+    let measure = QUANTITY_METHODS.iter().find(|(name, _)| *name == method)?.1;
+    let hex = result.as_str()?;
+    let digits = hex.strip_prefix("0x").filter(|d| !d.is_empty())?;
+    let n = u128::from_str_radix(digits, 16).ok()?;
+    let mut lines = vec![format!("{:<8} {}", "hex", hex), format!("{:<8} {}", "decimal", n)];
+    match measure {
+        Measure::Count => {}
+        Measure::GasPrice => lines.push(format!("{:<8} {}", "gwei", scaled(n, 9))),
+        Measure::Balance => lines.push(format!("{:<8} {}", currency.symbol, scaled(n, currency.decimals))),
+    }
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A downstream decoder: base fees of `eth_feeHistory` as a sparkline.
+    struct FeeHistory;
+
+    impl ResultDecoder for FeeHistory {
+        fn matches(&self, method: &str) -> bool {
+            method == "eth_feeHistory"
+        }
+
+        fn decode(&self, result: &Value, _prefs: &DisplayPrefs) -> Option<DecodedView> {
+            let values = result["baseFeePerGas"]
+                .as_array()?
+                .iter()
+                .map(|v| u64::from_str_radix(v.as_str()?.trim_start_matches("0x"), 16).ok())
+                .collect::<Option<Vec<_>>>()?;
+            Some(DecodedView::Sparkline { label: "base fee".into(), values })
+        }
+    }
+
+    #[test]
+    fn custom_decoders_plug_in() {
+        let prefs = DisplayPrefs::default();
+        let mut registry = DecoderRegistry::default();
+        let fees = json!({"baseFeePerGas": ["0x1", "0x4", "0x8"]});
+        assert_eq!(registry.decode("eth_feeHistory", &fees, &prefs), None);
+
+        registry.register(FeeHistory);
+        let view = registry.decode("eth_feeHistory", &fees, &prefs).unwrap();
+        assert_eq!(view.lines(), ["base fee ▁▄█ (min 1, max 8)"]);
+        // Unexpected shapes fall back to the JSON alone
+        assert_eq!(registry.decode("eth_feeHistory", &json!(null), &prefs), None);
+        assert_eq!(registry.decode("eth_blockNumber", &json!("0x10"), &prefs), None);
+        // Builtins are still consulted for their methods
+        assert!(registry.decode(simulate::METHOD, &json!([{"number": "0x1", "gasUsed": "0x0", "calls": []}]), &prefs).is_some());
+    }
+
+    #[test]
+    fn renders_each_form_as_lines() {
+        let table = DecodedView::Table {
+            columns: vec!["block".into(), "txs".into()],
+            rows: vec![vec!["0x10".into(), "3".into()], vec!["0x11".into(), "120".into()]],
+        };
+        assert_eq!(table.lines(), ["block  txs", "0x10   3", "0x11   120"]);
+        let gauge = DecodedView::Gauge { label: "synced".into(), ratio: 0.5 };
+        assert_eq!(gauge.lines(), [format!("synced [{}{}] 50%", "█".repeat(10), "░".repeat(10))]);
+        let tree = DecodedView::Tree(vec![(0, "block".into()), (1, "call 0".into())]);
+        assert_eq!(tree.lines(), ["block", "  call 0"]);
+        let flat = DecodedView::Sparkline { label: "gas".into(), values: vec![5, 5] };
+        assert_eq!(flat.lines(), ["gas ▁▁ (min 5, max 5)"]);
+    }
+
+    #[test]
+    fn quantities_decode_to_decimal_and_units() {
+        let eth = NativeCurrency::default();
+        assert_eq!(quantity_lines("eth_blockNumber", &json!("0x1a2b"), &eth).unwrap(), ["hex      0x1a2b", "decimal  6699"]);
+        assert_eq!(
+            quantity_lines("eth_gasPrice", &json!("0x4a817c801"), &eth).unwrap(),
+            ["hex      0x4a817c801", "decimal  20000000001", "gwei     20.000000001"]
+        );
+        let balance = quantity_lines("eth_getBalance", &json!("0x14d1120d7b160000"), &eth).unwrap();
+        assert_eq!(balance[2], "ETH      1.5");
+        let xdai = NativeCurrency { symbol: "XDAI".into(), ..NativeCurrency::default() };
+        assert_eq!(quantity_lines("eth_getBalance", &json!("0x0"), &xdai).unwrap()[2], "XDAI     0");
+    }
+
+    #[test]
+    fn other_methods_and_shapes_are_not_quantities() {
+        let eth = NativeCurrency::default();
+        assert_eq!(quantity_lines("eth_getCode", &json!("0x6080"), &eth), None);
+        assert_eq!(quantity_lines("eth_blockNumber", &json!(null), &eth), None);
+        assert_eq!(quantity_lines("eth_blockNumber", &json!("0x"), &eth), None);
+        assert_eq!(quantity_lines("eth_blockNumber", &json!("latest"), &eth), None);
+        // Past u128: left to the JSON
+        assert_eq!(quantity_lines("eth_getBalance", &json!(format!("0x1{}", "0".repeat(32))), &eth), None);
+    }
+}
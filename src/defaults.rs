@@ -0,0 +1,148 @@
+// src/defaults.rs
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::migrate::value_to_field;
+use crate::schema;
+use crate::storage::{self, Loaded};
+
+/// Default param values from `defaults.json`, scoped by namespace
+/// (`eth`, `debug`) or exact method name:
+///
+/// ```json
+/// {
+///   "eth": { "block": "latest" },
+///   "debug_traceTransaction": { "options": { "tracer": "callTracer" } }
+/// }
+/// ```
+///
+/// A field's starting value is decided in this order, first match wins:
+/// 1. whatever the field already holds (remembered or reloaded from
+///    history) — defaults only ever fill empty fields;
+/// 2. a default for the exact method;
+/// 3. a default for the method's namespace (the part before the first `_`);
+/// 4. a builtin default for the param name, from [`BUILTIN`]; a scope
+///    setting the param to `""` opts out of it;
+/// 5. nothing: the field stays empty and only its example/help text is
+///    shown, which is never sent.
+///
+/// A top-level `"version"` key, if present, is the file's schema version
+/// (see [`schema`]), not a scope.
+///
+/// Params are matched by their names in the active spec (builtin plus any
+/// chain extension), so methods without a spec get no defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ParamDefaults(pub BTreeMap<String, BTreeMap<String, Value>>);
+
+/// Defaults by param name alone, for every method: block params start at
+/// the chain head, as almost every request wants.
+pub const BUILTIN: &[(&str, &str)] =
+    &[("block", "latest"), ("newest_block", "latest"), ("fromBlock", "latest"), ("toBlock", "latest")];
+
+/// Reads `defaults.json`; a missing file means no defaults. A corrupt file
+/// falls back to its backup, with a warning.
+pub fn load(path: &Path) -> Result<Loaded<ParamDefaults>> {
+    Ok(storage::load_versioned(path, &schema::DEFAULTS)?.unwrap_or_else(|| Loaded::fresh(ParamDefaults::default())))
+}
+
+impl ParamDefaults {
+    /// The default for `param` of `method` as field text: the method's own
+    /// scope wins over its namespace, and both over [`BUILTIN`]. Non-string
+    /// values are written as JSON.
+    pub fn lookup(&self, method: &str, param: &str) -> Option<String> {
+        let namespace = method.split('_').next().unwrap_or(method);
+        match [method, namespace].iter().find_map(|scope| self.0.get(*scope)?.get(param)) {
+            Some(value) => Some(value_to_field(value)),
+            None => BUILTIN.iter().find(|(name, _)| *name == param).map(|(_, value)| value.to_string()),
+        }
+    }
+
+    /// Fills the empty `fields` of `method`, whose spec names its params
+    /// `params`, with their defaults; returns, per field, the default that
+    /// was put there.
+    pub fn seed(&self, method: &str, params: &[&str], fields: &mut Vec<String>) -> Vec<Option<String>> {
+        if params.is_empty() {
+            return Vec::new();
+        }
+        let mut seeded = vec![None; fields.len()];
+        for (i, param) in params.iter().enumerate() {
+            let Some(value) = self.lookup(method, param).filter(|v| !v.is_empty()) else { continue };
+            if fields.len() <= i {
+                fields.resize(i + 1, String::new());
+                seeded.resize(i + 1, None);
+            }
+            if fields[i].is_empty() {
+                fields[i] = value.clone();
+                seeded[i] = Some(value);
+            }
+        }
+        seeded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BALANCE: &[&str] = &["address", "block"];
+
+    fn defaults(value: Value) -> ParamDefaults {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn method_scope_beats_namespace() {
+        let d = defaults(json!({
+            "eth": {"block": "latest", "include_transactions": false},
+            "eth_getBalance": {"block": "finalized"},
+            "debug_traceTransaction": {"options": {"tracer": "callTracer"}}
+        }));
+        assert_eq!(d.lookup("eth_getBalance", "block").as_deref(), Some("finalized"));
+        assert_eq!(d.lookup("eth_call", "block").as_deref(), Some("latest"));
+        assert_eq!(d.lookup("eth_getBlockByNumber", "include_transactions").as_deref(), Some("false"));
+        assert_eq!(d.lookup("debug_traceTransaction", "options").as_deref(), Some(r#"{"tracer":"callTracer"}"#));
+        assert_eq!(d.lookup("net_version", "address"), None);
+    }
+
+    #[test]
+    fn seeds_only_empty_fields() {
+        let d = defaults(json!({"eth": {"address": "0x00000000000000000000000000000000000000a1", "block": "latest"}}));
+        // A value already in the field (remembered or reloaded) wins
+        let mut fields = vec!["0x00000000000000000000000000000000000000b2".to_string(), String::new()];
+        let seeded = d.seed("eth_getBalance", BALANCE, &mut fields);
+        assert_eq!(fields, ["0x00000000000000000000000000000000000000b2", "latest"]);
+        assert_eq!(seeded, [None, Some("latest".to_string())]);
+
+        let mut fields = Vec::new();
+        assert_eq!(d.seed("eth_getBalance", BALANCE, &mut fields).len(), 2);
+        assert_eq!(fields[0], "0x00000000000000000000000000000000000000a1");
+
+        // No spec, no param names to match
+        let mut fields = vec![String::new()];
+        assert!(d.seed("eth_unknownMethod", &[], &mut fields).is_empty());
+        assert_eq!(fields, [""]);
+    }
+
+    #[test]
+    fn block_params_default_to_latest_unless_configured() {
+        let d = defaults(json!({"eth_getBalance": {"block": "finalized"}, "trace": {"toBlock": ""}}));
+        assert_eq!(d.lookup("eth_call", "block").as_deref(), Some("latest"));
+        assert_eq!(d.lookup("eth_feeHistory", "newest_block").as_deref(), Some("latest"));
+        assert_eq!(d.lookup("eth_getBalance", "block").as_deref(), Some("finalized"));
+        assert_eq!(d.lookup("trace_filter", "fromBlock").as_deref(), Some("latest"));
+        assert_eq!(d.lookup("eth_getBalance", "address"), None);
+
+        // An empty default opts out: the field stays empty
+        let mut fields = vec![String::new(); 6];
+        let seeded = d.seed("trace_filter", crate::trace::FILTER_FIELDS, &mut fields);
+        assert_eq!(fields[..2], ["latest", ""]);
+        assert_eq!(seeded[..2], [Some("latest".to_string()), None]);
+    }
+}
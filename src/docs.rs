@@ -0,0 +1,188 @@
+// src/docs.rs
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use tokio::task::JoinHandle;
+
+use crate::extension::SpecExtension;
+use crate::schematree::{self, SchemaNode};
+use crate::spec;
+
+/// What the docs panel shows for a method.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MethodMeta {
+    pub description: Option<String>,
+    /// Param names with their help text, from the spec.
+    pub params: Vec<(String, Option<String>)>,
+    /// Shape of the result, when the spec document has a schema for it.
+    pub result: Option<SchemaNode>,
+}
+
+/// Which tab of the docs panel is shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DocsTab {
+    /// Description and params.
+    #[default]
+    Docs,
+    /// Result schema tree.
+    Schema,
+}
+
+/// Looks up a method's metadata; may be slow (e.g. reading a document).
+#[async_trait]
+pub trait MethodResolver: Send + Sync {
+    async fn resolve(&self, method: &str) -> MethodMeta;
+}
+
+/// One-line descriptions, shortened from the execution-apis method summaries.
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    ("eth_blockNumber", "Returns the number of the most recent block."),
+    ("eth_getBalance", "Returns the balance of the account of the given address."),
+    ("eth_gasPrice", "Returns the current price per gas in wei."),
+    ("eth_call", "Executes a new message call immediately without creating a transaction on the block chain."),
+    ("eth_createAccessList", "Generates an access list for a transaction, with the gas it would use."),
+    ("eth_sendTransaction", "Signs and submits a transaction."),
+    ("eth_getBlockByNumber", "Returns information about a block by number."),
+    ("eth_getBlockByHash", "Returns information about a block by hash."),
+    ("eth_simulateV1", "Executes a sequence of message calls building on each other's state without creating transactions on the block chain."),
+    ("trace_transaction", "Returns the flat traces of all calls made by a transaction."),
+    ("trace_block", "Returns the flat traces of every transaction in a block."),
+    ("trace_filter", "Returns the flat traces matching a block range and sender/recipient addresses."),
+    ("trace_call", "Executes a call without creating a transaction and returns the requested traces."),
+];
+
+/// Resolves from the builtin descriptions and the spec's params, or the
+/// spec extension's for methods it defines.
+#[derive(Debug, Default)]
+pub struct SpecResolver {
+    pub extension: Option<&'static SpecExtension>,
+}
+
+#[async_trait]
+impl MethodResolver for SpecResolver {
+    async fn resolve(&self, method: &str) -> MethodMeta {
+        let extended = self.extension.and_then(|e| e.methods.iter().find(|m| m.name == method));
+        let description = match (extended, self.extension) {
+            (Some(_), Some(extension)) => extension.description(method),
+            _ => DESCRIPTIONS.iter().find(|(name, _)| *name == method).map(|(_, d)| *d),
+        };
+        let description = description.map(str::to_string);
+        let params = extended
+            .or_else(|| spec::find(method))
+            .map(|s| s.params.iter().enumerate().map(|(i, p)| (p.to_string(), s.help(i).map(str::to_string))).collect())
+            .unwrap_or_default();
+        // Extension schemas may refer to the standard components
+        let result = match (extended, self.extension) {
+            (Some(_), Some(extension)) => extension.result_schema(method).map(|s| schematree::bundled().simplify(s)),
+            _ => schematree::bundled().tree(method),
+        };
+        MethodMeta { description, params, result }
+    }
+}
+
+/// Per-method metadata cache, filled in the background for whichever
+/// method is selected.
+///
+/// Only the latest selection is hydrated: selecting another method aborts
+/// the lookup still running for the previous one, so arrowing through the
+/// list never queues up work.
+pub struct Hydrator {
+    resolver: Arc<dyn MethodResolver>,
+    cache: Arc<Mutex<HashMap<String, MethodMeta>>>,
+    pending: Option<(String, JoinHandle<()>)>,
+}
+
+impl Hydrator {
+    pub fn new(resolver: Arc<dyn MethodResolver>) -> Self {
+        Hydrator { resolver, cache: Arc::default(), pending: None }
+    }
+
+    /// Notes that `method` is selected, starting a lookup unless it is
+    /// cached or already being looked up. Cheap enough to call every frame.
+    pub fn select(&mut self, method: &str) {
+        if self.pending.as_ref().is_some_and(|(m, h)| m == method && !h.is_finished()) {
+            return;
+        }
+        if let Some((_, handle)) = self.pending.take() {
+            handle.abort();
+        }
+        if self.cache.lock().unwrap().contains_key(method) {
+            return;
+        }
+        let (resolver, cache, name) = (self.resolver.clone(), self.cache.clone(), method.to_string());
+        let handle = tokio::spawn(async move {
+            let meta = resolver.resolve(&name).await;
+            cache.lock().unwrap().insert(name, meta);
+        });
+        self.pending = Some((method.to_string(), handle));
+    }
+
+    /// Cached metadata for `method`; `None` until its lookup has finished.
+    pub fn get(&self, method: &str) -> Option<MethodMeta> {
+        self.cache.lock().unwrap().get(method).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Takes a while per lookup and records the lookups that completed.
+    #[derive(Default)]
+    struct SlowResolver {
+        completed: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl MethodResolver for SlowResolver {
+        async fn resolve(&self, method: &str) -> MethodMeta {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            self.completed.lock().unwrap().push(method.to_string());
+            MethodMeta { description: Some(format!("about {}", method)), ..Default::default() }
+        }
+    }
+
+    #[tokio::test]
+    async fn rapid_selection_only_hydrates_the_latest() {
+        let resolver = Arc::new(SlowResolver::default());
+        let mut hydrator = Hydrator::new(resolver.clone());
+        for method in ["eth_blockNumber", "eth_getBalance", "eth_gasPrice", "eth_call"] {
+            hydrator.select(method);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        // Re-selecting the pending method doesn't restart it
+        hydrator.select("eth_call");
+        assert_eq!(hydrator.get("eth_call"), None);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(*resolver.completed.lock().unwrap(), vec!["eth_call"]);
+        assert_eq!(hydrator.get("eth_call").unwrap().description.as_deref(), Some("about eth_call"));
+        assert_eq!(hydrator.get("eth_getBalance"), None);
+
+        // Cached methods are served without another lookup
+        hydrator.select("eth_call");
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(resolver.completed.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn spec_resolver_includes_param_help() {
+        let meta = SpecResolver::default().resolve("eth_getBalance").await;
+        assert!(meta.description.unwrap().contains("balance"));
+        assert_eq!(meta.params[0].0, "address");
+        assert!(meta.params[1].1.as_deref().unwrap().contains("latest"));
+        assert_eq!(meta.result.unwrap().ty, "uint");
+        assert_eq!(SpecResolver::default().resolve("web3_unknown").await, MethodMeta::default());
+    }
+
+    #[tokio::test]
+    async fn extension_methods_use_the_extension_schema() {
+        let resolver = SpecResolver { extension: crate::extension::bundled().iter().find(|e| e.name == "optimism") };
+        let output = resolver.resolve("optimism_outputAtBlock").await.result.unwrap();
+        assert_eq!(output.children.iter().find(|c| c.name == "outputRoot").unwrap().ty, "bytes32");
+        assert_eq!(resolver.resolve("optimism_syncStatus").await.result, None);
+    }
+}
@@ -0,0 +1,329 @@
+// src/editor.rs
+
+use unicode_width::UnicodeWidthChar;
+
+/// Number of columns a tab advances to when rendered.
+pub const TAB_WIDTH: usize = 4;
+
+/// A multi-line text buffer with a cursor and a vertical scroll offset.
+///
+/// Positions are `(row, char index)` so that editing never splits a
+/// multi-byte character; use [`display_column`] to turn a char index into
+/// a terminal column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextArea {
+    /// Buffer contents, one entry per line (never empty).
+    pub lines: Vec<String>,
+    /// Cursor position as (row, char index within the row).
+    pub cursor: (usize, usize),
+    /// Index of the first visible row.
+    pub scroll: usize,
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl TextArea {
+    /// Creates a buffer holding `text`, with the cursor at the end.
+    pub fn new(text: &str) -> Self {
+        let lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        TextArea { lines, cursor: (row, col), scroll: 0 }
+    }
+
+    /// Returns the buffer contents joined with newlines.
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Inserts `c` at the cursor; `'\n'` splits the line.
+    pub fn insert_char(&mut self, c: char) {
+        if c == '\n' {
+            self.insert_newline();
+            return;
+        }
+        let (row, col) = self.cursor;
+        let at = byte_index(&self.lines[row], col);
+        self.lines[row].insert(at, c);
+        self.cursor.1 += 1;
+    }
+
+    /// Splits the current line at the cursor.
+    pub fn insert_newline(&mut self) {
+        let (row, col) = self.cursor;
+        let at = byte_index(&self.lines[row], col);
+        let rest = self.lines[row].split_off(at);
+        self.lines.insert(row + 1, rest);
+        self.cursor = (row + 1, 0);
+    }
+
+    /// Deletes the character before the cursor, joining lines at column 0.
+    pub fn backspace(&mut self) {
+        let (row, col) = self.cursor;
+        if col > 0 {
+            let at = byte_index(&self.lines[row], col - 1);
+            self.lines[row].remove(at);
+            self.cursor.1 -= 1;
+        } else if row > 0 {
+            let line = self.lines.remove(row);
+            let prev_len = self.lines[row - 1].chars().count();
+            self.lines[row - 1].push_str(&line);
+            self.cursor = (row - 1, prev_len);
+        }
+    }
+
+    /// Deletes the character under the cursor, joining lines at end of line.
+    pub fn delete(&mut self) {
+        let (row, col) = self.cursor;
+        if col < self.lines[row].chars().count() {
+            let at = byte_index(&self.lines[row], col);
+            self.lines[row].remove(at);
+        } else if row + 1 < self.lines.len() {
+            let next = self.lines.remove(row + 1);
+            self.lines[row].push_str(&next);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        let (row, col) = self.cursor;
+        if col > 0 {
+            self.cursor.1 -= 1;
+        } else if row > 0 {
+            self.cursor = (row - 1, self.lines[row - 1].chars().count());
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        let (row, col) = self.cursor;
+        if col < self.lines[row].chars().count() {
+            self.cursor.1 += 1;
+        } else if row + 1 < self.lines.len() {
+            self.cursor = (row + 1, 0);
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.cursor.0 > 0 {
+            self.cursor.0 -= 1;
+            self.clamp_col();
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.cursor.0 + 1 < self.lines.len() {
+            self.cursor.0 += 1;
+            self.clamp_col();
+        }
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor.1 = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor.1 = self.lines[self.cursor.0].chars().count();
+    }
+
+    /// Adjusts `scroll` so the cursor row is inside a viewport of `height` rows.
+    pub fn ensure_visible(&mut self, height: usize) {
+        let row = self.cursor.0;
+        if height == 0 {
+            return;
+        }
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + height {
+            self.scroll = row + 1 - height;
+        }
+    }
+
+    /// Maps a buffer position to (x, y) inside a viewport of `height` rows,
+    /// or `None` when the row is scrolled out of view.
+    pub fn to_viewport(&self, pos: (usize, usize), height: usize) -> Option<(u16, u16)> {
+        let (row, col) = pos;
+        if row < self.scroll || row >= self.scroll + height {
+            return None;
+        }
+        let x = display_column(self.lines.get(row)?, col);
+        Some((x as u16, (row - self.scroll) as u16))
+    }
+
+    fn clamp_col(&mut self) {
+        let len = self.lines[self.cursor.0].chars().count();
+        self.cursor.1 = self.cursor.1.min(len);
+    }
+}
+
+/// Cursor of a single-line input that lives in a plain `String`, as a char
+/// index. Unset means the end, so a value put in the field from elsewhere
+/// (a reloaded request, a default) leaves the cursor after it; a stale
+/// index is clamped to the text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineCursor(Option<usize>);
+
+impl LineCursor {
+    /// Char index of the cursor in `text`.
+    pub fn index(self, text: &str) -> usize {
+        let len = text.chars().count();
+        self.0.map_or(len, |i| i.min(len))
+    }
+
+    fn set(&mut self, text: &str, index: usize) {
+        self.0 = (index < text.chars().count()).then_some(index);
+    }
+
+    /// Back to the end of whatever the field holds.
+    pub fn reset(&mut self) {
+        self.0 = None;
+    }
+
+    pub fn insert(&mut self, text: &mut String, c: char) {
+        let at = self.index(text);
+        text.insert(byte_index(text, at), c);
+        self.set(text, at + 1);
+    }
+
+    /// Deletes the character before the cursor.
+    pub fn backspace(&mut self, text: &mut String) {
+        let at = self.index(text);
+        if at > 0 {
+            text.remove(byte_index(text, at - 1));
+            self.set(text, at - 1);
+        }
+    }
+
+    /// Deletes the character under the cursor.
+    pub fn delete(&mut self, text: &mut String) {
+        let at = self.index(text);
+        if at < text.chars().count() {
+            text.remove(byte_index(text, at));
+            self.set(text, at);
+        }
+    }
+
+    pub fn move_left(&mut self, text: &str) {
+        let at = self.index(text);
+        self.set(text, at.saturating_sub(1));
+    }
+
+    pub fn move_right(&mut self, text: &str) {
+        let at = self.index(text);
+        self.set(text, at + 1);
+    }
+
+    pub fn move_home(&mut self, text: &str) {
+        self.set(text, 0);
+    }
+
+    pub fn move_end(&mut self) {
+        self.0 = None;
+    }
+}
+
+/// Byte offset of the `char_idx`-th character of `line` (or its length past the end).
+pub fn byte_index(line: &str, char_idx: usize) -> usize {
+    line.char_indices().nth(char_idx).map(|(b, _)| b).unwrap_or(line.len())
+}
+
+/// Terminal column at which the `char_idx`-th character of `line` starts,
+/// expanding tabs to the next multiple of [`TAB_WIDTH`] and counting wide
+/// characters as two columns.
+pub fn display_column(line: &str, char_idx: usize) -> usize {
+    line.chars().take(char_idx).fold(0, |col, c| match c {
+        '\t' => (col / TAB_WIDTH + 1) * TAB_WIDTH,
+        c => col + c.width().unwrap_or(0),
+    })
+}
+
+/// Replaces tabs with spaces exactly as [`display_column`] counts them.
+pub fn expand_tabs(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut col = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let next = (col / TAB_WIDTH + 1) * TAB_WIDTH;
+            out.extend(std::iter::repeat_n(' ', next - col));
+            col = next;
+        } else {
+            out.push(c);
+            col += c.width().unwrap_or(0);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_cursor_edits_mid_string() {
+        let (mut text, mut cursor) = ("eth_gtBalnce".to_string(), LineCursor::default());
+        assert_eq!(cursor.index(&text), 12);
+        (0..7).for_each(|_| cursor.move_left(&text));
+        cursor.insert(&mut text, 'e');
+        assert_eq!((text.as_str(), cursor.index(&text)), ("eth_getBalnce", 6));
+        (0..4).for_each(|_| cursor.move_right(&text));
+        cursor.insert(&mut text, 'a');
+        assert_eq!(text, "eth_getBalance");
+        cursor.move_home(&text);
+        cursor.delete(&mut text);
+        cursor.backspace(&mut text);
+        assert_eq!((text.as_str(), cursor.index(&text)), ("th_getBalance", 0));
+        // Multi-byte characters are never split
+        cursor.move_end();
+        cursor.insert(&mut text, 'é');
+        cursor.move_left(&text);
+        cursor.backspace(&mut text);
+        assert_eq!(text, "th_getBalancé");
+        // Text replaced from elsewhere: a stale index clamps, the end follows
+        text = "0x".to_string();
+        assert_eq!(cursor.index(&text), 2);
+        cursor.move_right(&text);
+        cursor.insert(&mut text, '1');
+        assert_eq!((text.as_str(), cursor.index(&text)), ("0x1", 3));
+    }
+
+    #[test]
+    fn editing_across_lines() {
+        let mut area = TextArea::new("[1,\n2]");
+        assert_eq!(area.cursor, (1, 2));
+        area.move_home();
+        area.backspace();
+        assert_eq!(area.text(), "[1,2]");
+        assert_eq!(area.cursor, (0, 3));
+        area.insert_char('\n');
+        area.insert_char(' ');
+        assert_eq!(area.lines, vec!["[1,", " 2]"]);
+    }
+
+    #[test]
+    fn multibyte_insert_and_delete() {
+        let mut area = TextArea::new("\"é\"");
+        area.move_left();
+        area.backspace();
+        area.insert_char('ü');
+        assert_eq!(area.text(), "\"ü\"");
+    }
+
+    #[test]
+    fn display_column_handles_tabs_and_wide_chars() {
+        assert_eq!(display_column("\tx", 1), 4);
+        assert_eq!(display_column("ab\tx", 3), 4);
+        assert_eq!(display_column("日本x", 2), 4);
+        assert_eq!(expand_tabs("a\tb"), "a   b");
+    }
+
+    #[test]
+    fn viewport_follows_cursor() {
+        let mut area = TextArea::new("0\n1\n2\n3\n4");
+        area.ensure_visible(2);
+        assert_eq!(area.scroll, 3);
+        assert_eq!(area.to_viewport((4, 1), 2), Some((1, 1)));
+        assert_eq!(area.to_viewport((0, 0), 2), None);
+    }
+}
@@ -0,0 +1,181 @@
+// src/endpoint.rs
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::schema;
+use crate::storage::{self, Loaded};
+
+/// Endpoint used when nothing else is configured.
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:8545";
+
+/// A named endpoint that can be referred to instead of typing a full URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointProfile {
+    /// Short name the user types (e.g. "mainnet").
+    pub name: String,
+    /// HTTP(S) URL of the node.
+    pub url: String,
+    /// Shell command printing a token for the node's Authorization header,
+    /// e.g. "get-token staging". Re-run when the token expires or is refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_command: Option<String>,
+    /// Seconds a token from `auth_command` is reused; defaults to
+    /// [`DEFAULT_TTL`](crate::auth::DEFAULT_TTL).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_ttl_secs: Option<u64>,
+    /// Chain the node serves, assumed on switching to the profile until
+    /// the node reports its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    /// WebSocket URL of the same node. New heads are then pushed over it
+    /// rather than polled, unless another local eli holds its connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ws_url: Option<String>,
+}
+
+/// The `endpoints.json` config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointConfig {
+    /// Headers sent with every request, to every endpoint.
+    pub headers: BTreeMap<String, String>,
+    pub profiles: Vec<EndpointProfile>,
+}
+
+impl EndpointConfig {
+    /// `headers` for the HTTP client; fails on a name or value HTTP
+    /// doesn't allow.
+    pub fn header_map(&self) -> Result<HeaderMap> {
+        self.headers
+            .iter()
+            .map(|(name, value)| {
+                let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| anyhow!("invalid header name '{}'", name))?;
+                let value = HeaderValue::from_str(value).map_err(|_| anyhow!("invalid value for header '{}'", name))?;
+                Ok((name, value))
+            })
+            .collect()
+    }
+}
+
+/// Reads the endpoint config; a missing file means no profiles and no
+/// extra headers.
+pub fn load(path: &Path) -> Result<Loaded<EndpointConfig>> {
+    Ok(storage::load_versioned(path, &schema::ENDPOINTS)?.unwrap_or_else(|| Loaded::fresh(EndpointConfig::default())))
+}
+
+/// Checks that `input` parses as an HTTP(S) URL and returns it trimmed.
+pub fn validate_url(input: &str) -> Result<String> {
+    let input = input.trim();
+    let url = Url::parse(input).map_err(|e| anyhow!("invalid endpoint URL '{}': {}", input, e))?;
+    match url.scheme() {
+        "http" | "https" => Ok(input.to_string()),
+        other => bail!("unsupported endpoint scheme '{}' (expected http or https)", other),
+    }
+}
+
+/// Resolves user input to an endpoint URL.
+///
+/// An exact profile name wins over URL parsing, so a profile can shadow
+/// anything; otherwise the input must be a valid HTTP(S) URL.
+pub fn resolve_endpoint(input: &str, profiles: &[EndpointProfile]) -> Result<String> {
+    let input = input.trim();
+    if input.is_empty() {
+        bail!("endpoint is empty");
+    }
+    if let Some(profile) = profiles.iter().find(|p| p.name == input) {
+        return Ok(profile.url.clone());
+    }
+    validate_url(input).map_err(|e| anyhow!("{} (and no profile named '{}')", e, input))
+}
+
+/// `url` with anything that may carry a secret (credentials, path, query)
+/// replaced by "…", e.g. `https://eth-mainnet.example.com/…`.
+pub fn redact_url(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url) else { return "…".to_string() };
+    let origin = parsed.origin().ascii_serialization();
+    let bare = parsed.username().is_empty()
+        && parsed.password().is_none()
+        && matches!(parsed.path(), "" | "/")
+        && parsed.query().is_none();
+    if bare { origin } else { format!("{}/…", origin) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profiles() -> Vec<EndpointProfile> {
+        vec![
+            EndpointProfile { name: "local".into(), url: "http://127.0.0.1:8545".into(), ..Default::default() },
+            EndpointProfile {
+                name: "https://shadowed.example".into(),
+                url: "http://10.0.0.1:8545".into(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn resolves_profile_name() {
+        let url = resolve_endpoint("local", &profiles()).unwrap();
+        assert_eq!(url, "http://127.0.0.1:8545");
+    }
+
+    #[test]
+    fn resolves_plain_url() {
+        let url = resolve_endpoint(" https://rpc.example.org ", &profiles()).unwrap();
+        assert_eq!(url, "https://rpc.example.org");
+    }
+
+    #[test]
+    fn profile_name_takes_precedence_over_url() {
+        let url = resolve_endpoint("https://shadowed.example", &profiles()).unwrap();
+        assert_eq!(url, "http://10.0.0.1:8545");
+    }
+
+    #[test]
+    fn rejects_unknown_name_and_bad_scheme() {
+        assert!(resolve_endpoint("mainnet", &profiles()).is_err());
+        assert!(resolve_endpoint("ftp://example.org", &profiles()).is_err());
+        assert!(resolve_endpoint("   ", &profiles()).is_err());
+    }
+
+    #[test]
+    fn redacts_keys_in_paths_queries_and_credentials() {
+        assert_eq!(redact_url("http://localhost:8545"), "http://localhost:8545");
+        assert_eq!(redact_url("https://mainnet.infura.io/v3/abc123"), "https://mainnet.infura.io/…");
+        assert_eq!(redact_url("https://node.example?key=abc"), "https://node.example/…");
+        assert_eq!(redact_url("https://user:pw@node.example"), "https://node.example/…");
+        assert_eq!(redact_url("archive"), "…");
+    }
+
+    #[test]
+    fn endpoint_config_reads_headers_and_auth_commands() {
+        let dir = std::env::temp_dir().join(format!("eli-endpoints-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("endpoints.json");
+        assert_eq!(load(&path).unwrap().value, EndpointConfig::default());
+
+        std::fs::write(
+            &path,
+            r#"{"headers": {"X-Team": "infra"},
+                "profiles": [{"name": "staging", "url": "https://staging.example", "auth_command": "get-token staging"}]}"#,
+        )
+        .unwrap();
+        let config = load(&path).unwrap().value;
+        assert_eq!(config.headers["X-Team"], "infra");
+        assert_eq!(config.profiles[0].auth_command.as_deref(), Some("get-token staging"));
+        assert_eq!(config.profiles[0].auth_ttl_secs, None);
+        assert_eq!(config.header_map().unwrap()["x-team"], "infra");
+
+        let bad = EndpointConfig { headers: BTreeMap::from([("X Team".into(), "infra".into())]), ..Default::default() };
+        assert_eq!(bad.header_map().unwrap_err().to_string(), "invalid header name 'X Team'");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,53 +1,211 @@
 // src/events.rs
 
-use crate::app::{App, AppMode};
+use std::time::Instant;
+
+use crate::action::{dispatch, Action};
+use crate::app::{App, AppMode, HistoryAction, InputField};
+use crate::clipboard::Payload;
+use crate::docs::DocsTab;
+use crate::editor::{LineCursor, TextArea};
+use crate::focus::Focus;
+use crate::follow::Link;
+use crate::popup::{EditorPopup, PopupTarget};
+use crate::prefs::SETTING_COUNT;
+use crate::raw::RawParams;
+use crate::spec::ParamKind;
+use crate::txform::{self, TxForm};
+use crate::undo::EditKind;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::ListState;
+
+/// Esc ends a tutorial session from any mode, popups included. Returns
+/// whether the key was consumed.
+pub fn handle_tutorial_exit(app: &mut App, key: KeyEvent) -> bool {
+    if app.tutorial.is_none() || key.code != KeyCode::Esc {
+        return false;
+    }
+    app.should_quit = true;
+    true
+}
 
 /// Handle key events in Main mode:
 /// - Ctrl+C: quit
-/// - Character keys: append to search_input and filter methods
-/// - Backspace: remove last char and filter methods
-/// - Arrow keys: navigate filtered_methods list
-/// - Enter: switch to ParamInput mode and initialize param_inputs
-/// - 'h': switch to History mode
+/// - Tab/Shift+Tab: focus the next/previous pane: search, method list, docs
+/// - Character keys: insert into search_input at the cursor and filter methods,
+///   focusing the search
+/// - Backspace/Delete: remove the char before/under the cursor and filter methods
+/// - Left/Right: move the search cursor; Home/End too while there is a search
+/// - Up/Down, PageUp/PageDown, Home/End: navigate filtered_methods list, or
+///   scroll the docs panel while it has focus
+/// - Left/Right on the docs panel: switch between docs and the result schema
+/// - Enter: switch to ParamInput mode and initialize param_inputs, with configured defaults
+/// - 'h' (with an empty search): switch to History mode
+/// - ',': open the display settings popup
+/// - Ctrl+P: probe whether the session endpoint serves the highlighted method
+/// - Ctrl+D: toggle dry-run mode
+/// - 'D' (with an empty search): open the dashboard
+/// - Esc: drop requests queued behind the one in flight, else cancel the
+///   one in flight, else clear the search, else quit (the method list is
+///   the bottom of the Esc stack)
+/// - Ctrl+^ or '-' (with an empty search): open the previous method's params
+/// - Ctrl+E: change the session endpoint (Enter switches, Esc cancels)
+/// - 'A' (with an empty search): open the background activity popup
+/// - 'P' (with an empty search): pick an endpoint profile to switch to
+/// - '[' / ']' on the schema tab: show fewer/more levels of the schema
+/// - '?': list this mode's keys
 pub async fn handle_main_mode(app: &mut App, key: KeyEvent) {
+    if let Some(input) = app.endpoint_edit.as_mut() {
+        match key.code {
+            KeyCode::Esc => app.endpoint_edit = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => input.push(c),
+            KeyCode::Enter => {
+                let input = input.clone();
+                match app.set_endpoint(&input) {
+                    Ok(()) => {
+                        app.endpoint_edit = None;
+                        app.show_toast("switched endpoint");
+                    }
+                    // Keep the prompt open so the input can be fixed
+                    Err(e) => app.show_toast(format!("{:#}", e)),
+                }
+            }
+            _ => {}
+        }
+        return;
+    }
     match key {
         // Ctrl+C to quit
         KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
-            app.should_quit = true;
+            app.request_quit();
+        }
+        // Ctrl+D toggles dry-run
+        KeyEvent { code: KeyCode::Char('d'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.dry_run = !app.dry_run;
+        }
+        // Ctrl+P probes the highlighted method
+        KeyEvent { code: KeyCode::Char('p'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            dispatch(app, Action::ProbeMethod).await;
+        }
+        // Ctrl+E opens the endpoint prompt with the current one to edit
+        KeyEvent { code: KeyCode::Char('e'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.endpoint_edit = Some(app.endpoint.clone());
+        }
+        // ',' opens settings (method names never contain a comma)
+        KeyEvent { code: KeyCode::Char(','), .. } => {
+            app.settings = Some(0);
+        }
+        // '?' lists the keys, likewise
+        KeyEvent { code: KeyCode::Char('?'), .. } => {
+            app.help = Some(app.key_context());
+        }
+        // 'h' goes to History mode unless the user is typing a search
+        KeyEvent { code: KeyCode::Char('h'), modifiers: KeyModifiers::NONE, .. } if app.search_input.is_empty() => {
+            app.enter(AppMode::History);
+        }
+        // Esc clears the send queue, cancels the send in flight, clears the
+        // search, then quits
+        KeyEvent { code: KeyCode::Esc, .. } => {
+            let dropped = app.sends.clear();
+            if dropped > 0 {
+                app.show_toast(format!("dropped {} queued request(s)", dropped));
+            } else if app.cancel_send() {
+                // toast shown
+            } else if !app.search_input.is_empty() {
+                app.search_input.clear();
+                app.search_cursor.reset();
+                app.filter_methods();
+            } else {
+                app.request_quit();
+            }
+        }
+        // Ctrl+^ (or '-' when not searching) swaps to the previous method
+        KeyEvent { code: KeyCode::Char('^' | '6'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.switch_to_previous_method();
+        }
+        KeyEvent { code: KeyCode::Char('-'), .. } if app.search_input.is_empty() => {
+            app.switch_to_previous_method();
+        }
+        // 'D' opens the dashboard unless the user is typing a search
+        KeyEvent { code: KeyCode::Char('D'), .. } if app.search_input.is_empty() => {
+            app.enter(AppMode::Dashboard);
+        }
+        // 'A' lists background activity, likewise
+        KeyEvent { code: KeyCode::Char('A'), .. } if app.search_input.is_empty() => {
+            app.activity = Some(0);
+        }
+        // 'P' picks an endpoint profile, likewise
+        KeyEvent { code: KeyCode::Char('P'), .. } if app.search_input.is_empty() => {
+            app.open_profile_picker();
+        }
+        // Tab and Shift+Tab move between the panes
+        KeyEvent { code: KeyCode::Tab, .. } => app.cycle_pane(1),
+        KeyEvent { code: KeyCode::BackTab, .. } => app.cycle_pane(-1),
+        // On the docs panel, Left/Right flip between docs and result schema
+        // and the navigation keys scroll it
+        KeyEvent { code: KeyCode::Left | KeyCode::Right, .. } if app.pane() == Some(Focus::Docs) => {
+            app.docs_tab = match app.docs_tab {
+                DocsTab::Docs => DocsTab::Schema,
+                DocsTab::Schema => DocsTab::Docs,
+            };
+            app.pane_scroll = 0;
         }
-        // Printable characters add to search input
+        KeyEvent { code: KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End, .. }
+            if app.pane() == Some(Focus::Docs) =>
+        {
+            scroll_pane(app, key.code);
+        }
+        // '[' / ']' fold and unfold the schema (method names have no brackets)
+        KeyEvent { code: KeyCode::Char('['), .. } if app.docs_tab == DocsTab::Schema => {
+            app.schema_depth = app.schema_depth.saturating_sub(1).max(1);
+        }
+        KeyEvent { code: KeyCode::Char(']'), .. } if app.docs_tab == DocsTab::Schema => {
+            app.schema_depth += 1;
+        }
+        // Printable characters go into the search at the cursor, from any pane
         KeyEvent { code: KeyCode::Char(c), modifiers: KeyModifiers::NONE, .. } if !c.is_control() => {
-            app.search_input.push(c);
+            app.focus_pane(Focus::Search);
+            app.search_cursor.insert(&mut app.search_input, c);
             app.filter_methods();
         }
-        // Backspace deletes last character
         KeyEvent { code: KeyCode::Backspace, .. } => {
-            app.search_input.pop();
+            app.focus_pane(Focus::Search);
+            app.search_cursor.backspace(&mut app.search_input);
             app.filter_methods();
         }
-        // Navigate up in the filtered methods list
-        KeyEvent { code: KeyCode::Up, .. } => {
-            let i = app.methods_state.selected().unwrap_or(0);
-            if i > 0 {
-                app.methods_state.select(Some(i - 1));
-            }
+        KeyEvent { code: KeyCode::Delete, .. } => {
+            app.focus_pane(Focus::Search);
+            app.search_cursor.delete(&mut app.search_input);
+            app.filter_methods();
         }
-        // Navigate down in the filtered methods list
-        KeyEvent { code: KeyCode::Down, .. } => {
-            let i = app.methods_state.selected().unwrap_or(0);
-            if i + 1 < app.filtered_methods.len() {
-                app.methods_state.select(Some(i + 1));
-            }
+        // In the search, Left/Right move the cursor, Home/End too while
+        // there is a search to move in
+        KeyEvent { code: KeyCode::Left | KeyCode::Right, .. } if app.pane() == Some(Focus::Search) => {
+            move_cursor(&mut app.search_cursor, &app.search_input, key.code);
+        }
+        KeyEvent { code: KeyCode::Home | KeyCode::End, .. } if app.pane() == Some(Focus::Search) && !app.search_input.is_empty() => {
+            move_cursor(&mut app.search_cursor, &app.search_input, key.code);
+        }
+        // Navigate the filtered methods list
+        KeyEvent { code: KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End, .. } => {
+            let step = list_step(key.code, app.list_height);
+            move_selection(&mut app.methods_state, app.filtered_methods.len(), step);
+            app.pane_scroll = 0;
         }
         // Enter to go to ParamInput mode
         KeyEvent { code: KeyCode::Enter, .. } => {
-            app.param_inputs = vec!["".to_string(), "".to_string()];
-            app.mode = AppMode::ParamInput;
-        }
-        // 'h' goes to History mode
-        KeyEvent { code: KeyCode::Char('h'), modifiers: KeyModifiers::NONE, .. } => {
-            app.mode = AppMode::History;
+            let Some(method) = app.selected_method().map(str::to_string) else { return };
+            app.track_param_method(&method);
+            app.param_inputs = app.blank_params(&method);
+            app.focused_param = 0;
+            app.tx_form = (app.selected_method() == Some(txform::METHOD)).then(TxForm::default);
+            app.seeded_defaults = Vec::new();
+            if app.tx_form.is_none() {
+                app.apply_param_defaults();
+            }
+            app.enter(AppMode::ParamInput);
         }
         _ => {}
     }
@@ -55,35 +213,338 @@ pub async fn handle_main_mode(app: &mut App, key: KeyEvent) {
 
 /// Handle key events in ParamInput mode:
 /// - Ctrl+C: quit
-/// - Esc: return to Main mode
-/// - Enter: send request & return to Main mode
-/// - Character keys: append to first parameter
-/// - Backspace: remove last char from first parameter
+/// - Esc: go back to where the method was opened from
+/// - Enter: send request & return to Main mode (inserts a newline in raw mode)
+/// - Space/Enter on a boolean param: flip it
+/// - Ctrl+S: send request (any editing mode)
+/// - Ctrl+O: show/hide the one-off endpoint override field
+/// - Ctrl+R: switch between per-field and raw-JSON params editing
+/// - Character keys: insert at the cursor of the override field when shown, else the focused parameter;
+///   the first one typed into a field still holding its default replaces it
+/// - Backspace/Delete: remove the char before/under the cursor in the same field
+/// - Left/Right/Home/End: move the cursor in the same field
+/// - Tab/Shift+Tab: move focus to the next/previous parameter, from the last
+///   or first one to the params preview; arrows scroll the preview while it has focus
+/// - In raw mode: arrows/Home/End move the cursor, Tab inserts a tab, Delete deletes forward
+/// - Ctrl+Enter or F4: edit the focused param in a larger popup
+/// - Ctrl+D: toggle dry-run mode
+/// - Ctrl+B: accept the suggested getBlockByNumber/getBlockByHash switch
+/// - Transaction form: Tab/Down and Shift+Tab/Up move between fields (Tab on to the preview), Ctrl+N fills the nonce
+/// - Ctrl+^: switch to the previous method, keeping this one's fields as a draft
+/// - F1, or '?' in an empty field: list the keys of the current editor
+/// - Ctrl+Z: undo the last edit of the focused field (the whole buffer in raw mode);
+///   Ctrl+Y or Ctrl+Shift+Z: redo it. Runs of typing or deleting undo as one step
 pub async fn handle_param_input_mode(app: &mut App, key: KeyEvent) {
+    if app.editor_popup.is_some() {
+        return handle_editor_popup(app, key);
+    }
     match key {
         // Ctrl+C to quit
         KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
-            app.should_quit = true;
+            app.request_quit();
+        }
+        // Ctrl+D toggles dry-run
+        KeyEvent { code: KeyCode::Char('d'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.dry_run = !app.dry_run;
+        }
+        // Ctrl+O toggles the endpoint override field
+        KeyEvent { code: KeyCode::Char('o'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.endpoint_override = match app.endpoint_override {
+                Some(_) => None,
+                None => Some(String::new()),
+            };
+            app.input_cursor.reset();
+        }
+        // Ctrl+R toggles raw-JSON editing
+        KeyEvent { code: KeyCode::Char('r'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.notice = app.toggle_raw_params().err().map(|e| e.to_string());
+        }
+        // Ctrl+^ swaps to the previous method
+        KeyEvent { code: KeyCode::Char('^' | '6'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.switch_to_previous_method();
         }
-        // Esc to return to Main mode
+        // Ctrl+S sends from any editing mode
+        KeyEvent { code: KeyCode::Char('s'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            dispatch(app, Action::Send).await;
+        }
+        // Esc goes back one level, dropping the unsent edits
         KeyEvent { code: KeyCode::Esc, .. } => {
-            app.mode = AppMode::Main;
+            app.back();
+        }
+        // Ctrl+Enter / F4 opens the focused param in the popup editor
+        KeyEvent { code: KeyCode::F(4), .. } | KeyEvent { code: KeyCode::Enter, modifiers: KeyModifiers::CONTROL, .. }
+            if app.raw_params.is_none() && app.endpoint_override.is_none() =>
+        {
+            app.editor_popup = Some(match &app.tx_form {
+                Some(form) => {
+                    let (label, _, _) = txform::FIELDS[form.focus];
+                    EditorPopup::new(label, &form.fields[form.focus], PopupTarget::TxField(form.focus))
+                }
+                None => {
+                    let i = app.focused_param;
+                    let value = app.param_inputs.get(i).cloned().unwrap_or_default();
+                    EditorPopup::new(app.param_label(i), &value, PopupTarget::Param(i))
+                }
+            });
+        }
+        // Ctrl+Z undoes, Ctrl+Y / Ctrl+Shift+Z redo
+        KeyEvent { code: KeyCode::Char(c @ ('z' | 'Z' | 'y')), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            let redo = c == 'y' || c == 'Z' || modifiers.contains(KeyModifiers::SHIFT);
+            if !app.undo_edit(redo) {
+                app.show_toast(if redo { "nothing to redo" } else { "nothing to undo" });
+            }
+        }
+        // F1 lists the keys; so does '?', where it can't be the start of a value
+        KeyEvent { code: KeyCode::F(1), .. } => {
+            app.help = Some(app.key_context());
+        }
+        KeyEvent { code: KeyCode::Char('?'), .. }
+            if app.raw_params.is_none() && focused_input(app).is_some_and(|(field, _)| field.is_empty()) =>
+        {
+            app.help = Some(app.key_context());
         }
-        // Enter also returns to Main mode
+        // Raw mode owns all remaining editing keys unless the override field is shown
+        _ if app.raw_params.is_some() && app.endpoint_override.is_none() => {
+            if let Some(raw) = &mut app.raw_params {
+                let before = raw.area.clone();
+                edit_raw_params(raw, key);
+                match edit_kind(key) {
+                    Some(kind) => app.raw_undo.record(before, &raw.area, kind),
+                    None => app.raw_undo.seal(),
+                }
+            }
+        }
+        // Space or Enter flips a focused boolean param
+        KeyEvent { code: KeyCode::Enter | KeyCode::Char(' '), modifiers: KeyModifiers::NONE, .. }
+            if app.tx_form.is_none()
+                && app.endpoint_override.is_none()
+                && app.pane() == Some(Focus::Fields)
+                && app.param_kind(app.focused_param) != ParamKind::Text =>
+        {
+            let field = app.focused_field();
+            let before = app.param_inputs.get(app.focused_param).cloned().unwrap_or_default();
+            if app.flip_focused_toggle() {
+                app.record_edit(field, before, EditKind::Replace);
+            }
+        }
+        // Enter sends the request and returns to Main mode
         KeyEvent { code: KeyCode::Enter, .. } => {
-            app.mode = AppMode::Main;
+            dispatch(app, Action::Send).await;
         }
-        // Printable characters: append to first parameter
-        KeyEvent { code: KeyCode::Char(c), modifiers: KeyModifiers::NONE, .. } if !c.is_control() => {
-            if let Some(field) = app.param_inputs.get_mut(0) {
-                field.push(c);
+        // Ctrl+N asks the node for the pending nonce of the form's sender
+        KeyEvent { code: KeyCode::Char('n'), modifiers, .. }
+            if modifiers.contains(KeyModifiers::CONTROL) && app.tx_form.is_some() =>
+        {
+            dispatch(app, Action::FillNonce).await;
+        }
+        // Tab/Shift+Tab step through the params or form fields and on to
+        // the preview
+        KeyEvent { code: KeyCode::Tab, .. } if app.endpoint_override.is_none() => tab_through_fields(app, 1),
+        KeyEvent { code: KeyCode::BackTab, .. } if app.endpoint_override.is_none() => tab_through_fields(app, -1),
+        // The navigation keys scroll the preview while it has focus
+        KeyEvent { code: KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End, .. }
+            if app.pane() == Some(Focus::Preview) =>
+        {
+            scroll_pane(app, key.code);
+        }
+        // Up/Down move between transaction form fields
+        KeyEvent { code: KeyCode::Down | KeyCode::Up, .. } if app.endpoint_override.is_none() => {
+            if let Some(form) = &mut app.tx_form {
+                form.move_focus(if key.code == KeyCode::Down { 1 } else { -1 });
+                app.input_cursor.reset();
+                app.seal_undo();
             }
         }
-        // Backspace: remove last char from first parameter
-        KeyEvent { code: KeyCode::Backspace, .. } => {
-            if let Some(field) = app.param_inputs.get_mut(0) {
-                field.pop();
+        // Printable characters go into the focused field at the cursor
+        KeyEvent { code: KeyCode::Char(c), modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT, .. } if !c.is_control() => {
+            app.focus_pane(Focus::Fields);
+            // Typing over an untouched default replaces it; moving the
+            // cursor first edits it instead
+            let replace = app.endpoint_override.is_none()
+                && app.tx_form.is_none()
+                && app.input_cursor == LineCursor::default()
+                && app.is_default_param(app.focused_param);
+            let target = app.focused_field();
+            if let Some((field, cursor)) = focused_input(app) {
+                let before = field.clone();
+                if replace {
+                    field.clear();
+                }
+                cursor.insert(field, c);
+                app.record_edit(target, before, if replace { EditKind::Replace } else { EditKind::Insert });
+            }
+        }
+        KeyEvent { code: KeyCode::Backspace | KeyCode::Delete, .. } => {
+            app.focus_pane(Focus::Fields);
+            let target = app.focused_field();
+            if let Some((field, cursor)) = focused_input(app) {
+                let before = field.clone();
+                if key.code == KeyCode::Backspace {
+                    cursor.backspace(field);
+                } else {
+                    cursor.delete(field);
+                }
+                app.record_edit(target, before, EditKind::Remove);
+            }
+        }
+        KeyEvent { code: KeyCode::Left | KeyCode::Right | KeyCode::Home | KeyCode::End, .. } => {
+            app.focus_pane(Focus::Fields);
+            if let Some((field, cursor)) = focused_input(app) {
+                move_cursor(cursor, field, key.code);
             }
+            app.seal_undo();
+        }
+        _ => {}
+    }
+}
+
+/// Tab (`step` 1) or Shift+Tab (-1) in ParamInput: to the next or
+/// previous param or form field, from the last or first one to the
+/// preview, and from the preview back onto the first or last.
+fn tab_through_fields(app: &mut App, step: isize) {
+    let (row, rows) = match &app.tx_form {
+        Some(form) => (form.focus, txform::FIELDS.len()),
+        None => (app.focused_param, app.param_inputs.len()),
+    };
+    let edge = if step > 0 { row + 1 >= rows } else { row == 0 };
+    if app.pane() == Some(Focus::Preview) {
+        app.focus_pane(Focus::Fields);
+        let row = if step > 0 { 0 } else { rows.saturating_sub(1) };
+        match &mut app.tx_form {
+            Some(form) => form.focus = row,
+            None => app.focused_param = row,
+        }
+    } else if edge {
+        app.focus_pane(Focus::Preview);
+    } else {
+        match &mut app.tx_form {
+            Some(form) => form.move_focus(step),
+            None => app.move_param_focus(step),
+        }
+    }
+    app.input_cursor.reset();
+    app.seal_undo();
+}
+
+/// Handle key events while the value editor popup is open:
+/// - Ctrl+C: quit
+/// - Ctrl+S: save the value back into its field
+/// - Esc: cancel
+/// - Anything else: edit, with the same keys as raw mode
+fn handle_editor_popup(app: &mut App, key: KeyEvent) {
+    let Some(popup) = app.editor_popup.as_mut() else { return };
+    match key {
+        // Ctrl+C to quit
+        KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.request_quit();
+        }
+        // Ctrl+S saves, collapsed to one line
+        KeyEvent { code: KeyCode::Char('s'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            let value = popup.value();
+            let (field, before) = match popup.target {
+                PopupTarget::Param(i) => {
+                    if app.param_inputs.len() <= i {
+                        app.param_inputs.resize(i + 1, String::new());
+                    }
+                    (InputField::Param(i), std::mem::replace(&mut app.param_inputs[i], value))
+                }
+                PopupTarget::TxField(i) => {
+                    let before = app.tx_form.as_mut().map(|form| std::mem::replace(&mut form.fields[i], value));
+                    (InputField::TxField(i), before.unwrap_or_default())
+                }
+            };
+            // The whole edit made in the popup is one step
+            app.record_edit(field, before, EditKind::Replace);
+            app.editor_popup = None;
+        }
+        // Esc discards the edit
+        KeyEvent { code: KeyCode::Esc, .. } => app.editor_popup = None,
+        _ => {
+            edit_text_area(&mut popup.area, key);
+        }
+    }
+}
+
+/// Applies an editing key to a text buffer; returns whether the text changed.
+fn edit_text_area(area: &mut TextArea, key: KeyEvent) -> bool {
+    match key {
+        KeyEvent { code: KeyCode::Char(c), modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT, .. } if !c.is_control() => {
+            area.insert_char(c);
+        }
+        KeyEvent { code: KeyCode::Enter, .. } => area.insert_newline(),
+        KeyEvent { code: KeyCode::Tab, .. } => area.insert_char('\t'),
+        KeyEvent { code: KeyCode::Backspace, .. } => area.backspace(),
+        KeyEvent { code: KeyCode::Delete, .. } => area.delete(),
+        // Cursor movement doesn't change the text
+        KeyEvent { code: KeyCode::Left, .. } => area.move_left(),
+        KeyEvent { code: KeyCode::Right, .. } => area.move_right(),
+        KeyEvent { code: KeyCode::Up, .. } => area.move_up(),
+        KeyEvent { code: KeyCode::Down, .. } => area.move_down(),
+        KeyEvent { code: KeyCode::Home, .. } => area.move_home(),
+        KeyEvent { code: KeyCode::End, .. } => area.move_end(),
+        _ => return false,
+    }
+    !matches!(
+        key.code,
+        KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down | KeyCode::Home | KeyCode::End
+    )
+}
+
+/// How an editing key's change groups for undo; `None` for keys that
+/// only move the cursor.
+fn edit_kind(key: KeyEvent) -> Option<EditKind> {
+    match key.code {
+        KeyCode::Backspace | KeyCode::Delete => Some(EditKind::Remove),
+        KeyCode::Char(_) | KeyCode::Enter | KeyCode::Tab => Some(EditKind::Insert),
+        _ => None,
+    }
+}
+
+/// Applies an editing key to the raw-JSON buffer and schedules re-validation.
+fn edit_raw_params(raw: &mut RawParams, key: KeyEvent) {
+    if edit_text_area(&mut raw.area, key) {
+        raw.edited(Instant::now());
+    }
+}
+
+/// The text field keystrokes currently go to, with its cursor: the
+/// override when shown, else the focused transaction form row, else the
+/// focused parameter unless it is a toggle.
+fn focused_input(app: &mut App) -> Option<(&mut String, &mut LineCursor)> {
+    // Toggles only flip
+    let toggle = app.param_kind(app.focused_param) != ParamKind::Text;
+    let field = match (&mut app.endpoint_override, &mut app.tx_form) {
+        (Some(field), _) => Some(field),
+        (None, Some(form)) => Some(form.focused_mut()),
+        (None, None) if toggle => None,
+        (None, None) => app.param_inputs.get_mut(app.focused_param),
+    };
+    field.map(|field| (field, &mut app.input_cursor))
+}
+
+/// Moves `cursor` within `text` for Left/Right/Home/End.
+fn move_cursor(cursor: &mut LineCursor, text: &str, code: KeyCode) {
+    match code {
+        KeyCode::Left => cursor.move_left(text),
+        KeyCode::Right => cursor.move_right(text),
+        KeyCode::Home => cursor.move_home(text),
+        KeyCode::End => cursor.move_end(),
+        _ => {}
+    }
+}
+
+/// Handle key events in Dashboard mode:
+/// - Ctrl+C: quit
+/// - Esc / 'D': go back (polling pauses)
+pub async fn handle_dashboard_mode(app: &mut App, key: KeyEvent) {
+    match key {
+        // Ctrl+C to quit
+        KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.request_quit();
+        }
+        // Esc or 'D' closes the dashboard; nothing polls while it's hidden
+        KeyEvent { code: KeyCode::Esc | KeyCode::Char('D'), .. } => {
+            app.back();
         }
         _ => {}
     }
@@ -91,54 +552,419 @@ pub async fn handle_param_input_mode(app: &mut App, key: KeyEvent) {
 
 /// Handle key events in History mode:
 /// - Ctrl+C: quit
-/// - Esc: return to Main mode
-/// - Arrow keys: navigate history list
-/// - Enter: reload selected request into ParamInput mode
+/// - Esc: go back
+/// - Tab/Shift+Tab: focus the entry list or the selected entry's detail
+/// - Arrow keys, PageUp/PageDown, Home/End: navigate history list, or scroll
+///   the detail while it has focus
+/// - Enter/'v': view the selected response in full
+/// - 'r': reload selected request into ParamInput mode
+/// - 'y'/'Y': copy the selected request/response to the clipboard
+/// - ',': open the display settings popup
+/// - Space: mark or unmark the selected entry; 'a': mark every entry
+/// - 'd'/'e': delete/export the marked entries, or the selected one;
+///   'y' confirms, any other key cancels
+/// - '?': list this mode's keys
+///
+/// Marks are cleared once History is closed, not while a response or
+/// request opened from it is on top.
 pub async fn handle_history_mode(app: &mut App, key: KeyEvent) {
+    if app.history_confirm.is_some() {
+        if key.code == KeyCode::Char('y') {
+            app.confirm_history_action();
+        } else {
+            app.history_confirm = None;
+        }
+        return;
+    }
+    match key {
+        // Ctrl+C to quit
+        KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.request_quit();
+        }
+        // ',' opens settings
+        KeyEvent { code: KeyCode::Char(','), .. } => {
+            app.settings = Some(0);
+        }
+        KeyEvent { code: KeyCode::Char('?'), .. } => {
+            app.help = Some(app.key_context());
+        }
+        // Esc goes back one level
+        KeyEvent { code: KeyCode::Esc, .. } => {
+            app.back();
+        }
+        KeyEvent { code: KeyCode::Tab, .. } => app.cycle_pane(1),
+        KeyEvent { code: KeyCode::BackTab, .. } => app.cycle_pane(-1),
+        // Scroll the detail while it has focus, else navigate the list
+        KeyEvent { code: KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End, .. }
+            if app.pane() == Some(Focus::Detail) =>
+        {
+            scroll_pane(app, key.code);
+        }
+        KeyEvent { code: KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End, .. } => {
+            let step = list_step(key.code, app.list_height);
+            move_selection(&mut app.history_state, app.history.len(), step);
+            app.pane_scroll = 0;
+        }
+        // Enter or 'v' opens the full response
+        KeyEvent { code: KeyCode::Enter, .. } | KeyEvent { code: KeyCode::Char('v'), modifiers: KeyModifiers::NONE, .. } => {
+            app.open_selected_response();
+        }
+        // Reload selected history entry
+        KeyEvent { code: KeyCode::Char('r'), modifiers: KeyModifiers::NONE, .. } => {
+            app.reload_history_entry(app.history_state.selected().unwrap_or(0));
+        }
+        KeyEvent { code: KeyCode::Char('y'), modifiers: KeyModifiers::NONE, .. } => app.copy_selected(Payload::Request),
+        KeyEvent { code: KeyCode::Char('Y'), .. } => app.copy_selected(Payload::Response),
+        KeyEvent { code: KeyCode::Char(' '), .. } => app.toggle_history_mark(),
+        KeyEvent { code: KeyCode::Char('a'), modifiers: KeyModifiers::NONE, .. } => app.mark_all_history(),
+        KeyEvent { code: KeyCode::Char('d'), modifiers: KeyModifiers::NONE, .. } => {
+            app.request_history_action(HistoryAction::Delete);
+        }
+        KeyEvent { code: KeyCode::Char('e'), modifiers: KeyModifiers::NONE, .. } => {
+            app.request_history_action(HistoryAction::Export);
+        }
+        _ => {}
+    }
+}
+
+/// How far a list navigation key moves the selection: a row, a page of
+/// `height` visible rows, or the whole list for Home/End.
+fn list_step(code: KeyCode, height: usize) -> isize {
+    let page = height.max(1) as isize;
+    match code {
+        KeyCode::Up => -1,
+        KeyCode::Down => 1,
+        KeyCode::PageUp => -page,
+        KeyCode::PageDown => page,
+        KeyCode::Home => isize::MIN,
+        KeyCode::End => isize::MAX,
+        _ => 0,
+    }
+}
+
+/// Scrolls the focused pane by the step [`list_step`] gives `code`; the
+/// pane is clamped to its content when drawn.
+fn scroll_pane(app: &mut App, code: KeyCode) {
+    let step = list_step(code, app.list_height);
+    app.pane_scroll = (app.pane_scroll as isize).saturating_add(step).clamp(0, u16::MAX as isize) as u16;
+}
+
+/// Moves `state`'s selection by `step`, staying within a list of `len` rows.
+fn move_selection(state: &mut ListState, len: usize, step: isize) {
+    if len == 0 {
+        return;
+    }
+    let i = state.selected().unwrap_or(0);
+    state.select(Some(i.saturating_add_signed(step).min(len - 1)));
+}
+
+/// Handle key events in ResponseView mode:
+/// - Ctrl+C: quit
+/// - Up/Down, PageUp/PageDown: scroll
+/// - Home/End: jump to the top/the last page
+/// - '/': start a search; typing edits the query, Enter runs it and jumps to the first match
+/// - 'n'/'N': next/previous match
+/// - Ctrl+T: toggle case-sensitive search
+/// - ',': open the display settings popup
+/// - Ctrl+Shift+E: export an array result as CSV; typing edits the path, Enter writes it, Esc cancels
+/// - 'F': write the request and response as a test fixture directory in the working directory
+/// - 'L': label the top line's first address; typing edits the label, Enter saves it to the
+///   address book (an empty label removes it), Esc cancels
+/// - 'g': order a flat trace table by gas used, or back to call tree order
+/// - Tab: switch between the response and the provenance of its params
+/// - Enter: in a result spilled to disk, expand the element at the top, or collapse it
+/// - 'x': hex-dump the top line's byte string if longer than a word, else open the receipt's
+///   transaction or the transaction's receipt; 'b': the receipt's block
+/// - 'y'/'Y': copy the response/request to the clipboard
+/// - Esc: cancel the query being typed, else clear the search, else go back
+pub async fn handle_response_view_mode(app: &mut App, key: KeyEvent) {
+    let Some(view) = app.response_view.as_mut() else {
+        app.back();
+        return;
+    };
+    if view.hex.is_some() {
+        handle_hex_dump(app, key);
+        return;
+    }
+    if let Some(path) = view.export_path.as_mut() {
+        match key.code {
+            KeyCode::Esc => view.export_path = None,
+            KeyCode::Backspace => {
+                path.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => path.push(c),
+            KeyCode::Enter => {
+                let path = view.export_path.take().unwrap_or_default();
+                match app.export_csv(&path) {
+                    Ok(rows) => app.show_toast(format!("Exported {} row(s) to {}", rows, path)),
+                    Err(e) => app.show_toast(format!("Export failed: {:#}", e)),
+                }
+            }
+            _ => {}
+        }
+        return;
+    }
+    if let Some((_, label)) = view.label_prompt.as_mut() {
+        match key.code {
+            KeyCode::Esc => view.label_prompt = None,
+            KeyCode::Backspace => {
+                label.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => label.push(c),
+            KeyCode::Enter => {
+                let (address, label) = view.label_prompt.take().unwrap_or_default();
+                match app.label_address(&address, &label) {
+                    Ok(()) if label.is_empty() => app.show_toast(format!("label removed from {}", address)),
+                    Ok(()) => app.show_toast(format!("labelled {} as {}", address, label)),
+                    Err(e) => app.show_toast(format!("label not saved: {:#}", e)),
+                }
+            }
+            _ => {}
+        }
+        return;
+    }
     match key {
         // Ctrl+C to quit
         KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
-            app.should_quit = true;
+            app.request_quit();
+        }
+        // Ctrl+T toggles case sensitivity
+        KeyEvent { code: KeyCode::Char('t'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            view.toggle_case();
+        }
+        // Ctrl+Shift+E prompts for a CSV export path, named after the request
+        KeyEvent { code: KeyCode::Char('e' | 'E'), modifiers, .. }
+            if modifiers.contains(KeyModifiers::CONTROL | KeyModifiers::SHIFT) =>
+        {
+            let name = view.title.split_whitespace().take(2).collect::<Vec<_>>().join("-").replace('#', "");
+            view.export_path = Some(format!("{}.csv", name));
+        }
+        // 'F' writes a fixture directory next to where eli was started
+        KeyEvent { code: KeyCode::Char('F'), .. } if !view.search.editing => match app.export_fixture(std::path::Path::new(".")) {
+            Ok(dir) => app.show_toast(format!("fixture written to {}", dir.display())),
+            Err(e) => app.show_toast(format!("fixture not written: {:#}", e)),
+        },
+        // 'L' labels the first address on the top line in the address book
+        KeyEvent { code: KeyCode::Char('L'), .. } if !view.search.editing => {
+            let prompted = view.prompt_label(&app.address_book, app.chain_id);
+            if !prompted {
+                app.show_toast("no address on the top line to label");
+            }
         }
-        // Esc to return to Main mode
+        // 'g' re-sorts the trace table of a trace_* response
+        KeyEvent { code: KeyCode::Char('g'), .. } if !view.search.editing && view.title.starts_with("trace_") => {
+            app.prefs.sort_traces_by_gas = !app.prefs.sort_traces_by_gas;
+            app.apply_prefs();
+        }
+        // Esc unwinds one level: query input, then search, then the view itself
         KeyEvent { code: KeyCode::Esc, .. } => {
-            app.mode = AppMode::Main;
+            if view.search.editing || !view.search.query.is_empty() {
+                view.search.clear();
+            } else {
+                app.back();
+            }
+        }
+        // While typing a query, keys edit it
+        KeyEvent { code: KeyCode::Enter, .. } if view.search.editing => view.run_search(),
+        KeyEvent { code: KeyCode::Backspace, .. } if view.search.editing => {
+            view.search.query.pop();
+        }
+        KeyEvent { code: KeyCode::Char(c), modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT, .. } if view.search.editing => {
+            view.search.query.push(c);
+        }
+        // Enter expands the spilled element at the top, or collapses it
+        KeyEvent { code: KeyCode::Enter, .. } => {
+            if let Err(e) = view.toggle_spilled(&app.address_book, app.chain_id) {
+                app.show_toast(format!("element not loaded: {:#}", e));
+            }
+        }
+        // ',' opens settings
+        KeyEvent { code: KeyCode::Char(','), .. } => {
+            app.settings = Some(0);
         }
-        // Navigate up in history list
-        KeyEvent { code: KeyCode::Up, .. } => {
-            let i = app.history_state.selected().unwrap_or(0);
-            if i > 0 {
-                app.history_state.select(Some(i - 1));
+        // 'x' does two jobs: it dumps the long hex string on the top line,
+        // and on any other line follows the response to its counterpart
+        KeyEvent { code: KeyCode::Char('x'), modifiers: KeyModifiers::NONE, .. } => {
+            let dumped = view.open_hex();
+            if !dumped {
+                dispatch(app, Action::Follow(Link::Counterpart)).await;
             }
         }
-        // Navigate down in history list
+        KeyEvent { code: KeyCode::Char('b'), modifiers: KeyModifiers::NONE, .. } => {
+            dispatch(app, Action::Follow(Link::Block)).await;
+        }
+        KeyEvent { code: KeyCode::Char('y'), modifiers: KeyModifiers::NONE, .. } => app.copy_selected(Payload::Response),
+        KeyEvent { code: KeyCode::Char('Y'), .. } => app.copy_selected(Payload::Request),
+        // Tab switches to the params' provenance and back
+        KeyEvent { code: KeyCode::Tab, .. } => {
+            view.toggle_tab();
+            view.label_addresses(&app.address_book, app.chain_id);
+        }
+        // '/' starts a new search
+        KeyEvent { code: KeyCode::Char('/'), .. } => {
+            view.search.clear();
+            view.search.editing = true;
+        }
+        KeyEvent { code: KeyCode::Char('n'), modifiers: KeyModifiers::NONE, .. } => view.next_match(),
+        KeyEvent { code: KeyCode::Char('N'), .. } => view.prev_match(),
+        KeyEvent { code: KeyCode::Up, .. } => view.scroll_by(-1),
+        KeyEvent { code: KeyCode::Down, .. } => view.scroll_by(1),
+        KeyEvent { code: KeyCode::PageUp, .. } => view.scroll_by(-(view.height.max(1) as isize)),
+        KeyEvent { code: KeyCode::PageDown, .. } => view.scroll_by(view.height.max(1) as isize),
+        KeyEvent { code: KeyCode::Home, .. } => view.scroll = 0,
+        KeyEvent { code: KeyCode::End, .. } => view.scroll_to_end(),
+        _ => {}
+    }
+}
+
+/// Handle key events while a hex dump is open over the response view:
+/// - Ctrl+C: quit
+/// - Up/Down, PageUp/PageDown: move by row; Left/Right: by 32-byte word
+/// - Home/End: first/last row
+/// - 'y': copy the selected word
+/// - Esc: back to the response, where it was
+fn handle_hex_dump(app: &mut App, key: KeyEvent) {
+    let Some(hex) = app.response_view.as_mut().and_then(|v| v.hex.as_mut()) else { return };
+    let page = hex.height.max(1) as isize;
+    match key {
+        KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => app.request_quit(),
+        KeyEvent { code: KeyCode::Up, .. } => hex.move_rows(-1),
+        KeyEvent { code: KeyCode::Down, .. } => hex.move_rows(1),
+        KeyEvent { code: KeyCode::PageUp, .. } => hex.move_rows(-page),
+        KeyEvent { code: KeyCode::PageDown, .. } => hex.move_rows(page),
+        KeyEvent { code: KeyCode::Left, .. } => hex.move_words(-1),
+        KeyEvent { code: KeyCode::Right, .. } => hex.move_words(1),
+        KeyEvent { code: KeyCode::Home, .. } => hex.move_rows(isize::MIN),
+        KeyEvent { code: KeyCode::End, .. } => hex.move_rows(isize::MAX),
+        KeyEvent { code: KeyCode::Char('y'), modifiers: KeyModifiers::NONE, .. } => {
+            let (text, what) = (hex.word_text(), format!("word {}", hex.word()));
+            app.copy_text(text, &what);
+        }
+        KeyEvent { code: KeyCode::Esc, .. } => {
+            if let Some(view) = app.response_view.as_mut() {
+                view.hex = None;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Handle key events while the settings popup is open (any mode):
+/// - Ctrl+C: quit
+/// - Up/Down: select a setting
+/// - Enter/Space/Right: change the selected setting (applied immediately)
+/// - Esc or ',': close the popup and save the settings
+pub async fn handle_settings_popup(app: &mut App, key: KeyEvent) {
+    let Some(row) = app.settings else { return };
+    match key {
+        // Ctrl+C to quit
+        KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.request_quit();
+        }
+        KeyEvent { code: KeyCode::Up, .. } => app.settings = Some(row.saturating_sub(1)),
+        KeyEvent { code: KeyCode::Down, .. } => app.settings = Some((row + 1).min(SETTING_COUNT - 1)),
+        KeyEvent { code: KeyCode::Enter | KeyCode::Right | KeyCode::Char(' '), .. } => {
+            app.prefs.cycle(row);
+            app.apply_prefs();
+        }
+        KeyEvent { code: KeyCode::Esc | KeyCode::Char(','), .. } => {
+            app.settings = None;
+            dispatch(app, Action::SaveUiState).await;
+        }
+        _ => {}
+    }
+}
+
+/// Handle key events in the background activity popup:
+/// - Ctrl+C: quit
+/// - Up/Down: select a task
+/// - 'p': pause the selected task, 'r': resume it
+/// - 'k' or Delete: kill it (it stops and leaves the list)
+/// - Esc or 'A': close the popup
+pub fn handle_activity_popup(app: &mut App, key: KeyEvent) {
+    let Some(row) = app.activity else { return };
+    let selected = app.tasks.snapshot().tasks().get(row).map(|t| t.id);
+    match key {
+        KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.request_quit();
+        }
+        KeyEvent { code: KeyCode::Up, .. } => app.activity = Some(row.saturating_sub(1)),
         KeyEvent { code: KeyCode::Down, .. } => {
-            let i = app.history_state.selected().unwrap_or(0);
-            if i + 1 < app.history.len() {
-                app.history_state.select(Some(i + 1));
+            let count = app.tasks.snapshot().tasks().len();
+            app.activity = Some((row + 1).min(count.saturating_sub(1)));
+        }
+        KeyEvent { code: KeyCode::Char(c @ ('p' | 'r')), .. } => {
+            if let Some(id) = selected {
+                app.tasks.update(|r| r.set_paused(id, c == 'p'));
             }
         }
-        // Reload selected history entry
-        KeyEvent { code: KeyCode::Enter, .. } => {
-            if let Some((req, _)) = app.history.get(app.history_state.selected().unwrap_or(0)) {
-                // Reset filtered_methods and selection
-                app.filtered_methods = app.all_methods.clone();
-                if let Some(idx) = app.all_methods.iter().position(|m| m == &req.method) {
-                    app.methods_state.select(Some(idx));
-                }
-                // Load parameters
-                app.param_inputs = req.params.as_array()
-                    .unwrap_or(&vec![])
-                    .iter()
-                    .map(|v| v.to_string())
-                    .collect();
-                app.mode = AppMode::ParamInput;
+        KeyEvent { code: KeyCode::Char('k') | KeyCode::Delete, .. } => {
+            if let Some(id) = selected {
+                let count = app.tasks.update(|r| {
+                    r.remove(id);
+                    r.tasks().len()
+                });
+                app.activity = Some(row.min(count.saturating_sub(1)));
             }
         }
+        KeyEvent { code: KeyCode::Esc | KeyCode::Char('A'), .. } => app.activity = None,
+        _ => {}
+    }
+}
+
+/// Handle key events in the endpoint profile picker:
+/// - Ctrl+C: quit
+/// - Up/Down: select a profile
+/// - Enter: make it the session endpoint and close the picker
+/// - Esc or 'P': close the picker
+pub fn handle_profile_picker(app: &mut App, key: KeyEvent) {
+    let Some(row) = app.profile_picker else { return };
+    match key {
+        KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.request_quit();
+        }
+        KeyEvent { code: KeyCode::Up, .. } => app.profile_picker = Some(row.saturating_sub(1)),
+        KeyEvent { code: KeyCode::Down, .. } => {
+            app.profile_picker = Some((row + 1).min(app.profiles.len().saturating_sub(1)));
+        }
+        KeyEvent { code: KeyCode::Enter, .. } => match app.activate_profile(row) {
+            Ok(()) => app.profile_picker = None,
+            // Keep the picker open to choose another
+            Err(e) => app.show_toast(format!("{:#}", e)),
+        },
+        KeyEvent { code: KeyCode::Esc | KeyCode::Char('P'), .. } => app.profile_picker = None,
+        _ => {}
+    }
+}
+
+/// Handle key events while another eli holds the websocket connection
+/// live heads wanted:
+/// - Ctrl+C: quit
+/// - 'y' or Enter: subscribe anyway
+/// - 'p' or Esc: keep polling for new heads over HTTP
+pub fn handle_lease_prompt(app: &mut App, key: KeyEvent) {
+    match key {
+        KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.request_quit();
+        }
+        KeyEvent { code: KeyCode::Char('y') | KeyCode::Enter, .. } => app.answer_lease_prompt(true),
+        KeyEvent { code: KeyCode::Char('p') | KeyCode::Esc, .. } => app.answer_lease_prompt(false),
         _ => {}
     }
 }
 
+/// Handle key events in the help overlay:
+/// - Ctrl+C: quit
+/// - Anything else: close it, without acting on the key
+pub fn handle_help_overlay(app: &mut App, key: KeyEvent) {
+    match key {
+        KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.request_quit();
+        }
+        _ => app.help = None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,12 +1008,85 @@ mod tests {
         assert_eq!(app.methods_state.selected(), Some(2));
     }
 
+    #[tokio::test]
+    async fn page_keys_move_a_screenful_and_home_end_jump() {
+        let mut app = App::new();
+        app.filtered_methods = (0..50).map(|i| format!("m{}", i)).collect();
+        app.list_height = 20;
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        handle_main_mode(&mut app, key(KeyCode::PageDown)).await;
+        assert_eq!(app.methods_state.selected(), Some(20));
+        handle_main_mode(&mut app, key(KeyCode::PageDown)).await;
+        handle_main_mode(&mut app, key(KeyCode::PageDown)).await;
+        assert_eq!(app.methods_state.selected(), Some(49));
+        handle_main_mode(&mut app, key(KeyCode::PageUp)).await;
+        assert_eq!(app.methods_state.selected(), Some(29));
+        handle_main_mode(&mut app, key(KeyCode::Home)).await;
+        assert_eq!(app.methods_state.selected(), Some(0));
+        handle_main_mode(&mut app, key(KeyCode::End)).await;
+        assert_eq!(app.methods_state.selected(), Some(49));
+
+        // Same keys in History, over its own list
+        app.mode = AppMode::History;
+        let entry = crate::app::HistoryEntry {
+            seq: 1,
+            ..crate::app::HistoryEntry::new(
+                crate::rpc::JsonRpcRequest::new("eth_blockNumber", serde_json::json!([]), 1),
+                crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: None, id: 1 },
+            )
+        };
+        app.history = vec![entry; 30];
+        handle_history_mode(&mut app, key(KeyCode::End)).await;
+        assert_eq!(app.history_state.selected(), Some(29));
+        handle_history_mode(&mut app, key(KeyCode::PageUp)).await;
+        assert_eq!(app.history_state.selected(), Some(9));
+        handle_history_mode(&mut app, key(KeyCode::PageUp)).await;
+        assert_eq!(app.history_state.selected(), Some(0));
+    }
+
     #[tokio::test]
     async fn enter_switches_to_param_input_mode() {
         let mut app = App::new();
         handle_main_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
         assert_eq!(app.mode, AppMode::ParamInput);
+        // One field per spec param: eth_blockNumber takes none
+        assert_eq!(app.selected_method(), Some("eth_blockNumber"));
+        assert!(app.param_inputs.is_empty());
+
+        app.mode = AppMode::Main;
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)).await;
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        assert_eq!(app.selected_method(), Some("eth_getBalance"));
         assert_eq!(app.param_inputs.len(), 2);
+        assert_eq!((app.param_label(0), app.param_label(1)), ("address".into(), "block".into()));
+
+        // Unknown methods get a single free-form field
+        assert_eq!(app.blank_params("eth_notInTheRegistry"), vec![String::new()]);
+    }
+
+    #[tokio::test]
+    async fn focused_docs_panel_switches_tabs_and_brackets_fold_the_schema() {
+        let mut app = App::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        // Brackets are only for the schema tab
+        handle_main_mode(&mut app, key(KeyCode::Char(']'))).await;
+        assert_eq!(app.search_input, "]");
+        app.search_input.clear();
+
+        // Shift+Tab from the search goes around to the docs
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT)).await;
+        handle_main_mode(&mut app, key(KeyCode::Right)).await;
+        assert_eq!(app.docs_tab, DocsTab::Schema);
+        handle_main_mode(&mut app, key(KeyCode::Char(']'))).await;
+        assert_eq!(app.schema_depth, 3);
+        for _ in 0..5 {
+            handle_main_mode(&mut app, key(KeyCode::Char('['))).await;
+        }
+        assert_eq!(app.schema_depth, 1);
+        assert!(app.search_input.is_empty());
+        handle_main_mode(&mut app, key(KeyCode::Left)).await;
+        assert_eq!(app.docs_tab, DocsTab::Docs);
     }
 
     #[tokio::test]
@@ -203,4 +1102,1204 @@ mod tests {
         handle_main_mode(&mut app, KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)).await;
         assert!(app.should_quit);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn ctrl_o_toggles_override_and_captures_typing() {
+        let mut app = App::new();
+        app.param_inputs = vec!["".to_string()];
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL)).await;
+        assert_eq!(app.endpoint_override.as_deref(), Some(""));
+
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)).await;
+        assert_eq!(app.endpoint_override.as_deref(), Some("x"));
+        assert_eq!(app.param_inputs[0], "");
+
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL)).await;
+        assert!(app.endpoint_override.is_none());
+    }
+
+    #[tokio::test]
+    async fn ctrl_e_switches_the_session_endpoint() {
+        let mut app = App::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        app.chain_id = Some(1);
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL)).await;
+        assert_eq!(app.endpoint_edit.as_deref(), Some(crate::endpoint::DEFAULT_ENDPOINT));
+
+        // Typing goes to the prompt, not the search
+        app.endpoint_edit = Some(String::new());
+        for c in "reth".chars() {
+            handle_main_mode(&mut app, key(KeyCode::Char(c))).await;
+        }
+        assert!(app.search_input.is_empty());
+        // Not a URL: the prompt stays open with the error
+        handle_main_mode(&mut app, key(KeyCode::Enter)).await;
+        assert_eq!(app.endpoint_edit.as_deref(), Some("reth"));
+        assert!(app.toast.as_ref().unwrap().message.contains("invalid endpoint URL"));
+        assert_eq!(app.endpoint, crate::endpoint::DEFAULT_ENDPOINT);
+
+        app.endpoint_edit = Some("http://reth:8545".into());
+        handle_main_mode(&mut app, key(KeyCode::Enter)).await;
+        assert!(app.endpoint_edit.is_none());
+        assert_eq!(app.endpoint, "http://reth:8545");
+        // The old chain no longer applies
+        assert_eq!(app.chain_id, None);
+
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL)).await;
+        handle_main_mode(&mut app, key(KeyCode::Esc)).await;
+        assert!(app.endpoint_edit.is_none());
+        assert_eq!(app.endpoint, "http://reth:8545");
+    }
+
+    #[tokio::test]
+    async fn activity_popup_pauses_resumes_and_kills() {
+        let mut app = App::new();
+        let second = std::time::Duration::from_secs(1);
+        let sync = app.tasks.update(|r| r.register(crate::tasks::TaskKind::SyncProbe, "eth_syncing", second));
+        let head = app.tasks.update(|r| r.register(crate::tasks::TaskKind::HeadPoll, "eth_getBlockByNumber", second));
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT)).await;
+        assert_eq!(app.activity, Some(0));
+
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        handle_activity_popup(&mut app, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        handle_activity_popup(&mut app, key('p'));
+        assert!(app.tasks.snapshot().get(head).unwrap().paused);
+        handle_activity_popup(&mut app, key('r'));
+        assert!(!app.tasks.snapshot().get(head).unwrap().paused);
+
+        // Killing the last row moves the selection up
+        handle_activity_popup(&mut app, key('k'));
+        assert!(app.tasks.snapshot().get(head).is_none());
+        assert_eq!(app.activity, Some(0));
+        assert!(app.tasks.snapshot().get(sync).is_some());
+
+        handle_activity_popup(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(app.activity, None);
+    }
+
+    #[tokio::test]
+    async fn profile_picker_switches_the_session_endpoint() {
+        use crate::endpoint::EndpointProfile;
+        let mut app = App::new();
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        // Without profiles the picker says how to add them
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Char('P'), KeyModifiers::SHIFT)).await;
+        handle_profile_picker(&mut app, press(KeyCode::Enter));
+        assert_eq!(app.profile_picker, Some(0));
+        assert!(app.toast.as_ref().unwrap().message.contains("endpoints.json"));
+        handle_profile_picker(&mut app, press(KeyCode::Esc));
+
+        app.profiles = vec![
+            EndpointProfile { name: "local".into(), url: "http://127.0.0.1:1".into(), ..Default::default() },
+            EndpointProfile { name: "gnosis".into(), url: "http://127.0.0.1:2".into(), chain_id: Some(100), ..Default::default() },
+        ];
+        app.endpoint = "http://127.0.0.1:1".into();
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Char('P'), KeyModifiers::SHIFT)).await;
+        assert_eq!(app.breadcrumb(), "Main › Profiles");
+        handle_profile_picker(&mut app, press(KeyCode::Down));
+        handle_profile_picker(&mut app, press(KeyCode::Down));
+        handle_profile_picker(&mut app, press(KeyCode::Enter));
+        assert_eq!(app.profile_picker, None);
+        assert_eq!(app.endpoint, "http://127.0.0.1:2");
+        assert_eq!(app.active_profile().unwrap().name, "gnosis");
+        // The profile's chain stands in until the node answers
+        assert_eq!(app.chain_id, Some(100));
+
+        // Reopening starts on the active profile
+        app.open_profile_picker();
+        assert_eq!(app.profile_picker, Some(1));
+    }
+
+    #[tokio::test]
+    async fn a_held_websocket_asks_before_subscribing_to_heads() {
+        use crate::endpoint::EndpointProfile;
+        use crate::storage;
+        use crate::ws::{Connector, WsHandle};
+
+        /// Hands out one fake connection.
+        struct Once(std::sync::Mutex<Option<WsHandle>>);
+        #[async_trait::async_trait]
+        impl Connector for Once {
+            async fn connect(&self, url: &str) -> anyhow::Result<WsHandle> {
+                self.0.lock().unwrap().take().ok_or_else(|| anyhow::anyhow!("cannot connect to {}", url))
+            }
+        }
+
+        const WS: &str = "ws://127.0.0.1:1";
+        let dir = std::env::temp_dir().join(format!("eli-events-lease-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (handle, mut socket) = WsHandle::fake();
+        let mut app = App::new();
+        app.lease_dir = Some(dir.clone());
+        app.ws_connector = std::sync::Arc::new(Once(std::sync::Mutex::new(Some(handle))));
+        app.profiles = vec![EndpointProfile {
+            name: "node".into(),
+            url: "http://127.0.0.1:1".into(),
+            ws_url: Some(WS.into()),
+            ..Default::default()
+        }];
+        // Another instance holds the connection: pid 1 is always running
+        storage::acquire_lease(&dir, WS, 1, crate::head::unix_now(), |_| true).unwrap();
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        app.activate_profile(0).unwrap();
+        let holder = app.lease_prompt.as_ref().expect("asked");
+        assert_eq!(holder.in_use_message(), "subscription transport in use by another eli instance (pid 1)");
+        assert_eq!(app.breadcrumb(), "Main › Live heads");
+        // Polling over HTTP leaves the other instance's lease alone
+        handle_lease_prompt(&mut app, press(KeyCode::Char('p')));
+        assert!(app.lease_prompt.is_none() && app.live_heads.is_none());
+        assert_eq!(storage::acquire_lease(&dir, WS, 2, crate::head::unix_now(), |_| true).unwrap().unwrap().pid, 1);
+
+        // Asked again, subscribing anyway takes the lease and streams heads
+        app.open_live_heads(false);
+        handle_lease_prompt(&mut app, press(KeyCode::Enter));
+        assert!(app.live_heads.is_some());
+        let request = socket.requests.recv().await.unwrap();
+        assert_eq!((request.method.as_str(), &request.params), ("eth_subscribe", &serde_json::json!(["newHeads"])));
+        socket.reply(request.id, serde_json::json!("0xs1"));
+        socket.notify("0xs1", serde_json::json!({"number": "0x2a", "timestamp": "0x10"}));
+        for _ in 0..100 {
+            app.tick(app.clock.now());
+            if app.stats.last().head.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        assert_eq!(app.stats.last().head.map(|h| (h.number, h.timestamp)), Some((42, Some(16))));
+        let held = storage::acquire_lease(&dir, WS, 1, crate::head::unix_now(), |_| true).unwrap();
+        assert_eq!(held.map(|l| l.pid), Some(std::process::id()));
+
+        // Leaving the endpoint gives the connection up
+        app.set_endpoint("http://127.0.0.1:2").unwrap();
+        assert!(app.live_heads.is_none());
+        assert!(!storage::lease_path(&dir, WS).exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tab_moves_typing_between_params() {
+        let mut app = App::new();
+        app.methods_state.select(Some(1));
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        assert_eq!(app.selected_method(), Some("eth_getBalance"));
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        app.param_inputs = vec![String::new(), String::new()];
+
+        handle_param_input_mode(&mut app, key(KeyCode::Char('a'))).await;
+        handle_param_input_mode(&mut app, key(KeyCode::Tab)).await;
+        for c in "latesx".chars() {
+            handle_param_input_mode(&mut app, key(KeyCode::Char(c))).await;
+        }
+        handle_param_input_mode(&mut app, key(KeyCode::Backspace)).await;
+        handle_param_input_mode(&mut app, key(KeyCode::Char('t'))).await;
+        assert_eq!(app.param_inputs, ["a", "latest"]);
+
+        // Focus goes around both ways, by way of the preview
+        handle_param_input_mode(&mut app, key(KeyCode::Tab)).await;
+        assert_eq!(app.pane(), Some(Focus::Preview));
+        handle_param_input_mode(&mut app, key(KeyCode::Tab)).await;
+        assert_eq!((app.pane(), app.focused_param), (Some(Focus::Fields), 0));
+        let back_tab = || KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        handle_param_input_mode(&mut app, back_tab()).await;
+        handle_param_input_mode(&mut app, back_tab()).await;
+        assert_eq!((app.pane(), app.focused_param), (Some(Focus::Fields), 1));
+        handle_param_input_mode(&mut app, key(KeyCode::F(4))).await;
+        assert_eq!(app.editor_popup.as_ref().unwrap().target, crate::popup::PopupTarget::Param(1));
+    }
+
+    #[tokio::test]
+    async fn space_and_enter_flip_a_focused_toggle_instead_of_sending() {
+        let mut app = App::new();
+        let at = app.filtered_methods.iter().position(|m| m == "eth_getBlockByNumber");
+        app.methods_state.select(at);
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        handle_param_input_mode(&mut app, key(KeyCode::Tab)).await;
+
+        handle_param_input_mode(&mut app, key(KeyCode::Char(' '))).await;
+        assert_eq!(app.param_inputs[1], "true");
+        handle_param_input_mode(&mut app, key(KeyCode::Enter)).await;
+        assert_eq!(app.param_inputs[1], "false");
+        assert_eq!(app.mode, AppMode::ParamInput);
+        // Typing doesn't reach a toggle
+        handle_param_input_mode(&mut app, key(KeyCode::Char('T'))).await;
+        handle_param_input_mode(&mut app, key(KeyCode::Backspace)).await;
+        assert_eq!(app.param_inputs[1], "false");
+    }
+
+    #[tokio::test]
+    async fn raw_mode_routes_editing_keys_to_buffer() {
+        let mut app = App::new();
+        app.mode = AppMode::ParamInput;
+        app.param_inputs = vec!["".to_string()];
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL)).await;
+        assert_eq!(app.raw_params.as_ref().unwrap().area.text(), "[]");
+
+        // Enter inserts a newline instead of sending
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Left, KeyModifiers::NONE)).await;
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('1'), KeyModifiers::NONE)).await;
+        let raw = app.raw_params.as_ref().unwrap();
+        assert_eq!(raw.area.text(), "[\n1]");
+        assert!(raw.is_pending());
+        assert_eq!(app.mode, AppMode::ParamInput);
+        assert!(app.history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn response_view_search_cycles_and_esc_unwinds() {
+        let mut app = App::new();
+        app.enter(AppMode::History);
+        app.response_view = Some(crate::view::ResponseView {
+            lines: (0..100).map(|i| if i % 40 == 0 { format!("Hash {}", i) } else { "x".into() }).collect(),
+            height: 10,
+            ..Default::default()
+        });
+        app.enter(AppMode::ResponseView);
+
+        for key in [KeyCode::Char('/'), KeyCode::Char('h'), KeyCode::Char('a'), KeyCode::Enter] {
+            handle_response_view_mode(&mut app, KeyEvent::new(key, KeyModifiers::NONE)).await;
+        }
+        let view = app.response_view.as_ref().unwrap();
+        assert_eq!(view.search.indicator().as_deref(), Some("match 1/3"));
+
+        // 'n' moves to line 40 and scrolls it into view
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)).await;
+        let view = app.response_view.as_ref().unwrap();
+        assert_eq!(view.search.current_match().unwrap().line, 40);
+        assert!(view.scroll <= 40 && 40 < view.scroll + view.height);
+
+        // First Esc clears the search, second leaves the view
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await;
+        assert!(app.response_view.as_ref().unwrap().search.matches.is_empty());
+        assert_eq!(app.mode, AppMode::ResponseView);
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await;
+        assert_eq!(app.mode, AppMode::History);
+    }
+
+    #[tokio::test]
+    async fn response_view_home_end_jump_to_either_end() {
+        let mut app = App::new();
+        app.response_view = Some(crate::view::ResponseView { lines: vec!["x".into(); 5000], height: 40, ..Default::default() });
+        app.mode = AppMode::ResponseView;
+
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::End, KeyModifiers::NONE)).await;
+        assert_eq!(app.response_view.as_ref().unwrap().scroll, 4960);
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE)).await;
+        assert_eq!(app.response_view.as_ref().unwrap().scroll, 4920);
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::Home, KeyModifiers::NONE)).await;
+        assert_eq!(app.response_view.as_ref().unwrap().scroll, 0);
+    }
+
+    #[tokio::test]
+    async fn labelling_an_address_saves_the_address_book() {
+        let dir = std::env::temp_dir().join(format!("eli-events-label-{}", std::process::id()));
+        let vault = "0x00000000000000000000000000000000000000c1";
+        let mut app = App::new();
+        app.address_book_path = Some(dir.join("address-book.json"));
+        app.response_view =
+            Some(crate::view::ResponseView { lines: vec![format!("  \"to\": \"{}\",", vault)], ..Default::default() });
+        app.mode = AppMode::ResponseView;
+
+        for key in [KeyCode::Char('L'), KeyCode::Char('v'), KeyCode::Char('a'), KeyCode::Char('u'), KeyCode::Enter] {
+            handle_response_view_mode(&mut app, KeyEvent::new(key, KeyModifiers::NONE)).await;
+        }
+        assert_eq!(app.toast.as_ref().unwrap().message, format!("labelled {} as vau", vault));
+        assert_eq!(app.response_view.as_ref().unwrap().labels, [Some("vau".to_string())]);
+        let saved = crate::addressbook::load(&dir.join("address-book.json")).unwrap().value;
+        assert_eq!((saved.label(None, vault), saved.revision), (Some("vau"), 1));
+
+        // Prefilled with the label; clearing it removes the entry
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::Char('L'), KeyModifiers::NONE)).await;
+        for key in [KeyCode::Backspace, KeyCode::Backspace, KeyCode::Backspace, KeyCode::Enter] {
+            handle_response_view_mode(&mut app, KeyEvent::new(key, KeyModifiers::NONE)).await;
+        }
+        assert_eq!(app.address_book.label(None, vault), None);
+        assert_eq!(crate::addressbook::load(&dir.join("address-book.json")).unwrap().value.revision, 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn override_send_is_recorded_on_history_entry() {
+        let _m = mockito::mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "jsonrpc": "2.0", "result": "0x1", "id": 1 }"#)
+            .create();
+
+        let mut app = App::new();
+        app.mode = AppMode::ParamInput;
+        app.endpoint_override = Some(mockito::server_url());
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        app.finish_sends().await;
+
+        assert_eq!(app.mode, AppMode::Main);
+        assert_eq!(app.history.len(), 1);
+        assert_eq!(app.history[0].endpoint_override, Some(mockito::server_url()));
+        assert!(app.history[0].latency.is_some());
+        assert_eq!(app.endpoint, crate::endpoint::DEFAULT_ENDPOINT);
+        // The override is one-shot
+        assert!(app.endpoint_override.is_none());
+    }
+
+    #[tokio::test]
+    async fn unreachable_override_fails_without_recording() {
+        let mut app = App::new();
+        app.mode = AppMode::ParamInput;
+        app.endpoint_override = Some("http://127.0.0.1:1".to_string());
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        app.finish_sends().await;
+
+        // The send ran in the background; the failure surfaces as the notice
+        assert_eq!(app.mode, AppMode::Main);
+        assert!(app.history.is_empty());
+        assert!(app.notice.as_deref().unwrap().contains("127.0.0.1:1"));
+        assert_eq!(app.endpoint, crate::endpoint::DEFAULT_ENDPOINT);
+    }
+
+    #[tokio::test]
+    async fn enter_sends_the_selected_method_to_the_session_endpoint() {
+        let _m = mockito::mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "jsonrpc": "2.0", "result": "0x0", "id": 1 }"#)
+            .create();
+
+        let mut app = App::new();
+        app.endpoint = mockito::server_url();
+        app.methods_state.select(app.filtered_methods.iter().position(|m| m == "eth_getBalance"));
+        app.mode = AppMode::ParamInput;
+        app.param_inputs = vec!["0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f".to_string(), "latest".to_string()];
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        app.finish_sends().await;
+
+        assert_eq!(app.mode, AppMode::Main);
+        assert_eq!(app.history.len(), 1);
+        let entry = &app.history[0];
+        assert_eq!(entry.request.method, "eth_getBalance");
+        assert_eq!(entry.request.params, serde_json::json!(["0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f", "latest"]));
+        assert_eq!(entry.endpoint_override, None);
+        assert!(app.notice.is_none());
+    }
+
+    #[tokio::test]
+    async fn failed_sends_to_the_session_endpoint_are_surfaced() {
+        let mut app = App::new();
+        app.endpoint = "http://127.0.0.1:1".to_string();
+        app.methods_state.select(app.filtered_methods.iter().position(|m| m == "eth_blockNumber"));
+        app.mode = AppMode::ParamInput;
+        app.param_inputs = Vec::new();
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        app.finish_sends().await;
+
+        assert!(app.history.is_empty());
+        assert!(app.notice.as_deref().unwrap().contains("request to http://127.0.0.1:1 failed"));
+    }
+
+    #[tokio::test]
+    async fn settings_popup_changes_prefs_and_saves_on_close() {
+        let dir = std::env::temp_dir().join(format!("eli-settings-{}", std::process::id()));
+        let mut app = App::new();
+        app.ui_state_path = Some(dir.join("ui-state.json"));
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Char(','), KeyModifiers::NONE)).await;
+        assert_eq!(app.settings, Some(0));
+
+        handle_settings_popup(&mut app, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)).await;
+        handle_settings_popup(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        assert!(!app.prefs.separators);
+
+        handle_settings_popup(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await;
+        assert_eq!(app.settings, None);
+        let saved = crate::uistate::load(app.ui_state_path.as_ref().unwrap()).unwrap();
+        assert_eq!(saved.value.prefs, app.prefs);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn response_view_exports_array_results_as_csv() {
+        let dir = std::env::temp_dir().join(format!("eli-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut app = App::new();
+        app.history.push(crate::app::HistoryEntry {
+            seq: 7,
+            ..crate::app::HistoryEntry::new(
+                crate::rpc::JsonRpcRequest::new("eth_getLogs", serde_json::json!([{}]), 7),
+                crate::rpc::JsonRpcResponse {
+                    jsonrpc: "2.0".into(),
+                    result: Some(serde_json::json!([{"logIndex": "0x1", "topics": ["0xaa"]}])),
+                    error: None,
+                    id: 7,
+                },
+            )
+        });
+        app.open_selected_response();
+
+        let export = KeyEvent::new(KeyCode::Char('E'), KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+        handle_response_view_mode(&mut app, export).await;
+        let view = app.response_view.as_mut().unwrap();
+        assert_eq!(view.export_path.as_deref(), Some("eth_getLogs-7.csv"));
+        let path = dir.join("logs.csv");
+        view.export_path = Some(path.display().to_string());
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+
+        assert_eq!(app.response_view.as_ref().unwrap().export_path, None);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "logIndex,topics\n1,\"[\"\"0xaa\"\"]\"\n");
+        assert!(app.toast.as_ref().unwrap().message.contains("1 row(s)"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn transaction_form_edits_fields_and_fills_the_nonce() {
+        let _m = mockito::mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("eth_getTransactionCount".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "jsonrpc": "2.0", "result": "0x2a", "id": 1 }"#)
+            .create();
+        let mut app = App::new();
+        app.endpoint = mockito::server_url();
+        let idx = app.all_methods.iter().position(|m| m == txform::METHOD).unwrap();
+        app.methods_state.select(Some(idx));
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        assert!(app.tx_form.is_some());
+
+        let from = format!("0x{}", "a1".repeat(20));
+        for c in from.chars() {
+            handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).await;
+        }
+        // Up wraps around the form; Shift+Tab would go on to the preview
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)).await;
+        for c in "0x60".chars() {
+            handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).await;
+        }
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL)).await;
+
+        let form = app.tx_form.as_ref().unwrap();
+        assert_eq!(form.fields[txform::FROM], from);
+        assert_eq!(form.fields[txform::FIELDS.len() - 1], "0x60");
+        assert_eq!(form.fields[txform::NONCE], "0x2a");
+        assert_eq!(app.current_params().unwrap(), serde_json::json!([{"from": from, "nonce": "0x2a", "input": "0x60"}]));
+    }
+
+    #[tokio::test]
+    async fn leaving_unknown_method_reload_restores_list_selection() {
+        let mut app = App::new();
+        app.history.push(crate::app::HistoryEntry::new(
+            crate::rpc::JsonRpcRequest::new("bogus_method", serde_json::json!([]), 1),
+            crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: None, id: 1 },
+        ));
+        app.mode = AppMode::History;
+        handle_history_mode(&mut app, KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE)).await;
+        assert_eq!(app.mode, AppMode::ParamInput);
+        assert!(app.raw_params.is_some());
+
+        // Back to History first, then to the list
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await;
+        assert_eq!(app.mode, AppMode::History);
+        handle_history_mode(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await;
+        assert_eq!(app.mode, AppMode::Main);
+        assert_eq!(app.selected_method(), Some(app.all_methods[0].as_str()));
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)).await;
+        assert_eq!(app.selected_method(), Some(app.all_methods[1].as_str()));
+    }
+
+    #[tokio::test]
+    async fn popup_edits_focused_param_and_collapses_on_save() {
+        let mut app = App::new();
+        app.mode = AppMode::ParamInput;
+        app.param_inputs = vec!["0xab".to_string(), "".to_string()];
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::F(4), KeyModifiers::NONE)).await;
+        assert!(app.editor_popup.is_some());
+
+        // Enter inserts a newline inside the popup instead of sending
+        for key in [KeyCode::Enter, KeyCode::Char('c'), KeyCode::Char('d')] {
+            handle_param_input_mode(&mut app, KeyEvent::new(key, KeyModifiers::NONE)).await;
+        }
+        assert!(app.history.is_empty());
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)).await;
+        assert!(app.editor_popup.is_none());
+        assert_eq!(app.param_inputs[0], "0xabcd");
+
+        // Cancel leaves the field untouched
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL)).await;
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE)).await;
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await;
+        assert!(app.editor_popup.is_none());
+        assert_eq!(app.mode, AppMode::ParamInput);
+        assert_eq!(app.param_inputs[0], "0xabcd");
+    }
+
+    /// Answers every call with "method not found" and counts calls.
+    #[derive(Default)]
+    struct MissingMethodTransport {
+        calls: std::sync::atomic::AtomicUsize,
+        methods: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::rpc::Transport for MissingMethodTransport {
+        async fn send(&self, _url: &str, req: crate::rpc::JsonRpcRequest) -> anyhow::Result<crate::rpc::JsonRpcResponse> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.methods.lock().unwrap().push(req.method.clone());
+            Ok(crate::rpc::JsonRpcResponse {
+                jsonrpc: "2.0".into(),
+                result: None,
+                error: Some(serde_json::json!({"code": -32601, "message": "Method not found"})),
+                id: req.id,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn probe_runs_once_per_method_and_endpoint() {
+        use crate::capability::Availability;
+        let transport = std::sync::Arc::new(MissingMethodTransport::default());
+        let mut app = App::new();
+        app.prefetch_transport = transport.clone();
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        let method = app.selected_method().unwrap().to_string();
+        // The background polls go through the same transport
+        let probes = |method: &str| transport.methods.lock().unwrap().iter().filter(|m| *m == method).count();
+
+        async fn settle(app: &mut App) {
+            for _ in 0..100 {
+                app.tick(app.clock.now());
+                if app.capability_probe.is_none() {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            }
+        }
+
+        // In the background: the key returns before the answer
+        handle_main_mode(&mut app, ctrl_p).await;
+        assert!(app.capability_probe.is_some());
+        settle(&mut app).await;
+        handle_main_mode(&mut app, ctrl_p).await;
+        assert!(app.capability_probe.is_none());
+        assert_eq!(probes(&method), 1);
+        assert_eq!(app.capabilities.get(&app.endpoint, &method), Some(Availability::Unavailable));
+
+        // A different endpoint is probed separately
+        app.endpoint = "http://other:8545".into();
+        handle_main_mode(&mut app, ctrl_p).await;
+        settle(&mut app).await;
+        assert_eq!(probes(&method), 2);
+
+        // Methods that may change node state are left alone
+        app.unlisted_method = Some("evm_mine".into());
+        handle_main_mode(&mut app, ctrl_p).await;
+        assert!(app.capability_probe.is_none());
+        assert_eq!(app.toast.as_ref().unwrap().message, "evm_mine may change node state, so it isn't probed");
+    }
+
+    #[tokio::test]
+    async fn method_not_found_response_marks_method_unavailable() {
+        let _m = mockito::mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": 1 }"#)
+            .create();
+
+        let mut app = App::new();
+        app.endpoint = mockito::server_url();
+        app.mode = AppMode::ParamInput;
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        app.finish_sends().await;
+        let method = app.history[0].request.method.clone();
+        assert_eq!(
+            app.capabilities.get(&app.endpoint, &method),
+            Some(crate::capability::Availability::Unavailable)
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_records_without_sending() {
+        let m = mockito::mock("POST", "/").expect(0).create();
+        let transport = std::sync::Arc::new(MissingMethodTransport::default());
+
+        let mut app = App::new();
+        app.endpoint = mockito::server_url();
+        app.prefetch_transport = transport.clone();
+        app.prefetch = true;
+        app.mode = AppMode::ParamInput;
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)).await;
+        assert!(app.dry_run);
+        app.param_inputs = vec!["0xabc".to_string(), String::new()];
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+
+        m.assert();
+        assert_eq!(transport.calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+        assert!(app.latency.samples().is_empty());
+        assert_eq!(app.history.len(), 1);
+        assert!(app.history[0].dry_run);
+        assert_eq!(app.history[0].request.params, serde_json::json!(["0xabc"]));
+
+        // The would-be request is shown under a banner
+        assert_eq!(app.mode, AppMode::ResponseView);
+        let view = app.response_view.as_ref().unwrap();
+        assert!(view.dry_run);
+        assert!(view.lines[0].starts_with("DRY RUN"));
+        assert!(view.lines.iter().any(|l| l.contains("\"0xabc\"")));
+    }
+
+    #[tokio::test]
+    async fn dry_run_still_validates() {
+        let mut app = App::new();
+        app.dry_run = true;
+        app.mode = AppMode::ParamInput;
+        app.toggle_raw_params().unwrap();
+        if let Some(raw) = &mut app.raw_params {
+            raw.area = crate::editor::TextArea::new("[1,");
+        }
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)).await;
+        assert!(app.history.is_empty());
+        assert_eq!(app.mode, AppMode::ParamInput);
+        assert!(app.notice.as_deref().unwrap().contains("invalid params JSON"));
+    }
+
+    /// Answers after a pause, long enough for more sends to queue up.
+    struct SlowTransport;
+
+    #[async_trait::async_trait]
+    impl crate::rpc::Transport for SlowTransport {
+        async fn send(&self, _url: &str, req: crate::rpc::JsonRpcRequest) -> anyhow::Result<crate::rpc::JsonRpcResponse> {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            Ok(crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(serde_json::json!("0x1")), error: None, id: req.id })
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_confirmed_while_busy_are_queued_in_order() {
+        let mut app = App::new();
+        app.sends = crate::queue::SendQueue::new(std::sync::Arc::new(SlowTransport));
+        for _ in 0..3 {
+            handle_main_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+            handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+            assert_eq!(app.mode, AppMode::Main);
+        }
+        let (_, started) = app.sends.in_flight().unwrap();
+        let status = app.send_status(started + std::time::Duration::from_millis(1250)).unwrap();
+        assert_eq!(status, "⠹ eth_blockNumber 1,250 ms · 2 queued");
+
+        // Quitting with pending sends asks for confirmation first
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)).await;
+        assert!(!app.should_quit);
+        assert!(app.toast.as_ref().unwrap().message.contains("3 pending"));
+
+        app.finish_sends().await;
+        let ids: Vec<u64> = app.history.iter().map(|e| e.request.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert!(!app.quit_armed);
+    }
+
+    #[tokio::test]
+    async fn esc_in_method_list_clears_the_queue() {
+        let mut app = App::new();
+        app.sends = crate::queue::SendQueue::new(std::sync::Arc::new(SlowTransport));
+        for _ in 0..3 {
+            handle_main_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+            handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        }
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await;
+        assert_eq!(app.sends.queued(), 0);
+        app.finish_sends().await;
+        assert_eq!(app.history.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn esc_cancels_the_send_in_flight_without_blocking() {
+        let mut app = App::new();
+        app.sends = crate::queue::SendQueue::new(std::sync::Arc::new(SlowTransport));
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        assert!(app.send_status(std::time::Instant::now()).unwrap().contains("eth_blockNumber"));
+
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await;
+        assert!(!app.sends.is_busy());
+        assert_eq!(app.toast.as_ref().unwrap().message, "cancelled eth_blockNumber");
+        assert!(!app.should_quit);
+        tokio::time::sleep(std::time::Duration::from_millis(80)).await;
+        app.drain_sends();
+        assert!(app.history.is_empty());
+        assert_eq!(app.send_status(std::time::Instant::now()), None);
+    }
+
+    #[tokio::test]
+    async fn tutorial_walks_through_a_send_offline_and_esc_exits() {
+        let mut app = App::new();
+        app.start_tutorial();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        for c in "bal".chars() {
+            handle_main_mode(&mut app, key(KeyCode::Char(c))).await;
+        }
+        handle_main_mode(&mut app, key(KeyCode::Enter)).await;
+        for c in "0x00000000000000000000000000000000000000a1".chars() {
+            handle_param_input_mode(&mut app, key(KeyCode::Char(c))).await;
+        }
+        app.tick(Instant::now());
+        assert!(app.tutorial.as_ref().unwrap().banner().contains("press Enter to send"));
+
+        handle_param_input_mode(&mut app, key(KeyCode::Enter)).await;
+        app.finish_sends().await;
+        assert_eq!(app.history[0].response.result, Some(serde_json::json!("0x14d1120d7b160000")));
+        // The main loop ticks between keys
+        for code in [KeyCode::Backspace, KeyCode::Backspace, KeyCode::Backspace, KeyCode::Char('h')] {
+            handle_main_mode(&mut app, key(code)).await;
+        }
+        app.tick(Instant::now());
+        handle_history_mode(&mut app, key(KeyCode::Char('v'))).await;
+        app.tick(Instant::now());
+        assert!(app.tutorial.as_ref().unwrap().step().is_none());
+
+        assert!(!handle_tutorial_exit(&mut app, key(KeyCode::Enter)));
+        assert!(handle_tutorial_exit(&mut app, key(KeyCode::Esc)));
+        assert!(app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn entering_param_input_fills_configured_defaults() {
+        let mut app = App::new();
+        app.param_defaults = serde_json::from_value(serde_json::json!({"eth": {"address": "0xa1", "block": "latest"}})).unwrap();
+        app.methods_state.select(Some(1));
+        handle_main_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        assert_eq!(app.param_inputs, ["0xa1", "latest"]);
+        assert!(app.is_default_param(0) && app.is_default_param(1));
+
+        // Edited fields are no longer marked as defaults
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('2'), KeyModifiers::NONE)).await;
+        assert!(!app.is_default_param(0));
+        assert!(app.is_default_param(1));
+    }
+
+    #[tokio::test]
+    async fn block_params_start_at_latest_and_stay_editable() {
+        let mut app = App::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        app.methods_state.select(Some(1));
+        handle_main_mode(&mut app, key(KeyCode::Enter)).await;
+        assert_eq!(app.selected_method(), Some("eth_getBalance"));
+        assert_eq!(app.param_inputs, ["", "latest"]);
+
+        // Typing replaces the default...
+        handle_param_input_mode(&mut app, key(KeyCode::Tab)).await;
+        for c in "0x10".chars() {
+            handle_param_input_mode(&mut app, key(KeyCode::Char(c))).await;
+        }
+        assert_eq!(app.param_inputs[1], "0x10");
+
+        // ...unless the cursor moved into it first
+        app.param_inputs[1] = "latest".into();
+        app.seeded_defaults = vec![None, Some("latest".into())];
+        handle_param_input_mode(&mut app, key(KeyCode::Home)).await;
+        handle_param_input_mode(&mut app, key(KeyCode::Char('x'))).await;
+        assert_eq!(app.param_inputs[1], "xlatest");
+    }
+
+    #[tokio::test]
+    async fn ctrl_caret_toggles_between_the_last_two_methods() {
+        let mut app = App::new();
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        handle_main_mode(&mut app, key(KeyCode::Char('-'), KeyModifiers::NONE)).await;
+        assert_eq!(app.toast.as_ref().unwrap().message, "no previous method yet");
+        assert_eq!(app.mode, AppMode::Main);
+
+        // Open three methods in turn, typing a param into each
+        for (row, param) in [(1, "0xa1"), (3, "x"), (4, "0x10")] {
+            app.methods_state.select(Some(row));
+            handle_main_mode(&mut app, key(KeyCode::Enter, KeyModifiers::NONE)).await;
+            for c in param.chars() {
+                handle_param_input_mode(&mut app, key(KeyCode::Char(c), KeyModifiers::NONE)).await;
+            }
+            handle_param_input_mode(&mut app, key(KeyCode::Esc, KeyModifiers::NONE)).await;
+        }
+        assert_eq!(app.selected_method(), Some("eth_getBlockByNumber"));
+
+        handle_main_mode(&mut app, key(KeyCode::Char('^'), KeyModifiers::CONTROL)).await;
+        assert_eq!((app.mode, app.selected_method()), (AppMode::ParamInput, Some("eth_call")));
+        assert_eq!(app.param_inputs[0], "x");
+
+        // Back and forth, each side keeping its draft
+        handle_param_input_mode(&mut app, key(KeyCode::Char('y'), KeyModifiers::NONE)).await;
+        handle_param_input_mode(&mut app, key(KeyCode::Char('^'), KeyModifiers::CONTROL)).await;
+        assert_eq!(app.selected_method(), Some("eth_getBlockByNumber"));
+        assert_eq!(app.param_inputs[0], "0x10");
+        handle_param_input_mode(&mut app, key(KeyCode::Char('6'), KeyModifiers::CONTROL)).await;
+        assert_eq!(app.selected_method(), Some("eth_call"));
+        assert_eq!(app.param_inputs[0], "xy");
+    }
+    #[tokio::test]
+    async fn x_in_a_receipt_opens_its_transaction_once_it_arrives() {
+        let mut app = App::new();
+        app.start_tutorial();
+        let tx = "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b";
+        app.history.push(crate::app::HistoryEntry {
+            seq: 12,
+            ..crate::app::HistoryEntry::new(
+                crate::rpc::JsonRpcRequest::new("eth_getTransactionReceipt", serde_json::json!([tx]), 1),
+                crate::rpc::JsonRpcResponse {
+                    jsonrpc: "2.0".into(),
+                    result: Some(serde_json::json!({"transactionHash": tx, "blockHash": tx})),
+                    error: None,
+                    id: 1,
+                },
+            )
+        });
+        app.history_state.select(Some(0));
+        app.open_selected_response();
+        assert!(app.response_view.as_ref().unwrap().lines.iter().any(|l| l == "x → eth_getTransactionByHash · b → eth_getBlockByHash"));
+
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)).await;
+        app.finish_sends().await;
+        let followed = &app.history[1];
+        assert_eq!(followed.request.method, "eth_getTransactionByHash");
+        assert_eq!(followed.request.params, serde_json::json!([tx]));
+        // Linked by number, not by list position or wire id
+        assert_eq!((followed.seq, followed.derived_from), (13, Some(12)));
+        assert_eq!(app.history_state.selected(), Some(1));
+        assert_eq!(app.response_view.as_ref().unwrap().title, "eth_getTransactionByHash #13");
+
+        // A transaction without the needed hash has nothing to follow
+        handle_response_view_mode(&mut app, KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE)).await;
+        assert_eq!(app.toast.as_ref().unwrap().message, "nothing to follow with 'b' here");
+    }
+
+    #[tokio::test]
+    async fn marked_history_entries_are_deleted_together_after_confirming() {
+        let mut app = App::new();
+        for (id, method) in ["m1", "m2", "m3", "m4", "m5"].into_iter().enumerate() {
+            let response = crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: None, id: id as u64 };
+            app.record(crate::rpc::JsonRpcRequest::new(method, serde_json::json!([]), id as u64), response, None);
+        }
+        app.mode = AppMode::History;
+        app.history_state.select(Some(1));
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let methods = |app: &App| app.history.iter().map(|e| e.request.method.clone()).collect::<Vec<_>>();
+
+        handle_history_mode(&mut app, key(KeyCode::Char(' '))).await;
+        handle_history_mode(&mut app, key(KeyCode::Down)).await;
+        handle_history_mode(&mut app, key(KeyCode::Down)).await;
+        handle_history_mode(&mut app, key(KeyCode::Char(' '))).await;
+        assert_eq!(app.history_targets(), [2, 4]);
+
+        // Marks follow entries, not rows, when rows above go away
+        app.delete_history(&[1]);
+        assert_eq!(app.history_targets(), [2, 4]);
+        assert_eq!(app.history_state.selected(), Some(2));
+
+        handle_history_mode(&mut app, key(KeyCode::Char('d'))).await;
+        assert_eq!(app.history_prompt().as_deref(), Some("delete 2 entries?"));
+        handle_history_mode(&mut app, key(KeyCode::Char('n'))).await;
+        assert_eq!((app.history_confirm, app.history.len()), (None, 4));
+
+        handle_history_mode(&mut app, key(KeyCode::Char('d'))).await;
+        handle_history_mode(&mut app, key(KeyCode::Char('y'))).await;
+        assert_eq!(methods(&app), ["m3", "m5"]);
+        assert!(app.history_marks.is_empty());
+        assert_eq!(app.history_state.selected(), Some(1));
+
+        // Unmarked: the selected entry alone, still confirmed
+        handle_history_mode(&mut app, key(KeyCode::Char('d'))).await;
+        assert_eq!(app.history_prompt().as_deref(), Some("delete 1 entry?"));
+        handle_history_mode(&mut app, key(KeyCode::Char('y'))).await;
+        assert_eq!(methods(&app), ["m3"]);
+
+        // Leaving History mode drops the marks
+        handle_history_mode(&mut app, key(KeyCode::Char('a'))).await;
+        assert_eq!(app.history_marks.len(), 1);
+        handle_history_mode(&mut app, key(KeyCode::Esc)).await;
+        assert!(app.history_marks.is_empty());
+    }
+
+    #[test]
+    fn exported_history_reads_back_as_history() {
+        let mut app = App::new();
+        for id in 0..3 {
+            let response = crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(serde_json::json!(id)), error: None, id };
+            app.record(crate::rpc::JsonRpcRequest::new("eth_blockNumber", serde_json::json!([]), id), response, None);
+        }
+        let dir = std::env::temp_dir().join(format!("eli-history-export-{}", std::process::id()));
+        let path = app.export_history(&[1, 3], &dir, 1_700_000_000).unwrap();
+        assert_eq!(path, dir.join("history-1700000000.json"));
+        let entries = crate::history::decode(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(entries.iter().map(|e| e.seq).collect::<Vec<_>>(), [1, 3]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn esc_retraces_three_levels_one_at_a_time() {
+        let mut app = App::new();
+        let response = crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(serde_json::json!("0x1")), error: None, id: 1 };
+        app.record(crate::rpc::JsonRpcRequest::new("eth_blockNumber", serde_json::json!([]), 1), response, None);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        handle_main_mode(&mut app, key(KeyCode::Char('h'))).await;
+        handle_history_mode(&mut app, key(KeyCode::Char(' '))).await;
+        handle_history_mode(&mut app, key(KeyCode::Enter)).await;
+        assert_eq!(app.breadcrumb(), "Main › History › Response");
+        handle_response_view_mode(&mut app, key(KeyCode::Char(','))).await;
+        assert_eq!(app.breadcrumb(), "Main › History › Response › Settings");
+
+        // The popup closes first, then one mode per Esc
+        handle_settings_popup(&mut app, key(KeyCode::Esc)).await;
+        assert_eq!(app.mode, AppMode::ResponseView);
+        handle_response_view_mode(&mut app, key(KeyCode::Esc)).await;
+        assert_eq!((app.mode, app.response_view.is_none()), (AppMode::History, true));
+        // History kept its marks while the response was on top
+        assert_eq!(app.history_marks.len(), 1);
+
+        handle_history_mode(&mut app, key(KeyCode::Char('r'))).await;
+        assert_eq!(app.breadcrumb(), "Main › History › Params");
+        handle_param_input_mode(&mut app, key(KeyCode::Esc)).await;
+        assert_eq!(app.mode, AppMode::History);
+        handle_history_mode(&mut app, key(KeyCode::Esc)).await;
+        assert_eq!((app.mode, app.breadcrumb()), (AppMode::Main, "Main".to_string()));
+        assert!(app.history_marks.is_empty());
+
+        // Going to a mode already open closes what's above it
+        app.enter(AppMode::History);
+        app.open_selected_response();
+        app.enter(AppMode::History);
+        assert_eq!((app.nav.as_slice(), app.response_view.is_none()), ([AppMode::Main].as_slice(), true));
+    }
+
+    #[tokio::test]
+    async fn esc_at_the_method_list_clears_the_search_then_quits() {
+        let mut app = App::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        for c in "bal".chars() {
+            handle_main_mode(&mut app, key(KeyCode::Char(c))).await;
+        }
+        handle_main_mode(&mut app, key(KeyCode::Esc)).await;
+        assert!(app.search_input.is_empty());
+        assert_eq!(app.filtered_methods, app.all_methods);
+        assert!(!app.should_quit);
+        handle_main_mode(&mut app, key(KeyCode::Esc)).await;
+        assert!(app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn y_copies_the_request_or_response_with_a_toast() {
+        let mut app = App::new();
+        let copied = crate::clipboard::MemoryClipboard::default();
+        app.clipboard = Box::new(copied.clone());
+        let response = crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(serde_json::json!("0x1")), error: None, id: 4 };
+        app.record(crate::rpc::JsonRpcRequest::new("eth_blockNumber", serde_json::json!([]), 4), response, None);
+        app.enter(AppMode::History);
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        handle_history_mode(&mut app, key('y')).await;
+        assert_eq!(copied.0.lock().unwrap().as_deref(), Some(r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":4}"#));
+        assert_eq!(app.toast.as_ref().unwrap().message, "copied request");
+
+        handle_history_mode(&mut app, key('v')).await;
+        handle_response_view_mode(&mut app, key('y')).await;
+        assert_eq!(copied.0.lock().unwrap().as_deref(), Some("\"0x1\""));
+        assert_eq!(app.toast.as_ref().unwrap().message, "copied response");
+    }
+
+    #[tokio::test]
+    async fn x_dumps_the_long_hex_on_the_top_line_and_esc_returns_there() {
+        let mut app = App::new();
+        let copied = crate::clipboard::MemoryClipboard::default();
+        app.clipboard = Box::new(copied.clone());
+        let input = format!("0xa9059cbb{}{}", "00".repeat(32), "11".repeat(32));
+        let tx = serde_json::json!({"hash": format!("0x{}", "ab".repeat(32)), "input": input});
+        let response = crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(tx), error: None, id: 1 };
+        app.record(crate::rpc::JsonRpcRequest::new("eth_getTransactionByHash", serde_json::json!([]), 1), response, None);
+        app.enter(AppMode::History);
+        app.open_selected_response();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        let view = app.response_view.as_mut().unwrap();
+        view.scroll = view.lines.iter().position(|l| l.contains("\"input\"")).unwrap();
+        let scroll = view.scroll;
+        handle_response_view_mode(&mut app, key(KeyCode::Char('x'))).await;
+        let hex = app.response_view.as_ref().unwrap().hex.as_ref().unwrap();
+        assert_eq!((hex.field.as_str(), hex.bytes.len()), ("input", 68));
+
+        handle_response_view_mode(&mut app, key(KeyCode::Right)).await;
+        handle_response_view_mode(&mut app, key(KeyCode::Char('y'))).await;
+        assert_eq!(copied.0.lock().unwrap().as_deref(), Some(format!("0x{}{}", "00".repeat(4), "11".repeat(28)).as_str()));
+        assert_eq!(app.toast.as_ref().unwrap().message, "copied word 1");
+
+        handle_response_view_mode(&mut app, key(KeyCode::Esc)).await;
+        let view = app.response_view.as_ref().unwrap();
+        assert_eq!((view.hex.is_none(), view.scroll, app.mode), (true, scroll, AppMode::ResponseView));
+    }
+
+    #[tokio::test]
+    async fn typos_are_fixed_mid_string_in_the_search_and_params() {
+        let mut app = App::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        for c in "eth_getBalnce".chars() {
+            handle_main_mode(&mut app, key(KeyCode::Char(c))).await;
+        }
+        for code in [KeyCode::Left, KeyCode::Left, KeyCode::Left, KeyCode::Char('a')] {
+            handle_main_mode(&mut app, key(code)).await;
+        }
+        assert_eq!(app.search_input, "eth_getBalance");
+        // With a search, Home moves the cursor rather than the list
+        handle_main_mode(&mut app, key(KeyCode::Home)).await;
+        handle_main_mode(&mut app, key(KeyCode::Delete)).await;
+        assert_eq!((app.search_input.as_str(), app.search_cursor.index(&app.search_input)), ("th_getBalance", 0));
+        handle_main_mode(&mut app, key(KeyCode::Esc)).await;
+        handle_main_mode(&mut app, key(KeyCode::End)).await;
+        assert_eq!(app.methods_state.selected(), Some(app.filtered_methods.len() - 1));
+
+        app.param_inputs = vec!["0xab".into(), "latst".into()];
+        app.enter(AppMode::ParamInput);
+        for code in [KeyCode::Home, KeyCode::Right, KeyCode::Right, KeyCode::Char('1'), KeyCode::End, KeyCode::Backspace] {
+            handle_param_input_mode(&mut app, key(code)).await;
+        }
+        assert_eq!(app.param_inputs[0], "0x1a");
+        // Focus moves: the cursor starts at the end of the next field
+        handle_param_input_mode(&mut app, key(KeyCode::Tab)).await;
+        for code in [KeyCode::Left, KeyCode::Left, KeyCode::Char('e')] {
+            handle_param_input_mode(&mut app, key(code)).await;
+        }
+        assert_eq!(app.param_inputs[1], "latest");
+    }
+
+    #[tokio::test]
+    async fn help_lists_the_keys_of_the_mode_it_opens_over() {
+        let mut app = App::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        handle_main_mode(&mut app, key(KeyCode::Char('?'))).await;
+        assert_eq!(app.help, Some(crate::hints::Context::Main));
+        // Any key closes it without doing anything else
+        handle_help_overlay(&mut app, key(KeyCode::Char('h')));
+        assert_eq!((app.help, app.mode), (None, AppMode::Main));
+
+        app.param_inputs = vec!["0xab".into(), String::new()];
+        app.enter(AppMode::ParamInput);
+        // '?' is text in a filled field, help in an empty one
+        handle_param_input_mode(&mut app, key(KeyCode::Char('?'))).await;
+        assert_eq!((app.param_inputs[0].as_str(), app.help), ("0xab?", None));
+        app.move_param_focus(1);
+        handle_param_input_mode(&mut app, key(KeyCode::Char('?'))).await;
+        assert_eq!(app.help, Some(crate::hints::Context::ParamFields));
+        handle_help_overlay(&mut app, key(KeyCode::Esc));
+        assert_eq!((app.help, app.mode), (None, AppMode::ParamInput));
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL)).await;
+        handle_param_input_mode(&mut app, key(KeyCode::F(1))).await;
+        assert_eq!(app.help, Some(crate::hints::Context::ParamRaw));
+        handle_help_overlay(&mut app, key(KeyCode::Enter));
+
+        app.enter(AppMode::History);
+        handle_history_mode(&mut app, key(KeyCode::Char('?'))).await;
+        assert_eq!(app.help, Some(crate::hints::Context::History));
+    }
+
+    #[tokio::test]
+    async fn edits_undo_by_runs_until_the_request_leaves() {
+        let mut app = App::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        app.param_inputs = vec![String::new(), String::new()];
+        app.enter(AppMode::ParamInput);
+        for c in "0xabc".chars() {
+            handle_param_input_mode(&mut app, key(KeyCode::Char(c))).await;
+        }
+        handle_param_input_mode(&mut app, key(KeyCode::Backspace)).await;
+        handle_param_input_mode(&mut app, key(KeyCode::Left)).await;
+        handle_param_input_mode(&mut app, key(KeyCode::Char('1'))).await;
+        assert_eq!(app.param_inputs[0], "0xa1b");
+        // Undo goes back past the '1', the backspace, then the typing
+        handle_param_input_mode(&mut app, ctrl('z')).await;
+        assert_eq!(app.param_inputs[0], "0xab");
+        handle_param_input_mode(&mut app, ctrl('z')).await;
+        assert_eq!(app.param_inputs[0], "0xabc");
+        handle_param_input_mode(&mut app, ctrl('y')).await;
+        handle_param_input_mode(&mut app, KeyEvent::new(KeyCode::Char('Z'), KeyModifiers::CONTROL | KeyModifiers::SHIFT)).await;
+        assert_eq!(app.param_inputs[0], "0xa1b");
+        // Each field has its own history
+        handle_param_input_mode(&mut app, key(KeyCode::Tab)).await;
+        handle_param_input_mode(&mut app, ctrl('z')).await;
+        assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("nothing to undo"));
+
+        // The raw buffer too; switching modes starts over
+        handle_param_input_mode(&mut app, ctrl('r')).await;
+        let text = app.raw_params.as_ref().unwrap().area.text();
+        for code in [KeyCode::Backspace, KeyCode::Backspace, KeyCode::Char('x')] {
+            handle_param_input_mode(&mut app, key(code)).await;
+        }
+        handle_param_input_mode(&mut app, ctrl('z')).await;
+        handle_param_input_mode(&mut app, ctrl('z')).await;
+        assert_eq!(app.raw_params.as_ref().unwrap().area.text(), text);
+
+        app.back();
+        assert!(app.field_undo.is_empty() && !app.raw_undo.can_undo());
+    }
+
+    #[tokio::test]
+    async fn keys_go_to_the_focused_pane() {
+        let mut app = App::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(app.pane(), Some(Focus::Search));
+        // From the search, arrows still move through the list
+        handle_main_mode(&mut app, key(KeyCode::Down)).await;
+        handle_main_mode(&mut app, key(KeyCode::Tab)).await;
+        assert_eq!(app.pane(), Some(Focus::Methods));
+        handle_main_mode(&mut app, key(KeyCode::Down)).await;
+        assert_eq!(app.methods_state.selected(), Some(2));
+
+        // On the docs, they scroll it and leave the selection alone
+        handle_main_mode(&mut app, key(KeyCode::Tab)).await;
+        handle_main_mode(&mut app, key(KeyCode::Down)).await;
+        handle_main_mode(&mut app, key(KeyCode::Down)).await;
+        assert_eq!((app.methods_state.selected(), app.pane_scroll), (Some(2), 2));
+        // Left/Right switch its tab instead of moving the search cursor
+        handle_main_mode(&mut app, key(KeyCode::Right)).await;
+        assert_eq!((app.docs_tab, app.pane_scroll), (DocsTab::Schema, 0));
+
+        // Typing from any pane goes to the search, which takes focus
+        handle_main_mode(&mut app, key(KeyCode::Char('x'))).await;
+        assert_eq!((app.pane(), app.search_input.as_str()), (Some(Focus::Search), "x"));
+        handle_main_mode(&mut app, key(KeyCode::Left)).await;
+        assert_eq!(app.docs_tab, DocsTab::Schema);
+
+        // History: the detail scrolls while the list keeps its selection
+        handle_main_mode(&mut app, key(KeyCode::Esc)).await;
+        app.history.push(crate::app::HistoryEntry {
+            seq: 1,
+            ..crate::app::HistoryEntry::new(
+                crate::rpc::JsonRpcRequest::new("eth_chainId", serde_json::json!([]), 1),
+                crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(serde_json::json!("0x1")), error: None, id: 1 },
+            )
+        });
+        app.history_state.select(Some(0));
+        handle_main_mode(&mut app, key(KeyCode::Char('h'))).await;
+        assert_eq!(app.pane(), Some(Focus::List));
+        handle_history_mode(&mut app, key(KeyCode::Tab)).await;
+        handle_history_mode(&mut app, key(KeyCode::Down)).await;
+        assert_eq!((app.pane(), app.pane_scroll, app.history_state.selected()), (Some(Focus::Detail), 1, Some(0)));
+    }
+
+    #[tokio::test]
+    async fn entering_a_mode_focuses_its_first_pane() {
+        let mut app = App::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        handle_main_mode(&mut app, key(KeyCode::Tab)).await;
+        handle_main_mode(&mut app, key(KeyCode::Down)).await;
+        handle_main_mode(&mut app, key(KeyCode::Enter)).await;
+        assert_eq!((app.mode, app.pane()), (AppMode::ParamInput, Some(Focus::Fields)));
+
+        // The preview scrolls; typing goes back to the fields
+        handle_param_input_mode(&mut app, key(KeyCode::BackTab)).await;
+        assert_eq!(app.pane(), Some(Focus::Preview));
+        handle_param_input_mode(&mut app, key(KeyCode::Char('0'))).await;
+        assert_eq!((app.pane(), app.param_inputs[0].as_str()), (Some(Focus::Fields), "0"));
+        handle_param_input_mode(&mut app, key(KeyCode::BackTab)).await;
+
+        // Back on the method list, focus is on the search again
+        handle_param_input_mode(&mut app, key(KeyCode::Esc)).await;
+        assert_eq!((app.mode, app.pane(), app.pane_scroll), (AppMode::Main, Some(Focus::Search), 0));
+        handle_main_mode(&mut app, key(KeyCode::Char('h'))).await;
+        handle_history_mode(&mut app, key(KeyCode::Tab)).await;
+        handle_history_mode(&mut app, key(KeyCode::Esc)).await;
+        handle_main_mode(&mut app, key(KeyCode::Char('h'))).await;
+        assert_eq!(app.pane(), Some(Focus::List));
+    }
+}
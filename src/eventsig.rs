@@ -0,0 +1,162 @@
+// src/eventsig.rs
+
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+
+use crate::rpc::JsonRpcRequest;
+
+/// Event signatures common enough to recognise by topic0: token standards,
+/// WETH, Uniswap, OpenZeppelin ownership/roles/proxies.
+pub const KNOWN_EVENTS: &[&str] = &[
+    "Transfer(address,address,uint256)",
+    "Approval(address,address,uint256)",
+    "ApprovalForAll(address,address,bool)",
+    "TransferSingle(address,address,address,uint256,uint256)",
+    "TransferBatch(address,address,address,uint256[],uint256[])",
+    "Deposit(address,uint256)",
+    "Withdrawal(address,uint256)",
+    "PairCreated(address,address,address,uint256)",
+    "Sync(uint112,uint112)",
+    "Mint(address,uint256,uint256)",
+    "Burn(address,uint256,uint256,address)",
+    "Swap(address,uint256,uint256,uint256,uint256,address)",
+    "Swap(address,address,int256,int256,uint160,uint128,int24)",
+    "OwnershipTransferred(address,address)",
+    "RoleGranted(bytes32,address,address)",
+    "RoleRevoked(bytes32,address,address)",
+    "Upgraded(address)",
+    "AdminChanged(address,address)",
+    "Paused(address)",
+    "Unpaused(address)",
+];
+
+/// Types tried in place of each argument when looking for what a topic0
+/// was actually hashed from.
+const COMMON_TYPES: &[&str] =
+    &["address", "uint256", "uint", "uint8", "uint128", "int256", "int", "bool", "bytes32", "bytes", "string"];
+
+/// `0x`-prefixed keccak-256 of `signature`: the log's topic0.
+pub fn topic0(signature: &str) -> String {
+    let hash = Keccak256::digest(signature.as_bytes());
+    format!("0x{}", hash.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+/// Splits "Name(a,b)" into its name and argument types.
+fn split(signature: &str) -> Option<(&str, Vec<&str>)> {
+    let (name, rest) = signature.split_once('(')?;
+    let args = rest.strip_suffix(')')?;
+    Some((name, if args.is_empty() { Vec::new() } else { args.split(',').collect() }))
+}
+
+/// Signatures with `known`'s name but not quite its argument types, most
+/// likely mistakes first: every `uint256`/`int256` spelt `uint`/`int`,
+/// then each argument swapped for another common type, one at a time.
+fn near_misses(known: &str) -> Vec<String> {
+    let Some((name, args)) = split(known) else { return Vec::new() };
+    let join = |args: &[String]| format!("{}({})", name, args.join(","));
+    let mut misses = Vec::new();
+    let short: Vec<String> = args.iter().map(|a| a.replace("uint256", "uint").replace("int256", "int")).collect();
+    if short.iter().zip(&args).any(|(s, a)| s != a) {
+        misses.push(join(&short));
+    }
+    for (i, arg) in args.iter().enumerate() {
+        for ty in COMMON_TYPES.iter().filter(|ty| *ty != arg) {
+            let mut swapped: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            swapped[i] = ty.to_string();
+            misses.push(join(&swapped));
+        }
+    }
+    misses
+}
+
+/// The known signature whose near miss hashes to `topic`, if any: the
+/// signature the user most likely meant to write.
+pub fn nearest(topic: &str) -> Option<&'static str> {
+    let topic = topic.to_lowercase();
+    KNOWN_EVENTS.iter().copied().find(|known| near_misses(known).iter().any(|miss| topic0(miss) == topic))
+}
+
+/// Whether `topic` is the topic0 of a [`KNOWN_EVENTS`] entry.
+pub fn is_known(topic: &str) -> bool {
+    let topic = topic.to_lowercase();
+    KNOWN_EVENTS.iter().any(|known| topic0(known) == topic)
+}
+
+/// The topic0 values an `eth_getLogs` filter asks for: a single topic or
+/// the alternatives of an OR list.
+fn requested_topic0(params: &Value) -> Vec<&str> {
+    match &params[0]["topics"][0] {
+        Value::String(topic) => vec![topic.as_str()],
+        Value::Array(alternatives) => alternatives.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// For an `eth_getLogs` that returned no logs while filtering on a topic0
+/// no known event has, a nudge to check the signature it was hashed from,
+/// naming the nearest known one when a near miss matches. `None` whenever
+/// logs came back.
+pub fn empty_logs_hint(request: &JsonRpcRequest, result: &Value) -> Option<String> {
+    if request.method != "eth_getLogs" || !result.as_array().is_some_and(Vec::is_empty) {
+        return None;
+    }
+    let unknown: Vec<&str> = requested_topic0(&request.params).into_iter().filter(|t| !is_known(t)).collect();
+    if unknown.is_empty() {
+        return None;
+    }
+    let hint = "topic0 doesn't match any known event — double-check the signature";
+    Some(match unknown.iter().find_map(|t| nearest(t)) {
+        Some(meant) => format!("{}; did you mean {}?", hint, meant),
+        None => hint.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const TRANSFER: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    fn get_logs(topics: Value) -> JsonRpcRequest {
+        JsonRpcRequest::new("eth_getLogs", json!([{"fromBlock": "0x1", "topics": topics}]), 1)
+    }
+
+    #[test]
+    fn hashes_signatures_to_topic0() {
+        assert_eq!(topic0("Transfer(address,address,uint256)"), TRANSFER);
+        assert!(is_known(TRANSFER));
+        assert!(!is_known(&topic0("Transfer(address,address,uint)")));
+    }
+
+    #[test]
+    fn nearest_match_has_the_same_name_and_different_arg_types() {
+        assert_eq!(nearest(&topic0("Transfer(address,address,uint)")), Some("Transfer(address,address,uint256)"));
+        assert_eq!(nearest(&topic0("Approval(address,uint256,uint256)")), Some("Approval(address,address,uint256)"));
+        assert_eq!(
+            nearest(&topic0("Swap(address,address,int,int,uint160,uint128,int24)")),
+            Some("Swap(address,address,int256,int256,uint160,uint128,int24)")
+        );
+        // A different name, or too many differences, isn't a near miss
+        assert_eq!(nearest(&topic0("Transferred(address,address,uint256)")), None);
+        assert_eq!(nearest(&topic0("Transfer(uint,uint,uint)")), None);
+        assert_eq!(nearest(TRANSFER), None);
+    }
+
+    #[test]
+    fn hints_only_for_empty_results_with_unknown_topic0() {
+        let typo = get_logs(json!([topic0("Transfer(address,address,uint)")]));
+        assert_eq!(
+            empty_logs_hint(&typo, &json!([])).unwrap(),
+            "topic0 doesn't match any known event — double-check the signature; did you mean Transfer(address,address,uint256)?"
+        );
+        // Logs came back: nothing to suggest
+        assert_eq!(empty_logs_hint(&typo, &json!([{"topics": []}])), None);
+        // A known topic0 really has no logs in range
+        assert_eq!(empty_logs_hint(&get_logs(json!([TRANSFER])), &json!([])), None);
+        assert_eq!(empty_logs_hint(&get_logs(json!([null, TRANSFER])), &json!([])), None);
+
+        let custom = get_logs(json!([[TRANSFER, topic0("Custom(uint256)")]]));
+        assert_eq!(empty_logs_hint(&custom, &json!([])).unwrap(), "topic0 doesn't match any known event — double-check the signature");
+    }
+}
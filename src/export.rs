@@ -0,0 +1,123 @@
+// src/export.rs
+
+use std::io::Write;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::shape::{shape_of, Shape};
+
+/// `value` as CSV, or `None` if it isn't an array.
+///
+/// An array of objects becomes one row per object, with a column for every
+/// key seen in any of them, in order of first appearance (each object's own
+/// keys come sorted); missing keys are empty cells. Any other array becomes a single `value` column. Nested arrays
+/// and objects are JSON-encoded into their cell. With `decimal`, hex
+/// quantities (`0x` + up to 16 digits) are written as decimal integers.
+pub fn to_csv(value: &Value, decimal: bool) -> Option<String> {
+    let rows = value.as_array()?;
+    let mut out = Vec::new();
+    write_csv(&mut out, decimal, |row| rows.iter().try_for_each(row)).ok()?;
+    String::from_utf8(out).ok()
+}
+
+/// Writes the CSV [`to_csv`] would for an array read one element at a
+/// time, e.g. from a spill file. `each` hands every element to its
+/// argument in order; it runs twice, for the columns and then the rows.
+/// Returns the number of rows written.
+pub fn write_csv(
+    out: &mut impl Write,
+    decimal: bool,
+    mut each: impl FnMut(&mut dyn FnMut(&Value) -> Result<()>) -> Result<()>,
+) -> Result<usize> {
+    let (mut columns, mut objects, mut rows) = (Vec::<String>::new(), true, 0);
+    each(&mut |row| {
+        rows += 1;
+        match row.as_object() {
+            Some(row) => {
+                for key in row.keys() {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+            }
+            None => objects = false,
+        }
+        Ok(())
+    })?;
+    if rows == 0 || !objects {
+        (columns, objects) = (vec!["value".to_string()], false);
+    }
+    write_row(out, columns.iter().cloned())?;
+    each(&mut |row| {
+        if objects {
+            write_row(out, columns.iter().map(|c| row.get(c).map(|v| cell(v, decimal)).unwrap_or_default()))
+        } else {
+            write_row(out, [cell(row, decimal)].into_iter())
+        }
+    })?;
+    Ok(rows)
+}
+
+fn cell(value: &Value, decimal: bool) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) if decimal && shape_of(s) == Shape::Quantity => {
+            u64::from_str_radix(&s[2..], 16).map(|n| n.to_string()).unwrap_or_else(|_| s.clone())
+        }
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn write_row(out: &mut impl Write, cells: impl Iterator<Item = String>) -> Result<()> {
+    let cells: Vec<String> = cells.map(|c| escape(&c)).collect();
+    writeln!(out, "{}", cells.join(","))?;
+    Ok(())
+}
+
+/// Quotes a cell when it contains a delimiter, quote or line break.
+fn escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn heterogeneous_objects_share_a_column_union() {
+        let logs = json!([
+            {"address": "0x00000000000000000000000000000000000000c1", "logIndex": "0x1a"},
+            {"address": "0x00000000000000000000000000000000000000c2", "removed": false, "blockNumber": "0x10"},
+        ]);
+        assert_eq!(
+            to_csv(&logs, true).unwrap(),
+            "address,logIndex,blockNumber,removed\n0x00000000000000000000000000000000000000c1,26,,\n0x00000000000000000000000000000000000000c2,,16,false\n"
+        );
+        assert_eq!(to_csv(&logs, false).unwrap().lines().nth(1), Some("0x00000000000000000000000000000000000000c1,0x1a,,"));
+    }
+
+    #[test]
+    fn nested_values_are_json_encoded_and_quoted() {
+        let rows = json!([{"topics": ["0x01", "0x02"], "data": {"a": 1}, "note": "say \"hi\""}]);
+        assert_eq!(
+            to_csv(&rows, true).unwrap(),
+            "data,note,topics\n\"{\"\"a\"\":1}\",\"say \"\"hi\"\"\",\"[\"\"0x01\"\",\"\"0x02\"\"]\"\n"
+        );
+    }
+
+    #[test]
+    fn other_arrays_are_a_single_column() {
+        let hashes = json!(["0x10", format!("0x{}", "ab".repeat(32)), null, {"k": 1}]);
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert_eq!(to_csv(&hashes, true).unwrap(), format!("value\n16\n{}\n\n\"{{\"\"k\"\":1}}\"\n", hash));
+        assert_eq!(to_csv(&json!([]), true).unwrap(), "value\n");
+        assert_eq!(to_csv(&json!({"a": 1}), true), None);
+    }
+}
@@ -0,0 +1,205 @@
+// src/extension.rs
+
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::spec::{self, MethodSpec};
+
+/// Extension files bundled in the binary, one per L2 family.
+const BUNDLED: &[(&str, &str)] = &[
+    ("optimism.json", include_str!("../specs/optimism.json")),
+    ("arbitrum.json", include_str!("../specs/arbitrum.json")),
+    ("zksync.json", include_str!("../specs/zksync.json")),
+];
+
+/// An extension file: methods a family of chains adds on top of the
+/// standard spec.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtensionFile {
+    name: String,
+    chain_ids: Vec<u64>,
+    methods: Vec<MethodEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MethodEntry {
+    name: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    params: Vec<ParamEntry>,
+    /// OpenRPC result; only its schema is kept.
+    #[serde(default)]
+    result: Option<ResultEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultEntry {
+    schema: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ParamEntry {
+    name: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// A parsed extension, usable wherever the builtin spec is.
+#[derive(Debug)]
+pub struct SpecExtension {
+    pub name: String,
+    /// Chains it is merged for automatically.
+    pub chain_ids: Vec<u64>,
+    pub methods: Vec<MethodSpec>,
+    /// One-line method descriptions for the docs panel.
+    pub descriptions: Vec<(String, String)>,
+    /// Result schemas, for the docs panel's schema tab.
+    pub results: Vec<(String, serde_json::Value)>,
+}
+
+impl SpecExtension {
+    pub fn description(&self, method: &str) -> Option<&str> {
+        self.descriptions.iter().find(|(name, _)| name == method).map(|(_, d)| d.as_str())
+    }
+
+    pub fn result_schema(&self, method: &str) -> Option<&serde_json::Value> {
+        self.results.iter().find(|(name, _)| name == method).map(|(_, schema)| schema)
+    }
+}
+
+/// Parses an extension file. Bundled files are parsed once.
+pub fn parse(text: &str) -> Result<SpecExtension> {
+    let file: ExtensionFile = serde_json::from_str(text)?;
+    let mut methods = Vec::new();
+    let mut descriptions = Vec::new();
+    let mut results = Vec::new();
+    for method in file.methods {
+        let params: Vec<String> = method.params.iter().map(|p| p.name.clone()).collect();
+        let help: Vec<String> = method.params.into_iter().map(|p| p.description).collect();
+        if let Some(summary) = method.summary {
+            descriptions.push((method.name.clone(), summary));
+        }
+        if let Some(result) = method.result {
+            results.push((method.name.clone(), result.schema));
+        }
+        methods.push(MethodSpec::owned(method.name, params, help, Vec::new()));
+    }
+    Ok(SpecExtension { name: file.name, chain_ids: file.chain_ids, methods, descriptions, results })
+}
+
+/// The bundled extensions.
+pub fn bundled() -> &'static [SpecExtension] {
+    static BUNDLED_EXTENSIONS: OnceLock<Vec<SpecExtension>> = OnceLock::new();
+    BUNDLED_EXTENSIONS.get_or_init(|| {
+        BUNDLED
+            .iter()
+            .map(|(file, text)| parse(text).with_context(|| format!("bundled spec extension {}", file)).unwrap())
+            .collect()
+    })
+}
+
+/// Which extension to use: the one matching the detected chain, none, or
+/// a named one regardless of chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ExtensionChoice {
+    #[default]
+    Auto,
+    Off,
+    Force(String),
+}
+
+impl From<String> for ExtensionChoice {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "auto" | "" => ExtensionChoice::Auto,
+            "off" => ExtensionChoice::Off,
+            _ => ExtensionChoice::Force(s),
+        }
+    }
+}
+
+impl From<ExtensionChoice> for String {
+    fn from(choice: ExtensionChoice) -> Self {
+        match choice {
+            ExtensionChoice::Auto => "auto".to_string(),
+            ExtensionChoice::Off => "off".to_string(),
+            ExtensionChoice::Force(name) => name,
+        }
+    }
+}
+
+/// The extension `choice` selects for `chain_id`, if any.
+pub fn select(choice: &ExtensionChoice, chain_id: Option<u64>) -> Option<&'static SpecExtension> {
+    match choice {
+        ExtensionChoice::Off => None,
+        ExtensionChoice::Force(name) => bundled().iter().find(|e| e.name == *name),
+        ExtensionChoice::Auto => {
+            let id = chain_id?;
+            bundled().iter().find(|e| e.chain_ids.contains(&id))
+        }
+    }
+}
+
+/// `base` with `extension` merged in. A method both define takes the
+/// extension's entry, which describes it better for that chain; new
+/// methods go at the end.
+pub fn merge(base: &[MethodSpec], extension: &SpecExtension) -> Vec<MethodSpec> {
+    spec::merge(base, &extension.methods)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::RPC_SPECS;
+
+    #[test]
+    fn bundled_extensions_parse() {
+        let names: Vec<_> = bundled().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["optimism", "arbitrum", "zksync"]);
+        for extension in bundled() {
+            for method in &extension.methods {
+                assert_eq!(method.params.len(), method.help.len(), "{}", method.name);
+                assert!(extension.description(&method.name).is_some(), "{}", method.name);
+            }
+        }
+    }
+
+    #[test]
+    fn chain_10_selects_optimism_unless_overridden() {
+        assert_eq!(select(&ExtensionChoice::Auto, Some(10)).unwrap().name, "optimism");
+        assert_eq!(select(&ExtensionChoice::Auto, Some(1)).map(|e| e.name.as_str()), None);
+        assert_eq!(select(&ExtensionChoice::Auto, None).map(|e| e.name.as_str()), None);
+        assert_eq!(select(&ExtensionChoice::Off, Some(10)).map(|e| e.name.as_str()), None);
+        let forced = ExtensionChoice::Force("zksync".into());
+        assert_eq!(select(&forced, Some(10)).unwrap().name, "zksync");
+        assert_eq!(select(&ExtensionChoice::Force("scroll".into()), Some(10)).map(|e| e.name.as_str()), None);
+
+        let choice: ExtensionChoice = serde_json::from_str("\"off\"").unwrap();
+        assert_eq!(choice, ExtensionChoice::Off);
+        assert_eq!(serde_json::to_string(&ExtensionChoice::Force("arbitrum".into())).unwrap(), "\"arbitrum\"");
+    }
+
+    #[test]
+    fn merge_dedupes_by_name_preferring_the_extension() {
+        let extension = parse(
+            r#"{"name": "test", "chainIds": [10], "methods": [
+                {"name": "eth_getBalance", "summary": "Balance, in the L2's gas token.",
+                 "params": [{"name": "address", "description": "L2 account"}, {"name": "block", "description": "L2 block"}]},
+                {"name": "optimism_syncStatus", "summary": "Sync status."}
+            ]}"#,
+        )
+        .unwrap();
+        let merged = merge(RPC_SPECS, &extension);
+        assert_eq!(merged.len(), RPC_SPECS.len() + 1);
+        let balance: Vec<_> = merged.iter().filter(|m| m.name == "eth_getBalance").collect();
+        assert_eq!(balance.len(), 1);
+        assert_eq!(balance[0].help(0), Some("L2 account"));
+        assert_eq!(merged.last().unwrap().name, "optimism_syncStatus");
+        assert_eq!(merged[0].name, RPC_SPECS[0].name);
+    }
+}
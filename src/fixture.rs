@@ -0,0 +1,369 @@
+// src/fixture.rs
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use reqwest::Url;
+
+use crate::app::HistoryEntry;
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse, Transport};
+use crate::shape::{shape_of, Shape};
+
+/// Replaces addresses with numbered placeholders, the same address always
+/// getting the same one: the first address seen becomes
+/// `0xad00…01`, the next `0xad00…02`, and so on.
+#[derive(Debug, Default)]
+pub struct Redactor {
+    placeholders: HashMap<String, String>,
+}
+
+impl Redactor {
+    /// The placeholder for `address`, compared case-insensitively.
+    pub fn placeholder(&mut self, address: &str) -> String {
+        let next = self.placeholders.len() + 1;
+        self.placeholders.entry(address.to_lowercase()).or_insert_with(|| format!("0xad{:038x}", next)).clone()
+    }
+
+    /// Redacts every address in `value`, in string values and object keys.
+    /// Addresses inside longer hex (calldata, padded topics) are left alone.
+    pub fn redact(&mut self, value: &mut Value) {
+        match value {
+            Value::String(s) if shape_of(s) == Shape::Address => *s = self.placeholder(s),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact(item)),
+            Value::Object(fields) => {
+                let taken = std::mem::take(fields);
+                for (key, mut field) in taken {
+                    self.redact(&mut field);
+                    let key = if shape_of(&key) == Shape::Address { self.placeholder(&key) } else { key };
+                    fields.insert(key, field);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Fields zeroed in recorded fixtures unless other ones are asked for:
+/// they change from one recording to the next.
+pub const DEFAULT_SCRUB: &[&str] = &["timestamp"];
+
+/// What [`record`] makes deterministic before writing a fixture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrubRules {
+    /// Fields, at any depth, whose values become `0x0`.
+    pub fields: Vec<String>,
+    /// Also replace every 32-byte hash with a numbered placeholder.
+    pub hashes: bool,
+}
+
+impl Default for ScrubRules {
+    fn default() -> Self {
+        ScrubRules { fields: DEFAULT_SCRUB.iter().map(|f| f.to_string()).collect(), hashes: false }
+    }
+}
+
+impl ScrubRules {
+    /// What was scrubbed, for `meta.json`.
+    pub fn summary(&self) -> Vec<String> {
+        let mut summary = self.fields.clone();
+        if self.hashes {
+            summary.push("hashes".to_string());
+        }
+        summary
+    }
+}
+
+/// Applies [`ScrubRules`], the same hash always getting the same
+/// placeholder (`0x00…01`, `0x00…02`, …) across the request and response.
+#[derive(Debug)]
+pub struct Scrubber<'a> {
+    rules: &'a ScrubRules,
+    placeholders: HashMap<String, String>,
+}
+
+impl<'a> Scrubber<'a> {
+    pub fn new(rules: &'a ScrubRules) -> Self {
+        Scrubber { rules, placeholders: HashMap::new() }
+    }
+
+    pub fn scrub(&mut self, value: &mut Value) {
+        match value {
+            Value::String(s) if self.rules.hashes && shape_of(s) == Shape::Hash32 => {
+                let next = self.placeholders.len() + 1;
+                *s = self.placeholders.entry(s.to_lowercase()).or_insert_with(|| format!("0x{:064x}", next)).clone();
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.scrub(item)),
+            Value::Object(fields) => {
+                for (key, field) in fields.iter_mut() {
+                    if self.rules.fields.contains(key) {
+                        *field = Value::String("0x0".into());
+                    } else {
+                        self.scrub(field);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `meta.json` of a fixture.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Meta {
+    pub method: String,
+    /// Scheme and port of the endpoint; the host and anything after it are
+    /// left out.
+    pub endpoint: String,
+    pub chain_id: Option<u64>,
+    /// Latest block the session had seen when the fixture was written.
+    pub latest_block: Option<u64>,
+    /// Unix seconds.
+    pub exported_at: u64,
+    pub eli_version: String,
+    pub addresses_redacted: bool,
+    /// What [`record`] scrubbed; empty for fixtures exported from history.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scrubbed: Vec<String>,
+}
+
+/// `url` reduced to its scheme and port, e.g. "https://…:443".
+pub fn scheme_and_port(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(parsed) => match parsed.port_or_known_default() {
+            Some(port) => format!("{}://…:{}", parsed.scheme(), port),
+            None => format!("{}://…", parsed.scheme()),
+        },
+        Err(_) => "…".to_string(),
+    }
+}
+
+/// Directory name for a fixture of `method` written at `unix_secs`.
+pub fn dir_name(method: &str, unix_secs: u64) -> String {
+    format!("{}-{}", method, unix_secs)
+}
+
+/// Writes `entry` as a fixture directory under `parent`: `request.json`,
+/// `response.json` and `meta.json`. With `redact`, addresses are replaced
+/// by placeholders consistently across the request and response.
+pub fn write(parent: &Path, entry: &HistoryEntry, meta: &Meta, redact: bool) -> Result<PathBuf> {
+    let mut request = serde_json::to_value(&entry.request)?;
+    let mut response = serde_json::to_value(&entry.response)?;
+    if redact {
+        let mut redactor = Redactor::default();
+        redactor.redact(&mut request);
+        redactor.redact(&mut response);
+    }
+    write_values(parent, request, response, meta)
+}
+
+fn write_values(parent: &Path, request: Value, response: Value, meta: &Meta) -> Result<PathBuf> {
+    let dir = parent.join(dir_name(&meta.method, meta.exported_at));
+    std::fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+    for (file, value) in [("request.json", request), ("response.json", response), ("meta.json", serde_json::to_value(meta)?)] {
+        write_json(&dir.join(file), &value)?;
+    }
+    Ok(dir)
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)? + "\n").with_context(|| format!("writing {}", path.display()))
+}
+
+/// A fixture directory as tests read it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixture {
+    pub request: JsonRpcRequest,
+    pub response: JsonRpcResponse,
+    /// Missing for hand-written fixtures.
+    pub meta: Option<Meta>,
+}
+
+/// Reads the fixture in `dir`, whether written by [`write`], [`record`]
+/// or by hand.
+pub fn load(dir: &Path) -> Result<Fixture> {
+    fn parse<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("malformed {}", path.display()))
+    }
+    let meta = dir.join("meta.json");
+    Ok(Fixture {
+        request: parse(&dir.join("request.json"))?,
+        response: parse(&dir.join("response.json"))?,
+        meta: if meta.exists() { Some(parse(&meta)?) } else { None },
+    })
+}
+
+/// One line of a recording directory's `index.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Fixture directory, relative to the index.
+    pub dir: String,
+    pub method: String,
+}
+
+/// Sends `method` with `params` through `transport`, scrubs the pair by
+/// `rules` and writes it as a fixture under `out`, adding it to
+/// `out/index.json`. Error responses are recorded like any other.
+pub async fn record(
+    transport: &dyn Transport,
+    url: &str,
+    method: &str,
+    params: Value,
+    out: &Path,
+    rules: &ScrubRules,
+    now: u64,
+) -> Result<PathBuf> {
+    let request = JsonRpcRequest::new(method, params, 1);
+    let response = transport.send(url, request.clone()).await.with_context(|| format!("{} failed", method))?;
+    let (mut request, mut response) = (serde_json::to_value(&request)?, serde_json::to_value(&response)?);
+    let mut scrubber = Scrubber::new(rules);
+    scrubber.scrub(&mut request);
+    scrubber.scrub(&mut response);
+    let meta = Meta {
+        method: method.to_string(),
+        endpoint: scheme_and_port(url),
+        chain_id: None,
+        latest_block: None,
+        exported_at: now,
+        eli_version: env!("CARGO_PKG_VERSION").to_string(),
+        addresses_redacted: false,
+        scrubbed: rules.summary(),
+    };
+    let dir = write_values(out, request, response, &meta)?;
+
+    let index_path = out.join("index.json");
+    let mut index: Vec<IndexEntry> = match std::fs::read_to_string(&index_path) {
+        Ok(text) => serde_json::from_str(&text).with_context(|| format!("malformed {}", index_path.display()))?,
+        Err(_) => Vec::new(),
+    };
+    let name = dir_name(method, now);
+    index.retain(|entry| entry.dir != name);
+    index.push(IndexEntry { dir: name, method: method.to_string() });
+    index.sort_by(|a, b| a.dir.cmp(&b.dir));
+    write_json(&index_path, &index)?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{JsonRpcRequest, JsonRpcResponse};
+    use serde_json::json;
+
+    const A: &str = "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f";
+    const B: &str = "0x00000000000000000000000000000000000000b2";
+
+    #[test]
+    fn same_address_same_placeholder() {
+        let mut redactor = Redactor::default();
+        let mut request = json!([{"from": A, "to": B, "input": format!("0x{}", "ab".repeat(36))}, "latest"]);
+        let mut response = json!({"from": A.to_uppercase().replace("0X", "0x"), "logs": [{"address": B}], B: "0x1"});
+        redactor.redact(&mut request);
+        redactor.redact(&mut response);
+        let (a, b) = (format!("0xad{:038x}", 1), format!("0xad{:038x}", 2));
+        assert_eq!(request[0]["from"], a);
+        assert_eq!(request[0]["to"], b);
+        assert_eq!(response["from"], a);
+        assert_eq!(response["logs"][0]["address"], b);
+        assert_eq!(response[&b], "0x1");
+        // Longer hex isn't an address
+        assert_eq!(request[0]["input"].as_str().unwrap().len(), 74);
+        assert_eq!(request[1], "latest");
+    }
+
+    #[test]
+    fn writes_request_response_and_meta() {
+        let parent = std::env::temp_dir().join(format!("eli-fixture-{}", std::process::id()));
+        let entry = HistoryEntry::new(
+            JsonRpcRequest::new("eth_getBalance", json!([A, "latest"]), 3),
+            JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!("0x10")), error: None, id: 3 },
+        );
+        let meta = Meta {
+            method: "eth_getBalance".into(),
+            endpoint: scheme_and_port("https://mainnet.infura.io/v3/secret"),
+            chain_id: Some(1),
+            latest_block: Some(19_000_000),
+            exported_at: 1_700_000_000,
+            eli_version: env!("CARGO_PKG_VERSION").into(),
+            addresses_redacted: true,
+            scrubbed: Vec::new(),
+        };
+        let dir = write(&parent, &entry, &meta, true).unwrap();
+        assert_eq!(dir, parent.join("eth_getBalance-1700000000"));
+        let read = |file: &str| -> Value { serde_json::from_str(&std::fs::read_to_string(dir.join(file)).unwrap()).unwrap() };
+        assert_eq!(read("request.json")["params"], json!([format!("0xad{:038x}", 1), "latest"]));
+        assert_eq!(read("response.json")["result"], "0x10");
+        assert_eq!(read("meta.json")["endpoint"], "https://…:443");
+        assert_eq!(read("meta.json")["chain_id"], 1);
+        std::fs::remove_dir_all(parent).unwrap();
+
+        assert_eq!(scheme_and_port("http://127.0.0.1:8545"), "http://…:8545");
+        assert_eq!(scheme_and_port("not a url"), "…");
+    }
+
+    /// A node answering `eth_getBlockByNumber` with a block whose hash and
+    /// timestamp differ from any other recording.
+    struct Node;
+
+    #[async_trait::async_trait]
+    impl Transport for Node {
+        async fn send(&self, _url: &str, req: JsonRpcRequest) -> anyhow::Result<JsonRpcResponse> {
+            let block = json!({
+                "number": "0x10",
+                "hash": format!("0x{}", "ab".repeat(32)),
+                "parentHash": format!("0x{}", "cd".repeat(32)),
+                "timestamp": "0x65a8c1f3",
+                "transactions": [format!("0x{}", "ab".repeat(32))],
+            });
+            Ok(JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(block), error: None, id: req.id })
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_fixtures_load_back_scrubbed_and_indexed() {
+        let out = std::env::temp_dir().join(format!("eli-fixture-record-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&out);
+        let rules = ScrubRules { hashes: true, ..ScrubRules::default() };
+        let params = json!(["latest", false]);
+
+        let dir = record(&Node, "http://127.0.0.1:8545", "eth_getBlockByNumber", params.clone(), &out, &rules, 1_700_000_000)
+            .await
+            .unwrap();
+        let fixture = load(&dir).unwrap();
+        assert_eq!(fixture.request, JsonRpcRequest::new("eth_getBlockByNumber", params, 1));
+        let block = fixture.response.result.unwrap();
+        assert_eq!(block["timestamp"], "0x0");
+        assert_eq!(block["number"], "0x10");
+        // The same hash gets the same placeholder wherever it appears
+        assert_eq!(block["hash"], format!("0x{:064x}", 1));
+        assert_eq!(block["parentHash"], format!("0x{:064x}", 2));
+        assert_eq!(block["transactions"][0], block["hash"]);
+        let meta = fixture.meta.unwrap();
+        assert_eq!((meta.endpoint.as_str(), meta.scrubbed), ("http://…:8545", vec!["timestamp".to_string(), "hashes".into()]));
+
+        // Recording again adds to the index; the same name replaces its line
+        record(&Node, "http://127.0.0.1:8545", "eth_getBlockByNumber", json!(["0x10", true]), &out, &rules, 1_700_000_100)
+            .await
+            .unwrap();
+        record(&Node, "http://127.0.0.1:8545", "eth_getBlockByNumber", json!(["0x10", true]), &out, &rules, 1_700_000_100)
+            .await
+            .unwrap();
+        let index: Vec<IndexEntry> = serde_json::from_str(&std::fs::read_to_string(out.join("index.json")).unwrap()).unwrap();
+        assert_eq!(index.iter().map(|e| e.dir.as_str()).collect::<Vec<_>>(), [
+            "eth_getBlockByNumber-1700000000",
+            "eth_getBlockByNumber-1700000100"
+        ]);
+        std::fs::remove_dir_all(out).unwrap();
+    }
+
+    #[test]
+    fn loads_hand_written_fixtures() {
+        let fixture = load(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simulate"))).unwrap();
+        assert_eq!(fixture.request.method, "eth_simulateV1");
+        assert!(fixture.response.result.is_some());
+        assert_eq!(fixture.meta, None);
+    }
+}
@@ -0,0 +1,67 @@
+// src/focus.rs
+
+use crate::app::AppMode;
+
+/// A pane of a mode that takes the keys not bound mode-wide. Tab and
+/// Shift+Tab move between a mode's panes; the focused one has its border
+/// highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    /// Main: the search input. Arrows still move through the list.
+    Search,
+    /// Main: the method list.
+    Methods,
+    /// Main: the docs panel; arrows scroll it, Left/Right switch its tab.
+    Docs,
+    /// ParamInput: the param fields, form or raw editor.
+    Fields,
+    /// ParamInput: the params as they will be sent.
+    Preview,
+    /// History: the entry list.
+    List,
+    /// History: the selected entry's request and response.
+    Detail,
+}
+
+impl Focus {
+    /// The panes of `mode` in Tab order; empty for modes with a single pane.
+    pub fn ring(mode: AppMode) -> &'static [Focus] {
+        match mode {
+            AppMode::Main => &[Focus::Search, Focus::Methods, Focus::Docs],
+            AppMode::ParamInput => &[Focus::Fields, Focus::Preview],
+            AppMode::History => &[Focus::List, Focus::Detail],
+            AppMode::ResponseView | AppMode::Dashboard => &[],
+        }
+    }
+
+    /// The pane focused on entering `mode`: where typing went before panes
+    /// had focus.
+    pub fn initial(mode: AppMode) -> Option<Focus> {
+        Focus::ring(mode).first().copied()
+    }
+
+    /// The pane `step` places along `mode`'s ring from this one, wrapping;
+    /// `None` for a pane not in it.
+    pub fn cycle(self, mode: AppMode, step: isize) -> Option<Focus> {
+        let ring = Focus::ring(mode);
+        let at = ring.iter().position(|f| *f == self)?;
+        Some(ring[(at as isize + step).rem_euclid(ring.len() as isize) as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rings_wrap_both_ways() {
+        assert_eq!(Focus::initial(AppMode::Main), Some(Focus::Search));
+        assert_eq!(Focus::Search.cycle(AppMode::Main, 1), Some(Focus::Methods));
+        assert_eq!(Focus::Docs.cycle(AppMode::Main, 1), Some(Focus::Search));
+        assert_eq!(Focus::Search.cycle(AppMode::Main, -1), Some(Focus::Docs));
+        assert_eq!(Focus::List.cycle(AppMode::History, 3), Some(Focus::Detail));
+        // A pane left over from another mode doesn't cycle
+        assert_eq!(Focus::Docs.cycle(AppMode::History, 1), None);
+        assert_eq!(Focus::initial(AppMode::ResponseView), None);
+    }
+}
@@ -0,0 +1,90 @@
+// src/follow.rs
+
+use serde_json::{json, Value};
+
+use crate::app::HistoryEntry;
+use crate::shape::{shape_of, Shape};
+
+/// A request that follows naturally from a response on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Link {
+    /// Receipt → its transaction, transaction → its receipt (`x`).
+    Counterpart,
+    /// Receipt → the block containing it (`b`).
+    Block,
+}
+
+impl Link {
+    /// The response-view key that follows the link.
+    pub fn key(self) -> char {
+        match self {
+            Link::Counterpart => 'x',
+            Link::Block => 'b',
+        }
+    }
+}
+
+/// The method and params `link` leads to from `entry`, if its response
+/// has the hash needed.
+pub fn target(entry: &HistoryEntry, link: Link) -> Option<(&'static str, Value)> {
+    if entry.dry_run {
+        return None;
+    }
+    let result = entry.response.result.as_ref()?;
+    let hash = |field: &str| result.get(field)?.as_str().filter(|h| shape_of(h) == Shape::Hash32).map(str::to_string);
+    match (entry.request.method.as_str(), link) {
+        ("eth_getTransactionReceipt", Link::Counterpart) => Some(("eth_getTransactionByHash", json!([hash("transactionHash")?]))),
+        ("eth_getTransactionReceipt", Link::Block) => Some(("eth_getBlockByHash", json!([hash("blockHash")?, false]))),
+        ("eth_getTransactionByHash", Link::Counterpart) => Some(("eth_getTransactionReceipt", json!([hash("hash")?]))),
+        _ => None,
+    }
+}
+
+/// "x → eth_getTransactionByHash · b → eth_getBlockByHash" for the links
+/// `entry` offers, if any.
+pub fn hint(entry: &HistoryEntry) -> Option<String> {
+    let offered: Vec<String> = [Link::Counterpart, Link::Block]
+        .into_iter()
+        .filter_map(|link| target(entry, link).map(|(method, _)| format!("{} → {}", link.key(), method)))
+        .collect();
+    (!offered.is_empty()).then(|| offered.join(" · "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{JsonRpcRequest, JsonRpcResponse};
+
+    const TX: &str = "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b";
+    const BLOCK: &str = "0x1d59ff54b1eb26b013ce3cb5fc9dab3705b415a67127a003c3e61eb445bb8df2";
+
+    fn entry(method: &str, result: Value) -> HistoryEntry {
+        HistoryEntry::new(
+            JsonRpcRequest::new(method, json!([TX]), 1),
+            JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(result), error: None, id: 1 },
+        )
+    }
+
+    #[test]
+    fn receipts_lead_to_their_transaction_and_block() {
+        let receipt = entry("eth_getTransactionReceipt", json!({"transactionHash": TX, "blockHash": BLOCK, "status": "0x1"}));
+        assert_eq!(target(&receipt, Link::Counterpart), Some(("eth_getTransactionByHash", json!([TX]))));
+        assert_eq!(target(&receipt, Link::Block), Some(("eth_getBlockByHash", json!([BLOCK, false]))));
+        assert_eq!(hint(&receipt).unwrap(), "x → eth_getTransactionByHash · b → eth_getBlockByHash");
+    }
+
+    #[test]
+    fn transactions_lead_to_their_receipt() {
+        let tx = entry("eth_getTransactionByHash", json!({"hash": TX, "blockHash": BLOCK}));
+        assert_eq!(target(&tx, Link::Counterpart), Some(("eth_getTransactionReceipt", json!([TX]))));
+        assert_eq!(target(&tx, Link::Block), None);
+    }
+
+    #[test]
+    fn nothing_to_follow_without_a_hash() {
+        // Pending or unknown transactions come back as null
+        assert_eq!(target(&entry("eth_getTransactionReceipt", Value::Null), Link::Counterpart), None);
+        assert_eq!(target(&entry("eth_getTransactionByHash", json!({"hash": "0x12"})), Link::Counterpart), None);
+        assert_eq!(hint(&entry("eth_blockNumber", json!("0x10"))), None);
+    }
+}
@@ -0,0 +1,286 @@
+// src/gas.rs
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::capability::{classify_error, Availability};
+use crate::chains::{ChainTable, NativeCurrency};
+use crate::rpc::{BatchOutcome, JsonRpcRequest, RpcClient};
+
+/// Blocks of fee history the recommendation looks back over.
+pub const HISTORY_BLOCKS: u64 = 10;
+/// Reward percentiles asked of `eth_feeHistory`: slow, normal, fast.
+pub const PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+/// Gas used by a plain transfer, the cost estimates' yardstick.
+pub const TRANSFER_GAS: u128 = 21_000;
+
+const TIERS: [&str; 3] = ["slow", "normal", "fast"];
+
+/// A value of the overview, or why the node didn't give it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reading<T> {
+    Value(T),
+    /// The node doesn't serve the method.
+    Unsupported,
+    Failed(String),
+}
+
+/// Suggested fees at one speed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tier {
+    pub label: &'static str,
+    pub priority_fee: u128,
+    /// Room for the base fee to double before the transaction is priced out.
+    pub max_fee: u128,
+    /// What a [`TRANSFER_GAS`] transfer pays at the next block's base fee.
+    pub transfer_cost: u128,
+}
+
+/// What `eth_feeHistory` says about the next block, in wei.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeSummary {
+    pub base_fee: u128,
+    /// Slow, normal and fast, from the [`PERCENTILES`] rewards.
+    pub tiers: Vec<Tier>,
+}
+
+/// Everything `eli gas` shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasOverview {
+    pub gas_price: Reading<u128>,
+    pub max_priority_fee: Reading<u128>,
+    pub blob_base_fee: Reading<u128>,
+    pub fees: Reading<FeeSummary>,
+    pub currency: NativeCurrency,
+}
+
+fn quantity(value: &Value) -> Option<u128> {
+    u128::from_str_radix(value.as_str()?.strip_prefix("0x")?, 16).ok()
+}
+
+fn median(mut values: Vec<u128>) -> Option<u128> {
+    values.sort_unstable();
+    values.get(values.len() / 2).copied()
+}
+
+/// The slow/normal/fast recommendation from an `eth_feeHistory` result
+/// asked for the [`PERCENTILES`] rewards.
+///
+/// The base fee is the last of `baseFeePerGas`, the one of the block after
+/// the newest. Each tier's priority fee is the median over the blocks of
+/// that percentile's reward; empty blocks (gas used ratio 0) report zero
+/// rewards and are left out unless every block was empty. `None` when the
+/// result has no base fees or no rewards.
+pub fn recommend(history: &Value) -> Option<FeeSummary> {
+    let base_fee = quantity(history["baseFeePerGas"].as_array()?.last()?)?;
+    let rewards = history["reward"].as_array()?;
+    let ratios = history["gasUsedRatio"].as_array();
+    let busy: Vec<&Value> = rewards
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| ratios.and_then(|r| r.get(*i)?.as_f64()).is_none_or(|ratio| ratio > 0.0))
+        .map(|(_, reward)| reward)
+        .collect();
+    let blocks: Vec<&Value> = if busy.is_empty() { rewards.iter().collect() } else { busy };
+    let tiers = TIERS
+        .iter()
+        .enumerate()
+        .map(|(i, &label)| {
+            let priority_fee = median(blocks.iter().filter_map(|reward| quantity(&reward[i])).collect())?;
+            Some(Tier {
+                label,
+                priority_fee,
+                max_fee: base_fee.saturating_mul(2).saturating_add(priority_fee),
+                transfer_cost: base_fee.saturating_add(priority_fee).saturating_mul(TRANSFER_GAS),
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(FeeSummary { base_fee, tiers })
+}
+
+/// The overview's requests, ids 1 to 5: chain id (for the currency), gas
+/// price, priority fee, blob base fee and fee history.
+pub fn requests() -> Vec<JsonRpcRequest> {
+    let history = json!([format!("0x{:x}", HISTORY_BLOCKS), "latest", PERCENTILES]);
+    [
+        ("eth_chainId", json!([])),
+        ("eth_gasPrice", json!([])),
+        ("eth_maxPriorityFeePerGas", json!([])),
+        ("eth_blobBaseFee", json!([])),
+        ("eth_feeHistory", history),
+    ]
+    .into_iter()
+    .zip(1..)
+    .map(|((method, params), id)| JsonRpcRequest::new(method, params, id))
+    .collect()
+}
+
+fn reading<T>(outcome: BatchOutcome, parse: impl Fn(&Value) -> Option<T>) -> Reading<T> {
+    match outcome {
+        BatchOutcome::Ok(result) => match parse(&result) {
+            Some(value) => Reading::Value(value),
+            None => Reading::Failed(format!("unexpected result {}", result)),
+        },
+        BatchOutcome::RpcError(error) => {
+            let message = error["message"].as_str().unwrap_or("unknown error");
+            match classify_error(error["code"].as_i64().unwrap_or(0), message) {
+                Availability::Unavailable => Reading::Unsupported,
+                _ => Reading::Failed(message.to_string()),
+            }
+        }
+        BatchOutcome::Missing => Reading::Failed("no response".into()),
+    }
+}
+
+/// Builds the overview from one outcome per [`requests`] entry, in order.
+pub fn overview(outcomes: Vec<BatchOutcome>) -> GasOverview {
+    let mut outcomes = outcomes.into_iter();
+    let mut next = || outcomes.next().unwrap_or(BatchOutcome::Missing);
+    let currency = match next() {
+        BatchOutcome::Ok(id) => quantity(&id)
+            .and_then(|id| ChainTable::builtin().get(id as u64).map(|c| c.native_currency.clone()))
+            .unwrap_or_default(),
+        _ => NativeCurrency::default(),
+    };
+    GasOverview {
+        gas_price: reading(next(), quantity),
+        max_priority_fee: reading(next(), quantity),
+        blob_base_fee: reading(next(), quantity),
+        fees: reading(next(), recommend),
+        currency,
+    }
+}
+
+/// Asks `url` for the overview in one batch. A node that refuses batches
+/// is asked one request at a time instead.
+pub async fn fetch(client: &RpcClient, url: &str) -> Result<GasOverview> {
+    let requests = requests();
+    if let Ok(report) = client.send_batch(url, &requests).await {
+        return Ok(overview(report.outcomes));
+    }
+    let mut outcomes = Vec::new();
+    for request in requests {
+        let response = client.send(url, request).await?;
+        outcomes.push(match (response.result, response.error) {
+            (_, Some(error)) => BatchOutcome::RpcError(error),
+            (Some(result), None) => BatchOutcome::Ok(result),
+            (None, None) => BatchOutcome::Ok(Value::Null),
+        });
+    }
+    Ok(overview(outcomes))
+}
+
+fn gwei(wei: u128) -> String {
+    format!("{:.2} gwei", wei as f64 / 1e9)
+}
+
+impl GasOverview {
+    /// The plain-text report `eli gas` prints.
+    pub fn report(&self) -> String {
+        let shown = |reading: &Reading<u128>| match reading {
+            Reading::Value(wei) => gwei(*wei),
+            Reading::Unsupported => "not supported by this node".into(),
+            Reading::Failed(reason) => format!("unavailable ({})", reason),
+        };
+        let mut out = String::new();
+        out.push_str(&format!("gas price      {}\n", shown(&self.gas_price)));
+        out.push_str(&format!("priority fee   {}\n", shown(&self.max_priority_fee)));
+        out.push_str(&format!("blob base fee  {}\n", shown(&self.blob_base_fee)));
+        let summary = match &self.fees {
+            Reading::Value(summary) => summary,
+            Reading::Unsupported => return out + "fee history    not supported by this node\n",
+            Reading::Failed(reason) => return out + &format!("fee history    unavailable ({})\n", reason),
+        };
+        out.push_str(&format!("base fee       {} (next block)\n", gwei(summary.base_fee)));
+        out.push_str(&format!(
+            "\n{:<8}{:>16}{:>16}{:>24}\n",
+            "",
+            "priority fee",
+            "max fee",
+            format!("{} gas transfer", TRANSFER_GAS)
+        ));
+        let scale = 10f64.powi(self.currency.decimals.min(38) as i32);
+        for tier in &summary.tiers {
+            let cost = format!("{:.6} {}", tier.transfer_cost as f64 / scale, self.currency.symbol);
+            out.push_str(&format!("{:<8}{:>16}{:>16}{:>24}\n", tier.label, gwei(tier.priority_fee), gwei(tier.max_fee), cost));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(base_fees: &[u64], rewards: &[[u64; 3]], ratios: &[f64]) -> Value {
+        json!({
+            "oldestBlock": "0x100",
+            "baseFeePerGas": base_fees.iter().map(|f| format!("0x{:x}", f)).collect::<Vec<_>>(),
+            "reward": rewards.iter().map(|r| r.iter().map(|f| format!("0x{:x}", f)).collect::<Vec<_>>()).collect::<Vec<_>>(),
+            "gasUsedRatio": ratios,
+        })
+    }
+
+    #[test]
+    fn tiers_take_the_median_reward_of_each_percentile() {
+        let gwei = 1_000_000_000u64;
+        let history = history(
+            &[10 * gwei, 11 * gwei, 12 * gwei, 20 * gwei],
+            &[[gwei, 2 * gwei, 5 * gwei], [3 * gwei, 4 * gwei, 9 * gwei], [2 * gwei, 3 * gwei, 7 * gwei]],
+            &[0.5, 0.9, 0.7],
+        );
+        let summary = recommend(&history).unwrap();
+        // The base fee of the block after the newest
+        assert_eq!(summary.base_fee, 20 * gwei as u128);
+        let fees: Vec<(&str, u128, u128)> = summary.tiers.iter().map(|t| (t.label, t.priority_fee, t.max_fee)).collect();
+        let g = gwei as u128;
+        assert_eq!(fees, [("slow", 2 * g, 42 * g), ("normal", 3 * g, 43 * g), ("fast", 7 * g, 47 * g)]);
+        assert_eq!(summary.tiers[0].transfer_cost, 22 * g * TRANSFER_GAS);
+    }
+
+    #[test]
+    fn empty_blocks_do_not_drag_the_fees_down() {
+        let history = history(&[100, 100, 100, 100], &[[0, 0, 0], [5, 6, 7], [0, 0, 0]], &[0.0, 0.4, 0.0]);
+        let priority: Vec<u128> = recommend(&history).unwrap().tiers.iter().map(|t| t.priority_fee).collect();
+        assert_eq!(priority, [5, 6, 7]);
+
+        // All empty: zero tips are all there is to go on
+        let history = super::tests::history(&[100, 100], &[[0, 0, 0]], &[0.0]);
+        assert!(recommend(&history).unwrap().tiers.iter().all(|t| t.priority_fee == 0));
+    }
+
+    #[test]
+    fn malformed_histories_have_no_recommendation() {
+        assert_eq!(recommend(&json!(null)), None);
+        assert_eq!(recommend(&history(&[], &[[1, 2, 3]], &[0.5])), None);
+        assert_eq!(recommend(&history(&[100, 100], &[], &[])), None);
+        assert_eq!(recommend(&json!({"baseFeePerGas": ["0x64"], "reward": [["0x1"]]})), None);
+    }
+
+    #[test]
+    fn unsupported_methods_degrade_the_overview() {
+        let not_found = json!({"code": -32601, "message": "the method eth_blobBaseFee does not exist/is not available"});
+        let overview = overview(vec![
+            BatchOutcome::Ok(json!("0x89")),
+            BatchOutcome::Ok(json!("0x4a817c800")),
+            BatchOutcome::RpcError(json!({"code": -32000, "message": "busy"})),
+            BatchOutcome::RpcError(not_found.clone()),
+            BatchOutcome::RpcError(not_found),
+        ]);
+        assert_eq!(overview.blob_base_fee, Reading::Unsupported);
+        assert_eq!(overview.currency.symbol, "POL");
+        let report = overview.report();
+        assert!(report.contains("gas price      20.00 gwei"), "{}", report);
+        assert!(report.contains("priority fee   unavailable (busy)"), "{}", report);
+        assert!(report.contains("blob base fee  not supported by this node"), "{}", report);
+        assert!(report.contains("fee history    not supported by this node"), "{}", report);
+
+        let fees = history(&[10_000_000_000, 10_000_000_000], &[[1, 2, 3]], &[0.5]);
+        let price = || BatchOutcome::Ok(json!("0x3b9aca00"));
+        let outcomes = vec![BatchOutcome::Missing, BatchOutcome::Ok(json!({})), price(), price(), BatchOutcome::Ok(fees)];
+        let overview = super::overview(outcomes);
+        let report = overview.report();
+        assert!(report.contains("gas price      unavailable (unexpected result {})"), "{}", report);
+        assert!(report.contains("normal         0.00 gwei      20.00 gwei            0.000210 ETH"), "{}", report);
+    }
+}
@@ -0,0 +1,261 @@
+// src/head.rs
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+
+use crate::rpc::{JsonRpcRequest, Transport};
+use crate::stats::{Health, SharedStats};
+use crate::tasks::{SharedTasks, TaskHandle, TaskKind};
+
+/// How often the session endpoint is asked for its latest block.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Seconds without a new block before the chain counts as stalled, for
+/// chains with mainnet-like block times.
+pub const DEFAULT_STALL_AFTER: u64 = 60;
+
+/// Chains whose blocks come slowly or only on demand: (chain id, seconds
+/// before they count as stalled).
+const SLOW_CHAINS: &[(u64, u64)] = &[
+    // Filecoin mainnet and calibration: 30 s epochs
+    (314, 300),
+    (314159, 300),
+    // Arbitrum One, Nova and Sepolia: a block only when there are transactions
+    (42161, 600),
+    (42170, 600),
+    (421614, 600),
+];
+
+/// Seconds of silence before the chain counts as stalled: the configured
+/// value, else the chain's own when it is a known slow chain, else
+/// [`DEFAULT_STALL_AFTER`].
+pub fn stall_threshold(configured: Option<u64>, chain_id: Option<u64>) -> u64 {
+    configured
+        .or_else(|| SLOW_CHAINS.iter().find(|(id, _)| Some(*id) == chain_id).map(|(_, secs)| *secs))
+        .unwrap_or(DEFAULT_STALL_AFTER)
+}
+
+/// The latest block seen on the session endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Head {
+    pub number: u64,
+    /// The block's own timestamp (unix seconds), if it had one.
+    pub timestamp: Option<u64>,
+    /// When this block was first seen (unix seconds).
+    pub observed: u64,
+}
+
+impl Head {
+    /// Seconds since the block, as of `now` (unix seconds).
+    ///
+    /// Uses the block's timestamp when it has one that isn't ahead of when
+    /// the block was seen; a timestamp from the future means the node's
+    /// clock is off, so the observation time is used instead. Never
+    /// negative.
+    pub fn age(&self, now: u64) -> u64 {
+        match self.timestamp {
+            Some(ts) if ts <= self.observed => now.saturating_sub(ts),
+            _ => now.saturating_sub(self.observed),
+        }
+    }
+}
+
+/// "12s", "3m 05s", "2h 10m".
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+fn hex_u64(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.strip_prefix("0x")?, 16).ok()
+}
+
+/// Number and timestamp of a block, as `eth_getBlockByNumber` and
+/// `newHeads` give it.
+pub fn block_number(block: &Value) -> Option<(u64, Option<u64>)> {
+    Some((hex_u64(&block["number"])?, hex_u64(&block["timestamp"])))
+}
+
+/// Current time in unix seconds.
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Records block `number` as the head; a block already seen keeps its
+/// first observation time.
+pub fn observe(head: &mut Option<Head>, number: u64, timestamp: Option<u64>, unix_now: u64) {
+    if head.is_some_and(|h| h.number == number) {
+        return;
+    }
+    *head = Some(Head { number, timestamp, observed: unix_now });
+}
+
+/// "last block 12s ago", and whether that is past `threshold` seconds.
+pub fn status(head: Option<Head>, unix_now: u64, threshold: u64) -> Option<(String, bool)> {
+    let age = head?.age(unix_now);
+    Some((format!("last block {} ago", format_age(age)), age > threshold))
+}
+
+/// Periodic poll of the session endpoint's latest block; the poll writes
+/// it to [`Stats::head`](crate::stats::Stats::head).
+#[derive(Debug, Default)]
+pub struct HeadWatch {
+    url: String,
+    last_poll: Option<Instant>,
+    in_flight: Option<JoinHandle<()>>,
+    task: TaskHandle,
+}
+
+impl HeadWatch {
+    /// Starts the next poll if none is running, the interval has passed
+    /// and the poll isn't paused or killed in `tasks`. Switching to another
+    /// endpoint forgets the old head.
+    pub fn poll(&mut self, now: Instant, transport: Arc<dyn Transport>, url: &str, stats: &SharedStats, tasks: &SharedTasks) {
+        if self.url != url {
+            if let Some(handle) = self.in_flight.take() {
+                handle.abort();
+            }
+            let task = std::mem::take(&mut self.task);
+            *self = HeadWatch { url: url.to_string(), task, ..HeadWatch::default() };
+            stats.update(|s| {
+                s.head = None;
+                s.health = Health::Unknown;
+            });
+        }
+        if !self.task.may_run(tasks, TaskKind::HeadPoll, "eth_getBlockByNumber [\"latest\", false]", POLL_INTERVAL) {
+            if let Some(handle) = self.in_flight.take() {
+                handle.abort();
+            }
+            return;
+        }
+        if self.in_flight.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return;
+        }
+        self.in_flight = None;
+        if self.last_poll.is_some_and(|last| now.duration_since(last) < POLL_INTERVAL) {
+            return;
+        }
+        self.last_poll = Some(now);
+        let (stats, tasks, url, id) = (stats.clone(), tasks.clone(), url.to_string(), self.task.id());
+        self.in_flight = Some(tokio::spawn(async move {
+            let request = JsonRpcRequest::new("eth_getBlockByNumber", json!(["latest", false]), 1);
+            let response = transport.send(&url, request).await;
+            let health = response.as_ref().map_or_else(Health::down, |_| Health::Up);
+            let block = response.ok().and_then(|r| r.result);
+            let number = block.as_ref().and_then(|b| hex_u64(&b["number"]));
+            let timestamp = block.as_ref().and_then(|b| hex_u64(&b["timestamp"]));
+            let seen = unix_now();
+            stats.update(|s| {
+                s.health = health;
+                if let Some(number) = number {
+                    observe(&mut s.head, number, timestamp, seen);
+                }
+            });
+            if let Some(id) = id {
+                tasks.update(|r| r.record(id, number.is_some(), unix_now()));
+            }
+        }));
+    }
+
+    /// Drops the poll in flight and polls again on the next call.
+    pub fn reschedule(&mut self) {
+        if let Some(handle) = self.in_flight.take() {
+            handle.abort();
+        }
+        self.last_poll = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::JsonRpcResponse;
+    use async_trait::async_trait;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn age_prefers_the_block_timestamp() {
+        let head = Head { number: 1, timestamp: Some(NOW - 20), observed: NOW - 5 };
+        assert_eq!(head.age(NOW), 20);
+        // No timestamp: time since it was seen
+        assert_eq!(Head { timestamp: None, ..head }.age(NOW), 5);
+    }
+
+    #[test]
+    fn age_survives_clock_skew() {
+        // The node's clock runs 30 s ahead of ours
+        let head = Head { number: 1, timestamp: Some(NOW + 30), observed: NOW };
+        assert_eq!(head.age(NOW), 0);
+        assert_eq!(head.age(NOW + 90), 90);
+        // Our clock stepped back after the block was seen
+        assert_eq!(Head { number: 1, timestamp: None, observed: NOW }.age(NOW - 10), 0);
+    }
+
+    #[test]
+    fn thresholds_by_chain_and_config() {
+        assert_eq!(stall_threshold(None, Some(1)), 60);
+        assert_eq!(stall_threshold(None, None), 60);
+        assert_eq!(stall_threshold(None, Some(314)), 300);
+        assert_eq!(stall_threshold(Some(15), Some(314)), 15);
+        assert_eq!(format_age(12), "12s");
+        assert_eq!(format_age(185), "3m 05s");
+        assert_eq!(format_age(7800), "2h 10m");
+    }
+
+    #[test]
+    fn repeated_heads_keep_their_first_sighting() {
+        let mut head = None;
+        assert_eq!(status(head, NOW, 60), None);
+        observe(&mut head, 7, None, NOW);
+        observe(&mut head, 7, None, NOW + 50);
+        assert_eq!(status(head, NOW + 61, 60), Some(("last block 1m 01s ago".to_string(), true)));
+        observe(&mut head, 8, None, NOW + 61);
+        assert_eq!(status(head, NOW + 73, 60), Some(("last block 12s ago".to_string(), false)));
+    }
+
+    struct Latest;
+
+    #[async_trait]
+    impl Transport for Latest {
+        async fn send(&self, _url: &str, req: JsonRpcRequest) -> anyhow::Result<JsonRpcResponse> {
+            assert_eq!(req.params, json!(["latest", false]));
+            let block = json!({"number": "0x10", "timestamp": format!("{:#x}", NOW - 3)});
+            Ok(JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(block), error: None, id: req.id })
+        }
+    }
+
+    #[tokio::test]
+    async fn polls_the_latest_block_per_endpoint() {
+        let (mut watch, stats, tasks) = (HeadWatch::default(), SharedStats::default(), SharedTasks::default());
+        let t0 = Instant::now();
+        watch.poll(t0, Arc::new(Latest), "http://a", &stats, &tasks);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let head = stats.try_snapshot().unwrap().head.unwrap();
+        assert_eq!((head.number, head.timestamp), (16, Some(NOW - 3)));
+        assert_eq!(stats.try_snapshot().unwrap().health, Health::Up);
+
+        // Paused from the activity panel: the new endpoint isn't polled
+        let id = tasks.snapshot().tasks()[0].id;
+        tasks.update(|r| r.set_paused(id, true));
+        watch.poll(t0, Arc::new(Latest), "http://b", &stats, &tasks);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(stats.try_snapshot().unwrap().head, None);
+        assert_eq!(stats.try_snapshot().unwrap().health, Health::Unknown);
+    }
+
+    #[tokio::test]
+    async fn an_unreachable_endpoint_is_down_with_the_root_cause() {
+        let (mut watch, stats, tasks) = (HeadWatch::default(), SharedStats::default(), SharedTasks::default());
+        watch.poll(Instant::now(), Arc::new(crate::rpc::RpcClient::new()), "http://127.0.0.1:1", &stats, &tasks);
+        watch.in_flight.take().unwrap().await.unwrap();
+        let Health::Down(error) = stats.try_snapshot().unwrap().health else { panic!("endpoint not down") };
+        assert!(error.contains("refused"), "{}", error);
+    }
+}
@@ -0,0 +1,160 @@
+// src/hexdump.rs
+
+/// Bytes per dump row.
+pub const ROW: usize = 16;
+
+/// ABI words are 32 bytes; rows are grouped by word.
+pub const WORD: usize = 32;
+
+/// Rows in one word.
+const ROWS_PER_WORD: usize = WORD / ROW;
+
+/// `bytes` as dump rows: offset, 16 bytes in hex (split in two halves of
+/// 8) and the printable ones as ASCII. A short last row is padded so its
+/// ASCII column lines up.
+pub fn dump(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(ROW)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut hex = String::with_capacity(3 * ROW + 1);
+            for j in 0..ROW {
+                if j == ROW / 2 {
+                    hex.push(' ');
+                }
+                match chunk.get(j) {
+                    Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+                    None => hex.push_str("   "),
+                }
+            }
+            let ascii: String = chunk.iter().map(|&b| if (0x20..0x7f).contains(&b) { b as char } else { '.' }).collect();
+            format!("{:06x}  {} |{}|", i * ROW, hex, ascii)
+        })
+        .collect()
+}
+
+/// The field name and bytes of a response line holding a hex string
+/// longer than one word, e.g. `"input": "0xa9059cbb…",`. Array elements
+/// are named "value". Odd-length hex is a quantity, not bytes.
+pub fn hex_leaf(line: &str) -> Option<(String, Vec<u8>)> {
+    let line = line.trim().trim_end_matches(',');
+    let (name, value) = match line.split_once(": ") {
+        Some((name, value)) => (name.trim_matches('"'), value),
+        None => ("value", line),
+    };
+    let digits = value.strip_prefix("\"0x")?.strip_suffix('"')?;
+    if digits.len() % 2 != 0 || digits.len() <= 2 * WORD || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let bytes = (0..digits.len()).step_by(2).map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap()).collect();
+    Some((name.to_string(), bytes))
+}
+
+/// Hex dump of one field, opened over the response view with `x`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexView {
+    /// Field the bytes came from.
+    pub field: String,
+    pub bytes: Vec<u8>,
+    /// [`dump`] of `bytes`.
+    pub lines: Vec<String>,
+    /// Selected row.
+    pub row: usize,
+    /// Index of the first visible row.
+    pub scroll: usize,
+    /// Visible row count from the last draw.
+    pub height: usize,
+}
+
+impl HexView {
+    /// A dump of `line`'s hex string, if [`hex_leaf`] finds one.
+    pub fn open(line: &str) -> Option<Self> {
+        let (field, bytes) = hex_leaf(line)?;
+        Some(HexView { field, lines: dump(&bytes), bytes, row: 0, scroll: 0, height: 0 })
+    }
+
+    /// Moves the selection by `delta` rows, keeping it on screen.
+    pub fn move_rows(&mut self, delta: isize) {
+        self.row = self.row.saturating_add_signed(delta).min(self.lines.len().saturating_sub(1));
+        let height = self.height.max(1);
+        if self.row < self.scroll {
+            self.scroll = self.row;
+        } else if self.row >= self.scroll + height {
+            self.scroll = self.row + 1 - height;
+        }
+    }
+
+    /// Moves the selection to the first row of the word `delta` words away.
+    pub fn move_words(&mut self, delta: isize) {
+        let target = self.word().saturating_add_signed(delta) * ROWS_PER_WORD;
+        self.move_rows(target as isize - self.row as isize);
+    }
+
+    /// Index of the selected word.
+    pub fn word(&self) -> usize {
+        self.row / ROWS_PER_WORD
+    }
+
+    /// Whether `row` belongs to the selected word.
+    pub fn in_word(&self, row: usize) -> bool {
+        row / ROWS_PER_WORD == self.word()
+    }
+
+    /// The selected word as 0x-hex; the last one may be short.
+    pub fn word_text(&self) -> String {
+        let start = self.word() * WORD;
+        let word = &self.bytes[start..(start + WORD).min(self.bytes.len())];
+        format!("0x{}", word.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_have_offsets_halves_and_ascii() {
+        let mut bytes = vec![0u8; 16];
+        bytes.extend(b"hi!\x01");
+        let lines = dump(&bytes);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|");
+        // 4 bytes of 16: the ASCII column stays aligned
+        assert_eq!(lines[1], "000010  68 69 21 01                                       |hi!.|");
+        assert_eq!(lines[1].find('|'), lines[0].find('|'));
+        assert!(dump(&[]).is_empty());
+    }
+
+    #[test]
+    fn only_hex_longer_than_a_word_is_a_leaf() {
+        let data = format!("0x{}", "ab".repeat(33));
+        let (name, bytes) = hex_leaf(&format!("    \"data\": \"{}\",", data)).unwrap();
+        assert_eq!((name.as_str(), bytes.len()), ("data", 33));
+        assert_eq!(hex_leaf(&format!("      \"{}\"", data)).unwrap().0, "value");
+        // A hash, a quantity, odd digits, not hex
+        assert_eq!(hex_leaf(&format!("\"hash\": \"0x{}\",", "cd".repeat(32))), None);
+        assert_eq!(hex_leaf("\"gas\": \"0x5208\","), None);
+        assert_eq!(hex_leaf(&format!("\"data\": \"0x{}a\"", "ab".repeat(40))), None);
+        assert_eq!(hex_leaf(&format!("\"data\": \"0x{}zz\"", "ab".repeat(40))), None);
+    }
+
+    #[test]
+    fn selection_moves_by_row_and_word() {
+        // 3 words and a half: 7 rows, the last one half full
+        let data = (0..112u8).map(|b| format!("{:02x}", b)).collect::<String>();
+        let mut view = HexView::open(&format!("\"input\": \"0x{}\"", data)).unwrap();
+        view.height = 3;
+        assert_eq!(view.lines.len(), 7);
+        view.move_rows(1);
+        assert_eq!((view.row, view.word()), (1, 0));
+        view.move_words(1);
+        assert_eq!((view.row, view.word()), (2, 1));
+        assert!(view.in_word(3) && !view.in_word(4));
+        view.move_words(5);
+        assert_eq!((view.row, view.scroll), (6, 4));
+        assert_eq!(view.word_text(), format!("0x{}", (96..112u8).map(|b| format!("{:02x}", b)).collect::<String>()));
+        view.move_words(-10);
+        assert_eq!((view.row, view.scroll), (0, 0));
+        assert_eq!(view.word_text().len(), 2 + 64);
+    }
+}
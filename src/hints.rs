@@ -0,0 +1,259 @@
+// src/hints.rs
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Where the user is, as far as the key hints are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// The method list.
+    Main,
+    /// ParamInput with the per-param fields.
+    ParamFields,
+    /// ParamInput with a boolean param focused.
+    ParamToggle,
+    /// ParamInput with the `eth_sendTransaction` form.
+    TxForm,
+    /// ParamInput editing raw JSON.
+    ParamRaw,
+    Response,
+    /// Response view with an executed search.
+    ResponseMatches,
+    /// Response view asking for a CSV path.
+    ExportPrompt,
+    /// Response view asking for an address label.
+    LabelPrompt,
+    /// Hex dump of a field over the response view.
+    HexDump,
+    /// Main mode asking for a new session endpoint.
+    EndpointPrompt,
+    History,
+    /// History mode asking to confirm an action on several entries.
+    HistoryConfirm,
+    Dashboard,
+    Settings,
+    EditorPopup,
+    /// The background activity popup.
+    Activity,
+    /// The endpoint profile picker.
+    Profiles,
+    /// Another eli holds the websocket connection live heads wanted.
+    LeasePrompt,
+}
+
+/// One key hint. Lower priority values are kept longer when space runs out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    pub keys: &'static str,
+    pub action: &'static str,
+    pub priority: u8,
+}
+
+const fn hint(keys: &'static str, action: &'static str, priority: u8) -> Hint {
+    Hint { keys, action, priority }
+}
+
+const MAIN: &[Hint] = &[
+    hint("type", "Search methods", 0),
+    hint("↑/↓/PgUp/PgDn", "Navigate", 0),
+    hint("Enter", "Open params", 0),
+    hint("h", "History", 1),
+    hint("D", "Dashboard", 2),
+    hint("A", "Background activity", 3),
+    hint("-/Ctrl+^", "Previous method", 3),
+    hint("Ctrl+E", "Change endpoint", 1),
+    hint("P", "Profiles", 2),
+    hint("Ctrl+P", "Probe method", 3),
+    hint("Ctrl+D", "Dry run", 2),
+    hint("Tab", "Next pane", 2),
+    hint("[/]", "Schema depth", 3),
+    hint(",", "Settings", 2),
+    hint("?", "Help", 1),
+    hint("Esc", "Cancel/Clear/Quit", 0),
+];
+const PARAM_FIELDS: &[Hint] = &[
+    hint("Enter", "Send", 0),
+    hint("Tab", "Next param", 3),
+    hint("F4", "Edit value", 2),
+    hint("Ctrl+R", "Raw JSON", 1),
+    hint("Ctrl+D", "Dry run", 2),
+    hint("Ctrl+O", "Endpoint override", 3),
+    hint("Ctrl+^", "Previous method", 3),
+    hint("Ctrl+Z/Y", "Undo/Redo", 3),
+    hint("?/F1", "Help", 3),
+    hint("Esc", "Back", 0),
+];
+const PARAM_TOGGLE: &[Hint] = &[
+    hint("Space/Enter", "Toggle", 0),
+    hint("Ctrl+S", "Send", 0),
+    hint("Tab", "Next param", 1),
+    hint("Ctrl+R", "Raw JSON", 2),
+    hint("Ctrl+D", "Dry run", 3),
+    hint("Ctrl+Z/Y", "Undo/Redo", 3),
+    hint("F1", "Help", 3),
+    hint("Esc", "Back", 0),
+];
+const TX_FORM: &[Hint] = &[
+    hint("Enter", "Send", 0),
+    hint("Tab/↑/↓", "Field", 1),
+    hint("Ctrl+N", "Fill nonce", 2),
+    hint("F4", "Edit value", 3),
+    hint("Ctrl+R", "Raw JSON", 2),
+    hint("Ctrl+D", "Dry run", 3),
+    hint("Ctrl+Z/Y", "Undo/Redo", 3),
+    hint("F1", "Help", 3),
+    hint("Esc", "Back", 0),
+];
+const PARAM_RAW: &[Hint] = &[
+    hint("Ctrl+S", "Send", 0),
+    hint("Ctrl+R", "Fields", 1),
+    hint("Ctrl+D", "Dry run", 2),
+    hint("Ctrl+O", "Endpoint override", 3),
+    hint("Ctrl+Z/Y", "Undo/Redo", 3),
+    hint("F1", "Help", 3),
+    hint("Esc", "Back", 0),
+];
+const RESPONSE: &[Hint] = &[
+    hint("↑/↓/PgUp/PgDn", "Scroll", 1),
+    hint("Home/End", "Top/Bottom", 3),
+    hint("/", "Search", 1),
+    hint("Ctrl+T", "Case", 3),
+    hint("Ctrl+Shift+E", "CSV", 2),
+    hint("F", "Fixture", 3),
+    hint("Tab", "Provenance", 2),
+    hint("x", "Hex dump (hex line) / Follow", 3),
+    hint("L", "Label address", 3),
+    hint("y/Y", "Copy resp/req", 3),
+    hint(",", "Settings", 3),
+    hint("Esc", "Back", 0),
+];
+const RESPONSE_MATCHES: &[Hint] = &[hint("n/N", "Next/Prev", 0), hint("Ctrl+T", "Case", 2), hint("Esc", "Clear", 0)];
+const HEX_DUMP: &[Hint] = &[
+    hint("↑/↓/PgUp/PgDn", "Row", 1),
+    hint("←/→", "Word", 1),
+    hint("y", "Copy word", 0),
+    hint("Esc", "Back", 0),
+];
+const EXPORT_PROMPT: &[Hint] = &[hint("Enter", "Write", 0), hint("Esc", "Cancel", 0)];
+const LABEL_PROMPT: &[Hint] = &[hint("Enter", "Save", 0), hint("Esc", "Cancel", 0)];
+const ENDPOINT_PROMPT: &[Hint] = &[hint("Enter", "Switch", 0), hint("Esc", "Cancel", 0)];
+const HISTORY: &[Hint] = &[
+    hint("↑/↓/PgUp/PgDn", "Navigate", 1),
+    hint("Tab", "Detail", 2),
+    hint("Enter", "View", 0),
+    hint("r", "Reload", 0),
+    hint("Space", "Mark", 1),
+    hint("a", "Mark all", 2),
+    hint("d", "Delete", 1),
+    hint("e", "Export", 1),
+    hint("y/Y", "Copy req/resp", 2),
+    hint(",", "Settings", 2),
+    hint("?", "Help", 2),
+    hint("Esc", "Back", 0),
+];
+const HISTORY_CONFIRM: &[Hint] = &[hint("y", "Confirm", 0), hint("other", "Cancel", 0)];
+const DASHBOARD: &[Hint] = &[hint("Esc", "Back", 0)];
+const SETTINGS: &[Hint] = &[hint("Enter", "Change", 0), hint("Esc", "Close", 0)];
+const ACTIVITY: &[Hint] = &[
+    hint("↑/↓", "Select", 2),
+    hint("p", "Pause", 1),
+    hint("r", "Resume", 1),
+    hint("k", "Kill", 1),
+    hint("Esc", "Close", 0),
+];
+const PROFILES: &[Hint] = &[hint("↑/↓", "Select", 1), hint("Enter", "Switch", 0), hint("Esc", "Close", 0)];
+const LEASE_PROMPT: &[Hint] = &[hint("y/Enter", "Subscribe anyway", 0), hint("p/Esc", "Poll over HTTP", 0)];
+const EDITOR_POPUP: &[Hint] = &[hint("Ctrl+S", "Save", 0), hint("Esc", "Cancel", 0)];
+
+/// The keys that do something in `context`, in display order: the one
+/// list behind both the key hint lines and the help overlay. Keep in sync
+/// with the handlers in `events.rs`.
+pub fn hints(context: Context) -> &'static [Hint] {
+    match context {
+        Context::Main => MAIN,
+        Context::ParamFields => PARAM_FIELDS,
+        Context::ParamToggle => PARAM_TOGGLE,
+        Context::TxForm => TX_FORM,
+        Context::ParamRaw => PARAM_RAW,
+        Context::Response => RESPONSE,
+        Context::ResponseMatches => RESPONSE_MATCHES,
+        Context::ExportPrompt => EXPORT_PROMPT,
+        Context::LabelPrompt => LABEL_PROMPT,
+        Context::HexDump => HEX_DUMP,
+        Context::EndpointPrompt => ENDPOINT_PROMPT,
+        Context::History => HISTORY,
+        Context::HistoryConfirm => HISTORY_CONFIRM,
+        Context::Dashboard => DASHBOARD,
+        Context::Settings => SETTINGS,
+        Context::EditorPopup => EDITOR_POPUP,
+        Context::Activity => ACTIVITY,
+        Context::Profiles => PROFILES,
+        Context::LeasePrompt => LEASE_PROMPT,
+    }
+}
+
+const SEPARATOR: &str = " • ";
+
+/// `hints` as "Key=Action • …" in at most `width` columns. The least
+/// important hints (later ones first among equals) are dropped until the
+/// rest fit; if even the most important one doesn't, it is cut with "…".
+pub fn fit(hints: &[Hint], width: usize) -> String {
+    let mut kept: Vec<&Hint> = hints.iter().collect();
+    loop {
+        let text = kept.iter().map(|h| format!("{}={}", h.keys, h.action)).collect::<Vec<_>>().join(SEPARATOR);
+        if text.width() <= width || kept.len() <= 1 {
+            return truncate(&text, width);
+        }
+        let drop = kept.iter().enumerate().max_by_key(|(i, h)| (h.priority, *i)).map(|(i, _)| i).unwrap_or(0);
+        kept.remove(drop);
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    for c in text.chars() {
+        if out.width() + c.width().unwrap_or(0) + 1 > width {
+            break;
+        }
+        out.push(c);
+    }
+    if width > 0 {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_terminals_show_every_hint_in_order() {
+        assert_eq!(
+            fit(hints(Context::ParamFields), 250),
+            "Enter=Send • Tab=Next param • F4=Edit value • Ctrl+R=Raw JSON • Ctrl+D=Dry run • Ctrl+O=Endpoint override • Ctrl+^=Previous method • Ctrl+Z/Y=Undo/Redo • ?/F1=Help • Esc=Back"
+        );
+    }
+
+    #[test]
+    fn narrow_terminals_drop_low_priority_hints_first() {
+        let param = hints(Context::ParamFields);
+        assert_eq!(fit(param, 60), "Enter=Send • F4=Edit value • Ctrl+R=Raw JSON • Esc=Back");
+        assert_eq!(fit(param, 40), "Enter=Send • Ctrl+R=Raw JSON • Esc=Back");
+        assert_eq!(fit(param, 25), "Enter=Send • Esc=Back");
+        assert_eq!(fit(param, 12), "Enter=Send");
+        assert_eq!(fit(param, 6), "Enter…");
+        assert_eq!(fit(param, 0), "");
+    }
+
+    #[test]
+    fn widths_count_display_columns() {
+        let response = hints(Context::Response);
+        let fitted = fit(response, 40);
+        assert!(fitted.width() <= 40, "{}", fitted);
+        assert!(fitted.ends_with("Esc=Back"), "{}", fitted);
+        assert!(fitted.contains("↑/↓/PgUp/PgDn=Scroll"), "{}", fitted);
+    }
+}
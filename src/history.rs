@@ -0,0 +1,490 @@
+// src/history.rs
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{ErrorKind, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::app::HistoryEntry;
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse};
+use crate::storage;
+
+/// Version written by this build. Bump when `HistoryEntry` changes shape.
+/// Exported files carry it in their envelope, `history.jsonl` in its
+/// header line.
+///
+/// - 0: bare array of `[request, response]` pairs (no envelope)
+/// - 1: `{ "version": 1, "entries": [HistoryEntry…] }`
+pub const HISTORY_FORMAT_VERSION: u32 = 1;
+
+/// Entries kept when `--history-limit` isn't given.
+pub const DEFAULT_LIMIT: usize = 1000;
+
+/// On-disk envelope for history.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryFile {
+    pub version: u32,
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// First line of `history.jsonl`: the format version of the entries on
+/// the lines after it. Files written before it existed have none.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Header {
+    version: u32,
+}
+
+/// Serializes entries in the current format.
+pub fn encode(entries: &[HistoryEntry]) -> Result<String> {
+    let file = HistoryFile { version: HISTORY_FORMAT_VERSION, entries: entries.to_vec() };
+    Ok(serde_json::to_string_pretty(&file)?)
+}
+
+/// Parses history written by this or any older version of eli.
+pub fn decode(text: &str) -> Result<Vec<HistoryEntry>> {
+    let value: Value = serde_json::from_str(text).context("history is not valid JSON")?;
+    if value.is_array() {
+        let pairs: Vec<(JsonRpcRequest, JsonRpcResponse)> =
+            serde_json::from_value(value).context("unrecognized version 0 history")?;
+        let mut entries: Vec<HistoryEntry> = pairs
+            .into_iter()
+            .map(|(request, response)| HistoryEntry::new(request, response))
+            .collect();
+        number_entries(&mut entries);
+        return Ok(entries);
+    }
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > HISTORY_FORMAT_VERSION as u64 {
+        bail!("history format version {} is newer than this eli supports ({})", version, HISTORY_FORMAT_VERSION);
+    }
+    let mut file: HistoryFile = serde_json::from_value(value).context("malformed history file")?;
+    number_entries(&mut file.entries);
+    Ok(file.entries)
+}
+
+/// Numbers entries recorded before [`HistoryEntry::seq`] existed, one past
+/// the entry before them, so they get the same numbers on every load. Their
+/// `derived_from` held a list position, which becomes the source's number.
+pub fn number_entries(entries: &mut [HistoryEntry]) {
+    let mut unnumbered = Vec::new();
+    let mut previous = 0;
+    for (i, entry) in entries.iter_mut().enumerate() {
+        if entry.seq == 0 {
+            entry.seq = previous + 1;
+            unnumbered.push(i);
+        }
+        previous = entry.seq;
+    }
+    for i in unnumbered {
+        let source = entries[i].derived_from.and_then(|position| entries.get(position as usize)).map(|e| e.seq);
+        entries[i].derived_from = source;
+    }
+}
+
+/// Drops the oldest entries once `entries` is a tenth over `limit`, down
+/// to `limit`, so a full history isn't rewritten on every request. Returns
+/// how many were dropped.
+pub fn trim(entries: &mut Vec<HistoryEntry>, limit: usize) -> usize {
+    if entries.len() <= limit + limit / 10 {
+        return 0;
+    }
+    let excess = entries.len() - limit;
+    entries.drain(..excess);
+    excess
+}
+
+/// The session's history file, `history.jsonl`: a `{"version": N}` header
+/// line, then one entry per line, appended as requests complete, and
+/// trimmed to the newest entries (see [`trim`]).
+///
+/// The writable instance holds an exclusive advisory lock on the file for
+/// the whole session. A second eli finds it locked and gets a read-only
+/// store that loads the entries but never writes.
+#[derive(Debug)]
+pub struct HistoryStore {
+    path: PathBuf,
+    /// The locked file; `None` when read-only.
+    file: Option<File>,
+}
+
+/// What [`HistoryStore::open`] found.
+#[derive(Debug)]
+pub struct OpenedHistory {
+    pub store: HistoryStore,
+    pub entries: Vec<HistoryEntry>,
+    /// Set when another instance holds the lock, so the store is read-only.
+    pub locked_elsewhere: bool,
+    /// What was done about malformed lines, if there were any.
+    pub repaired: Option<String>,
+}
+
+impl HistoryStore {
+    /// Opens (creating if needed) the history file at `path` and loads it.
+    ///
+    /// Startup is never blocked by the file: if another instance holds the
+    /// lock the store is read-only, and if lines fail to parse the entries
+    /// that did parse are kept, the file as found is saved next to it as
+    /// `<name>.corrupt-<timestamp>` and the file is rewritten without the
+    /// broken lines. `read_only` opens without locking or writing at all,
+    /// not even to create the file: a missing one loads as empty.
+    /// Only the newest `limit` entries are loaded, and kept in the file.
+    /// A file whose header names a newer format than this build's is an
+    /// error, so it is never rewritten; one without a header gets one, the
+    /// original kept as `<name>.v<N>.bak`.
+    pub fn open(path: &Path, read_only: bool, limit: usize) -> Result<OpenedHistory> {
+        if !read_only && let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+        }
+        let file = match OpenOptions::new().read(true).append(!read_only).create(!read_only).open(path) {
+            Ok(file) => Some(file),
+            Err(e) if read_only && e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("cannot open {}", path.display())),
+        };
+        let mut text = String::new();
+        let mut locked = None;
+        if let Some(mut file) = file {
+            let held = !read_only
+                && match file.try_lock() {
+                    Ok(()) => true,
+                    Err(TryLockError::WouldBlock) => false,
+                    Err(TryLockError::Error(e)) => return Err(e).with_context(|| format!("cannot lock {}", path.display())),
+                };
+            file.read_to_string(&mut text).with_context(|| format!("cannot read {}", path.display()))?;
+            locked = held.then_some(file);
+        }
+        let (version, body) = split_header(&text);
+        if let Some(version) = version
+            && version > HISTORY_FORMAT_VERSION
+        {
+            bail!(
+                "{}: history format version {} is newer than this eli supports ({})",
+                path.display(),
+                version,
+                HISTORY_FORMAT_VERSION
+            );
+        }
+        let (mut entries, bad_lines) = parse_lines(body);
+        let trimmed = trim(&mut entries, limit) > 0;
+        let mut repaired = None;
+        if bad_lines > 0 {
+            let mut problem = format!("history: skipped {} malformed line(s)", bad_lines);
+            if let Some(file) = &mut locked {
+                let aside = corrupt_path(path);
+                std::fs::write(&aside, &text).with_context(|| format!("cannot write {}", aside.display()))?;
+                rewrite(file, &entries).with_context(|| format!("cannot rewrite {}", path.display()))?;
+                problem.push_str(&format!("; original moved to {}", aside.display()));
+            }
+            repaired = Some(problem);
+        }
+        if (trimmed || version.is_none())
+            && bad_lines == 0
+            && let Some(file) = &mut locked
+        {
+            // Written before the header: kept as found, like other upgraded files
+            if version.is_none() && !body.trim().is_empty() {
+                let backup = storage::migration_backup_path(path, headerless_version(body));
+                std::fs::write(&backup, &text).with_context(|| format!("cannot write {}", backup.display()))?;
+            }
+            rewrite(file, &entries).with_context(|| format!("cannot rewrite {}", path.display()))?;
+        }
+        let locked_elsewhere = !read_only && locked.is_none();
+        let store = HistoryStore { path: path.to_path_buf(), file: locked };
+        Ok(OpenedHistory { store, entries, locked_elsewhere, repaired })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.file.is_none()
+    }
+
+    /// Appends `entry`; does nothing when read-only.
+    pub fn append(&mut self, entry: &HistoryEntry) -> Result<()> {
+        let Some(file) = &mut self.file else { return Ok(()) };
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        file.write_all(line.as_bytes()).with_context(|| format!("cannot write {}", self.path.display()))
+    }
+
+    /// Replaces the file's contents with `entries`, after [`trim`] dropped
+    /// some; does nothing when read-only.
+    pub fn replace(&mut self, entries: &[HistoryEntry]) -> Result<()> {
+        let Some(file) = &mut self.file else { return Ok(()) };
+        rewrite(file, entries).with_context(|| format!("cannot rewrite {}", self.path.display()))
+    }
+}
+
+/// The version in `text`'s [`Header`] line, if it starts with one, and
+/// the rest of the text.
+fn split_header(text: &str) -> (Option<u32>, &str) {
+    let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+    match serde_json::from_str::<Header>(first) {
+        Ok(header) => (Some(header.version), rest),
+        Err(_) => (None, text),
+    }
+}
+
+/// The format version of history written before the [`Header`] line: a
+/// single document's own (0 for the bare array), else 1, the format whose
+/// entries were first written a line each.
+fn headerless_version(text: &str) -> u64 {
+    match serde_json::from_str::<Value>(text) {
+        Ok(Value::Array(_)) => 0,
+        Ok(value) => value.get("version").and_then(Value::as_u64).unwrap_or(1),
+        Err(_) => 1,
+    }
+}
+
+/// Entries from history text, and how many non-blank lines didn't parse.
+/// A file in the older single-document format is read whole.
+fn parse_lines(text: &str) -> (Vec<HistoryEntry>, usize) {
+    if let Ok(entries) = decode(text) {
+        return (entries, 0);
+    }
+    let mut entries = Vec::new();
+    let mut bad = 0;
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => bad += 1,
+        }
+    }
+    number_entries(&mut entries);
+    (entries, bad)
+}
+
+/// `<path>.corrupt-<UTC timestamp>`.
+fn corrupt_path(path: &Path) -> PathBuf {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}.corrupt-{}", name, stamp))
+}
+
+/// Replaces the contents of the (locked) file with a [`Header`] and
+/// `entries`.
+fn rewrite(file: &mut File, entries: &[HistoryEntry]) -> Result<()> {
+    file.set_len(0)?;
+    file.rewind()?;
+    writeln!(file, "{}", serde_json::to_string(&Header { version: HISTORY_FORMAT_VERSION })?)?;
+    for entry in entries {
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    }
+    file.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_current_format() {
+        let entry = HistoryEntry {
+            request: JsonRpcRequest::new("eth_getBalance", json!(["0xabc"]), 3),
+            response: JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!("0x0")), error: None, id: 3 },
+            endpoint_override: Some("http://archive:8545".into()),
+            latency: Some(std::time::Duration::from_millis(12)),
+            spec_params: Some(vec!["address".into(), "block".into()]),
+            dry_run: false,
+            provider: None,
+            provenance: Default::default(),
+            derived_from: None,
+            spec: None,
+            spill: None,
+            seq: 3,
+            needs_review: false,
+        };
+        let text = encode(std::slice::from_ref(&entry)).unwrap();
+        assert!(text.contains("\"version\": 1"));
+        assert_eq!(decode(&text).unwrap(), vec![entry]);
+    }
+
+    #[test]
+    fn upgrades_version_zero_pairs() {
+        let text = r#"[[
+            {"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1},
+            {"jsonrpc":"2.0","result":"0x10","error":null,"id":1}
+        ]]"#;
+        let entries = decode(text).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].request.method, "eth_blockNumber");
+        assert_eq!(entries[0].spec_params, None);
+        assert_eq!(entries[0].seq, 1);
+    }
+
+    #[test]
+    fn numbers_legacy_entries_stably() {
+        // Written before numbering: derived_from was a list position
+        let mut legacy = [entry(0), entry(0), entry(0)];
+        legacy[2].derived_from = Some(0);
+        let mut lines: String = legacy.iter().map(|e| serde_json::to_string(e).unwrap() + "\n").collect();
+        let mut newer = entry(4);
+        newer.derived_from = Some(3);
+        lines += &(serde_json::to_string(&newer).unwrap() + "\n");
+
+        for _ in 0..2 {
+            let (entries, bad) = parse_lines(&lines);
+            assert_eq!(bad, 0);
+            assert_eq!(entries.iter().map(|e| e.seq).collect::<Vec<_>>(), [1, 2, 3, 4]);
+            assert_eq!(entries[2].derived_from, Some(1));
+            assert_eq!(entries[3].derived_from, Some(3));
+        }
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let err = decode(r#"{"version": 99, "entries": []}"#).unwrap_err();
+        assert!(err.to_string().contains("newer"));
+    }
+
+    fn entry(id: u64) -> HistoryEntry {
+        HistoryEntry {
+            spec_params: Some(Vec::new()),
+            seq: id,
+            ..HistoryEntry::new(
+                JsonRpcRequest::new("eth_blockNumber", json!([]), id),
+                JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!("0x10")), error: None, id },
+            )
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("eli-history-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn appends_and_reloads() {
+        let dir = temp_dir("append");
+        let path = dir.join("history.jsonl");
+        let mut opened = HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap();
+        assert!(opened.entries.is_empty() && opened.repaired.is_none() && !opened.locked_elsewhere);
+        opened.store.append(&entry(1)).unwrap();
+        opened.store.append(&entry(2)).unwrap();
+        drop(opened);
+
+        let reopened = HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap();
+        assert_eq!(reopened.entries, vec![entry(1), entry(2)]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_only_the_newest_entries() {
+        let mut entries: Vec<_> = (1..=10).map(entry).collect();
+        // Within a tenth of the limit: left alone
+        assert_eq!(trim(&mut entries, 10), 0);
+        entries.push(entry(11));
+        assert_eq!(trim(&mut entries, 10), 0);
+        entries.push(entry(12));
+        assert_eq!(trim(&mut entries, 10), 2);
+        assert_eq!(entries.first().map(|e| e.seq), Some(3));
+
+        let dir = temp_dir("limit");
+        let path = dir.join("history.jsonl");
+        let mut opened = HistoryStore::open(&path, false, 100).unwrap();
+        for id in 1..=5 {
+            opened.store.append(&entry(id)).unwrap();
+        }
+        drop(opened);
+        let opened = HistoryStore::open(&path, false, 2).unwrap();
+        assert_eq!(opened.entries, vec![entry(4), entry(5)]);
+        drop(opened);
+        // The file was trimmed too, under its header
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn locked_file_opens_read_only() {
+        let dir = temp_dir("locked");
+        let path = dir.join("history.jsonl");
+        let mut first = HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap();
+        first.store.append(&entry(1)).unwrap();
+
+        let mut second = HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap();
+        assert!(second.store.is_read_only());
+        assert_eq!(second.entries, vec![entry(1)]);
+        assert!(second.locked_elsewhere);
+        second.store.append(&entry(2)).unwrap();
+        drop(second);
+
+        // Only the lock holder's write landed
+        drop(first);
+        assert_eq!(HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap().entries, vec![entry(1)]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn read_only_open_creates_nothing() {
+        let dir = temp_dir("read-only");
+        let path = dir.join("history.jsonl");
+        let opened = HistoryStore::open(&path, true, DEFAULT_LIMIT).unwrap();
+        assert!(opened.entries.is_empty() && opened.store.is_read_only() && !opened.locked_elsewhere);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn garbage_in_the_middle_is_moved_aside() {
+        let dir = temp_dir("corrupt");
+        let path = dir.join("history.jsonl");
+        std::fs::create_dir_all(&dir).unwrap();
+        let line = |id| serde_json::to_string(&entry(id)).unwrap();
+        let original = format!("{}\n{{\"request\": tru\n{}\n", line(1), line(2));
+        std::fs::write(&path, &original).unwrap();
+
+        let opened = HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap();
+        assert_eq!(opened.entries, vec![entry(1), entry(2)]);
+        let repaired = opened.repaired.unwrap();
+        assert!(repaired.contains("skipped 1 malformed line"), "{}", repaired);
+
+        let aside: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.to_string_lossy().contains("history.jsonl.corrupt-"))
+            .collect();
+        assert_eq!(aside.len(), 1);
+        assert_eq!(std::fs::read_to_string(&aside[0]).unwrap(), original);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), format!("{{\"version\":1}}\n{}\n{}\n", line(1), line(2)));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn the_header_line_carries_the_format_version() {
+        let dir = temp_dir("header");
+        let path = dir.join("history.jsonl");
+        let mut opened = HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap();
+        opened.store.append(&entry(1)).unwrap();
+        drop(opened);
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().next(), Some(format!("{{\"version\":{}}}", HISTORY_FORMAT_VERSION).as_str()));
+        assert_eq!(HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap().entries, vec![entry(1)]);
+
+        // Written before the header existed: it gets one, the original kept
+        let line = serde_json::to_string(&entry(1)).unwrap();
+        std::fs::write(&path, format!("{}\n", line)).unwrap();
+        assert_eq!(HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap().entries, vec![entry(1)]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        let backup = storage::migration_backup_path(&path, 1);
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), format!("{}\n", line));
+        let pairs = json!([[entry(1).request, entry(1).response]]).to_string();
+        std::fs::write(&path, &pairs).unwrap();
+        HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap();
+        assert_eq!(std::fs::read_to_string(storage::migration_backup_path(&path, 0)).unwrap(), pairs);
+
+        // A newer eli's file is neither read nor rewritten
+        let newer = format!("{{\"version\":{}}}\n{}\n", HISTORY_FORMAT_VERSION + 1, line);
+        std::fs::write(&path, &newer).unwrap();
+        let err = HistoryStore::open(&path, false, DEFAULT_LIMIT).unwrap_err();
+        assert!(err.to_string().contains("is newer than this eli supports"), "{}", err);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), newer);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
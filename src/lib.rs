@@ -2,10 +2,138 @@
 
 /// JSON-RPC client
 pub mod rpc;
+/// Request/response hooks for the JSON-RPC client
+pub mod middleware;
 // TUI application state & logic
 pub mod app;
 // Key-event handlers
 pub mod events;
 // UI rendering
 pub mod ui;
-pub mod spec;
\ No newline at end of file
+pub mod spec;
+// Endpoint URLs and named profiles
+pub mod endpoint;
+// Multi-line text buffer shared by editors
+pub mod editor;
+// Raw-JSON params editing and validation
+pub mod raw;
+// Scrollable response viewer
+pub mod view;
+// Plain-text search over formatted lines
+pub mod search;
+// Versioned history serialization
+pub mod history;
+// Fitting stored params onto the current spec
+pub mod migrate;
+// Cache of responses to immutable requests
+pub mod cache;
+// Bounded background prefetching into the cache
+pub mod prefetch;
+// Transaction signing hashes and sender recovery
+pub mod tx;
+// Display preferences and value formatters
+pub mod prefs;
+// Persisted UI state
+pub mod uistate;
+// Chain metadata (builtin table plus ethereum-lists/chains files)
+pub mod chains;
+// Centered single-value editor popup
+pub mod popup;
+// Per-endpoint method availability probing
+pub mod capability;
+// Command-line arguments
+pub mod cli;
+// Value shape detection (hash, address, quantity, …)
+pub mod shape;
+// Side-effecting actions and the checks that gate them
+pub mod action;
+// Polled monitoring cards
+pub mod dashboard;
+// Versions of persisted files and the steps that upgrade older ones
+pub mod schema;
+// Atomic file writes with a backup of the previous version
+pub mod storage;
+// Address labels: the user's address book over a builtin registry
+pub mod addressbook;
+// Background send queue for user requests
+pub mod queue;
+// eth_simulateV1 payload builder and result summary
+pub mod simulate;
+// Method docs, hydrated in the background for the selected method
+pub mod docs;
+// CSV export of array results
+pub mod export;
+// eth_syncing health probe and the syncing warning
+pub mod sync;
+// eth_sendTransaction field form
+pub mod txform;
+// Key hints fitted to the terminal width
+pub mod hints;
+// Hosted provider detection and error envelope normalization
+pub mod provider;
+// Guided walkthrough against an offline transport
+pub mod tutorial;
+// Namespace- and method-scoped default params
+pub mod defaults;
+// Per-param record of how typed values became the sent params
+pub mod provenance;
+// Receipt/transaction/block follow-up links from the response view
+pub mod follow;
+// Bundled L2 spec extensions merged in per detected chain
+pub mod extension;
+// Per-method result decoders for the response view
+pub mod decode;
+// Latest-block poll and stalled-chain detection
+pub mod head;
+// Session readings shared with background tasks
+pub mod stats;
+// Registry of periodic background tasks, for the activity popup
+pub mod tasks;
+// eth_createAccessList result table and access list diff
+pub mod accesslist;
+// Test fixtures, exported from history or recorded from a node
+pub mod fixture;
+// Known event signatures and the hint for empty eth_getLogs results
+pub mod eventsig;
+// trace_* methods: flat trace table and the trace_filter builder
+pub mod trace;
+// Opt-in check for a newer release on crates.io
+pub mod update;
+// Method result schemas simplified into display trees
+pub mod schematree;
+// Pretty-printing large responses in the background, in batches
+pub mod pretty;
+// WebSocket transport for eth_subscribe and its notifications
+pub mod ws;
+// Last value, method, endpoint and chain left for the shell on exit
+pub mod context;
+// User-defined methods from plugin files: spec, params template, summary
+pub mod plugin;
+// Input-to-frame timing and the "UI slow" indicator
+pub mod perf;
+// Copying requests and responses to the system clipboard
+pub mod clipboard;
+// Method listing for scripts and shell completion scripts
+pub mod completions;
+// Injectable time source and suspend/resume detection
+pub mod clock;
+// Hex dumps of long byte strings in responses
+pub mod hexdump;
+// Auth commands and cached tokens for endpoint profiles
+pub mod auth;
+// Subscriptions that survive WebSocket reconnects
+pub mod subscription;
+// Offset index over responses on disk, for paging without reading them whole
+pub mod spill;
+// Undo and redo over snapshots of an edited buffer
+pub mod undo;
+// Differences between two method lists
+pub mod specdiff;
+// Gas price overview and fee recommendations from fee history
+pub mod gas;
+// Which pane of a mode takes the keys
+pub mod focus;
+// Relaxed JSON (comments, trailing commas, bare keys) rewritten as strict
+pub mod relaxed;
+// Which spec sources defined a history entry's method, and whether that changed
+pub mod origin;
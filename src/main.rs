@@ -1,23 +1,54 @@
 // src/main.rs
 
-mod rpc;
-mod app;
-mod events;
-mod ui;
-
 use anyhow::Result;
 use crossterm::{
-    event::{self, Event, KeyEvent},
+    event::{self, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use app::{App, AppMode};
-use events::{handle_main_mode, handle_param_input_mode, handle_history_mode};
-use ui::draw_ui;
+use eli::app::{App, AppMode};
+use eli::events::{
+    handle_activity_popup, handle_dashboard_mode, handle_help_overlay, handle_history_mode, handle_lease_prompt,
+    handle_main_mode, handle_param_input_mode, handle_profile_picker, handle_response_view_mode, handle_settings_popup,
+    handle_tutorial_exit,
+};
+use eli::ui::draw_ui;
+use eli::dashboard::{self, Dashboard};
+use eli::endpoint::{self, DEFAULT_ENDPOINT};
+use eli::auth::Credentials;
+use eli::rpc::{RetryPolicy, RpcClient};
+use eli::spec::{self, MethodSpec, RENAMED_METHODS, RPC_SPECS};
+use eli::context::SessionContext;
+use eli::{addressbook, chains, cli, completions, defaults, fixture, gas, head, history, perf, plugin, schema, specdiff, uistate};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args = cli::parse(std::env::args().skip(1))?;
+    if let Some(record) = &args.record {
+        return record_fixture(record, &args).await;
+    }
+    if let Some(shell) = args.completions {
+        print!("{}", completions::script(shell));
+        return Ok(());
+    }
+    // Read before the terminal is taken over, so a bad file fails plainly
+    let specs = args.spec.as_deref().map(MethodSpec::load_from_file).transpose()?;
+    if let Some(format) = args.spec_list {
+        print!("{}", completions::list(&active_specs(specs), format));
+        return Ok(());
+    }
+    if let Some((a, b)) = &args.spec_diff {
+        let active = active_specs(specs);
+        let (a_specs, b_specs) = (specdiff::resolve(a, &active)?, specdiff::resolve(b, &active)?);
+        print!("{}", specdiff::diff(&a_specs, &b_specs, RENAMED_METHODS).report(a, b));
+        return Ok(());
+    }
+    let credentials = credentials(&args)?;
+    if args.gas {
+        return print_gas(&args, credentials).await;
+    }
+
     // initialize terminal
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
@@ -27,17 +58,178 @@ async fn main() -> Result<()> {
 
     // create app state
     let mut app = App::new();
+    app.dry_run = args.dry_run;
+    app.spectator = args.spectator;
+    app.redact_addresses = args.redact_addresses;
+    if let (Some(specs), Some(path)) = (specs, &args.spec) {
+        let label = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+        app.set_spec_source(label, specs);
+    }
+    if let Some(url) = args.url {
+        app.endpoint = url;
+    }
+    if let Some(mib) = args.max_response_mib {
+        app.set_response_limit(mib * 1024 * 1024);
+    }
+    if let Some(limit) = args.history_limit {
+        app.history_limit = limit;
+    }
+    if let Some(secs) = args.timeout_secs {
+        app.set_timeout(std::time::Duration::from_secs(secs));
+    }
+    if let Some(retries) = args.retries {
+        app.set_retry(RetryPolicy { max_attempts: retries + 1, ..RetryPolicy::default() });
+    }
+    if let Some(credentials) = credentials {
+        app.set_credentials(credentials);
+    }
+    // The tutorial runs offline and leaves the user's files alone
+    if args.tutorial {
+        app.start_tutorial();
+    }
+    if !args.tutorial
+        && let Some(path) = uistate::default_path()
+        && let Err(e) = app.load_ui_state(path)
+    {
+        app.notice = Some(format!("using default settings: {:#}", e));
+    }
+    if !args.tutorial
+        && let Some(dir) = uistate::config_dir()
+    {
+        app.lease_dir = Some(dir.clone());
+        match plugin::load_dir(&dir.join("plugins")) {
+            Ok((plugins, errors)) => {
+                app.add_plugins(plugins);
+                if let Some(first) = errors.first() {
+                    let more = if errors.len() > 1 { format!(" (+{} more)", errors.len() - 1) } else { String::new() };
+                    app.show_toast(format!("plugin skipped: {}{}", first, more));
+                }
+            }
+            Err(e) => app.notice = Some(format!("plugins not loaded: {:#}", e)),
+        }
+        match chains::load_dir(&dir.join("chains")) {
+            Ok((user_chains, errors)) => {
+                app.chains.merge(user_chains);
+                if let Some(first) = errors.first() {
+                    app.notice = Some(format!("skipped chain file {}", first));
+                }
+            }
+            Err(e) => app.notice = Some(format!("{:#}", e)),
+        }
+        let book_path = dir.join("address-book.json");
+        match addressbook::load(&book_path) {
+            Ok(book) => {
+                app.note_migration(&schema::ADDRESS_BOOK, book.migrated_from);
+                // Labels added in a spectator session last only as long as it
+                if !app.spectator {
+                    app.address_book_path = Some(book_path);
+                }
+                app.address_book_base = book.value.clone();
+                app.address_book = book.value;
+                if book.warning.is_some() {
+                    app.notice = book.warning;
+                }
+            }
+            Err(e) => app.notice = Some(format!("address book not loaded: {:#}", e)),
+        }
+        // Spectator sessions read history but never write it
+        match history::HistoryStore::open(&dir.join("history.jsonl"), app.spectator, app.history_limit) {
+            Ok(opened) => {
+                app.history = opened.entries;
+                app.review_migrations();
+                app.history_store = Some(opened.store);
+                // Large results go to disk, unless another eli owns the files
+                if !opened.locked_elsewhere && !app.spectator {
+                    app.set_spill_dir(dir.join("spill"));
+                }
+                if opened.locked_elsewhere {
+                    app.notice = Some("history is in use by another eli — read-only this session".into());
+                }
+                if let Some(repaired) = opened.repaired {
+                    app.show_toast(repaired);
+                }
+            }
+            Err(e) => app.notice = Some(format!("history not loaded: {:#}", e)),
+        }
+        match defaults::load(&dir.join("defaults.json")) {
+            Ok(loaded) => {
+                app.note_migration(&schema::DEFAULTS, loaded.migrated_from);
+                app.param_defaults = loaded.value;
+                if loaded.warning.is_some() {
+                    app.notice = loaded.warning;
+                }
+            }
+            Err(e) => app.notice = Some(format!("default params not loaded: {:#}", e)),
+        }
+        match dashboard::load(&dir.join("dashboard.json")) {
+            Ok(config) => {
+                app.note_migration(&schema::DASHBOARD, config.migrated_from);
+                app.dashboard = Dashboard::new(config.value);
+                if config.warning.is_some() {
+                    app.notice = config.warning;
+                }
+            }
+            Err(e) => app.notice = Some(format!("using default dashboard: {:#}", e)),
+        }
+        let endpoints = endpoint::load(&dir.join("endpoints.json"))
+            .and_then(|loaded| {
+                app.note_migration(&schema::ENDPOINTS, loaded.migrated_from);
+                app.apply_endpoint_config(loaded.value)?;
+                Ok(loaded.warning)
+            });
+        match endpoints {
+            Ok(warning) => {
+                if warning.is_some() {
+                    app.notice = warning;
+                }
+            }
+            Err(e) => app.notice = Some(format!("endpoint profiles not loaded: {:#}", e)),
+        }
+    }
+    app.report_migrations();
+
+    // Merge the chain's spec extension once the endpoint says which chain it is
+    app.detect_chain();
+    app.open_live_heads(false);
 
     // main event loop
+    let mut first_frame = true;
     loop {
+        app.tick(app.clock.now());
         terminal.draw(|f| draw_ui(f, &mut app))?;
+        app.frame.drawn(app.clock.now());
+        // Only once the UI is up, so a slow network never delays startup
+        if std::mem::take(&mut first_frame)
+            && let Some(dir) = uistate::config_dir()
+        {
+            app.start_update_check(dir.join("update-check.json"));
+        }
 
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+        {
+            app.frame.input(app.clock.now(), app.mode);
+            // Any input supersedes background prefetching
+            app.prefetcher.cancel();
+            if handle_tutorial_exit(&mut app, key) {
+                // the tutorial is over
+            } else if app.lease_prompt.is_some() {
+                handle_lease_prompt(&mut app, key);
+            } else if app.settings.is_some() {
+                handle_settings_popup(&mut app, key).await;
+            } else if app.activity.is_some() {
+                handle_activity_popup(&mut app, key);
+            } else if app.profile_picker.is_some() {
+                handle_profile_picker(&mut app, key);
+            } else if app.help.is_some() {
+                handle_help_overlay(&mut app, key);
+            } else {
                 match app.mode {
                     AppMode::Main       => handle_main_mode(&mut app, key).await,
                     AppMode::ParamInput => handle_param_input_mode(&mut app, key).await,
                     AppMode::History    => handle_history_mode(&mut app, key).await,
+                    AppMode::ResponseView => handle_response_view_mode(&mut app, key).await,
+                    AppMode::Dashboard  => handle_dashboard_mode(&mut app, key).await,
                 }
             }
         }
@@ -50,5 +242,73 @@ async fn main() -> Result<()> {
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
+
+    if !args.tutorial
+        && !app.spectator
+        && let Some(report) = app.frame.report()
+        && let Some(dir) = uistate::config_dir()
+    {
+        let _ = perf::append_log(&dir.join("eli.log"), &report, head::unix_now());
+    }
+    if let Some(path) = &args.emit_context {
+        let context = SessionContext::from_history(&app.history, &app.endpoint, app.chain_id, &app.primary_pointers());
+        if let Err(e) = context.write(path, args.emit_context_format) {
+            eprintln!("context not written: {:#}", e);
+        }
+    }
+    Ok(())
+}
+
+/// The method list a session would start with: the `--spec` file or the
+/// builtins, plus the plugins' methods. Broken plugins are left out quietly.
+fn active_specs(specs: Option<Vec<MethodSpec>>) -> Vec<MethodSpec> {
+    let base = specs.unwrap_or_else(|| RPC_SPECS.to_vec());
+    let Some(dir) = uistate::config_dir() else { return base };
+    match plugin::load_dir(&dir.join("plugins")) {
+        Ok((plugins, _)) => {
+            let extra: Vec<MethodSpec> = plugins.iter().flat_map(|p| &p.methods).map(|m| m.spec.clone()).collect();
+            spec::merge(&base, &extra)
+        }
+        Err(_) => base,
+    }
+}
+
+/// Credentials for the session endpoint: the flags', else the environment's.
+fn credentials(args: &cli::Args) -> Result<Option<Credentials>> {
+    match &args.credentials {
+        Some(credentials) => Ok(Some(credentials.clone())),
+        None => Credentials::from_env(|name| std::env::var(name).ok()),
+    }
+}
+
+async fn print_gas(args: &cli::Args, credentials: Option<Credentials>) -> Result<()> {
+    let mut client = RpcClient::new().with_retry(RetryPolicy::default());
+    if let Some(secs) = args.timeout_secs {
+        client = client.with_timeout(std::time::Duration::from_secs(secs));
+    }
+    if let Some(retries) = args.retries {
+        client = client.with_retry(RetryPolicy { max_attempts: retries + 1, ..RetryPolicy::default() });
+    }
+    let url = args.url.as_deref().unwrap_or(DEFAULT_ENDPOINT);
+    if let Some(credentials) = credentials {
+        client = client.with_credentials(url, credentials);
+    }
+    print!("{}", gas::fetch(&client, url).await?.report());
+    Ok(())
+}
+
+/// `eli fixture record`: one request to `--url` (or the default endpoint),
+/// written as a fixture; no terminal UI.
+async fn record_fixture(record: &cli::RecordArgs, args: &cli::Args) -> Result<()> {
+    let mut client = RpcClient::new();
+    if let Some(secs) = args.timeout_secs {
+        client = client.with_timeout(std::time::Duration::from_secs(secs));
+    }
+    let url = args.url.as_deref().unwrap_or(DEFAULT_ENDPOINT);
+    if let Some(credentials) = credentials(args)? {
+        client = client.with_credentials(url, credentials);
+    }
+    let dir = fixture::record(&client, url, &record.method, record.params.clone(), &record.out, &record.scrub, head::unix_now()).await?;
+    println!("recorded {}", dir.display());
     Ok(())
 }
@@ -0,0 +1,255 @@
+// src/middleware.rs
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::rpc::JsonRpcResponse;
+
+/// Outgoing request as seen by middleware before it is sent.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    /// JSON-RPC method being called.
+    pub method: String,
+    /// JSON-RPC id of the request.
+    pub id: u64,
+    /// Endpoint the request is about to be sent to.
+    pub url: String,
+    /// HTTP headers to send; middleware may add, change, or remove entries.
+    pub headers: HeaderMap,
+}
+
+/// Outcome of a request as seen by middleware after it completes.
+#[derive(Debug)]
+pub struct ResponseContext<'a> {
+    /// JSON-RPC method that was called.
+    pub method: &'a str,
+    /// JSON-RPC id of the request.
+    pub id: u64,
+    /// Endpoint the request was sent to.
+    pub url: &'a str,
+    /// Time from just before sending until the response was parsed or failed.
+    pub elapsed: Duration,
+    /// The parsed response, or the transport/parse error.
+    pub outcome: Result<&'a JsonRpcResponse, &'a anyhow::Error>,
+}
+
+/// Hooks run around every request sent through an [`RpcClient`](crate::rpc::RpcClient).
+///
+/// `before` hooks run in registration order, `after` hooks in the same order
+/// once the request completes (successfully or not).
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Called before the request is sent; may modify headers.
+    async fn before(&self, _ctx: &mut RequestContext) {}
+
+    /// Called after the request completes.
+    async fn after(&self, _ctx: &ResponseContext<'_>) {}
+}
+
+/// Adds a fixed set of headers to every request.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderInjector {
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl HeaderInjector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a header to inject, replacing any existing value for that name.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+}
+
+#[async_trait]
+impl Middleware for HeaderInjector {
+    async fn before(&self, ctx: &mut RequestContext) {
+        for (name, value) in &self.headers {
+            ctx.headers.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+/// A single timed request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencySample {
+    pub method: String,
+    pub id: u64,
+    pub url: String,
+    pub elapsed: Duration,
+    /// Whether a JSON-RPC response was received (an RPC error still counts).
+    pub ok: bool,
+}
+
+/// Round trips longer than this are not latency but a suspend in the
+/// middle of the request (the monotonic clock keeps running through sleep
+/// on some platforms); they are left out of the samples.
+pub const MAX_SAMPLE: Duration = Duration::from_secs(10 * 60);
+
+/// Records the latency and outcome of every request.
+///
+/// Clones share the same sample buffer, so keep a clone to read what the
+/// client recorded. Only the most recent `capacity` samples are kept, and
+/// none longer than [`MAX_SAMPLE`].
+#[derive(Debug, Clone)]
+pub struct LatencyRecorder {
+    samples: Arc<Mutex<Vec<LatencySample>>>,
+    capacity: usize,
+}
+
+impl Default for LatencyRecorder {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl LatencyRecorder {
+    pub fn new(capacity: usize) -> Self {
+        LatencyRecorder { samples: Arc::new(Mutex::new(Vec::new())), capacity: capacity.max(1) }
+    }
+
+    /// All retained samples, oldest first.
+    pub fn samples(&self) -> Vec<LatencySample> {
+        self.samples.lock().unwrap().clone()
+    }
+
+    /// The most recent sample, if any.
+    pub fn last(&self) -> Option<LatencySample> {
+        self.samples.lock().unwrap().last().cloned()
+    }
+
+    /// The newest sample for request `id` of `method`, wherever it is
+    /// among the retained ones: background requests may finish after it.
+    pub fn find(&self, method: &str, id: u64) -> Option<LatencySample> {
+        self.samples.lock().unwrap().iter().rev().find(|s| s.method == method && s.id == id).cloned()
+    }
+}
+
+#[async_trait]
+impl Middleware for LatencyRecorder {
+    async fn after(&self, ctx: &ResponseContext<'_>) {
+        if ctx.elapsed > MAX_SAMPLE {
+            return;
+        }
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == self.capacity {
+            samples.remove(0);
+        }
+        samples.push(LatencySample {
+            method: ctx.method.to_string(),
+            id: ctx.id,
+            url: ctx.url.to_string(),
+            elapsed: ctx.elapsed,
+            ok: ctx.outcome.is_ok(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{JsonRpcRequest, RpcClient};
+    use mockito::{mock, server_url};
+    use serde_json::json;
+
+    /// Records the order in which hooks fire.
+    struct Tracer {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Middleware for Tracer {
+        async fn before(&self, ctx: &mut RequestContext) {
+            self.log.lock().unwrap().push(format!("{} before {}", self.name, ctx.method));
+        }
+
+        async fn after(&self, ctx: &ResponseContext<'_>) {
+            self.log.lock().unwrap().push(format!("{} after {}", self.name, ctx.outcome.is_ok()));
+        }
+    }
+
+    #[tokio::test]
+    async fn header_injector_stamps_outgoing_request() {
+        let _m = mock("POST", "/")
+            .match_header("x-trace-id", "abc123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "jsonrpc": "2.0", "result": "0x1", "id": 1 }"#)
+            .create();
+
+        let client = RpcClient::new().with_middleware(
+            HeaderInjector::new().header(HeaderName::from_static("x-trace-id"), HeaderValue::from_static("abc123")),
+        );
+        let res = client.send(&server_url(), JsonRpcRequest::new("eth_blockNumber", json!([]), 1)).await;
+        // A missing header would make mockito answer 501 with an empty body
+        assert_eq!(res.unwrap().result, Some(json!("0x1")));
+    }
+
+    #[tokio::test]
+    async fn latency_recorder_records_success_and_failure() {
+        let _m = mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "jsonrpc": "2.0", "result": "0x1", "id": 1 }"#)
+            .create();
+
+        let recorder = LatencyRecorder::new(1);
+        let client = RpcClient::new().with_middleware(recorder.clone());
+        client.send(&server_url(), JsonRpcRequest::new("eth_gasPrice", json!([]), 1)).await.unwrap();
+        let ok = recorder.last().unwrap();
+        assert_eq!(ok.method, "eth_gasPrice");
+        assert!(ok.ok);
+
+        let dead = "http://127.0.0.1:1";
+        assert!(client.send(dead, JsonRpcRequest::new("eth_chainId", json!([]), 2)).await.is_err());
+        // Capacity 1 keeps only the newest sample
+        let samples = recorder.samples();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].url, dead);
+        assert!(!samples[0].ok);
+    }
+
+    #[tokio::test]
+    async fn round_trips_spanning_a_suspend_are_not_samples() {
+        let recorder = LatencyRecorder::default();
+        let response = JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!("0x1")), error: None, id: 1 };
+        for (id, elapsed) in [(1, Duration::from_millis(80)), (2, Duration::from_secs(3 * 3600))] {
+            let ctx = ResponseContext { method: "eth_blockNumber", id, url: "http://node", elapsed, outcome: Ok(&response) };
+            recorder.after(&ctx).await;
+        }
+        assert_eq!(recorder.samples().iter().map(|s| s.id).collect::<Vec<_>>(), [1]);
+    }
+
+    #[tokio::test]
+    async fn samples_are_found_by_method_and_id_behind_later_ones() {
+        let recorder = LatencyRecorder::default();
+        let response = JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!("0x1")), error: None, id: 1 };
+        for (method, id, ms) in [("eth_call", 7, 40), ("eth_blockNumber", 8, 5), ("eth_syncing", 9, 6)] {
+            let elapsed = Duration::from_millis(ms);
+            let ctx = ResponseContext { method, id, url: "http://node", elapsed, outcome: Ok(&response) };
+            recorder.after(&ctx).await;
+        }
+        assert_eq!(recorder.find("eth_call", 7).map(|s| s.elapsed), Some(Duration::from_millis(40)));
+        assert!(recorder.find("eth_call", 8).is_none());
+    }
+
+    #[tokio::test]
+    async fn hooks_run_in_registration_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let client = RpcClient::new()
+            .with_middleware(Tracer { name: "a", log: log.clone() })
+            .with_middleware(Tracer { name: "b", log: log.clone() });
+        let _ = client.send("http://127.0.0.1:1", JsonRpcRequest::new("net_version", json!([]), 1)).await;
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["a before net_version", "b before net_version", "a after false", "b after false"]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+// src/migrate.rs
+
+use serde_json::Value;
+
+use crate::spec::MethodSpec;
+
+/// Result of fitting stored params onto the current spec for a method.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamsMigration {
+    /// Stored params line up with the spec as-is.
+    Unchanged { method: String, fields: Vec<String> },
+    /// Params were realigned by position; new trailing params are empty.
+    Migrated { method: String, fields: Vec<String> },
+    /// Params can't be mapped onto fields (unknown method, named params, or
+    /// more values than the spec has slots); edit them as raw JSON.
+    Raw { method: String, params: Value },
+}
+
+impl ParamsMigration {
+    /// Whether the user should double-check the params before sending.
+    pub fn needs_review(&self) -> bool {
+        !matches!(self, ParamsMigration::Unchanged { .. })
+    }
+
+    /// The method the params were migrated onto.
+    pub fn method(&self) -> &str {
+        match self {
+            ParamsMigration::Unchanged { method, .. }
+            | ParamsMigration::Migrated { method, .. }
+            | ParamsMigration::Raw { method, .. } => method,
+        }
+    }
+}
+
+/// Text shown in a param field for a stored JSON value: strings unquoted,
+/// everything else as compact JSON. Strings that would read back as other
+/// JSON (`"123"`, `"true"`) stay quoted, so
+/// [`parse_param`](crate::app::parse_param) gives the same value back.
+pub fn value_to_field(value: &Value) -> String {
+    match value {
+        Value::String(s) if crate::app::parse_param(s) == *value => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Fits `params` recorded for `method` onto the current `specs`.
+///
+/// `recorded` is the method's param list at the time the entry was created
+/// (`None` for entries that predate recording it).
+///
+/// Rules, in order:
+/// 1. A method listed in `renames` (old → new) is looked up under its new name.
+/// 2. Unknown methods and non-array params fall back to raw editing.
+/// 3. More stored values than spec params: raw editing, so the extras are
+///    preserved instead of silently dropped.
+/// 4. Same method name and the recorded param list equals the current one:
+///    unchanged (omitted trailing params stay empty).
+/// 5. Otherwise (spec changed or unknown): shared prefix by position, the
+///    rest left empty, flagged for review. Legacy entries whose value count
+///    already equals the spec's count are taken as unchanged.
+pub fn migrate_params(
+    method: &str,
+    params: &Value,
+    recorded: Option<&[String]>,
+    specs: &[MethodSpec],
+    renames: &[(&str, &str)],
+) -> ParamsMigration {
+    let renamed = renames.iter().find(|(old, _)| *old == method).map(|(_, new)| *new);
+    let current = renamed.unwrap_or(method).to_string();
+
+    let (Some(spec), Value::Array(values)) = (specs.iter().find(|s| s.name == current), params) else {
+        return ParamsMigration::Raw { method: current, params: params.clone() };
+    };
+    if values.len() > spec.params.len() {
+        return ParamsMigration::Raw { method: current, params: params.clone() };
+    }
+
+    let mut fields: Vec<String> = values.iter().map(value_to_field).collect();
+    fields.resize(spec.params.len(), String::new());
+    let same_shape = match recorded {
+        Some(names) => names.iter().map(String::as_str).eq(spec.params.iter()),
+        None => values.len() == spec.params.len(),
+    };
+    if renamed.is_none() && same_shape {
+        ParamsMigration::Unchanged { method: current, fields }
+    } else {
+        ParamsMigration::Migrated { method: current, fields }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SPECS: &[MethodSpec] = &[
+        MethodSpec::builtin("eth_getBalance", &["address", "block"], &[], &[]),
+        MethodSpec::builtin("eth_getLogs", &["filter", "options"], &[], &[]),
+        MethodSpec::builtin("eth_newName", &["value"], &[], &[]),
+    ];
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn matching_params_are_unchanged() {
+        let m = migrate_params("eth_getBalance", &json!(["0xabc", "latest"]), None, SPECS, &[]);
+        assert_eq!(m, ParamsMigration::Unchanged {
+            method: "eth_getBalance".into(),
+            fields: vec!["0xabc".into(), "latest".into()],
+        });
+        assert!(!m.needs_review());
+    }
+
+    #[test]
+    fn omitted_trailing_param_under_same_spec_is_unchanged() {
+        let recorded = names(&["address", "block"]);
+        let m = migrate_params("eth_getBalance", &json!(["0xabc"]), Some(&recorded), SPECS, &[]);
+        assert_eq!(m, ParamsMigration::Unchanged {
+            method: "eth_getBalance".into(),
+            fields: vec!["0xabc".into(), "".into()],
+        });
+    }
+
+    #[test]
+    fn new_trailing_param_is_left_empty() {
+        let recorded = names(&["filter"]);
+        let m = migrate_params("eth_getLogs", &json!([{"address": "0x1"}]), Some(&recorded), SPECS, &[]);
+        assert_eq!(m, ParamsMigration::Migrated {
+            method: "eth_getLogs".into(),
+            fields: vec![r#"{"address":"0x1"}"#.into(), "".into()],
+        });
+        assert!(m.needs_review());
+    }
+
+    #[test]
+    fn extras_fall_back_to_raw() {
+        let params = json!(["0xabc", "latest", true]);
+        let m = migrate_params("eth_getBalance", &params, None, SPECS, &[]);
+        assert_eq!(m, ParamsMigration::Raw { method: "eth_getBalance".into(), params });
+    }
+
+    #[test]
+    fn renamed_method_is_migrated() {
+        let m = migrate_params("eth_oldName", &json!([7]), None, SPECS, &[("eth_oldName", "eth_newName")]);
+        assert_eq!(m, ParamsMigration::Migrated { method: "eth_newName".into(), fields: vec!["7".into()] });
+    }
+
+    #[test]
+    fn unknown_method_and_named_params_fall_back_to_raw() {
+        assert!(matches!(migrate_params("foo_bar", &json!([1]), None, SPECS, &[]), ParamsMigration::Raw { .. }));
+        let named = json!({"address": "0xabc"});
+        assert_eq!(
+            migrate_params("eth_getBalance", &named, None, SPECS, &[]),
+            ParamsMigration::Raw { method: "eth_getBalance".into(), params: named.clone() }
+        );
+    }
+}
@@ -0,0 +1,107 @@
+// src/origin.rs
+
+use serde::{Deserialize, Serialize};
+
+use crate::spec::MethodSpec;
+
+/// Where a history entry's method was defined when the entry was recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpecStamp {
+    /// Stable ids of the sources defining the method, e.g. "spec:builtin",
+    /// "spec:openrpc.json", "plugin:myrollup", "extension:optimism".
+    pub sources: Vec<String>,
+    /// [`definition_hash`] of the method's spec.
+    pub hash: String,
+}
+
+impl SpecStamp {
+    /// "spec:builtin + extension:optimism (1f0c9a2e)", for the detail view.
+    pub fn describe(&self) -> String {
+        let sources = if self.sources.is_empty() { "unknown source".to_string() } else { self.sources.join(" + ") };
+        format!("{} ({})", sources, self.hash.get(..8).unwrap_or(&self.hash))
+    }
+}
+
+/// A hash of what a method's definition means for its requests: its name
+/// and its params' names and kinds. Help text doesn't count. FNV-1a, so
+/// the same definition hashes the same across runs and builds.
+pub fn definition_hash(spec: &MethodSpec) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |text: &str| {
+        for byte in text.bytes().chain([0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+    feed(&spec.name);
+    for (index, param) in spec.params.iter().enumerate() {
+        feed(param);
+        feed(spec.kind(index).label());
+    }
+    format!("{:016x}", hash)
+}
+
+/// What changed about `method` since `recorded` was stamped, given its
+/// stamp now (`None` once nothing defines it); `None` when nothing did.
+pub fn drift(method: &str, recorded: &SpecStamp, current: Option<&SpecStamp>) -> Option<String> {
+    let Some(current) = current else {
+        return Some(format!("{} came from {}, which no longer defines it", method, recorded.sources.join(" + ")));
+    };
+    if recorded.sources != current.sources {
+        Some(format!("{} was from {}, now from {}", method, recorded.sources.join(" + "), current.sources.join(" + ")))
+    } else if recorded.hash != current.hash {
+        Some(format!("{} changed in {} since this was recorded", method, current.sources.join(" + ")))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::{Labels, ParamKind};
+
+    const BALANCE: MethodSpec = MethodSpec::builtin("eth_getBalance", &["address", "block"], &[], &[]);
+
+    fn stamp(sources: &[&str], spec: &MethodSpec) -> SpecStamp {
+        SpecStamp { sources: sources.iter().map(|s| s.to_string()).collect(), hash: definition_hash(spec) }
+    }
+
+    #[test]
+    fn hashes_cover_names_and_kinds_but_not_help() {
+        assert_eq!(definition_hash(&BALANCE), definition_hash(&BALANCE.clone()));
+        let documented = MethodSpec { help: Labels::Static(&["who", "when"]), ..BALANCE };
+        assert_eq!(definition_hash(&documented), definition_hash(&BALANCE));
+        let renamed = MethodSpec { params: Labels::Static(&["address", "blockTag"]), ..BALANCE };
+        assert_ne!(definition_hash(&renamed), definition_hash(&BALANCE));
+        let toggle = &[ParamKind::Text, ParamKind::Bool { default: false, on: "true", off: "false" }];
+        let retyped = MethodSpec { kinds: std::borrow::Cow::Borrowed(toggle), ..BALANCE };
+        assert_ne!(definition_hash(&retyped), definition_hash(&BALANCE));
+        // Params don't run together: ["ab"] isn't ["a", "b"]
+        let joined = MethodSpec { params: Labels::Static(&["addressblock"]), ..BALANCE };
+        assert_ne!(definition_hash(&joined), definition_hash(&BALANCE));
+    }
+
+    #[test]
+    fn drift_names_what_changed() {
+        let recorded = stamp(&["spec:builtin"], &BALANCE);
+        assert_eq!(drift("eth_getBalance", &recorded, Some(&recorded.clone())), None);
+
+        let upgraded = stamp(&["spec:builtin"], &MethodSpec { params: Labels::Static(&["address", "blockTag"]), ..BALANCE });
+        assert_eq!(
+            drift("eth_getBalance", &recorded, Some(&upgraded)).unwrap(),
+            "eth_getBalance changed in spec:builtin since this was recorded"
+        );
+        let moved = stamp(&["spec:node.json"], &BALANCE);
+        assert_eq!(
+            drift("eth_getBalance", &recorded, Some(&moved)).unwrap(),
+            "eth_getBalance was from spec:builtin, now from spec:node.json"
+        );
+        let plugin = stamp(&["plugin:myrollup"], &BALANCE);
+        assert_eq!(
+            drift("myrollup_getBatch", &plugin, None).unwrap(),
+            "myrollup_getBatch came from plugin:myrollup, which no longer defines it"
+        );
+        assert!(recorded.describe().starts_with("spec:builtin ("));
+    }
+}
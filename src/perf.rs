@@ -0,0 +1,202 @@
+// src/perf.rs
+
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use crate::app::AppMode;
+use crate::stats::Rolling;
+
+/// Input-to-frame time past which eli feels sluggish.
+pub const DEFAULT_BUDGET: Duration = Duration::from_millis(50);
+
+/// Frames the percentile is taken over.
+const WINDOW: usize = 128;
+
+/// Slowest frames kept for the log.
+const OFFENDERS: usize = 5;
+
+/// A frame that took longer than the budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowFrame {
+    pub elapsed: Duration,
+    pub mode: AppMode,
+    /// Expensive passes the input caused, e.g. "filter", "format".
+    pub passes: Vec<&'static str>,
+}
+
+/// Time from each input event to the frame drawn after it.
+///
+/// The main loop calls [`FrameBudget::input`] on every key and
+/// [`FrameBudget::drawn`] after every draw; code doing something expensive
+/// in between names it with [`FrameBudget::pass`]. Below the budget that is
+/// all it costs: the percentile is only taken again once a frame goes over,
+/// or while the UI is flagged slow.
+#[derive(Debug, Clone)]
+pub struct FrameBudget {
+    /// `None` when disabled.
+    budget: Option<Duration>,
+    times: Rolling<Duration>,
+    pending: Option<(Instant, AppMode)>,
+    passes: Vec<&'static str>,
+    slow: bool,
+    /// Slowest first.
+    offenders: Vec<SlowFrame>,
+}
+
+impl Default for FrameBudget {
+    fn default() -> Self {
+        FrameBudget::new(Some(DEFAULT_BUDGET))
+    }
+}
+
+impl FrameBudget {
+    pub fn new(budget: Option<Duration>) -> Self {
+        FrameBudget { budget, times: Rolling::new(WINDOW), pending: None, passes: Vec::new(), slow: false, offenders: Vec::new() }
+    }
+
+    /// An input event arrived in `mode`.
+    pub fn input(&mut self, at: Instant, mode: AppMode) {
+        if self.budget.is_some() {
+            self.pending = Some((at, mode));
+            self.passes.clear();
+        }
+    }
+
+    /// Notes an expensive pass run for the pending input.
+    pub fn pass(&mut self, name: &'static str) {
+        if self.pending.is_some() && !self.passes.contains(&name) {
+            self.passes.push(name);
+        }
+    }
+
+    /// The frame after the pending input finished drawing.
+    pub fn drawn(&mut self, at: Instant) {
+        let (Some(budget), Some((started, mode))) = (self.budget, self.pending.take()) else { return };
+        let elapsed = at.saturating_duration_since(started);
+        self.times.push(elapsed);
+        if elapsed > budget {
+            let frame = SlowFrame { elapsed, mode, passes: std::mem::take(&mut self.passes) };
+            let at = self.offenders.partition_point(|o| o.elapsed >= elapsed);
+            self.offenders.insert(at, frame);
+            self.offenders.truncate(OFFENDERS);
+        }
+        if elapsed > budget || self.slow {
+            self.slow = self.times.percentile(95.0).is_some_and(|p95| p95 > budget);
+        }
+    }
+
+    /// Forgets the pending input, e.g. when the machine slept before its
+    /// frame was drawn.
+    pub fn discard(&mut self) {
+        self.pending = None;
+        self.passes.clear();
+    }
+
+    /// Whether the p95 input-to-frame time is over budget.
+    pub fn is_slow(&self) -> bool {
+        self.slow
+    }
+
+    /// The slowest frames, slowest first.
+    pub fn offenders(&self) -> &[SlowFrame] {
+        &self.offenders
+    }
+
+    /// Log lines about the slowest frames; `None` if none went over budget.
+    pub fn report(&self) -> Option<String> {
+        let budget = self.budget?;
+        if self.offenders.is_empty() {
+            return None;
+        }
+        let p95 = self.times.percentile(95.0).unwrap_or_default();
+        let mut text = format!("input-to-frame p95 {} ms over {} frames (budget {} ms); slowest:\n", p95.as_millis(), self.times.len(), budget.as_millis());
+        for frame in &self.offenders {
+            let passes = if frame.passes.is_empty() { "no format/filter pass".to_string() } else { frame.passes.join(", ") };
+            text.push_str(&format!("  {} ms in {:?} ({})\n", frame.elapsed.as_millis(), frame.mode, passes));
+        }
+        Some(text)
+    }
+}
+
+/// Appends `text` to the log at `path`, stamped with `now` (unix seconds).
+pub fn append_log(path: &Path, text: &str, now: u64) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path).with_context(|| format!("cannot open {}", path.display()))?;
+    write!(file, "[{}] {}", now, text).with_context(|| format!("cannot write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// One input in `mode` taking `ms` to draw, with `passes`.
+    fn frame(budget: &mut FrameBudget, start: Instant, ms: u64, mode: AppMode, passes: &[&'static str]) {
+        budget.input(start, mode);
+        passes.iter().for_each(|p| budget.pass(p));
+        budget.drawn(start + Duration::from_millis(ms));
+    }
+
+    #[test]
+    fn flags_a_slow_p95_and_keeps_the_worst_frames() {
+        let mut budget = FrameBudget::default();
+        let start = Instant::now();
+        for _ in 0..100 {
+            frame(&mut budget, start, 5, AppMode::Main, &[]);
+        }
+        assert!(!budget.is_slow());
+        assert_eq!(budget.report(), None);
+
+        // A few slow frames don't move the p95...
+        frame(&mut budget, start, 400, AppMode::ResponseView, &["format"]);
+        frame(&mut budget, start, 90, AppMode::Main, &["filter", "filter"]);
+        assert!(!budget.is_slow());
+        // ...a steady run of them does, until fast frames push them out
+        for _ in 0..20 {
+            frame(&mut budget, start, 60, AppMode::History, &[]);
+        }
+        assert!(budget.is_slow());
+        assert_eq!(budget.offenders().len(), OFFENDERS);
+        assert_eq!(budget.offenders()[0], SlowFrame { elapsed: 400 * MS, mode: AppMode::ResponseView, passes: vec!["format"] });
+        assert_eq!(budget.offenders()[1].passes, ["filter"]);
+        let report = budget.report().unwrap();
+        assert!(report.contains("400 ms in ResponseView (format)"), "{}", report);
+        assert!(report.contains("60 ms in History (no format/filter pass)"), "{}", report);
+        for _ in 0..WINDOW {
+            frame(&mut budget, start, 5, AppMode::Main, &[]);
+        }
+        assert!(!budget.is_slow());
+
+        // Drawing without a new input measures nothing
+        budget.drawn(start + 1000 * MS);
+        assert_eq!(budget.offenders()[0].elapsed, 400 * MS);
+    }
+
+    #[test]
+    fn disabled_budget_measures_nothing() {
+        let mut budget = FrameBudget::new(None);
+        let start = Instant::now();
+        for _ in 0..10 {
+            frame(&mut budget, start, 500, AppMode::Main, &["format"]);
+        }
+        assert!(!budget.is_slow());
+        assert!(budget.offenders().is_empty());
+        assert_eq!(budget.report(), None);
+    }
+
+    #[test]
+    fn log_lines_are_appended() {
+        let dir = std::env::temp_dir().join(format!("eli-perf-{}", std::process::id()));
+        let path = dir.join("eli.log");
+        append_log(&path, "first\n", 1).unwrap();
+        append_log(&path, "second\n", 2).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[1] first\n[2] second\n");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
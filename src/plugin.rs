@@ -0,0 +1,314 @@
+// src/plugin.rs
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::decode::{DecodedView, ResultDecoder};
+use crate::prefs::DisplayPrefs;
+use crate::spec::{MethodSpec, ParamKind};
+
+/// A plugin file from `plugins/` in the config directory: methods a node
+/// serves that eli doesn't know, described fully in JSON.
+#[derive(Debug, Deserialize)]
+struct PluginFile {
+    name: String,
+    methods: Vec<MethodEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MethodEntry {
+    name: String,
+    #[serde(default)]
+    params: Vec<ParamEntry>,
+    /// Params as sent, with `{param}` placeholders for the fields.
+    #[serde(default)]
+    template: Option<Value>,
+    #[serde(default)]
+    extract: Vec<Extract>,
+    /// Result summary, with `{/pointer}` placeholders.
+    #[serde(default)]
+    summary: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ParamEntry {
+    name: String,
+    /// `boolean` params are toggles; any other type is shown in the help.
+    #[serde(default, rename = "type")]
+    ty: Option<String>,
+    #[serde(default)]
+    example: Option<String>,
+    #[serde(default)]
+    help: String,
+}
+
+/// A labelled value picked out of a result by JSON pointer.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Extract {
+    pub label: String,
+    pub pointer: String,
+}
+
+/// One method a plugin defines.
+#[derive(Debug, Clone)]
+pub struct PluginMethod {
+    pub spec: MethodSpec,
+    /// Name of the plugin defining it.
+    pub plugin: String,
+    pub template: Option<Value>,
+    /// The first is the result's primary value.
+    pub extract: Vec<Extract>,
+    pub summary: Option<String>,
+}
+
+/// A loaded plugin file.
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub name: String,
+    /// File name, for messages.
+    pub file: String,
+    pub methods: Vec<PluginMethod>,
+}
+
+/// Parses a plugin file.
+pub fn parse(text: &str) -> Result<Plugin> {
+    let file: PluginFile = serde_json::from_str(text)?;
+    let mut methods: Vec<PluginMethod> = Vec::new();
+    for method in file.methods {
+        if method.name.is_empty() {
+            bail!("a method has no name");
+        }
+        if methods.iter().any(|m| m.spec.name == method.name) {
+            bail!("{} is defined twice", method.name);
+        }
+        if let Some(extract) = method.extract.iter().find(|e| !e.pointer.is_empty() && !e.pointer.starts_with('/')) {
+            bail!("{}: extract pointer '{}' must start with /", method.name, extract.pointer);
+        }
+        if let Some(template) = &method.template {
+            let mut names = Vec::new();
+            placeholders(template, &mut names);
+            if let Some(unknown) = names.iter().find(|n| !method.params.iter().any(|p| &p.name == *n)) {
+                bail!("{}: template placeholder {{{}}} is not a param", method.name, unknown);
+            }
+        }
+        let params: Vec<String> = method.params.iter().map(|p| p.name.clone()).collect();
+        let help: Vec<String> = method.params.iter().map(param_help).collect();
+        let kinds: Vec<ParamKind> = method
+            .params
+            .iter()
+            .map(|p| match p.ty.as_deref() {
+                Some("boolean") => ParamKind::Bool { default: false, on: "true", off: "false" },
+                _ => ParamKind::Text,
+            })
+            .collect();
+        let spec = MethodSpec::owned(method.name, params, help, kinds);
+        methods.push(PluginMethod {
+            spec,
+            plugin: file.name.clone(),
+            template: method.template,
+            extract: method.extract,
+            summary: method.summary,
+        });
+    }
+    Ok(Plugin { name: file.name, file: String::new(), methods })
+}
+
+/// "batch number, hex (quantity, e.g. 0x2a)"
+fn param_help(param: &ParamEntry) -> String {
+    let detail = match (&param.ty, &param.example) {
+        (Some(ty), Some(example)) => format!("{}, e.g. {}", ty, example),
+        (Some(ty), None) => ty.clone(),
+        (None, Some(example)) => format!("e.g. {}", example),
+        (None, None) => return param.help.clone(),
+    };
+    if param.help.is_empty() { detail } else { format!("{} ({})", param.help, detail) }
+}
+
+/// Names of the `{param}` placeholders in `template`'s strings.
+fn placeholders(template: &Value, names: &mut Vec<String>) {
+    match template {
+        Value::String(s) => {
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find('{') {
+                let Some(len) = rest[start..].find('}') else { break };
+                names.push(rest[start + 1..start + len].to_string());
+                rest = &rest[start + len + 1..];
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| placeholders(item, names)),
+        Value::Object(fields) => fields.values().for_each(|field| placeholders(field, names)),
+        _ => {}
+    }
+}
+
+/// `template` with its placeholders filled from `values` (param name,
+/// value). A string that is just `"{param}"` becomes the value itself;
+/// placeholders inside longer strings get its text.
+pub fn fill(template: &Value, values: &[(&str, Value)]) -> Value {
+    match template {
+        Value::String(s) => {
+            let whole = s.strip_prefix('{').and_then(|s| s.strip_suffix('}'));
+            if let Some((_, value)) = whole.and_then(|name| values.iter().find(|(n, _)| *n == name)) {
+                return value.clone();
+            }
+            let mut text = s.clone();
+            for (name, value) in values {
+                let shown = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                text = text.replace(&format!("{{{}}}", name), &shown);
+            }
+            Value::String(text)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| fill(item, values)).collect()),
+        Value::Object(fields) => Value::Object(fields.iter().map(|(k, v)| (k.clone(), fill(v, values))).collect()),
+        other => other.clone(),
+    }
+}
+
+/// The value at `pointer` in `result` as text; strings without quotes.
+fn text_at(result: &Value, pointer: &str) -> Option<String> {
+    result.pointer(pointer).map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string()))
+}
+
+/// `format` with each `{/pointer}` replaced by that value of `result`,
+/// or `?` where the result has none.
+pub fn summarize(format: &str, result: &Value) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find("{/") {
+        let Some(len) = rest[start..].find('}') else { break };
+        out.push_str(&rest[..start]);
+        out.push_str(&text_at(result, &rest[start + 1..start + len]).unwrap_or_else(|| "?".to_string()));
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Shows a plugin method's summary and extracted values above its JSON.
+pub struct PluginDecoder {
+    method: String,
+    extract: Vec<Extract>,
+    summary: Option<String>,
+}
+
+impl PluginDecoder {
+    /// The decoder for `method`, if the plugin gives it anything to show.
+    pub fn new(method: &PluginMethod) -> Option<Self> {
+        (method.summary.is_some() || !method.extract.is_empty()).then(|| PluginDecoder {
+            method: method.spec.name.to_string(),
+            extract: method.extract.clone(),
+            summary: method.summary.clone(),
+        })
+    }
+}
+
+impl ResultDecoder for PluginDecoder {
+    fn matches(&self, method: &str) -> bool {
+        method == self.method
+    }
+
+    fn decode(&self, result: &Value, _prefs: &DisplayPrefs) -> Option<DecodedView> {
+        let mut lines: Vec<String> = self.summary.iter().map(|format| summarize(format, result)).collect();
+        lines.extend(self.extract.iter().filter_map(|e| Some(format!("{}: {}", e.label, text_at(result, &e.pointer)?))));
+        (!lines.is_empty()).then_some(DecodedView::Summary(lines))
+    }
+}
+
+/// Loads every `*.json` plugin in `dir`, in file name order. A missing
+/// directory means no plugins. Files that don't parse, or that define a
+/// method an earlier file already did, are skipped and reported as
+/// "file: error".
+pub fn load_dir(dir: &Path) -> Result<(Vec<Plugin>, Vec<String>)> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), Vec::new())),
+        Err(e) => return Err(e).with_context(|| format!("cannot read {}", dir.display())),
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut plugins: Vec<Plugin> = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        let file = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let plugin = std::fs::read_to_string(&path).map_err(anyhow::Error::from).and_then(|text| parse(&text));
+        let plugin = plugin.and_then(|plugin| {
+            for method in &plugin.methods {
+                if let Some(earlier) = plugins.iter().find(|p| p.methods.iter().any(|m| m.spec.name == method.spec.name)) {
+                    bail!("{} is already defined by {}", method.spec.name, earlier.file);
+                }
+            }
+            Ok(plugin)
+        });
+        match plugin {
+            Ok(plugin) => plugins.push(Plugin { file, ..plugin }),
+            Err(e) => errors.push(format!("{}: {}", file, e)),
+        }
+    }
+    Ok((plugins, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const EXAMPLE: &str = include_str!("../tests/fixtures/plugins/myrollup.json");
+
+    #[test]
+    fn example_plugin_defines_its_methods() {
+        let plugin = parse(EXAMPLE).unwrap();
+        assert_eq!(plugin.name, "myrollup");
+        let batch = &plugin.methods[0];
+        assert_eq!(batch.spec.name, "myrollup_getBatch");
+        assert_eq!(batch.spec.params, ["batch", "withTransactions"]);
+        assert_eq!(batch.spec.help(0), Some("batch number, hex (quantity, e.g. 0x2a)"));
+        assert!(matches!(batch.spec.kind(1), ParamKind::Bool { .. }));
+
+        let params = fill(batch.template.as_ref().unwrap(), &[("batch", json!("0x2a")), ("withTransactions", json!(true))]);
+        assert_eq!(params, json!([{"number": "0x2a", "full": true}]));
+        assert_eq!(fill(&json!(["batch-{batch}"]), &[("batch", json!(7))]), json!(["batch-7"]));
+
+        let result = json!({"number": "0x2a", "transactionCount": 12, "l1BlockNumber": "0x1234", "stateRoot": "0xab"});
+        let decoder = PluginDecoder::new(batch).unwrap();
+        let Some(DecodedView::Summary(lines)) = decoder.decode(&result, &DisplayPrefs::default()) else { panic!("no summary") };
+        assert_eq!(lines, ["batch 0x2a · 12 txs · posted in L1 block 0x1234", "batch: 0x2a", "l1 block: 0x1234", "state root: 0xab"]);
+        assert_eq!(summarize("{/mode} at {/missing}", &json!({"mode": "active"})), "active at ?");
+    }
+
+    #[test]
+    fn malformed_plugins_say_why() {
+        assert!(parse("{").is_err());
+        let twice = r#"{"name": "x", "methods": [{"name": "a_b"}, {"name": "a_b"}]}"#;
+        assert_eq!(parse(twice).unwrap_err().to_string(), "a_b is defined twice");
+        let unknown = r#"{"name": "x", "methods": [{"name": "a_b", "template": ["{nope}"]}]}"#;
+        assert!(parse(unknown).unwrap_err().to_string().contains("{nope}"));
+        let pointer = r#"{"name": "x", "methods": [{"name": "a_b", "extract": [{"label": "n", "pointer": "number"}]}]}"#;
+        assert!(parse(pointer).unwrap_err().to_string().contains("must start with /"));
+    }
+
+    #[test]
+    fn later_plugins_cannot_redefine_a_method() {
+        let dir = std::env::temp_dir().join(format!("eli-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a-rollup.json"), EXAMPLE).unwrap();
+        std::fs::write(dir.join("b-other.json"), r#"{"name": "other", "methods": [{"name": "sequencer_status"}]}"#).unwrap();
+        std::fs::write(dir.join("c-broken.json"), "{").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a plugin").unwrap();
+
+        let (plugins, errors) = load_dir(&dir).unwrap();
+        assert_eq!(plugins.iter().map(|p| p.file.as_str()).collect::<Vec<_>>(), ["a-rollup.json"]);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0], "b-other.json: sequencer_status is already defined by a-rollup.json");
+        assert!(errors[1].starts_with("c-broken.json: "));
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert!(load_dir(Path::new("/nonexistent/eli/plugins")).unwrap().0.is_empty());
+    }
+}
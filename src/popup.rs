@@ -0,0 +1,195 @@
+// src/popup.rs
+
+use ratatui::layout::Rect;
+use unicode_width::UnicodeWidthChar;
+
+use crate::editor::{display_column, expand_tabs, TextArea};
+
+/// What an [`EditorPopup`] writes back to when saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupTarget {
+    /// A single param field, by index into `App::param_inputs`.
+    Param(usize),
+    /// A row of the `eth_sendTransaction` form, by index into `TxForm::fields`.
+    TxField(usize),
+}
+
+/// A centered multi-line editor for one value, with Save/Cancel.
+///
+/// Editing keys are the same as raw-params mode. Unlike the raw editor the
+/// popup also scrolls horizontally, so long unbroken values such as
+/// calldata stay editable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorPopup {
+    /// Shown in the border, e.g. "Param 1".
+    pub title: String,
+    pub area: TextArea,
+    /// First visible display column.
+    pub hscroll: usize,
+    pub target: PopupTarget,
+}
+
+impl EditorPopup {
+    pub fn new(title: impl Into<String>, text: &str, target: PopupTarget) -> Self {
+        EditorPopup { title: title.into(), area: TextArea::new(text), hscroll: 0, target }
+    }
+
+    /// The popup's rectangle: 80% × 60% of `screen`, centered, at least 20 × 5.
+    pub fn layout(screen: Rect) -> Rect {
+        let width = (screen.width * 4 / 5).max(20).min(screen.width);
+        let height = (screen.height * 3 / 5).max(5).min(screen.height);
+        Rect::new(
+            screen.x + (screen.width - width) / 2,
+            screen.y + (screen.height - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    /// Scrolls both ways so the cursor is inside a `width` × `height` viewport.
+    pub fn fit(&mut self, width: usize, height: usize) {
+        self.area.ensure_visible(height);
+        if width == 0 {
+            return;
+        }
+        let (row, col) = self.area.cursor;
+        let x = display_column(&self.area.lines[row], col);
+        if x < self.hscroll {
+            self.hscroll = x;
+        } else if x >= self.hscroll + width {
+            self.hscroll = x + 1 - width;
+        }
+    }
+
+    /// Cursor position inside the viewport, after [`EditorPopup::fit`].
+    pub fn cursor_in_viewport(&self, height: usize) -> Option<(u16, u16)> {
+        let (x, y) = self.area.to_viewport(self.area.cursor, height)?;
+        Some(((x as usize).checked_sub(self.hscroll)? as u16, y))
+    }
+
+    /// The visible slice of each row in a `width` × `height` viewport.
+    pub fn visible_lines(&self, width: usize, height: usize) -> Vec<String> {
+        self.area
+            .lines
+            .iter()
+            .skip(self.area.scroll)
+            .take(height)
+            .map(|line| clip_columns(&expand_tabs(line), self.hscroll, width))
+            .collect()
+    }
+
+    /// The edited value, collapsed to a single line.
+    pub fn value(&self) -> String {
+        collapse(&self.area.text())
+    }
+}
+
+/// Display columns `[start, start + width)` of an already tab-expanded line.
+/// A wide character straddling either edge is dropped.
+fn clip_columns(line: &str, start: usize, width: usize) -> String {
+    let mut out = String::new();
+    let mut col = 0;
+    for c in line.chars() {
+        let w = c.width().unwrap_or(0);
+        if col >= start && col + w <= start + width {
+            out.push(c);
+        }
+        col += w;
+        if col >= start + width {
+            break;
+        }
+    }
+    out
+}
+
+/// Joins a multi-line value back into one logical line: each line is
+/// trimmed and blank lines dropped. Hex data wrapped across lines is joined
+/// without separators; anything else (e.g. pretty-printed JSON) with spaces.
+pub fn collapse(text: &str) -> String {
+    let parts: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    let separator = if parts.first().is_some_and(|p| p.starts_with("0x")) { "" } else { " " };
+    parts.join(separator)
+}
+
+/// Human-readable size of `bytes`: "512 B", "1.2 KiB", "3.4 MiB".
+pub fn format_size(bytes: usize) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KiB", b as f64 / 1024.0),
+        b => format!("{:.1} MiB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+/// `value` cut to fit `width` columns, ending in "…(1.2 KiB)" when shortened.
+pub fn preview(value: &str, width: usize) -> String {
+    if value.chars().count() <= width {
+        return value.to_string();
+    }
+    let suffix = format!("…({})", format_size(value.len()));
+    let keep = width.saturating_sub(suffix.chars().count());
+    value.chars().take(keep).chain(suffix.chars()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tall(rows: usize) -> String {
+        (0..rows).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn scrolls_to_keep_cursor_in_tall_content() {
+        let mut popup = EditorPopup::new("Param 1", &tall(30), PopupTarget::Param(0));
+        // Cursor starts at the end, below a 10-row viewport
+        popup.fit(40, 10);
+        assert_eq!(popup.area.scroll, 20);
+        assert_eq!(popup.cursor_in_viewport(10), Some((7, 9)));
+        assert_eq!(popup.visible_lines(40, 10).first().map(String::as_str), Some("line 20"));
+
+        // Moving to the top scrolls back up
+        for _ in 0..29 {
+            popup.area.move_up();
+        }
+        popup.fit(40, 10);
+        assert_eq!(popup.area.scroll, 0);
+        assert_eq!(popup.cursor_in_viewport(10), Some((6, 0)));
+    }
+
+    #[test]
+    fn scrolls_horizontally_for_long_lines() {
+        let calldata = format!("0x{}", "ab".repeat(100));
+        let mut popup = EditorPopup::new("Param 1", &calldata, PopupTarget::Param(0));
+        popup.fit(20, 3);
+        assert_eq!(popup.hscroll, 202 - 19);
+        assert_eq!(popup.cursor_in_viewport(3), Some((19, 0)));
+        assert_eq!(popup.visible_lines(20, 3)[0].len(), 19);
+
+        popup.area.move_home();
+        popup.fit(20, 3);
+        assert_eq!(popup.hscroll, 0);
+        assert_eq!(popup.visible_lines(20, 3)[0], &calldata[..20]);
+    }
+
+    #[test]
+    fn layout_is_centered_and_bounded() {
+        assert_eq!(EditorPopup::layout(Rect::new(0, 0, 100, 40)), Rect::new(10, 8, 80, 24));
+        assert_eq!(EditorPopup::layout(Rect::new(0, 0, 10, 4)), Rect::new(0, 0, 10, 4));
+    }
+
+    #[test]
+    fn collapse_joins_wrapped_hex_and_json() {
+        assert_eq!(collapse("0xabcd\n  ef01\n"), "0xabcdef01");
+        assert_eq!(collapse("{\n  \"a\": 1,\n  \"b\": [2]\n}"), r#"{ "a": 1, "b": [2] }"#);
+    }
+
+    #[test]
+    fn preview_truncates_with_size() {
+        assert_eq!(preview("0x1234", 10), "0x1234");
+        let long = format!("0x{}", "00".repeat(600));
+        let shown = preview(&long, 24);
+        assert_eq!(shown.chars().count(), 24);
+        assert!(shown.ends_with("…(1.2 KiB)"), "{}", shown);
+        assert_eq!(format_size(512), "512 B");
+    }
+}
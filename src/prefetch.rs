@@ -0,0 +1,181 @@
+// src/prefetch.rs
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tokio::task::JoinHandle;
+
+use crate::cache::{is_immutable, ResponseCache};
+use crate::rpc::{JsonRpcRequest, Transport};
+
+/// Most prefetch requests allowed in flight at once.
+pub const MAX_OUTSTANDING: usize = 2;
+
+/// Background fetcher that warms the response cache.
+///
+/// Prefetched responses only ever land in the cache: they are not recorded
+/// in history, and the transport handed in should not carry the latency
+/// middleware so they don't skew latency stats.
+#[derive(Debug, Default)]
+pub struct Prefetcher {
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Prefetcher {
+    /// Number of prefetches still running.
+    pub fn outstanding(&mut self) -> usize {
+        self.handles.retain(|h| !h.is_finished());
+        self.handles.len()
+    }
+
+    /// Starts fetching `requests` in the background, skipping mutable
+    /// requests and ones already cached, and stopping at [`MAX_OUTSTANDING`].
+    ///
+    /// Returns how many were started.
+    pub fn schedule(
+        &mut self,
+        transport: Arc<dyn Transport>,
+        cache: Arc<Mutex<ResponseCache>>,
+        url: &str,
+        requests: Vec<JsonRpcRequest>,
+    ) -> usize {
+        let mut started = 0;
+        for req in requests {
+            if self.outstanding() >= MAX_OUTSTANDING {
+                break;
+            }
+            if !is_immutable(&req.method, &req.params) || cache.lock().unwrap().contains(url, &req) {
+                continue;
+            }
+            let (transport, cache, url) = (transport.clone(), cache.clone(), url.to_string());
+            self.handles.push(tokio::spawn(async move {
+                if let Ok(res) = transport.send(&url, req.clone()).await {
+                    cache.lock().unwrap().insert(&url, &req, &res);
+                }
+            }));
+            started += 1;
+        }
+        started
+    }
+
+    /// Aborts every outstanding prefetch.
+    pub fn cancel(&mut self) {
+        for handle in self.handles.drain(..) {
+            handle.abort();
+        }
+    }
+}
+
+/// Requests for the blocks after and before the one `req` fetched by
+/// number, keeping the remaining params (e.g. the full-transactions flag).
+pub fn neighbor_blocks(req: &JsonRpcRequest) -> Vec<JsonRpcRequest> {
+    if req.method != "eth_getBlockByNumber" || !is_immutable(&req.method, &req.params) {
+        return Vec::new();
+    }
+    let Value::Array(params) = &req.params else { return Vec::new() };
+    let Some(n) = params[0].as_str().and_then(|b| u64::from_str_radix(&b[2..], 16).ok()) else {
+        return Vec::new();
+    };
+    [n.checked_add(1), n.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .map(|m| {
+            let mut next = params.clone();
+            next[0] = Value::String(format!("0x{:x}", m));
+            JsonRpcRequest::new(req.method.clone(), Value::Array(next), req.id)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::JsonRpcResponse;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Semaphore;
+
+    /// Transport that blocks every call until a permit is released and counts calls.
+    struct GatedTransport {
+        calls: AtomicUsize,
+        gate: Semaphore,
+    }
+
+    impl Default for GatedTransport {
+        fn default() -> Self {
+            GatedTransport { calls: AtomicUsize::new(0), gate: Semaphore::new(0) }
+        }
+    }
+
+    #[async_trait]
+    impl Transport for GatedTransport {
+        async fn send(&self, _url: &str, req: JsonRpcRequest) -> anyhow::Result<JsonRpcResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.gate.acquire().await?.forget();
+            Ok(JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(req.params[0].clone()), error: None, id: req.id })
+        }
+    }
+
+    fn block(n: u64) -> JsonRpcRequest {
+        JsonRpcRequest::new("eth_getBlockByNumber", json!([format!("0x{:x}", n), false]), 1)
+    }
+
+    #[tokio::test]
+    async fn never_exceeds_two_outstanding() {
+        let transport = Arc::new(GatedTransport::default());
+        let cache = Arc::new(Mutex::new(ResponseCache::default()));
+        let mut prefetcher = Prefetcher::default();
+
+        let started = prefetcher.schedule(transport.clone(), cache.clone(), "u", vec![block(1), block(2), block(3)]);
+        assert_eq!(started, 2);
+        assert_eq!(prefetcher.schedule(transport.clone(), cache.clone(), "u", vec![block(4)]), 0);
+        assert_eq!(prefetcher.outstanding(), 2);
+
+        // Releasing both lets their results reach the cache
+        transport.gate.add_permits(2);
+        while prefetcher.outstanding() > 0 {
+            tokio::task::yield_now().await;
+        }
+        assert!(cache.lock().unwrap().contains("u", &block(1)));
+        assert!(cache.lock().unwrap().contains("u", &block(2)));
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn cancel_drops_in_flight_results() {
+        let transport = Arc::new(GatedTransport::default());
+        let cache = Arc::new(Mutex::new(ResponseCache::default()));
+        let mut prefetcher = Prefetcher::default();
+
+        prefetcher.schedule(transport.clone(), cache.clone(), "u", vec![block(1)]);
+        tokio::task::yield_now().await;
+        prefetcher.cancel();
+        transport.gate.add_permits(1);
+        tokio::task::yield_now().await;
+        assert_eq!(prefetcher.outstanding(), 0);
+        assert!(cache.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn skips_tagged_and_cached_requests() {
+        let transport = Arc::new(GatedTransport::default());
+        let cache = Arc::new(Mutex::new(ResponseCache::default()));
+        let res = JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!({})), error: None, id: 1 };
+        cache.lock().unwrap().insert("u", &block(5), &res);
+
+        let latest = JsonRpcRequest::new("eth_getBlockByNumber", json!(["latest", false]), 1);
+        let mut prefetcher = Prefetcher::default();
+        assert_eq!(prefetcher.schedule(transport.clone(), cache, "u", vec![latest, block(5)]), 0);
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn neighbors_keep_trailing_params() {
+        let next: Vec<_> = neighbor_blocks(&block(16)).into_iter().map(|r| r.params).collect();
+        assert_eq!(next, vec![json!(["0x11", false]), json!(["0xf", false])]);
+        assert_eq!(neighbor_blocks(&block(0)).len(), 1);
+        let latest = JsonRpcRequest::new("eth_getBlockByNumber", json!(["latest", true]), 1);
+        assert!(neighbor_blocks(&latest).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+// src/prefs.rs
+
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::chains::NativeCurrency;
+use crate::rpc::JsonRpcResponse;
+
+/// Unit used for wei-denominated amounts (balances, gas prices).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Units {
+    Wei,
+    Gwei,
+    #[default]
+    Ether,
+}
+
+impl Units {
+    fn next(self) -> Self {
+        match self {
+            Units::Wei => Units::Gwei,
+            Units::Gwei => Units::Ether,
+            Units::Ether => Units::Wei,
+        }
+    }
+}
+
+/// Timezone block timestamps are shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimeZone {
+    #[default]
+    Utc,
+    Local,
+}
+
+/// Address truncation steps offered in the settings popup (0 = full address).
+const TRUNCATION_STEPS: &[usize] = &[0, 4, 6, 8];
+
+/// How humanized values are rendered. Every formatter in the UI takes these,
+/// so changing a preference changes all of them at once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayPrefs {
+    /// Unit for wei amounts.
+    pub units: Units,
+    /// Group integer digits with thousands separators.
+    pub separators: bool,
+    /// Timezone for block timestamps.
+    pub timezone: TimeZone,
+    /// Append the original hex after humanized numbers.
+    pub hex_alongside: bool,
+    /// Hex digits kept on each side of a shortened address; 0 shows it in full.
+    pub address_truncation: usize,
+    /// Write hex quantities as decimal in CSV exports.
+    pub csv_decimal: bool,
+    /// Order flat trace tables by gas used, most first, instead of by
+    /// position in the call tree.
+    pub sort_traces_by_gas: bool,
+}
+
+impl Default for DisplayPrefs {
+    fn default() -> Self {
+        DisplayPrefs {
+            units: Units::Ether,
+            separators: true,
+            timezone: TimeZone::Utc,
+            hex_alongside: false,
+            address_truncation: 0,
+            csv_decimal: true,
+            sort_traces_by_gas: false,
+        }
+    }
+}
+
+/// Number of rows in the settings popup.
+pub const SETTING_COUNT: usize = 7;
+
+fn parse_hex(hex: &str) -> Option<u128> {
+    u128::from_str_radix(hex.strip_prefix("0x")?, 16).ok()
+}
+
+impl DisplayPrefs {
+    /// Label and current value of settings row `index`.
+    pub fn setting(&self, index: usize) -> (&'static str, String) {
+        let on_off = |b: bool| if b { "on" } else { "off" }.to_string();
+        match index {
+            0 => ("Units", format!("{:?}", self.units).to_lowercase()),
+            1 => ("Thousands separators", on_off(self.separators)),
+            2 => ("Timestamps", format!("{:?}", self.timezone).to_uppercase()),
+            3 => ("Hex alongside", on_off(self.hex_alongside)),
+            4 => (
+                "Address truncation",
+                match self.address_truncation {
+                    0 => "off".to_string(),
+                    n => format!("{} chars", n),
+                },
+            ),
+            5 => ("CSV quantities", if self.csv_decimal { "decimal" } else { "hex" }.to_string()),
+            _ => ("Trace order", if self.sort_traces_by_gas { "gas used" } else { "call tree" }.to_string()),
+        }
+    }
+
+    /// Advances settings row `index` to its next value.
+    pub fn cycle(&mut self, index: usize) {
+        match index {
+            0 => self.units = self.units.next(),
+            1 => self.separators = !self.separators,
+            2 => {
+                self.timezone = match self.timezone {
+                    TimeZone::Utc => TimeZone::Local,
+                    TimeZone::Local => TimeZone::Utc,
+                }
+            }
+            3 => self.hex_alongside = !self.hex_alongside,
+            4 => {
+                let pos = TRUNCATION_STEPS.iter().position(|n| *n == self.address_truncation).unwrap_or(0);
+                self.address_truncation = TRUNCATION_STEPS[(pos + 1) % TRUNCATION_STEPS.len()];
+            }
+            5 => self.csv_decimal = !self.csv_decimal,
+            _ => self.sort_traces_by_gas = !self.sort_traces_by_gas,
+        }
+    }
+
+    /// An integer with separators applied.
+    pub fn integer(&self, n: u128) -> String {
+        let digits = n.to_string();
+        if !self.separators {
+            return digits;
+        }
+        let mut out = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(',');
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    fn with_hex(&self, text: String, hex: &str) -> String {
+        if self.hex_alongside { format!("{} ({})", text, hex) } else { text }
+    }
+
+    /// A hex quantity (block number, gas, nonce) as a decimal integer.
+    pub fn quantity(&self, hex: &str) -> String {
+        match parse_hex(hex) {
+            Some(n) => self.with_hex(self.integer(n), hex),
+            None => hex.to_string(),
+        }
+    }
+
+    /// A hex wei amount in the configured unit; whole units use the chain's
+    /// native currency symbol and decimals.
+    pub fn wei(&self, hex: &str, currency: &NativeCurrency) -> String {
+        let Some(wei) = parse_hex(hex) else { return hex.to_string() };
+        let (decimals, unit) = match self.units {
+            Units::Wei => (0, "wei"),
+            Units::Gwei => (9, "gwei"),
+            Units::Ether => (currency.decimals.min(38), currency.symbol.as_str()),
+        };
+        let scale = 10u128.pow(decimals);
+        let whole = self.integer(wei / scale);
+        let frac = format!("{:0width$}", wei % scale, width = decimals as usize);
+        let frac = frac.trim_end_matches('0');
+        let text = if frac.is_empty() { format!("{} {}", whole, unit) } else { format!("{}.{} {}", whole, frac, unit) };
+        self.with_hex(text, hex)
+    }
+
+    /// A hex unix timestamp as a date in the configured timezone.
+    pub fn timestamp(&self, hex: &str) -> String {
+        let Some(time) = parse_hex(hex).and_then(|s| DateTime::<Utc>::from_timestamp(s as i64, 0)) else {
+            return hex.to_string();
+        };
+        let text = match self.timezone {
+            TimeZone::Utc => time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            TimeZone::Local => time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S %:z").to_string(),
+        };
+        self.with_hex(text, hex)
+    }
+
+    /// The time of day of unix time `secs`, e.g. "12:03:41", in the
+    /// configured timezone.
+    pub fn time_of_day(&self, secs: u64) -> String {
+        let Some(time) = DateTime::<Utc>::from_timestamp(secs as i64, 0) else { return secs.to_string() };
+        match self.timezone {
+            TimeZone::Utc => time.format("%H:%M:%S").to_string(),
+            TimeZone::Local => time.with_timezone(&Local).format("%H:%M:%S").to_string(),
+        }
+    }
+
+    /// An address, shortened to `0x1234…abcd` when truncation is on.
+    pub fn address(&self, address: &str) -> String {
+        let n = self.address_truncation;
+        match address.strip_prefix("0x") {
+            Some(hex) if n > 0 && hex.len() > 2 * n && hex.is_ascii() => {
+                format!("0x{}…{}", &hex[..n], &hex[hex.len() - n..])
+            }
+            _ => address.to_string(),
+        }
+    }
+
+    /// A measured round-trip time.
+    pub fn latency(&self, latency: Duration) -> String {
+        format!("{} ms", self.integer(latency.as_millis()))
+    }
+
+    /// One-line summary of a response for list views.
+    pub fn summarize(&self, method: &str, response: &JsonRpcResponse, currency: &NativeCurrency) -> String {
+        if let Some(error) = response.error_summary() {
+            return format!("error {}", error);
+        }
+        let str_field = |v: &Value, name: &str| v.get(name).and_then(Value::as_str).map(str::to_string);
+        match (method, response.result.as_ref()) {
+            (_, None | Some(Value::Null)) => "null".to_string(),
+            ("eth_getBalance" | "eth_gasPrice" | "eth_maxPriorityFeePerGas", Some(Value::String(hex))) => {
+                self.wei(hex, currency)
+            }
+            (_, Some(Value::String(s))) if s.len() == 42 && s.starts_with("0x") => self.address(s),
+            (_, Some(Value::String(s))) if s.starts_with("0x") && s.len() <= 34 => self.quantity(s),
+            (_, Some(block)) if block.get("timestamp").is_some() && block.get("number").is_some() => format!(
+                "block {} @ {}",
+                self.quantity(&str_field(block, "number").unwrap_or_default()),
+                self.timestamp(&str_field(block, "timestamp").unwrap_or_default())
+            ),
+            (_, Some(tx)) if tx.get("from").is_some() && tx.get("nonce").is_some() => format!(
+                "tx from {} to {}",
+                self.address(&str_field(tx, "from").unwrap_or_default()),
+                str_field(tx, "to").map(|to| self.address(&to)).unwrap_or_else(|| "contract creation".into())
+            ),
+            (_, Some(other)) => other.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wei_in_each_unit() {
+        let mut prefs = DisplayPrefs::default();
+        let eth = NativeCurrency::default();
+        assert_eq!(prefs.wei("0x1bc16d674ec80000", &eth), "2 ETH");
+        assert_eq!(prefs.wei("0x14d1120d7b160000", &eth), "1.5 ETH");
+        prefs.units = Units::Gwei;
+        assert_eq!(prefs.wei("0x4a817c800", &eth), "20 gwei");
+        prefs.units = Units::Wei;
+        prefs.hex_alongside = true;
+        assert_eq!(prefs.wei("0x4a817c800", &eth), "20,000,000,000 wei (0x4a817c800)");
+    }
+
+    #[test]
+    fn whole_units_follow_native_currency() {
+        let prefs = DisplayPrefs::default();
+        let six = NativeCurrency { name: "Test".into(), symbol: "TST".into(), decimals: 6 };
+        assert_eq!(prefs.wei("0x16e360", &six), "1.5 TST");
+    }
+
+    #[test]
+    fn separators_and_truncation() {
+        let mut prefs = DisplayPrefs::default();
+        assert_eq!(prefs.quantity("0x12d687"), "1,234,567");
+        prefs.separators = false;
+        assert_eq!(prefs.quantity("0x12d687"), "1234567");
+        let addr = "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f";
+        assert_eq!(prefs.address(addr), addr);
+        prefs.cycle(4);
+        assert_eq!(prefs.address(addr), "0x9d8a…5a4f");
+    }
+
+    #[test]
+    fn timestamps_in_utc() {
+        let prefs = DisplayPrefs::default();
+        assert_eq!(prefs.timestamp("0x0"), "1970-01-01 00:00:00 UTC");
+        assert_eq!(prefs.timestamp("not hex"), "not hex");
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let prefs: DisplayPrefs = serde_json::from_str(r#"{"units": "Gwei"}"#).unwrap();
+        assert_eq!(prefs, DisplayPrefs { units: Units::Gwei, ..DisplayPrefs::default() });
+    }
+}
@@ -0,0 +1,232 @@
+// src/pretty.rs
+
+use std::ops::ControlFlow;
+use std::sync::mpsc::{self, Receiver};
+
+use serde_json::Value;
+
+use crate::rpc::JsonRpcResponse;
+
+/// Responses with more values than this are formatted in the background.
+pub const INLINE_LIMIT: usize = 20_000;
+
+/// Lines handed over at a time.
+pub const BATCH: usize = 2_000;
+
+/// Number of values in `value`, itself included.
+pub fn count(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(count).sum::<usize>(),
+        Value::Object(fields) => 1 + fields.values().map(count).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+/// The fields of `response` in the order its `Serialize` writes them.
+pub fn response_fields(response: &JsonRpcResponse) -> Vec<(&'static str, Value)> {
+    vec![
+        ("jsonrpc", Value::String(response.jsonrpc.clone())),
+        ("result", response.result.clone().unwrap_or(Value::Null)),
+        ("error", response.error.clone().unwrap_or(Value::Null)),
+        ("id", Value::from(response.id)),
+    ]
+}
+
+/// Writes the object made of `fields`, in their order, line by line exactly
+/// as `serde_json::to_string_pretty` would, handing `emit` a batch of lines
+/// at a time with the share of values written so far. Stops as soon as
+/// `emit` returns false; returns whether it got to the end.
+pub fn write_lines(fields: &[(&str, Value)], batch: usize, emit: impl FnMut(Vec<String>, f64) -> bool) -> bool {
+    let total = 1 + fields.iter().map(|(_, v)| count(v)).sum::<usize>();
+    let mut writer = Writer { emit, pending: Vec::new(), batch: batch.max(1), done: 1, total };
+    let finished = writer.object(String::new(), "", fields.iter().map(|(k, v)| (*k, v)), fields.len(), "");
+    finished.is_continue() && writer.flush().is_continue()
+}
+
+/// All lines of `response` at once; what [`Formatting`] streams.
+pub fn response_lines(response: &JsonRpcResponse) -> Vec<String> {
+    let mut lines = Vec::new();
+    write_lines(&response_fields(response), usize::MAX, |batch, _| {
+        lines.extend(batch);
+        true
+    });
+    lines
+}
+
+struct Writer<F> {
+    emit: F,
+    pending: Vec<String>,
+    batch: usize,
+    done: usize,
+    total: usize,
+}
+
+impl<F: FnMut(Vec<String>, f64) -> bool> Writer<F> {
+    fn line(&mut self, line: String) -> ControlFlow<()> {
+        self.pending.push(line);
+        if self.pending.len() >= self.batch { self.flush() } else { ControlFlow::Continue(()) }
+    }
+
+    fn flush(&mut self) -> ControlFlow<()> {
+        let lines = std::mem::take(&mut self.pending);
+        if (self.emit)(lines, self.done as f64 / self.total as f64) { ControlFlow::Continue(()) } else { ControlFlow::Break(()) }
+    }
+
+    /// `value` at `indent`, after `prefix` (`"key": ` or nothing) and
+    /// followed by `suffix` (`,` or nothing).
+    fn value(&mut self, indent: &str, prefix: &str, value: &Value, suffix: &str) -> ControlFlow<()> {
+        self.done += 1;
+        match value {
+            Value::Array(items) if !items.is_empty() => {
+                self.line(format!("{}{}[", indent, prefix))?;
+                let inner = format!("{}  ", indent);
+                for (i, item) in items.iter().enumerate() {
+                    self.value(&inner, "", item, if i + 1 < items.len() { "," } else { "" })?;
+                }
+                self.line(format!("{}]{}", indent, suffix))
+            }
+            Value::Object(fields) if !fields.is_empty() => {
+                self.object(indent.to_string(), prefix, fields.iter().map(|(k, v)| (k.as_str(), v)), fields.len(), suffix)
+            }
+            scalar => self.line(format!("{}{}{}{}", indent, prefix, scalar, suffix)),
+        }
+    }
+
+    fn object<'v>(
+        &mut self,
+        indent: String,
+        prefix: &str,
+        fields: impl Iterator<Item = (&'v str, &'v Value)>,
+        len: usize,
+        suffix: &str,
+    ) -> ControlFlow<()> {
+        self.line(format!("{}{}{{", indent, prefix))?;
+        let inner = format!("{}  ", indent);
+        for (i, (key, field)) in fields.enumerate() {
+            let key = format!("{}: ", Value::from(key));
+            self.value(&inner, &key, field, if i + 1 < len { "," } else { "" })?;
+        }
+        self.line(format!("{}}}{}", indent, suffix))
+    }
+}
+
+/// A response being formatted on a background thread. Dropping it stops
+/// the thread at its next batch.
+#[derive(Debug)]
+pub struct Formatting {
+    batches: Receiver<(Vec<String>, f64)>,
+    /// Share of the response formatted so far, 0 to 1.
+    pub progress: f64,
+}
+
+impl Formatting {
+    pub fn start(response: &JsonRpcResponse) -> Self {
+        let fields = response_fields(response);
+        let (sender, batches) = mpsc::channel();
+        std::thread::spawn(move || write_lines(&fields, BATCH, |lines, progress| sender.send((lines, progress)).is_ok()));
+        Formatting { batches, progress: 0.0 }
+    }
+
+    /// Lines formatted since the last call, and whether that was all.
+    pub fn poll(&mut self) -> (Vec<String>, bool) {
+        let mut lines = Vec::new();
+        loop {
+            match self.batches.try_recv() {
+                Ok((batch, progress)) => {
+                    lines.extend(batch);
+                    self.progress = progress;
+                }
+                Err(mpsc::TryRecvError::Empty) => return (lines, false),
+                Err(mpsc::TryRecvError::Disconnected) => return (lines, true),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response(result: Value) -> JsonRpcResponse {
+        JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(result), error: None, id: 7 }
+    }
+
+    /// A nested value that varies with `seed`: every shape the pretty
+    /// printer has a case for, including empty containers and escapes.
+    fn nested(seed: u64, depth: u32) -> Value {
+        let pick = seed % 7;
+        if depth == 0 {
+            return match pick {
+                0 => json!(null),
+                1 => json!(seed.is_multiple_of(2)),
+                2 => json!(seed as f64 / 3.0),
+                3 => json!(-(seed as i64)),
+                4 => json!(format!("line\n\"{}\"\t\u{e9}", seed)),
+                5 => json!([]),
+                _ => json!({}),
+            };
+        }
+        let width = (seed % 4) as usize;
+        let next = |i: usize| seed.wrapping_mul(6364136223846793005).wrapping_add(i as u64 * 1442695040888963407) >> 7;
+        if pick.is_multiple_of(2) {
+            Value::Array((0..width).map(|i| nested(next(i), depth - 1)).collect())
+        } else {
+            Value::Object((0..width).map(|i| (format!("k{}\"{}", i, next(i) % 10), nested(next(i), depth - 1))).collect())
+        }
+    }
+
+    #[test]
+    fn chunked_output_matches_one_shot() {
+        for seed in 0..300 {
+            let response = response(nested(seed, (seed % 6) as u32));
+            let expected: Vec<String> = serde_json::to_string_pretty(&response).unwrap().lines().map(String::from).collect();
+            for batch in [1, 3, 64] {
+                let mut lines = Vec::new();
+                assert!(write_lines(&response_fields(&response), batch, |chunk, _| {
+                    assert!(chunk.len() <= batch);
+                    lines.extend(chunk);
+                    true
+                }));
+                assert_eq!(lines, expected, "seed {} batch {}", seed, batch);
+            }
+        }
+        let error = JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: Some(json!({"code": -1})), id: 1 };
+        assert_eq!(response_lines(&error).join("\n"), serde_json::to_string_pretty(&error).unwrap());
+    }
+
+    #[test]
+    fn progress_rises_and_emit_can_stop_it() {
+        let big = response(Value::Array((0..1000).map(|i| json!({"i": i})).collect()));
+        let mut seen = Vec::new();
+        write_lines(&response_fields(&big), 100, |_, progress| {
+            seen.push(progress);
+            true
+        });
+        assert!(seen.windows(2).all(|w| w[0] <= w[1]) && *seen.last().unwrap() == 1.0, "{:?}", seen);
+
+        let mut calls = 0;
+        assert!(!write_lines(&response_fields(&big), 100, |_, _| {
+            calls += 1;
+            calls < 3
+        }));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn background_formatting_streams_then_finishes() {
+        let big = response(Value::Array((0..50_000).map(|i| json!([i, "x"])).collect()));
+        let mut formatting = Formatting::start(&big);
+        let mut lines = Vec::new();
+        loop {
+            let (batch, done) = formatting.poll();
+            lines.extend(batch);
+            if done {
+                break;
+            }
+            std::thread::yield_now();
+        }
+        assert_eq!(lines, response_lines(&big));
+        assert_eq!(formatting.progress, 1.0);
+    }
+}
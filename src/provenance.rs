@@ -0,0 +1,89 @@
+// src/provenance.rs
+
+use serde::{Deserialize, Serialize};
+
+/// One transformation applied to a param on its way into the request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    /// What did it, e.g. "config default" or "unit conversion".
+    pub transform: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// What happened to one param between the field and the request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParamTrail {
+    /// Spec name of the param or form field.
+    pub param: String,
+    /// The field's text as the user left it.
+    pub typed: String,
+    pub changes: Vec<Change>,
+}
+
+/// Provenance of a request's params; only params that were changed appear.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Trail(pub Vec<ParamTrail>);
+
+impl Trail {
+    /// Notes that `transform` turned `param` into `after`. The first change
+    /// of a param starts from `typed`, later ones from the previous result.
+    pub fn record(&mut self, param: &str, typed: &str, transform: &str, after: &str) {
+        let index = match self.0.iter().position(|t| t.param == param) {
+            Some(index) => index,
+            None => {
+                self.0.push(ParamTrail { param: param.to_string(), typed: typed.to_string(), changes: Vec::new() });
+                self.0.len() - 1
+            }
+        };
+        let trail = &mut self.0[index];
+        let before = trail.changes.last().map(|c| c.after.clone()).unwrap_or_else(|| trail.typed.clone());
+        trail.changes.push(Change { transform: transform.to_string(), before, after: after.to_string() });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// One line per param and per change, for the provenance tab.
+    pub fn lines(&self) -> Vec<String> {
+        if self.0.is_empty() {
+            return vec!["No transformations: every param was sent as typed.".to_string()];
+        }
+        let mut lines = Vec::new();
+        for trail in &self.0 {
+            lines.push(format!("{}: typed {:?}", trail.param, trail.typed));
+            for change in &trail.changes {
+                lines.push(format!("  {}: {:?} → {:?}", change.transform, change.before, change.after));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_changes_per_param() {
+        let mut trail = Trail::default();
+        assert_eq!(trail.lines(), ["No transformations: every param was sent as typed."]);
+        trail.record("value", "0.5 ether", "unit conversion", "0x6f05b59d3b20000");
+        trail.record("block", "", "config default", "latest");
+        trail.record("value", "0.5 ether", "normalized", "0x6F05B59D3B20000");
+        assert_eq!(trail.0.len(), 2);
+        assert_eq!(trail.0[0].changes[1].before, "0x6f05b59d3b20000");
+        assert_eq!(
+            trail.lines(),
+            [
+                r#"value: typed "0.5 ether""#,
+                r#"  unit conversion: "0.5 ether" → "0x6f05b59d3b20000""#,
+                r#"  normalized: "0x6f05b59d3b20000" → "0x6F05B59D3B20000""#,
+                r#"block: typed """#,
+                r#"  config default: "" → "latest""#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,304 @@
+// src/provider.rs
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::rpc::JsonRpcResponse;
+
+/// Hosted RPC providers whose error envelopes get special handling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Provider {
+    Infura,
+    Alchemy,
+    QuickNode,
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Provider::Infura => "Infura",
+            Provider::Alchemy => "Alchemy",
+            Provider::QuickNode => "QuickNode",
+        })
+    }
+}
+
+/// (host suffix, provider); also used to sniff provider names in bodies.
+const HOSTS: &[(&str, Provider)] = &[
+    ("infura.io", Provider::Infura),
+    ("alchemy.com", Provider::Alchemy),
+    ("alchemyapi.io", Provider::Alchemy),
+    ("quiknode.pro", Provider::QuickNode),
+    ("quicknode.com", Provider::QuickNode),
+];
+
+/// The provider behind `url`, judged by its host.
+pub fn detect(url: &str) -> Option<Provider> {
+    let host = url.split("://").nth(1).unwrap_or(url).split(['/', ':', '?']).next().unwrap_or("");
+    HOSTS
+        .iter()
+        .find(|(suffix, _)| host == *suffix || host.ends_with(&format!(".{}", suffix)))
+        .map(|(_, provider)| *provider)
+}
+
+/// The provider named in a response body, for endpoints behind a custom
+/// domain or proxy.
+fn sniff(body: &str) -> Option<Provider> {
+    let body = body.to_ascii_lowercase();
+    [("infura", Provider::Infura), ("alchemy", Provider::Alchemy), ("quicknode", Provider::QuickNode)]
+        .iter()
+        .find(|(name, _)| body.contains(name))
+        .map(|(_, provider)| *provider)
+}
+
+/// What went wrong, independent of who reported it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Too many requests or quota exhausted; retrying later can succeed.
+    RateLimited,
+    /// The endpoint is down or in maintenance.
+    Unavailable,
+    /// The body isn't a JSON-RPC response at all.
+    Malformed,
+}
+
+/// A transport-level failure reported in a provider's own envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderError {
+    pub kind: ErrorKind,
+    pub provider: Option<Provider>,
+    pub status: u16,
+    /// The response's `Content-Type`, when it wasn't JSON.
+    pub content_type: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let what = match self.kind {
+            ErrorKind::RateLimited => "rate limited",
+            ErrorKind::Unavailable => "endpoint unavailable",
+            ErrorKind::Malformed => "not a JSON-RPC response",
+        };
+        write!(f, "{}", what)?;
+        if let Some(provider) = self.provider {
+            write!(f, " ({})", provider)?;
+        }
+        write!(f, ", HTTP {}", self.status)?;
+        if let Some(content_type) = &self.content_type {
+            write!(f, " ({})", content_type)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if self.kind == ErrorKind::RateLimited {
+            write!(f, " — wait before retrying")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+/// What a rule looks at in the body.
+#[derive(Debug, Clone, Copy)]
+enum Body {
+    Any,
+    Html,
+    /// A JSON-RPC error with this code.
+    Code(i64),
+}
+
+/// One row of the classification table; `None` matches anything.
+#[derive(Debug, Clone, Copy)]
+struct Rule {
+    provider: Option<Provider>,
+    status: Option<u16>,
+    body: Body,
+    kind: ErrorKind,
+}
+
+const fn rule(provider: Option<Provider>, status: Option<u16>, body: Body, kind: ErrorKind) -> Rule {
+    Rule { provider, status, body, kind }
+}
+
+/// Envelopes that are failures of the endpoint rather than answers to the
+/// request, first match wins.
+const RULES: &[Rule] = &[
+    // Alchemy: 429 with a JSON-RPC error body (code 429)
+    rule(Some(Provider::Alchemy), None, Body::Code(429), ErrorKind::RateLimited),
+    // Infura: 200 with -32005 and backoff details under error.data
+    rule(Some(Provider::Infura), None, Body::Code(-32005), ErrorKind::RateLimited),
+    // QuickNode: 200 with an HTML maintenance page
+    rule(Some(Provider::QuickNode), None, Body::Html, ErrorKind::Unavailable),
+    // Anyone
+    rule(None, Some(429), Body::Any, ErrorKind::RateLimited),
+    rule(None, Some(502), Body::Any, ErrorKind::Unavailable),
+    rule(None, Some(503), Body::Any, ErrorKind::Unavailable),
+    rule(None, Some(504), Body::Any, ErrorKind::Unavailable),
+    rule(None, None, Body::Html, ErrorKind::Unavailable),
+];
+
+fn is_html(body: &str) -> bool {
+    let start = body.trim_start().get(..15).unwrap_or(body.trim_start()).to_ascii_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html")
+}
+
+/// The start of a body that isn't JSON, on one line, for error messages.
+fn snippet(text: &str) -> String {
+    const LEN: usize = 120;
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.is_empty() {
+        return "empty body".to_string();
+    }
+    match line.char_indices().nth(LEN) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line,
+    }
+}
+
+/// Readable text of an HTML page: its title, else the first text.
+fn html_text(body: &str) -> String {
+    let lower = body.to_ascii_lowercase();
+    if let (Some(start), Some(end)) = (lower.find("<title>"), lower.find("</title>"))
+        && start + 7 <= end
+    {
+        return body[start + 7..end].trim().to_string();
+    }
+    body.split(['<', '>']).skip(1).step_by(2).map(str::trim).find(|t| !t.is_empty()).unwrap_or("").to_string()
+}
+
+/// Turns a raw HTTP response into a JSON-RPC response, or the failure its
+/// envelope describes. Bodies are parsed whatever `content_type` says, as
+/// some nodes label JSON as text; it's reported when the body isn't JSON.
+///
+/// JSON-RPC errors that carry provider details under `error.data` (Infura)
+/// are flattened so `message` says what happened and `data` holds the
+/// revert data, if any.
+pub fn normalize(
+    url: &str,
+    status: u16,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<JsonRpcResponse, ProviderError> {
+    let text = String::from_utf8_lossy(body);
+    let provider = detect(url).or_else(|| sniff(&text));
+    let parsed = serde_json::from_slice::<JsonRpcResponse>(body).ok();
+    let code = parsed.as_ref().and_then(|r| r.error.as_ref()).and_then(|e| e["code"].as_i64());
+    let matches = |rule: &&Rule| {
+        rule.provider.is_none_or(|p| Some(p) == provider)
+            && rule.status.is_none_or(|s| s == status)
+            && match rule.body {
+                Body::Any => true,
+                Body::Html => parsed.is_none() && is_html(&text),
+                Body::Code(c) => code == Some(c),
+            }
+    };
+    let message = || match parsed.as_ref().and_then(|r| r.error.as_ref()) {
+        Some(error) => error["message"].as_str().unwrap_or("").to_string(),
+        None if is_html(&text) => html_text(&text),
+        None => snippet(&text),
+    };
+    let content_type = content_type.filter(|t| parsed.is_none() && !t.contains("json")).map(str::to_string);
+    if let Some(rule) = RULES.iter().find(matches) {
+        return Err(ProviderError { kind: rule.kind, provider, status, content_type, message: message() });
+    }
+    match parsed {
+        Some(mut response) => {
+            if let Some(error) = &mut response.error {
+                flatten_error_data(error);
+            }
+            Ok(response)
+        }
+        None => Err(ProviderError { kind: ErrorKind::Malformed, provider, status, content_type, message: message() }),
+    }
+}
+
+/// `{"message": m, "data": {"message": detail, "data": d}}` becomes
+/// `{"message": detail, "data": d}` when the detail extends the message.
+fn flatten_error_data(error: &mut Value) {
+    let Some(detail) = error["data"]["message"].as_str().map(str::to_string) else { return };
+    let message = error["message"].as_str().unwrap_or("");
+    if !detail.starts_with(message) {
+        return;
+    }
+    let inner = error["data"].get("data").cloned();
+    error["message"] = json!(detail);
+    match inner {
+        Some(data) => error["data"] = data,
+        None => {
+            if let Some(fields) = error.as_object_mut() {
+                fields.remove("data");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALCHEMY_429: &str = include_str!("../tests/fixtures/providers/alchemy-429.json");
+    const INFURA_REVERT: &str = include_str!("../tests/fixtures/providers/infura-revert.json");
+    const INFURA_RATE_LIMIT: &str = include_str!("../tests/fixtures/providers/infura-rate-limit.json");
+    const QUICKNODE_MAINTENANCE: &str = include_str!("../tests/fixtures/providers/quicknode-maintenance.html");
+
+    const ALCHEMY: &str = "https://eth-mainnet.g.alchemy.com/v2/key";
+    const INFURA: &str = "https://mainnet.infura.io/v3/key";
+    const QUICKNODE: &str = "https://old-bold-lake.quiknode.pro/token/";
+
+    #[test]
+    fn detects_providers_by_host() {
+        assert_eq!(detect(ALCHEMY), Some(Provider::Alchemy));
+        assert_eq!(detect(INFURA), Some(Provider::Infura));
+        assert_eq!(detect(QUICKNODE), Some(Provider::QuickNode));
+        assert_eq!(detect("http://localhost:8545"), None);
+        assert_eq!(detect("https://notinfura.io.example.com"), None);
+    }
+
+    #[test]
+    fn alchemy_429_is_rate_limited() {
+        let err = normalize(ALCHEMY, 429, None, ALCHEMY_429.as_bytes()).unwrap_err();
+        assert_eq!((err.kind, err.provider, err.status), (ErrorKind::RateLimited, Some(Provider::Alchemy), 429));
+        assert!(err.message.starts_with("Your app has exceeded"));
+    }
+
+    #[test]
+    fn infura_envelopes() {
+        let err = normalize(INFURA, 200, None, INFURA_RATE_LIMIT.as_bytes()).unwrap_err();
+        assert_eq!((err.kind, err.provider), (ErrorKind::RateLimited, Some(Provider::Infura)));
+        assert!(err.to_string().contains("wait before retrying"));
+
+        // Revert details nested under error.data are lifted
+        let response = normalize(INFURA, 200, None, INFURA_REVERT.as_bytes()).unwrap();
+        let error = response.error.unwrap();
+        assert_eq!(error["message"], "execution reverted: ERC20: transfer amount exceeds balance");
+        assert!(error["data"].as_str().unwrap().starts_with("0x08c379a0"));
+        assert_eq!(error["code"], -32000);
+    }
+
+    #[test]
+    fn quicknode_html_is_unavailable_even_with_200() {
+        let err = normalize(QUICKNODE, 200, None, QUICKNODE_MAINTENANCE.as_bytes()).unwrap_err();
+        assert_eq!((err.kind, err.provider), (ErrorKind::Unavailable, Some(Provider::QuickNode)));
+        assert_eq!(err.message, "503 Service Temporarily Unavailable");
+        // Behind a proxy the page itself names the provider
+        let proxied = normalize("https://rpc.example.com", 200, None, QUICKNODE_MAINTENANCE.as_bytes()).unwrap_err();
+        assert_eq!(proxied.provider, Some(Provider::QuickNode));
+    }
+
+    #[test]
+    fn ordinary_responses_pass_through() {
+        let ok = br#"{"jsonrpc":"2.0","id":1,"result":"0x10"}"#;
+        assert_eq!(normalize(INFURA, 200, None, ok).unwrap().result, Some(json!("0x10")));
+        let revert = br#"{"jsonrpc":"2.0","id":1,"error":{"code":3,"message":"execution reverted","data":"0x"}}"#;
+        assert_eq!(normalize("http://localhost:8545", 200, None, revert).unwrap().error.unwrap()["data"], "0x");
+        let err = normalize("http://localhost:8545", 500, None, b"internal error").unwrap_err();
+        assert_eq!((err.kind, err.message.as_str()), (ErrorKind::Malformed, "internal error"));
+        assert_eq!(normalize("http://localhost:8545", 503, None, b"").unwrap_err().kind, ErrorKind::Unavailable);
+        let empty = normalize("http://localhost:8545", 200, Some("text/plain"), b"  \n").unwrap_err();
+        assert_eq!(empty.to_string(), "not a JSON-RPC response, HTTP 200 (text/plain): empty body");
+        let long = normalize("http://localhost:8545", 500, None, "x".repeat(200).as_bytes()).unwrap_err();
+        assert_eq!(long.message.chars().count(), 121);
+        // JSON under a text content type is still read
+        assert!(normalize(INFURA, 200, Some("text/plain"), ok).is_ok());
+    }
+}
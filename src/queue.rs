@@ -0,0 +1,199 @@
+// src/queue.rs
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use anyhow::Result;
+use tokio::task::JoinHandle;
+
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse, Transport};
+
+/// Most sends allowed to wait behind the one in flight.
+pub const MAX_QUEUED: usize = 5;
+
+/// A request confirmed by the user, with where it goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingSend {
+    pub url: String,
+    pub request: JsonRpcRequest,
+    /// The resolved endpoint override, recorded on the history entry.
+    pub endpoint_override: Option<String>,
+}
+
+/// Returned by [`SendQueue::submit`] when [`MAX_QUEUED`] sends are already waiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueFull;
+
+type Slot = Arc<Mutex<Option<Result<JsonRpcResponse>>>>;
+
+#[derive(Debug)]
+struct InFlight {
+    send: PendingSend,
+    started: Instant,
+    handle: JoinHandle<()>,
+    slot: Slot,
+}
+
+/// Sends user requests in the background, one at a time, in the order they
+/// were confirmed.
+///
+/// At most one send is in flight; up to [`MAX_QUEUED`] more wait behind it.
+/// [`SendQueue::poll`] hands back each finished send and starts the next.
+pub struct SendQueue {
+    transport: Arc<dyn Transport>,
+    queued: VecDeque<PendingSend>,
+    in_flight: Option<InFlight>,
+}
+
+impl SendQueue {
+    pub fn new(transport: Arc<dyn Transport>) -> Self {
+        SendQueue { transport, queued: VecDeque::new(), in_flight: None }
+    }
+
+    /// Starts `send` right away if nothing is in flight, otherwise queues it.
+    pub fn submit(&mut self, send: PendingSend) -> Result<(), QueueFull> {
+        if self.in_flight.is_none() {
+            self.start(send);
+        } else if self.queued.len() < MAX_QUEUED {
+            self.queued.push_back(send);
+        } else {
+            return Err(QueueFull);
+        }
+        Ok(())
+    }
+
+    fn start(&mut self, send: PendingSend) {
+        let slot: Slot = Arc::default();
+        let (transport, sink) = (self.transport.clone(), slot.clone());
+        let (url, request) = (send.url.clone(), send.request.clone());
+        let handle = tokio::spawn(async move {
+            let result = transport.send(&url, request).await;
+            *sink.lock().unwrap() = Some(result);
+        });
+        self.in_flight = Some(InFlight { send, started: Instant::now(), handle, slot });
+    }
+
+    /// The send in flight, if any, and when it started.
+    pub fn in_flight(&self) -> Option<(&PendingSend, Instant)> {
+        self.in_flight.as_ref().map(|f| (&f.send, f.started))
+    }
+
+    /// Number of sends waiting behind the one in flight.
+    pub fn queued(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Whether anything is in flight or waiting.
+    pub fn is_busy(&self) -> bool {
+        self.in_flight.is_some()
+    }
+
+    /// Drops every waiting send (the one in flight completes); returns how many.
+    pub fn clear(&mut self) -> usize {
+        let dropped = self.queued.len();
+        self.queued.clear();
+        dropped
+    }
+
+    /// Aborts the send in flight, which is never recorded, and starts the
+    /// next queued one; returns the aborted send.
+    pub fn cancel(&mut self) -> Option<PendingSend> {
+        let aborted = self.in_flight.take()?;
+        aborted.handle.abort();
+        if let Some(next) = self.queued.pop_front() {
+            self.start(next);
+        }
+        Some(aborted.send)
+    }
+
+    /// If the send in flight has finished, returns it with its outcome and
+    /// starts the next queued one.
+    pub fn poll(&mut self) -> Option<(PendingSend, Result<JsonRpcResponse>)> {
+        if !self.in_flight.as_ref()?.handle.is_finished() {
+            return None;
+        }
+        let done = self.in_flight.take()?;
+        let result = done.slot.lock().unwrap().take().unwrap_or_else(|| Err(anyhow::anyhow!("send was aborted")));
+        if let Some(next) = self.queued.pop_front() {
+            self.start(next);
+        }
+        Some((done.send, result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    /// Answers each request with its id after a delay that shrinks with
+    /// the id, so out-of-order sending would show up as reordering.
+    struct Echo;
+
+    #[async_trait]
+    impl Transport for Echo {
+        async fn send(&self, _url: &str, req: JsonRpcRequest) -> Result<JsonRpcResponse> {
+            tokio::time::sleep(std::time::Duration::from_millis(20u64.saturating_sub(req.id * 3))).await;
+            Ok(JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!(req.id)), error: None, id: req.id })
+        }
+    }
+
+    fn send(id: u64) -> PendingSend {
+        PendingSend { url: "http://node".into(), request: JsonRpcRequest::new("eth_blockNumber", json!([]), id), endpoint_override: None }
+    }
+
+    async fn drain(queue: &mut SendQueue) -> Vec<u64> {
+        let mut done = Vec::new();
+        while queue.is_busy() {
+            match queue.poll() {
+                Some((send, result)) => {
+                    assert_eq!(result.unwrap().id, send.request.id);
+                    done.push(send.request.id);
+                }
+                None => tokio::time::sleep(std::time::Duration::from_millis(2)).await,
+            }
+        }
+        done
+    }
+
+    #[tokio::test]
+    async fn sends_one_at_a_time_in_order() {
+        let mut queue = SendQueue::new(Arc::new(Echo));
+        for id in 1..=4 {
+            queue.submit(send(id)).unwrap();
+        }
+        assert_eq!(queue.in_flight().map(|(s, _)| s.request.id), Some(1));
+        assert_eq!(queue.queued(), 3);
+        assert_eq!(drain(&mut queue).await, vec![1, 2, 3, 4]);
+        assert!(queue.poll().is_none());
+    }
+
+    #[tokio::test]
+    async fn bounded_and_clearable() {
+        let mut queue = SendQueue::new(Arc::new(Echo));
+        queue.submit(send(1)).unwrap();
+        for id in 2..2 + MAX_QUEUED as u64 {
+            queue.submit(send(id)).unwrap();
+        }
+        assert_eq!(queue.submit(send(99)), Err(QueueFull));
+        assert_eq!(queue.queued(), MAX_QUEUED);
+
+        // Clearing keeps the send already in flight
+        assert_eq!(queue.clear(), MAX_QUEUED);
+        assert_eq!(drain(&mut queue).await, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn cancelling_aborts_the_send_in_flight_and_starts_the_next() {
+        let mut queue = SendQueue::new(Arc::new(Echo));
+        assert_eq!(queue.cancel(), None);
+        for id in 1..=3 {
+            queue.submit(send(id)).unwrap();
+        }
+        assert_eq!(queue.cancel().map(|s| s.request.id), Some(1));
+        assert_eq!(queue.in_flight().map(|(s, _)| s.request.id), Some(2));
+        assert_eq!(drain(&mut queue).await, vec![2, 3]);
+    }
+}
@@ -0,0 +1,251 @@
+// src/raw.rs
+
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::editor::TextArea;
+use crate::relaxed;
+
+/// How long typing must pause before the buffer is re-parsed.
+pub const VALIDATE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// A JSON parse error located in the edited buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    /// serde_json's message without the trailing "at line … column …".
+    pub message: String,
+    /// Position of the offending character as (row, char index).
+    pub pos: (usize, usize),
+}
+
+impl JsonError {
+    /// Human-facing summary with 1-based line/column.
+    pub fn describe(&self) -> String {
+        format!("{} (line {}, column {})", self.message, self.pos.0 + 1, self.pos.1 + 1)
+    }
+}
+
+/// Raw-JSON params editor: the whole `params` value edited as text.
+#[derive(Debug, Clone)]
+pub struct RawParams {
+    /// The text being edited.
+    pub area: TextArea,
+    /// Result of the last validation; `None` means the buffer parsed.
+    pub error: Option<JsonError>,
+    /// Time of the last edit not yet validated.
+    edited_at: Option<Instant>,
+    /// Accept relaxed JSON (see [`relaxed::normalize`]), sent as strict.
+    pub relaxed: bool,
+}
+
+impl RawParams {
+    /// Creates an editor for `text` and validates it immediately.
+    pub fn new(text: &str) -> Self {
+        RawParams::with_relaxed(text, false)
+    }
+
+    /// [`RawParams::new`], accepting relaxed JSON when `relaxed` is set.
+    pub fn with_relaxed(text: &str, relaxed: bool) -> Self {
+        let mut raw = RawParams { area: TextArea::new(text), error: None, edited_at: None, relaxed };
+        raw.validate();
+        raw
+    }
+
+    /// Records that the buffer changed; validation happens on a later `tick`.
+    pub fn edited(&mut self, now: Instant) {
+        self.edited_at = Some(now);
+    }
+
+    /// Whether an edit is waiting for the debounce to elapse.
+    pub fn is_pending(&self) -> bool {
+        self.edited_at.is_some()
+    }
+
+    /// Re-validates once the debounce has elapsed since the last edit.
+    pub fn tick(&mut self, now: Instant) {
+        if let Some(at) = self.edited_at
+            && now.duration_since(at) >= VALIDATE_DEBOUNCE
+        {
+            self.validate();
+        }
+    }
+
+    /// Parses the buffer now and stores the outcome.
+    pub fn validate(&mut self) {
+        self.edited_at = None;
+        self.error = self.parse().err();
+    }
+
+    /// Parses the buffer into a JSON value. In relaxed mode the buffer is
+    /// normalized first; errors still point into the buffer as typed.
+    pub fn parse(&self) -> Result<Value, JsonError> {
+        let text = self.area.text();
+        if !self.relaxed {
+            return serde_json::from_str(&text).map_err(|e| JsonError {
+                message: strip_position(&e.to_string()),
+                pos: locate_error(&self.area.lines, e.line(), e.column()),
+            });
+        }
+        let normalized = relaxed::normalize(&text);
+        serde_json::from_str(&normalized.text).map_err(|e| {
+            let lines: Vec<String> = normalized.text.split('\n').map(str::to_string).collect();
+            let pos = locate_error(&lines, e.line(), e.column());
+            JsonError { message: strip_position(&e.to_string()), pos: normalized.source_pos(pos, &self.area.lines) }
+        })
+    }
+
+    /// Partner of the bracket under the cursor, if the cursor is on one.
+    pub fn matching_bracket(&self) -> Option<(usize, usize)> {
+        matching_bracket(&self.area.lines, self.area.cursor)
+    }
+}
+
+/// Maps serde_json's 1-based line and byte column onto a (row, char index)
+/// position in `lines`.
+///
+/// serde_json reports the column just past the byte it choked on, so the
+/// offending character starts at byte `column - 1`. Positions past the end
+/// (EOF errors) are clamped to the last line.
+pub fn locate_error(lines: &[String], line: usize, column: usize) -> (usize, usize) {
+    if lines.is_empty() {
+        return (0, 0);
+    }
+    let row = line.saturating_sub(1).min(lines.len() - 1);
+    let text = &lines[row];
+    let byte = column.saturating_sub(1);
+    let col = text.char_indices().take_while(|(b, _)| *b <= byte).count().saturating_sub(1);
+    let col = if byte >= text.len() { text.chars().count() } else { col };
+    (row, col)
+}
+
+/// Finds the bracket matching the one at `pos`, ignoring brackets inside strings.
+pub fn matching_bracket(lines: &[String], pos: (usize, usize)) -> Option<(usize, usize)> {
+    let mut stack: Vec<(char, (usize, usize))> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (row, line) in lines.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                '"' => in_string = true,
+                '[' | '{' => stack.push((c, (row, col))),
+                ']' | '}' => {
+                    let open = if c == ']' { '[' } else { '{' };
+                    match stack.pop() {
+                        Some((o, start)) if o == open => {
+                            if start == pos {
+                                return Some((row, col));
+                            }
+                            if (row, col) == pos {
+                                return Some(start);
+                            }
+                        }
+                        _ => {
+                            if (row, col) == pos {
+                                return None;
+                            }
+                            stack.clear();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+/// Drops serde_json's " at line X column Y" suffix; the position is shown separately.
+fn strip_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(i) => message[..i].to_string(),
+        None => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::display_column;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(str::to_string).collect()
+    }
+
+    fn error_at(text: &str) -> JsonError {
+        RawParams::new(text).error.expect("expected a parse error")
+    }
+
+    #[test]
+    fn missing_comma_on_later_line() {
+        let err = error_at("[\n  \"0xabc\"\n  \"latest\"\n]");
+        assert_eq!(err.pos, (2, 2));
+        assert!(err.message.contains("expected `,` or `]`"));
+        assert!(!err.message.contains("at line"));
+    }
+
+    #[test]
+    fn tabs_map_to_char_index_and_display_column() {
+        let text = "{\n\t\"a\": 1\n\t\"b\": 2\n}";
+        let err = error_at(text);
+        assert_eq!(err.pos, (2, 1));
+        assert_eq!(display_column(&lines(text)[2], err.pos.1), 4);
+    }
+
+    #[test]
+    fn unicode_before_error_counts_chars_not_bytes() {
+        let text = "[\"日本\", \"é\" x]";
+        let err = error_at(text);
+        // `x` is char 11 but byte 16
+        assert_eq!(err.pos, (0, 11));
+        assert_eq!(display_column(text, err.pos.1), 13);
+    }
+
+    #[test]
+    fn eof_error_clamps_to_end_of_buffer() {
+        let err = error_at("[1,\n2");
+        assert_eq!(err.pos.0, 1);
+        assert!(err.pos.1 <= 1);
+        assert_eq!(locate_error(&lines("ab"), 9, 99), (0, 2));
+    }
+
+    #[test]
+    fn debounce_delays_validation() {
+        let mut raw = RawParams::new("[]");
+        raw.area.insert_char('x');
+        let t0 = Instant::now();
+        raw.edited(t0);
+        raw.tick(t0 + Duration::from_millis(10));
+        assert!(raw.error.is_none());
+        raw.tick(t0 + VALIDATE_DEBOUNCE);
+        assert!(raw.error.is_some());
+        assert!(!raw.is_pending());
+    }
+
+    #[test]
+    fn brackets_match_across_lines_and_skip_strings() {
+        let l = lines("[\n  {\"a\": \"]\"},\n  [1]\n]");
+        assert_eq!(matching_bracket(&l, (0, 0)), Some((3, 0)));
+        assert_eq!(matching_bracket(&l, (1, 11)), Some((1, 2)));
+        assert_eq!(matching_bracket(&l, (2, 2)), Some((2, 4)));
+        assert_eq!(matching_bracket(&l, (1, 3)), None);
+    }
+
+    #[test]
+    fn relaxed_errors_point_into_the_buffer_as_typed() {
+        let raw = RawParams::with_relaxed("[\n  {to: 'a', // x\n   value: one two},\n]", true);
+        let err = raw.error.expect("an unquoted value is still an error");
+        assert_eq!(err.pos, (2, 10));
+        assert!(RawParams::new("[1,]").error.is_some());
+        assert!(RawParams::with_relaxed("[1,]", true).error.is_none());
+    }
+}
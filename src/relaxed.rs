@@ -0,0 +1,212 @@
+// src/relaxed.rs
+
+/// Relaxed JSON rewritten as strict JSON, remembering where each written
+/// character came from so parse errors point into the text as typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Normalized {
+    pub text: String,
+    /// Per row, the source char index of each char written. Rows line up
+    /// with the source's: newlines are never added or dropped.
+    origins: Vec<Vec<usize>>,
+}
+
+impl Normalized {
+    /// The (row, char index) in `source` (the text as typed, split into
+    /// lines) that `pos` in the normalized text came from. Positions past
+    /// the end of a row map to the end of the source row.
+    pub fn source_pos(&self, (row, col): (usize, usize), source: &[String]) -> (usize, usize) {
+        match self.origins.get(row).and_then(|origins| origins.get(col)) {
+            Some(col) => (row, *col),
+            None => (row, source.get(row).map(|line| line.chars().count()).unwrap_or(0)),
+        }
+    }
+}
+
+/// Writes the normalized text while reading the source.
+struct Writer {
+    text: String,
+    origins: Vec<Vec<usize>>,
+    /// Char index in the current source row.
+    col: usize,
+}
+
+impl Writer {
+    /// Writes `c`, attributed to the source char being read.
+    fn write(&mut self, c: char) {
+        self.text.push(c);
+        self.origins.last_mut().expect("there is always a row").push(self.col);
+    }
+
+    /// Reads source char `c` and writes it unchanged.
+    fn copy(&mut self, c: char) {
+        if c == '\n' {
+            self.text.push('\n');
+            self.origins.push(Vec::new());
+            self.col = 0;
+        } else {
+            self.write(c);
+            self.col += 1;
+        }
+    }
+
+    /// Reads a source char (never a newline) without writing it.
+    fn skip(&mut self) {
+        self.col += 1;
+    }
+}
+
+fn is_identifier(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// The first char at or after `from` that isn't whitespace or in a `//`
+/// comment.
+fn significant(chars: &[char], mut from: usize) -> Option<char> {
+    while from < chars.len() {
+        match chars[from] {
+            c if c.is_whitespace() => from += 1,
+            '/' if chars.get(from + 1) == Some(&'/') => {
+                while from < chars.len() && chars[from] != '\n' {
+                    from += 1;
+                }
+            }
+            c => return Some(c),
+        }
+    }
+    None
+}
+
+/// Rewrites the JSON5-style conveniences people reach for when typing
+/// params by hand into strict JSON: `//` line comments (dropped), trailing
+/// commas (dropped), unquoted object keys (quoted) and single-quoted
+/// strings (double-quoted). Everything else is left alone, so strict JSON
+/// comes out as it went in and anything still invalid, like an unquoted
+/// value, fails when parsed.
+pub fn normalize(text: &str) -> Normalized {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = Writer { text: String::with_capacity(text.len()), origins: vec![Vec::new()], col: 0 };
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '"' => {
+                out.copy('"');
+                i += 1;
+                let mut escaped = false;
+                while let Some(&c) = chars.get(i) {
+                    out.copy(c);
+                    i += 1;
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '\'' => {
+                out.write('"');
+                out.skip();
+                i += 1;
+                while let Some(&c) = chars.get(i) {
+                    match (c, chars.get(i + 1)) {
+                        ('\\', Some('\'')) => {
+                            out.write('\'');
+                            out.skip();
+                            out.skip();
+                            i += 2;
+                        }
+                        ('\\', Some(&next)) => {
+                            out.copy('\\');
+                            out.copy(next);
+                            i += 2;
+                        }
+                        ('"', _) => {
+                            out.write('\\');
+                            out.copy('"');
+                            i += 1;
+                        }
+                        ('\'', _) => {
+                            out.write('"');
+                            out.skip();
+                            i += 1;
+                            break;
+                        }
+                        _ => {
+                            out.copy(c);
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    out.skip();
+                    i += 1;
+                }
+            }
+            ',' if matches!(significant(&chars, i + 1), Some(']' | '}')) => {
+                out.skip();
+                i += 1;
+            }
+            c if is_identifier(c) && !c.is_ascii_digit() => {
+                let end = (i..chars.len()).find(|j| !is_identifier(chars[*j])).unwrap_or(chars.len());
+                let key = significant(&chars, end) == Some(':');
+                if key {
+                    out.write('"');
+                }
+                chars[i..end].iter().for_each(|c| out.copy(*c));
+                if key {
+                    out.write('"');
+                }
+                i = end;
+            }
+            c => {
+                out.copy(c);
+                i += 1;
+            }
+        }
+    }
+    Normalized { text: out.text, origins: out.origins }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn parse(text: &str) -> Value {
+        let normalized = normalize(text);
+        serde_json::from_str(&normalized.text).unwrap_or_else(|e| panic!("{}: {}", normalized.text, e))
+    }
+
+    #[test]
+    fn each_relaxation_becomes_strict_json() {
+        // Unquoted keys
+        assert_eq!(parse(r#"[{to: "0xabc", $gas_2: 1}]"#), json!([{"to": "0xabc", "$gas_2": 1}]));
+        // Single quotes, with the other quote and escapes inside
+        assert_eq!(parse(r#"['latest', 'say "hi"', 'it\'s', 'a\nb']"#), json!(["latest", "say \"hi\"", "it's", "a\nb"]));
+        // Trailing commas, also before a comment
+        assert_eq!(parse("[1, {\"a\": 2,},\n// done\n]"), json!([1, {"a": 2}]));
+        // Line comments, but not inside strings
+        assert_eq!(parse("[\n  \"http://x\", // the url\n  true // full\n]"), json!(["http://x", true]));
+        // Keywords and numbers stay as they are
+        assert_eq!(parse("[true, false, null, 1e5, -2]"), json!([true, false, null, 1e5, -2]));
+    }
+
+    #[test]
+    fn strict_json_is_unchanged() {
+        let strict = "[\n  {\"from\": \"0x1\", \"data\": \"it's // fine\"},\n  \"latest\"\n]";
+        assert_eq!(normalize(strict).text, strict);
+    }
+
+    #[test]
+    fn unquoted_values_still_fail() {
+        let text = "[{to: hello world}]";
+        let normalized = normalize(text);
+        assert_eq!(normalized.text, r#"[{"to": hello world}]"#);
+        let err = serde_json::from_str::<Value>(&normalized.text).unwrap_err();
+        // The error maps back to the `h` of hello as typed
+        let pos = normalized.source_pos((0, err.column() - 1), &[text.to_string()]);
+        assert_eq!(pos, (0, 6));
+    }
+}
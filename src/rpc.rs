@@ -1,9 +1,18 @@
 // src/rpc.rs
 
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use reqwest::Client;
+use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
 use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::auth::{self, AuthToken, Credentials};
+use crate::middleware::{Middleware, RequestContext, ResponseContext};
+use crate::provider;
 
 /// Represents a JSON-RPC request payload.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -28,6 +37,98 @@ impl JsonRpcRequest {
             id,
         }
     }
+
+    /// Starts building a request for `method`, with no params and id 1.
+    ///
+    /// ```
+    /// use eli::rpc::JsonRpcRequest;
+    ///
+    /// let req = JsonRpcRequest::builder("eth_getBalance")
+    ///     .param("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f")
+    ///     .param("latest")
+    ///     .id(7)
+    ///     .build();
+    /// assert_eq!(req.params, serde_json::json!(["0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f", "latest"]));
+    /// assert_eq!(req.id, 7);
+    /// ```
+    pub fn builder(method: impl Into<String>) -> RequestBuilder {
+        RequestBuilder { method: method.into(), params: Value::Array(Vec::new()), id: 1 }
+    }
+}
+
+/// Builder for [`JsonRpcRequest`], from [`JsonRpcRequest::builder`].
+///
+/// Params are positional unless [`RequestBuilder::params_object`] is used.
+/// Requests without params serialize as `"params": []` rather than leaving
+/// the key out, since some servers reject a missing `params`.
+///
+/// ```
+/// use eli::rpc::JsonRpcRequest;
+///
+/// let req = JsonRpcRequest::builder("eth_blockNumber").no_params().build();
+/// assert_eq!(
+///     serde_json::to_string(&req).unwrap(),
+///     r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestBuilder {
+    method: String,
+    params: Value,
+    id: u64,
+}
+
+impl RequestBuilder {
+    /// Appends a positional param; replaces named params if any were set.
+    ///
+    /// # Panics
+    ///
+    /// If `value` can't be represented as JSON (e.g. a map with non-string
+    /// keys), like `serde_json::json!` does.
+    pub fn param(mut self, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).expect("param is not representable as JSON");
+        match &mut self.params {
+            Value::Array(params) => params.push(value),
+            other => *other = Value::Array(vec![value]),
+        }
+        self
+    }
+
+    /// Uses named params: `object` must serialize to a JSON object.
+    ///
+    /// ```
+    /// use eli::rpc::JsonRpcRequest;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let named = BTreeMap::from([("address", "0xabc"), ("block", "latest")]);
+    /// let req = JsonRpcRequest::builder("custom_getBalance").params_object(named).build();
+    /// assert_eq!(req.params, serde_json::json!({"address": "0xabc", "block": "latest"}));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `object` doesn't serialize to a JSON object.
+    pub fn params_object(mut self, object: impl Serialize) -> Self {
+        let object = serde_json::to_value(object).expect("params are not representable as JSON");
+        assert!(object.is_object(), "named params must serialize to a JSON object, got {}", object);
+        self.params = object;
+        self
+    }
+
+    /// Clears any params; the request is sent with `"params": []`.
+    pub fn no_params(mut self) -> Self {
+        self.params = Value::Array(Vec::new());
+        self
+    }
+
+    pub fn id(mut self, id: u64) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn build(self) -> JsonRpcRequest {
+        JsonRpcRequest::new(self.method, self.params, self.id)
+    }
 }
 
 /// Represents a JSON-RPC response payload.
@@ -43,30 +144,537 @@ pub struct JsonRpcResponse {
     pub id: u64,
 }
 
+impl JsonRpcResponse {
+    /// "-32602 invalid params" for an error response: the error's code
+    /// and message, whichever it has.
+    pub fn error_summary(&self) -> Option<String> {
+        let error = self.error.as_ref()?;
+        let code = error.get("code").and_then(Value::as_i64).map(|c| c.to_string());
+        let message = error.get("message").and_then(Value::as_str).map(str::to_string);
+        Some(match (code, message) {
+            (Some(code), Some(message)) => format!("{} {}", code, message),
+            (code, message) => code.or(message).unwrap_or_else(|| error.to_string()),
+        })
+    }
+}
+
+/// How one request of a batch came back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchOutcome {
+    /// A response with this id carried a result.
+    Ok(Value),
+    /// A response with this id carried a JSON-RPC error.
+    RpcError(Value),
+    /// No response had this id.
+    Missing,
+}
+
+/// The outcome of every request of a batch, in request order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchReport {
+    pub outcomes: Vec<BatchOutcome>,
+    /// Responses that answered no request: unknown ids, ids already
+    /// answered, or entries that aren't JSON-RPC responses.
+    pub unmatched: usize,
+}
+
+impl BatchReport {
+    /// Matches `responses` to `requests` strictly by id, never by position.
+    ///
+    /// Servers may reorder a batch and may leave responses out (or answer
+    /// some requests with an error), so a request nobody answered is
+    /// [`BatchOutcome::Missing`] rather than taking its neighbour's result.
+    /// When an id comes back twice the first response is kept and the
+    /// second is counted as unmatched.
+    pub fn correlate(requests: &[JsonRpcRequest], responses: Vec<Value>) -> Self {
+        let mut outcomes = vec![BatchOutcome::Missing; requests.len()];
+        let mut unmatched = 0;
+        for value in responses {
+            let Ok(response) = serde_json::from_value::<JsonRpcResponse>(value) else {
+                unmatched += 1;
+                continue;
+            };
+            let slot = requests.iter().position(|r| r.id == response.id).map(|i| &mut outcomes[i]);
+            match slot {
+                Some(slot @ BatchOutcome::Missing) => {
+                    *slot = match response.error {
+                        Some(error) => BatchOutcome::RpcError(error),
+                        None => BatchOutcome::Ok(response.result.unwrap_or(Value::Null)),
+                    }
+                }
+                _ => unmatched += 1,
+            }
+        }
+        BatchReport { outcomes, unmatched }
+    }
+
+    /// (ok, rpc error, missing) counts.
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.outcomes.iter().fold((0, 0, 0), |(ok, err, missing), outcome| match outcome {
+            BatchOutcome::Ok(_) => (ok + 1, err, missing),
+            BatchOutcome::RpcError(_) => (ok, err + 1, missing),
+            BatchOutcome::Missing => (ok, err, missing + 1),
+        })
+    }
+
+    /// "Batch: 2 ok · 1 error · 1 missing", for a result pane's title.
+    pub fn title(&self) -> String {
+        let (ok, err, missing) = self.counts();
+        let mut title = format!("Batch: {} ok · {} error · {} missing", ok, err, missing);
+        if self.unmatched > 0 {
+            title.push_str(&format!(" · {} unmatched", self.unmatched));
+        }
+        title
+    }
+}
+
+/// Something that can deliver a JSON-RPC request and return its response.
+///
+/// Implemented by [`RpcClient`]; tests substitute in-memory transports.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, url: &str, req_body: JsonRpcRequest) -> Result<JsonRpcResponse>;
+
+    /// Sends `requests` as one batch, like [`RpcClient::send_batch`].
+    /// Transports that can't batch fail, and callers send one at a time.
+    async fn send_batch(&self, _url: &str, _requests: &[JsonRpcRequest]) -> Result<BatchReport> {
+        anyhow::bail!("batches are not supported by this transport")
+    }
+}
+
+/// Default cap on a response body: 64 MiB.
+pub const DEFAULT_RESPONSE_LIMIT: usize = 64 * 1024 * 1024;
+
+/// A response body exceeded the client's limit; reading stopped there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooLarge {
+    pub method: String,
+    /// Bytes read before giving up (the limit plus the chunk that crossed it).
+    pub read: usize,
+    pub limit: usize,
+}
+
+impl std::fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} response is larger than the {} byte limit (stopped after {} bytes)", self.method, self.limit, self.read)
+    }
+}
+
+impl std::error::Error for TooLarge {}
+
+/// Default time allowed for a whole request, response body included.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An endpoint didn't finish answering within the client's timeout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimedOut {
+    pub method: String,
+    pub url: String,
+    pub timeout: Duration,
+}
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} timed out: {} did not answer within {:?}", self.method, self.url, self.timeout)
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// How often a request that failed in passing is sent again: connection
+/// errors and HTTP 429/5xx answers that aren't JSON-RPC errors. Requests
+/// that send or sign a transaction are only sent again if they never
+/// connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Sends in all, the first included; 1 never retries.
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for each one after it.
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Sends once, whatever happens.
+    pub const NONE: RetryPolicy = RetryPolicy { max_attempts: 1, base_delay: Duration::ZERO };
+
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        RetryPolicy { max_attempts: max_attempts.max(1), base_delay }
+    }
+
+    /// The wait after failed attempt number `attempt` (from 1).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay.saturating_mul(1 << attempt.saturating_sub(1).min(16))
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, 250 ms then 500 ms apart.
+    fn default() -> Self {
+        RetryPolicy::new(3, Duration::from_millis(250))
+    }
+}
+
+/// An HTTP response as read, before it's taken for JSON-RPC.
+struct Fetched {
+    status: u16,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Whether `method` can be sent twice without doing twice what it does.
+/// Signing and sending a transaction can't: the first attempt may have
+/// reached the node even though its answer never came back.
+fn replayable(method: &str) -> bool {
+    !matches!(method, "eth_sendTransaction" | "personal_sendTransaction") && !method.starts_with("eth_sign")
+}
+
+/// Whether a fetch failed in a way sending it again may fix. A JSON-RPC
+/// error is the endpoint's answer, whatever the status it came with.
+/// Unless `replayable`, only failing to connect counts: anything later
+/// may have been after the endpoint acted on the request.
+fn transient(result: &Result<Fetched>, replayable: bool) -> bool {
+    if !replayable {
+        return matches!(result, Err(e) if e.downcast_ref::<reqwest::Error>().is_some_and(|e| e.is_connect()));
+    }
+    match result {
+        Ok(Fetched { status, body, .. }) => {
+            let retryable = *status == 429 || (500..600).contains(status);
+            let rpc_error = match serde_json::from_slice::<Value>(body) {
+                Ok(Value::Object(object)) => object.contains_key("error"),
+                Ok(Value::Array(_)) => true,
+                _ => false,
+            };
+            retryable && !rpc_error
+        }
+        Err(e) => e.downcast_ref::<reqwest::Error>().is_some_and(|e| e.is_connect() || e.is_request()),
+    }
+}
+
+/// JSON-RPC client that runs registered [`Middleware`] around every request.
+///
+/// The underlying HTTP client is built once and shared by clones, so
+/// connections are reused across requests.
+#[derive(Clone)]
+pub struct RpcClient {
+    http: Client,
+    middleware: Vec<Arc<dyn Middleware>>,
+    response_limit: usize,
+    timeout: Duration,
+    /// Sent with every request; middleware headers win over these.
+    headers: HeaderMap,
+    /// Tokens of the endpoints that need one.
+    auth: Vec<Arc<AuthToken>>,
+    /// Fixed credentials and the endpoint they are for.
+    credentials: Option<(String, Credentials)>,
+    retry: RetryPolicy,
+}
+
+impl Default for RpcClient {
+    fn default() -> Self {
+        RpcClient {
+            http: http_client(DEFAULT_TIMEOUT),
+            middleware: Vec::new(),
+            response_limit: DEFAULT_RESPONSE_LIMIT,
+            timeout: DEFAULT_TIMEOUT,
+            headers: HeaderMap::new(),
+            auth: Vec::new(),
+            credentials: None,
+            retry: RetryPolicy::NONE,
+        }
+    }
+}
+
+fn http_client(timeout: Duration) -> Client {
+    // Like `Client::new`, this only fails if the TLS backend can't start
+    Client::builder().timeout(timeout).build().expect("cannot initialise the HTTP client")
+}
+
+impl RpcClient {
+    /// Creates a client with no middleware.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caps response bodies at `bytes`; larger ones fail with [`TooLarge`]
+    /// as soon as the cap is crossed, without reading the rest.
+    pub fn with_response_limit(mut self, bytes: usize) -> Self {
+        self.response_limit = bytes;
+        self
+    }
+
+    /// Fails requests that take longer than `timeout` with [`TimedOut`].
+    /// Rebuilds the HTTP client, so call it while setting up.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.http = http_client(timeout);
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends `headers` with every request and batch.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Authorizes requests to the token's endpoint with it. A request the
+    /// endpoint answers with 401 is retried once with a refreshed token.
+    pub fn with_auth(mut self, token: AuthToken) -> Self {
+        self.auth.retain(|t| t.url() != token.url());
+        self.auth.push(Arc::new(token));
+        self
+    }
+
+    /// Authorizes requests to `url` with `credentials`, unless an auth
+    /// command covers it too: the command's token wins.
+    pub fn with_credentials(mut self, url: &str, credentials: Credentials) -> Self {
+        self.credentials = Some((url.to_string(), credentials));
+        self
+    }
+
+    /// Sends requests and batches that fail in passing again, as `policy`
+    /// says. Middleware sees each request once, retries included in its
+    /// elapsed time.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// The same HTTP client, limit, timeout, headers and tokens, without
+    /// the middleware.
+    pub fn without_middleware(&self) -> Self {
+        RpcClient { middleware: Vec::new(), ..self.clone() }
+    }
+
+    /// Appends a middleware; hooks run in the order they were registered.
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Sends a JSON-RPC request to `url` and returns the parsed response.
+    pub async fn send(&self, url: &str, req_body: JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let mut ctx = RequestContext {
+            method: req_body.method.clone(),
+            id: req_body.id,
+            url: url.to_string(),
+            headers: HeaderMap::new(),
+        };
+        for m in &self.middleware {
+            m.before(&mut ctx).await;
+        }
+
+        let started = Instant::now();
+        let result = self.post(url, ctx.headers, &req_body).await;
+
+        let done = ResponseContext {
+            method: &req_body.method,
+            id: req_body.id,
+            url,
+            elapsed: started.elapsed(),
+            outcome: result.as_ref(),
+        };
+        for m in &self.middleware {
+            m.after(&done).await;
+        }
+        result
+    }
+
+    async fn post(&self, url: &str, headers: HeaderMap, req_body: &JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let replayable = replayable(&req_body.method);
+        let fetched = self.retrying_fetch(url, headers, req_body, &req_body.method, replayable).await?;
+
+        // Parse response JSON into JsonRpcResponse, unwrapping provider envelopes
+        Ok(provider::normalize(url, fetched.status, fetched.content_type.as_deref(), &fetched.body)?)
+    }
+
+    /// [`RpcClient::authorized_fetch`], sent again with exponential backoff
+    /// while it fails in passing, up to the client's [`RetryPolicy`] (see
+    /// [`transient`] for what `replayable` changes).
+    async fn retrying_fetch(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+        payload: &impl Serialize,
+        method: &str,
+        replayable: bool,
+    ) -> Result<Fetched> {
+        let mut attempt = 1;
+        loop {
+            let result = self.authorized_fetch(url, headers.clone(), payload, method).await;
+            if attempt >= self.retry.max_attempts || !transient(&result, replayable) {
+                return result;
+            }
+            tokio::time::sleep(self.retry.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// [`RpcClient::fetch`] with `url`'s token, if it has one, else its
+    /// fixed credentials. A 401 gets the token refreshed and the request
+    /// sent once more; a failing auth command is an [`auth::AuthFailed`]
+    /// error.
+    async fn authorized_fetch(
+        &self,
+        url: &str,
+        mut headers: HeaderMap,
+        payload: &impl Serialize,
+        method: &str,
+    ) -> Result<Fetched> {
+        let Some(auth) = self.auth.iter().find(|t| t.url() == url) else {
+            if let Some((_, credentials)) = self.credentials.as_ref().filter(|(for_url, _)| for_url == url)
+                && let Some(value) = credentials.header()
+            {
+                headers.insert(AUTHORIZATION, value);
+            }
+            return self.fetch(url, headers, payload, method).await;
+        };
+        let token = auth.token().await?;
+        if let Some(value) = auth::header_value(&token) {
+            headers.insert(AUTHORIZATION, value);
+        }
+        let fetched = self.fetch(url, headers.clone(), payload, method).await?;
+        if fetched.status != 401 {
+            return Ok(fetched);
+        }
+        let token = auth.refresh(&token).await?;
+        if let Some(value) = auth::header_value(&token) {
+            headers.insert(AUTHORIZATION, value);
+        }
+        self.fetch(url, headers, payload, method).await
+    }
+
+    /// POSTs `payload` and reads the body, giving up once it exceeds the
+    /// limit; `method` names the request in a [`TooLarge`] error.
+    async fn fetch(&self, url: &str, headers: HeaderMap, payload: &impl Serialize, method: &str) -> Result<Fetched> {
+        let mut all = self.headers.clone();
+        all.extend(headers);
+        // Send POST request with JSON body
+        let mut resp = self.http
+            .post(url)
+            .headers(all)
+            .json(payload)
+            .send()
+            .await
+            .map_err(|e| self.explain(e, url, method))?;
+
+        // Read the body chunk by chunk, giving up once it exceeds the limit
+        let status = resp.status().as_u16();
+        let content_type = resp.headers().get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let mut body = Vec::new();
+        while let Some(chunk) = resp.chunk().await.map_err(|e| self.explain(e, url, method))? {
+            if body.len() + chunk.len() > self.response_limit {
+                return Err(TooLarge {
+                    method: method.to_string(),
+                    read: body.len() + chunk.len(),
+                    limit: self.response_limit,
+                }
+                .into());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(Fetched { status, content_type, body })
+    }
+
+    /// Turns reqwest's timeout into [`TimedOut`]; other errors pass through.
+    fn explain(&self, error: reqwest::Error, url: &str, method: &str) -> anyhow::Error {
+        if error.is_timeout() {
+            TimedOut { method: method.to_string(), url: url.to_string(), timeout: self.timeout }.into()
+        } else {
+            error.into()
+        }
+    }
+
+    /// Sends `requests` as one JSON-RPC batch and matches the responses
+    /// back to them by id (see [`BatchReport::correlate`]). Middleware
+    /// hooks are per request and don't run for batches.
+    ///
+    /// Fails if the endpoint answers with anything other than an array,
+    /// e.g. a single error for the whole batch or a provider envelope.
+    pub async fn send_batch(&self, url: &str, requests: &[JsonRpcRequest]) -> Result<BatchReport> {
+        let replayable = requests.iter().all(|r| replayable(&r.method));
+        let fetched = self.retrying_fetch(url, HeaderMap::new(), &requests, "batch", replayable).await?;
+        if let Ok(Value::Array(responses)) = serde_json::from_slice(&fetched.body) {
+            return Ok(BatchReport::correlate(requests, responses));
+        }
+        let response = provider::normalize(url, fetched.status, fetched.content_type.as_deref(), &fetched.body)?;
+        let reason = response.error.map(|e| e["message"].as_str().unwrap_or("").to_string()).unwrap_or_default();
+        anyhow::bail!("endpoint did not answer the batch with an array: {}", reason)
+    }
+}
+
+#[async_trait]
+impl Transport for RpcClient {
+    async fn send(&self, url: &str, req_body: JsonRpcRequest) -> Result<JsonRpcResponse> {
+        RpcClient::send(self, url, req_body).await
+    }
+
+    async fn send_batch(&self, url: &str, requests: &[JsonRpcRequest]) -> Result<BatchReport> {
+        RpcClient::send_batch(self, url, requests).await
+    }
+}
+
 /// Sends a JSON-RPC request to the specified URL and returns the parsed response.
 ///
 /// # Arguments
 ///
 /// * `url` - The HTTP endpoint of the Ethereum node (e.g., "http://localhost:8545").
 /// * `req_body` - The JSON-RPC request payload.
-
+///
+/// Uses one shared client with the default limit and [`DEFAULT_TIMEOUT`].
 pub async fn send_rpc_request(
     url: &str,
     req_body: JsonRpcRequest,
 ) -> Result<JsonRpcResponse> {
-    // Initialize HTTP client
-    let client = Client::new();
-    
-    // Send POST request with JSON body
-    let resp = client
-        .post(url)
-        .json(&req_body)
-        .send()
-        .await?;
+    send_rpc_request_with_client(shared_client(), url, req_body).await
+}
 
-    // Parse response JSON into JsonRpcResponse
-    let rpc_res = resp.json::<JsonRpcResponse>().await?;
-    Ok(rpc_res)
+fn shared_client() -> &'static RpcClient {
+    static CLIENT: OnceLock<RpcClient> = OnceLock::new();
+    CLIENT.get_or_init(RpcClient::new)
+}
+
+/// [`send_rpc_request`] through `client`, e.g. one built with a different
+/// timeout.
+pub async fn send_rpc_request_with_client(
+    client: &RpcClient,
+    url: &str,
+    req_body: JsonRpcRequest,
+) -> Result<JsonRpcResponse> {
+    client.send(url, req_body).await
+}
+
+/// [`send_rpc_request`], sent up to `max_attempts` times while it fails
+/// in passing (see [`RetryPolicy`]), waiting `base_delay` before the first
+/// retry and twice as long before each one after it.
+pub async fn send_rpc_request_with_retry(
+    url: &str,
+    req_body: JsonRpcRequest,
+    max_attempts: u32,
+    base_delay: Duration,
+) -> Result<JsonRpcResponse> {
+    let client = shared_client().clone().with_retry(RetryPolicy::new(max_attempts, base_delay));
+    client.send(url, req_body).await
+}
+
+/// Sends `requests` as one JSON-RPC batch and returns one response per
+/// request, in request order whatever order the server answered in.
+///
+/// Fails if any request went unanswered; [`RpcClient::send_batch`] keeps
+/// the partial results instead.
+pub async fn send_rpc_batch(url: &str, requests: Vec<JsonRpcRequest>) -> Result<Vec<JsonRpcResponse>> {
+    let report = shared_client().send_batch(url, &requests).await?;
+    requests
+        .iter()
+        .zip(report.outcomes)
+        .map(|(request, outcome)| {
+            let (result, error) = match outcome {
+                BatchOutcome::Ok(result) => (Some(result), None),
+                BatchOutcome::RpcError(error) => (None, Some(error)),
+                BatchOutcome::Missing => anyhow::bail!("batch: no response for {} (id {})", request.method, request.id),
+            };
+            Ok(JsonRpcResponse { jsonrpc: "2.0".into(), result, error, id: request.id })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -94,6 +702,43 @@ mod tests {
         assert_eq!(deserialized, request);
     }
 
+    /// Error responses summarize as their code and message.
+    #[test]
+    fn error_summary_has_code_and_message() {
+        let response = |error: Value| JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: Some(error), id: 1 };
+        let summary = |error| response(error).error_summary().unwrap();
+        assert_eq!(summary(json!({"code": -32602, "message": "invalid params"})), "-32602 invalid params");
+        assert_eq!(summary(json!({"message": "execution reverted"})), "execution reverted");
+        assert_eq!(summary(json!("boom")), "\"boom\"");
+        assert_eq!(JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: None, id: 1 }.error_summary(), None);
+    }
+
+    /// Builder output for positional, named and empty params.
+    #[test]
+    fn builder_produces_expected_json() {
+        let positional = JsonRpcRequest::builder("eth_getBlockByNumber").param("0x10").param(true).id(3).build();
+        assert_eq!(
+            serde_json::to_value(&positional).unwrap(),
+            json!({"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["0x10", true], "id": 3})
+        );
+        assert_eq!(positional, JsonRpcRequest::new("eth_getBlockByNumber", json!(["0x10", true]), 3));
+
+        let named = JsonRpcRequest::builder("custom_call").params_object(json!({"to": "0xabc", "gas": 21000})).build();
+        assert_eq!(named.params, json!({"to": "0xabc", "gas": 21000}));
+        // Positional params after named ones start a fresh list
+        assert_eq!(JsonRpcRequest::builder("m").params_object(json!({"a": 1})).param(2).build().params, json!([2]));
+
+        let empty = serde_json::to_string(&JsonRpcRequest::builder("eth_chainId").param(1).no_params().build()).unwrap();
+        assert!(empty.contains(r#""params":[]"#), "{}", empty);
+        assert_eq!(JsonRpcRequest::builder("eth_chainId").build().id, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "named params must serialize to a JSON object")]
+    fn builder_rejects_non_object_named_params() {
+        JsonRpcRequest::builder("m").params_object(vec![1, 2]);
+    }
+
     /// Integration test using a mock server to validate HTTP behavior.
     #[tokio::test]
     async fn send_rpc_request_uses_mock_server() {
@@ -117,4 +762,312 @@ mod tests {
         assert_eq!(response.id, 1);
         assert!(response.error.is_none());
     }
+
+    fn batch() -> Vec<JsonRpcRequest> {
+        vec![
+            JsonRpcRequest::new("eth_blockNumber", json!([]), 1),
+            JsonRpcRequest::new("eth_gasPrice", json!([]), 2),
+            JsonRpcRequest::new("eth_getBalance", json!(["0xabc", "latest"]), 3),
+        ]
+    }
+
+    async fn send_batch_to(path: &str, body: &str) -> BatchReport {
+        let _m = mock("POST", path)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+        RpcClient::new().send_batch(&format!("{}{}", server_url(), path), &batch()).await.unwrap()
+    }
+
+    /// Responses are matched by id whatever order they arrive in.
+    #[tokio::test]
+    async fn batch_matches_out_of_order_responses_by_id() {
+        let report = send_batch_to(
+            "/batch-reordered",
+            r#"[{"jsonrpc": "2.0", "result": "0x10", "id": 3},
+                {"jsonrpc": "2.0", "error": {"code": -32000, "message": "busy"}, "id": 2},
+                {"jsonrpc": "2.0", "result": "0x1", "id": 1}]"#,
+        )
+        .await;
+        assert_eq!(
+            report.outcomes,
+            [
+                BatchOutcome::Ok(json!("0x1")),
+                BatchOutcome::RpcError(json!({"code": -32000, "message": "busy"})),
+                BatchOutcome::Ok(json!("0x10")),
+            ]
+        );
+        assert_eq!(report.title(), "Batch: 2 ok · 1 error · 0 missing");
+    }
+
+    /// A response left out is Missing; the ones after it don't shift up.
+    #[tokio::test]
+    async fn batch_marks_omitted_responses_missing() {
+        let report = send_batch_to(
+            "/batch-short",
+            r#"[{"jsonrpc": "2.0", "result": "0x1", "id": 1}, {"jsonrpc": "2.0", "result": "0x10", "id": 3}]"#,
+        )
+        .await;
+        assert_eq!(report.outcomes, [BatchOutcome::Ok(json!("0x1")), BatchOutcome::Missing, BatchOutcome::Ok(json!("0x10"))]);
+        assert_eq!(report.counts(), (2, 0, 1));
+        assert_eq!(report.unmatched, 0);
+    }
+
+    /// The plain batch call hands back responses in request order.
+    #[tokio::test]
+    async fn send_rpc_batch_returns_responses_in_request_order() {
+        let _m = mock("POST", "/batch-plain")
+            .with_status(200)
+            .with_body(
+                r#"[{"jsonrpc": "2.0", "result": "0x10", "id": 3}, {"jsonrpc": "2.0", "result": "0x1", "id": 1},
+                    {"jsonrpc": "2.0", "error": {"code": -32000, "message": "busy"}, "id": 2}]"#,
+            )
+            .create();
+        let responses = send_rpc_batch(&format!("{}/batch-plain", server_url()), batch()).await.unwrap();
+        assert_eq!(responses.iter().map(|r| r.id).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(responses[0].result, Some(json!("0x1")));
+        assert_eq!(responses[1].error, Some(json!({"code": -32000, "message": "busy"})));
+        assert_eq!(responses[2].result, Some(json!("0x10")));
+
+        let _m = mock("POST", "/batch-plain-short")
+            .with_status(200)
+            .with_body(r#"[{"jsonrpc": "2.0", "result": "0x10", "id": 3}, {"jsonrpc": "2.0", "result": "0x1", "id": 1}]"#)
+            .create();
+        let err = send_rpc_batch(&format!("{}/batch-plain-short", server_url()), batch()).await.unwrap_err();
+        assert_eq!(err.to_string(), "batch: no response for eth_gasPrice (id 2)");
+    }
+
+    /// A second response for an id answers nothing.
+    #[tokio::test]
+    async fn batch_keeps_the_first_of_duplicate_ids() {
+        let report = send_batch_to(
+            "/batch-duplicate",
+            r#"[{"jsonrpc": "2.0", "result": "0x1", "id": 1}, {"jsonrpc": "2.0", "result": "0x2", "id": 1},
+                {"jsonrpc": "2.0", "result": "0x10", "id": 3}]"#,
+        )
+        .await;
+        assert_eq!(report.outcomes, [BatchOutcome::Ok(json!("0x1")), BatchOutcome::Missing, BatchOutcome::Ok(json!("0x10"))]);
+        assert_eq!(report.title(), "Batch: 2 ok · 0 error · 1 missing · 1 unmatched");
+    }
+
+    /// A single error object for the whole batch is a failure, not an outcome.
+    #[tokio::test]
+    async fn batch_rejected_as_a_whole_is_an_error() {
+        let _m = mock("POST", "/batch-rejected")
+            .with_status(200)
+            .with_body(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "batches not supported"}, "id": 0}"#)
+            .create();
+        let url = format!("{}/batch-rejected", server_url());
+        let err = RpcClient::new().send_batch(&url, &batch()).await.unwrap_err();
+        assert!(err.to_string().contains("batches not supported"), "{}", err);
+    }
+
+    /// An oversized body is abandoned once the limit is crossed.
+    #[tokio::test]
+    async fn oversized_response_is_cut_off() {
+        use std::io::{Read, Write};
+
+        // A server that streams a 64 MiB body until the client hangs up
+        const TOTAL: usize = 64 * 1024 * 1024;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert!(stream.read(&mut [0; 4096]).unwrap() > 0);
+            let head = format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n", TOTAL);
+            stream.write_all(head.as_bytes()).unwrap();
+            let mut written = 0;
+            while written < TOTAL && stream.write_all(&[b' '; 4096]).is_ok() {
+                written += 4096;
+            }
+            written
+        });
+
+        let client = RpcClient::new().with_response_limit(16 * 1024);
+        let err = client.send(&url, JsonRpcRequest::new("eth_getLogs", json!([{}]), 1)).await.unwrap_err();
+        let too_large = err.downcast_ref::<TooLarge>().expect("a TooLarge error");
+        assert_eq!(too_large.method, "eth_getLogs");
+        assert_eq!(too_large.limit, 16 * 1024);
+        assert!(too_large.read > too_large.limit && too_large.read < 1024 * 1024, "read {} bytes", too_large.read);
+
+        // Dropping the client closes the connection before the body is through
+        drop(client);
+        let written = tokio::task::spawn_blocking(move || server.join().unwrap()).await.unwrap();
+        assert!(written < TOTAL, "server wrote the whole body");
+    }
+
+    /// An endpoint that accepts the connection but never answers times out
+    /// with an error instead of hanging.
+    #[tokio::test]
+    async fn silent_endpoint_times_out() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let client = RpcClient::new().with_timeout(Duration::from_millis(200));
+        let started = Instant::now();
+        let err = send_rpc_request_with_client(&client, &url, JsonRpcRequest::new("eth_blockNumber", json!([]), 1))
+            .await
+            .unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let timed_out = err.downcast_ref::<TimedOut>().expect("a TimedOut error");
+        assert_eq!(timed_out.timeout, Duration::from_millis(200));
+        assert_eq!(err.to_string(), format!("eth_blockNumber timed out: {} did not answer within 200ms", url));
+        drop(listener);
+    }
+
+    /// A 401 re-runs the auth command and the request goes out once more
+    /// with the new token; default headers go with both.
+    #[tokio::test]
+    async fn rejected_tokens_are_refreshed_and_the_request_retried() {
+        use crate::auth::FakeRunner;
+        use reqwest::header::HeaderValue;
+        use std::sync::atomic::Ordering;
+
+        let _rejected = mock("POST", "/auth")
+            .match_header("authorization", "Bearer token-1")
+            .match_header("x-team", "infra")
+            .with_status(401)
+            .with_body("token expired")
+            .expect(1)
+            .create();
+        let _accepted = mock("POST", "/auth")
+            .match_header("authorization", "Bearer token-2")
+            .match_header("x-team", "infra")
+            .with_status(200)
+            .with_body(r#"{"jsonrpc": "2.0", "result": "0x1", "id": 1}"#)
+            .create();
+        let url = format!("{}/auth", server_url());
+        let runner = Arc::new(FakeRunner::default());
+        let headers = HeaderMap::from_iter([(reqwest::header::HeaderName::from_static("x-team"), HeaderValue::from_static("infra"))]);
+        let client = RpcClient::new()
+            .with_headers(headers)
+            .with_auth(AuthToken::new(&url, "get-token staging", auth::DEFAULT_TTL, runner.clone()));
+
+        let request = || JsonRpcRequest::new("eth_blockNumber", json!([]), 1);
+        assert_eq!(client.send(&url, request()).await.unwrap().result, Some(json!("0x1")));
+        // The refreshed token is cached
+        assert_eq!(client.without_middleware().send(&url, request()).await.unwrap().result, Some(json!("0x1")));
+        assert_eq!(runner.runs.load(Ordering::SeqCst), 2);
+        _rejected.assert();
+    }
+
+    /// Fixed credentials go to their endpoint only.
+    #[tokio::test]
+    async fn credentials_are_sent_to_their_endpoint() {
+        let _bearer = mock("POST", "/bearer")
+            .match_header("authorization", "Bearer abc123")
+            .with_status(200)
+            .with_body(r#"{"jsonrpc": "2.0", "result": "0x1", "id": 1}"#)
+            .expect(1)
+            .create();
+        let _elsewhere = mock("POST", "/elsewhere")
+            .match_header("authorization", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(r#"{"jsonrpc": "2.0", "result": "0x2", "id": 1}"#)
+            .expect(1)
+            .create();
+        let url = format!("{}/bearer", server_url());
+        let client = RpcClient::new().with_credentials(&url, Credentials::Bearer("abc123".into()));
+        let request = || JsonRpcRequest::new("eth_blockNumber", json!([]), 1);
+        assert_eq!(client.send(&url, request()).await.unwrap().result, Some(json!("0x1")));
+        let elsewhere = format!("{}/elsewhere", server_url());
+        assert_eq!(client.send(&elsewhere, request()).await.unwrap().result, Some(json!("0x2")));
+        _bearer.assert();
+        _elsewhere.assert();
+    }
+
+    /// A failing auth command fails the request before anything is sent.
+    #[tokio::test]
+    async fn auth_command_failures_are_auth_errors() {
+        let runner = Arc::new(crate::auth::FakeRunner { fail: true, ..Default::default() });
+        let client = RpcClient::new().with_auth(AuthToken::new("http://127.0.0.1:1", "get-token", auth::DEFAULT_TTL, runner));
+        let err = client.send("http://127.0.0.1:1", JsonRpcRequest::new("eth_blockNumber", json!([]), 1)).await.unwrap_err();
+        assert!(err.downcast_ref::<auth::AuthFailed>().is_some(), "{:#}", err);
+        assert!(err.to_string().contains("not logged in"), "{}", err);
+    }
+
+    /// 503s are retried until the endpoint answers; JSON-RPC errors and
+    /// running out of attempts are not.
+    #[tokio::test]
+    async fn transient_failures_are_retried_with_backoff() {
+        let _unavailable = mock("POST", "/flaky").with_status(503).with_body("upstream busy").expect(2).create();
+        let _ok = mock("POST", "/flaky")
+            .with_status(200)
+            .with_body(r#"{"jsonrpc": "2.0", "result": "0x1", "id": 1}"#)
+            .expect(1)
+            .create();
+        let url = format!("{}/flaky", server_url());
+        let request = || JsonRpcRequest::new("eth_blockNumber", json!([]), 1);
+        let started = Instant::now();
+        let response = send_rpc_request_with_retry(&url, request(), 3, Duration::from_millis(20)).await.unwrap();
+        assert_eq!(response.result, Some(json!("0x1")));
+        // 20 ms, then 40 ms
+        assert!(started.elapsed() >= Duration::from_millis(60));
+        _unavailable.assert();
+        _ok.assert();
+
+        let _rpc_error = mock("POST", "/reverted")
+            .with_status(500)
+            .with_body(r#"{"jsonrpc": "2.0", "error": {"code": 3, "message": "execution reverted"}, "id": 1}"#)
+            .expect(1)
+            .create();
+        let url = format!("{}/reverted", server_url());
+        let response = send_rpc_request_with_retry(&url, request(), 3, Duration::ZERO).await.unwrap();
+        assert!(response.error.is_some());
+        _rpc_error.assert();
+
+        let _down = mock("POST", "/down").with_status(502).expect(2).create();
+        let url = format!("{}/down", server_url());
+        assert!(send_rpc_request_with_retry(&url, request(), 2, Duration::ZERO).await.is_err());
+        _down.assert();
+        assert_eq!(RetryPolicy::default().delay(3), Duration::from_secs(1));
+    }
+
+    /// Sending or signing a transaction isn't repeated on a 5xx: the node
+    /// may already have acted on it.
+    #[tokio::test]
+    async fn transactions_are_not_retried_after_reaching_the_endpoint() {
+        for method in ["eth_sendTransaction", "personal_sendTransaction", "eth_signTypedData_v4"] {
+            let path = format!("/unsure-{}", method);
+            let _busy = mock("POST", path.as_str()).with_status(503).expect(1).create();
+            let url = format!("{}{}", server_url(), path);
+            let request = JsonRpcRequest::new(method, json!([]), 1);
+            assert!(send_rpc_request_with_retry(&url, request, 3, Duration::ZERO).await.is_err());
+            _busy.assert();
+        }
+
+        let _busy = mock("POST", "/unsure-batch").with_status(503).expect(1).create();
+        let url = format!("{}/unsure-batch", server_url());
+        let client = RpcClient::new().with_retry(RetryPolicy::new(3, Duration::ZERO));
+        let requests =
+            [JsonRpcRequest::new("eth_blockNumber", json!([]), 1), JsonRpcRequest::new("eth_sendTransaction", json!([]), 2)];
+        assert!(client.send_batch(&url, &requests).await.is_err());
+        _busy.assert();
+    }
+
+    /// Bodies that aren't JSON fail with the status, content type and the
+    /// start of the body rather than a parse error.
+    #[tokio::test]
+    async fn non_json_bodies_report_status_and_snippet() {
+        let _html = mock("POST", "/html")
+            .with_status(500)
+            .with_header("content-type", "text/html")
+            .with_body("<html><head><title>500 Internal Server Error</title></head><body>nginx</body></html>")
+            .create();
+        let url = format!("{}/html", server_url());
+        let err = send_rpc_request(&url, JsonRpcRequest::new("eth_blockNumber", json!([]), 1)).await.unwrap_err();
+        let provider_error = err.downcast_ref::<provider::ProviderError>().unwrap();
+        assert_eq!(provider_error.status, 500);
+        assert_eq!(err.to_string(), "endpoint unavailable, HTTP 500 (text/html): 500 Internal Server Error");
+
+        let _garbage = mock("POST", "/garbage")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body("OK\n  not json")
+            .create();
+        let url = format!("{}/garbage", server_url());
+        let err = send_rpc_request(&url, JsonRpcRequest::new("eth_blockNumber", json!([]), 1)).await.unwrap_err();
+        assert_eq!(err.to_string(), "not a JSON-RPC response, HTTP 200 (text/plain): OK not json");
+    }
 }
@@ -0,0 +1,142 @@
+// src/schema.rs
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+/// Upgrades a file's JSON from one version to the next. Steps are pure:
+/// they get the whole document and return the upgraded one.
+pub type Step = fn(Value) -> Value;
+
+/// The versions of one persisted JSON file.
+///
+/// Files carry their version in a top-level `"version"` field. Files from
+/// before versioning have none and are taken as version 1, the layout they
+/// already had. `history.jsonl` is not covered here; see
+/// [`HISTORY_FORMAT_VERSION`](crate::history::HISTORY_FORMAT_VERSION).
+#[derive(Debug, Clone, Copy)]
+pub struct Schema {
+    /// File name, for messages.
+    pub file: &'static str,
+    /// `steps[i]` upgrades version `i + 1` to `i + 2`.
+    pub steps: &'static [Step],
+}
+
+/// Version of files written before versioning was introduced.
+pub const UNVERSIONED: u64 = 1;
+
+impl Schema {
+    /// Version written by this build.
+    pub fn current(&self) -> u64 {
+        UNVERSIONED + self.steps.len() as u64
+    }
+}
+
+// Add a step here (and its test) whenever the file's layout changes.
+
+pub const UI_STATE: Schema = Schema { file: "ui-state.json", steps: &[] };
+pub const ADDRESS_BOOK: Schema = Schema { file: "address-book.json", steps: &[] };
+pub const DEFAULTS: Schema = Schema { file: "defaults.json", steps: &[] };
+pub const DASHBOARD: Schema = Schema { file: "dashboard.json", steps: &[] };
+pub const ENDPOINTS: Schema = Schema { file: "endpoints.json", steps: &[] };
+
+/// The version `value` was written with.
+pub fn version_of(value: &Value) -> Result<u64> {
+    match value.get("version") {
+        None => Ok(UNVERSIONED),
+        Some(v) => match v.as_u64() {
+            Some(n) if n >= UNVERSIONED => Ok(n),
+            _ => bail!("invalid version {}", v),
+        },
+    }
+}
+
+/// Applies the steps from `from` to the current version and stamps it.
+/// Fails for files written by a newer eli rather than guessing.
+pub fn upgrade(schema: &Schema, value: Value, from: u64) -> Result<Value> {
+    if from > schema.current() {
+        bail!(
+            "{} is version {}, written by a newer eli (this one reads up to {})",
+            schema.file,
+            from,
+            schema.current()
+        );
+    }
+    let skip = (from - UNVERSIONED) as usize;
+    let upgraded = schema.steps[skip..].iter().fold(value, |value, step| step(value));
+    Ok(stamp(upgraded, schema.current()))
+}
+
+/// `value` with its `"version"` field set; non-objects are left alone.
+pub fn stamp(mut value: Value, version: u64) -> Value {
+    if let Value::Object(fields) = &mut value {
+        fields.insert("version".into(), version.into());
+    }
+    value
+}
+
+/// `value` without its `"version"` field, ready to deserialize.
+pub fn unstamp(mut value: Value) -> Value {
+    if let Value::Object(fields) = &mut value {
+        fields.remove("version");
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // A file that went through two layout changes
+    fn rename_units(mut value: Value) -> Value {
+        if let Some(units) = value.as_object_mut().and_then(|o| o.remove("unit")) {
+            value["units"] = units;
+        }
+        value
+    }
+
+    fn nest_prefs(value: Value) -> Value {
+        let mut fields = match value {
+            Value::Object(fields) => fields,
+            other => return other,
+        };
+        let prefs: serde_json::Map<String, Value> =
+            ["units", "separators"].iter().filter_map(|k| fields.remove_entry(*k)).collect();
+        fields.insert("prefs".into(), Value::Object(prefs));
+        Value::Object(fields)
+    }
+
+    const TEST: Schema = Schema { file: "test.json", steps: &[rename_units, nest_prefs] };
+
+    #[test]
+    fn steps_are_pure_functions() {
+        assert_eq!(rename_units(json!({"unit": "wei", "x": 1})), json!({"units": "wei", "x": 1}));
+        assert_eq!(rename_units(json!({"units": "gwei"})), json!({"units": "gwei"}));
+        assert_eq!(
+            nest_prefs(json!({"units": "wei", "separators": false, "x": 1})),
+            json!({"prefs": {"units": "wei", "separators": false}, "x": 1})
+        );
+    }
+
+    #[test]
+    fn upgrades_step_by_step_from_any_older_version() {
+        assert_eq!(TEST.current(), 3);
+        let v1 = json!({"unit": "wei"});
+        assert_eq!(version_of(&v1).unwrap(), 1);
+        assert_eq!(upgrade(&TEST, v1, 1).unwrap(), json!({"version": 3, "prefs": {"units": "wei"}}));
+
+        let v2 = json!({"version": 2, "units": "wei"});
+        assert_eq!(upgrade(&TEST, v2, 2).unwrap(), json!({"version": 3, "prefs": {"units": "wei"}}));
+
+        let v3 = json!({"version": 3, "prefs": {}});
+        assert_eq!(upgrade(&TEST, v3.clone(), 3).unwrap(), v3);
+    }
+
+    #[test]
+    fn newer_and_invalid_versions_are_errors() {
+        let err = upgrade(&TEST, json!({"version": 4}), 4).unwrap_err();
+        assert!(err.to_string().contains("newer eli"), "{}", err);
+        assert!(version_of(&json!({"version": "2"})).is_err());
+        assert!(version_of(&json!({"version": 0})).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+// src/schematree.rs
+
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Result schemas of the standard methods, from ethereum/execution-apis.
+const BUNDLED: &str = include_str!("../specs/results.json");
+
+/// Levels below the root shown before anything is expanded.
+pub const DEFAULT_DEPTH: usize = 2;
+
+/// Refs deeper than this are shown by name only, in case a schema refers
+/// to itself.
+const MAX_NESTING: usize = 8;
+
+/// One field of a result, simplified for display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaNode {
+    pub name: String,
+    /// Type as a reader would write it: a component name (`hash32`,
+    /// `Block`), `array of X`, or alternatives joined with `|`.
+    pub ty: String,
+    pub required: bool,
+    /// Fields of an object, or of an array's items.
+    pub children: Vec<SchemaNode>,
+}
+
+/// An OpenRPC document's method result schemas and the components they
+/// refer to.
+#[derive(Debug, Default)]
+pub struct ResultSchemas {
+    results: Vec<(String, Value)>,
+    components: Value,
+}
+
+#[derive(Deserialize)]
+struct Document {
+    methods: Vec<MethodEntry>,
+    #[serde(default)]
+    components: Value,
+}
+
+#[derive(Deserialize)]
+struct MethodEntry {
+    name: String,
+    #[serde(default)]
+    result: Option<ResultEntry>,
+}
+
+#[derive(Deserialize)]
+struct ResultEntry {
+    schema: Value,
+}
+
+impl ResultSchemas {
+    /// The raw result schema of `method`, if the document has one.
+    pub fn schema(&self, method: &str) -> Option<&Value> {
+        self.results.iter().find(|(name, _)| name == method).map(|(_, schema)| schema)
+    }
+
+    /// `method`'s result as a display tree.
+    pub fn tree(&self, method: &str) -> Option<SchemaNode> {
+        self.schema(method).map(|schema| simplify("result", schema, &self.components))
+    }
+
+    /// Simplifies `schema` against this document's components, e.g. a
+    /// result schema from a spec extension.
+    pub fn simplify(&self, schema: &Value) -> SchemaNode {
+        simplify("result", schema, &self.components)
+    }
+}
+
+/// Parses an OpenRPC document, keeping only what result trees need.
+pub fn parse(text: &str) -> Result<ResultSchemas> {
+    let document: Document = serde_json::from_str(text)?;
+    let results = document.methods.into_iter().filter_map(|m| Some((m.name, m.result?.schema))).collect();
+    Ok(ResultSchemas { results, components: document.components })
+}
+
+/// The bundled execution-apis result schemas.
+pub fn bundled() -> &'static ResultSchemas {
+    static SCHEMAS: OnceLock<ResultSchemas> = OnceLock::new();
+    SCHEMAS.get_or_init(|| parse(BUNDLED).context("bundled result schemas").unwrap())
+}
+
+/// Simplifies the JSON Schema subset OpenRPC documents use (`$ref` into
+/// `#/components/schemas`, `type`, `properties`/`required`, `items`,
+/// `oneOf`/`anyOf`/`allOf`) into a tree of named, typed fields.
+pub fn simplify(name: &str, schema: &Value, components: &Value) -> SchemaNode {
+    node(name, schema, true, components, &mut Vec::new())
+}
+
+fn node(name: &str, schema: &Value, required: bool, components: &Value, path: &mut Vec<String>) -> SchemaNode {
+    let (ty, children) = describe(schema, components, path);
+    SchemaNode { name: name.to_string(), ty, required, children }
+}
+
+/// A schema's type name and fields.
+fn describe(schema: &Value, components: &Value, path: &mut Vec<String>) -> (String, Vec<SchemaNode>) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.rsplit('/').next().unwrap_or(reference).to_string();
+        let target = reference
+            .strip_prefix("#/components/schemas/")
+            .and_then(|key| components.get("schemas")?.get(key));
+        let Some(target) = target.filter(|_| !path.contains(&name) && path.len() < MAX_NESTING) else {
+            return (name, Vec::new());
+        };
+        path.push(name.clone());
+        let (ty, children) = describe(target, components, path);
+        path.pop();
+        // Components read better by name, except arrays, unions and null
+        // (`notFound`), whose shape says more
+        let by_shape = is_structured(target) || target.get("type").and_then(Value::as_str) == Some("null");
+        return (if by_shape { ty } else { name }, children);
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(alternatives) = schema.get(key).and_then(Value::as_array) {
+            let described: Vec<_> = alternatives.iter().map(|a| describe(a, components, path)).collect();
+            let ty = described.iter().map(|(ty, _)| ty.as_str()).collect::<Vec<_>>().join(" | ");
+            let children = described.into_iter().map(|(_, c)| c).find(|c| !c.is_empty()).unwrap_or_default();
+            return (ty, children);
+        }
+    }
+    if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+        let described: Vec<_> = parts.iter().map(|p| describe(p, components, path)).collect();
+        let ty = described.iter().map(|(ty, _)| ty.as_str()).collect::<Vec<_>>().join(" & ");
+        return (ty, described.into_iter().flat_map(|(_, c)| c).collect());
+    }
+    match schema.get("type") {
+        Some(Value::String(t)) if t == "array" => {
+            let (item, children) = schema.get("items").map(|i| describe(i, components, path)).unwrap_or(("any".into(), Vec::new()));
+            (format!("array of {}", item), children)
+        }
+        Some(Value::String(t)) if t == "object" || schema.get("properties").is_some() => {
+            ("object".to_string(), properties(schema, components, path))
+        }
+        Some(Value::String(t)) => (t.clone(), Vec::new()),
+        Some(Value::Array(types)) => (types.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" | "), Vec::new()),
+        _ if schema.get("properties").is_some() => ("object".to_string(), properties(schema, components, path)),
+        _ => ("any".to_string(), Vec::new()),
+    }
+}
+
+fn properties(schema: &Value, components: &Value, path: &mut Vec<String>) -> Vec<SchemaNode> {
+    let required: Vec<&str> =
+        schema.get("required").and_then(Value::as_array).map(|r| r.iter().filter_map(Value::as_str).collect()).unwrap_or_default();
+    let Some(Value::Object(fields)) = schema.get("properties") else { return Vec::new() };
+    fields.iter().map(|(name, field)| node(name, field, required.contains(&name.as_str()), components, path)).collect()
+}
+
+fn is_structured(schema: &Value) -> bool {
+    ["oneOf", "anyOf", "allOf"].iter().any(|k| schema.get(k).is_some())
+        || schema.get("type").and_then(Value::as_str) == Some("array")
+}
+
+/// The tree as indented lines, `depth` levels below the root. Deeper
+/// fields are collapsed into their parent's line as a field count.
+/// Optional fields are marked `?`.
+pub fn lines(root: &SchemaNode, depth: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    push_lines(root, 0, depth, &mut out);
+    out
+}
+
+fn push_lines(node: &SchemaNode, level: usize, depth: usize, out: &mut Vec<String>) {
+    let indent = "  ".repeat(level);
+    let optional = if node.required { "" } else { "?" };
+    let collapsed = level >= depth && !node.children.is_empty();
+    let marker = if collapsed { "▸ " } else { "" };
+    let mut line = format!("{}{}{}{}: {}", indent, marker, node.name, optional, node.ty);
+    if collapsed {
+        line.push_str(&format!(" ({} fields)", node.children.len()));
+    }
+    out.push(line);
+    if !collapsed {
+        for child in &node.children {
+            push_lines(child, level + 1, depth, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn component(name: &str) -> SchemaNode {
+        bundled().simplify(&json!({"$ref": format!("#/components/schemas/{}", name)}))
+    }
+
+    fn child<'a>(node: &'a SchemaNode, name: &str) -> &'a SchemaNode {
+        node.children.iter().find(|c| c.name == name).unwrap_or_else(|| panic!("no field {}", name))
+    }
+
+    #[test]
+    fn block_fields_have_types_and_required_ness() {
+        let block = component("Block");
+        assert_eq!(block.ty, "Block");
+        assert_eq!(child(&block, "hash").ty, "hash32");
+        assert!(child(&block, "hash").required);
+        assert_eq!(child(&block, "miner").ty, "address");
+        assert!(!child(&block, "baseFeePerGas").required);
+        assert_eq!(child(&block, "uncles").ty, "array of hash32");
+
+        // Alternatives: the first with fields supplies the nested ones
+        let transactions = child(&block, "transactions");
+        assert_eq!(transactions.ty, "array of hash32 | array of TransactionInfo");
+        assert_eq!(child(transactions, "to").ty, "address | null");
+        let withdrawals = child(&block, "withdrawals");
+        assert_eq!(withdrawals.ty, "array of Withdrawal");
+        assert_eq!(child(withdrawals, "amount").ty, "uint256");
+    }
+
+    #[test]
+    fn receipt_nests_its_logs() {
+        let receipt = component("ReceiptInfo");
+        assert_eq!(child(&receipt, "contractAddress").ty, "address | null");
+        assert!(!child(&receipt, "status").required);
+        let logs = child(&receipt, "logs");
+        assert_eq!(logs.ty, "array of Log");
+        assert!(logs.required);
+        assert_eq!(child(logs, "topics").ty, "array of bytes32");
+        assert_eq!(child(logs, "removed").ty, "boolean");
+    }
+
+    #[test]
+    fn method_results_resolve_through_the_document() {
+        let block = bundled().tree("eth_getBlockByNumber").unwrap();
+        assert_eq!(block.ty, "null | Block");
+        assert_eq!(bundled().tree("eth_blockNumber").unwrap().ty, "uint");
+        assert_eq!(bundled().tree("eth_simulateV1"), None);
+    }
+
+    #[test]
+    fn lines_expand_two_levels_and_collapse_the_rest() {
+        let receipt = bundled().tree("eth_getTransactionReceipt").unwrap();
+        let shown = lines(&receipt, DEFAULT_DEPTH);
+        assert_eq!(shown[0], "result: null | ReceiptInfo");
+        assert!(shown.contains(&"  blockHash: hash32".to_string()), "{:#?}", shown);
+        assert!(shown.contains(&"  status?: uint".to_string()));
+        assert!(shown.contains(&"  logs: array of Log".to_string()));
+        assert!(shown.contains(&"    address?: address".to_string()));
+
+        let shallow = lines(&receipt, 1);
+        assert!(shallow.contains(&"  ▸ logs: array of Log (9 fields)".to_string()), "{:#?}", shallow);
+        assert!(!shallow.iter().any(|l| l.contains("topics")));
+    }
+
+    #[test]
+    fn self_references_stop_at_the_name() {
+        let components = json!({"schemas": {"Node": {"type": "object", "properties": {"next": {"$ref": "#/components/schemas/Node"}}}}});
+        let tree = simplify("result", &json!({"$ref": "#/components/schemas/Node"}), &components);
+        assert_eq!(child(&tree, "next").ty, "Node");
+        assert!(child(&tree, "next").children.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+// src/search.rs
+
+/// A single match: line index and char range `[start, end)` within that line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Finds every occurrence of `query` in `lines`, including overlapping ones
+/// ("aa" matches "aaa" twice). Ranges are in chars, not bytes.
+pub fn find_matches(lines: &[String], query: &str, case_sensitive: bool) -> Vec<Match> {
+    let needle: Vec<char> = query.chars().collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let eq = |a: char, b: char| {
+        if case_sensitive {
+            a == b
+        } else {
+            a == b || a.to_lowercase().eq(b.to_lowercase())
+        }
+    };
+    let mut matches = Vec::new();
+    for (line_idx, line) in lines.iter().enumerate() {
+        let hay: Vec<char> = line.chars().collect();
+        if hay.len() < needle.len() {
+            continue;
+        }
+        for start in 0..=hay.len() - needle.len() {
+            if needle.iter().zip(&hay[start..]).all(|(n, h)| eq(*h, *n)) {
+                matches.push(Match { line: line_idx, start, end: start + needle.len() });
+            }
+        }
+    }
+    matches
+}
+
+/// How well `query` matches `candidate` as a subsequence, ignoring case;
+/// `None` if it doesn't. Higher is better: matched chars that run together,
+/// start a word (after `_` or at a camelCase hump) or come early score more.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let needle: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let hay: Vec<char> = candidate.chars().collect();
+    if needle.is_empty() {
+        return Some(0);
+    }
+    let lower: Vec<char> = hay.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    let boundary = |j: usize| j == 0 || !hay[j - 1].is_alphanumeric() || (hay[j].is_uppercase() && hay[j - 1].is_lowercase());
+    // best[j]: best score with the current needle char matched at `j`
+    let mut best: Vec<Option<i64>> = vec![None; hay.len()];
+    for (i, n) in needle.iter().enumerate() {
+        let mut next = vec![None; hay.len()];
+        // Best match of the previous needle char before `j`, less one per
+        // char skipped since
+        let mut carried: Option<i64> = None;
+        for j in 0..hay.len() {
+            if j > 0 {
+                carried = carried.map(|s| s - 1).max(best[j - 1]);
+            }
+            if lower[j] == *n {
+                let bonus = if boundary(j) { 10 } else { 0 };
+                let score = if i == 0 {
+                    Some(10 + bonus - j.min(8) as i64)
+                } else {
+                    let run = j.checked_sub(1).and_then(|k| best[k]).map(|s| s + 10 + bonus.max(6));
+                    let gap = carried.map(|s| s + 10 + bonus);
+                    run.max(gap)
+                };
+                next[j] = score;
+            }
+        }
+        best = next;
+    }
+    best.into_iter().flatten().max()
+}
+
+/// The `items` `query` fuzzily matches, best first; ties keep their order.
+/// An empty query keeps everything.
+pub fn fuzzy_filter(items: &[String], query: &str) -> Vec<String> {
+    let mut scored: Vec<(i64, &String)> = items.iter().filter_map(|item| Some((fuzzy_score(query, item)?, item))).collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, item)| item.clone()).collect()
+}
+
+/// Search state for a scrollable text view.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchState {
+    /// Text being searched for.
+    pub query: String,
+    /// Whether the query input currently has focus.
+    pub editing: bool,
+    /// Match case exactly instead of ignoring it (off by default).
+    pub case_sensitive: bool,
+    /// Matches of the last executed search.
+    pub matches: Vec<Match>,
+    /// Index into `matches` of the current match.
+    pub current: Option<usize>,
+}
+
+impl SearchState {
+    /// Runs the query over `lines` and selects the first match.
+    pub fn run(&mut self, lines: &[String]) {
+        self.matches = find_matches(lines, &self.query, self.case_sensitive);
+        self.current = if self.matches.is_empty() { None } else { Some(0) };
+    }
+
+    /// Moves to the next match, wrapping around.
+    pub fn next(&mut self) {
+        if let Some(i) = self.current {
+            self.current = Some((i + 1) % self.matches.len());
+        }
+    }
+
+    /// Moves to the previous match, wrapping around.
+    pub fn prev(&mut self) {
+        if let Some(i) = self.current {
+            self.current = Some((i + self.matches.len() - 1) % self.matches.len());
+        }
+    }
+
+    /// The currently selected match.
+    pub fn current_match(&self) -> Option<Match> {
+        self.current.and_then(|i| self.matches.get(i).copied())
+    }
+
+    /// "match 4/31", "no matches", or `None` before any search ran.
+    pub fn indicator(&self) -> Option<String> {
+        if self.query.is_empty() || self.editing {
+            return None;
+        }
+        Some(match self.current {
+            Some(i) => format!("match {}/{}", i + 1, self.matches.len()),
+            None => "no matches".to_string(),
+        })
+    }
+
+    /// Forgets the query and all matches.
+    pub fn clear(&mut self) {
+        *self = SearchState { case_sensitive: self.case_sensitive, ..SearchState::default() };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn overlapping_matches_on_one_line() {
+        let m = find_matches(&lines("aaaa"), "aa", false);
+        let starts: Vec<usize> = m.iter().map(|m| m.start).collect();
+        assert_eq!(starts, vec![0, 1, 2]);
+        assert!(m.iter().all(|m| m.end == m.start + 2));
+    }
+
+    #[test]
+    fn case_insensitive_by_default_with_toggle() {
+        let l = lines("\"hash\": \"0xAbC\"\n\"from\": \"0xabc\"");
+        assert_eq!(find_matches(&l, "0XABC", false).len(), 2);
+        let exact = find_matches(&l, "0xabc", true);
+        assert_eq!(exact, vec![Match { line: 1, start: 9, end: 14 }]);
+    }
+
+    #[test]
+    fn char_offsets_with_unicode() {
+        let m = find_matches(&lines("é→x é→x"), "→x", false);
+        assert_eq!(m, vec![Match { line: 0, start: 1, end: 3 }, Match { line: 0, start: 5, end: 7 }]);
+    }
+
+    #[test]
+    fn zero_matches_and_empty_query() {
+        let l = lines("abc");
+        assert!(find_matches(&l, "", false).is_empty());
+        let mut s = SearchState { query: "zzz".into(), ..Default::default() };
+        s.run(&l);
+        assert_eq!(s.current, None);
+        s.next();
+        assert_eq!(s.indicator().as_deref(), Some("no matches"));
+    }
+
+    #[test]
+    fn cycling_wraps_both_ways() {
+        let mut s = SearchState { query: "a".into(), ..Default::default() };
+        s.run(&lines("a\nba\nca"));
+        assert_eq!(s.indicator().as_deref(), Some("match 1/3"));
+        s.prev();
+        assert_eq!(s.current_match().unwrap().line, 2);
+        s.next();
+        s.next();
+        assert_eq!(s.indicator().as_deref(), Some("match 2/3"));
+    }
+
+    #[test]
+    fn fuzzy_scores_prefer_runs_word_starts_and_early_matches() {
+        assert_eq!(fuzzy_score("gbn", "eth_getBlockByHash"), None);
+        assert!(fuzzy_score("GBBN", "eth_getBlockByNumber").is_some());
+        // A run beats the same letters spread out
+        assert!(fuzzy_score("block", "eth_getBlockByHash") > fuzzy_score("block", "eth_getBalanceLock"));
+        // Word starts beat letters mid-word
+        assert!(fuzzy_score("gbn", "eth_getBlockByNumber") > fuzzy_score("gbn", "eth_gasbn"));
+        // Earlier beats later
+        assert!(fuzzy_score("eth", "eth_chainId") > fuzzy_score("eth", "net_ethVersion"));
+
+        let methods: Vec<String> = ["eth_getBlockTransactionCountByNumber", "eth_getBalance", "eth_getBlockByNumber", "eth_blockNumber"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(fuzzy_filter(&methods, "blocknum"), ["eth_blockNumber", "eth_getBlockByNumber", "eth_getBlockTransactionCountByNumber"]);
+        assert_eq!(fuzzy_filter(&methods, "gbbn")[0], "eth_getBlockByNumber");
+        assert_eq!(fuzzy_filter(&methods, ""), methods);
+        assert!(fuzzy_filter(&methods, "xyz").is_empty());
+    }
+}
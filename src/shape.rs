@@ -0,0 +1,90 @@
+// src/shape.rs
+
+/// What a param or result value looks like, judged from its text alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// `0x` + 64 hex digits: a block, transaction, or storage hash.
+    Hash32,
+    /// `0x` + 40 hex digits.
+    Address,
+    /// `0x` + 1–16 hex digits: a block number, nonce, gas amount, …
+    Quantity,
+    /// Plain decimal digits, e.g. a block number typed by hand.
+    Decimal,
+    /// latest / earliest / pending / safe / finalized
+    BlockTag,
+    /// Any other `0x`-prefixed hex.
+    Data,
+    Unknown,
+}
+
+const BLOCK_TAGS: &[&str] = &["latest", "earliest", "pending", "safe", "finalized"];
+
+/// Classifies `text` (surrounding whitespace and JSON quotes are ignored).
+pub fn shape_of(text: &str) -> Shape {
+    let text = text.trim().trim_matches('"');
+    if BLOCK_TAGS.contains(&text) {
+        return Shape::BlockTag;
+    }
+    if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+        return Shape::Decimal;
+    }
+    let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) else {
+        return Shape::Unknown;
+    };
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Shape::Unknown;
+    }
+    match hex.len() {
+        64 => Shape::Hash32,
+        40 => Shape::Address,
+        1..=16 => Shape::Quantity,
+        _ => Shape::Data,
+    }
+}
+
+/// Whether `text` can name a block by number (including tags).
+pub fn is_block_number(text: &str) -> bool {
+    matches!(shape_of(text), Shape::Quantity | Shape::Decimal | Shape::BlockTag)
+}
+
+/// `text` as a hex quantity: decimals are converted, other shapes kept as-is.
+pub fn to_quantity(text: &str) -> String {
+    let trimmed = text.trim().trim_matches('"');
+    match (shape_of(trimmed), trimmed.parse::<u64>()) {
+        (Shape::Decimal, Ok(n)) => format!("0x{:x}", n),
+        _ => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_common_shapes() {
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert_eq!(shape_of(&hash), Shape::Hash32);
+        assert_eq!(shape_of(&format!("\"{}\"", hash)), Shape::Hash32);
+        assert_eq!(shape_of("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"), Shape::Address);
+        assert_eq!(shape_of("0x10d4f"), Shape::Quantity);
+        assert_eq!(shape_of(" 1234 "), Shape::Decimal);
+        assert_eq!(shape_of("finalized"), Shape::BlockTag);
+        assert_eq!(shape_of("0xa9059cbb000000"), Shape::Quantity);
+        assert_eq!(shape_of(&format!("0x{}", "00".repeat(36))), Shape::Data);
+        assert_eq!(shape_of("0x"), Shape::Unknown);
+        assert_eq!(shape_of("0xzz"), Shape::Unknown);
+        assert_eq!(shape_of(""), Shape::Unknown);
+    }
+
+    #[test]
+    fn block_numbers_and_conversion() {
+        assert!(is_block_number("latest"));
+        assert!(is_block_number("0x1"));
+        assert!(is_block_number("17000000"));
+        assert!(!is_block_number(&format!("0x{}", "11".repeat(32))));
+        assert_eq!(to_quantity("17000000"), "0x1036640");
+        assert_eq!(to_quantity("0x10"), "0x10");
+        assert_eq!(to_quantity("latest"), "latest");
+    }
+}
@@ -0,0 +1,149 @@
+// src/simulate.rs
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+
+use crate::popup::preview;
+use crate::prefs::DisplayPrefs;
+
+pub const METHOD: &str = "eth_simulateV1";
+
+/// Params for the common single-block case: `calls` is a JSON call object
+/// or array of them, `block_overrides` an optional JSON object. The
+/// simulation runs on top of `latest`.
+pub fn assemble(calls: &str, block_overrides: &str) -> Result<Value> {
+    let calls = match serde_json::from_str(calls).context("calls: invalid JSON")? {
+        Value::Object(call) => vec![Value::Object(call)],
+        Value::Array(calls) if calls.iter().all(Value::is_object) => calls,
+        _ => bail!("calls: expected a call object or an array of them"),
+    };
+    let mut block = Map::new();
+    if !block_overrides.trim().is_empty() {
+        match serde_json::from_str(block_overrides).context("block overrides: invalid JSON")? {
+            Value::Object(overrides) => block.insert("blockOverrides".into(), Value::Object(overrides)),
+            _ => bail!("block overrides: expected an object"),
+        };
+    }
+    block.insert("calls".into(), Value::Array(calls));
+    Ok(json!([{ "blockStateCalls": [block] }, "latest"]))
+}
+
+/// The builder fields (calls, block overrides) that [`assemble`] would turn
+/// back into `params`, or `None` if they use more than the builder covers
+/// (several blocks, state overrides, validation flags, another block tag).
+pub fn builder_fields(params: &Value) -> Option<Vec<String>> {
+    let [payload, tag] = params.as_array()?.as_slice() else { return None };
+    let payload = payload.as_object()?;
+    if tag != "latest" || payload.len() != 1 {
+        return None;
+    }
+    let [block] = payload.get("blockStateCalls")?.as_array()?.as_slice() else { return None };
+    let block = block.as_object()?;
+    if block.keys().any(|k| k != "calls" && k != "blockOverrides") {
+        return None;
+    }
+    let calls = block.get("calls")?.to_string();
+    let overrides = block.get("blockOverrides").map(Value::to_string).unwrap_or_default();
+    Some(vec![calls, overrides])
+}
+
+fn quantity(prefs: &DisplayPrefs, value: &Value) -> String {
+    value.as_str().map(|hex| prefs.quantity(hex)).unwrap_or_else(|| "?".into())
+}
+
+/// Result lines grouped by simulated block, then call index, with each
+/// call's status, gas, return data or error, and logs.
+pub fn summarize(result: &Value, prefs: &DisplayPrefs) -> Vec<String> {
+    let mut lines = Vec::new();
+    for block in result.as_array().into_iter().flatten() {
+        let calls = block["calls"].as_array().map(Vec::as_slice).unwrap_or_default();
+        lines.push(format!(
+            "Simulated block {} — {} call(s), gas {}",
+            quantity(prefs, &block["number"]),
+            calls.len(),
+            quantity(prefs, &block["gasUsed"])
+        ));
+        for (i, call) in calls.iter().enumerate() {
+            let ok = call["status"] == "0x1";
+            let mut line = format!(
+                "  call {}: {} · gas {}",
+                i,
+                if ok { "success" } else { "failed" },
+                quantity(prefs, &call["gasUsed"])
+            );
+            if let Some(message) = call["error"]["message"].as_str() {
+                line.push_str(&format!(" · {}", message));
+            } else if let Some(data) = call["returnData"].as_str().filter(|d| *d != "0x") {
+                line.push_str(&format!(" · returned {}", preview(data, 26)));
+            }
+            lines.push(line);
+            for (j, log) in call["logs"].as_array().into_iter().flatten().enumerate() {
+                let topic = log["topics"][0].as_str().map(|t| preview(t, 20)).unwrap_or_else(|| "(anonymous)".into());
+                lines.push(format!(
+                    "    log {}: {} {}",
+                    j,
+                    prefs.address(log["address"].as_str().unwrap_or("?")),
+                    topic
+                ));
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUEST: &str = include_str!("../tests/fixtures/simulate/request.json");
+    const RESPONSE: &str = include_str!("../tests/fixtures/simulate/response.json");
+
+    fn example_params() -> Value {
+        serde_json::from_str::<Value>(REQUEST).unwrap()["params"].clone()
+    }
+
+    #[test]
+    fn assembles_the_example_payload() {
+        let params = example_params();
+        let calls = params[0]["blockStateCalls"][0]["calls"].to_string();
+        assert_eq!(assemble(&calls, r#"{"baseFeePerGas": "0x9"}"#).unwrap(), params);
+
+        // A single call object is wrapped; no overrides means no key
+        let single = assemble(r#"{"to": "0xc1"}"#, "  ").unwrap();
+        assert_eq!(single, json!([{"blockStateCalls": [{"calls": [{"to": "0xc1"}]}]}, "latest"]));
+
+        assert!(assemble("[1]", "").is_err());
+        assert!(assemble("[]", "[]").is_err());
+    }
+
+    #[test]
+    fn builder_fields_round_trip() {
+        let params = example_params();
+        let fields = builder_fields(&params).unwrap();
+        assert_eq!(assemble(&fields[0], &fields[1]).unwrap(), params);
+
+        let mut flagged = params.clone();
+        flagged[0]["validation"] = json!(true);
+        assert_eq!(builder_fields(&flagged), None);
+        let mut pending = params;
+        pending[1] = json!("pending");
+        assert_eq!(builder_fields(&pending), None);
+    }
+
+    #[test]
+    fn groups_results_by_block_and_call() {
+        let response: Value = serde_json::from_str(RESPONSE).unwrap();
+        let lines = summarize(&response["result"], &DisplayPrefs::default());
+        assert_eq!(
+            lines,
+            vec![
+                "Simulated block 1 — 2 call(s), gas 42,000",
+                "  call 0: success · gas 21,000",
+                "  call 1: success · gas 21,000",
+                "    log 0: 0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee 0xddf252ad1be…(66 B)",
+                "Simulated block 2 — 1 call(s), gas 23,457",
+                "  call 0: failed · gas 23,457 · execution reverted: nope",
+            ]
+        );
+    }
+}
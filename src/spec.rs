@@ -1,20 +1,504 @@
 // src/spec.rs
 
+use std::borrow::Cow;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::shape::{is_block_number, shape_of, Shape};
+
 /// A single RPC method’s signature:
 ///  - `name`: the RPC method (e.g. "eth_getBalance")
 ///  - `params`: an ordered list of parameter names
+///  - `help`: what each param expects, by position; empty or missing
+///    entries have no help
+///  - `kinds`: how each param is edited, by position; missing entries are
+///    [`ParamKind::Text`]
+///
+/// Built-in specs borrow their strings; specs read from a file, plugin or
+/// extension own theirs, so reloading one frees the last.
 #[derive(Debug, Clone)]
 pub struct MethodSpec {
-    pub name: &'static str,
-    pub params: &'static [&'static str],
+    pub name: Cow<'static, str>,
+    pub params: Labels,
+    pub help: Labels,
+    pub kinds: Cow<'static, [ParamKind]>,
+}
+
+impl MethodSpec {
+    /// A spec from the built-in tables, borrowing everything.
+    pub const fn builtin(
+        name: &'static str,
+        params: &'static [&'static str],
+        help: &'static [&'static str],
+        kinds: &'static [ParamKind],
+    ) -> Self {
+        MethodSpec {
+            name: Cow::Borrowed(name),
+            params: Labels::Static(params),
+            help: Labels::Static(help),
+            kinds: Cow::Borrowed(kinds),
+        }
+    }
+
+    /// A spec read at runtime, owning everything.
+    pub fn owned(name: String, params: Vec<String>, help: Vec<String>, kinds: Vec<ParamKind>) -> Self {
+        MethodSpec {
+            name: Cow::Owned(name),
+            params: Labels::Owned(params),
+            help: Labels::Owned(help),
+            kinds: Cow::Owned(kinds),
+        }
+    }
+
+    /// Help text for the param at `index`, if any.
+    pub fn help(&self, index: usize) -> Option<&str> {
+        self.help.get(index).filter(|h| !h.is_empty())
+    }
+
+    /// How the param at `index` is edited.
+    pub fn kind(&self, index: usize) -> ParamKind {
+        self.kinds.get(index).copied().unwrap_or(ParamKind::Text)
+    }
+
+    /// Whether the param at `index` must be filled in: toggles have a
+    /// default, and help starting with "optional" or saying what "empty"
+    /// means marks params that may be left out.
+    pub fn required(&self, index: usize) -> bool {
+        let optional = self.help(index).is_some_and(|h| h.starts_with("optional") || h.contains("; empty for"));
+        self.kind(index) == ParamKind::Text && !optional
+    }
+
+    /// Reads a method list to use instead of [`RPC_SPECS`], e.g. a node's
+    /// own OpenRPC document. See [`parse_document`].
+    pub fn load_from_file(path: &Path) -> Result<Vec<MethodSpec>> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        parse_document(&text).with_context(|| format!("{} is not a method spec", path.display()))
+    }
+}
+
+/// Param names or help texts of a [`MethodSpec`], by position.
+#[derive(Debug, Clone)]
+pub enum Labels {
+    Static(&'static [&'static str]),
+    Owned(Vec<String>),
+}
+
+impl Labels {
+    pub fn len(&self) -> usize {
+        match self {
+            Labels::Static(items) => items.len(),
+            Labels::Owned(items) => items.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        match self {
+            Labels::Static(items) => items.get(index).copied(),
+            Labels::Owned(items) => items.get(index).map(String::as_str),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        (0..self.len()).filter_map(|i| self.get(i))
+    }
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        Labels::Static(&[])
+    }
+}
+
+impl std::ops::Index<usize> for Labels {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        self.get(index).expect("label index out of range")
+    }
+}
+
+impl PartialEq for Labels {
+    fn eq(&self, other: &Labels) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<const N: usize> PartialEq<[&str; N]> for Labels {
+    fn eq(&self, other: &[&str; N]) -> bool {
+        self.iter().eq(other.iter().copied())
+    }
+}
+
+/// Why a request's params don't match its method's spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Required params left empty, in spec order.
+    Missing { method: String, expected: usize, missing: Vec<String> },
+    /// A param that isn't what its name says it is.
+    Malformed { param: String, expected: &'static str },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::Missing { method, expected, missing } => {
+                let verb = if missing.len() == 1 { "is" } else { "are" };
+                let plural = if *expected == 1 { "" } else { "s" };
+                write!(f, "{} takes {} param{}; {} {} missing", method, expected, plural, missing.join(", "), verb)
+            }
+            ValidationError::Malformed { param, expected } => write!(f, "{}: expected {}", param, expected),
+        }
+    }
 }
 
-/// Hard‑coded registry of the few methods we care about for now.
-/// In the future you could deserialize a JSON file or hook into reth’s types.
+impl std::error::Error for ValidationError {}
+
+/// What a param with a well-known name should look like, if `text`
+/// doesn't look like it.
+fn mismatch(name: &str, text: &str) -> Option<&'static str> {
+    let (fits, expected) = match name {
+        "address" => (shape_of(text) == Shape::Address, "a 20-byte 0x-prefixed address"),
+        "block" => (is_block_number(text) || shape_of(text) == Shape::Hash32, "a block number, tag or hash"),
+        "block_hash" | "tx_hash" => (shape_of(text) == Shape::Hash32, "a 32-byte 0x-prefixed hash"),
+        "index" => (matches!(shape_of(text), Shape::Quantity | Shape::Decimal), "a hex or decimal number"),
+        "call_object" | "tx_object" | "filter" => {
+            (serde_json::from_str::<Value>(text).is_ok_and(|v| v.is_object()), "a JSON object")
+        }
+        _ => return None,
+    };
+    (!fits).then_some(expected)
+}
+
+/// Checks the param `fields` of a request for `spec` before it is sent:
+/// every required param filled in, and params with well-known names
+/// (`address`, `block`, `tx_hash`, …) shaped like them. Toggles and params
+/// whose help calls them optional may stay empty.
+pub fn validate(spec: &MethodSpec, fields: &[String]) -> Result<(), ValidationError> {
+    let field = |i: usize| fields.get(i).map(|f| f.trim()).unwrap_or("");
+    let missing: Vec<String> = (0..spec.params.len())
+        .filter(|&i| spec.required(i) && field(i).is_empty())
+        .map(|i| spec.params[i].to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(ValidationError::Missing { method: spec.name.to_string(), expected: spec.params.len(), missing });
+    }
+    for (i, param) in spec.params.iter().enumerate() {
+        let text = field(i);
+        if let ParamKind::Bool { .. } = spec.kind(i) {
+            if spec.kind(i).toggle_state(text).is_none() {
+                return Err(ValidationError::Malformed { param: param.to_string(), expected: "true or false" });
+            }
+            continue;
+        }
+        if let Some(expected) = mismatch(param, text).filter(|_| !text.is_empty()) {
+            return Err(ValidationError::Malformed { param: param.to_string(), expected });
+        }
+    }
+    Ok(())
+}
+
+/// How a param field is edited and sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    /// Free text: sent as JSON when it parses, else as a string.
+    Text,
+    /// A JSON boolean, edited as a toggle. `on`/`off` describe the two
+    /// states; an empty field means `default`.
+    Bool { default: bool, on: &'static str, off: &'static str },
+}
+
+impl ParamKind {
+    /// The param's type as `eli spec list --json` and `eli spec diff` name it.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ParamKind::Text => "text",
+            ParamKind::Bool { .. } => "bool",
+        }
+    }
+
+    /// The toggle's state for field text `text`: `true`/`false` in any
+    /// case, the default when empty. `None` for text params and for text
+    /// that isn't a boolean.
+    pub fn toggle_state(&self, text: &str) -> Option<bool> {
+        let ParamKind::Bool { default, .. } = self else { return None };
+        match text.trim() {
+            "" => Some(*default),
+            t if t.eq_ignore_ascii_case("true") => Some(true),
+            t if t.eq_ignore_ascii_case("false") => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// A spec document: OpenRPC's `{"methods": [...]}`, or just the array.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Document {
+    OpenRpc { methods: Vec<MethodEntry> },
+    Methods(Vec<MethodEntry>),
+}
+
+#[derive(Deserialize)]
+struct MethodEntry {
+    name: String,
+    #[serde(default)]
+    params: Vec<ParamEntry>,
+}
+
+#[derive(Deserialize)]
+struct ParamEntry {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    schema: Value,
+}
+
+/// Parses a spec document into method specs, in document order. Param
+/// descriptions become help, and boolean params toggles.
+pub fn parse_document(text: &str) -> Result<Vec<MethodSpec>> {
+    let methods = match serde_json::from_str(text)? {
+        Document::OpenRpc { methods } | Document::Methods(methods) => methods,
+    };
+    Ok(methods
+        .into_iter()
+        .map(|method| {
+            let params: Vec<String> = method.params.iter().map(|p| p.name.clone()).collect();
+            let kinds: Vec<ParamKind> = method
+                .params
+                .iter()
+                .map(|p| match p.schema.get("type").and_then(Value::as_str) {
+                    Some("boolean") => ParamKind::Bool { default: false, on: "true", off: "false" },
+                    _ => ParamKind::Text,
+                })
+                .collect();
+            let help: Vec<String> = method.params.into_iter().map(|p| p.description).collect();
+            MethodSpec::owned(method.name, params, help, kinds)
+        })
+        .collect())
+}
+
+/// `base` with `extra` merged in: a method both define takes `extra`'s
+/// entry, and new methods go at the end.
+pub fn merge(base: &[MethodSpec], extra: &[MethodSpec]) -> Vec<MethodSpec> {
+    let from_extra = |name: &str| extra.iter().find(|m| m.name == name);
+    let mut merged: Vec<MethodSpec> = base.iter().map(|m| from_extra(&m.name).unwrap_or(m).clone()).collect();
+    merged.extend(extra.iter().filter(|m| !base.iter().any(|b| b.name == m.name)).cloned());
+    merged
+}
+
+/// Looks up a method's spec by name.
+pub fn find(method: &str) -> Option<&'static MethodSpec> {
+    RPC_SPECS.iter().find(|s| s.name == method)
+}
+
+// Help strings follow the param descriptions of the execution-apis OpenRPC
+// document, shortened to fit on one line.
+const BLOCK_HELP: &str = "hex-encoded block number, or one of latest/pending/safe/finalized/earliest";
+const BLOCK_OR_HASH_HELP: &str = "hex block number, a tag (latest/pending/safe/finalized/earliest), or a 32-byte block hash";
+const ADDRESS_HELP: &str = "20-byte account address, 0x-prefixed hex";
+const TX_HELP: &str = "transaction object: {\"from\", \"to\", \"gas\", \"value\", \"input\", …} with hex values";
+const BLOCK_HASH_HELP: &str = "32-byte block hash, 0x-prefixed hex";
+const TX_HASH_HELP: &str = "32-byte transaction hash, 0x-prefixed hex";
+const INDEX_HELP: &str = "position in the block, hex";
+const FILTER_HELP: &str = "filter object: {\"fromBlock\", \"toBlock\", \"address\", \"topics\"} or {\"blockHash\", …}";
+const FILTER_ID_HELP: &str = "id returned by eth_newFilter, eth_newBlockFilter or eth_newPendingTransactionFilter";
+const HYDRATED_HELP: &str = "true for full transaction objects, false for hashes only";
+const HYDRATED: ParamKind = ParamKind::Bool { default: false, on: "include full transactions", off: "hashes only" };
+
+/// Every method eli lists, with its params. The method list is built from
+/// this in order: the everyday methods and those with builders first, then
+/// the rest of `eth_*` by topic, `net_*` and `web3_*`.
 pub const RPC_SPECS: &[MethodSpec] = &[
-    MethodSpec { name: "eth_blockNumber", params: &[] },
-    MethodSpec { name: "eth_getBalance", params: &["address", "block"] },
-    MethodSpec { name: "eth_sendTransaction", params: &["tx_object"] },
-    MethodSpec { name: "eth_call", params: &["call_object", "block"] },
-    // … more …
+    MethodSpec::builtin("eth_blockNumber", &[], &[], &[]),
+    MethodSpec::builtin("eth_getBalance", &["address", "block"], &[ADDRESS_HELP, BLOCK_OR_HASH_HELP], &[]),
+    MethodSpec::builtin("eth_gasPrice", &[], &[], &[]),
+    MethodSpec::builtin("eth_call", &["call_object", "block"], &[TX_HELP, BLOCK_OR_HASH_HELP], &[]),
+    MethodSpec::builtin(
+        "eth_getBlockByNumber",
+        &["block", "include_transactions"],
+        &[BLOCK_HELP, HYDRATED_HELP],
+        &[ParamKind::Text, HYDRATED],
+    ),
+    MethodSpec::builtin(
+        "eth_getBlockByHash",
+        &["block_hash", "include_transactions"],
+        &[BLOCK_HASH_HELP, HYDRATED_HELP],
+        &[ParamKind::Text, HYDRATED],
+    ),
+    // Builder fields for a single simulated block on `latest`; anything
+    // more involved is edited as raw JSON (Ctrl+R)
+    MethodSpec::builtin(
+        "eth_simulateV1",
+        &["calls", "block_overrides"],
+        &[
+            "call object or JSON array of them: {\"from\", \"to\", \"input\", \"value\", …}",
+            "optional JSON object, e.g. {\"baseFeePerGas\": \"0x9\", \"time\": \"0x…\"}",
+        ],
+        &[],
+    ),
+    MethodSpec::builtin("eth_sendTransaction", &["tx_object"], &[TX_HELP], &[]),
+    MethodSpec::builtin(
+        "eth_createAccessList",
+        &["call_object", "block"],
+        &["transaction object, optionally with an \"accessList\" to compare against", BLOCK_OR_HASH_HELP],
+        &[],
+    ),
+    // OpenEthereum-style tracing (Erigon, Nethermind, some providers)
+    MethodSpec::builtin("trace_transaction", &["tx_hash"], &[TX_HASH_HELP], &[]),
+    MethodSpec::builtin("trace_block", &["block"], &[BLOCK_HELP], &[]),
+    // Builder fields assembled into the one filter object
+    MethodSpec::builtin(
+        "trace_filter",
+        crate::trace::FILTER_FIELDS,
+        &[
+            "first block: decimal or hex number, or a tag; empty for earliest",
+            "last block: decimal or hex number, or a tag; empty for latest",
+            "traces from these addresses, comma-separated; empty for any",
+            "traces to these addresses, comma-separated; empty for any",
+            "skip this many matching traces",
+            "return at most this many traces",
+        ],
+        &[],
+    ),
+    MethodSpec::builtin(
+        "trace_call",
+        &["call_object", "trace_types", "block"],
+        &[TX_HELP, "JSON array of \"trace\", \"vmTrace\", \"stateDiff\"", BLOCK_OR_HASH_HELP],
+        &[],
+    ),
+    // Chain and fee market
+    MethodSpec::builtin("eth_chainId", &[], &[], &[]),
+    MethodSpec::builtin("eth_syncing", &[], &[], &[]),
+    MethodSpec::builtin("eth_maxPriorityFeePerGas", &[], &[], &[]),
+    MethodSpec::builtin("eth_blobBaseFee", &[], &[], &[]),
+    MethodSpec::builtin(
+        "eth_feeHistory",
+        &["block_count", "newest_block", "reward_percentiles"],
+        &["number of blocks, hex or decimal", BLOCK_HELP, "JSON array of ascending percentiles, e.g. [25, 75]"],
+        &[],
+    ),
+    // Account state
+    MethodSpec::builtin("eth_getTransactionCount", &["address", "block"], &[ADDRESS_HELP, BLOCK_OR_HASH_HELP], &[]),
+    MethodSpec::builtin("eth_getCode", &["address", "block"], &[ADDRESS_HELP, BLOCK_OR_HASH_HELP], &[]),
+    MethodSpec::builtin(
+        "eth_getStorageAt",
+        &["address", "position", "block"],
+        &[ADDRESS_HELP, "storage slot, hex", BLOCK_OR_HASH_HELP],
+        &[],
+    ),
+    MethodSpec::builtin(
+        "eth_getProof",
+        &["address", "storage_keys", "block"],
+        &[ADDRESS_HELP, "JSON array of 32-byte storage keys", BLOCK_OR_HASH_HELP],
+        &[],
+    ),
+    MethodSpec::builtin("eth_accounts", &[], &[], &[]),
+    // Transactions and receipts
+    MethodSpec::builtin("eth_estimateGas", &["call_object", "block"], &[TX_HELP, BLOCK_OR_HASH_HELP], &[]),
+    MethodSpec::builtin(
+        "eth_sendRawTransaction",
+        &["signed_tx"],
+        &["signed transaction, RLP-encoded and 0x-prefixed"],
+        &[],
+    ),
+    MethodSpec::builtin("eth_getTransactionByHash", &["tx_hash"], &[TX_HASH_HELP], &[]),
+    MethodSpec::builtin("eth_getTransactionReceipt", &["tx_hash"], &[TX_HASH_HELP], &[]),
+    MethodSpec::builtin(
+        "eth_getTransactionByBlockHashAndIndex",
+        &["block_hash", "index"],
+        &[BLOCK_HASH_HELP, INDEX_HELP],
+        &[],
+    ),
+    MethodSpec::builtin("eth_getTransactionByBlockNumberAndIndex", &["block", "index"], &[BLOCK_HELP, INDEX_HELP], &[]),
+    MethodSpec::builtin("eth_getBlockReceipts", &["block"], &[BLOCK_OR_HASH_HELP], &[]),
+    MethodSpec::builtin("eth_getBlockTransactionCountByHash", &["block_hash"], &[BLOCK_HASH_HELP], &[]),
+    MethodSpec::builtin("eth_getBlockTransactionCountByNumber", &["block"], &[BLOCK_HELP], &[]),
+    MethodSpec::builtin("eth_getUncleCountByBlockHash", &["block_hash"], &[BLOCK_HASH_HELP], &[]),
+    MethodSpec::builtin("eth_getUncleCountByBlockNumber", &["block"], &[BLOCK_HELP], &[]),
+    // Logs and filters
+    MethodSpec::builtin("eth_getLogs", &["filter"], &[FILTER_HELP], &[]),
+    MethodSpec::builtin("eth_newFilter", &["filter"], &[FILTER_HELP], &[]),
+    MethodSpec::builtin("eth_newBlockFilter", &[], &[], &[]),
+    MethodSpec::builtin("eth_newPendingTransactionFilter", &[], &[], &[]),
+    MethodSpec::builtin("eth_getFilterChanges", &["filter_id"], &[FILTER_ID_HELP], &[]),
+    MethodSpec::builtin("eth_getFilterLogs", &["filter_id"], &[FILTER_ID_HELP], &[]),
+    MethodSpec::builtin("eth_uninstallFilter", &["filter_id"], &[FILTER_ID_HELP], &[]),
+    // Node
+    MethodSpec::builtin("net_version", &[], &[], &[]),
+    MethodSpec::builtin("net_listening", &[], &[], &[]),
+    MethodSpec::builtin("net_peerCount", &[], &[], &[]),
+    MethodSpec::builtin("web3_clientVersion", &[], &[], &[]),
+    MethodSpec::builtin("web3_sha3", &["data"], &["0x-prefixed hex data to hash with keccak-256"], &[]),
 ];
+
+/// Old method name → current name, for history recorded before a rename.
+pub const RENAMED_METHODS: &[(&str, &str)] = &[];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_openrpc_documents_and_bare_method_lists() {
+        let specs = parse_document(
+            r#"{"openrpc": "1.2.4", "info": {"title": "anvil"}, "methods": [
+                {"name": "anvil_mine", "params": [
+                    {"name": "blocks", "description": "number of blocks, hex", "schema": {"type": "string"}},
+                    {"name": "interval", "schema": {"type": "string"}}
+                ]},
+                {"name": "debug_traceTransaction", "params": [
+                    {"name": "hash", "description": "32-byte transaction hash"},
+                    {"name": "withMemory", "schema": {"type": "boolean"}}
+                ]},
+                {"name": "eth_blockNumber"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(specs.iter().map(|s| &*s.name).collect::<Vec<_>>(), ["anvil_mine", "debug_traceTransaction", "eth_blockNumber"]);
+        assert_eq!(specs[0].params, ["blocks", "interval"]);
+        assert_eq!((specs[0].help(0), specs[0].help(1)), (Some("number of blocks, hex"), None));
+        assert_eq!(specs[1].kind(0), ParamKind::Text);
+        assert!(matches!(specs[1].kind(1), ParamKind::Bool { default: false, .. }));
+        assert!(specs[2].params.is_empty());
+
+        let bare = parse_document(r#"[{"name": "reth_getBalanceChangesInBlock", "params": [{"name": "block"}]}]"#).unwrap();
+        assert_eq!((&*bare[0].name, &bare[0].params), ("reth_getBalanceChangesInBlock", &Labels::Static(&["block"])));
+
+        assert!(parse_document(r#"{"methods": [{"params": []}]}"#).is_err());
+        assert!(MethodSpec::load_from_file(Path::new("/nonexistent/eli-spec.json")).unwrap_err().to_string().contains("cannot read"));
+    }
+
+    #[test]
+    fn validation_reports_missing_and_malformed_params() {
+        let fields = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let address = "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f";
+        let balance = find("eth_getBalance").unwrap();
+        assert_eq!(validate(balance, &fields(&[address, "latest"])), Ok(()));
+        assert_eq!(validate(balance, &fields(&[address, "17000000"])), Ok(()));
+
+        let err = validate(balance, &fields(&[address])).unwrap_err();
+        assert_eq!(err.to_string(), "eth_getBalance takes 2 params; block is missing");
+        let err = validate(balance, &fields(&["  ", ""])).unwrap_err();
+        let missing = vec!["address".to_string(), "block".to_string()];
+        assert_eq!(err, ValidationError::Missing { method: "eth_getBalance".into(), expected: 2, missing });
+        let err = validate(balance, &fields(&["0xabc", "latest"])).unwrap_err();
+        assert_eq!(err.to_string(), "address: expected a 20-byte 0x-prefixed address");
+
+        // Toggles default and optional params may stay empty, but not be nonsense
+        let block = find("eth_getBlockByNumber").unwrap();
+        assert_eq!(validate(block, &fields(&["latest"])), Ok(()));
+        let err = validate(block, &fields(&["latest", "maybe"])).unwrap_err();
+        assert_eq!(err.to_string(), "include_transactions: expected true or false");
+        assert_eq!(validate(find("eth_simulateV1").unwrap(), &fields(&["{}"])), Ok(()));
+        let err = validate(find("eth_call").unwrap(), &fields(&["[1]", "latest"])).unwrap_err();
+        assert_eq!(err.to_string(), "call_object: expected a JSON object");
+    }
+}
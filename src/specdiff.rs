@@ -0,0 +1,232 @@
+// src/specdiff.rs
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::extension;
+use crate::spec::{MethodSpec, RPC_SPECS};
+
+/// How one param differs between two specs of the same method, compared
+/// by position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamChange<'a> {
+    Renamed { index: usize, from: &'a str, to: &'a str },
+    Added { index: usize, name: &'a str, kind: &'static str },
+    Removed { index: usize, name: &'a str },
+    /// Same param, edited as another type (see [`crate::spec::ParamKind::label`]).
+    Retyped { name: &'a str, from: &'static str, to: &'static str },
+}
+
+impl std::fmt::Display for ParamChange<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamChange::Renamed { index, from, to } => write!(f, "param {}: {} renamed to {}", index + 1, from, to),
+            ParamChange::Added { index, name, kind } => write!(f, "param {}: {} ({}) added", index + 1, name, kind),
+            ParamChange::Removed { index, name } => write!(f, "param {}: {} removed", index + 1, name),
+            ParamChange::Retyped { name, from, to } => write!(f, "{}: {} → {}", name, from, to),
+        }
+    }
+}
+
+/// A method both specs have, under the same name or a renamed one, with
+/// its param changes; empty when only the name changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Changed<'a> {
+    pub method: &'a str,
+    /// The name in the second spec, when the method was renamed.
+    pub renamed_to: Option<&'a str>,
+    pub params: Vec<ParamChange<'a>>,
+}
+
+/// What differs between two method lists, each part in the order of the
+/// spec it comes from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecDiff<'a> {
+    pub only_a: Vec<&'a str>,
+    pub only_b: Vec<&'a str>,
+    pub changed: Vec<Changed<'a>>,
+}
+
+/// The params of `b`'s entry for a method compared to `a`'s.
+pub fn param_changes<'a>(a: &'a MethodSpec, b: &'a MethodSpec) -> Vec<ParamChange<'a>> {
+    let mut changes = Vec::new();
+    for index in 0..a.params.len().max(b.params.len()) {
+        match (a.params.get(index), b.params.get(index)) {
+            (Some(from), Some(to)) => {
+                if from != to {
+                    changes.push(ParamChange::Renamed { index, from, to });
+                }
+                let (from_kind, to_kind) = (a.kind(index).label(), b.kind(index).label());
+                if from_kind != to_kind {
+                    changes.push(ParamChange::Retyped { name: to, from: from_kind, to: to_kind });
+                }
+            }
+            (None, Some(name)) => changes.push(ParamChange::Added { index, name, kind: b.kind(index).label() }),
+            (Some(name), None) => changes.push(ParamChange::Removed { index, name }),
+            (None, None) => {}
+        }
+    }
+    changes
+}
+
+/// Compares method lists `a` and `b`. A method only in `a` whose new name
+/// in `renames` (old → new, like [`crate::spec::RENAMED_METHODS`]) is only in
+/// `b` counts as renamed rather than removed and added.
+pub fn diff<'a>(a: &'a [MethodSpec], b: &'a [MethodSpec], renames: &[(&str, &str)]) -> SpecDiff<'a> {
+    let find = |specs: &[MethodSpec], name: &str| specs.iter().position(|m| m.name == name);
+    let renamed = |name: &str| {
+        let new = renames.iter().find(|(old, _)| *old == name)?.1;
+        find(b, new).filter(|_| find(a, new).is_none())
+    };
+    let mut result = SpecDiff::default();
+    let mut matched_b = vec![false; b.len()];
+    for method in a {
+        let (at, renamed_to) = match find(b, &method.name) {
+            Some(at) => (at, None),
+            None => match renamed(&method.name) {
+                Some(at) => (at, Some(&*b[at].name)),
+                None => {
+                    result.only_a.push(&method.name);
+                    continue;
+                }
+            },
+        };
+        matched_b[at] = true;
+        let params = param_changes(method, &b[at]);
+        if renamed_to.is_some() || !params.is_empty() {
+            result.changed.push(Changed { method: &method.name, renamed_to, params });
+        }
+    }
+    result.only_b = b.iter().zip(matched_b).filter(|(_, matched)| !matched).map(|(m, _)| &*m.name).collect();
+    result
+}
+
+impl SpecDiff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.only_a.is_empty() && self.only_b.is_empty() && self.changed.is_empty()
+    }
+
+    /// A plain-text report naming the specs `a` and `b`.
+    pub fn report(&self, a: &str, b: &str) -> String {
+        if self.is_empty() {
+            return format!("{} and {} define the same methods and params\n", a, b);
+        }
+        let mut out = String::new();
+        for (label, methods) in [(a, &self.only_a), (b, &self.only_b)] {
+            if !methods.is_empty() {
+                out.push_str(&format!("only in {} ({}):\n", label, methods.len()));
+                methods.iter().for_each(|m| out.push_str(&format!("  {}\n", m)));
+            }
+        }
+        if !self.changed.is_empty() {
+            out.push_str(&format!("different in {} and {} ({}):\n", a, b, self.changed.len()));
+            for changed in &self.changed {
+                match changed.renamed_to {
+                    Some(to) => out.push_str(&format!("  {} → {}\n", changed.method, to)),
+                    None => out.push_str(&format!("  {}\n", changed.method)),
+                }
+                changed.params.iter().for_each(|p| out.push_str(&format!("    {}\n", p)));
+            }
+        }
+        out
+    }
+}
+
+/// The method list `eli spec diff` names `source`: "builtin", "active"
+/// (the list a session starts with, given as `active`), a spec file, or
+/// either of the first two with a bundled extension merged in, e.g.
+/// "builtin+optimism".
+pub fn resolve(source: &str, active: &[MethodSpec]) -> Result<Vec<MethodSpec>> {
+    let (base, extension) = match source.split_once('+') {
+        Some((base, name)) if matches!(base, "builtin" | "active") => (base, Some(name)),
+        _ => (source, None),
+    };
+    let specs = match base {
+        "builtin" => RPC_SPECS.to_vec(),
+        "active" => active.to_vec(),
+        path => return MethodSpec::load_from_file(Path::new(path)),
+    };
+    let Some(name) = extension else { return Ok(specs) };
+    let extension = extension::bundled().iter().find(|e| e.name == *name).with_context(|| {
+        let names: Vec<_> = extension::bundled().iter().map(|e| e.name.as_str()).collect();
+        format!("no spec extension named '{}' (bundled: {})", name, names.join(", "))
+    })?;
+    Ok(extension::merge(&specs, extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::{parse_document, ParamKind};
+
+    fn specs(document: &str) -> Vec<MethodSpec> {
+        parse_document(document).unwrap()
+    }
+
+    #[test]
+    fn methods_only_on_one_side_are_listed_in_order() {
+        let a = specs(r#"[{"name": "eth_chainId"}, {"name": "eth_mining"}, {"name": "eth_hashrate"}]"#);
+        let b = specs(r#"[{"name": "eth_chainId"}, {"name": "debug_traceTransaction"}]"#);
+        let diff = diff(&a, &b, &[]);
+        assert_eq!(diff.only_a, ["eth_mining", "eth_hashrate"]);
+        assert_eq!(diff.only_b, ["debug_traceTransaction"]);
+        assert!(diff.changed.is_empty());
+        assert!(super::diff(&a, &a, &[]).is_empty());
+    }
+
+    #[test]
+    fn param_changes_are_named_per_param() {
+        let a = specs(
+            r#"[{"name": "eth_getBlockByNumber", "params": [{"name": "block"}, {"name": "full"}]},
+                {"name": "eth_getBalance", "params": [{"name": "address"}, {"name": "block"}]},
+                {"name": "eth_call", "params": [{"name": "tx"}, {"name": "block"}, {"name": "overrides"}]}]"#,
+        );
+        let b = specs(
+            r#"[{"name": "eth_getBlockByNumber", "params": [{"name": "block"}, {"name": "full", "schema": {"type": "boolean"}}]},
+                {"name": "eth_getBalance", "params": [{"name": "address"}, {"name": "blockTag"}, {"name": "proof"}]},
+                {"name": "eth_call", "params": [{"name": "tx"}]}]"#,
+        );
+        let diff = diff(&a, &b, &[]);
+        let changes: Vec<(&str, Vec<String>)> =
+            diff.changed.iter().map(|c| (c.method, c.params.iter().map(|p| p.to_string()).collect())).collect();
+        assert_eq!(
+            changes,
+            [
+                ("eth_getBlockByNumber", vec!["full: text → bool".to_string()]),
+                ("eth_getBalance", vec!["param 2: block renamed to blockTag".into(), "param 3: proof (text) added".into()]),
+                ("eth_call", vec!["param 2: block removed".into(), "param 3: overrides removed".into()]),
+            ]
+        );
+        assert_eq!(b[0].kind(1), ParamKind::Bool { default: false, on: "true", off: "false" });
+    }
+
+    #[test]
+    fn renamed_methods_pair_up_with_their_new_name() {
+        let a = specs(r#"[{"name": "eth_getProof", "params": [{"name": "address"}]}, {"name": "eth_old"}]"#);
+        let b = specs(r#"[{"name": "eth_getProof", "params": [{"name": "address"}]}, {"name": "eth_new"}]"#);
+        let diff = diff(&a, &b, &[("eth_old", "eth_new")]);
+        assert!(diff.only_a.is_empty() && diff.only_b.is_empty());
+        assert_eq!(diff.changed, [Changed { method: "eth_old", renamed_to: Some("eth_new"), params: Vec::new() }]);
+        assert_eq!(
+            diff.report("builtin", "node.json"),
+            "different in builtin and node.json (1):\n  eth_old → eth_new\n"
+        );
+        // Without the rename, they are unrelated methods
+        let diff = super::diff(&a, &b, &[]);
+        assert_eq!((diff.only_a, diff.only_b), (vec!["eth_old"], vec!["eth_new"]));
+    }
+
+    #[test]
+    fn sources_resolve_with_extensions() {
+        let builtin = resolve("builtin", &[]).unwrap();
+        assert_eq!(builtin.len(), RPC_SPECS.len());
+        let optimism = resolve("builtin+optimism", &[]).unwrap();
+        let report = diff(&builtin, &optimism, &[]).report("builtin", "builtin+optimism");
+        assert!(report.contains("only in builtin+optimism"), "{}", report);
+        assert!(report.contains("optimism_syncStatus"), "{}", report);
+        assert!(resolve("active", &builtin[..2]).unwrap().len() == 2);
+        assert!(resolve("builtin+scroll", &[]).unwrap_err().to_string().contains("bundled: optimism"));
+        assert!(resolve("/nonexistent/eli-spec.json", &[]).is_err());
+    }
+}
@@ -0,0 +1,383 @@
+// src/spill.rs
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::pretty;
+use crate::rpc::JsonRpcResponse;
+
+/// Results with more values than this are written to disk (see [`write`])
+/// instead of being kept in history.
+pub const SPILL_LIMIT: usize = 5 * pretty::INLINE_LIMIT;
+
+/// Bytes read from disk at a time while indexing.
+const CHUNK: usize = 64 * 1024;
+
+/// Longest object key kept in the index; longer ones are cut.
+const MAX_KEY: usize = 256;
+
+/// Kind of the value an index covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    Array,
+    Object,
+    /// A string, number, bool or null: nothing to page through.
+    Scalar,
+}
+
+/// Writes `response` to `<name>.json` under `dir`, creating it, for a
+/// [`SpillIndex`] to page through later; returns the file.
+pub fn write(dir: &Path, name: &str, response: &JsonRpcResponse) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    let path = dir.join(format!("{}.json", name));
+    let file = File::create(&path).with_context(|| format!("cannot create {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    serde_json::to_writer(&mut out, response)?;
+    out.flush().with_context(|| format!("cannot write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Removes the spill files in `dir` that aren't in `keep`, e.g. those of
+/// entries trimmed from history; returns how many went.
+pub fn sweep(dir: &Path, keep: &[&Path]) -> Result<usize> {
+    let read = match std::fs::read_dir(dir) {
+        Ok(read) => read,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e).with_context(|| format!("cannot read {}", dir.display())),
+    };
+    let mut removed = 0;
+    for file in read {
+        let path = file?.path();
+        if path.extension().is_some_and(|ext| ext == "json") && !keep.contains(&path.as_path()) {
+            std::fs::remove_file(&path).with_context(|| format!("cannot remove {}", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Where one element of the indexed value sits in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Member name, for objects.
+    pub key: Option<String>,
+    /// Byte range of the element's JSON text.
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Offsets of the elements of a response written to disk, so a viewer can
+/// show its outline and parse single elements on demand instead of reading
+/// the whole file into memory.
+///
+/// The indexed value is the `result` of a JSON-RPC response, or the whole
+/// document when it isn't one. Building the index reads the file once, in
+/// [`CHUNK`]-sized pieces, keeping only the offsets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpillIndex {
+    pub path: PathBuf,
+    pub container: Container,
+    pub entries: Vec<Entry>,
+}
+
+/// A container open while scanning.
+struct Frame {
+    object: bool,
+    /// In an object, before the next member's name.
+    expect_key: bool,
+    key: Option<String>,
+    /// Start of the element being read.
+    value_start: Option<u64>,
+    /// Whether this frame's elements go into the index.
+    record: bool,
+    entries: Vec<Entry>,
+}
+
+impl Frame {
+    fn close_element(&mut self, end: u64) {
+        if let Some(start) = self.value_start.take()
+            && self.record
+        {
+            self.entries.push(Entry { key: self.key.take(), start, end });
+        }
+    }
+}
+
+impl SpillIndex {
+    /// Scans `path` and indexes the elements of its response value.
+    pub fn build(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+        let mut reader = BufReader::with_capacity(CHUNK, file);
+        let mut chunk = vec![0u8; CHUNK];
+        let mut stack: Vec<Frame> = Vec::new();
+        // Top-level children, and the children of a top-level "result"
+        let mut top: Option<(Container, Vec<Entry>)> = None;
+        let mut result: Option<(Container, Vec<Entry>)> = None;
+        let (mut in_string, mut escape) = (false, false);
+        let mut key: Option<Vec<u8>> = None;
+        let (mut pos, mut last) = (0u64, 0u64);
+        loop {
+            let read = reader.read(&mut chunk).with_context(|| format!("cannot read {}", path.display()))?;
+            if read == 0 {
+                break;
+            }
+            for &byte in &chunk[..read] {
+                if in_string {
+                    if escape {
+                        escape = false;
+                    } else if byte == b'\\' {
+                        escape = true;
+                    } else if byte == b'"' {
+                        in_string = false;
+                        if let (Some(name), Some(frame)) = (key.take(), stack.last_mut()) {
+                            frame.key = Some(unescape(&name));
+                        }
+                    }
+                    if let Some(name) = key.as_mut().filter(|n| n.len() < MAX_KEY && in_string) {
+                        name.push(byte);
+                    }
+                    last = pos;
+                    pos += 1;
+                    continue;
+                }
+                match byte {
+                    b' ' | b'\t' | b'\n' | b'\r' => {
+                        pos += 1;
+                        continue;
+                    }
+                    b',' => {
+                        let Some(frame) = stack.last_mut() else {
+                            bail!("{}: unexpected ',' at byte {}", path.display(), pos)
+                        };
+                        frame.close_element(last + 1);
+                        frame.expect_key = frame.object;
+                    }
+                    b':' => {
+                        if let Some(frame) = stack.last_mut() {
+                            frame.expect_key = false;
+                        }
+                    }
+                    b'}' | b']' => {
+                        let Some(mut frame) = stack.pop() else {
+                            bail!("{}: unbalanced '{}' at byte {}", path.display(), byte as char, pos)
+                        };
+                        frame.close_element(last + 1);
+                        let kind = if frame.object { Container::Object } else { Container::Array };
+                        let slot = if stack.is_empty() { &mut top } else { &mut result };
+                        if frame.record {
+                            *slot = Some((kind, frame.entries));
+                        }
+                    }
+                    _ => {
+                        let expecting_key = stack.last().is_some_and(|f| f.expect_key);
+                        if expecting_key && byte == b'"' {
+                            key = Some(Vec::new());
+                            in_string = true;
+                        } else {
+                            if let Some(frame) = stack.last_mut() {
+                                frame.value_start.get_or_insert(pos);
+                            }
+                            match byte {
+                                b'"' => in_string = true,
+                                b'{' | b'[' => {
+                                    // Only the top level and the response's result are indexed
+                                    let record = match stack.as_slice() {
+                                        [] => true,
+                                        [parent] => parent.object && parent.key.as_deref() == Some("result"),
+                                        _ => false,
+                                    };
+                                    let object = byte == b'{';
+                                    let (key, value_start, entries) = (None, None, Vec::new());
+                                    stack.push(Frame { object, expect_key: object, key, value_start, record, entries });
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                last = pos;
+                pos += 1;
+            }
+        }
+        if in_string || !stack.is_empty() {
+            bail!("{} ends in the middle of a value", path.display());
+        }
+        let is_response = |entries: &[Entry]| entries.iter().any(|e| e.key.as_deref() == Some("result"));
+        let (container, entries) = match (top, result) {
+            (_, Some(result)) => result,
+            // A result that is a string or number has no elements
+            (Some((Container::Object, entries)), None) if is_response(&entries) => (Container::Scalar, Vec::new()),
+            (Some(top), None) => top,
+            (None, None) => (Container::Scalar, Vec::new()),
+        };
+        Ok(SpillIndex { path: path.to_path_buf(), container, entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Parses element `index` from disk, reading only its bytes.
+    pub fn load(&self, index: usize) -> Result<Value> {
+        let mut file = File::open(&self.path).with_context(|| format!("cannot open {}", self.path.display()))?;
+        self.load_from(&mut file, index)
+    }
+
+    fn load_from(&self, file: &mut File, index: usize) -> Result<Value> {
+        let Some(entry) = self.entries.get(index) else {
+            bail!("{} has no element {} (it has {})", self.path.display(), index, self.entries.len())
+        };
+        file.seek(SeekFrom::Start(entry.start))?;
+        let mut text = vec![0u8; (entry.end - entry.start) as usize];
+        file.read_exact(&mut text).with_context(|| format!("{} changed since it was indexed", self.path.display()))?;
+        serde_json::from_slice(&text).with_context(|| format!("malformed element {} in {}", index, self.path.display()))
+    }
+
+    /// Parses up to `count` elements from `start`, for one page of a view.
+    pub fn page(&self, start: usize, count: usize) -> Result<Vec<Value>> {
+        let mut file = File::open(&self.path).with_context(|| format!("cannot open {}", self.path.display()))?;
+        (start..(start + count).min(self.len())).map(|i| self.load_from(&mut file, i)).collect()
+    }
+
+    /// Calls `f` with every element in order, holding one at a time, e.g.
+    /// to export a response without reading it whole.
+    pub fn for_each(&self, mut f: impl FnMut(usize, Value) -> Result<()>) -> Result<()> {
+        let mut file = File::open(&self.path).with_context(|| format!("cannot open {}", self.path.display()))?;
+        for i in 0..self.len() {
+            f(i, self.load_from(&mut file, i)?)?;
+        }
+        Ok(())
+    }
+
+    /// How element `index` is named in an outline: `"logs":` or `[3]`.
+    pub fn name(&self, index: usize) -> Option<String> {
+        Some(match &self.entries.get(index)?.key {
+            Some(key) => format!("{:?}:", key),
+            None => format!("[{}]", index),
+        })
+    }
+
+    /// The outline line of element `index`, without parsing it, e.g.
+    /// `"logs": …2.0 KiB` or `[3] …512 B`.
+    pub fn outline(&self, index: usize) -> Option<String> {
+        let entry = self.entries.get(index)?;
+        Some(format!("{} …{}", self.name(index)?, format_size(entry.end - entry.start)))
+    }
+}
+
+/// A member name as read from the file, escapes resolved when possible.
+fn unescape(raw: &[u8]) -> String {
+    let raw = String::from_utf8_lossy(raw);
+    serde_json::from_str(&format!("\"{}\"", raw)).unwrap_or_else(|_| raw.into_owned())
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::{BufWriter, Write};
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("eli-spill-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("response.json")
+    }
+
+    fn log(i: usize) -> Value {
+        json!({"logIndex": format!("0x{:x}", i), "data": "0x\"],{", "topics": [format!("0x{:064x}", i)]})
+    }
+
+    #[test]
+    fn indexes_and_pages_a_100k_element_result_from_disk() {
+        let path = temp_file("100k");
+        // Written element by element: the fixture is never whole in memory either
+        let mut out = BufWriter::new(File::create(&path).unwrap());
+        write!(out, "{{\"jsonrpc\": \"2.0\", \"id\": 7, \"result\": [").unwrap();
+        for i in 0..100_000 {
+            let sep = if i == 0 { "\n  " } else { ",\n  " };
+            write!(out, "{}{}", sep, log(i)).unwrap();
+        }
+        writeln!(out, "\n]}}").unwrap();
+        out.flush().unwrap();
+        drop(out);
+
+        let index = SpillIndex::build(&path).unwrap();
+        assert_eq!((index.container, index.len()), (Container::Array, 100_000));
+        assert_eq!(index.load(0).unwrap(), log(0));
+        assert_eq!(index.load(99_999).unwrap(), log(99_999));
+        assert_eq!(index.page(50_000, 3).unwrap(), [log(50_000), log(50_001), log(50_002)]);
+        assert_eq!(index.page(99_998, 10).unwrap().len(), 2);
+        assert!(index.load(100_000).is_err());
+        assert!(index.outline(5).unwrap().starts_with("[5] …"));
+
+        let mut seen = 0;
+        index.for_each(|i, value| {
+            assert_eq!(value["logIndex"], format!("0x{:x}", i));
+            seen += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(seen, 100_000);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn objects_keep_their_member_names() {
+        let path = temp_file("object");
+        let block = json!({"number": "0x10", "transactions": [{"hash": "0x1"}], "extra\"Data": "0x{}"});
+        std::fs::write(&path, json!({"jsonrpc": "2.0", "id": 1, "result": block}).to_string()).unwrap();
+        let index = SpillIndex::build(&path).unwrap();
+        assert_eq!(index.container, Container::Object);
+        let keys: Vec<_> = index.entries.iter().map(|e| e.key.clone().unwrap()).collect();
+        let values: Vec<_> = (0..index.len()).map(|i| index.load(i).unwrap()).collect();
+        for (key, value) in keys.iter().zip(&values) {
+            assert_eq!(&block[key], value);
+        }
+        assert_eq!(keys.len(), 3);
+        assert!(index.outline(0).unwrap().contains(": …"));
+
+        // Not a JSON-RPC response: the document itself is indexed
+        std::fs::write(&path, "[1, \"two\", [3]]").unwrap();
+        let index = SpillIndex::build(&path).unwrap();
+        assert_eq!(index.page(0, 5).unwrap(), [json!(1), json!("two"), json!([3])]);
+
+        std::fs::write(&path, "{\"id\": 1, \"result\": \"0x10\"}").unwrap();
+        assert_eq!(SpillIndex::build(&path).unwrap().container, Container::Scalar);
+
+        std::fs::write(&path, "{\"result\": [1, 2").unwrap();
+        assert!(SpillIndex::build(&path).unwrap_err().to_string().contains("middle of a value"));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn written_responses_index_and_unkept_ones_are_swept() {
+        let dir = temp_file("write").parent().unwrap().join("spill");
+        let response = |id| JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!([log(0), log(1)])), error: None, id };
+        let kept = write(&dir, "1-1", &response(1)).unwrap();
+        let stale = write(&dir, "1-2", &response(2)).unwrap();
+        let index = SpillIndex::build(&kept).unwrap();
+        assert_eq!(index.page(0, 2).unwrap(), [log(0), log(1)]);
+        assert_eq!(index.name(1).as_deref(), Some("[1]"));
+
+        assert_eq!(sweep(&dir, &[&kept]).unwrap(), 1);
+        assert!(kept.exists() && !stale.exists());
+        assert_eq!(sweep(&dir.join("missing"), &[]).unwrap(), 0);
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+}
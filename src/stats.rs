@@ -0,0 +1,206 @@
+// src/stats.rs
+
+use std::collections::VecDeque;
+use std::sync::{Arc, PoisonError, RwLock, TryLockError};
+
+use crate::head::Head;
+use crate::sync::SyncState;
+
+/// Readings about the session endpoint that background tasks produce and
+/// the screen only displays.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Result of the last `eth_syncing` probe.
+    pub sync: SyncState,
+    /// Latest block seen by the head poll.
+    pub head: Option<Head>,
+    /// Whether the endpoint answered the last request made to it.
+    pub health: Health,
+}
+
+/// Connectivity of the session endpoint, from the head poll and from
+/// user sends to it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Health {
+    /// Nothing has been sent to it yet.
+    #[default]
+    Unknown,
+    Up,
+    /// The last request failed to get an answer, with why.
+    Down(String),
+}
+
+impl Health {
+    /// `Down` with the innermost cause of `error`, e.g. "Connection
+    /// refused (os error 111)", which is shorter than the whole chain.
+    pub fn down(error: &anyhow::Error) -> Self {
+        Health::Down(error.root_cause().to_string())
+    }
+}
+
+/// [`Stats`] shared between background tasks and the event loop.
+///
+/// Ownership is split like this:
+/// - `App` and everything the user edits (inputs, modes, history, views)
+///   is owned by the event loop and only ever touched from it;
+/// - `Stats` is written by the probe and poll tasks directly, through a
+///   clone of this handle, each write a short closure under the lock;
+/// - the event loop reads it through a [`StatsView`], never blocking;
+/// - latency samples live in the client's
+///   [`LatencyRecorder`](crate::middleware::LatencyRecorder), which the
+///   middleware appends to under its own short lock.
+#[derive(Debug, Clone, Default)]
+pub struct SharedStats(Arc<RwLock<Stats>>);
+
+impl SharedStats {
+    /// Applies `write` under the lock; keep it short, readers are waiting.
+    pub fn update(&self, write: impl FnOnce(&mut Stats)) {
+        write(&mut self.0.write().unwrap_or_else(PoisonError::into_inner));
+    }
+
+    /// A copy of the current stats, or `None` if a writer holds the lock.
+    pub fn try_snapshot(&self) -> Option<Stats> {
+        match self.0.try_read() {
+            Ok(stats) => Some(stats.clone()),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner().clone()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+}
+
+/// The event loop's side of [`SharedStats`]: the last snapshot it managed
+/// to take. Drawing reads only that copy, so it never waits on a writer.
+#[derive(Debug, Default)]
+pub struct StatsView {
+    shared: SharedStats,
+    last: Stats,
+}
+
+impl StatsView {
+    /// Handle for background tasks to write through.
+    pub fn shared(&self) -> &SharedStats {
+        &self.shared
+    }
+
+    /// Takes a fresh snapshot unless a writer is busy, in which case the
+    /// previous one stays; called once per tick.
+    pub fn refresh(&mut self) -> &Stats {
+        if let Some(stats) = self.shared.try_snapshot() {
+            self.last = stats;
+        }
+        &self.last
+    }
+
+    /// The last snapshot taken.
+    pub fn last(&self) -> &Stats {
+        &self.last
+    }
+}
+
+/// The last `capacity` samples of something measured over and over, for
+/// percentiles over a sliding window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rolling<T> {
+    samples: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T: Copy + Ord> Rolling<T> {
+    pub fn new(capacity: usize) -> Self {
+        Rolling { samples: VecDeque::new(), capacity: capacity.max(1) }
+    }
+
+    /// Adds `sample`, dropping the oldest once full.
+    pub fn push(&mut self, sample: T) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The nearest-rank `p`th percentile (0 to 100) of the window.
+    pub fn percentile(&self, p: f64) -> Option<T> {
+        let mut sorted: Vec<T> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = ((p.clamp(0.0, 100.0) / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.saturating_sub(1)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_keeps_the_last_snapshot_while_a_writer_holds_the_lock() {
+        let mut view = StatsView::default();
+        view.shared().update(|s| s.sync = SyncState::Synced);
+        assert_eq!(view.refresh().sync, SyncState::Synced);
+
+        let shared = view.shared().clone();
+        let guard = shared.0.write().unwrap();
+        assert_eq!(view.refresh().sync, SyncState::Synced);
+        drop(guard);
+    }
+
+    #[test]
+    fn concurrent_writers_never_expose_a_torn_update() {
+        let stats = SharedStats::default();
+        let writers: Vec<_> = (0..4u64)
+            .map(|t| {
+                let stats = stats.clone();
+                std::thread::spawn(move || {
+                    for i in 0..1000 {
+                        let n = t * 1000 + i;
+                        // Both fields carry `n`: a reader must never see them differ
+                        stats.update(|s| {
+                            s.head = Some(Head { number: n, timestamp: Some(n), observed: n });
+                            s.sync = SyncState::Syncing { current: n, highest: n };
+                        });
+                    }
+                })
+            })
+            .collect();
+        let mut view = StatsView { shared: stats.clone(), last: Stats::default() };
+        while writers.iter().any(|w| !w.is_finished()) {
+            let snapshot = view.refresh();
+            if let (Some(head), SyncState::Syncing { current, .. }) = (snapshot.head, snapshot.sync) {
+                assert_eq!((head.number, head.observed), (current, current));
+            }
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        let last = view.refresh();
+        let SyncState::Syncing { current, .. } = last.sync else { panic!("{:?}", last.sync) };
+        assert_eq!(last.head.map(|h| h.number), Some(current));
+    }
+
+    #[test]
+    fn rolling_percentiles_cover_the_window_only() {
+        let mut window = Rolling::new(100);
+        assert_eq!(window.percentile(95.0), None);
+        for n in 1..=100u64 {
+            window.push(n);
+        }
+        assert_eq!((window.percentile(95.0), window.percentile(50.0), window.percentile(100.0)), (Some(95), Some(50), Some(100)));
+        assert_eq!(window.percentile(0.0), Some(1));
+        // Older samples fall out as new ones come in
+        for _ in 0..100 {
+            window.push(7);
+        }
+        assert_eq!((window.len(), window.percentile(95.0)), (100, Some(7)));
+
+        let mut small = Rolling::new(3);
+        [30, 10, 20, 40].into_iter().for_each(|n| small.push(n));
+        assert_eq!((small.percentile(95.0), small.percentile(34.0)), (Some(40), Some(20)));
+    }
+}
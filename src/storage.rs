@@ -0,0 +1,575 @@
+// src/storage.rs
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+
+use crate::schema::{self, Schema};
+
+/// A loaded file, with a warning when it came from the backup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loaded<T> {
+    pub value: T,
+    pub warning: Option<String>,
+    /// The older version the file was upgraded from, if it was.
+    pub migrated_from: Option<u64>,
+}
+
+impl<T> Loaded<T> {
+    /// A value that wasn't read from disk (e.g. defaults for a missing file).
+    pub fn fresh(value: T) -> Self {
+        Loaded { value, warning: None, migrated_from: None }
+    }
+}
+
+/// `<path>.bak`: the previous version kept by [`write_atomic`].
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, "", ".bak")
+}
+
+/// `<path>.v<version>.bak`: the file as it was before being upgraded from
+/// `version` by [`load_versioned`].
+pub fn migration_backup_path(path: &Path, version: u64) -> PathBuf {
+    sibling(path, "", &format!(".v{}.bak", version))
+}
+
+fn sibling(path: &Path, prefix: &str, suffix: &str) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}{}{}", prefix, name, suffix))
+}
+
+/// Replaces `path` with `contents` so that a crash leaves either the old or
+/// the new file, never a truncated one.
+///
+/// The data is written to a temp file in the same directory and fsynced,
+/// the current file (if any) is copied to `<path>.bak`, and the temp file
+/// is renamed over `path`. Missing parent directories are created.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+
+    let temp = sibling(path, ".", ".tmp");
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp);
+        return Err(e).with_context(|| format!("cannot write {}", temp.display()));
+    }
+
+    if path.exists() {
+        std::fs::copy(path, backup_path(path)).with_context(|| format!("cannot back up {}", path.display()))?;
+    }
+    std::fs::rename(&temp, path).with_context(|| format!("cannot replace {}", path.display()))?;
+    // Persist the rename itself; not every platform can open a directory
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("malformed {}", path.display()))
+}
+
+/// Reads a JSON file written by [`write_atomic`]; `Ok(None)` if it doesn't exist.
+///
+/// If the file can't be read or parsed but its `.bak` can, the backup is
+/// returned with a warning. Otherwise the original error is.
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<Option<Loaded<T>>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let err = match read_json(path) {
+        Ok(value) => return Ok(Some(Loaded::fresh(value))),
+        Err(e) => e,
+    };
+    let backup = backup_path(path);
+    match read_json(&backup) {
+        Ok(value) => {
+            let warning = format!("{:#}; restored the previous version from {}", err, backup.display());
+            Ok(Some(Loaded { value, warning: Some(warning), migrated_from: None }))
+        }
+        Err(_) => Err(err),
+    }
+}
+
+/// Reads a JSON file versioned by `schema`; `Ok(None)` if it doesn't exist.
+///
+/// A file at the current version is deserialized as is. An older one is
+/// upgraded step by step, the original kept as `<path>.v<N>.bak`, and the
+/// upgraded file written back so the next start doesn't redo it. A file
+/// from a newer eli is an error. Falls back to `.bak` like [`load_json`].
+pub fn load_versioned<T: DeserializeOwned>(path: &Path, schema: &Schema) -> Result<Option<Loaded<T>>> {
+    let Some(Loaded { value, warning, .. }) = load_json::<Value>(path)? else { return Ok(None) };
+    let version = schema::version_of(&value).with_context(|| format!("malformed {}", path.display()))?;
+    let mut migrated_from = None;
+    let value = if version == schema.current() {
+        value
+    } else {
+        let backup = migration_backup_path(path, version);
+        let upgraded = schema::upgrade(schema, value.clone(), version)?;
+        std::fs::write(&backup, serde_json::to_string_pretty(&value)?)
+            .with_context(|| format!("cannot write {}", backup.display()))?;
+        write_atomic(path, serde_json::to_string_pretty(&upgraded)?.as_bytes())?;
+        migrated_from = Some(version);
+        upgraded
+    };
+    let value = serde_json::from_value(schema::unstamp(value)).with_context(|| format!("malformed {}", path.display()))?;
+    Ok(Some(Loaded { value, warning, migrated_from }))
+}
+
+/// A state file that carries a revision, bumped on every save, so an
+/// instance can tell another one saved since it loaded.
+pub trait Revisioned {
+    fn revision(&self) -> u64;
+    fn set_revision(&mut self, revision: u64);
+}
+
+/// How a save of a [`Revisioned`] file went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Saved {
+    /// The revision now on disk.
+    pub revision: u64,
+    /// Another instance had saved since `ours` was loaded; its changes
+    /// were merged in rather than overwritten.
+    pub merged: bool,
+}
+
+/// Saves `ours`, which was loaded at revision `loaded`, with the next
+/// revision and the current `schema` version. If the file on disk has a
+/// newer revision, another instance saved in between: `merge(theirs, ours)`
+/// decides what is written, so its changes aren't lost. A file from a newer
+/// eli is left alone. Returns what was written.
+pub fn save_revisioned<T>(
+    path: &Path,
+    schema: &Schema,
+    loaded: u64,
+    ours: T,
+    merge: impl FnOnce(T, T) -> Result<T>,
+) -> Result<(T, Saved)>
+where
+    T: Revisioned + Serialize + DeserializeOwned,
+{
+    let on_disk = load_versioned::<T>(path, schema)?.map(|l| l.value);
+    let newer = on_disk.filter(|theirs| theirs.revision() > loaded);
+    let merged = newer.is_some();
+    let base = newer.as_ref().map(|theirs| theirs.revision()).unwrap_or(loaded);
+    let mut value = match newer {
+        Some(theirs) => merge(theirs, ours)?,
+        None => ours,
+    };
+    value.set_revision(base + 1);
+    let stamped = schema::stamp(serde_json::to_value(&value)?, schema.current());
+    write_atomic(path, serde_json::to_string_pretty(&stamped)?.as_bytes())?;
+    Ok((value, Saved { revision: base + 1, merged }))
+}
+
+// Merge rules, by the shape of the file.
+
+/// Settings-shaped files: fields `ours` changed from `base` (what was
+/// loaded) win, every other field keeps `theirs`. Objects are merged field
+/// by field, recursively; anything else is taken whole.
+pub fn merge_changed(base: &Value, ours: Value, theirs: Value) -> Value {
+    match (base, ours, theirs) {
+        (Value::Object(base), Value::Object(ours), Value::Object(mut theirs)) => {
+            for (key, value) in ours {
+                let merged = match (base.get(key.as_str()), theirs.remove(key.as_str())) {
+                    (Some(b), Some(t)) => merge_changed(b, value, t),
+                    // Added by us, or by both: ours
+                    (None, _) => value,
+                    // Removed by them: keep it only if we changed it
+                    (Some(b), None) if *b == value => continue,
+                    (Some(_), None) => value,
+                };
+                theirs.insert(key, merged);
+            }
+            Value::Object(theirs)
+        }
+        (base, ours, theirs) => {
+            if ours != *base {
+                ours
+            } else {
+                theirs
+            }
+        }
+    }
+}
+
+/// Set-shaped files (e.g. the address book's entries): everything either
+/// side has, theirs first, without duplicates.
+pub fn merge_union<T: PartialEq>(theirs: Vec<T>, ours: Vec<T>) -> Vec<T> {
+    let mut merged = theirs;
+    for item in ours {
+        if !merged.contains(&item) {
+            merged.push(item);
+        }
+    }
+    merged
+}
+
+// Endpoint leases, so local instances can tell they share a connection.
+
+/// Seconds a lease stays valid without being renewed, in case its holder
+/// died where [`pid_alive`] can't tell.
+pub const LEASE_TTL: u64 = 5 * 60;
+
+/// Who holds the subscription transport to an endpoint, as written in its
+/// lease file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lease {
+    pub endpoint: String,
+    pub pid: u32,
+    /// When the holder last renewed it (unix seconds).
+    pub renewed_at: u64,
+}
+
+impl Lease {
+    /// Whether the lease no longer counts: its process is gone, or it
+    /// wasn't renewed within [`LEASE_TTL`].
+    pub fn is_stale(&self, now: u64, alive: impl Fn(u32) -> bool) -> bool {
+        !alive(self.pid) || now.saturating_sub(self.renewed_at) > LEASE_TTL
+    }
+
+    /// What a second instance tells the user before subscribing anyway or
+    /// falling back to polling.
+    pub fn in_use_message(&self) -> String {
+        format!("subscription transport in use by another eli instance (pid {})", self.pid)
+    }
+}
+
+/// `<dir>/leases/<hash>.json`: the lease file for `endpoint`, named by a
+/// hash so any URL makes a valid file name.
+pub fn lease_path(dir: &Path, endpoint: &str) -> PathBuf {
+    let hash = Keccak256::digest(endpoint.as_bytes());
+    let name: String = hash[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    dir.join("leases").join(format!("{}.json", name))
+}
+
+/// Takes or renews the lease on `endpoint` for `pid`. Returns the current
+/// holder instead when another live instance has it; stale leases are
+/// taken over. Of two instances racing for a free lease, only one wins.
+pub fn acquire_lease(dir: &Path, endpoint: &str, pid: u32, now: u64, alive: impl Fn(u32) -> bool) -> Result<Option<Lease>> {
+    let path = lease_path(dir, endpoint);
+    let lease = Lease { endpoint: endpoint.to_string(), pid, renewed_at: now };
+    for _ in 0..2 {
+        match read_json::<Lease>(&path) {
+            Ok(held) if held.pid == pid => return write_lease(&path, &lease, false).map(|()| None),
+            Ok(held) if !held.is_stale(now, &alive) => return Ok(Some(held)),
+            // Stale, or unreadable (a holder crashed mid-write)
+            Ok(_) | Err(_) if path.exists() => {
+                let _ = std::fs::remove_file(&path);
+            }
+            _ => {}
+        }
+        match write_lease(&path, &lease, true) {
+            Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::AlreadyExists) => continue,
+            written => return written.map(|()| None),
+        }
+    }
+    // Lost the race to another instance
+    Ok(read_json::<Lease>(&path).ok())
+}
+
+/// Takes the lease on `endpoint` whoever holds it, for "proceed anyway".
+pub fn take_lease(dir: &Path, endpoint: &str, pid: u32, now: u64) -> Result<()> {
+    write_lease(&lease_path(dir, endpoint), &Lease { endpoint: endpoint.to_string(), pid, renewed_at: now }, false)
+}
+
+/// Gives up `pid`'s lease on `endpoint`; someone else's is left alone.
+pub fn release_lease(dir: &Path, endpoint: &str, pid: u32) -> Result<()> {
+    let path = lease_path(dir, endpoint);
+    if read_json::<Lease>(&path).is_ok_and(|held| held.pid == pid) {
+        std::fs::remove_file(&path).with_context(|| format!("cannot remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// The lease on an endpoint while this process holds it; dropping it
+/// gives the lease up.
+#[derive(Debug)]
+pub struct HeldLease {
+    dir: PathBuf,
+    endpoint: String,
+    pid: u32,
+    renewed_at: u64,
+}
+
+impl HeldLease {
+    /// Renews the lease once a third of [`LEASE_TTL`] has passed since it
+    /// was last renewed. A lease another instance took meanwhile ("proceed
+    /// anyway") is left with it.
+    pub fn renew(&mut self, now: u64) -> Result<()> {
+        if now.saturating_sub(self.renewed_at) < LEASE_TTL / 3 {
+            return Ok(());
+        }
+        self.renewed_at = now;
+        acquire_lease(&self.dir, &self.endpoint, self.pid, now, pid_alive).map(|_| ())
+    }
+}
+
+impl Drop for HeldLease {
+    fn drop(&mut self) {
+        let _ = release_lease(&self.dir, &self.endpoint, self.pid);
+    }
+}
+
+/// Holds the lease on `endpoint` for `pid` until the returned
+/// [`HeldLease`] is dropped. When another live instance has it, returns
+/// that instance's lease instead, unless `force` takes it anyway.
+pub fn hold_lease(
+    dir: &Path,
+    endpoint: &str,
+    pid: u32,
+    now: u64,
+    force: bool,
+    alive: impl Fn(u32) -> bool,
+) -> Result<std::result::Result<HeldLease, Lease>> {
+    if force {
+        take_lease(dir, endpoint, pid, now)?;
+    } else if let Some(holder) = acquire_lease(dir, endpoint, pid, now, alive)? {
+        return Ok(Err(holder));
+    }
+    Ok(Ok(HeldLease { dir: dir.to_path_buf(), endpoint: endpoint.to_string(), pid, renewed_at: now }))
+}
+
+/// Writes a lease file; `new` fails if one already exists.
+fn write_lease(path: &Path, lease: &Lease, new: bool) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    }
+    let mut file = OpenOptions::new().write(true).create(true).create_new(new).truncate(true).open(path)?;
+    file.write_all(&serde_json::to_vec(lease)?).with_context(|| format!("cannot write {}", path.display()))
+}
+
+/// Whether process `pid` is running, asked with a null signal on unix: a
+/// process we may not signal still exists.
+#[cfg(unix)]
+pub fn pid_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else { return false };
+    // 0 and negative pids address process groups, not one process
+    if pid <= 0 {
+        return false;
+    }
+    // SAFETY: signal 0 only checks that the process exists
+    unsafe { libc::kill(pid, 0) == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM) }
+}
+
+/// Whether process `pid` is running. Windows can't tell without another
+/// dependency, so every holder counts as alive there and leases expire
+/// by [`LEASE_TTL`] alone.
+#[cfg(not(unix))]
+pub fn pid_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("eli-storage-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn replaces_and_keeps_previous_version() {
+        let dir = temp_dir("write");
+        let path = dir.join("state.json");
+        write_atomic(&path, b"{\"v\": 1}").unwrap();
+        assert!(!backup_path(&path).exists());
+
+        write_atomic(&path, b"{\"v\": 2}").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"v\": 2}");
+        assert_eq!(std::fs::read_to_string(backup_path(&path)).unwrap(), "{\"v\": 1}");
+        // No temp file left behind
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn falls_back_to_backup_on_parse_failure() {
+        let dir = temp_dir("fallback");
+        let path = dir.join("state.json");
+        write_atomic(&path, b"{\"v\": 1}").unwrap();
+        write_atomic(&path, b"{\"v\": 2}").unwrap();
+        // Simulate a truncated write
+        std::fs::write(&path, "{\"v\":").unwrap();
+
+        let loaded: Loaded<Value> = load_json(&path).unwrap().unwrap();
+        assert_eq!(loaded.value, json!({"v": 1}));
+        let warning = loaded.warning.unwrap();
+        assert!(warning.contains("malformed") && warning.contains("state.json.bak"), "{}", warning);
+
+        // Without a usable backup the parse error surfaces
+        std::fs::write(backup_path(&path), "").unwrap();
+        assert!(load_json::<Value>(&path).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_file_is_none() {
+        assert_eq!(load_json::<Value>(&temp_dir("missing").join("state.json")).unwrap(), None);
+    }
+
+    fn add_label(mut value: Value) -> Value {
+        value["label"] = json!("unnamed");
+        value
+    }
+
+    const LABELLED: Schema = Schema { file: "state.json", steps: &[add_label] };
+
+    #[test]
+    fn upgrades_older_files_once_and_keeps_the_original() {
+        let dir = temp_dir("migrate");
+        let path = dir.join("state.json");
+        write_atomic(&path, b"{\"v\": 1}").unwrap();
+
+        let loaded: Loaded<Value> = load_versioned(&path, &LABELLED).unwrap().unwrap();
+        assert_eq!(loaded.migrated_from, Some(1));
+        assert_eq!(loaded.value, json!({"v": 1, "label": "unnamed"}));
+        let backup = migration_backup_path(&path, 1);
+        assert_eq!(serde_json::from_str::<Value>(&std::fs::read_to_string(&backup).unwrap()).unwrap(), json!({"v": 1}));
+
+        // Written back at the current version: the next load is a plain read
+        std::fs::remove_file(&backup).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let again: Loaded<Value> = load_versioned(&path, &LABELLED).unwrap().unwrap();
+        assert_eq!(again, Loaded::fresh(json!({"v": 1, "label": "unnamed"})));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), written);
+        assert!(!backup.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn files_from_a_newer_eli_are_refused() {
+        let dir = temp_dir("newer");
+        let path = dir.join("state.json");
+        write_atomic(&path, b"{\"version\": 3}").unwrap();
+        let err = load_versioned::<Value>(&path, &LABELLED).unwrap_err();
+        assert!(err.to_string().contains("newer eli"), "{}", err);
+        // and left as it was
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"version\": 3}");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn merge_keeps_fields_only_the_other_side_changed() {
+        let base = json!({"units": "eth", "prefs": {"hex": true, "width": 80}, "gone": 1, "old": 2});
+        let ours = json!({"units": "eth", "prefs": {"hex": false, "width": 80}, "gone": 1, "old": 3, "new": 4});
+        let theirs = json!({"units": "wei", "prefs": {"hex": true, "width": 100}});
+        assert_eq!(
+            merge_changed(&base, ours, theirs),
+            // "gone" was removed by them and untouched by us; "old" we changed
+            json!({"units": "wei", "prefs": {"hex": false, "width": 100}, "old": 3, "new": 4})
+        );
+    }
+
+    #[test]
+    fn set_merges_keep_both_sides() {
+        assert_eq!(merge_union(vec!["a", "b"], vec!["b", "c"]), ["a", "b", "c"]);
+    }
+
+    const WS: &str = "wss://node.example/ws";
+
+    #[test]
+    fn lease_is_held_until_released_or_its_holder_is_gone() {
+        let dir = temp_dir("lease");
+        // Fake pids: 100 and 200 are running, 300 isn't
+        let alive = |pid| pid != 300;
+
+        assert_eq!(acquire_lease(&dir, WS, 100, 1000, alive).unwrap(), None);
+        let held = acquire_lease(&dir, WS, 200, 1010, alive).unwrap().unwrap();
+        assert_eq!(held, Lease { endpoint: WS.into(), pid: 100, renewed_at: 1000 });
+        assert_eq!(held.in_use_message(), "subscription transport in use by another eli instance (pid 100)");
+        // Other endpoints are independent
+        assert_eq!(acquire_lease(&dir, "wss://other.example", 200, 1010, alive).unwrap(), None);
+
+        // The holder renews; someone else's release doesn't touch it
+        assert_eq!(acquire_lease(&dir, WS, 100, 1200, alive).unwrap(), None);
+        release_lease(&dir, WS, 200).unwrap();
+        assert_eq!(acquire_lease(&dir, WS, 200, 1300, alive).unwrap().unwrap().renewed_at, 1200);
+
+        release_lease(&dir, WS, 100).unwrap();
+        assert!(!lease_path(&dir, WS).exists());
+        assert_eq!(acquire_lease(&dir, WS, 200, 1300, alive).unwrap(), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn stale_leases_are_taken_over() {
+        let dir = temp_dir("lease-stale");
+        let alive = |pid| pid != 300;
+
+        // Holder's process is gone
+        acquire_lease(&dir, WS, 300, 1000, |_| true).unwrap();
+        assert_eq!(acquire_lease(&dir, WS, 100, 1001, alive).unwrap(), None);
+
+        // Holder alive but silent for longer than the TTL
+        assert!(acquire_lease(&dir, WS, 200, 1000 + LEASE_TTL, alive).unwrap().is_some());
+        assert_eq!(acquire_lease(&dir, WS, 200, 1002 + LEASE_TTL, alive).unwrap(), None);
+
+        // A half-written file doesn't block anyone
+        std::fs::write(lease_path(&dir, WS), "{\"pid\": ").unwrap();
+        assert_eq!(acquire_lease(&dir, WS, 100, 5000, alive).unwrap(), None);
+
+        // "Proceed anyway" takes a live lease
+        take_lease(&dir, WS, 200, 5001).unwrap();
+        assert_eq!(acquire_lease(&dir, WS, 100, 5002, alive).unwrap().unwrap().pid, 200);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn held_leases_are_released_when_dropped() {
+        let dir = temp_dir("lease-held");
+        let alive = |pid| pid != 300;
+
+        let held = hold_lease(&dir, WS, 100, 1000, false, alive).unwrap().unwrap();
+        let holder = hold_lease(&dir, WS, 200, 1001, false, alive).unwrap().unwrap_err();
+        assert_eq!(holder.pid, 100);
+        drop(held);
+        assert!(!lease_path(&dir, WS).exists());
+
+        // Proceeding anyway takes it from a live holder, whose drop then leaves it be
+        let first = hold_lease(&dir, WS, 100, 2000, false, alive).unwrap().unwrap();
+        let second = hold_lease(&dir, WS, 200, 2001, true, alive).unwrap().unwrap();
+        drop(first);
+        assert_eq!(acquire_lease(&dir, WS, 100, 2002, alive).unwrap().unwrap().pid, 200);
+        drop(second);
+        assert!(!lease_path(&dir, WS).exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn own_process_is_alive() {
+        assert!(pid_alive(std::process::id()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exited_processes_and_group_pids_are_not_alive() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!pid_alive(pid));
+        assert!(!pid_alive(0));
+        assert!(!pid_alive(u32::MAX));
+    }
+
+    #[cfg(not(unix))]
+    #[test]
+    fn every_pid_counts_as_alive_without_a_way_to_check() {
+        assert!(pid_alive(0));
+        assert!(pid_alive(u32::MAX));
+    }
+}
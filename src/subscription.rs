@@ -0,0 +1,417 @@
+// src/subscription.rs
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::clock::Clock;
+use crate::prefs::DisplayPrefs;
+use crate::rpc::JsonRpcRequest;
+use crate::storage::{self, HeldLease, Lease};
+use crate::ws::{self, Connector, Frame, ReconnectPolicy};
+
+/// Stable id of a subscription pane. Server subscription ids change on
+/// every reconnect; pane ids don't.
+pub type PaneId = usize;
+
+/// Notifications missed between a drop and the resubscribe that followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    /// Unix seconds the connection was found dropped.
+    pub lost: u64,
+    /// Unix seconds the server took the subscription back.
+    pub resubscribed: u64,
+}
+
+impl Gap {
+    /// The line a pane shows where its stream broke, e.g.
+    /// "— connection lost 12:03:41, resubscribed 12:03:44 —".
+    pub fn marker(&self, prefs: &DisplayPrefs) -> String {
+        let (lost, resubscribed) = (prefs.time_of_day(self.lost), prefs.time_of_day(self.resubscribed));
+        format!("— connection lost {}, resubscribed {} —", lost, resubscribed)
+    }
+}
+
+/// What happened to a pane's subscription.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaneEvent {
+    Notification(PaneId, Value),
+    Gap(PaneId, Gap),
+    /// The server refused the subscription, or reconnecting gave up. The
+    /// pane gets nothing more.
+    Failed(PaneId, String),
+}
+
+#[derive(Debug, Clone)]
+struct Subscription {
+    pane: PaneId,
+    /// `eth_subscribe` params, sent again on every reconnect.
+    params: Value,
+    /// The server's id, once it answered.
+    server_id: Option<String>,
+    /// Id of the `eth_subscribe` waiting for an answer.
+    pending: Option<u64>,
+    /// When the connection dropped, until the server takes it back.
+    lost: Option<u64>,
+}
+
+/// Tracks the subscriptions of one connection across reconnects, mapping
+/// server ids to pane ids. It does no I/O: it hands out the requests to
+/// send and turns incoming frames into [`PaneEvent`]s.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionManager {
+    subs: Vec<Subscription>,
+    next_id: u64,
+}
+
+impl SubscriptionManager {
+    fn request(&mut self, method: &str, params: Value) -> JsonRpcRequest {
+        self.next_id += 1;
+        JsonRpcRequest::new(method, params, self.next_id)
+    }
+
+    /// Starts a subscription for `pane`; returns the `eth_subscribe` to send.
+    pub fn subscribe(&mut self, pane: PaneId, params: Value) -> JsonRpcRequest {
+        let request = self.request("eth_subscribe", params.clone());
+        self.subs.push(Subscription { pane, params, server_id: None, pending: Some(request.id), lost: None });
+        request
+    }
+
+    /// Forgets `pane`'s subscription; returns the `eth_unsubscribe` to send
+    /// if the server knows it.
+    pub fn unsubscribe(&mut self, pane: PaneId) -> Option<JsonRpcRequest> {
+        let index = self.subs.iter().position(|s| s.pane == pane)?;
+        let server_id = self.subs.remove(index).server_id?;
+        Some(self.request("eth_unsubscribe", json!([server_id])))
+    }
+
+    /// Panes with a live or pending subscription.
+    pub fn panes(&self) -> Vec<PaneId> {
+        self.subs.iter().map(|s| s.pane).collect()
+    }
+
+    /// The connection dropped at `now`: every server id is void. A
+    /// subscription already waiting to be taken back keeps its first drop
+    /// time, so the gap covers failed attempts too.
+    pub fn disconnected(&mut self, now: u64) {
+        for sub in &mut self.subs {
+            sub.server_id = None;
+            sub.pending = None;
+            sub.lost.get_or_insert(now);
+        }
+    }
+
+    /// The `eth_subscribe`s that put every subscription back on a new
+    /// connection.
+    pub fn resubscribe(&mut self) -> Vec<JsonRpcRequest> {
+        let mut requests = Vec::with_capacity(self.subs.len());
+        for i in 0..self.subs.len() {
+            let request = self.request("eth_subscribe", self.subs[i].params.clone());
+            self.subs[i].pending = Some(request.id);
+            requests.push(request);
+        }
+        requests
+    }
+
+    /// What `frame`, arriving at `now`, means for the panes. Notifications
+    /// for ids from before a reconnect, and answers to unsubscribes, mean
+    /// nothing.
+    pub fn on_frame(&mut self, frame: Frame, now: u64) -> Option<PaneEvent> {
+        match frame {
+            Frame::Notification(notification) => {
+                let sub = self.subs.iter().find(|s| s.server_id.as_deref() == Some(notification.subscription.as_str()))?;
+                Some(PaneEvent::Notification(sub.pane, notification.result))
+            }
+            Frame::Response(response) => {
+                let index = self.subs.iter().position(|s| s.pending == Some(response.id))?;
+                let server_id = response.result.as_ref().and_then(Value::as_str);
+                let (Some(server_id), None) = (server_id, &response.error) else {
+                    let sub = self.subs.remove(index);
+                    let reason = response.error_summary().unwrap_or_else(|| "no subscription id in the answer".into());
+                    return Some(PaneEvent::Failed(sub.pane, reason));
+                };
+                let sub = &mut self.subs[index];
+                sub.server_id = Some(server_id.to_string());
+                sub.pending = None;
+                let lost = sub.lost.take()?;
+                Some(PaneEvent::Gap(sub.pane, Gap { lost, resubscribed: now }))
+            }
+        }
+    }
+}
+
+enum Command {
+    Subscribe(PaneId, Value),
+    Unsubscribe(PaneId),
+}
+
+/// Subscriptions on one WebSocket endpoint that survive the connection
+/// dropping: it is reopened as the [`ReconnectPolicy`] says and every
+/// subscription is taken out again, with a [`Gap`] in each pane's stream.
+/// Dropping it closes the connection and gives up its lease, if any.
+pub struct Subscriptions {
+    commands: mpsc::UnboundedSender<Command>,
+    events: mpsc::UnboundedReceiver<PaneEvent>,
+    next_pane: PaneId,
+    task: JoinHandle<()>,
+    /// Tells other local instances the endpoint's connection is taken.
+    lease: Option<HeldLease>,
+}
+
+/// What [`Subscriptions::open`] found.
+pub enum Opened {
+    Started(Subscriptions),
+    /// Another live eli instance holds the connection to the endpoint.
+    InUse(Lease),
+}
+
+impl Subscriptions {
+    /// Connects to `url` in the background.
+    pub fn start(url: &str, connector: Arc<dyn Connector>, policy: ReconnectPolicy, clock: Arc<dyn Clock>) -> Self {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let (sender, events) = mpsc::unbounded_channel();
+        let task = tokio::spawn(drive(url.to_string(), connector, policy, clock, receiver, sender));
+        Subscriptions { commands, events, next_pane: 0, task, lease: None }
+    }
+
+    /// [`Subscriptions::start`], holding the lease on `url` in `dir` for as
+    /// long as the subscriptions live, so other local instances can tell
+    /// the endpoint's connection is taken. When another live instance
+    /// holds it, nothing is started and its lease is returned, unless
+    /// `force` ("proceed anyway") takes it over.
+    pub fn open(
+        dir: &Path,
+        url: &str,
+        connector: Arc<dyn Connector>,
+        policy: ReconnectPolicy,
+        clock: Arc<dyn Clock>,
+        force: bool,
+    ) -> Result<Opened> {
+        let lease = match storage::hold_lease(dir, url, std::process::id(), clock.unix_now(), force, storage::pid_alive)? {
+            Ok(lease) => lease,
+            Err(holder) => return Ok(Opened::InUse(holder)),
+        };
+        let mut subs = Subscriptions::start(url, connector, policy, clock);
+        subs.lease = Some(lease);
+        Ok(Opened::Started(subs))
+    }
+
+    /// Renews the lease, if held, so it doesn't expire while connected.
+    pub fn renew_lease(&mut self, now: u64) -> Result<()> {
+        match &mut self.lease {
+            Some(lease) => lease.renew(now),
+            None => Ok(()),
+        }
+    }
+
+    /// Subscribes with `params` (e.g. `["newHeads"]`) in a new pane.
+    pub fn subscribe(&mut self, params: Value) -> PaneId {
+        let pane = self.next_pane;
+        self.next_pane += 1;
+        let _ = self.commands.send(Command::Subscribe(pane, params));
+        pane
+    }
+
+    pub fn unsubscribe(&self, pane: PaneId) {
+        let _ = self.commands.send(Command::Unsubscribe(pane));
+    }
+
+    /// The next event, waiting for it; `None` once the connection is given up.
+    pub async fn next(&mut self) -> Option<PaneEvent> {
+        self.events.recv().await
+    }
+
+    /// The next event if there is one, for polling from the draw loop.
+    pub fn try_next(&mut self) -> Option<PaneEvent> {
+        self.events.try_recv().ok()
+    }
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn drive(
+    url: String,
+    connector: Arc<dyn Connector>,
+    policy: ReconnectPolicy,
+    clock: Arc<dyn Clock>,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    events: mpsc::UnboundedSender<PaneEvent>,
+) {
+    let mut manager = SubscriptionManager::default();
+    let mut connection = connector.connect(&url).await;
+    loop {
+        let mut ws = match connection {
+            Ok(ws) => ws,
+            Err(e) => {
+                // Subscriptions asked for meanwhile fail along with the rest
+                while let Ok(command) = commands.try_recv() {
+                    if let Command::Subscribe(pane, params) = command {
+                        manager.subscribe(pane, params);
+                    }
+                }
+                for pane in manager.panes() {
+                    let _ = events.send(PaneEvent::Failed(pane, format!("{:#}", e)));
+                }
+                return;
+            }
+        };
+        for request in manager.resubscribe() {
+            let _ = ws.send(request);
+        }
+        loop {
+            tokio::select! {
+                command = commands.recv() => match command {
+                    None => return,
+                    Some(Command::Subscribe(pane, params)) => {
+                        let _ = ws.send(manager.subscribe(pane, params));
+                    }
+                    Some(Command::Unsubscribe(pane)) => {
+                        if let Some(request) = manager.unsubscribe(pane) {
+                            let _ = ws.send(request);
+                        }
+                    }
+                },
+                frame = ws.next() => match frame {
+                    Some(Ok(frame)) => {
+                        if let Some(event) = manager.on_frame(frame, clock.unix_now()) {
+                            let _ = events.send(event);
+                        }
+                    }
+                    // Unreadable frames don't end the connection
+                    Some(Err(_)) => {}
+                    None => break,
+                },
+            }
+        }
+        manager.disconnected(clock.unix_now());
+        connection = ws::reconnect(connector.as_ref(), &url, &policy).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::ws::scripted;
+    use std::time::Duration;
+
+    const FAST: ReconnectPolicy =
+        ReconnectPolicy { initial_delay: Duration::from_millis(1), max_delay: Duration::from_millis(2), max_attempts: 3 };
+
+    #[test]
+    fn server_ids_are_remapped_to_panes_after_a_reconnect() {
+        let mut manager = SubscriptionManager::default();
+        let heads = manager.subscribe(0, json!(["newHeads"]));
+        let logs = manager.subscribe(1, json!(["logs", {}]));
+        let answer = |id, result: Value| {
+            Frame::Response(crate::rpc::JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(result), error: None, id })
+        };
+        let notify = |id: &str, n| Frame::Notification(ws::Notification { subscription: id.into(), result: json!(n) });
+
+        assert_eq!(manager.on_frame(answer(heads.id, json!("0xa")), 100), None);
+        assert_eq!(manager.on_frame(answer(logs.id, json!("0xb")), 100), None);
+        assert_eq!(manager.on_frame(notify("0xb", 1), 101), Some(PaneEvent::Notification(1, json!(1))));
+
+        // Dropped at 110, one failed attempt, taken back at 114 under new ids
+        manager.disconnected(110);
+        manager.disconnected(112);
+        let again = manager.resubscribe();
+        assert_eq!(again.iter().map(|r| &r.params).collect::<Vec<_>>(), [&json!(["newHeads"]), &json!(["logs", {}])]);
+        assert_eq!(manager.on_frame(notify("0xa", 2), 113), None);
+        let gap = Gap { lost: 110, resubscribed: 114 };
+        assert_eq!(manager.on_frame(answer(again[1].id, json!("0xd")), 114), Some(PaneEvent::Gap(1, gap)));
+        assert_eq!(manager.on_frame(answer(again[0].id, json!("0xc")), 114), Some(PaneEvent::Gap(0, gap)));
+        assert_eq!(manager.on_frame(notify("0xc", 3), 115), Some(PaneEvent::Notification(0, json!(3))));
+        assert_eq!(manager.on_frame(notify("0xa", 4), 115), None);
+
+        assert_eq!(manager.unsubscribe(0).unwrap().params, json!(["0xc"]));
+        assert_eq!(manager.panes(), [1]);
+        assert_eq!(gap.marker(&DisplayPrefs::default()), "— connection lost 00:01:50, resubscribed 00:01:54 —");
+    }
+
+    #[test]
+    fn refused_subscriptions_fail_their_pane() {
+        let mut manager = SubscriptionManager::default();
+        let request = manager.subscribe(3, json!(["bogus"]));
+        let refusal = crate::rpc::JsonRpcResponse {
+            jsonrpc: "2.0".into(),
+            result: None,
+            error: Some(json!({"code": -32602, "message": "invalid subscription"})),
+            id: request.id,
+        };
+        let failed = Some(PaneEvent::Failed(3, "-32602 invalid subscription".into()));
+        assert_eq!(manager.on_frame(Frame::Response(refusal), 0), failed);
+        assert!(manager.panes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn panes_keep_streaming_across_a_dropped_connection() {
+        let (connector, mut sockets) = scripted(2);
+        let clock = Arc::new(ManualClock::new(43_200));
+        let mut subs = Subscriptions::start("ws://node", connector, FAST, clock.clone());
+        let pane = subs.subscribe(json!(["newHeads"]));
+
+        let first = &mut sockets[0];
+        let request = first.requests.recv().await.unwrap();
+        first.reply(request.id, json!("0xs1"));
+        first.notify("0xs1", json!({"number": "0x1"}));
+        assert_eq!(subs.next().await, Some(PaneEvent::Notification(pane, json!({"number": "0x1"}))));
+
+        // The node restarts
+        clock.advance(Duration::from_secs(1));
+        drop(sockets.remove(0));
+        let second = &mut sockets[0];
+        let request = second.requests.recv().await.unwrap();
+        assert_eq!((request.method.as_str(), &request.params), ("eth_subscribe", &json!(["newHeads"])));
+        clock.advance(Duration::from_secs(3));
+        second.reply(request.id, json!("0xs2"));
+        second.notify("0xs2", json!({"number": "0x2"}));
+
+        let Some(PaneEvent::Gap(gap_pane, gap)) = subs.next().await else { panic!("no gap marker") };
+        assert_eq!(gap_pane, pane);
+        assert_eq!(gap.marker(&DisplayPrefs::default()), "— connection lost 12:00:01, resubscribed 12:00:04 —");
+        assert_eq!(subs.next().await, Some(PaneEvent::Notification(pane, json!({"number": "0x2"}))));
+
+        // Down for good: the pane is told once the attempts run out
+        drop(sockets.remove(0));
+        let Some(PaneEvent::Failed(failed, reason)) = subs.next().await else { panic!("no failure") };
+        assert_eq!(failed, pane);
+        assert_eq!(reason, "gave up reconnecting after 3 attempts: cannot connect to ws://node");
+        assert_eq!(subs.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn a_second_instance_finds_the_connection_in_use() {
+        let dir = std::env::temp_dir().join(format!("eli-subscription-lease-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let clock = Arc::new(ManualClock::new(43_200));
+        // Another instance holds the lease: pid 1 is always running
+        let other = 1;
+        storage::acquire_lease(&dir, "ws://node", other, 43_200, |_| true).unwrap();
+
+        let (connector, _sockets) = scripted(1);
+        let Opened::InUse(holder) = Subscriptions::open(&dir, "ws://node", connector.clone(), FAST, clock.clone(), false).unwrap()
+        else {
+            panic!("opened over a held lease")
+        };
+        assert_eq!(holder.in_use_message(), format!("subscription transport in use by another eli instance (pid {})", other));
+
+        // Proceeding anyway takes the lease until the subscriptions are dropped
+        let Opened::Started(subs) = Subscriptions::open(&dir, "ws://node", connector, FAST, clock, true).unwrap() else {
+            panic!("not started")
+        };
+        let held = storage::acquire_lease(&dir, "ws://node", other, 43_201, |_| true).unwrap();
+        assert_eq!(held.map(|l| l.pid), Some(std::process::id()));
+        drop(subs);
+        assert!(!storage::lease_path(&dir, "ws://node").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
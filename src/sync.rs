@@ -0,0 +1,201 @@
+// src/sync.rs
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tokio::task::JoinHandle;
+
+use crate::prefs::DisplayPrefs;
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse, Transport};
+use crate::head::unix_now;
+use crate::stats::SharedStats;
+use crate::tasks::{SharedTasks, TaskHandle, TaskKind};
+
+/// How often the session endpoint is asked for `eth_syncing`.
+pub const PROBE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The session endpoint's sync status as of the last probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncState {
+    /// Not probed yet, or the probe failed or returned something unexpected.
+    #[default]
+    Unknown,
+    Synced,
+    Syncing { current: u64, highest: u64 },
+}
+
+fn hex_u64(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.strip_prefix("0x")?, 16).ok()
+}
+
+impl SyncState {
+    /// Interprets an `eth_syncing` response: `false` is synced, an object
+    /// with current and highest block is syncing, anything else is unknown.
+    pub fn from_response(response: &JsonRpcResponse) -> Self {
+        match &response.result {
+            Some(Value::Bool(false)) if response.error.is_none() => SyncState::Synced,
+            Some(sync @ Value::Object(_)) => match (hex_u64(&sync["currentBlock"]), hex_u64(&sync["highestBlock"])) {
+                (Some(current), Some(highest)) => SyncState::Syncing { current, highest },
+                _ => SyncState::Unknown,
+            },
+            _ => SyncState::Unknown,
+        }
+    }
+
+    /// "SYNCING (block 12,345,678 / 19,000,000)" while syncing.
+    pub fn badge(&self, prefs: &DisplayPrefs) -> Option<String> {
+        match self {
+            SyncState::Syncing { current, highest } => Some(format!(
+                "SYNCING (block {} / {})",
+                prefs.integer(*current as u128),
+                prefs.integer(*highest as u128)
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `params` refer to a moving block tag (`latest` or `pending`)
+/// anywhere, including inside filter and call objects.
+pub fn uses_moving_tag(params: &Value) -> bool {
+    match params {
+        Value::String(s) => s == "latest" || s == "pending",
+        Value::Array(items) => items.iter().any(uses_moving_tag),
+        Value::Object(fields) => fields.values().any(uses_moving_tag),
+        _ => false,
+    }
+}
+
+/// Whether a request with `params` should be held for the syncing warning:
+/// only when the endpoint is known to be syncing, the warning hasn't been
+/// shown this session and isn't suppressed.
+pub fn should_warn(state: SyncState, params: &Value, warned: bool, suppressed: bool) -> bool {
+    matches!(state, SyncState::Syncing { .. }) && !warned && !suppressed && uses_moving_tag(params)
+}
+
+/// Periodic `eth_syncing` probe of the session endpoint; the probe writes
+/// its result to [`Stats::sync`](crate::stats::Stats::sync).
+#[derive(Debug, Default)]
+pub struct SyncWatch {
+    /// The syncing warning was shown this session.
+    pub warned: bool,
+    url: String,
+    last_probe: Option<Instant>,
+    in_flight: Option<JoinHandle<()>>,
+    task: TaskHandle,
+}
+
+impl SyncWatch {
+    /// Starts the next probe if none is running, the interval has passed
+    /// and the probe isn't paused or killed in `tasks`. Switching to
+    /// another endpoint forgets the old state.
+    pub fn poll(&mut self, now: Instant, transport: Arc<dyn Transport>, url: &str, stats: &SharedStats, tasks: &SharedTasks) {
+        if self.url != url {
+            if let Some(handle) = self.in_flight.take() {
+                handle.abort();
+            }
+            let task = std::mem::take(&mut self.task);
+            *self = SyncWatch { url: url.to_string(), warned: self.warned, task, ..SyncWatch::default() };
+            stats.update(|s| s.sync = SyncState::Unknown);
+        }
+        if !self.task.may_run(tasks, TaskKind::SyncProbe, "eth_syncing", PROBE_INTERVAL) {
+            if let Some(handle) = self.in_flight.take() {
+                handle.abort();
+            }
+            return;
+        }
+        if self.in_flight.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return;
+        }
+        self.in_flight = None;
+        if self.last_probe.is_some_and(|last| now.duration_since(last) < PROBE_INTERVAL) {
+            return;
+        }
+        self.last_probe = Some(now);
+        let (stats, tasks, url, id) = (stats.clone(), tasks.clone(), url.to_string(), self.task.id());
+        self.in_flight = Some(tokio::spawn(async move {
+            let request = JsonRpcRequest::new("eth_syncing", Value::Array(Vec::new()), 1);
+            let state = transport.send(&url, request).await.map(|r| SyncState::from_response(&r));
+            let ok = matches!(state, Ok(SyncState::Synced | SyncState::Syncing { .. }));
+            stats.update(|s| s.sync = state.unwrap_or_default());
+            if let Some(id) = id {
+                tasks.update(|r| r.record(id, ok, unix_now()));
+            }
+        }));
+    }
+
+    /// Drops the probe in flight and probes again on the next call.
+    pub fn reschedule(&mut self) {
+        if let Some(handle) = self.in_flight.take() {
+            handle.abort();
+        }
+        self.last_probe = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    fn response(result: Value) -> JsonRpcResponse {
+        JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(result), error: None, id: 1 }
+    }
+
+    #[test]
+    fn interprets_eth_syncing() {
+        assert_eq!(SyncState::from_response(&response(json!(false))), SyncState::Synced);
+        let syncing = response(json!({"startingBlock": "0x0", "currentBlock": "0xbc614e", "highestBlock": "0x121eac0"}));
+        let state = SyncState::from_response(&syncing);
+        assert_eq!(state, SyncState::Syncing { current: 12_345_678, highest: 19_000_000 });
+        assert_eq!(state.badge(&DisplayPrefs::default()).unwrap(), "SYNCING (block 12,345,678 / 19,000,000)");
+
+        // Anything the probe can't make sense of is unknown, not synced
+        assert_eq!(SyncState::from_response(&response(json!(true))), SyncState::Unknown);
+        assert_eq!(SyncState::from_response(&response(json!({"stage": "headers"}))), SyncState::Unknown);
+        let error = JsonRpcResponse { result: None, error: Some(json!({"code": -32601})), ..response(json!(null)) };
+        assert_eq!(SyncState::from_response(&error), SyncState::Unknown);
+    }
+
+    #[test]
+    fn warns_once_for_moving_tags_while_syncing() {
+        let syncing = SyncState::Syncing { current: 1, highest: 2 };
+        let latest = json!(["0xabc", "latest"]);
+        assert!(should_warn(syncing, &latest, false, false));
+        assert!(should_warn(syncing, &json!([{"fromBlock": "0x1", "toBlock": "pending"}]), false, false));
+        assert!(!should_warn(syncing, &json!(["0xabc", "0x10"]), false, false));
+        assert!(!should_warn(syncing, &latest, true, false));
+        assert!(!should_warn(syncing, &latest, false, true));
+        assert!(!should_warn(SyncState::Synced, &latest, false, false));
+        assert!(!should_warn(SyncState::Unknown, &latest, false, false));
+    }
+
+    struct Syncing;
+
+    #[async_trait]
+    impl Transport for Syncing {
+        async fn send(&self, _url: &str, req: JsonRpcRequest) -> anyhow::Result<JsonRpcResponse> {
+            assert_eq!(req.method, "eth_syncing");
+            Ok(response(json!({"currentBlock": "0x1", "highestBlock": "0x2"})))
+        }
+    }
+
+    #[tokio::test]
+    async fn tracks_state_per_endpoint() {
+        let (mut watch, stats, tasks) = (SyncWatch::default(), SharedStats::default(), SharedTasks::default());
+        let t0 = Instant::now();
+        watch.poll(t0, Arc::new(Syncing), "http://a", &stats, &tasks);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(stats.try_snapshot().unwrap().sync, SyncState::Syncing { current: 1, highest: 2 });
+        let task = tasks.snapshot().tasks()[0].clone();
+        assert_eq!((task.kind, task.errors), (TaskKind::SyncProbe, 0));
+        assert!(task.last_result.is_some());
+
+        watch.poll(t0, Arc::new(Syncing), "http://b", &stats, &tasks);
+        assert_eq!(stats.try_snapshot().unwrap().sync, SyncState::Unknown);
+        // Still the same registered task
+        assert_eq!(tasks.snapshot().tasks().len(), 1);
+    }
+}
@@ -0,0 +1,238 @@
+// src/tasks.rs
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// Identifies a registered background task.
+pub type TaskId = u64;
+
+/// What a background task does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    /// `eth_syncing` probe of the session endpoint.
+    SyncProbe,
+    /// Latest-block poll of the session endpoint.
+    HeadPoll,
+    /// Dashboard cards, polled while the dashboard is open.
+    Dashboard,
+    /// `newHeads` subscription on the active profile's websocket.
+    LiveHeads,
+}
+
+impl TaskKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            TaskKind::SyncProbe => "sync probe",
+            TaskKind::HeadPoll => "head poll",
+            TaskKind::Dashboard => "dashboard",
+            TaskKind::LiveHeads => "live heads",
+        }
+    }
+}
+
+/// One registered task, as the activity panel shows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskInfo {
+    pub id: TaskId,
+    pub kind: TaskKind,
+    /// Method and params summary, and where they go.
+    pub target: String,
+    /// Zero for subscriptions, which are pushed to rather than polled.
+    pub interval: Duration,
+    pub paused: bool,
+    /// When the last run finished (unix seconds), successful or not.
+    pub last_result: Option<u64>,
+    /// Runs that failed so far.
+    pub errors: u32,
+}
+
+/// Every periodic background task, so the user can see and stop them.
+///
+/// Pollers register themselves before their first run, report how each run
+/// went, and check before each run whether they were paused or killed
+/// (removed). The registry only keeps the books; stopping work is up to the
+/// poller, usually through a [`TaskHandle`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskRegistry {
+    tasks: Vec<TaskInfo>,
+    next_id: TaskId,
+}
+
+impl TaskRegistry {
+    pub fn register(&mut self, kind: TaskKind, target: impl Into<String>, interval: Duration) -> TaskId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.push(TaskInfo { id, kind, target: target.into(), interval, paused: false, last_result: None, errors: 0 });
+        id
+    }
+
+    pub fn get(&self, id: TaskId) -> Option<&TaskInfo> {
+        self.tasks.iter().find(|t| t.id == id)
+    }
+
+    fn get_mut(&mut self, id: TaskId) -> Option<&mut TaskInfo> {
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
+
+    /// Notes that a run of `id` finished at `at` (unix seconds).
+    pub fn record(&mut self, id: TaskId, ok: bool, at: u64) {
+        if let Some(task) = self.get_mut(id) {
+            task.last_result = Some(at);
+            task.errors += u32::from(!ok);
+        }
+    }
+
+    /// Changes what `id` is aimed at, e.g. after an endpoint switch.
+    pub fn retarget(&mut self, id: TaskId, target: &str) {
+        if let Some(task) = self.get_mut(id).filter(|t| t.target != target) {
+            task.target = target.to_string();
+        }
+    }
+
+    /// Pauses or resumes `id`; false if it isn't registered.
+    pub fn set_paused(&mut self, id: TaskId, paused: bool) -> bool {
+        self.get_mut(id).map(|t| t.paused = paused).is_some()
+    }
+
+    /// Unregisters `id`, which tells its poller to stop for good.
+    pub fn remove(&mut self, id: TaskId) -> bool {
+        let before = self.tasks.len();
+        self.tasks.retain(|t| t.id != id);
+        self.tasks.len() != before
+    }
+
+    /// Registered tasks, oldest first.
+    pub fn tasks(&self) -> &[TaskInfo] {
+        &self.tasks
+    }
+}
+
+/// [`TaskRegistry`] shared between the pollers' background tasks, which
+/// report their runs, and the event loop.
+#[derive(Debug, Clone, Default)]
+pub struct SharedTasks(Arc<Mutex<TaskRegistry>>);
+
+impl SharedTasks {
+    /// Applies `f` under the lock; keep it short.
+    pub fn update<R>(&self, f: impl FnOnce(&mut TaskRegistry) -> R) -> R {
+        f(&mut self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// A copy of the registry, for drawing.
+    pub fn snapshot(&self) -> TaskRegistry {
+        self.update(|r| r.clone())
+    }
+}
+
+/// A poller's registration: registered on first use, and asked before each
+/// run whether the run may start.
+#[derive(Debug, Default)]
+pub struct TaskHandle {
+    id: Option<TaskId>,
+    killed: bool,
+}
+
+impl TaskHandle {
+    pub fn id(&self) -> Option<TaskId> {
+        self.id
+    }
+
+    /// Whether the task was killed from the activity panel.
+    pub fn killed(&self) -> bool {
+        self.killed
+    }
+
+    /// Whether the poller may start a run, registering it the first time.
+    /// False while paused, and from then on once the task has been killed.
+    pub fn may_run(&mut self, tasks: &SharedTasks, kind: TaskKind, target: &str, interval: Duration) -> bool {
+        if self.killed {
+            return false;
+        }
+        let Some(id) = self.id else {
+            self.id = Some(tasks.update(|r| r.register(kind, target, interval)));
+            return true;
+        };
+        tasks.update(|r| {
+            r.retarget(id, target);
+            match r.get(id) {
+                Some(task) => !task.paused,
+                None => {
+                    self.killed = true;
+                    false
+                }
+            }
+        })
+    }
+
+    /// Unregisters the task because the poller stopped on its own (e.g.
+    /// the dashboard was closed); the next [`TaskHandle::may_run`] starts
+    /// afresh, even after a kill.
+    pub fn release(&mut self, tasks: &SharedTasks) {
+        if let Some(id) = self.id.take() {
+            tasks.update(|r| r.remove(id));
+        }
+        self.killed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn registry_keeps_books_per_task() {
+        let mut registry = TaskRegistry::default();
+        let sync = registry.register(TaskKind::SyncProbe, "eth_syncing @ http://a", SECOND);
+        let head = registry.register(TaskKind::HeadPoll, "eth_getBlockByNumber @ http://a", SECOND);
+        assert_ne!(sync, head);
+
+        registry.record(sync, true, 100);
+        registry.record(sync, false, 115);
+        let task = registry.get(sync).unwrap();
+        assert_eq!((task.last_result, task.errors), (Some(115), 1));
+        assert_eq!(registry.get(head).unwrap().last_result, None);
+
+        assert!(registry.set_paused(head, true));
+        assert!(registry.get(head).unwrap().paused);
+        registry.retarget(head, "eth_getBlockByNumber @ http://b");
+        assert_eq!(registry.get(head).unwrap().target, "eth_getBlockByNumber @ http://b");
+
+        assert!(registry.remove(sync));
+        assert!(!registry.remove(sync));
+        assert!(!registry.set_paused(sync, true));
+        // Reports from a task already removed are dropped
+        registry.record(sync, true, 200);
+        assert_eq!(registry.tasks().iter().map(|t| t.id).collect::<Vec<_>>(), [head]);
+    }
+
+    #[test]
+    fn handle_registers_once_and_obeys_pause_and_kill() {
+        let tasks = SharedTasks::default();
+        let mut handle = TaskHandle::default();
+        assert!(handle.may_run(&tasks, TaskKind::HeadPoll, "x", SECOND));
+        assert!(handle.may_run(&tasks, TaskKind::HeadPoll, "x", SECOND));
+        let id = handle.id().unwrap();
+        assert_eq!(tasks.snapshot().tasks().len(), 1);
+
+        tasks.update(|r| r.set_paused(id, true));
+        assert!(!handle.may_run(&tasks, TaskKind::HeadPoll, "x", SECOND));
+        tasks.update(|r| r.set_paused(id, false));
+        assert!(handle.may_run(&tasks, TaskKind::HeadPoll, "y", SECOND));
+        assert_eq!(tasks.snapshot().get(id).unwrap().target, "y");
+
+        // Killed: stays stopped and doesn't re-register
+        tasks.update(|r| r.remove(id));
+        assert!(!handle.may_run(&tasks, TaskKind::HeadPoll, "y", SECOND));
+        assert!(!handle.may_run(&tasks, TaskKind::HeadPoll, "y", SECOND));
+        assert!(tasks.snapshot().tasks().is_empty());
+
+        // Released: starts afresh under a new id
+        handle.release(&tasks);
+        assert!(handle.may_run(&tasks, TaskKind::HeadPoll, "y", SECOND));
+        assert_ne!(handle.id(), Some(id));
+        handle.release(&tasks);
+        assert!(tasks.snapshot().tasks().is_empty());
+    }
+}
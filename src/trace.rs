@@ -0,0 +1,300 @@
+// src/trace.rs
+
+use anyhow::{bail, Result};
+use serde_json::{Map, Value};
+
+use crate::chains::NativeCurrency;
+use crate::decode::{DecodedView, ResultDecoder};
+use crate::prefs::DisplayPrefs;
+use crate::shape::{shape_of, Shape};
+
+pub const FILTER: &str = "trace_filter";
+
+/// `trace_filter` builder rows, the filter keys they fill; the spec uses
+/// them as the method's params.
+pub const FILTER_FIELDS: &[&str] = &["fromBlock", "toBlock", "fromAddress", "toAddress", "after", "count"];
+
+const TAGS: &[&str] = &["latest", "pending", "safe", "finalized", "earliest"];
+
+fn block(name: &str, text: &str) -> Result<Value> {
+    match shape_of(text) {
+        Shape::Quantity => Ok(Value::String(text.to_lowercase())),
+        Shape::Decimal => match text.parse::<u64>() {
+            Ok(n) => Ok(Value::String(format!("0x{:x}", n))),
+            Err(_) => bail!("{}: number too large", name),
+        },
+        _ if TAGS.contains(&text) => Ok(Value::String(text.to_string())),
+        _ => bail!("{}: expected a block number or one of {}", name, TAGS.join("/")),
+    }
+}
+
+fn addresses(name: &str, text: &str) -> Result<Value> {
+    let list = text
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(|a| match shape_of(a) {
+            Shape::Address => Ok(Value::String(a.to_string())),
+            _ => bail!("{}: {:?} is not a 20-byte 0x-prefixed address", name, a),
+        })
+        .collect::<Result<_>>()?;
+    Ok(Value::Array(list))
+}
+
+fn count(name: &str, text: &str) -> Result<Value> {
+    let n = match text.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    };
+    match n {
+        Some(n) => Ok(Value::from(n)),
+        None => bail!("{}: expected a decimal or 0x-prefixed hex number", name),
+    }
+}
+
+/// `trace_filter` params for the builder's non-empty fields: block numbers
+/// become hex, address lists arrays, and `after`/`count` plain numbers.
+pub fn assemble_filter(fields: &[String]) -> Result<Value> {
+    let mut filter = Map::new();
+    for (i, key) in FILTER_FIELDS.iter().enumerate() {
+        let text = fields.get(i).map(|f| f.trim()).unwrap_or("");
+        if text.is_empty() {
+            continue;
+        }
+        let value = match *key {
+            "fromBlock" | "toBlock" => block(key, text)?,
+            "fromAddress" | "toAddress" => addresses(key, text)?,
+            _ => count(key, text)?,
+        };
+        filter.insert(key.to_string(), value);
+    }
+    Ok(Value::Array(vec![Value::Object(filter)]))
+}
+
+/// The builder fields [`assemble_filter`] would turn back into `params`, or
+/// `None` if the filter has keys or values the builder doesn't cover.
+pub fn filter_fields(params: &Value) -> Option<Vec<String>> {
+    let [Value::Object(filter)] = params.as_array()?.as_slice() else { return None };
+    if filter.keys().any(|k| !FILTER_FIELDS.contains(&k.as_str())) {
+        return None;
+    }
+    FILTER_FIELDS
+        .iter()
+        .map(|key| match filter.get(*key) {
+            None => Some(String::new()),
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            Some(Value::Array(list)) => {
+                list.iter().map(|a| a.as_str()).collect::<Option<Vec<_>>>().map(|list| list.join(", "))
+            }
+            Some(_) => None,
+        })
+        .collect()
+}
+
+/// One entry of a flat (OpenEthereum-style) trace, as shown in the table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatTrace {
+    /// Position in the call tree; empty for the top-level call.
+    pub trace_address: Vec<u64>,
+    /// Call type for calls (call, delegatecall, staticcall, …), otherwise
+    /// the trace type (create, suicide, reward).
+    pub kind: String,
+    pub from: Option<String>,
+    /// Callee, created contract, refund address or reward author.
+    pub to: Option<String>,
+    /// Hex wei transferred.
+    pub value: Option<String>,
+    /// Hex gas used; traces without a result (failed, self-destructs,
+    /// rewards) have none.
+    pub gas_used: Option<String>,
+    pub error: Option<String>,
+}
+
+impl FlatTrace {
+    /// Parses one flat trace, whichever action shape it has.
+    pub fn parse(trace: &Value) -> Option<Self> {
+        let kind = trace["type"].as_str()?;
+        let action = trace.get("action")?;
+        let text = |v: &Value| v.as_str().map(str::to_string);
+        let (kind, from, to, value) = match kind {
+            "call" => (
+                action["callType"].as_str().unwrap_or("call").to_string(),
+                text(&action["from"]),
+                text(&action["to"]),
+                text(&action["value"]),
+            ),
+            "create" => ("create".to_string(), text(&action["from"]), text(&trace["result"]["address"]), text(&action["value"])),
+            "suicide" | "selfdestruct" => {
+                (kind.to_string(), text(&action["address"]), text(&action["refundAddress"]), text(&action["balance"]))
+            }
+            other => (other.to_string(), text(&action["from"]), text(&action["author"]).or(text(&action["to"])), text(&action["value"])),
+        };
+        let trace_address = match &trace["traceAddress"] {
+            Value::Null => Vec::new(),
+            path => path.as_array()?.iter().map(Value::as_u64).collect::<Option<_>>()?,
+        };
+        Some(FlatTrace {
+            trace_address,
+            kind,
+            from,
+            to,
+            value,
+            gas_used: text(&trace["result"]["gasUsed"]),
+            error: text(&trace["error"]),
+        })
+    }
+
+    /// "root" for the top-level call, else the path as "0.2.1".
+    pub fn path(&self) -> String {
+        match self.trace_address.as_slice() {
+            [] => "root".to_string(),
+            path => path.iter().map(u64::to_string).collect::<Vec<_>>().join("."),
+        }
+    }
+
+    fn gas(&self) -> u64 {
+        self.gas_used.as_deref().and_then(|g| u64::from_str_radix(g.trim_start_matches("0x"), 16).ok()).unwrap_or(0)
+    }
+}
+
+/// The flat traces in a `trace_*` result: the array itself, or the `trace`
+/// array of a `trace_call`/`trace_replay*` result.
+pub fn parse(result: &Value) -> Option<Vec<FlatTrace>> {
+    let traces = match result {
+        Value::Array(traces) => traces,
+        Value::Object(fields) => fields.get("trace")?.as_array()?,
+        _ => return None,
+    };
+    traces.iter().map(FlatTrace::parse).collect()
+}
+
+/// Flat traces of the `trace_*` methods as a table, in result order or
+/// by gas used (most first) per [`DisplayPrefs::sort_traces_by_gas`].
+pub struct FlatTraceDecoder;
+
+impl ResultDecoder for FlatTraceDecoder {
+    fn matches(&self, method: &str) -> bool {
+        method.starts_with("trace_")
+    }
+
+    fn decode(&self, result: &Value, prefs: &DisplayPrefs) -> Option<DecodedView> {
+        let mut traces = parse(result)?;
+        if traces.is_empty() {
+            return None;
+        }
+        if prefs.sort_traces_by_gas {
+            traces.sort_by_key(|t| std::cmp::Reverse(t.gas()));
+        }
+        let currency = NativeCurrency::default();
+        let or_dash = |s: Option<String>| s.unwrap_or_else(|| "-".to_string());
+        let rows = traces
+            .into_iter()
+            .map(|t| {
+                vec![
+                    t.path(),
+                    t.kind.clone(),
+                    format!(
+                        "{} → {}",
+                        or_dash(t.from.as_deref().map(|a| prefs.address(a))),
+                        or_dash(t.to.as_deref().map(|a| prefs.address(a)))
+                    ),
+                    or_dash(t.value.as_deref().map(|v| prefs.wei(v, &currency))),
+                    or_dash(t.gas_used.as_deref().map(|g| prefs.quantity(g))),
+                    t.error.unwrap_or_default(),
+                ]
+            })
+            .collect();
+        let columns = ["trace", "type", "from → to", "value", "gas used", "error"].map(str::to_string).to_vec();
+        Some(DecodedView::Table { columns, rows })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const A: &str = "0x00000000000000000000000000000000000000a1";
+    const B: &str = "0x00000000000000000000000000000000000000b2";
+    const C: &str = "0x00000000000000000000000000000000000000c3";
+
+    /// A `trace_transaction` result: a call that creates a contract, calls
+    /// into it (which self-destructs), and a reverted static call.
+    fn transaction_trace() -> Value {
+        json!([
+            {
+                "action": {"callType": "call", "from": A, "to": B, "value": "0xde0b6b3a7640000", "gas": "0x30d40", "input": "0x"},
+                "result": {"gasUsed": "0x1d4c0", "output": "0x"},
+                "subtraces": 2, "traceAddress": [], "type": "call"
+            },
+            {
+                "action": {"from": B, "value": "0x0", "gas": "0x1e848", "init": "0x6080", "creationMethod": "create"},
+                "result": {"address": C, "code": "0x6080", "gasUsed": "0xc350"},
+                "subtraces": 1, "traceAddress": [0], "type": "create"
+            },
+            {
+                "action": {"address": C, "refundAddress": A, "balance": "0x0"},
+                "result": null,
+                "subtraces": 0, "traceAddress": [0, 0], "type": "suicide"
+            },
+            {
+                "action": {"callType": "staticcall", "from": B, "to": C, "value": "0x0", "gas": "0x2710", "input": "0x"},
+                "error": "Reverted",
+                "subtraces": 0, "traceAddress": [1], "type": "call"
+            }
+        ])
+    }
+
+    #[test]
+    fn parses_call_create_and_suicide_actions() {
+        let traces = parse(&transaction_trace()).unwrap();
+        let paths: Vec<String> = traces.iter().map(FlatTrace::path).collect();
+        assert_eq!(paths, ["root", "0", "0.0", "1"]);
+        assert_eq!(traces[1].kind, "create");
+        assert_eq!((traces[1].from.as_deref(), traces[1].to.as_deref()), (Some(B), Some(C)));
+        assert_eq!(traces[2].kind, "suicide");
+        assert_eq!((traces[2].from.as_deref(), traces[2].to.as_deref()), (Some(C), Some(A)));
+        assert_eq!(traces[2].gas_used, None);
+        assert_eq!(traces[3].kind, "staticcall");
+        assert_eq!(traces[3].error.as_deref(), Some("Reverted"));
+        // trace_call wraps the traces in an object
+        assert_eq!(parse(&json!({"output": "0x", "trace": transaction_trace(), "stateDiff": null})).unwrap(), traces);
+        assert_eq!(parse(&json!("0x1")), None);
+    }
+
+    #[test]
+    fn renders_a_table_sortable_by_gas() {
+        let mut prefs = DisplayPrefs::default();
+        let lines = FlatTraceDecoder.decode(&transaction_trace(), &prefs).unwrap().lines();
+        assert!(lines[0].starts_with("trace"));
+        assert!(lines[1].starts_with("root") && lines[1].contains(&format!("{} → {}", A, B)) && lines[1].contains("1 ETH"));
+        assert!(lines[4].starts_with("1 ") && lines[4].ends_with("Reverted"));
+
+        // Most gas first; traces without gas keep their relative order
+        prefs.sort_traces_by_gas = true;
+        let mut reversed = transaction_trace();
+        reversed.as_array_mut().unwrap().reverse();
+        let lines = FlatTraceDecoder.decode(&reversed, &prefs).unwrap().lines();
+        let order: Vec<&str> = lines[1..].iter().map(|l| l.split_whitespace().next().unwrap()).collect();
+        assert_eq!(order, ["root", "0", "1", "0.0"]);
+        assert!(lines[2].contains(&prefs.quantity("0xc350")));
+    }
+
+    #[test]
+    fn filter_builder_round_trips() {
+        let fields: Vec<String> = ["19000000", "latest", &format!("{}, {}", A, B), "", "0x10", "5"].map(str::to_string).to_vec();
+        let params = assemble_filter(&fields).unwrap();
+        assert_eq!(
+            params,
+            json!([{"fromBlock": "0x121eac0", "toBlock": "latest", "fromAddress": [A, B], "after": 16, "count": 5}])
+        );
+        let back = filter_fields(&params).unwrap();
+        assert_eq!(back, ["0x121eac0", "latest", &format!("{}, {}", A, B), "", "16", "5"]);
+        assert_eq!(assemble_filter(&back).unwrap(), params);
+
+        assert!(assemble_filter(&["soon".to_string()]).unwrap_err().to_string().starts_with("fromBlock:"));
+        assert!(assemble_filter(&["".into(), "".into(), "0x12".into()]).is_err());
+        assert_eq!(filter_fields(&json!([{"mode": "union"}])), None);
+    }
+}
@@ -0,0 +1,210 @@
+// src/tutorial.rs
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::action::Action;
+use crate::app::{App, AppMode};
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse, Transport};
+use crate::shape::{shape_of, Shape};
+
+/// Session endpoint shown during the tutorial; nothing listens there.
+pub const ENDPOINT: &str = "offline://tutorial";
+
+/// Canned answers for the tutorial, so it works without a node.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OfflineTransport;
+
+#[async_trait]
+impl Transport for OfflineTransport {
+    async fn send(&self, _url: &str, req: JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let result = match req.method.as_str() {
+            "eth_chainId" => json!("0x1"),
+            "eth_syncing" => json!(false),
+            "eth_blockNumber" => json!("0x12a05f2"),
+            "eth_gasPrice" => json!("0x4a817c800"),
+            // 1.5 ether
+            "eth_getBalance" => json!("0x14d1120d7b160000"),
+            "eth_getTransactionCount" => json!("0x0"),
+            _ => {
+                let error = json!({"code": -32601, "message": "not available in the tutorial"});
+                return Ok(JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: Some(error), id: req.id });
+            }
+        };
+        Ok(JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(result), error: None, id: req.id })
+    }
+}
+
+/// The part of the screen a step is about; drawn highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Search,
+    Methods,
+    Params,
+    History,
+    Response,
+}
+
+/// What completes a step.
+#[derive(Debug, Clone, Copy)]
+pub enum Expect {
+    /// The user triggers this action and it succeeds.
+    Action(Action),
+    /// The app reaches a state this predicate accepts.
+    State(fn(&App) -> bool),
+}
+
+/// One instruction of the walkthrough.
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    pub instruction: &'static str,
+    pub pane: Pane,
+    pub expect: Expect,
+}
+
+const fn step(instruction: &'static str, pane: Pane, expect: Expect) -> Step {
+    Step { instruction, pane, expect }
+}
+
+/// The walkthrough, in order.
+pub const STEPS: &[Step] = &[
+    step("type 'bal' to filter the method list", Pane::Search, Expect::State(|app| app.search_input.contains("bal"))),
+    step(
+        "press Enter to select eth_getBalance",
+        Pane::Methods,
+        Expect::State(|app| app.mode == AppMode::ParamInput && app.selected_method() == Some("eth_getBalance")),
+    ),
+    step(
+        "fill the address field: 0x followed by 40 hex digits",
+        Pane::Params,
+        Expect::State(|app| app.param_inputs.first().is_some_and(|p| shape_of(p) == Shape::Address)),
+    ),
+    step("press Enter to send — the tutorial answers offline", Pane::Params, Expect::Action(Action::Send)),
+    step("clear the search with Backspace, then press 'h' for the history", Pane::History, Expect::State(|app| app.mode == AppMode::History)),
+    step("press Enter to read the response", Pane::Response, Expect::State(|app| app.mode == AppMode::ResponseView)),
+];
+
+/// Progress through a list of steps.
+#[derive(Debug, Clone)]
+pub struct Tutorial {
+    steps: &'static [Step],
+    current: usize,
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Tutorial::new(STEPS)
+    }
+}
+
+impl Tutorial {
+    pub fn new(steps: &'static [Step]) -> Self {
+        Tutorial { steps, current: 0 }
+    }
+
+    /// The step waiting to be completed; `None` once all are done.
+    pub fn step(&self) -> Option<&Step> {
+        self.steps.get(self.current)
+    }
+
+    /// Whether `pane` should be highlighted.
+    pub fn highlights(&self, pane: Pane) -> bool {
+        self.step().is_some_and(|s| s.pane == pane)
+    }
+
+    /// "TUTORIAL 2/6 — press Enter … (Esc exits)" for the banner.
+    pub fn banner(&self) -> String {
+        match self.step() {
+            Some(step) => {
+                format!("TUTORIAL {}/{} — {} (Esc exits)", self.current + 1, self.steps.len(), step.instruction)
+            }
+            None => "TUTORIAL done — that's the whole loop: filter, fill, send, review. Esc exits".to_string(),
+        }
+    }
+
+    /// Advances past the current step if it waits for `action`.
+    pub fn on_action(&mut self, action: Action) {
+        if matches!(self.step(), Some(Step { expect: Expect::Action(expected), .. }) if *expected == action) {
+            self.current += 1;
+        }
+    }
+
+    /// Advances past every leading step whose state predicate `app` meets.
+    pub fn on_state(&mut self, app: &App) {
+        while let Some(Step { expect: Expect::State(done), .. }) = self.step() {
+            if !done(app) {
+                break;
+            }
+            self.current += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "0x00000000000000000000000000000000000000a1";
+
+    #[test]
+    fn advances_only_on_the_expected_action_or_state() {
+        let mut app = App::new();
+        let mut tutorial = Tutorial::default();
+        tutorial.on_state(&app);
+        assert!(tutorial.highlights(Pane::Search));
+
+        // Scripted session: wrong moves leave the step where it is
+        app.search_input = "ba".into();
+        tutorial.on_state(&app);
+        tutorial.on_action(Action::Send);
+        assert_eq!(tutorial.current, 0);
+        app.search_input = "bal".into();
+        app.filter_methods();
+        tutorial.on_state(&app);
+        assert_eq!(tutorial.current, 1);
+
+        app.mode = AppMode::ParamInput;
+        app.param_inputs = vec!["0xabc".into()];
+        tutorial.on_state(&app);
+        assert_eq!(tutorial.current, 2);
+        app.param_inputs = vec![ADDRESS.into()];
+        tutorial.on_state(&app);
+        assert!(tutorial.banner().starts_with("TUTORIAL 4/6 — press Enter to send"));
+
+        tutorial.on_action(Action::ProbeMethod);
+        assert_eq!(tutorial.current, 3);
+        tutorial.on_action(Action::Send);
+        app.mode = AppMode::History;
+        tutorial.on_state(&app);
+        app.mode = AppMode::ResponseView;
+        tutorial.on_state(&app);
+        assert!(tutorial.step().is_none());
+        assert!(tutorial.banner().contains("Esc exits"));
+    }
+
+    #[test]
+    fn state_steps_already_met_are_skipped_together() {
+        let mut app = App::new();
+        app.search_input = "bal".into();
+        app.filter_methods();
+        app.mode = AppMode::ParamInput;
+        let mut tutorial = Tutorial::default();
+        tutorial.on_state(&app);
+        assert_eq!(tutorial.current, 2);
+        // An action step stops the sweep even when later predicates hold
+        app.param_inputs = vec![ADDRESS.into()];
+        app.mode = AppMode::History;
+        tutorial.on_state(&app);
+        assert_eq!(tutorial.current, 3);
+    }
+
+    #[tokio::test]
+    async fn offline_transport_answers_the_walkthrough() {
+        let request = JsonRpcRequest::new("eth_getBalance", json!([ADDRESS]), 7);
+        let response = OfflineTransport.send(ENDPOINT, request).await.unwrap();
+        assert_eq!((response.result, response.id), (Some(json!("0x14d1120d7b160000")), 7));
+        let other = OfflineTransport.send(ENDPOINT, JsonRpcRequest::new("debug_traceCall", json!([]), 1)).await.unwrap();
+        assert_eq!(other.error.unwrap()["code"], -32601);
+    }
+}
@@ -0,0 +1,432 @@
+// src/tx.rs
+
+use anyhow::{anyhow, bail, Result};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+
+use crate::prefs::DisplayPrefs;
+
+/// Transaction envelope, as far as signing is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxKind {
+    /// Legacy transaction signed without a chain id (v = 27/28).
+    Legacy,
+    /// Legacy transaction with EIP-155 replay protection.
+    Legacy155 { chain_id: u64 },
+    /// Type 1, EIP-2930 access-list transaction.
+    Eip2930,
+    /// Type 2, EIP-1559 dynamic-fee transaction.
+    Eip1559,
+    /// Type 3, EIP-4844 blob transaction.
+    Eip4844,
+}
+
+impl TxKind {
+    pub fn label(&self) -> String {
+        match self {
+            TxKind::Legacy => "legacy (pre-EIP-155)".to_string(),
+            TxKind::Legacy155 { chain_id } => format!("legacy (EIP-155, chain {})", chain_id),
+            TxKind::Eip2930 => "type 1 (EIP-2930)".to_string(),
+            TxKind::Eip1559 => "type 2 (EIP-1559)".to_string(),
+            TxKind::Eip4844 => "type 3 (EIP-4844)".to_string(),
+        }
+    }
+}
+
+/// Outcome of checking a transaction's signature against its `from` field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SenderCheck {
+    Verified { kind: TxKind },
+    Mismatch { kind: TxKind, recovered: String, claimed: String },
+}
+
+/// Minimal RLP item.
+enum Rlp {
+    Bytes(Vec<u8>),
+    List(Vec<Rlp>),
+}
+
+impl Rlp {
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            Rlp::Bytes(b) if b.len() == 1 && b[0] < 0x80 => b.clone(),
+            Rlp::Bytes(b) => [length_prefix(0x80, b.len()), b.clone()].concat(),
+            Rlp::List(items) => {
+                let body: Vec<u8> = items.iter().flat_map(Rlp::encode).collect();
+                [length_prefix(0xc0, body.len()), body].concat()
+            }
+        }
+    }
+}
+
+fn length_prefix(offset: u8, len: usize) -> Vec<u8> {
+    if len < 56 {
+        return vec![offset + len as u8];
+    }
+    let len_bytes: Vec<u8> = len.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+    [vec![offset + 55 + len_bytes.len() as u8], len_bytes].concat()
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    let s = s.strip_prefix("0x").ok_or_else(|| anyhow!("'{}' is not 0x-prefixed hex", s))?;
+    // Checked up front: slicing below assumes one byte per char
+    if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(anyhow!("invalid hex '0x{}'", s));
+    }
+    let s = if s.len() % 2 == 1 { format!("0{}", s) } else { s.to_string() };
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|_| anyhow!("invalid hex '{}'", s)))
+        .collect()
+}
+
+fn field<'a>(tx: &'a Value, name: &str) -> Result<&'a str> {
+    tx.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("transaction is missing '{}'", name))
+}
+
+/// A hex quantity as minimal big-endian bytes (zero is empty).
+fn quantity(tx: &Value, name: &str) -> Result<Rlp> {
+    let bytes = decode_hex(field(tx, name)?)?;
+    Ok(Rlp::Bytes(bytes.into_iter().skip_while(|b| *b == 0).collect()))
+}
+
+fn quantity_u64(tx: &Value, name: &str) -> Result<u64> {
+    let s = field(tx, name)?;
+    u64::from_str_radix(s.trim_start_matches("0x"), 16).map_err(|_| anyhow!("'{}' is not a u64 quantity: {}", name, s))
+}
+
+fn data(tx: &Value, name: &str) -> Result<Rlp> {
+    Ok(Rlp::Bytes(decode_hex(field(tx, name)?)?))
+}
+
+/// `to`, which is null for contract creation.
+fn to(tx: &Value) -> Result<Rlp> {
+    match tx.get("to") {
+        None | Some(Value::Null) => Ok(Rlp::Bytes(Vec::new())),
+        Some(_) => data(tx, "to"),
+    }
+}
+
+fn access_list(tx: &Value) -> Result<Rlp> {
+    let entries = tx.get("accessList").and_then(Value::as_array).cloned().unwrap_or_default();
+    let items = entries
+        .iter()
+        .map(|entry| {
+            let keys = entry
+                .get("storageKeys")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default()
+                .iter()
+                .map(|k| Ok(Rlp::Bytes(decode_hex(k.as_str().unwrap_or_default())?)))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Rlp::List(vec![data(entry, "address")?, Rlp::List(keys)]))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Rlp::List(items))
+}
+
+fn blob_hashes(tx: &Value) -> Result<Rlp> {
+    let hashes = tx.get("blobVersionedHashes").and_then(Value::as_array).cloned().unwrap_or_default();
+    let items = hashes
+        .iter()
+        .map(|h| Ok(Rlp::Bytes(decode_hex(h.as_str().unwrap_or_default())?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Rlp::List(items))
+}
+
+/// Determines the envelope from `type` and, for legacy transactions, `v`.
+pub fn tx_kind(tx: &Value) -> Result<TxKind> {
+    let ty = tx.get("type").and_then(Value::as_str).unwrap_or("0x0");
+    match u64::from_str_radix(ty.trim_start_matches("0x"), 16) {
+        Ok(0) => match quantity_u64(tx, "v")? {
+            27 | 28 => Ok(TxKind::Legacy),
+            v if v >= 35 => Ok(TxKind::Legacy155 { chain_id: (v - 35) / 2 }),
+            v => bail!("unexpected legacy v value {}", v),
+        },
+        Ok(1) => Ok(TxKind::Eip2930),
+        Ok(2) => Ok(TxKind::Eip1559),
+        Ok(3) => Ok(TxKind::Eip4844),
+        _ => bail!("unsupported transaction type {}", ty),
+    }
+}
+
+/// The exact bytes that were hashed and signed.
+pub fn signing_payload(tx: &Value) -> Result<(TxKind, Vec<u8>)> {
+    let kind = tx_kind(tx)?;
+    let legacy = || -> Result<Vec<Rlp>> {
+        Ok(vec![
+            quantity(tx, "nonce")?,
+            quantity(tx, "gasPrice")?,
+            quantity(tx, "gas")?,
+            to(tx)?,
+            quantity(tx, "value")?,
+            data(tx, "input")?,
+        ])
+    };
+    let payload = match kind {
+        TxKind::Legacy => Rlp::List(legacy()?).encode(),
+        TxKind::Legacy155 { chain_id } => {
+            let mut fields = legacy()?;
+            let chain: Vec<u8> = chain_id.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+            fields.extend([Rlp::Bytes(chain), Rlp::Bytes(Vec::new()), Rlp::Bytes(Vec::new())]);
+            Rlp::List(fields).encode()
+        }
+        TxKind::Eip2930 => {
+            let fields = vec![
+                quantity(tx, "chainId")?,
+                quantity(tx, "nonce")?,
+                quantity(tx, "gasPrice")?,
+                quantity(tx, "gas")?,
+                to(tx)?,
+                quantity(tx, "value")?,
+                data(tx, "input")?,
+                access_list(tx)?,
+            ];
+            [vec![0x01], Rlp::List(fields).encode()].concat()
+        }
+        TxKind::Eip1559 | TxKind::Eip4844 => {
+            let mut fields = vec![
+                quantity(tx, "chainId")?,
+                quantity(tx, "nonce")?,
+                quantity(tx, "maxPriorityFeePerGas")?,
+                quantity(tx, "maxFeePerGas")?,
+                quantity(tx, "gas")?,
+                to(tx)?,
+                quantity(tx, "value")?,
+                data(tx, "input")?,
+                access_list(tx)?,
+            ];
+            let prefix = if kind == TxKind::Eip4844 {
+                fields.extend([quantity(tx, "maxFeePerBlobGas")?, blob_hashes(tx)?]);
+                0x03
+            } else {
+                0x02
+            };
+            [vec![prefix], Rlp::List(fields).encode()].concat()
+        }
+    };
+    Ok((kind, payload))
+}
+
+/// Keccak-256 of the signing payload.
+pub fn signing_hash(tx: &Value) -> Result<(TxKind, [u8; 32])> {
+    let (kind, payload) = signing_payload(tx)?;
+    Ok((kind, Keccak256::digest(&payload).into()))
+}
+
+/// Lower-case 0x address of an uncompressed public key.
+fn address_of(key: &VerifyingKey) -> String {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    format!("0x{}", hash[12..].iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+/// Recovers the signer's address from `v`/`yParity`, `r`, and `s`.
+pub fn recover_sender(tx: &Value) -> Result<(TxKind, String)> {
+    let (kind, hash) = signing_hash(tx)?;
+    let parity = match kind {
+        TxKind::Legacy => quantity_u64(tx, "v")? - 27,
+        TxKind::Legacy155 { chain_id } => quantity_u64(tx, "v")? - 35 - 2 * chain_id,
+        _ => quantity_u64(tx, "yParity").or_else(|_| quantity_u64(tx, "v"))?,
+    };
+    let scalar = |name: &str| -> Result<[u8; 32]> {
+        let bytes = decode_hex(field(tx, name)?)?;
+        let bytes: Vec<u8> = bytes.into_iter().skip_while(|b| *b == 0).collect();
+        if bytes.len() > 32 {
+            bail!("'{}' is longer than 32 bytes", name);
+        }
+        let mut out = [0u8; 32];
+        out[32 - bytes.len()..].copy_from_slice(&bytes);
+        Ok(out)
+    };
+    let signature = Signature::from_scalars(scalar("r")?, scalar("s")?)?;
+    let recovery = RecoveryId::from_byte(parity as u8).ok_or_else(|| anyhow!("invalid recovery id {}", parity))?;
+    let key = VerifyingKey::recover_from_prehash(&hash, &signature, recovery)?;
+    Ok((kind, address_of(&key)))
+}
+
+/// Recovers the sender and compares it with the transaction's `from`.
+pub fn verify_sender(tx: &Value) -> Result<SenderCheck> {
+    let (kind, recovered) = recover_sender(tx)?;
+    let claimed = field(tx, "from")?.to_lowercase();
+    if recovered == claimed {
+        Ok(SenderCheck::Verified { kind })
+    } else {
+        Ok(SenderCheck::Mismatch { kind, recovered, claimed })
+    }
+}
+
+/// One-line verdict for display above a transaction response.
+pub fn sender_summary(tx: &Value, prefs: &DisplayPrefs) -> String {
+    match verify_sender(tx) {
+        Ok(SenderCheck::Verified { kind }) => format!("{} • sender verified ✓", kind.label()),
+        Ok(SenderCheck::Mismatch { kind, recovered, claimed }) => format!(
+            "{} • ⚠ sender mismatch: signature recovers {} but from is {}",
+            kind.label(),
+            prefs.address(&recovered),
+            prefs.address(&claimed)
+        ),
+        Err(e) => format!("sender not verified: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+    use serde_json::json;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// The example transaction from the EIP-155 specification.
+    fn eip155_example() -> Value {
+        json!({
+            "type": "0x0",
+            "nonce": "0x9",
+            "gasPrice": "0x4a817c800",
+            "gas": "0x5208",
+            "to": "0x3535353535353535353535353535353535353535",
+            "value": "0xde0b6b3a7640000",
+            "input": "0x",
+            "v": "0x25",
+            "r": "0x28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276",
+            "s": "0x67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+            "from": "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"
+        })
+    }
+
+    /// Signs `tx` with the EIP-155 example key and fills in the signature fields.
+    fn sign(mut tx: Value) -> Value {
+        let key = SigningKey::from_slice(&[0x46; 32]).unwrap();
+        let (kind, hash) = signing_hash(&tx).unwrap();
+        let (sig, recid) = key.sign_prehash_recoverable(&hash).unwrap();
+        let (r, s) = sig.split_bytes();
+        let parity = recid.to_byte() as u64;
+        tx["r"] = json!(format!("0x{}", hex(&r)));
+        tx["s"] = json!(format!("0x{}", hex(&s)));
+        tx["v"] = json!(format!("0x{:x}", if kind == TxKind::Legacy { 27 + parity } else { parity }));
+        if kind != TxKind::Legacy {
+            tx["yParity"] = json!(format!("0x{:x}", parity));
+        }
+        tx["from"] = json!(address_of(key.verifying_key()));
+        tx
+    }
+
+    fn base(ty: &str) -> Value {
+        json!({
+            "type": ty, "chainId": "0x1", "nonce": "0x0", "gas": "0x5208",
+            "to": "0x3535353535353535353535353535353535353535", "value": "0x0", "input": "0x",
+            "accessList": [], "v": "0x1b"
+        })
+    }
+
+    #[test]
+    fn eip155_spec_vector() {
+        let tx = eip155_example();
+        let (kind, payload) = signing_payload(&tx).unwrap();
+        assert_eq!(kind, TxKind::Legacy155 { chain_id: 1 });
+        assert_eq!(
+            hex(&payload),
+            "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080"
+        );
+        assert_eq!(
+            hex(&signing_hash(&tx).unwrap().1),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+        assert_eq!(verify_sender(&tx).unwrap(), SenderCheck::Verified { kind });
+    }
+
+    #[test]
+    fn tampered_from_is_a_mismatch() {
+        let mut tx = eip155_example();
+        tx["from"] = json!("0x0000000000000000000000000000000000000001");
+        match verify_sender(&tx).unwrap() {
+            SenderCheck::Mismatch { recovered, .. } => {
+                assert_eq!(recovered, "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f")
+            }
+            other => panic!("expected mismatch, got {:?}", other),
+        }
+        assert!(sender_summary(&tx, &DisplayPrefs::default()).contains("mismatch"));
+    }
+
+    #[test]
+    fn non_ascii_hex_is_an_error() {
+        assert!(decode_hex("0x1é").is_err());
+        assert!(decode_hex("0xé1").is_err());
+        let mut tx = eip155_example();
+        tx["r"] = json!("0xaé");
+        assert!(verify_sender(&tx).is_err());
+    }
+
+    #[test]
+    fn legacy_without_chain_id() {
+        let mut tx = base("0x0");
+        tx["gasPrice"] = json!("0x1");
+        let tx = sign(tx);
+        let (kind, payload) = signing_payload(&tx).unwrap();
+        assert_eq!(kind, TxKind::Legacy);
+        // [nonce, gasPrice, gas, to, value, data] with no chain id suffix
+        assert_eq!(hex(&payload), format!("dc8001825208{}8080", "94".to_string() + &"35".repeat(20)));
+        assert!(sender_summary(&tx, &DisplayPrefs::default()).ends_with("sender verified ✓"));
+    }
+
+    #[test]
+    fn eip2930_field_order() {
+        let mut tx = base("0x1");
+        tx["gasPrice"] = json!("0x1");
+        tx["accessList"] = json!([{ "address": "0x3535353535353535353535353535353535353535", "storageKeys": [] }]);
+        let tx = sign(tx);
+        let (_, payload) = signing_payload(&tx).unwrap();
+        let to = "94".to_string() + &"35".repeat(20);
+        // 0x01 || [chainId, nonce, gasPrice, gas, to, value, data, [[address, []]]]
+        assert_eq!(hex(&payload), format!("01f5018001825208{}8080d7d6{}c0", to, to));
+        assert_eq!(verify_sender(&tx).unwrap(), SenderCheck::Verified { kind: TxKind::Eip2930 });
+    }
+
+    #[test]
+    fn eip1559_field_order() {
+        let mut tx = base("0x2");
+        tx["maxPriorityFeePerGas"] = json!("0x1");
+        tx["maxFeePerGas"] = json!("0x2");
+        let tx = sign(tx);
+        let (_, payload) = signing_payload(&tx).unwrap();
+        // 0x02 || [chainId, nonce, tip, maxFee, gas, to, value, data, accessList]
+        assert_eq!(hex(&payload), format!("02df01800102825208{}8080c0", "94".to_string() + &"35".repeat(20)));
+        assert_eq!(verify_sender(&tx).unwrap(), SenderCheck::Verified { kind: TxKind::Eip1559 });
+    }
+
+    #[test]
+    fn eip4844_field_order() {
+        let mut tx = base("0x3");
+        tx["maxPriorityFeePerGas"] = json!("0x1");
+        tx["maxFeePerGas"] = json!("0x2");
+        tx["maxFeePerBlobGas"] = json!("0x3");
+        tx["blobVersionedHashes"] = json!([format!("0x01{}", "00".repeat(31))]);
+        let tx = sign(tx);
+        let (_, payload) = signing_payload(&tx).unwrap();
+        // ... accessList, maxFeePerBlobGas, [hash]
+        let expected = format!(
+            "03f84201800102825208{}8080c003e1a001{}",
+            "94".to_string() + &"35".repeat(20),
+            "00".repeat(31)
+        );
+        assert_eq!(hex(&payload), expected);
+        assert_eq!(verify_sender(&tx).unwrap(), SenderCheck::Verified { kind: TxKind::Eip4844 });
+    }
+
+    #[test]
+    fn contract_creation_has_empty_to() {
+        let mut tx = base("0x2");
+        tx["to"] = Value::Null;
+        tx["maxPriorityFeePerGas"] = json!("0x1");
+        tx["maxFeePerGas"] = json!("0x2");
+        let (_, payload) = signing_payload(&tx).unwrap();
+        assert_eq!(hex(&payload), "02cb01800102825208808080c0");
+    }
+}
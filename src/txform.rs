@@ -0,0 +1,234 @@
+// src/txform.rs
+
+use anyhow::{bail, Result};
+use serde_json::{Map, Value};
+
+use crate::provenance::Trail;
+use crate::shape::{shape_of, Shape};
+
+pub const METHOD: &str = "eth_sendTransaction";
+
+/// Form rows: (label, object key, help). Amount rows accept a unit suffix.
+pub const FIELDS: &[(&str, &str, &str)] = &[
+    ("from", "from", "required: sending account, 20-byte 0x-prefixed address"),
+    ("to", "to", "recipient address; leave empty to deploy a contract"),
+    ("value", "value", "amount sent: wei, or with a unit, e.g. 0.5 ether / 20 gwei"),
+    ("gas", "gas", "gas limit, decimal or hex; empty lets the node estimate"),
+    ("gas price", "gasPrice", "legacy fee per gas, e.g. 20 gwei; don't combine with the 1559 fees"),
+    ("max fee", "maxFeePerGas", "EIP-1559 max fee per gas, e.g. 30 gwei"),
+    ("max priority fee", "maxPriorityFeePerGas", "EIP-1559 tip per gas, e.g. 1.5 gwei"),
+    ("nonce", "nonce", "decimal or hex; Ctrl+N fetches the pending nonce of `from`"),
+    ("input", "input", "calldata, 0x-prefixed hex"),
+];
+
+pub const FROM: usize = 0;
+pub const NONCE: usize = 7;
+const TO: usize = 1;
+const GAS: usize = 3;
+const GAS_PRICE: usize = 4;
+const MAX_FEE: usize = 5;
+const MAX_PRIORITY_FEE: usize = 6;
+const INPUT: usize = 8;
+
+/// Field values being edited for `eth_sendTransaction`, and which one has focus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxForm {
+    pub fields: Vec<String>,
+    pub focus: usize,
+}
+
+impl Default for TxForm {
+    fn default() -> Self {
+        TxForm { fields: vec![String::new(); FIELDS.len()], focus: 0 }
+    }
+}
+
+impl TxForm {
+    /// A form holding the transaction in `params`, or `None` if it uses
+    /// keys the form has no row for (access lists, blobs, …).
+    pub fn from_params(params: &Value) -> Option<Self> {
+        let [Value::Object(tx)] = params.as_array()?.as_slice() else { return None };
+        if tx.keys().any(|k| !FIELDS.iter().any(|(_, key, _)| key == k)) {
+            return None;
+        }
+        let fields = FIELDS
+            .iter()
+            .map(|(_, key, _)| match tx.get(*key) {
+                None => Some(String::new()),
+                Some(value) => value.as_str().map(str::to_string),
+            })
+            .collect::<Option<_>>()?;
+        Some(TxForm { fields, focus: 0 })
+    }
+
+    pub fn focused_mut(&mut self) -> &mut String {
+        &mut self.fields[self.focus]
+    }
+
+    /// Moves focus by `delta` rows, wrapping around.
+    pub fn move_focus(&mut self, delta: isize) {
+        self.focus = (self.focus as isize + delta).rem_euclid(FIELDS.len() as isize) as usize;
+    }
+}
+
+/// `text` in wei as a hex quantity. Plain numbers are wei; `wei`, `gwei`
+/// and `ether` (or `eth`) suffixes scale them, allowing as many decimals
+/// as the unit has. Hex is passed through.
+pub fn parse_amount(name: &str, text: &str) -> Result<String> {
+    let text = text.trim();
+    if text.starts_with("0x") {
+        return parse_quantity(name, text);
+    }
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = (&text[..split], text[split..].trim());
+    let decimals = match unit.to_ascii_lowercase().as_str() {
+        "" | "wei" => 0,
+        "gwei" => 9,
+        "eth" | "ether" => 18,
+        other => bail!("{}: unknown unit {:?} (wei, gwei or ether)", name, other),
+    };
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty() || fraction.contains('.') {
+        bail!("{}: expected a number, e.g. 1.5 gwei", name);
+    }
+    if fraction.len() > decimals {
+        bail!("{}: at most {} decimals in {}", name, decimals, if unit.is_empty() { "wei" } else { unit });
+    }
+    let digits = format!("{}{:0<width$}", whole, fraction, width = decimals);
+    match digits.parse::<u128>() {
+        Ok(wei) => Ok(format!("0x{:x}", wei)),
+        Err(_) => bail!("{}: amount too large", name),
+    }
+}
+
+/// A decimal or hex integer as a hex quantity.
+fn parse_quantity(name: &str, text: &str) -> Result<String> {
+    let text = text.trim();
+    match shape_of(text) {
+        Shape::Quantity => Ok(text.to_lowercase()),
+        Shape::Decimal => match text.parse::<u64>() {
+            Ok(n) => Ok(format!("0x{:x}", n)),
+            Err(_) => bail!("{}: number too large", name),
+        },
+        _ => bail!("{}: expected a decimal or 0x-prefixed hex number", name),
+    }
+}
+
+/// Warning for a form that sets both the legacy and the EIP-1559 fee fields.
+pub fn fee_conflict(fields: &[String]) -> Option<&'static str> {
+    let set = |i: usize| fields.get(i).is_some_and(|f| !f.trim().is_empty());
+    (set(GAS_PRICE) && (set(MAX_FEE) || set(MAX_PRIORITY_FEE)))
+        .then_some("both gas price and 1559 fees are set — nodes reject or ignore one of them")
+}
+
+/// The transaction object for the form's non-empty fields. `from` is
+/// required; addresses and calldata are checked, amounts and quantities
+/// converted to hex, with each conversion noted in `trail`.
+pub fn assemble(fields: &[String], trail: &mut Trail) -> Result<Value> {
+    let field = |i: usize| fields.get(i).map(|f| f.trim()).unwrap_or("");
+    if field(FROM).is_empty() {
+        bail!("from: required");
+    }
+    let mut tx = Map::new();
+    for (i, (label, key, _)) in FIELDS.iter().enumerate() {
+        let text = field(i);
+        if text.is_empty() {
+            continue;
+        }
+        let value = match i {
+            FROM | TO if shape_of(text) != Shape::Address => bail!("{}: expected a 20-byte 0x-prefixed address", label),
+            FROM | TO => text.to_string(),
+            INPUT if !text.starts_with("0x") || !text[2..].chars().all(|c| c.is_ascii_hexdigit()) => {
+                bail!("{}: expected 0x-prefixed hex", label)
+            }
+            INPUT => text.to_string(),
+            GAS | NONCE => parse_quantity(label, text)?,
+            _ => parse_amount(label, text)?,
+        };
+        if value != text {
+            let transform = if text.starts_with("0x") {
+                "lowercased"
+            } else if text.ends_with(|c: char| c.is_ascii_alphabetic()) {
+                "unit conversion"
+            } else {
+                "decimal → hex"
+            };
+            trail.record(label, text, transform, &value);
+        }
+        tx.insert(key.to_string(), Value::String(value));
+    }
+    Ok(Value::Object(tx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FROM_ADDR: &str = "0x00000000000000000000000000000000000000a1";
+    const TO_ADDR: &str = "0x00000000000000000000000000000000000000b2";
+
+    fn assemble_form(fields: &[String]) -> Result<Value> {
+        assemble(fields, &mut Trail::default())
+    }
+
+    fn form(values: &[(usize, &str)]) -> Vec<String> {
+        let mut fields = vec![String::new(); FIELDS.len()];
+        for (i, v) in values {
+            fields[*i] = v.to_string();
+        }
+        fields
+    }
+
+    #[test]
+    fn assembles_legacy_transactions() {
+        let fields = form(&[(FROM, FROM_ADDR), (TO, TO_ADDR), (2, "0.5 ether"), (GAS, "21000"), (GAS_PRICE, "20 gwei"), (NONCE, "7")]);
+        assert_eq!(fee_conflict(&fields), None);
+        let mut trail = Trail::default();
+        assert_eq!(
+            assemble(&fields, &mut trail).unwrap(),
+            json!({"from": FROM_ADDR, "to": TO_ADDR, "value": "0x6f05b59d3b20000", "gas": "0x5208", "gasPrice": "0x4a817c800", "nonce": "0x7"})
+        );
+        // Every converted field is on the trail; addresses pass unchanged
+        let changed: Vec<_> = trail.0.iter().map(|t| (t.param.as_str(), t.changes[0].transform.as_str())).collect();
+        assert_eq!(
+            changed,
+            [("value", "unit conversion"), ("gas", "decimal → hex"), ("gas price", "unit conversion"), ("nonce", "decimal → hex")]
+        );
+        assert_eq!(trail.0[0].changes[0].after, "0x6f05b59d3b20000");
+    }
+
+    #[test]
+    fn assembles_1559_transactions() {
+        let fields = form(&[(FROM, FROM_ADDR), (MAX_FEE, "30gwei"), (MAX_PRIORITY_FEE, "1.5 gwei"), (INPUT, "0x60806040")]);
+        assert_eq!(fee_conflict(&fields), None);
+        assert_eq!(
+            assemble_form(&fields).unwrap(),
+            json!({"from": FROM_ADDR, "maxFeePerGas": "0x6fc23ac00", "maxPriorityFeePerGas": "0x59682f00", "input": "0x60806040"})
+        );
+    }
+
+    #[test]
+    fn mixed_fee_fields_warn_but_assemble() {
+        let fields = form(&[(FROM, FROM_ADDR), (GAS_PRICE, "0x1"), (MAX_PRIORITY_FEE, "1 gwei")]);
+        assert!(fee_conflict(&fields).is_some());
+        assert_eq!(assemble_form(&fields).unwrap()["gasPrice"], "0x1");
+    }
+
+    #[test]
+    fn rejects_bad_fields() {
+        assert_eq!(assemble_form(&form(&[])).unwrap_err().to_string(), "from: required");
+        assert!(assemble_form(&form(&[(FROM, "0xabc")])).is_err());
+        assert!(assemble_form(&form(&[(FROM, FROM_ADDR), (2, "1 finney")])).is_err());
+        assert!(assemble_form(&form(&[(FROM, FROM_ADDR), (GAS_PRICE, "1.0000000001 gwei")])).is_err());
+        assert!(assemble_form(&form(&[(FROM, FROM_ADDR), (INPUT, "deadbeef")])).is_err());
+    }
+
+    #[test]
+    fn form_round_trips_through_params() {
+        let fields = form(&[(FROM, FROM_ADDR), (2, "0x10"), (NONCE, "0x3")]);
+        let params = json!([assemble_form(&fields).unwrap()]);
+        assert_eq!(TxForm::from_params(&params).unwrap().fields, fields);
+        assert_eq!(TxForm::from_params(&json!([{"from": FROM_ADDR, "accessList": []}])), None);
+    }
+}
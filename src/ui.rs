@@ -2,23 +2,342 @@
 
 use ratatui::{
   Frame,
-  layout::{Constraint, Direction, Layout},
-  style::{Color, Style},
-  widgets::{Block, Borders, List, ListItem, Paragraph},
+  layout::{Constraint, Direction, Layout, Rect},
+  style::{Color, Modifier, Style},
+  text::{Line, Span},
+  widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Sparkline, Wrap},
 };
+use unicode_width::UnicodeWidthStr;
 use crate::app::{App, AppMode};
+use crate::dashboard::card_grid;
+use crate::docs::DocsTab;
+use crate::editor::display_column;
+use crate::hexdump::{HexView, ROW, WORD};
+use crate::endpoint::redact_url;
+use crate::focus::Focus;
+use crate::hints::{self, Context};
+use crate::popup::{preview, EditorPopup};
+use crate::prefs::SETTING_COUNT;
+use crate::pretty;
+use crate::schematree;
+use crate::spec::ParamKind;
+use crate::stats::Health;
+use crate::txform::{self, TxForm};
+use crate::tutorial::Pane;
+use crate::view::Tab;
 
 /// Top-level dispatch: draw according to current AppMode
 pub fn draw_ui(f: &mut Frame, app: &mut App) {
+  // The status bar takes the bottom row in every mode
+  let [body, bar] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(f.area());
+  let mut area = body;
+  // Spectator sessions are marked on every screen
+  if app.spectator {
+      let [banner, rest] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+      let text = " SPECTATOR — read-only: sending and saving are disabled ";
+      f.render_widget(Paragraph::new(text).style(Style::default().bg(Color::Magenta).fg(Color::White)), banner);
+      area = rest;
+  }
+  // So is a syncing session endpoint, whose latest/pending answers lag
+  if let Some(badge) = app.stats.last().sync.badge(&app.prefs) {
+      let [banner, rest] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+      f.render_widget(Paragraph::new(format!(" {} ", badge)).style(Style::default().bg(Color::Yellow).fg(Color::Black)), banner);
+      area = rest;
+  }
+  // The tutorial's current instruction stays on top of every screen
+  if let Some(tutorial) = &app.tutorial {
+      let [banner, rest] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+      f.render_widget(Paragraph::new(format!(" {} ", tutorial.banner())).style(Style::default().bg(Color::Green).fg(Color::Black)), banner);
+      area = rest;
+  }
   match app.mode {
-      AppMode::Main       => draw_main_mode(f, app),
-      AppMode::ParamInput => draw_param_input_mode(f, app),
-      AppMode::History    => draw_history_mode(f, app),
+      AppMode::Main       => draw_main_mode(f, app, area),
+      AppMode::ParamInput => draw_param_input_mode(f, app, area),
+      AppMode::History    => draw_history_mode(f, app, area),
+      AppMode::ResponseView => draw_response_view(f, app, area),
+      AppMode::Dashboard => draw_dashboard(f, app, area),
+  }
+  if app.settings.is_some() {
+      draw_settings_popup(f, app);
+  }
+  if app.activity.is_some() {
+      draw_activity_popup(f, app);
+  }
+  if app.profile_picker.is_some() {
+      draw_profile_picker(f, app);
+  }
+  if app.lease_prompt.is_some() {
+      draw_lease_prompt(f, app);
+  }
+  if app.help.is_some() {
+      draw_help_overlay(f, app);
+  }
+  // Where Esc leads, over the bottom border; nothing to show at the root
+  let popup = app.settings.is_some()
+      || app.activity.is_some()
+      || app.profile_picker.is_some()
+      || app.lease_prompt.is_some()
+      || app.editor_popup.is_some()
+      || app.help.is_some();
+  if app.mode != AppMode::Main || !app.nav.is_empty() || popup {
+      let text = format!(" {} ", app.breadcrumb());
+      let width = (text.width() as u16).min(body.width.saturating_sub(2));
+      let corner = Rect::new(body.x + 1.min(body.width), body.bottom().saturating_sub(1), width, 1.min(body.height));
+      f.render_widget(Paragraph::new(text).style(Style::default().fg(Color::DarkGray)), corner);
+  }
+  draw_status_bar(f, app, bar);
+  if app.toast.is_some() {
+      draw_toast(f, app, body);
+  }
+  // Subtle: bottom-right corner, over the help box's border
+  if app.frame.is_slow() {
+      let text = " UI slow ";
+      let width = (text.len() as u16).min(body.width);
+      let corner = Rect::new(body.right().saturating_sub(width + 1), body.bottom().saturating_sub(1), width, 1.min(body.height));
+      f.render_widget(Paragraph::new(text).style(Style::default().fg(Color::DarkGray)), corner);
+  }
+}
+
+/// Key hints for `context`, fitted to `width` columns.
+fn key_hints(context: Context, width: u16) -> String {
+  hints::fit(hints::hints(context), width as usize)
+}
+
+/// Border style for `pane`: green while the tutorial points at it.
+fn pane_style(app: &App, pane: Pane) -> Style {
+  match &app.tutorial {
+      Some(tutorial) if tutorial.highlights(pane) => Style::default().fg(Color::Green),
+      _ => Style::default(),
+  }
+}
+
+/// Border style for the pane `focus` names: the tutorial's green while it
+/// points at `tutorial`, else yellow while the pane has focus.
+fn focus_style(app: &App, tutorial: Option<Pane>, focus: Focus) -> Style {
+  match tutorial.map(|pane| pane_style(app, pane)) {
+      Some(style) if style != Style::default() => style,
+      _ if app.pane() == Some(focus) => Style::default().fg(Color::Yellow),
+      _ => Style::default(),
+  }
+}
+
+/// Terminals this tall show the preview and detail panes even when they
+/// don't have focus.
+const ROOMY_HEIGHT: u16 = 30;
+
+/// Spinner, in-flight method and queue length for a block's top-right
+/// corner; empty when nothing is being sent.
+fn send_status(app: &App) -> Line<'static> {
+  let status = app.send_status(app.clock.now()).map(|s| format!(" {} ", s)).unwrap_or_default();
+  Line::styled(status, Style::default().fg(Color::Cyan)).right_aligned()
+}
+
+/// "last block 12s ago" for a block's bottom-right corner, red once the
+/// chain looks stalled; empty until a block has been seen.
+fn head_status(app: &App) -> Line<'static> {
+  match app.head_status(app.clock.unix_now()) {
+      Some((text, stalled)) => {
+          let style = if stalled { Style::default().fg(Color::White).bg(Color::Red) } else { Style::default().fg(Color::DarkGray) };
+          Line::styled(format!(" {} ", text), style).right_aligned()
+      }
+      None => Line::default(),
+  }
+}
+
+/// Grid of dashboard cards: label, current value and a sparkline each.
+fn draw_dashboard(f: &mut Frame, app: &mut App, area: Rect) {
+  let title = format!("Dashboard — every {}s", app.dashboard.config.interval_secs.max(1));
+  let title = format!("{} • {}", title, key_hints(Context::Dashboard, area.width.saturating_sub(title.width() as u16 + 5)));
+  let outer = Block::default().title(title).borders(Borders::ALL);
+  let inner = outer.inner(area);
+  f.render_widget(outer, area);
+
+  let dashboard = &app.dashboard;
+  for ((rect, spec), state) in card_grid(inner, dashboard.config.cards.len())
+      .into_iter()
+      .zip(&dashboard.config.cards)
+      .zip(&dashboard.cards)
+  {
+      let card = Block::default().title(spec.label.as_str()).borders(Borders::ALL);
+      let body = card.inner(rect);
+      f.render_widget(card, rect);
+      let [value_area, trend_area] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(body);
+      let value = state.value.as_ref().map(|v| v.text.as_str()).unwrap_or("…");
+      f.render_widget(Paragraph::new(value).style(Style::default().fg(Color::Cyan)), value_area);
+      let trend: Vec<u64> = state.trend.iter().copied().collect();
+      f.render_widget(Sparkline::default().data(&trend).style(Style::default().fg(Color::Green)), trend_area);
   }
 }
 
-fn draw_main_mode(f: &mut Frame, app: &mut App) {
+/// Transient message in the bottom-right corner of `area`, above
+/// everything else.
+fn draw_toast(f: &mut Frame, app: &App, area: Rect) {
+  let Some(toast) = &app.toast else { return };
+  let width = (toast.message.chars().count() as u16 + 4).min(area.width);
+  let height = 3.min(area.height);
+  let rect = Rect::new(area.right() - width, area.bottom() - height, width, height);
+  f.render_widget(Clear, rect);
+  let body = Paragraph::new(toast.message.as_str())
+      .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Yellow)));
+  f.render_widget(body, rect);
+}
+
+/// Display settings, drawn over whatever mode is active.
+fn draw_settings_popup(f: &mut Frame, app: &mut App) {
+  let Some(selected) = app.settings else { return };
   let area = f.area();
+  let width = area.width.min(44);
+  let height = (SETTING_COUNT as u16 + 4).min(area.height);
+  let popup = Rect::new(
+      area.x + (area.width - width) / 2,
+      area.y + (area.height - height) / 2,
+      width,
+      height,
+  );
+
+  let mut lines: Vec<Line> = (0..SETTING_COUNT)
+      .map(|i| {
+          let (label, value) = app.prefs.setting(i);
+          let text = format!("{:<22}{}", label, value);
+          if i == selected {
+              Line::styled(text, Style::default().fg(Color::Yellow))
+          } else {
+              Line::from(text)
+          }
+      })
+      .collect();
+  lines.push(Line::from(""));
+  lines.push(Line::styled(key_hints(Context::Settings, width.saturating_sub(2)), Style::default().fg(Color::DarkGray)));
+
+  f.render_widget(Clear, popup);
+  let body = Paragraph::new(lines)
+      .block(Block::default().title("Settings").borders(Borders::ALL));
+  f.render_widget(body, popup);
+}
+
+/// Background activity: one row per registered task, drawn over any mode.
+fn draw_activity_popup(f: &mut Frame, app: &App) {
+  let Some(selected) = app.activity else { return };
+  let registry = app.tasks.snapshot();
+  let area = f.area();
+  let width = area.width.min(100);
+  let height = (registry.tasks().len().max(1) as u16 + 5).min(area.height);
+  let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+
+  let now = app.clock.unix_now();
+  let mut lines = vec![Line::styled(
+      format!("{:<11}{:<8}{:<10}{:<8}{:<8}{}", "type", "every", "last", "errors", "state", "target"),
+      Style::default().fg(Color::DarkGray),
+  )];
+  if registry.tasks().is_empty() {
+      lines.push(Line::from("nothing running in the background"));
+  }
+  for (i, task) in registry.tasks().iter().enumerate() {
+      let last = task.last_result.map(|at| format!("{} ago", crate::head::format_age(now.saturating_sub(at)))).unwrap_or_else(|| "—".into());
+      let state = if task.paused { "paused" } else { "running" };
+      let text = format!(
+          "{:<11}{:<8}{:<10}{:<8}{:<8}{}",
+          task.kind.label(),
+          if task.interval.is_zero() { "push".to_string() } else { format!("{}s", task.interval.as_secs()) },
+          last,
+          task.errors,
+          state,
+          task.target
+      );
+      let style = match (i == selected, task.paused) {
+          (true, _) => Style::default().fg(Color::Yellow),
+          (false, true) => Style::default().fg(Color::DarkGray),
+          (false, false) => Style::default(),
+      };
+      lines.push(Line::styled(text, style));
+  }
+  lines.push(Line::from(""));
+  lines.push(Line::styled(key_hints(Context::Activity, width.saturating_sub(2)), Style::default().fg(Color::DarkGray)));
+
+  f.render_widget(Clear, popup);
+  let body = Paragraph::new(lines).block(Block::default().title("Background activity").borders(Borders::ALL));
+  f.render_widget(body, popup);
+}
+
+/// Endpoint profiles, one per row with the active one starred, drawn over
+/// Main.
+fn draw_profile_picker(f: &mut Frame, app: &App) {
+  let Some(selected) = app.profile_picker else { return };
+  let area = f.area();
+  let width = area.width.min(100);
+  let height = (app.profiles.len().max(1) as u16 + 5).min(area.height);
+  let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+
+  let name_width = app.profiles.iter().map(|p| p.name.width()).max().unwrap_or(0).max(4) + 2;
+  let mut lines = vec![Line::styled(
+      format!("  {:<name_width$}{:<10}{:<6}{}", "name", "chain", "auth", "url"),
+      Style::default().fg(Color::DarkGray),
+  )];
+  if app.profiles.is_empty() {
+      lines.push(Line::from("no profiles — add them to endpoints.json"));
+  }
+  let active = app.active_profile().map(|p| p.name.as_str());
+  for (i, profile) in app.profiles.iter().enumerate() {
+      let marker = if Some(profile.name.as_str()) == active { "* " } else { "  " };
+      let chain = profile.chain_id.map(|id| id.to_string()).unwrap_or_else(|| "—".into());
+      let auth = if profile.auth_command.is_some() { "yes" } else { "" };
+      let url = if app.spectator { redact_url(&profile.url) } else { profile.url.clone() };
+      let text = format!("{}{:<name_width$}{:<10}{:<6}{}", marker, profile.name, chain, auth, url);
+      let style = if i == selected { Style::default().fg(Color::Yellow) } else { Style::default() };
+      lines.push(Line::styled(text, style));
+  }
+  lines.push(Line::from(""));
+  lines.push(Line::styled(key_hints(Context::Profiles, width.saturating_sub(2)), Style::default().fg(Color::DarkGray)));
+
+  f.render_widget(Clear, popup);
+  let body = Paragraph::new(lines).block(Block::default().title("Endpoint profiles").borders(Borders::ALL));
+  f.render_widget(body, popup);
+}
+
+/// Who holds the websocket connection live heads wanted, and the choice
+/// between subscribing anyway and polling over HTTP.
+fn draw_lease_prompt(f: &mut Frame, app: &App) {
+  let Some(holder) = &app.lease_prompt else { return };
+  let message = holder.in_use_message();
+  let area = f.area();
+  let width = (message.width() as u16 + 4).max(50).min(area.width);
+  let height = 5.min(area.height);
+  let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+  let lines = vec![
+      Line::from(message),
+      Line::from(""),
+      Line::styled(key_hints(Context::LeasePrompt, width.saturating_sub(2)), Style::default().fg(Color::DarkGray)),
+  ];
+  f.render_widget(Clear, popup);
+  f.render_widget(Paragraph::new(lines).block(Block::default().title("Live heads").borders(Borders::ALL)), popup);
+}
+
+/// Every key of the screen under the overlay, one per row, from the same
+/// list as its hint line.
+fn draw_help_overlay(f: &mut Frame, app: &App) {
+  let Some(context) = app.help else { return };
+  let keys = hints::hints(context);
+  let key_width = keys.iter().map(|h| h.keys.width()).max().unwrap_or(0);
+  let area = f.area();
+  let width = (keys.iter().map(|h| key_width + 2 + h.action.width()).max().unwrap_or(0) as u16 + 4).max(30).min(area.width);
+  let height = (keys.len() as u16 + 2).min(area.height);
+  let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+
+  let lines: Vec<Line> = keys
+      .iter()
+      .map(|h| {
+          Line::from(vec![
+              Span::styled(format!(" {:>1$}  ", h.keys, key_width), Style::default().fg(Color::Yellow)),
+              Span::raw(h.action),
+          ])
+      })
+      .collect();
+  f.render_widget(Clear, popup);
+  let body = Paragraph::new(lines).block(Block::default().title("Keys — any key closes").borders(Borders::ALL));
+  f.render_widget(body, popup);
+}
+
+fn draw_main_mode(f: &mut Frame, app: &mut App, area: Rect) {
   let chunks = Layout::default()
       .direction(Direction::Vertical)
       .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
@@ -26,78 +345,1100 @@ fn draw_main_mode(f: &mut Frame, app: &mut App) {
 
   // 1) Search box (string slice to avoid type ambiguity)
   let search = Paragraph::new(app.search_input.as_str())
-      .block(Block::default().title("Search").borders(Borders::ALL));
+      .block(Block::default().title("Search").borders(Borders::ALL).border_style(focus_style(app, Some(Pane::Search), Focus::Search)));
   f.render_widget(search, chunks[0]);
+  if app.endpoint_edit.is_none() && app.settings.is_none() && app.activity.is_none() && app.profile_picker.is_none() {
+      let column = display_column(&app.search_input, app.search_cursor.index(&app.search_input));
+      set_field_cursor(f, chunks[0], column);
+  }
 
-  // 2) Methods list
+  // 2) Methods list, with availability on the session endpoint once known
   let items: Vec<ListItem> = app
       .filtered_methods
       .iter()
-      .map(|m| ListItem::new(m.clone()))
+      .map(|m| match app.capabilities.get(&app.endpoint, m) {
+          Some(availability) => ListItem::new(format!("{} {}", m, availability.badge())),
+          None => ListItem::new(m.clone()),
+      })
       .collect();
 
+  let title = if app.dry_run { "Methods — DRY RUN (Ctrl+D)" } else { "Methods" };
   let list = List::new(items)
-      .block(Block::default().title("Methods").borders(Borders::ALL))
+      .block(Block::default().title(title).title(send_status(app)).title_bottom(head_status(app)).borders(Borders::ALL).border_style(focus_style(app, Some(Pane::Methods), Focus::Methods)))
       .highlight_style(Style::default().fg(Color::Yellow));
 
-  f.render_stateful_widget(list, chunks[1], &mut app.methods_state);
+  let [list_area, docs_area] = Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(chunks[1]);
+  app.list_height = list_area.height.saturating_sub(2) as usize;
+  f.render_stateful_widget(list, list_area, &mut app.methods_state);
+  draw_docs_panel(f, app, docs_area);
 }
 
-fn draw_param_input_mode(f: &mut Frame, app: &mut App) {
-  let area = f.area();
+/// Bottom bar in every mode: a connectivity dot, the active profile's name
+/// and the session endpoint (redacted for spectators) with its chain, and
+/// why the endpoint is unreachable if it is. While Ctrl+E is active, the
+/// prompt for a new endpoint replaces it.
+fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
+  let dim = Style::default().fg(Color::DarkGray);
+  let line = match &app.endpoint_edit {
+      Some(input) => {
+          let prefix = format!(" Endpoint: {}", input);
+          let keys = key_hints(Context::EndpointPrompt, area.width.saturating_sub(prefix.width() as u16 + 2));
+          f.set_cursor_position((area.x + (prefix.width() as u16).min(area.width.saturating_sub(1)), area.y));
+          Line::from(vec![Span::styled(prefix, Style::default().fg(Color::Cyan)), Span::styled(format!("  {}", keys), dim)])
+      }
+      None => {
+          let endpoint = if app.spectator { redact_url(&app.endpoint) } else { app.endpoint.clone() };
+          let chain = match (app.chain_id, app.chain()) {
+              (Some(id), Some(info)) => format!(" (chain {}, {})", id, info.name),
+              (Some(id), None) => format!(" (chain {})", id),
+              _ => String::new(),
+          };
+          let (dot, problem) = match &app.stats.last().health {
+              Health::Unknown => (Span::styled(" ○ ", dim), None),
+              Health::Up => (Span::styled(" ● ", Style::default().fg(Color::Green)), None),
+              Health::Down(error) => {
+                  // Errors can quote the URL, key and all
+                  let error = if app.spectator { error.replace(&app.endpoint, &endpoint) } else { error.clone() };
+                  (Span::styled(" ● ", Style::default().fg(Color::Red)), Some(error))
+              }
+          };
+          let mut spans = vec![dot];
+          if let Some(profile) = app.active_profile() {
+              spans.push(Span::styled(format!("{} ", profile.name), Style::default().fg(Color::Cyan)));
+          }
+          spans.push(Span::styled(format!("{}{}", endpoint, chain), dim));
+          // Which credentials, never the secret
+          if let Some(credentials) = app.session_credentials() {
+              spans.push(Span::styled(format!(" • {}", credentials), dim));
+          }
+          if let Some(error) = problem {
+              spans.push(Span::styled(format!(" • unreachable: {}", error), Style::default().fg(Color::Red)));
+          }
+          if app.mode == AppMode::Main {
+              spans.push(Span::styled(" • Ctrl+E=Change endpoint • ?=Help", dim));
+          }
+          Line::from(spans)
+      }
+  };
+  f.render_widget(Paragraph::new(line), area);
+}
+
+/// Whatever is cached about the selected method; never waits on a lookup.
+fn draw_docs_panel(f: &mut Frame, app: &mut App, area: Rect) {
+  let dim = Style::default().fg(Color::DarkGray);
+  let mut lines = Vec::new();
+  if let Some(method) = app.selected_method() {
+      let availability = match app.capabilities.get(&app.endpoint, method) {
+          Some(a) => format!("{} on this endpoint", a.badge()),
+          None => "availability unknown — Ctrl+P to probe".to_string(),
+      };
+      lines.push(Line::styled(availability, dim));
+      if let Some(origin) = app.spec_origin(method) {
+          lines.push(Line::styled(format!("from: {}", origin), dim));
+      }
+      match app.docs.get(method) {
+          Some(meta) if app.docs_tab == DocsTab::Schema => match meta.result {
+              Some(tree) => lines.extend(schematree::lines(&tree, app.schema_depth).into_iter().map(Line::from)),
+              None => lines.push(Line::styled("no schema available", dim)),
+          },
+          Some(meta) => {
+              if let Some(description) = meta.description {
+                  lines.push(Line::from(description));
+              }
+              for (name, help) in meta.params {
+                  lines.push(Line::from(vec![
+                      Span::styled(format!("• {}", name), Style::default().fg(Color::Cyan)),
+                      Span::styled(help.map(|h| format!(" — {}", h)).unwrap_or_default(), dim),
+                  ]));
+              }
+          }
+          None => lines.push(Line::styled("loading…", dim)),
+      }
+  }
+  let focused = app.pane() == Some(Focus::Docs);
+  let title = match (app.docs_tab, focused) {
+      (DocsTab::Docs, false) => "Docs",
+      (DocsTab::Docs, true) => "Docs (←/→=Schema • ↑/↓=Scroll)",
+      (DocsTab::Schema, false) => "Result schema ([/]=Fold/Unfold)",
+      (DocsTab::Schema, true) => "Result schema ([/]=Fold/Unfold • ←/→=Docs • ↑/↓=Scroll)",
+  };
+  let scroll = if focused { app.pane_scroll.min(lines.len().saturating_sub(1) as u16) } else { 0 };
+  if focused {
+      app.pane_scroll = scroll;
+  }
+  // Schema lines keep their indentation
+  let docs = Paragraph::new(lines)
+      .wrap(Wrap { trim: app.docs_tab == DocsTab::Docs })
+      .scroll((scroll, 0))
+      .block(Block::default().title(title).borders(Borders::ALL).border_style(focus_style(app, None, Focus::Docs)));
+  f.render_widget(docs, area);
+}
+
+fn draw_param_input_mode(f: &mut Frame, app: &mut App, area: Rect) {
+  let override_height = if app.endpoint_override.is_some() { 3 } else { 0 };
+  let editor_constraints = if app.raw_params.is_some() || app.tx_form.is_some() {
+      vec![Constraint::Min(5)]
+  } else {
+      vec![Constraint::Length(3); app.param_inputs.len()]
+  };
+  // Instructions, then suggestions, the dry-run banner and the last error if any
+  let keys = key_hints(app.key_context(), area.width.saturating_sub(2));
+  let mut lines = vec![Line::from(keys)];
+  if let Some((_, suggestion)) = app.block_method_suggestion() {
+      lines.push(Line::styled(suggestion, Style::default().fg(Color::Cyan)));
+  }
+  if app.dry_run {
+      lines.push(Line::styled("DRY RUN — Enter builds and records the request without sending (Ctrl+D)", Style::default().fg(Color::Yellow)));
+  }
+  // One-line hint under the fields: the last error wins over a fee
+  // conflict in the transaction form, which wins over the focused field's
+  // help
+  let form = app.tx_form.as_ref().filter(|_| app.raw_params.is_none());
+  let help = match form {
+      Some(form) => Some(txform::FIELDS[form.focus].2),
+      None if app.raw_params.is_none() && app.param_inputs.is_empty() => Some("this method takes no params — Enter sends it"),
+      None => app.raw_params.is_none().then(|| app.param_help(app.focused_param)).flatten(),
+  };
+  let hint = match (&app.notice, form.and_then(|f| txform::fee_conflict(&f.fields)), help) {
+      (Some(notice), _, _) => Line::styled(notice.clone(), Style::default().fg(Color::Red)),
+      (None, Some(conflict), _) => Line::styled(conflict, Style::default().fg(Color::Yellow)),
+      (None, None, Some(help)) => Line::styled(help.to_string(), Style::default().fg(Color::DarkGray)),
+      (None, None, None) => Line::default(),
+  };
+
+  // The params as they will be sent, while focused or there is room (strict
+  // raw mode already shows them; relaxed raw mode always does, as what is
+  // sent differs from what was typed)
+  let relaxed = app.raw_params.as_ref().is_some_and(|raw| raw.relaxed);
+  let show_preview =
+      relaxed || app.raw_params.is_none() && (app.pane() == Some(Focus::Preview) || area.height >= ROOMY_HEIGHT);
+  let preview_lines: Vec<String> = match show_preview.then(|| app.current_params()) {
+      None => Vec::new(),
+      Some(Ok(params)) => serde_json::to_string_pretty(&params).unwrap_or_default().lines().map(str::to_string).collect(),
+      Some(Err(e)) => vec![format!("not valid yet: {:#}", e)],
+  };
+  let preview_height = if preview_lines.is_empty() { 0 } else { (preview_lines.len() as u16 + 2).min(10) };
+
+  let editors = editor_constraints.len();
+  let help_height = lines.len().max(2) as u16 + 2;
   let chunks = Layout::default()
       .direction(Direction::Vertical)
-      .constraints([
-          Constraint::Length(3),
-          Constraint::Length(3),
-          Constraint::Min(0),
-      ].as_ref())
+      .constraints(editor_constraints.into_iter().chain([
+          Constraint::Length(1),
+          Constraint::Length(override_height),
+          Constraint::Length(preview_height),
+          Constraint::Length(help_height),
+      ]))
       .split(area);
 
-  // Param 1
-  let p1 = app.param_inputs.get(0).map(|s| s.as_str()).unwrap_or("");
-  let input1 = Paragraph::new(p1)
-      .block(Block::default().title("Param 1").borders(Borders::ALL));
-  f.render_widget(input1, chunks[0]);
+  if app.raw_params.is_some() {
+      draw_raw_params(f, app, chunks[0]);
+  } else if let Some(form) = &app.tx_form {
+      draw_tx_form(f, form, chunks[0]);
+      if app.endpoint_override.is_none() {
+          let value = &form.fields[form.focus];
+          let label_width = txform::FIELDS.iter().map(|(label, _, _)| label.len()).max().unwrap_or(0);
+          let row = Rect { y: chunks[0].y + form.focus as u16, height: 3, ..chunks[0] };
+          set_field_cursor(f, row, label_width + 2 + display_column(value, app.input_cursor.index(value)));
+      }
+  } else {
+      // One box per param, the focused one highlighted (long values are
+      // previewed; F4 edits them in full)
+      for (i, chunk) in chunks[..editors].iter().enumerate() {
+          let value = app.param_inputs.get(i).map(|s| s.as_str()).unwrap_or("");
+          let border = match pane_style(app, Pane::Params) {
+              style if style != Style::default() => style,
+              _ if i == app.focused_param && app.pane() == Some(Focus::Fields) => Style::default().fg(Color::Yellow),
+              style => style,
+          };
+          let shown = match app.param_kind(i) {
+              kind @ ParamKind::Bool { on, off, .. } => match kind.toggle_state(value) {
+                  Some(true) => format!("[x] {}", on),
+                  Some(false) => format!("[ ] {}", off),
+                  // Not a boolean (e.g. loaded from old history); flipping replaces it
+                  None => format!("[?] {}", value),
+              },
+              ParamKind::Text => preview(value, chunk.width.saturating_sub(2) as usize),
+          };
+          let input = Paragraph::new(shown)
+              .style(param_style(app, i))
+              .block(Block::default().title(param_title(app, i)).borders(Borders::ALL).border_style(border));
+          f.render_widget(input, *chunk);
+          if i == app.focused_param && app.endpoint_override.is_none() && app.param_kind(i) == ParamKind::Text {
+              set_field_cursor(f, *chunk, display_column(value, app.input_cursor.index(value)));
+          }
+      }
+  }
 
-  // Param 2
-  let p2 = app.param_inputs.get(1).map(|s| s.as_str()).unwrap_or("");
-  let input2 = Paragraph::new(p2)
-      .block(Block::default().title("Param 2").borders(Borders::ALL));
-  f.render_widget(input2, chunks[1]);
+  f.render_widget(Paragraph::new(hint), chunks[editors]);
 
-  // Instructions
-  let help = Paragraph::new("Enter=Send • Esc=Back")
-      .block(Block::default().title("Help").borders(Borders::ALL));
-  f.render_widget(help, chunks[2]);
+  // One-off endpoint override (URL or profile name), only while toggled on
+  if let Some(endpoint) = &app.endpoint_override {
+      let input = Paragraph::new(endpoint.as_str())
+          .block(Block::default().title("Endpoint override (this send only)").borders(Borders::ALL))
+          .style(Style::default().fg(Color::Cyan));
+      f.render_widget(input, chunks[editors + 1]);
+      set_field_cursor(f, chunks[editors + 1], display_column(endpoint, app.input_cursor.index(endpoint)));
+  }
+
+  if preview_height > 0 {
+      let height = preview_height.saturating_sub(2) as usize;
+      draw_scrolled(f, app, preview_lines, height, "Params preview", Focus::Preview, chunks[editors + 2]);
+  }
+
+  let help = Paragraph::new(lines)
+      .block(Block::default().title("Help").title(send_status(app)).borders(Borders::ALL));
+  f.render_widget(help, chunks[editors + 3]);
+
+  if app.editor_popup.is_some() {
+      draw_editor_popup(f, app);
+  }
 }
 
-fn draw_history_mode(f: &mut Frame, app: &mut App) {
-  let area = f.area();
+/// `lines` in a bordered pane `height` rows tall inside, scrolled by
+/// [`App::pane_scroll`] (clamped to the content) while `focus` has it.
+fn draw_scrolled(f: &mut Frame, app: &mut App, lines: Vec<String>, height: usize, title: &str, focus: Focus, area: Rect) {
+  let focused = app.pane() == Some(focus);
+  let scroll = if focused { (app.pane_scroll as usize).min(lines.len().saturating_sub(height)) } else { 0 };
+  if focused {
+      app.pane_scroll = scroll as u16;
+  }
+  let title = if focused { format!("{} (↑/↓=Scroll)", title) } else { title.to_string() };
+  let shown: Vec<Line> = lines.into_iter().skip(scroll).take(height).map(Line::from).collect();
+  let pane = Paragraph::new(shown).block(Block::default().title(title).borders(Borders::ALL).border_style(focus_style(app, None, focus)));
+  f.render_widget(pane, area);
+}
+
+/// Puts the terminal cursor `column` columns into the one-line bordered
+/// field `area`, at its last column if the value runs past it.
+fn set_field_cursor(f: &mut Frame, area: Rect, column: usize) {
+  let inner = area.width.saturating_sub(2);
+  if inner == 0 || area.height < 3 {
+      return;
+  }
+  f.set_cursor_position((area.x + 1 + column.min(inner as usize - 1) as u16, area.y + 1));
+}
+
+/// Config defaults are dim until edited.
+fn param_style(app: &App, index: usize) -> Style {
+  if app.is_default_param(index) { Style::default().fg(Color::DarkGray) } else { Style::default() }
+}
+
+fn param_title(app: &App, index: usize) -> String {
+  let suffix = if app.is_default_param(index) { " (default)" } else { "" };
+  format!("{}{}", app.param_label(index), suffix)
+}
+
+/// Single-value editor popup over ParamInput mode.
+fn draw_editor_popup(f: &mut Frame, app: &mut App) {
+  let Some(popup) = app.editor_popup.as_mut() else { return };
+  let rect = EditorPopup::layout(f.area());
+  let (width, height) = (rect.width.saturating_sub(2) as usize, rect.height.saturating_sub(2) as usize);
+  popup.fit(width, height);
+
+  let keys = key_hints(Context::EditorPopup, rect.width.saturating_sub(popup.title.width() as u16 + 5));
+  let title = format!("{} — {}", popup.title, keys);
+  let lines: Vec<Line> = popup.visible_lines(width, height).into_iter().map(Line::from).collect();
+  f.render_widget(Clear, rect);
+  let body = Paragraph::new(lines)
+      .block(Block::default().title(title).borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)));
+  f.render_widget(body, rect);
+
+  if let Some((x, y)) = popup.cursor_in_viewport(height) {
+      f.set_cursor_position((rect.x + 1 + x, rect.y + 1 + y));
+  }
+}
+
+/// Raw-JSON editor: the buffer with the parse error and matching bracket
+/// highlighted, and the validation result in the title.
+fn draw_raw_params(f: &mut Frame, app: &mut App, area: Rect) {
+  let Some(raw) = app.raw_params.as_mut() else { return };
+  let height = area.height.saturating_sub(2) as usize;
+  raw.area.ensure_visible(height);
+
+  let error_style = Style::default().bg(Color::Red).fg(Color::White);
+  let bracket_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+  let mut highlights = Vec::new();
+  if let Some(partner) = raw.matching_bracket() {
+      highlights.push((raw.area.cursor, bracket_style));
+      highlights.push((partner, bracket_style));
+  }
+  if let Some(err) = &raw.error {
+      highlights.push((err.pos, error_style));
+  }
+
+  let lines: Vec<Line> = raw
+      .area
+      .lines
+      .iter()
+      .enumerate()
+      .skip(raw.area.scroll)
+      .take(height)
+      .map(|(row, text)| highlighted_line(text, row, &highlights))
+      .collect();
+
+  let syntax = if raw.relaxed { "relaxed JSON" } else { "JSON" };
+  let (title, title_style) = match (&raw.error, raw.is_pending()) {
+      (_, true) => (format!("Raw params ({}) — checking…", syntax), Style::default()),
+      (None, false) => (format!("Raw params ({}) — valid", syntax), Style::default().fg(Color::Green)),
+      (Some(err), false) => (format!("Raw params ({}) — {}", syntax, err.describe()), Style::default().fg(Color::Red)),
+  };
+  let editor = Paragraph::new(lines)
+      .block(Block::default().title(Span::styled(title, title_style)).borders(Borders::ALL));
+  f.render_widget(editor, area);
+
+  if app.endpoint_override.is_none()
+      && let Some((x, y)) = raw.area.to_viewport(raw.area.cursor, height)
+  {
+      f.set_cursor_position((area.x + 1 + x, area.y + 1 + y));
+  }
+}
+
+/// Renders one buffer line with tabs expanded, styling the characters at the
+/// given (row, char index) positions. A position at end of line styles a
+/// trailing space so EOF errors stay visible.
+fn highlighted_line<'a>(text: &str, row: usize, highlights: &[((usize, usize), Style)]) -> Line<'a> {
+  let style_at = |col: usize| {
+      highlights
+          .iter()
+          .rev()
+          .find(|(pos, _)| *pos == (row, col))
+          .map(|(_, style)| *style)
+  };
+  let mut spans = Vec::new();
+  let mut plain = String::new();
+  for (col, c) in text.chars().enumerate() {
+      let rendered = if c == '\t' {
+          let start = display_column(text, col);
+          " ".repeat(display_column(text, col + 1) - start)
+      } else {
+          c.to_string()
+      };
+      match style_at(col) {
+          Some(style) => {
+              spans.push(Span::raw(std::mem::take(&mut plain)));
+              spans.push(Span::styled(rendered, style));
+          }
+          None => plain.push_str(&rendered),
+      }
+  }
+  spans.push(Span::raw(plain));
+  if let Some(style) = style_at(text.chars().count()) {
+      spans.push(Span::styled(" ", style));
+  }
+  Line::from(spans)
+}
+
+/// "History", with the number of marked entries once there are any.
+fn history_title(app: &App) -> String {
+  match app.history_marks.len() {
+      0 => "History".to_string(),
+      marked => format!("History ({} marked)", marked),
+  }
+}
+
+fn draw_history_mode(f: &mut Frame, app: &mut App, area: Rect) {
+  // The selected entry's detail, while focused or there is room
+  let show_detail = app.pane() == Some(Focus::Detail) || area.height >= ROOMY_HEIGHT;
+  let detail_height = if show_detail { area.height.saturating_sub(3) / 2 } else { 0 };
   let chunks = Layout::default()
       .direction(Direction::Vertical)
-      .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+      .constraints([Constraint::Min(0), Constraint::Length(detail_height), Constraint::Length(3)].as_ref())
       .split(area);
 
   // History list items
+  let currency = app.currency();
   let items: Vec<ListItem> = app
       .history
       .iter()
-      .enumerate()
-      .map(|(i, (req, res))| {
-          let line = format!("{}: {} → {:?}", i, req.method, res.result);
+      .map(|entry| {
+          // Marker column, while anything is marked
+          let mark = match (app.history_marks.is_empty(), app.history_marks.contains(&entry.seq)) {
+              (true, _) => "",
+              (false, true) => "● ",
+              (false, false) => "  ",
+          };
+          if entry.dry_run {
+              let params = entry.request.params.to_string();
+              let line = format!("{}#{}: [DRY RUN] {} {} (not sent)", mark, entry.seq, entry.request.method, params);
+              return ListItem::new(line).style(Style::default().fg(Color::DarkGray));
+          }
+          let summary = app.prefs.summarize(&entry.request.method, &entry.response, &currency);
+          let mut line = format!("{}#{}: {} → {}", mark, entry.seq, entry.request.method, summary);
+          if let Some(endpoint) = &entry.endpoint_override {
+              // URLs often embed API keys; keep them off shared screens
+              let shown = if app.spectator { redact_url(endpoint) } else { endpoint.clone() };
+              line.push_str(&format!(" @ {}", shown));
+          }
+          if let Some(latency) = entry.latency {
+              line.push_str(&format!(" ({})", app.prefs.latency(latency)));
+          }
+          if let Some(source) = entry.derived_from {
+              line.push_str(&format!(" (from #{})", source));
+          }
+          if entry.needs_review {
+              line.push_str(" [migrated — verify params before sending]");
+          }
+          if entry.response.error.is_some() {
+              return ListItem::new(line).style(Style::default().fg(Color::Red));
+          }
           ListItem::new(line)
       })
       .collect();
 
   let list = List::new(items)
-      .block(Block::default().title("History").borders(Borders::ALL))
+      .block(Block::default().title(history_title(app)).title(send_status(app)).title_bottom(head_status(app)).borders(Borders::ALL).border_style(focus_style(app, Some(Pane::History), Focus::List)))
       .highlight_style(Style::default().fg(Color::Yellow));
 
+  app.list_height = chunks[0].height.saturating_sub(2) as usize;
   f.render_stateful_widget(list, chunks[0], &mut app.history_state);
 
+  if detail_height > 0 {
+      let height = detail_height.saturating_sub(2) as usize;
+      let lines = history_detail(app, app.pane_scroll as usize + height);
+      draw_scrolled(f, app, lines, height, "Detail", Focus::Detail, chunks[1]);
+  }
+
   // Instructions
-  let help = Paragraph::new("↑/↓=Navigate • Enter=Load • Esc=Back")
+  let width = chunks[2].width.saturating_sub(2);
+  let footer = match app.history_prompt() {
+      Some(prompt) => {
+          let prompt = format!("{}  ", prompt);
+          format!("{}{}", prompt, key_hints(Context::HistoryConfirm, width.saturating_sub(prompt.width() as u16)))
+      }
+      None => key_hints(Context::History, width),
+  };
+  let help = Paragraph::new(footer)
+      .block(Block::default().title("Help").borders(Borders::ALL));
+  f.render_widget(help, chunks[2]);
+}
+
+/// Up to `limit` lines about the selected history entry: its request, then
+/// its response pretty-printed, formatted no further than needed.
+fn history_detail(app: &App, limit: usize) -> Vec<String> {
+  let Some(entry) = app.history_state.selected().and_then(|i| app.history.get(i)) else { return Vec::new() };
+  let mut lines = vec![format!("→ {} {}", entry.request.method, entry.request.params)];
+  if let Some(stamp) = &entry.spec {
+      lines.push(format!("  spec: {}", stamp.describe()));
+  }
+  if let Some(spill) = &entry.spill {
+      lines.push(format!("  result on disk: {} (v to page through it)", spill.display()));
+  }
+  pretty::write_lines(&pretty::response_fields(&entry.response), 64, |batch, _| {
+      lines.extend(batch);
+      lines.len() < limit
+  });
+  lines.truncate(limit.max(1));
+  lines
+}
+
+fn draw_response_view(f: &mut Frame, app: &mut App, area: Rect) {
+  let border = pane_style(app, Pane::Response);
+  let Some(view) = app.response_view.as_mut() else { return };
+  let chunks = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+      .split(area);
+
+  // Remember the viewport so search jumps can keep matches on screen
+  view.height = chunks[0].height.saturating_sub(2) as usize;
+  view.width = chunks[0].width.saturating_sub(2) as usize;
+  if let Some(hex) = view.hex.as_mut() {
+      hex.height = view.height;
+      draw_hex_dump(f, &view.title, hex, border, chunks[0]);
+      let help = Paragraph::new(key_hints(Context::HexDump, chunks[1].width.saturating_sub(2)))
+          .block(Block::default().title("Help").borders(Borders::ALL));
+      f.render_widget(help, chunks[1]);
+      return;
+  }
+
+  let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+  let current_style = Style::default().bg(Color::Magenta).fg(Color::White);
+  let current = view.search.current_match();
+  // Long values (calldata, logs data) wrap rather than run off screen;
+  // the view counts the same rows to scroll by
+  let lines: Vec<Line> = view
+      .visible()
+      .map(|i| (i, &view.lines[i]))
+      .flat_map(|(i, text)| {
+          let highlights: Vec<_> = view
+              .search
+              .matches
+              .iter()
+              .filter(|m| m.line == i)
+              .map(|m| (m.start, m.end, if Some(*m) == current { current_style } else { match_style }))
+              .collect();
+          let mut line = match_line(text, &highlights);
+          // Access list diff entries
+          if i == 0 && view.failed && view.tab == Tab::Response {
+              line = line.style(Style::default().fg(Color::Red));
+          } else if text.starts_with("+ 0x") {
+              line = line.style(Style::default().fg(Color::Green));
+          } else if text.starts_with("- 0x") {
+              line = line.style(Style::default().fg(Color::Red));
+          }
+          if let Some(Some(label)) = view.labels.get(i) {
+              line.push_span(Span::styled(format!("  {}", label), Style::default().fg(Color::DarkGray)));
+          }
+          split_rows(line, &view.row_breaks(i))
+      })
+      .take(view.height)
+      .collect();
+  let title = match view.tab {
+      Tab::Response => view.title.clone(),
+      Tab::Provenance => format!("{} — provenance of params (Tab)", view.title),
+  };
+  let mut block = Block::default().title(title).borders(Borders::ALL).border_style(border);
+  if let Some(status) = view.formatting_status() {
+      block = block.title_bottom(status);
+  }
+  if view.dry_run {
+      block = block.border_style(Style::default().fg(Color::Yellow));
+  } else if view.failed {
+      block = block.border_style(Style::default().fg(Color::Red));
+  }
+  let body = Paragraph::new(lines).block(block);
+  f.render_widget(body, chunks[0]);
+
+  // Search input / match indicator, or key help
+  // Hints get whatever width the prompt or query leaves
+  let case = if view.search.case_sensitive { "Aa" } else { "aa" };
+  let width = chunks[1].width.saturating_sub(2);
+  let with_hints = |prefix: String, context: Context| {
+      let keys = key_hints(context, width.saturating_sub(prefix.width() as u16));
+      format!("{}{}", prefix, keys)
+  };
+  let footer = if let Some(path) = &view.export_path {
+      with_hints(format!("Export CSV to: {}  ", path), Context::ExportPrompt)
+  } else if let Some((address, label)) = &view.label_prompt {
+      with_hints(format!("Label {}: {}  ", address, label), Context::LabelPrompt)
+  } else if view.search.editing {
+      format!("/{}  [{}]", view.search.query, case)
+  } else if let Some(indicator) = view.search.indicator() {
+      with_hints(format!("/{} — {}  [{}] • ", view.search.query, indicator, case), Context::ResponseMatches)
+  } else {
+      key_hints(Context::Response, width)
+  };
+  let help = Paragraph::new(footer)
       .block(Block::default().title("Help").borders(Borders::ALL));
   f.render_widget(help, chunks[1]);
 }
+
+/// The eth_sendTransaction form: one row per field, the focused one highlighted.
+fn draw_tx_form(f: &mut Frame, form: &TxForm, area: Rect) {
+  let label_width = txform::FIELDS.iter().map(|(label, _, _)| label.len()).max().unwrap_or(0);
+  let value_width = (area.width as usize).saturating_sub(label_width + 4);
+  let rows: Vec<Line> = txform::FIELDS
+      .iter()
+      .zip(&form.fields)
+      .enumerate()
+      .map(|(i, ((label, _, _), value))| {
+          let style = if i == form.focus { Style::default().fg(Color::Yellow) } else { Style::default() };
+          Line::from(vec![
+              Span::styled(format!("{:>width$}: ", label, width = label_width), style),
+              Span::raw(preview(value, value_width)),
+          ])
+      })
+      .collect();
+  let body = Paragraph::new(rows).block(Block::default().title("Transaction").borders(Borders::ALL));
+  f.render_widget(body, area);
+}
+
+/// Hex dump rows, words shaded alternately so 32-byte boundaries stand
+/// out; the selected word in yellow, its selected row in bold.
+fn draw_hex_dump(f: &mut Frame, title: &str, hex: &HexView, border: Style, area: Rect) {
+  let lines: Vec<Line> = hex
+      .lines
+      .iter()
+      .enumerate()
+      .skip(hex.scroll)
+      .take(hex.height)
+      .map(|(i, text)| {
+          let style = if hex.in_word(i) {
+              let style = Style::default().fg(Color::Yellow);
+              if i == hex.row { style.add_modifier(Modifier::BOLD) } else { style }
+          } else if i * ROW / WORD % 2 == 1 {
+              Style::default().fg(Color::Gray)
+          } else {
+              Style::default()
+          };
+          Line::styled(text.clone(), style)
+      })
+      .collect();
+  let title = format!("{} — {}, {} bytes, word {}", title, hex.field, hex.bytes.len(), hex.word());
+  let body = Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL).border_style(border));
+  f.render_widget(body, area);
+}
+
+/// Styles the char ranges `[start, end)` of `text`. Overlapping ranges are
+/// merged, with the later range's style winning where they overlap.
+/// Splits `line` into screen rows at the char indices in `breaks` (see
+/// [`crate::view::ResponseView::row_breaks`]), keeping each span's style.
+fn split_rows<'a>(line: Line<'a>, breaks: &[usize]) -> Vec<Line<'a>> {
+  if breaks.is_empty() {
+      return vec![line];
+  }
+  let style = line.style;
+  let mut rows = vec![Line::default().style(style)];
+  let mut breaks = breaks.iter().peekable();
+  let mut col = 0;
+  for span in line.spans {
+      let mut chunk = String::new();
+      for c in span.content.chars() {
+          if breaks.next_if_eq(&&col).is_some() {
+              if !chunk.is_empty() {
+                  rows.last_mut().unwrap().push_span(Span::styled(std::mem::take(&mut chunk), span.style));
+              }
+              rows.push(Line::default().style(style));
+          }
+          chunk.push(c);
+          col += 1;
+      }
+      if !chunk.is_empty() {
+          rows.last_mut().unwrap().push_span(Span::styled(chunk, span.style));
+      }
+  }
+  rows
+}
+
+fn match_line<'a>(text: &str, highlights: &[(usize, usize, Style)]) -> Line<'a> {
+  let style_at = |col: usize| {
+      highlights
+          .iter()
+          .rev()
+          .find(|(start, end, _)| (*start..*end).contains(&col))
+          .map(|(_, _, style)| *style)
+  };
+  let mut spans: Vec<Span> = Vec::new();
+  let mut run = String::new();
+  let mut run_style = None;
+  for (col, c) in text.chars().enumerate() {
+      let style = style_at(col);
+      if style != run_style && !run.is_empty() {
+          let chunk = std::mem::take(&mut run);
+          spans.push(match run_style {
+              Some(s) => Span::styled(chunk, s),
+              None => Span::raw(chunk),
+          });
+      }
+      run_style = style;
+      run.push(c);
+  }
+  spans.push(match run_style {
+      Some(s) => Span::styled(run, s),
+      None => Span::raw(run),
+  });
+  Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::HistoryEntry;
+  use crate::prefs::Units;
+  use crate::rpc::{JsonRpcRequest, JsonRpcResponse};
+  use crate::tasks::TaskKind;
+  use ratatui::{backend::TestBackend, Terminal};
+  use serde_json::json;
+
+  fn render(app: &mut App) -> String {
+      let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+      terminal.draw(|f| draw_ui(f, app)).unwrap();
+      let buffer = terminal.backend().buffer();
+      buffer.content().chunks(buffer.area.width as usize)
+          .map(|row| row.iter().map(|c| c.symbol()).collect::<String>())
+          .collect::<Vec<_>>()
+          .join("\n")
+  }
+
+  fn app_with_balance() -> App {
+      let mut app = App::new();
+      app.history.push(HistoryEntry {
+          latency: Some(std::time::Duration::from_millis(1250)),
+          ..HistoryEntry::new(
+              JsonRpcRequest::new("eth_getBalance", json!(["0xabc", "latest"]), 1),
+              JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(json!("0x1bc16d674ec80000")), error: None, id: 1 },
+          )
+      });
+      app.mode = AppMode::History;
+      app
+  }
+
+  #[test]
+  fn history_follows_display_prefs() {
+      let mut app = app_with_balance();
+      let screen = render(&mut app);
+      assert!(screen.contains("eth_getBalance → 2 ETH (1,250 ms)"), "{}", screen);
+
+      app.prefs.units = Units::Wei;
+      app.prefs.separators = false;
+      app.prefs.hex_alongside = true;
+      let screen = render(&mut app);
+      assert!(
+          screen.contains("→ 2000000000000000000 wei (0x1bc16d674ec80000) (1250 ms)"),
+          "{}",
+          screen
+      );
+  }
+
+  #[test]
+  fn settings_popup_shows_current_values() {
+      let mut app = app_with_balance();
+      app.settings = Some(0);
+      assert!(render(&mut app).contains("Units                 ether"));
+      app.prefs.cycle(0);
+      assert!(render(&mut app).contains("Units                 wei"));
+  }
+
+  #[test]
+  fn dry_run_entries_render_distinctly_in_history() {
+      let mut app = app_with_balance();
+      app.record_dry_run(JsonRpcRequest::new("eth_getBalance", json!(["0xabc"]), 2), None);
+      let screen = render(&mut app);
+      assert!(screen.contains(r#"1: [DRY RUN] eth_getBalance ["0xabc"] (not sent)"#), "{}", screen);
+      assert!(!screen.contains("1: eth_getBalance →"), "{}", screen);
+  }
+
+  #[test]
+  fn error_responses_show_code_and_message_in_red() {
+      let mut app = App::new();
+      let error = json!({"code": -32602, "message": "invalid params"});
+      app.record(
+          JsonRpcRequest::new("eth_getBalance", json!(["0xabc"]), 1),
+          JsonRpcResponse { jsonrpc: "2.0".into(), result: None, error: Some(error), id: 1 },
+          None,
+      );
+      app.mode = AppMode::History;
+      app.history_state.select(None);
+      let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+      terminal.draw(|f| draw_ui(f, &mut app)).unwrap();
+      let buffer = terminal.backend().buffer();
+      let row: String = (0..buffer.area.width).map(|x| buffer[(x, 1)].symbol()).collect();
+      assert!(row.contains("#1: eth_getBalance → error -32602 invalid params"), "{}", row);
+      assert_eq!(buffer[(2, 1)].fg, Color::Red);
+
+      app.history_state.select(Some(0));
+      app.open_selected_response();
+      let view = app.response_view.as_ref().unwrap();
+      assert!(view.failed);
+      assert_eq!(view.lines[0], "error -32602 invalid params");
+      terminal.draw(|f| draw_ui(f, &mut app)).unwrap();
+      assert_eq!(terminal.backend().buffer()[(1, 1)].fg, Color::Red);
+  }
+
+  #[test]
+  fn spectator_is_marked_and_hides_endpoint_secrets() {
+      let mut app = app_with_balance();
+      app.history[0].endpoint_override = Some("https://mainnet.infura.io/v3/secretkey".into());
+      assert!(render(&mut app).contains("secretkey"));
+
+      app.spectator = true;
+      app.show_toast("send: disabled in spectator mode");
+      let screen = render(&mut app);
+      assert!(screen.contains("SPECTATOR"), "{}", screen);
+      assert!(screen.contains("@ https://mainnet.infura.io/…"), "{}", screen);
+      assert!(!screen.contains("secretkey"), "{}", screen);
+      assert!(screen.contains("send: disabled in spectator mode"), "{}", screen);
+  }
+
+  #[test]
+  fn activity_popup_lists_tasks() {
+      let mut app = App::new();
+      app.activity = Some(0);
+      assert!(render(&mut app).contains("nothing running in the background"));
+
+      let id = app.tasks.update(|r| r.register(TaskKind::HeadPoll, "eth_getBlockByNumber", std::time::Duration::from_secs(5)));
+      app.tasks.update(|r| {
+          r.record(id, false, crate::head::unix_now());
+          r.set_paused(id, true)
+      });
+      let screen = render(&mut app);
+      assert!(screen.contains("head poll  5s      0s ago    1       paused  eth_getBlockByNumber"), "{}", screen);
+  }
+
+  #[test]
+  fn status_bar_shows_endpoint_chain_and_health_in_every_mode() {
+      let mut app = App::new();
+      app.endpoint = "https://mainnet.infura.io/v3/secretkey".into();
+      app.chain_id = Some(1);
+      let screen = render(&mut app);
+      let bar = screen.lines().last().unwrap();
+      assert!(bar.starts_with(" ○ https://mainnet.infura.io/v3/secretkey (chain 1, Ethereum Mainnet) • Ctrl+E"), "{}", screen);
+
+      app.stats.shared().update(|s| s.health = Health::Down("error sending request for url (https://mainnet.infura.io/v3/secretkey)".into()));
+      app.stats.refresh();
+      app.mode = AppMode::History;
+      let mut terminal = Terminal::new(TestBackend::new(140, 12)).unwrap();
+      terminal.draw(|f| draw_ui(f, &mut app)).unwrap();
+      let buffer = terminal.backend().buffer();
+      let bar: String = (0..buffer.area.width).map(|x| buffer[(x, 11)].symbol()).collect();
+      assert!(bar.contains("(chain 1, Ethereum Mainnet) • unreachable: error sending request"), "{}", bar);
+      assert!(!bar.contains("Ctrl+E"), "{}", bar);
+      assert_eq!(buffer[(1, 11)].fg, Color::Red);
+
+      app.stats.shared().update(|s| s.health = Health::Up);
+      app.stats.refresh();
+      app.spectator = true;
+      terminal.draw(|f| draw_ui(f, &mut app)).unwrap();
+      assert_eq!(terminal.backend().buffer()[(1, 11)].fg, Color::Green);
+      app.stats.shared().update(|s| s.health = Health::Down("timed out: https://mainnet.infura.io/v3/secretkey".into()));
+      app.stats.refresh();
+      assert!(!render(&mut app).contains("secretkey"));
+      app.mode = AppMode::Main;
+
+      app.endpoint_edit = Some("http://reth".into());
+      let screen = render(&mut app);
+      assert!(screen.contains("Endpoint: http://reth  Enter=Switch • Esc=Cancel"), "{}", screen);
+  }
+
+  #[test]
+  fn status_bar_names_the_credentials_but_not_the_secret() {
+      let mut app = App::new();
+      app.set_credentials(crate::auth::Credentials::Bearer("abc123".into()));
+      let screen = render(&mut app);
+      assert!(screen.contains(" • bearer token • Ctrl+E"), "{}", screen);
+      assert!(!screen.contains("abc123"), "{}", screen);
+      // They stay with the endpoint they were given for
+      app.endpoint = "http://127.0.0.1:8545".into();
+      assert!(!render(&mut app).contains("bearer token"));
+  }
+
+  #[test]
+  fn profiles_are_named_in_the_status_bar_and_picker() {
+      let mut app = App::new();
+      app.profiles = vec![
+          crate::endpoint::EndpointProfile { name: "local".into(), url: crate::endpoint::DEFAULT_ENDPOINT.into(), ..Default::default() },
+          crate::endpoint::EndpointProfile {
+              name: "mainnet".into(),
+              url: "https://mainnet.infura.io/v3/secretkey".into(),
+              auth_command: Some("get-token".into()),
+              chain_id: Some(1),
+              ..Default::default()
+          },
+      ];
+      let screen = render(&mut app);
+      assert!(screen.lines().last().unwrap().starts_with(" ○ local http://localhost:8545"), "{}", screen);
+
+      app.profile_picker = Some(1);
+      let screen = render(&mut app);
+      assert!(screen.contains("* local    —               http://localhost:8545"), "{}", screen);
+      assert!(screen.contains("  mainnet  1         yes   https://mainnet.infura.io/v3/secretkey"), "{}", screen);
+      app.spectator = true;
+      assert!(!render(&mut app).contains("secretkey"));
+  }
+
+  #[test]
+  fn the_terminal_cursor_follows_the_edit_position() {
+      let mut app = App::new();
+      app.search_input = "eth_call".into();
+      app.search_cursor.move_home(&app.search_input);
+      app.search_cursor.move_right(&app.search_input);
+      let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+      terminal.draw(|f| draw_ui(f, &mut app)).unwrap();
+      terminal.backend_mut().assert_cursor_position((2, 1));
+
+      app.mode = AppMode::ParamInput;
+      app.param_inputs = vec!["0x1".into(), "latest".into()];
+      app.focused_param = 1;
+      app.input_cursor.move_home(&app.param_inputs[1]);
+      terminal.draw(|f| draw_ui(f, &mut app)).unwrap();
+      terminal.backend_mut().assert_cursor_position((1, 4));
+  }
+
+  #[test]
+  fn breadcrumb_shows_the_way_back_below_the_root() {
+      assert!(!render(&mut App::new()).contains("Main ›"));
+      let mut app = app_with_balance();
+      app.open_selected_response();
+      let screen = render(&mut app);
+      assert!(screen.lines().nth_back(1).unwrap().contains(" Main › History › Response "), "{}", screen);
+  }
+
+  #[test]
+  fn param_hint_shows_help_until_an_error_replaces_it() {
+      let mut app = App::new();
+      app.mode = AppMode::ParamInput;
+      app.param_inputs = vec!["0xabc".into(), "latest".into()];
+      let at = |app: &App| app.filtered_methods.iter().position(|m| m == "eth_getBalance");
+      app.methods_state.select(at(&app));
+      let screen = render(&mut app);
+      assert!(screen.contains("20-byte account address"), "{}", screen);
+      assert!(screen.contains("┌address") && screen.contains("┌block"), "{}", screen);
+
+      app.notice = Some("invalid params JSON: expected value".into());
+      let screen = render(&mut app);
+      assert!(screen.contains("invalid params JSON"), "{}", screen);
+      assert!(!screen.contains("20-byte account address"), "{}", screen);
+
+      // Methods without help leave the hint line empty
+      app.notice = None;
+      app.methods_state.select(Some(0));
+      assert!(!render(&mut app).contains("hex"));
+
+      app.param_inputs = Vec::new();
+      assert!(render(&mut app).contains("this method takes no params"));
+  }
+
+  #[test]
+  fn boolean_params_render_as_toggles() {
+      let mut app = App::new();
+      app.mode = AppMode::ParamInput;
+      let at = app.filtered_methods.iter().position(|m| m == "eth_getBlockByNumber");
+      app.methods_state.select(at);
+      app.param_inputs = vec!["latest".into(), String::new()];
+      assert!(render(&mut app).contains("[ ] hashes only"));
+
+      app.param_inputs[1] = "true".into();
+      app.focused_param = 1;
+      let screen = render(&mut app);
+      assert!(screen.contains("[x] include full transactions"), "{}", screen);
+      assert!(screen.contains("Space/Enter=Toggle"), "{}", screen);
+  }
+
+  #[test]
+  fn known_addresses_are_labelled_for_display_only() {
+      let mut app = App::new();
+      app.history.push(HistoryEntry::new(
+          JsonRpcRequest::new("eth_call", json!([{"to": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"}, "latest"]), 1),
+          JsonRpcResponse {
+              jsonrpc: "2.0".into(),
+              result: Some(json!({"token": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"})),
+              error: None,
+              id: 1,
+          },
+      ));
+      app.chain_id = Some(1);
+      app.open_selected_response();
+      let screen = render(&mut app);
+      assert!(screen.contains("\"0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2\"  WETH"), "{}", screen);
+      assert!(app.response_view.as_ref().unwrap().lines.iter().all(|l| !l.contains("WETH")));
+
+      // Other chains don't get mainnet labels
+      app.chain_id = Some(10);
+      app.open_selected_response();
+      assert!(!render(&mut app).contains("WETH"));
+  }
+
+  #[test]
+  fn long_response_lines_wrap() {
+      let mut app = App::new();
+      let data = format!("0x{}{}", "ab".repeat(60), "cd".repeat(10));
+      app.response_view = Some(crate::view::ResponseView { lines: vec![format!("  \"data\": \"{}\"", data)], ..Default::default() });
+      app.mode = AppMode::ResponseView;
+      let screen = render(&mut app);
+      assert!(screen.contains(&"cd".repeat(10)), "{}", screen);
+  }
+
+  #[test]
+  fn end_and_jumps_count_wrapped_rows() {
+      let mut app = App::new();
+      // Each line wraps onto three rows of the 98-column viewport
+      let lines = (0..20).map(|i| format!("  \"line {:02}\": \"0x{}\"", i, "ab".repeat(120))).collect();
+      app.response_view = Some(crate::view::ResponseView { lines, ..Default::default() });
+      app.mode = AppMode::ResponseView;
+      render(&mut app);
+      app.response_view.as_mut().unwrap().scroll_to_end();
+      let screen = render(&mut app);
+      assert!(screen.contains("line 19") && !screen.contains("line 16"), "{}", screen);
+
+      app.response_view.as_mut().unwrap().scroll = 0;
+      render(&mut app);
+      app.response_view.as_mut().unwrap().reveal(10);
+      let screen = render(&mut app);
+      assert!(screen.contains("line 10") && !screen.contains("line 11"), "{}", screen);
+  }
+
+  #[tokio::test]
+  async fn docs_panel_shows_loading_then_cached_docs() {
+      let mut app = App::new();
+      app.methods_state.select(Some(1));
+      let screen = render(&mut app);
+      assert!(screen.contains("loading…"), "{}", screen);
+
+      app.tick(std::time::Instant::now());
+      while app.docs.get("eth_getBalance").is_none() {
+          tokio::task::yield_now().await;
+      }
+      let screen = render(&mut app);
+      assert!(!screen.contains("loading…"), "{}", screen);
+      assert!(screen.contains("• address"), "{}", screen);
+  }
+
+  #[tokio::test]
+  async fn schema_tab_shows_the_result_tree() {
+      let mut app = App::new();
+      app.docs_tab = DocsTab::Schema;
+      for (method, shown) in [("eth_getBalance", "result: uint"), ("eth_simulateV1", "no schema available")] {
+          let at = app.filtered_methods.iter().position(|m| m == method);
+          app.methods_state.select(at);
+          app.tick(std::time::Instant::now());
+          while app.docs.get(method).is_none() {
+              tokio::task::yield_now().await;
+          }
+          let screen = render(&mut app);
+          assert!(screen.contains(shown), "{}", screen);
+      }
+  }
+
+  #[test]
+  fn help_overlay_lists_every_key_of_the_mode_under_it() {
+      let mut app = App::new();
+      app.help = Some(Context::ParamRaw);
+      let screen = render(&mut app);
+      assert!(screen.contains("Keys — any key closes"), "{}", screen);
+      for hint in hints::hints(Context::ParamRaw) {
+          assert!(screen.contains(&format!("{}  {}", hint.keys, hint.action)), "{}", screen);
+      }
+      assert!(screen.contains("Main › Help"), "{}", screen);
+  }
+
+  #[test]
+  fn docs_panel_names_where_the_method_comes_from() {
+      let mut app = App::new();
+      app.extension = crate::extension::bundled().iter().find(|e| e.name == "optimism");
+      app.set_spec_source("node.json", crate::spec::RPC_SPECS[..2].to_vec());
+      app.methods_state.select(Some(0));
+      let screen = render(&mut app);
+      assert!(screen.contains("from: node.json"), "{}", screen);
+      assert_eq!(app.spec_origin("optimism_syncStatus").as_deref(), Some("optimism extension"));
+      assert_eq!(app.spec_origin("eth_mining"), None);
+  }
+
+  #[test]
+  fn focused_detail_and_preview_panes_show_on_short_terminals() {
+      let mut app = app_with_balance();
+      app.mode = AppMode::History;
+      app.history_state.select(Some(0));
+      assert!(!render(&mut app).contains("Detail"));
+      app.focus = Some(Focus::Detail);
+      let screen = render(&mut app);
+      assert!(screen.contains("Detail (↑/↓=Scroll)"), "{}", screen);
+      assert!(screen.contains(r#"→ eth_getBalance ["0xabc","latest"]"#), "{}", screen);
+
+      app.enter(AppMode::ParamInput);
+      app.param_inputs = vec!["0xabc".into()];
+      app.focus = Some(Focus::Preview);
+      let screen = render(&mut app);
+      assert!(screen.contains("Params preview"), "{}", screen);
+      assert!(screen.contains(r#""0xabc""#), "{}", screen);
+  }
+
+  #[test]
+  fn lease_prompt_offers_both_choices() {
+      let mut app = App::new();
+      app.lease_prompt = Some(crate::storage::Lease { endpoint: "ws://node".into(), pid: 1234, renewed_at: 0 });
+      let screen = render(&mut app);
+      assert!(screen.contains("subscription transport in use by another eli instance (pid 1234)"), "{}", screen);
+      assert!(screen.contains("y/Enter=Subscribe anyway • p/Esc=Poll over HTTP"), "{}", screen);
+  }
+
+  #[test]
+  fn history_detail_shows_which_spec_defined_the_method() {
+      let mut app = app_with_balance();
+      app.history[0].spec = app.spec_stamp("eth_getBalance");
+      app.history_state.select(Some(0));
+      app.focus = Some(Focus::Detail);
+      let screen = render(&mut app);
+      assert!(screen.contains("spec: spec:builtin ("), "{}", screen);
+  }
+}
@@ -0,0 +1,134 @@
+// src/uistate.rs
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::extension::ExtensionChoice;
+use crate::prefs::DisplayPrefs;
+use crate::schema;
+use crate::storage::{self, Loaded, Revisioned, Saved};
+
+/// UI settings that survive restarts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub prefs: DisplayPrefs,
+    /// Never hold requests for the "endpoint is syncing" warning.
+    pub suppress_sync_warning: bool,
+    /// Chain spec extension: "auto" (by detected chain id), "off", or an
+    /// extension name to always use.
+    pub spec_extension: ExtensionChoice,
+    /// Seconds without a new block before the chain is flagged as stalled;
+    /// unset picks a threshold by chain.
+    pub stall_after_secs: Option<u64>,
+    /// Look for a newer eli on crates.io once a day (opt-in; edit the
+    /// file to enable).
+    pub update_check: bool,
+    /// Milliseconds from a key to its frame before the UI is flagged as
+    /// slow; unset is 50, 0 turns the check off.
+    pub frame_budget_ms: Option<u64>,
+    /// Accept comments, trailing commas, unquoted keys and single quotes
+    /// in the raw params editor, sent as strict JSON (opt-in; edit the
+    /// file to enable).
+    pub relaxed_json: bool,
+    /// Bumped on every save; see [`storage::save_revisioned`].
+    pub revision: u64,
+}
+
+impl Revisioned for UiState {
+    fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn set_revision(&mut self, revision: u64) {
+        self.revision = revision;
+    }
+}
+
+/// eli's config directory: `$XDG_CONFIG_HOME/eli`, falling back to `~/.config/eli`.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("eli"))
+}
+
+/// `ui-state.json` in the config directory.
+pub fn default_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("ui-state.json"))
+}
+
+/// Reads the state file; a missing file yields the defaults, and a corrupt
+/// one its backup (with a warning) when there is one.
+pub fn load(path: &Path) -> Result<Loaded<UiState>> {
+    Ok(storage::load_versioned(path, &schema::UI_STATE)?.unwrap_or_else(|| Loaded::fresh(UiState::default())))
+}
+
+/// Writes `state` atomically, creating the directory if needed; `base` is
+/// the state as loaded. If another eli saved since, the settings this one
+/// didn't change keep the other's values. Returns what was written.
+pub fn save(path: &Path, base: &UiState, state: UiState) -> Result<(UiState, Saved)> {
+    storage::save_revisioned(path, &schema::UI_STATE, base.revision, state, |theirs, ours| {
+        let merged = storage::merge_changed(&serde_json::to_value(base)?, serde_json::to_value(ours)?, serde_json::to_value(theirs)?);
+        Ok(serde_json::from_value(merged)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prefs::Units;
+
+    #[test]
+    fn round_trips_and_defaults_when_missing() {
+        let dir = std::env::temp_dir().join(format!("eli-uistate-{}", std::process::id()));
+        let path = dir.join("ui-state.json");
+        assert_eq!(load(&path).unwrap().value, UiState::default());
+
+        let mut state = UiState::default();
+        state.prefs.units = Units::Wei;
+        let (written, saved) = save(&path, &UiState::default(), state.clone()).unwrap();
+        assert_eq!(saved, Saved { revision: 1, merged: false });
+        assert_eq!(load(&path).unwrap().value, UiState { revision: 1, ..state });
+        assert_eq!(written.revision, 1);
+        let on_disk: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(on_disk["version"], schema::UI_STATE.current());
+
+        // A newer eli's file is neither read nor overwritten
+        std::fs::write(&path, r#"{"version": 99}"#).unwrap();
+        assert!(load(&path).is_err());
+        assert!(save(&path, &UiState::default(), UiState::default()).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_saves_keep_each_others_changes() {
+        let dir = std::env::temp_dir().join(format!("eli-uistate-merge-{}", std::process::id()));
+        let path = dir.join("ui-state.json");
+        let (base, _) = save(&path, &UiState::default(), UiState::default()).unwrap();
+
+        // Two instances load revision 1; the first changes units and saves
+        let mut first = base.clone();
+        first.prefs.units = Units::Wei;
+        save(&path, &base, first).unwrap();
+
+        // The second changes something else, unaware of the first save
+        let mut second = base.clone();
+        second.suppress_sync_warning = true;
+        let (written, saved) = save(&path, &base, second).unwrap();
+        assert_eq!(saved, Saved { revision: 3, merged: true });
+        assert_eq!(written.prefs.units, Units::Wei);
+        assert!(written.suppress_sync_warning);
+        assert_eq!(load(&path).unwrap().value, written);
+
+        // Both changing the same setting: the later save wins
+        let mut third = base.clone();
+        third.prefs.units = Units::Gwei;
+        let (written, _) = save(&path, &base, third).unwrap();
+        assert_eq!(written.prefs.units, Units::Gwei);
+        assert!(written.suppress_sync_warning);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+// src/undo.rs
+
+/// Undo steps kept per buffer; the oldest go first.
+pub const DEPTH: usize = 100;
+
+/// How an edit groups with the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    /// A typed character. A run of them undoes as one step.
+    Insert,
+    /// Backspace or Delete; a run of them is one step too.
+    Remove,
+    /// A value put in whole (pasted, cleared, loaded, toggled): always
+    /// its own step.
+    Replace,
+}
+
+/// Undo and redo over snapshots of one buffer, e.g. a param's `String`
+/// or the raw-JSON `TextArea`.
+///
+/// Callers record each edit with the state it started from; consecutive
+/// edits of the same kind share one step until [`UndoStack::seal`] (the
+/// cursor moved, focus left) or an edit of another kind ends the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoStack<S> {
+    undo: Vec<S>,
+    redo: Vec<S>,
+    /// Kind of the run the next edit may join.
+    run: Option<EditKind>,
+    depth: usize,
+}
+
+impl<S> Default for UndoStack<S> {
+    fn default() -> Self {
+        Self::new(DEPTH)
+    }
+}
+
+impl<S> UndoStack<S> {
+    pub fn new(depth: usize) -> Self {
+        UndoStack { undo: Vec::new(), redo: Vec::new(), run: None, depth: depth.max(1) }
+    }
+}
+
+impl<S: Clone + PartialEq> UndoStack<S> {
+    /// Records an edit of `kind` that turned `before` into `after`. Edits
+    /// that changed nothing are ignored; any other drops the redo steps.
+    pub fn record(&mut self, before: S, after: &S, kind: EditKind) {
+        if before == *after {
+            return;
+        }
+        self.redo.clear();
+        let joins = kind != EditKind::Replace && self.run == Some(kind) && !self.undo.is_empty();
+        self.run = Some(kind);
+        if joins {
+            return;
+        }
+        self.undo.push(before);
+        if self.undo.len() > self.depth {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Ends the current run, so the next edit starts a step of its own.
+    pub fn seal(&mut self) {
+        self.run = None;
+    }
+
+    /// The state before the last step, given the `current` one, which
+    /// redo returns to.
+    pub fn undo(&mut self, current: S) -> Option<S> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        self.run = None;
+        Some(previous)
+    }
+
+    /// The state the last undo left, given the `current` one.
+    pub fn redo(&mut self, current: S) -> Option<S> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        self.run = None;
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.run = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Types `text` into `buffer` one character at a time, recording each.
+    fn type_into(stack: &mut UndoStack<String>, buffer: &mut String, text: &str) {
+        for c in text.chars() {
+            let before = buffer.clone();
+            buffer.push(c);
+            stack.record(before, buffer, EditKind::Insert);
+        }
+    }
+
+    fn backspace(stack: &mut UndoStack<String>, buffer: &mut String) {
+        let before = buffer.clone();
+        buffer.pop();
+        stack.record(before, buffer, EditKind::Remove);
+    }
+
+    #[test]
+    fn runs_of_one_kind_undo_together() {
+        let mut stack = UndoStack::default();
+        let mut buffer = String::new();
+        type_into(&mut stack, &mut buffer, "0xab");
+        backspace(&mut stack, &mut buffer);
+        backspace(&mut stack, &mut buffer);
+        type_into(&mut stack, &mut buffer, "cd");
+        // Moving the cursor ends the run
+        stack.seal();
+        type_into(&mut stack, &mut buffer, "ef");
+        assert_eq!(buffer, "0xcdef");
+
+        let mut states = Vec::new();
+        while let Some(previous) = stack.undo(buffer.clone()) {
+            buffer = previous;
+            states.push(buffer.clone());
+        }
+        assert_eq!(states, ["0xcd", "0x", "0xab", ""]);
+    }
+
+    #[test]
+    fn replacements_are_steps_of_their_own() {
+        let mut stack = UndoStack::default();
+        let mut buffer = String::from("latest");
+        stack.record(buffer.clone(), &String::new(), EditKind::Replace);
+        buffer.clear();
+        stack.record(buffer.clone(), &"0x1".to_string(), EditKind::Replace);
+        buffer = "0x1".into();
+        // A backspace that deletes nothing isn't a step
+        stack.record(String::new(), &String::new(), EditKind::Remove);
+        assert_eq!(stack.undo(buffer).as_deref(), Some(""));
+        assert_eq!(stack.undo(String::new()).as_deref(), Some("latest"));
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn a_new_edit_drops_the_redo_steps() {
+        let mut stack = UndoStack::default();
+        let mut buffer = String::new();
+        type_into(&mut stack, &mut buffer, "abc");
+        stack.seal();
+        type_into(&mut stack, &mut buffer, "def");
+        buffer = stack.undo(buffer).unwrap();
+        assert_eq!(buffer, "abc");
+        buffer = stack.redo(buffer).unwrap();
+        assert_eq!(buffer, "abcdef");
+        buffer = stack.undo(buffer).unwrap();
+        // Typing after an undo starts a new branch: "def" can't come back
+        type_into(&mut stack, &mut buffer, "x");
+        assert!(!stack.can_redo());
+        assert_eq!(stack.redo(buffer.clone()), None);
+        assert_eq!(stack.undo(buffer).as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn the_oldest_steps_go_past_the_depth() {
+        let mut stack = UndoStack::new(2);
+        let mut buffer = String::new();
+        for text in ["a", "b", "c"] {
+            stack.seal();
+            type_into(&mut stack, &mut buffer, text);
+        }
+        buffer = stack.undo(buffer).unwrap();
+        buffer = stack.undo(buffer).unwrap();
+        assert_eq!((buffer.as_str(), stack.can_undo()), ("a", false));
+        stack.clear();
+        assert!(!stack.can_redo());
+    }
+}
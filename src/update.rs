@@ -0,0 +1,138 @@
+// src/update.rs
+
+use std::path::Path;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+/// eli's entry in the crates.io sparse index: one JSON line per release.
+pub const INDEX_URL: &str = "https://index.crates.io/3/e/eli";
+
+/// Seconds between checks.
+pub const CHECK_EVERY: u64 = 24 * 60 * 60;
+
+/// What the last check found, kept in `update-check.json` next to the
+/// other config files.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckState {
+    /// When crates.io last answered (unix seconds).
+    pub checked_at: u64,
+    /// The newest version already announced, so each is announced once.
+    pub announced: Option<String>,
+}
+
+/// One line of the sparse index.
+#[derive(Deserialize)]
+struct Release {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// The newest release in the sparse index `text` that isn't yanked.
+/// Pre-releases only count for users already on one.
+pub fn newest(text: &str, current: &Version) -> Option<Version> {
+    text.lines()
+        .filter_map(|line| serde_json::from_str::<Release>(line).ok())
+        .filter(|release| !release.yanked)
+        .filter_map(|release| Version::parse(&release.vers).ok())
+        .filter(|version| version.pre.is_empty() || !current.pre.is_empty())
+        .max()
+}
+
+/// The toast for `newest`, if it is newer than `current` and wasn't
+/// announced before.
+pub fn announcement(current: &Version, newest: &Version, state: &CheckState) -> Option<String> {
+    let already = state.announced.as_deref() == Some(newest.to_string().as_str());
+    (newest > current && !already).then(|| format!("eli {} available (you have {})", newest, current))
+}
+
+/// Asks `url` for the newest release at most once per [`CHECK_EVERY`],
+/// remembering the answer in `state_path`. Returns the toast to show, if
+/// any. Every failure, network or file, is silent: this is a courtesy.
+pub async fn check(url: &str, state_path: &Path, current: &str, now: u64) -> Option<String> {
+    let current = Version::parse(current).ok()?;
+    let mut state: CheckState =
+        std::fs::read(state_path).ok().and_then(|bytes| serde_json::from_slice(&bytes).ok()).unwrap_or_default();
+    if now.saturating_sub(state.checked_at) < CHECK_EVERY {
+        return None;
+    }
+    let client = reqwest::Client::builder().timeout(std::time::Duration::from_secs(10)).build().ok()?;
+    let response = client.get(url).header("User-Agent", concat!("eli/", env!("CARGO_PKG_VERSION"))).send().await.ok()?;
+    let text = response.error_for_status().ok()?.text().await.ok()?;
+    let message = newest(&text, &current).and_then(|newest| {
+        let message = announcement(&current, &newest, &state);
+        state.announced = Some(newest.to_string());
+        message
+    });
+    state.checked_at = now;
+    let _ = serde_json::to_vec_pretty(&state).map(|bytes| storage::write_atomic(state_path, &bytes));
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    const INDEX: &str = r#"{"name":"eli","vers":"0.3.1","yanked":false}
+{"name":"eli","vers":"0.4.0-rc.1","yanked":false}
+{"name":"eli","vers":"0.4.0","yanked":false}
+{"name":"eli","vers":"0.5.0-alpha.2","yanked":false}
+{"name":"eli","vers":"0.4.1","yanked":true}
+not json"#;
+
+    #[test]
+    fn pre_releases_sort_before_their_release() {
+        assert!(v("0.4.0-rc.1") < v("0.4.0"));
+        assert!(v("0.4.0-alpha.2") < v("0.4.0-alpha.10"));
+        assert!(v("0.4.0-alpha.10") < v("0.4.0-beta"));
+        assert!(v("0.3.1") < v("0.4.0-rc.1"));
+        assert!(v("0.10.0") > v("0.9.9"));
+    }
+
+    #[test]
+    fn newest_skips_yanked_and_pre_releases_unless_on_one() {
+        assert_eq!(newest(INDEX, &v("0.3.1")), Some(v("0.4.0")));
+        assert_eq!(newest(INDEX, &v("0.5.0-alpha.1")), Some(v("0.5.0-alpha.2")));
+        assert_eq!(newest("", &v("0.3.1")), None);
+    }
+
+    #[test]
+    fn announces_each_newer_version_once() {
+        let state = CheckState::default();
+        assert_eq!(announcement(&v("0.3.1"), &v("0.4.0"), &state).unwrap(), "eli 0.4.0 available (you have 0.3.1)");
+        assert_eq!(announcement(&v("0.4.0"), &v("0.4.0"), &state), None);
+        assert_eq!(announcement(&v("0.4.0"), &v("0.4.0-rc.1"), &state), None);
+        let announced = CheckState { announced: Some("0.4.0".into()), ..state };
+        assert_eq!(announcement(&v("0.3.1"), &v("0.4.0"), &announced), None);
+    }
+
+    #[tokio::test]
+    async fn checks_once_a_day_and_stays_quiet_on_failure() {
+        let dir = std::env::temp_dir().join(format!("eli-update-{}", std::process::id()));
+        let path = dir.join("update-check.json");
+        let _m = mockito::mock("GET", "/3/e/eli").with_body(INDEX).create();
+        let url = format!("{}/3/e/eli", mockito::server_url());
+
+        let message = check(&url, &path, "0.3.1", CHECK_EVERY).await;
+        assert_eq!(message.as_deref(), Some("eli 0.4.0 available (you have 0.3.1)"));
+        // Within the day: not asked again
+        assert_eq!(check(&url, &path, "0.3.1", CHECK_EVERY + 60).await, None);
+        // A day later: asked, but 0.4.0 was already announced
+        assert_eq!(check(&url, &path, "0.3.1", 2 * CHECK_EVERY).await, None);
+        let state: CheckState = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(state, CheckState { checked_at: 2 * CHECK_EVERY, announced: Some("0.4.0".into()) });
+
+        // Nothing listening: no toast, and the next start tries again
+        assert_eq!(check("http://127.0.0.1:9/3/e/eli", &path, "0.3.1", 4 * CHECK_EVERY).await, None);
+        assert_eq!(serde_json::from_slice::<CheckState>(&std::fs::read(&path).unwrap()).unwrap().checked_at, 2 * CHECK_EVERY);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,444 @@
+// src/view.rs
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::accesslist;
+use crate::addressbook::{self, AddressBook};
+use crate::app::HistoryEntry;
+use crate::chains::ChainInfo;
+use crate::decode::{self, DecoderRegistry};
+use crate::eventsig;
+use crate::follow;
+use crate::hexdump::HexView;
+use crate::prefs::DisplayPrefs;
+use crate::pretty::{self, Formatting};
+use crate::search::SearchState;
+use crate::spill::{Container, SpillIndex};
+
+/// Longest preview of a spilled element, in characters.
+const PREVIEW: usize = 160;
+
+/// What a [`ResponseView`] is showing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tab {
+    #[default]
+    Response,
+    /// How the request's params were derived from the fields.
+    Provenance,
+}
+
+/// Full-screen, scrollable view of one response.
+#[derive(Debug, Default)]
+pub struct ResponseView {
+    /// Block title, e.g. "eth_getBlockByNumber #12".
+    pub title: String,
+    /// Pretty-printed response, formatted once and cached.
+    pub lines: Vec<String>,
+    /// Index of the first visible line.
+    pub scroll: usize,
+    /// Visible row count from the last draw, used to keep jumps on screen.
+    pub height: usize,
+    /// Row width from the last draw; lines wrap at it. 0 until drawn.
+    pub width: usize,
+    /// In-view text search.
+    pub search: SearchState,
+    /// Showing a dry-run entry: the request that would have been sent.
+    pub dry_run: bool,
+    /// The node answered with an error, summarized on the first line.
+    pub failed: bool,
+    /// Address labels per line, drawn after it; never part of `lines`, so
+    /// search and copied text only see the response itself.
+    pub labels: Vec<Option<String>>,
+    /// Path being typed for a CSV export, while the prompt is open.
+    pub export_path: Option<String>,
+    /// Address on the top line and the label being typed for it, while
+    /// the prompt is open.
+    pub label_prompt: Option<(String, String)>,
+    /// Which tab `lines` holds.
+    pub tab: Tab,
+    /// Lines of the tab not shown.
+    pub hidden: Vec<String>,
+    /// Large responses are formatted in the background; their lines are
+    /// appended as they come (see [`ResponseView::poll_formatting`]).
+    pub formatting: Option<Formatting>,
+    /// Hex dump of a field, shown instead of the lines while open.
+    pub hex: Option<HexView>,
+    /// The result when it was spilled to disk: its elements are outlined,
+    /// previewed as they scroll into view and parsed whole on Enter.
+    pub spill: Option<SpillView>,
+}
+
+/// The outline of a spilled result, kept in step with the view's lines.
+#[derive(Debug)]
+pub struct SpillView {
+    pub index: SpillIndex,
+    /// The element each line outlines, for the lines that outline one.
+    pub rows: Vec<Option<usize>>,
+    /// Lines shown under each expanded element.
+    pub expanded: HashMap<usize, usize>,
+    /// Elements whose outline line already has its preview.
+    previewed: Vec<bool>,
+}
+
+impl SpillView {
+    /// Appends the outline of `index` to `lines`.
+    fn open(index: SpillIndex, lines: &mut Vec<String>) -> Self {
+        let mut rows = vec![None; lines.len()];
+        lines.push(match index.container {
+            Container::Array => format!("\"result\": [{} elements on disk; Enter expands the top one]", index.len()),
+            Container::Object => format!("\"result\": {{{} members on disk; Enter expands the top one}}", index.len()),
+            Container::Scalar => format!("\"result\" is on disk: {}", index.path.display()),
+        });
+        rows.push(None);
+        for i in 0..index.len() {
+            lines.push(format!("  {}", index.outline(i).unwrap_or_default()));
+            rows.push(Some(i));
+        }
+        let previewed = vec![false; index.len()];
+        SpillView { index, rows, expanded: HashMap::new(), previewed }
+    }
+}
+
+impl ResponseView {
+    /// Formats the response of `entry` for display; dry-run entries show
+    /// the serialized request under a banner instead. Error responses open
+    /// with their code and message. Results a registered
+    /// decoder understands get its rendering first (transactions their
+    /// sender check, `eth_simulateV1` a per-call summary), created access
+    /// lists a diff against the one sent, transactions and blocks a
+    /// block-explorer link when `chain` has one, and receipts and
+    /// transactions the keys that open their counterparts. Hex quantities
+    /// are decoded beneath the JSON.
+    pub fn new(entry: &HistoryEntry, prefs: &DisplayPrefs, chain: Option<&ChainInfo>, decoders: &DecoderRegistry) -> Self {
+        if entry.dry_run {
+            let text = serde_json::to_string_pretty(&entry.request)
+                .unwrap_or_else(|e| format!("<unprintable request: {}>", e));
+            let mut lines = vec!["DRY RUN — this request was not sent".to_string(), String::new()];
+            lines.extend(text.lines().map(str::to_string));
+            return ResponseView {
+                title: format!("{} #{} (dry run)", entry.request.method, entry.seq),
+                lines,
+                dry_run: true,
+                hidden: entry.provenance.lines(),
+                ..Default::default()
+            };
+        }
+        let mut lines: Vec<String> = Vec::new();
+        let failed = entry.response.error_summary();
+        if let Some(error) = &failed {
+            lines.push(format!("error {}", error));
+        }
+        if let Some(decoded) = entry.response.result.as_ref().and_then(|r| decoders.decode(&entry.request.method, r, prefs)) {
+            lines.extend(decoded.lines());
+        }
+        if entry.request.method == accesslist::METHOD
+            && let Some(diff) = entry.response.result.as_ref().and_then(|r| accesslist::diff_with_request(&entry.request.params, r))
+        {
+            if diff.is_empty() {
+                lines.push("Access list sent matches the node's.".to_string());
+            } else {
+                lines.push("Access list vs the one sent (+ added by the node, - unnecessary):".to_string());
+                lines.extend(diff.lines());
+            }
+        }
+        if let Some(hint) = entry.response.result.as_ref().and_then(|r| eventsig::empty_logs_hint(&entry.request, r)) {
+            lines.push(hint);
+        }
+        let result = entry.response.result.as_ref();
+        let field = |name: &str| result.and_then(|r| r.get(name)).and_then(|v| v.as_str());
+        let link = match (entry.request.method.as_str(), chain) {
+            ("eth_getTransactionByHash" | "eth_getTransactionReceipt", Some(chain)) => field("hash")
+                .or(field("transactionHash"))
+                .and_then(|hash| chain.explorer_tx_url(hash)),
+            ("eth_getBlockByNumber" | "eth_getBlockByHash", Some(chain)) => {
+                field("number")
+                    .and_then(|n| u64::from_str_radix(n.trim_start_matches("0x"), 16).ok())
+                    .and_then(|n| chain.explorer_block_url(&n.to_string()))
+            }
+            _ => None,
+        };
+        if let Some(link) = link {
+            lines.push(format!("{}: {}", chain.map(|c| c.name.as_str()).unwrap_or_default(), link));
+        }
+        if let Some(hint) = follow::hint(entry) {
+            lines.push(hint);
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        let spill = match &entry.spill {
+            Some(path) => match SpillIndex::build(path) {
+                Ok(index) => Some(SpillView::open(index, &mut lines)),
+                Err(e) => {
+                    lines.push(format!("response not readable from disk: {:#}", e));
+                    None
+                }
+            },
+            None => None,
+        };
+        let large = entry.response.result.as_ref().map(pretty::count).unwrap_or(0) > pretty::INLINE_LIMIT;
+        let formatting = large.then(|| Formatting::start(&entry.response));
+        if formatting.is_none() && entry.spill.is_none() {
+            lines.extend(pretty::response_lines(&entry.response));
+        }
+        let currency = chain.map(|c| c.native_currency.clone()).unwrap_or_default();
+        if let Some(decoded) = result.and_then(|r| decode::quantity_lines(&entry.request.method, r, &currency)) {
+            lines.push(String::new());
+            lines.extend(decoded);
+        }
+        ResponseView {
+            title: format!("{} #{}", entry.request.method, entry.seq),
+            lines,
+            hidden: entry.provenance.lines(),
+            formatting,
+            failed: failed.is_some(),
+            spill,
+            ..Default::default()
+        }
+    }
+
+    /// Reads the spilled elements outlined on screen that have no preview
+    /// yet, one [`SpillIndex::page`] at a time, and shows each beside its
+    /// name. Returns whether any line changed.
+    pub fn page_spill(&mut self, book: &AddressBook, chain_id: Option<u64>) -> bool {
+        let visible = self.visible();
+        let Some(spill) = self.spill.as_mut().filter(|_| self.tab == Tab::Response) else { return false };
+        let wanted: Vec<usize> =
+            spill.rows[visible.clone()].iter().flatten().copied().filter(|&i| !spill.previewed[i]).collect();
+        let (Some(&first), Some(&last)) = (wanted.first(), wanted.last()) else { return false };
+        let page = match spill.index.page(first, last + 1 - first) {
+            Ok(page) => page,
+            Err(e) => {
+                self.lines[visible.start] = format!("  response not readable from disk: {:#}", e);
+                spill.previewed[first..=last].fill(true);
+                return true;
+            }
+        };
+        let labelled = self.labels.len() == self.lines.len();
+        for line in visible {
+            let Some(i) = spill.rows[line].filter(|i| (first..=last).contains(i) && !spill.previewed[*i]) else { continue };
+            let text = page[i - first].to_string();
+            let short: String = text.chars().take(PREVIEW).collect();
+            let ellipsis = if short.len() < text.len() { "…" } else { "" };
+            self.lines[line] = format!("  {} {}{}", spill.index.name(i).unwrap_or_default(), short, ellipsis);
+            if labelled {
+                self.labels[line] = book.annotate(chain_id, &self.lines[line]);
+            }
+            spill.previewed[i] = true;
+        }
+        true
+    }
+
+    /// Expands the spilled element outlined at or above the top visible
+    /// line, parsing just that element from disk, or collapses it when it
+    /// is expanded. Returns whether anything changed.
+    pub fn toggle_spilled(&mut self, book: &AddressBook, chain_id: Option<u64>) -> anyhow::Result<bool> {
+        let Some(spill) = self.spill.as_mut().filter(|_| self.tab == Tab::Response) else { return Ok(false) };
+        let top = self.scroll.min(spill.rows.len().saturating_sub(1));
+        let Some((line, i)) = (0..=top).rev().find_map(|l| Some((l, spill.rows.get(l).copied().flatten()?))) else {
+            return Ok(false);
+        };
+        let labelled = self.labels.len() == self.lines.len();
+        let below = line + 1;
+        if let Some(shown) = spill.expanded.remove(&i) {
+            self.lines.drain(below..below + shown);
+            spill.rows.drain(below..below + shown);
+            if labelled {
+                self.labels.drain(below..below + shown);
+            }
+            self.scroll = line;
+        } else {
+            let value = spill.index.load(i)?;
+            let text = serde_json::to_string_pretty(&value)?;
+            let added: Vec<String> = text.lines().map(|l| format!("    {}", l)).collect();
+            spill.expanded.insert(i, added.len());
+            spill.rows.splice(below..below, std::iter::repeat_n(None, added.len()));
+            if labelled {
+                self.labels.splice(below..below, added.iter().map(|l| book.annotate(chain_id, l)).collect::<Vec<_>>());
+            }
+            self.lines.splice(below..below, added);
+        }
+        if !self.search.editing && !self.search.query.is_empty() {
+            self.search.run(&self.lines);
+        }
+        Ok(true)
+    }
+
+    /// Appends the lines formatted in the background since the last poll,
+    /// labelling them like the rest, and re-runs a search once the whole
+    /// response is in. Returns whether anything changed.
+    pub fn poll_formatting(&mut self, book: &AddressBook, chain_id: Option<u64>) -> bool {
+        let Some(formatting) = self.formatting.as_mut() else { return false };
+        let (new, done) = formatting.poll();
+        if done {
+            self.formatting = None;
+        }
+        if new.is_empty() && !done {
+            return false;
+        }
+        match self.tab {
+            Tab::Response => {
+                // Labels are per shown line; keep them in step when present
+                if self.labels.len() == self.lines.len() {
+                    self.labels.extend(new.iter().map(|line| book.annotate(chain_id, line)));
+                }
+                self.lines.extend(new);
+            }
+            Tab::Provenance => self.hidden.extend(new),
+        }
+        if done && self.tab == Tab::Response && !self.search.editing && !self.search.query.is_empty() {
+            self.search.run(&self.lines);
+        }
+        true
+    }
+
+    /// "formatting… 40%" while the response is still being formatted.
+    pub fn formatting_status(&self) -> Option<String> {
+        self.formatting.as_ref().map(|f| format!("formatting… {:.0}%", f.progress * 100.0))
+    }
+
+    /// Switches between the response and provenance tabs, from the top and
+    /// without a search.
+    pub fn toggle_tab(&mut self) {
+        std::mem::swap(&mut self.lines, &mut self.hidden);
+        self.tab = match self.tab {
+            Tab::Response => Tab::Provenance,
+            Tab::Provenance => Tab::Response,
+        };
+        self.scroll = 0;
+        self.search.clear();
+        self.labels.clear();
+    }
+
+    /// Replaces the response text (after a display change), whichever tab
+    /// is shown.
+    pub fn set_response_lines(&mut self, lines: Vec<String>) {
+        match self.tab {
+            Tab::Response => self.lines = lines,
+            Tab::Provenance => self.hidden = lines,
+        }
+    }
+
+    /// Looks up labels for the addresses on each line.
+    pub fn label_addresses(&mut self, book: &AddressBook, chain_id: Option<u64>) {
+        self.labels = self.lines.iter().map(|line| book.annotate(chain_id, line)).collect();
+    }
+
+    /// Scrolls by `delta` lines, clamped to the content.
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.lines.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+
+    /// Scrolls so the last page of the response fills the viewport.
+    pub fn scroll_to_end(&mut self) {
+        self.scroll = self.lines.len().checked_sub(1).map_or(0, |last| self.fitting_top(last));
+    }
+
+    /// Scrolls the minimum needed to make `line` visible.
+    pub fn reveal(&mut self, line: usize) {
+        if line < self.scroll {
+            self.scroll = line;
+        } else {
+            self.scroll = self.scroll.max(self.fitting_top(line));
+        }
+    }
+
+    /// The char indices `line` wraps at, label included: where each row
+    /// after its first starts. A char that doesn't fit what's left of a
+    /// row starts the next one.
+    pub fn row_breaks(&self, line: usize) -> Vec<usize> {
+        let mut breaks = Vec::new();
+        if self.width == 0 {
+            return breaks;
+        }
+        let label = self.labels.get(line).and_then(Option::as_deref).map(|l| format!("  {}", l)).unwrap_or_default();
+        let mut used = 0;
+        for (i, c) in self.lines[line].chars().chain(label.chars()).enumerate() {
+            let width = c.width().unwrap_or(0);
+            if used > 0 && used + width > self.width {
+                breaks.push(i);
+                used = 0;
+            }
+            used += width;
+        }
+        breaks
+    }
+
+    /// Screen rows `line` takes once wrapped.
+    fn rows(&self, line: usize) -> usize {
+        self.row_breaks(line).len() + 1
+    }
+
+    /// The lines drawn from the current scroll, the last maybe cut off.
+    pub fn visible(&self) -> Range<usize> {
+        let height = self.height.max(1);
+        let (mut end, mut used) = (self.scroll.min(self.lines.len()), 0);
+        while end < self.lines.len() && used < height {
+            used += self.rows(end);
+            end += 1;
+        }
+        self.scroll.min(end)..end
+    }
+
+    /// The earliest top line from which `line` still shows whole (or from
+    /// its first row, when it's taller than the viewport).
+    fn fitting_top(&self, line: usize) -> usize {
+        let height = self.height.max(1);
+        let (mut top, mut used) = (line, self.rows(line));
+        while top > 0 && used + self.rows(top - 1) <= height {
+            top -= 1;
+            used += self.rows(top);
+        }
+        top
+    }
+
+    /// Opens a hex dump of the top visible line's hex string, if it holds
+    /// one longer than a word; returns whether it did.
+    pub fn open_hex(&mut self) -> bool {
+        self.hex = self.lines.get(self.scroll).and_then(|line| HexView::open(line));
+        self.hex.is_some()
+    }
+
+    /// Opens the label prompt for the first address on the top visible
+    /// line, prefilled with its label; returns whether there was one.
+    pub fn prompt_label(&mut self, book: &AddressBook, chain_id: Option<u64>) -> bool {
+        let address = self.lines.get(self.scroll).and_then(|line| addressbook::addresses_in(line).next());
+        self.label_prompt = address.map(|a| (a.to_string(), book.label(chain_id, a).unwrap_or_default().to_string()));
+        self.label_prompt.is_some()
+    }
+
+    /// Executes the current query and jumps to the first match.
+    pub fn run_search(&mut self) {
+        self.search.editing = false;
+        self.search.run(&self.lines);
+        self.reveal_current();
+    }
+
+    pub fn next_match(&mut self) {
+        self.search.next();
+        self.reveal_current();
+    }
+
+    pub fn prev_match(&mut self) {
+        self.search.prev();
+        self.reveal_current();
+    }
+
+    /// Flips case sensitivity and re-runs the query if one was executed.
+    pub fn toggle_case(&mut self) {
+        self.search.case_sensitive = !self.search.case_sensitive;
+        if !self.search.editing && !self.search.query.is_empty() {
+            self.run_search();
+        }
+    }
+
+    fn reveal_current(&mut self) {
+        if let Some(m) = self.search.current_match() {
+            self.reveal(m.line);
+        }
+    }
+}
@@ -0,0 +1,381 @@
+// src/ws.rs
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::error::CapacityError;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse, TooLarge, DEFAULT_RESPONSE_LIMIT};
+
+/// Method of the notifications a subscription sends.
+pub const NOTIFICATION_METHOD: &str = "eth_subscription";
+
+/// A message pushed for a subscription, e.g. a new head for `newHeads`.
+/// Unlike responses it has no `id`; the subscription id says what it is for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// Id returned by the `eth_subscribe` that started the subscription.
+    pub subscription: String,
+    pub result: Value,
+}
+
+/// One text frame from the server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    /// The answer to a request sent on the connection, matched by `id`.
+    Response(JsonRpcResponse),
+    Notification(Notification),
+}
+
+#[derive(Deserialize)]
+struct NotificationFrame {
+    method: String,
+    params: NotificationParams,
+}
+
+#[derive(Deserialize)]
+struct NotificationParams {
+    subscription: String,
+    result: Value,
+}
+
+/// Reads a text frame: an `eth_subscription` notification, or a response.
+pub fn parse_frame(text: &str) -> Result<Frame> {
+    let value: Value = serde_json::from_str(text).context("frame is not JSON")?;
+    if value.get("id").is_none() && value.get("method").is_some() {
+        let frame: NotificationFrame = serde_json::from_value(value).context("malformed notification")?;
+        if frame.method != NOTIFICATION_METHOD {
+            bail!("unexpected notification method {}", frame.method);
+        }
+        let NotificationParams { subscription, result } = frame.params;
+        return Ok(Frame::Notification(Notification { subscription, result }));
+    }
+    Ok(Frame::Response(serde_json::from_value(value).context("malformed response")?))
+}
+
+/// An open WebSocket connection to a JSON-RPC endpoint.
+///
+/// Requests go out through [`WsHandle::send`]; every response and
+/// notification comes back through [`WsHandle::next`], in arrival order.
+/// Frames that can't be read arrive as errors, and the stream ends when the
+/// server closes the connection. Dropping the handle closes it.
+#[derive(Debug)]
+pub struct WsHandle {
+    requests: mpsc::UnboundedSender<JsonRpcRequest>,
+    frames: mpsc::UnboundedReceiver<Result<Frame>>,
+    task: JoinHandle<()>,
+}
+
+impl WsHandle {
+    /// Queues `request` to be sent; fails once the connection is gone.
+    pub fn send(&self, request: JsonRpcRequest) -> Result<()> {
+        self.requests.send(request).ok().context("websocket connection closed")
+    }
+
+    /// The next frame, waiting for it; `None` once the connection is closed.
+    pub async fn next(&mut self) -> Option<Result<Frame>> {
+        self.frames.recv().await
+    }
+
+    /// The next frame if one has arrived, for polling from the draw loop.
+    pub fn try_next(&mut self) -> Option<Result<Frame>> {
+        self.frames.try_recv().ok()
+    }
+}
+
+impl Drop for WsHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Connects to a `ws://` or `wss://` endpoint. A message or frame larger
+/// than `limit` bytes arrives as a [`TooLarge`] error and ends the
+/// connection, like an HTTP response over the client's limit.
+pub async fn connect_ws(url: &str, limit: usize) -> Result<WsHandle> {
+    if !url.starts_with("ws://") && !url.starts_with("wss://") {
+        bail!("'{}' is not a ws:// or wss:// URL", url);
+    }
+    let config = WebSocketConfig { max_message_size: Some(limit), max_frame_size: Some(limit), ..Default::default() };
+    let (socket, _) = tokio_tungstenite::connect_async_with_config(url, Some(config), false)
+        .await
+        .with_context(|| format!("cannot connect to {}", url))?;
+    let (mut sink, mut stream) = socket.split();
+    let (requests, mut outgoing) = mpsc::unbounded_channel::<JsonRpcRequest>();
+    let (incoming, frames) = mpsc::unbounded_channel();
+    let task = tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                request = outgoing.recv() => {
+                    let Some(request) = request else { break };
+                    let Ok(text) = serde_json::to_string(&request) else { continue };
+                    if let Err(e) = sink.send(Message::Text(text)).await {
+                        let _ = incoming.send(Err(anyhow::Error::new(e).context("websocket send failed")));
+                        break;
+                    }
+                }
+                message = stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        if incoming.send(parse_frame(&text)).is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    // Pings are answered by tungstenite; binary frames aren't JSON-RPC
+                    Some(Ok(_)) => {}
+                    Some(Err(WsError::Capacity(CapacityError::MessageTooLong { size, max_size }))) => {
+                        let too_large = TooLarge { method: "websocket".into(), read: size, limit: max_size };
+                        let _ = incoming.send(Err(too_large.into()));
+                        break;
+                    }
+                    Some(Err(e)) => {
+                        let _ = incoming.send(Err(anyhow::Error::new(e).context("websocket receive failed")));
+                        break;
+                    }
+                },
+            }
+        }
+        let _ = sink.close().await;
+    });
+    Ok(WsHandle { requests, frames, task })
+}
+
+/// How a dropped connection is retried: after `initial_delay`, then
+/// after twice as long each time up to `max_delay`, `max_attempts` times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Attempts before giving up; 0 never reconnects.
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy { initial_delay: Duration::from_millis(500), max_delay: Duration::from_secs(30), max_attempts: 10 }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Wait before attempt `attempt` (counting from 0).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial_delay.saturating_mul(2u32.saturating_pow(attempt)).min(self.max_delay)
+    }
+}
+
+/// Opens connections; tests swap in scripted ones.
+#[async_trait]
+pub trait Connector: Send + Sync {
+    async fn connect(&self, url: &str) -> Result<WsHandle>;
+}
+
+/// Connects with [`connect_ws`].
+#[derive(Debug, Clone, Copy)]
+pub struct WsConnector {
+    /// Largest message accepted, in bytes.
+    pub response_limit: usize,
+}
+
+impl Default for WsConnector {
+    fn default() -> Self {
+        WsConnector { response_limit: DEFAULT_RESPONSE_LIMIT }
+    }
+}
+
+#[async_trait]
+impl Connector for WsConnector {
+    async fn connect(&self, url: &str) -> Result<WsHandle> {
+        connect_ws(url, self.response_limit).await
+    }
+}
+
+/// Connects to `url` again after a drop, waiting between attempts as
+/// `policy` says; fails with the last error once attempts run out.
+pub async fn reconnect(connector: &dyn Connector, url: &str, policy: &ReconnectPolicy) -> Result<WsHandle> {
+    let mut last = anyhow!("reconnecting is turned off");
+    for attempt in 0..policy.max_attempts {
+        tokio::time::sleep(policy.delay(attempt)).await;
+        match connector.connect(url).await {
+            Ok(handle) => return Ok(handle),
+            Err(e) => last = e,
+        }
+    }
+    Err(last.context(format!("gave up reconnecting after {} attempts", policy.max_attempts)))
+}
+
+/// The server side of a [`WsHandle::fake`] connection.
+#[cfg(test)]
+pub struct FakeSocket {
+    /// What the client sent.
+    pub requests: mpsc::UnboundedReceiver<JsonRpcRequest>,
+    /// Frames for the client; dropping it closes the connection.
+    pub frames: mpsc::UnboundedSender<Result<Frame>>,
+}
+
+#[cfg(test)]
+impl FakeSocket {
+    /// Answers request `id` with `result`.
+    pub fn reply(&self, id: u64, result: Value) {
+        let response = JsonRpcResponse { jsonrpc: "2.0".into(), result: Some(result), error: None, id };
+        let _ = self.frames.send(Ok(Frame::Response(response)));
+    }
+
+    /// Pushes `result` for `subscription`.
+    pub fn notify(&self, subscription: &str, result: Value) {
+        let _ = self.frames.send(Ok(Frame::Notification(Notification { subscription: subscription.into(), result })));
+    }
+}
+
+#[cfg(test)]
+impl WsHandle {
+    /// A connection to nothing: the test plays the server through the
+    /// returned [`FakeSocket`].
+    pub fn fake() -> (WsHandle, FakeSocket) {
+        let (requests, server_requests) = mpsc::unbounded_channel();
+        let (server_frames, frames) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async {});
+        (WsHandle { requests, frames, task }, FakeSocket { requests: server_requests, frames: server_frames })
+    }
+}
+
+/// Hands out the scripted connections in order, then refuses.
+#[cfg(test)]
+pub struct Scripted(std::sync::Mutex<Vec<WsHandle>>);
+
+#[cfg(test)]
+#[async_trait]
+impl Connector for Scripted {
+    async fn connect(&self, url: &str) -> Result<WsHandle> {
+        let mut handles = self.0.lock().unwrap();
+        if handles.is_empty() {
+            bail!("cannot connect to {}", url);
+        }
+        Ok(handles.remove(0))
+    }
+}
+
+/// A connector handing out `count` fake connections, and their server
+/// sides.
+#[cfg(test)]
+pub fn scripted(count: usize) -> (std::sync::Arc<Scripted>, Vec<FakeSocket>) {
+    let (handles, sockets) = (0..count).map(|_| WsHandle::fake()).unzip();
+    (std::sync::Arc::new(Scripted(std::sync::Mutex::new(handles))), sockets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn notifications_have_no_id() {
+        let notification = r#"{"jsonrpc": "2.0", "method": "eth_subscription",
+            "params": {"subscription": "0x9ce59a13059e417087c02d3236a0b1cc", "result": {"number": "0x1b4"}}}"#;
+        assert_eq!(
+            parse_frame(notification).unwrap(),
+            Frame::Notification(Notification {
+                subscription: "0x9ce59a13059e417087c02d3236a0b1cc".into(),
+                result: json!({"number": "0x1b4"}),
+            })
+        );
+        let response = r#"{"jsonrpc": "2.0", "id": 1, "result": "0x9ce59a13059e417087c02d3236a0b1cc"}"#;
+        let Frame::Response(response) = parse_frame(response).unwrap() else { panic!("not a response") };
+        assert_eq!((response.id, response.result), (1, Some(json!("0x9ce59a13059e417087c02d3236a0b1cc"))));
+
+        assert!(parse_frame(r#"{"jsonrpc": "2.0", "method": "other", "params": {}}"#).is_err());
+        assert!(parse_frame("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn subscribes_and_receives_notifications() {
+        // A node that answers eth_subscribe and then pushes two heads
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let Some(Ok(Message::Text(text))) = socket.next().await else { panic!("no request") };
+            let request: JsonRpcRequest = serde_json::from_str(&text).unwrap();
+            assert_eq!((request.method.as_str(), &request.params), ("eth_subscribe", &json!(["newHeads"])));
+            let reply = json!({"jsonrpc": "2.0", "id": request.id, "result": "0xs1"});
+            socket.send(Message::Text(reply.to_string())).await.unwrap();
+            for number in ["0x1", "0x2"] {
+                let head = json!({"jsonrpc": "2.0", "method": "eth_subscription",
+                    "params": {"subscription": "0xs1", "result": {"number": number}}});
+                socket.send(Message::Text(head.to_string())).await.unwrap();
+            }
+            socket.close(None).await.unwrap();
+        });
+
+        let mut ws = connect_ws(&url, DEFAULT_RESPONSE_LIMIT).await.unwrap();
+        ws.send(JsonRpcRequest::new("eth_subscribe", json!(["newHeads"]), 5)).unwrap();
+        let Some(Ok(Frame::Response(reply))) = ws.next().await else { panic!("no reply") };
+        assert_eq!((reply.id, reply.result), (5, Some(json!("0xs1"))));
+        for number in ["0x1", "0x2"] {
+            let Some(Ok(Frame::Notification(head))) = ws.next().await else { panic!("no head") };
+            assert_eq!((head.subscription.as_str(), &head.result), ("0xs1", &json!({"number": number})));
+        }
+        // Closed by the server: the stream ends
+        assert!(ws.next().await.is_none());
+        server.await.unwrap();
+
+        assert!(connect_ws("http://127.0.0.1:8545", DEFAULT_RESPONSE_LIMIT).await.unwrap_err().to_string().contains("ws://"));
+    }
+
+    #[tokio::test]
+    async fn messages_over_the_limit_fail_as_too_large() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let head = json!({"jsonrpc": "2.0", "method": "eth_subscription",
+                "params": {"subscription": "0xs1", "result": {"extraData": format!("0x{}", "ab".repeat(1024))}}});
+            let _ = socket.send(Message::Text(head.to_string())).await;
+        });
+
+        let mut ws = connect_ws(&url, 1024).await.unwrap();
+        let Some(Err(e)) = ws.next().await else { panic!("message over the limit was read") };
+        let too_large = e.downcast_ref::<TooLarge>().expect("a TooLarge error");
+        assert_eq!(too_large.limit, 1024);
+        assert!(too_large.read > 1024);
+        // The connection is given up
+        assert!(ws.next().await.is_none());
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn reconnect_delays_double_up_to_the_cap() {
+        let policy =
+            ReconnectPolicy { initial_delay: Duration::from_millis(500), max_delay: Duration::from_secs(3), max_attempts: 5 };
+        let delays: Vec<u128> = (0..5).map(|a| policy.delay(a).as_millis()).collect();
+        assert_eq!(delays, [500, 1000, 2000, 3000, 3000]);
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn reconnecting_gives_up_after_max_attempts() {
+        struct Down(std::sync::atomic::AtomicU32);
+        #[async_trait]
+        impl Connector for Down {
+            async fn connect(&self, url: &str) -> Result<WsHandle> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                bail!("cannot connect to {}", url)
+            }
+        }
+        let down = Down(Default::default());
+        let policy =
+            ReconnectPolicy { initial_delay: Duration::from_millis(1), max_delay: Duration::from_millis(4), max_attempts: 3 };
+        let err = reconnect(&down, "ws://node", &policy).await.unwrap_err();
+        assert_eq!(format!("{:#}", err), "gave up reconnecting after 3 attempts: cannot connect to ws://node");
+        assert_eq!(down.0.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+}